@@ -1,3 +1,142 @@
+use std::path::Path;
+
 fn main() {
+    generate_web_rpc_commands();
     tauri_build::build()
 }
+
+/// Extract the command names matched by `server::rpc::dispatch` into
+/// `$OUT_DIR/web_rpc_commands.rs`, so `command_registry` can tell which registered
+/// commands work in web mode without a hand-maintained list.
+///
+/// The arms of the first `match` in `dispatch` are read with `match_arm_patterns`,
+/// which tokenizes rather than matching lines, so formatting doesn't matter.
+fn generate_web_rpc_commands() {
+    let rpc_path = Path::new("src/server/rpc.rs");
+    println!("cargo:rerun-if-changed={}", rpc_path.display());
+
+    let source = std::fs::read_to_string(rpc_path).unwrap_or_default();
+    let arms = source
+        .split_once("pub async fn dispatch(")
+        .and_then(|(_, rest)| rest.split_once("match "))
+        .and_then(|(_, rest)| rest.split_once('{'))
+        .map_or("", |(_, arms)| arms);
+
+    let mut commands: Vec<String> = match_arm_patterns(arms)
+        .into_iter()
+        .filter(|name| !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_'))
+        .collect();
+    commands.sort();
+    commands.dedup();
+
+    let list = commands
+        .iter()
+        .map(|c| format!("    \"{}\",\n", c))
+        .collect::<String>();
+    let generated = format!(
+        "/// Commands with an arm in `server::rpc::dispatch` (generated by build.rs)\npub const WEB_RPC_COMMANDS: &[&str] = &[\n{}];\n",
+        list
+    );
+
+    let out_dir = std::env::var("OUT_DIR").expect("OUT_DIR is set by cargo");
+    std::fs::write(Path::new(&out_dir).join("web_rpc_commands.rs"), generated)
+        .expect("failed to write web_rpc_commands.rs");
+}
+
+/// String literals used as patterns (`"a" =>`, `"a" | "b" =>`, across any line breaks)
+/// by the match arms in `body`, which starts just after the match's opening brace.
+/// Arm bodies, comments, char literals and nested blocks are skipped.
+fn match_arm_patterns(body: &str) -> Vec<String> {
+    let bytes = body.as_bytes();
+    let mut patterns = Vec::new();
+    // String literals seen at the arm level since the last other token
+    let mut pending: Vec<String> = Vec::new();
+    let mut depth = 0usize;
+    let mut i = 0;
+
+    while i < bytes.len() {
+        match bytes[i] {
+            b'/' if bytes.get(i + 1) == Some(&b'/') => {
+                i = body[i..].find('\n').map_or(bytes.len(), |n| i + n);
+                continue;
+            }
+            b'/' if bytes.get(i + 1) == Some(&b'*') => {
+                i = body[i + 2..].find("*/").map_or(bytes.len(), |n| i + 2 + n + 2);
+                continue;
+            }
+            b'"' => {
+                let (literal, end) = string_literal(body, i + 1, "\"");
+                if depth == 0 {
+                    pending.push(literal);
+                }
+                i = end;
+                continue;
+            }
+            b'r' if bytes.get(i + 1).is_some_and(|b| *b == b'"' || *b == b'#')
+                && (i == 0 || !(bytes[i - 1].is_ascii_alphanumeric() || bytes[i - 1] == b'_')) =>
+            {
+                let hashes = body[i + 1..].bytes().take_while(|b| *b == b'#').count();
+                if bytes.get(i + 1 + hashes) == Some(&b'"') {
+                    let closing = format!("\"{}", "#".repeat(hashes));
+                    let open = i + 2 + hashes;
+                    let end = body[open..].find(&closing).map_or(bytes.len(), |n| open + n + closing.len());
+                    pending.clear();
+                    i = end;
+                    continue;
+                }
+                pending.clear();
+            }
+            b'\'' => {
+                // A char literal ('a', '\n', '\u{..}'); otherwise a lifetime
+                if bytes.get(i + 1) == Some(&b'\\') {
+                    i = body[i + 2..].find('\'').map_or(bytes.len(), |n| i + 2 + n + 1);
+                    continue;
+                }
+                let width = body[i + 1..].chars().next().map_or(0, char::len_utf8);
+                if bytes.get(i + 1 + width) == Some(&b'\'') {
+                    i += width + 2;
+                    continue;
+                }
+                pending.clear();
+            }
+            b'{' | b'(' | b'[' => {
+                depth += 1;
+                pending.clear();
+            }
+            b'}' | b')' | b']' => {
+                if depth == 0 {
+                    break;
+                }
+                depth -= 1;
+                pending.clear();
+            }
+            b'=' if bytes.get(i + 1) == Some(&b'>') => {
+                if depth == 0 {
+                    patterns.append(&mut pending);
+                }
+                i += 2;
+                continue;
+            }
+            b'|' => {}
+            b if b.is_ascii_whitespace() => {}
+            _ => pending.clear(),
+        }
+        i += 1;
+    }
+    patterns
+}
+
+/// The contents of the string literal starting at `start` (after its opening quote),
+/// with escapes left as written, and the index just past its closing quote
+fn string_literal(body: &str, start: usize, quote: &str) -> (String, usize) {
+    let bytes = body.as_bytes();
+    let mut i = start;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 2,
+            _ if body[i..].starts_with(quote) => return (body[start..i].to_string(), i + quote.len()),
+            _ => i += 1,
+        }
+    }
+    (body[start..].to_string(), bytes.len())
+}
@@ -0,0 +1,96 @@
+// Native series analytics vs the Python subprocess path
+// Run with: cargo run --release --example series_analytics_bench [points]
+//
+// Times each native `series_*` transform, then the same pandas call in a fresh
+// Python process fed the series as JSON on stdin, which is how the pmdarima
+// commands run. The Python timings include interpreter and pandas start-up,
+// which is the cost the native commands avoid. Set PYTHON to pick the
+// interpreter; the Python column shows n/a if it or pandas is missing.
+
+use fincept_terminal_desktop_lib::commands::series_analytics as series;
+use std::hint::black_box;
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+const NATIVE_RUNS: u32 = 50;
+const PYTHON_RUNS: u32 = 3;
+
+/// Deterministic random walk (xorshift) so runs are comparable
+fn prices(n: usize) -> Vec<f64> {
+    let mut state: u64 = 0x9E37_79B9_7F4A_7C15;
+    let mut price = 100.0;
+    (0..n)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            let step = (state % 2001) as f64 / 1000.0 - 1.0;
+            price *= 1.0 + step * 0.01;
+            price
+        })
+        .collect()
+}
+
+fn time_native(f: impl Fn()) -> Duration {
+    let start = Instant::now();
+    for _ in 0..NATIVE_RUNS {
+        f();
+    }
+    start.elapsed() / NATIVE_RUNS
+}
+
+fn time_python(python: &str, expr: &str, data: &str) -> Option<Duration> {
+    let script = format!(
+        "import json, sys\nimport numpy as np\nimport pandas as pd\ns = pd.Series(json.load(sys.stdin))\nr = {}\nprint(pd.Series(r).to_json() if hasattr(r, '__len__') else r)",
+        expr
+    );
+    let mut total = Duration::ZERO;
+    for _ in 0..PYTHON_RUNS {
+        let start = Instant::now();
+        let mut child = Command::new(python)
+            .args(["-c", &script])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .ok()?;
+        child.stdin.take()?.write_all(data.as_bytes()).ok()?;
+        let output = child.wait_with_output().ok()?;
+        if !output.status.success() {
+            return None;
+        }
+        total += start.elapsed();
+    }
+    Some(total / PYTHON_RUNS)
+}
+
+fn main() {
+    let n: usize = std::env::args().nth(1).and_then(|a| a.parse().ok()).unwrap_or(10_000);
+    let python = std::env::var("PYTHON").unwrap_or_else(|_| "python3".to_string());
+    let data = prices(n);
+    let reversed: Vec<f64> = data.iter().rev().copied().collect();
+    let json = serde_json::to_string(&data).unwrap();
+
+    type Case<'a> = (&'a str, Box<dyn Fn() + 'a>, &'a str);
+    let cases: Vec<Case> = vec![
+        ("rolling_mean(20)", Box::new(|| drop(black_box(series::rolling_mean(&data, 20)))), "s.rolling(20).mean()"),
+        ("rolling_std(20)", Box::new(|| drop(black_box(series::rolling_std(&data, 20)))), "s.rolling(20).std()"),
+        ("returns", Box::new(|| drop(black_box(series::simple_returns(&data)))), "s.pct_change()"),
+        ("log_returns", Box::new(|| drop(black_box(series::log_returns(&data)))), "np.log(s / s.shift(1))"),
+        ("zscore", Box::new(|| drop(black_box(series::zscore(&data)))), "(s - s.mean()) / s.std(ddof=0)"),
+        (
+            "correlation",
+            Box::new(|| drop(black_box(series::correlation(&data, &reversed)))),
+            "s.corr(pd.Series(s.values[::-1]))",
+        ),
+    ];
+
+    println!("{} points; native averaged over {} runs, {} over {} runs", n, NATIVE_RUNS, python, PYTHON_RUNS);
+    println!("{:<18} {:>12} {:>12}", "transform", "native", "python");
+    for (name, run, expr) in &cases {
+        let native = format!("{:.1?}", time_native(run));
+        let python = time_python(&python, expr, &json).map_or_else(|| "n/a".to_string(), |d| format!("{:.1?}", d));
+        println!("{:<18} {:>12} {:>12}", name, native, python);
+    }
+}
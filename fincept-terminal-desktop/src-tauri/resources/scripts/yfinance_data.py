@@ -25,9 +25,12 @@ def get_quote(symbol):
         change = current_price - previous_close
         change_percent = (change / previous_close) * 100 if previous_close else 0
 
+        # FX pairs (e.g. EURUSD=X) need more precision than cents
+        price_decimals = 6 if symbol.endswith('=X') else 2
+
         quote_data = {
             "symbol": symbol,
-            "price": round(float(current_price), 2),
+            "price": round(float(current_price), price_decimals),
             "change": round(float(change), 2),
             "change_percent": round(float(change_percent), 2),
             "volume": int(hist['Volume'].iloc[-1]) if not hist['Volume'].empty else None,
@@ -35,18 +38,23 @@ def get_quote(symbol):
             "low": round(float(hist['Low'].iloc[-1]), 2) if not hist['Low'].empty else None,
             "open": round(float(hist['Open'].iloc[-1]), 2) if not hist['Open'].empty else None,
             "previous_close": round(float(previous_close), 2),
-            "timestamp": int(datetime.now().timestamp())
+            "timestamp": int(datetime.now().timestamp()),
+            # Currency the price is quoted in (e.g. "USD", "GBp" for pence); used for FX conversion
+            "currency": info.get('currency')
         }
 
         return quote_data
     except Exception as e:
         return {"error": str(e), "symbol": symbol}
 
-def get_historical(symbol, start_date, end_date):
+def get_historical(symbol, start_date, end_date, interval="1d"):
     """Fetch historical data for a symbol"""
     try:
         ticker = yf.Ticker(symbol)
-        hist = ticker.history(start=start_date, end=end_date)
+        # auto_adjust=False exposes 'Adj Close' next to the traded 'Close'; OHLC is then
+        # scaled by Adj Close / Close below, which is what auto_adjust=True does, so
+        # consumers keep adjusted candles and also get the traded close for price returns
+        hist = ticker.history(start=start_date, end=end_date, interval=interval, auto_adjust=False)
 
         if hist.empty:
             # what: return an empty array instead of null when no historical data is available
@@ -56,15 +64,19 @@ def get_historical(symbol, start_date, end_date):
 
         historical_data = []
         for index, row in hist.iterrows():
+            raw_close = float(row['Close'])
+            adj_close = float(row.get('Adj Close', raw_close))
+            factor = adj_close / raw_close if raw_close else 1.0
             historical_data.append({
                 "symbol": symbol,
                 "timestamp": int(index.timestamp()),
-                "open": round(float(row['Open']), 2),
-                "high": round(float(row['High']), 2),
-                "low": round(float(row['Low']), 2),
-                "close": round(float(row['Close']), 2),
+                "open": round(float(row['Open']) * factor, 2),
+                "high": round(float(row['High']) * factor, 2),
+                "low": round(float(row['Low']) * factor, 2),
+                "close": round(adj_close, 2),
                 "volume": int(row['Volume']),
-                "adj_close": round(float(row['Close']), 2)
+                "adj_close": round(adj_close, 4),
+                "raw_close": round(raw_close, 4)
             })
 
         return historical_data
@@ -209,12 +221,13 @@ def main(args=None):
 
     elif command == "historical":
         if len(args) < 4:
-            result = {"error": "Usage: python yfinance_data.py historical <symbol> <start_date> <end_date>"}
+            result = {"error": "Usage: python yfinance_data.py historical <symbol> <start_date> <end_date> [interval]"}
         else:
             symbol = args[1]
             start_date = args[2]
             end_date = args[3]
-            result = get_historical(symbol, start_date, end_date)
+            interval = args[4] if len(args) > 4 else "1d"
+            result = get_historical(symbol, start_date, end_date, interval)
 
     elif command == "info":
         if len(args) < 2:
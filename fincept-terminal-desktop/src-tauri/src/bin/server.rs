@@ -13,8 +13,21 @@
 //   FINCEPT_HOST - Server host (default: 0.0.0.0)
 //   FINCEPT_PORT - Server port (default: 3000)
 //   FINCEPT_CORS_ORIGINS - Comma-separated list of allowed CORS origins
+//   FINCEPT_MAX_WS_CONNECTIONS - Concurrent /ws clients before new ones get 503 (default: 200, 0 = unlimited)
+//   FINCEPT_WS_SLOW_CLIENT_SECS - Seconds a /ws client may stop reading before it is dropped (default: 10)
 //   FINCEPT_PYTHON_PATH - Path to Python executable
 //   FINCEPT_SCRIPTS_PATH - Path to Python scripts directory
+//   FINCEPT_DATA_DIR - Base directory for database, Python runtime and logs
+//                      (same as --data-dir <path>; the flag wins over the env var)
+//   FINCEPT_STATIC_DIR - Built frontend (dist) to serve at / alongside the API;
+//                        unset serves only the API and its documentation page
+//   FINCEPT_API_KEY - Key /api/rpc requests must send (x-fincept-api-key or
+//                     Authorization: Bearer) unless they carry a scoped token;
+//                     unset leaves the API open and disables scoped tokens
+//   FINCEPT_SETTING_<KEY> - Setting value; takes precedence over the settings table
+//   FINCEPT_CRED_<SERVICE>_<FIELD> - Credential field (API_KEY, API_SECRET, USERNAME,
+//                        PASSWORD, ADDITIONAL_DATA); overrides that field of the stored
+//                        credential (see database::env_overlay)
 
 #[cfg(feature = "web")]
 fn main() {
@@ -42,6 +55,27 @@ fn main() {
             config.cors_origins = parsed;
         }
     }
+
+    if let Some(max) = std::env::var("FINCEPT_MAX_WS_CONNECTIONS").ok().and_then(|v| v.parse().ok()) {
+        config.max_ws_connections = max;
+    }
+    if let Some(secs) = std::env::var("FINCEPT_WS_SLOW_CLIENT_SECS").ok().and_then(|v| v.parse().ok()) {
+        config.ws_slow_client_timeout = std::time::Duration::from_secs(secs);
+    }
+    // Clients written for the old API expect 200 with `success: false` on failure
+    if let Ok(value) = std::env::var("FINCEPT_RPC_LEGACY_STATUS") {
+        config.rpc_status_codes = !matches!(value.trim(), "1" | "true" | "yes");
+    }
+    if let Ok(key) = std::env::var("FINCEPT_API_KEY") {
+        if !key.trim().is_empty() {
+            config.api_key = Some(key.trim().to_string());
+        }
+    }
+    if let Ok(dir) = std::env::var("FINCEPT_STATIC_DIR") {
+        if !dir.trim().is_empty() {
+            config.static_dir = Some(std::path::PathBuf::from(dir.trim()));
+        }
+    }
     
     println!("╔═══════════════════════════════════════════════════════════╗");
     println!("║         FINCEPT TERMINAL WEB SERVER v{}              ║", env!("CARGO_PKG_VERSION"));
@@ -9,36 +9,71 @@ macro_rules! for_each_tauri_command {
             cleanup_running_workflows,
             setup::check_setup_status,
             setup::run_setup,
+            setup::get_setup_progress,
+            setup::verify_setup,
+            setup::repair_setup,
             spawn_mcp_server,
             send_mcp_request,
             send_mcp_notification,
             ping_mcp_server,
             kill_mcp_server,
             sha256_hash,
+            hmac_sha256,
+            hash,
+            get_log_file_path,
+            commands::log_viewer::tail_log,
+            commands::log_viewer::start_log_stream,
+            commands::log_viewer::stop_log_stream,
+            get_command_timings,
+            get_command_availability,
+            cancel_request,
+            drain_worker_pool,
             ws_set_config,
             ws_connect,
             ws_disconnect,
             ws_subscribe,
+            ws_subscribe_many,
             ws_unsubscribe,
             ws_get_metrics,
+            get_provider_capabilities,
             ws_get_all_metrics,
             ws_reconnect,
+            ws_set_raw_capture,
+            ws_get_last_raw,
+            set_tick_recording,
+            tick_recorder_status,
+            get_tick_history,
+            replay_ticks,
+            stop_tick_replay,
             monitor_add_condition,
+            test_monitor_condition,
             monitor_get_conditions,
             monitor_delete_condition,
             monitor_get_alerts,
+            monitor_acknowledge_alert,
+            monitor_acknowledge_all_alerts,
+            monitor_add_condition_group,
+            monitor_get_condition_groups,
+            monitor_delete_condition_group,
             monitor_load_conditions,
+            monitor_set_notifications_enabled,
             execute_python_script,
             commands::news::fetch_all_rss_news,
+            commands::news::force_refresh_news,
             commands::news::get_rss_feed_count,
             commands::news::get_active_sources,
             commands::market_data::get_market_quote,
             commands::market_data::get_market_quotes,
+            commands::market_data::get_watchlist_quotes,
             commands::market_data::get_period_returns,
             commands::market_data::check_market_data_health,
             commands::market_data::get_historical_data,
+            commands::market_data::get_historical_data_multi,
+            commands::market_data::get_historical_data_cached,
+            commands::market_data::clear_historical_cache,
             commands::market_data::get_stock_info,
             commands::market_data::get_financials,
+            commands::market_data::get_fx_rate,
             commands::polygon::execute_polygon_command,
             commands::yfinance::execute_yfinance_command,
             commands::edgar::execute_edgar_command,
@@ -48,17 +83,26 @@ macro_rules! for_each_tauri_command {
             commands::alphavantage::get_alphavantage_intraday,
             commands::alphavantage::get_alphavantage_overview,
             commands::alphavantage::search_alphavantage_symbols,
+            commands::symbol_search::search_symbols,
             commands::alphavantage::get_alphavantage_comprehensive,
             commands::alphavantage::get_alphavantage_market_movers,
             commands::pmdarima::pmdarima_fit_auto_arima,
             commands::pmdarima::pmdarima_forecast_auto_arima,
             commands::pmdarima::pmdarima_forecast_arima,
             commands::pmdarima::pmdarima_boxcox_transform,
+            commands::pmdarima::pmdarima_boxcox_auto,
             commands::pmdarima::pmdarima_inverse_boxcox,
             commands::pmdarima::pmdarima_calculate_acf,
             commands::pmdarima::pmdarima_calculate_pacf,
             commands::pmdarima::pmdarima_decompose_timeseries,
             commands::pmdarima::pmdarima_cross_validate,
+            commands::series_analytics::series_rolling_mean,
+            commands::series_analytics::series_rolling_std,
+            commands::series_analytics::series_returns,
+            commands::series_analytics::series_log_returns,
+            commands::series_analytics::series_zscore,
+            commands::series_analytics::series_correlation,
+            commands::indicators::compute_indicators,
             commands::government_us::execute_government_us_command,
             commands::government_us::get_treasury_prices,
             commands::government_us::get_treasury_auctions,
@@ -613,6 +657,8 @@ macro_rules! for_each_tauri_command {
             commands::portfolio_management::portfolio_sell_asset,
             commands::portfolio_management::portfolio_get_assets,
             commands::portfolio_management::portfolio_get_transactions,
+            commands::portfolio_management::portfolio_compute_rebalance,
+            commands::portfolio_management::get_portfolio_summary,
             // Portfolio Analytics
             commands::portfolio::calculate_portfolio_metrics,
             commands::portfolio::optimize_portfolio,
@@ -921,25 +967,60 @@ macro_rules! for_each_tauri_command {
             commands::orderbook::batch_merge_orderbook,
             // High-Performance Rust SQLite Database Commands
             commands::database::db_check_health,
+            commands::database::get_db_status,
+            commands::database::get_db_stats,
+            commands::system_health::system_health,
+            commands::process_usage::get_process_resource_usage,
+            commands::process_usage::start_process_usage_stream,
+            commands::process_usage::stop_process_usage_stream,
+            commands::orphans::list_orphans,
+            commands::orphans::reap_orphans,
+            commands::database::import_database,
+            commands::database::export_config,
+            commands::database::import_config,
+            commands::database::export_settings_snapshot,
+            commands::database::diff_settings,
+            commands::task_scheduler::schedule_task,
+            commands::task_scheduler::list_scheduled_tasks,
+            commands::task_scheduler::set_scheduled_task_enabled,
+            commands::task_scheduler::delete_scheduled_task,
+            commands::trading_calendar::is_market_open,
+            commands::trading_calendar::next_session,
+            commands::trading_calendar::set_calendar_override,
+            commands::trading_calendar::delete_calendar_override,
+            commands::trading_calendar::list_calendar_overrides,
+            commands::database::unlock_database,
+            commands::database::rekey_database,
             commands::database::db_save_setting,
+            commands::database::db_save_settings,
             commands::database::db_get_setting,
             commands::database::db_get_all_settings,
+            commands::database::list_profiles,
+            commands::database::create_profile,
+            commands::database::delete_profile,
+            commands::database::switch_profile,
             commands::database::db_save_credential,
             commands::database::db_get_credentials,
             commands::database::db_get_credential_by_service,
             commands::database::db_delete_credential,
             commands::database::db_get_llm_configs,
             commands::database::db_save_llm_config,
+            commands::llm_check::test_llm_config,
+            commands::llm_chat::llm_chat_stream,
+            commands::llm_tokens::estimate_tokens,
             commands::database::db_get_llm_global_settings,
             commands::database::db_save_llm_global_settings,
             commands::database::db_create_chat_session,
             commands::database::db_get_chat_sessions,
             commands::database::db_add_chat_message,
             commands::database::db_get_chat_messages,
+            commands::database::db_get_chat_messages_paged,
             commands::database::db_delete_chat_session,
             commands::database::db_save_data_source,
             commands::database::db_get_all_data_sources,
             commands::database::db_delete_data_source,
+            commands::data_source_probe::test_data_source,
+            commands::data_source_probe::probe_all_data_sources,
             commands::database::db_add_mcp_server,
             commands::database::db_get_mcp_servers,
             commands::database::db_delete_mcp_server,
@@ -965,20 +1046,35 @@ macro_rules! for_each_tauri_command {
             commands::database::db_get_portfolio,
             commands::database::db_list_portfolios,
             commands::database::db_update_portfolio_balance,
+            commands::database::db_adjust_portfolio_balance,
             commands::database::db_delete_portfolio,
+            commands::database::purge_portfolio,
             commands::database::db_create_position,
             commands::database::db_get_position,
             commands::database::db_get_position_by_symbol,
             commands::database::db_get_position_by_symbol_and_side,
             commands::database::db_get_portfolio_positions,
             commands::database::db_update_position,
+            commands::database::db_reduce_position,
+            commands::database::recompute_unrealized_pnl,
+            commands::database::ingest_marks,
+            commands::database::get_trade_statistics,
             commands::database::db_delete_position,
             commands::database::db_create_order,
+            commands::database::create_bracket_order,
+            commands::database::open_market_position,
+            commands::paper_trading::preview_order,
+            commands::paper_consolidation::get_consolidated_positions,
+            commands::paper_risk::get_paper_risk_limits,
+            commands::paper_risk::set_paper_risk_limits,
+            commands::paper_precision::get_paper_symbol_spec,
+            commands::paper_precision::set_paper_symbol_spec,
             commands::database::db_get_order,
             commands::database::db_get_pending_orders,
             commands::database::db_get_portfolio_orders,
             commands::database::db_update_order,
             commands::database::db_delete_order,
+            commands::database::db_cancel_all_orders,
             commands::database::db_create_trade,
             commands::database::db_get_trade,
             commands::database::db_get_order_trades,
@@ -1017,9 +1113,45 @@ macro_rules! tauri_command_path_list {
 pub const TAURI_COMMAND_PATHS: &[&str] = crate::for_each_tauri_command!(tauri_command_path_list);
 
 pub fn is_known_command(command: &str) -> bool {
-    TAURI_COMMAND_PATHS.iter().any(|path| {
-        path.rsplit("::").next().map_or(false, |name| name == command)
-    })
+    TAURI_COMMAND_PATHS.iter().any(|path| command_name(path) == command)
+}
+
+// Commands handled by `server::rpc::dispatch`, extracted from its match arms by
+// build.rs so web/desktop parity can't drift from the code.
+include!(concat!(env!("OUT_DIR"), "/web_rpc_commands.rs"));
+
+/// Invoke name of a registered command path (`commands::news::fetch_all_rss_news` -> `fetch_all_rss_news`)
+pub fn command_name(path: &str) -> &str {
+    path.rsplit("::").next().unwrap_or(path)
+}
+
+/// Registered commands partitioned by where they can be called
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct CommandAvailability {
+    /// Registered and dispatched by the web RPC endpoint
+    pub web: Vec<&'static str>,
+    /// Registered for the desktop app only
+    pub desktop_only: Vec<&'static str>,
+    /// Dispatched by the web RPC endpoint without a desktop command
+    pub web_only: Vec<&'static str>,
+}
+
+pub fn command_availability() -> CommandAvailability {
+    let mut registered: Vec<&'static str> = TAURI_COMMAND_PATHS.iter().map(|p| command_name(p)).collect();
+    registered.sort_unstable();
+    registered.dedup();
+
+    let (web, desktop_only) = registered
+        .iter()
+        .copied()
+        .partition(|name| WEB_RPC_COMMANDS.contains(name));
+    let web_only = WEB_RPC_COMMANDS
+        .iter()
+        .copied()
+        .filter(|name| !registered.contains(name))
+        .collect();
+
+    CommandAvailability { web, desktop_only, web_only }
 }
 
 #[macro_export]
@@ -1028,3 +1160,20 @@ macro_rules! generate_handler_from_list {
         tauri::generate_handler![$($handler),*]
     };
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_command_availability_partitions_registry() {
+        let availability = command_availability();
+        assert!(availability.web.contains(&"greet"));
+        assert!(availability.web.contains(&"get_command_availability"));
+        assert!(availability.desktop_only.iter().all(|c| !WEB_RPC_COMMANDS.contains(c)));
+
+        let registered: std::collections::HashSet<&str> =
+            TAURI_COMMAND_PATHS.iter().map(|p| command_name(p)).collect();
+        assert_eq!(availability.web.len() + availability.desktop_only.len(), registered.len());
+    }
+}
@@ -57,7 +57,7 @@ async fn execute_python_alpha_command(args: &[&str]) -> Result<Value, String> {
                 }
 
                 if !result.status.success() {
-                    return Err(format!("Python error: {}", stderr));
+                    return Err(crate::utils::python_errors::python_failure(&stderr));
                 }
 
                 Ok(String::from_utf8_lossy(&result.stdout).to_string())
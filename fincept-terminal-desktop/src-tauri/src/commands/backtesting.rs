@@ -10,7 +10,8 @@ use std::process::{Command, Stdio};
 use std::sync::{Arc, Mutex};
 use std::collections::HashMap;
 use tauri::State;
-use crate::utils::python;
+use crate::utils::command_timeout::with_timeout;
+use crate::utils::{python, python_errors};
 
 // Windows-specific imports to hide console windows
 #[cfg(target_os = "windows")]
@@ -87,6 +88,7 @@ pub async fn execute_lean_cli(
         .map_err(|e| format!("Failed to spawn Lean CLI: {}", e))?;
 
     let pid = child.id();
+    crate::commands::orphans::record("backtest", program, pid);
 
     // Store process info
     {
@@ -202,16 +204,19 @@ pub async fn execute_python_backtest(
     #[cfg(target_os = "windows")]
     cmd.creation_flags(0x08000000); // CREATE_NO_WINDOW
 
-    let output = cmd
-        .arg(&script_path)
-        .arg(&command)
-        .arg(&args)
-        .output()
-        .map_err(|e| format!("Failed to execute Python script: {}", e))?;
+    cmd.arg(&script_path).arg(&command).arg(&args);
+
+    // kill_on_drop: a backtest that outlives its timeout (utils::command_timeout) is killed
+    let output = with_timeout(
+        "execute_python_backtest",
+        tokio::process::Command::from(cmd).kill_on_drop(true).output(),
+    )
+    .await?
+    .map_err(|e| format!("Failed to execute Python script: {}", e))?;
 
     if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr);
-        return Err(format!("Python script failed: {}", stderr));
+        return Err(python_errors::python_failure(&stderr));
     }
 
     let stdout = String::from_utf8_lossy(&output.stdout).to_string();
@@ -0,0 +1,238 @@
+// Data Source Probe - reachability check for configured data sources
+//
+// What: `test_data_source(id)` checks that one stored data source's endpoint
+//       answers; `probe_all_data_sources()` checks every enabled source at once
+//       and returns id -> { ok, latency_ms, error } for the settings page's
+//       "check all" button.
+// Why:  The data sources page had no way to tell a dead endpoint from a quiet one
+//       short of subscribing and waiting for data.
+// How:  The endpoint is the `url`/`endpoint`/`base_url` in the source's config
+//       JSON, falling back to the WebSocket provider config's endpoint. HTTP(S)
+//       endpoints get a GET and anything below a 5xx counts as reachable (a 401
+//       still proves the host is up); ws:// and wss:// endpoints get a TCP
+//       connect, which avoids provider-specific handshakes. Probes run at most
+//       `concurrency` at a time, each under its own timeout, and the batch is
+//       registered with `utils::cancellation` so `cancel_request` drops it along
+//       with any in-flight connections.
+
+use crate::database::operations;
+use crate::database::types::DataSource;
+use crate::utils::cancellation;
+use futures::stream::{self, StreamExt};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+
+const DEFAULT_TIMEOUT_MS: u64 = 5_000;
+const DEFAULT_CONCURRENCY: usize = 4;
+const MAX_CONCURRENCY: usize = 16;
+/// Config keys that may hold a source's endpoint, in order of preference
+const URL_KEYS: [&str; 6] = ["url", "endpoint", "base_url", "baseUrl", "ws_url", "wsUrl"];
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ProbeResult {
+    pub ok: bool,
+    pub latency_ms: Option<u64>,
+    pub error: Option<String>,
+}
+
+impl ProbeResult {
+    fn failed(error: impl Into<String>) -> Self {
+        Self {
+            ok: false,
+            latency_ms: None,
+            error: Some(error.into()),
+        }
+    }
+}
+
+/// Endpoint from the source's config, else `ws_endpoint` (its provider's config)
+pub fn source_endpoint(source: &DataSource, ws_endpoint: Option<&str>) -> Result<String, String> {
+    let config: Value = serde_json::from_str(&source.config).unwrap_or(Value::Null);
+    URL_KEYS
+        .iter()
+        .filter_map(|key| config.get(*key).and_then(|v| v.as_str()))
+        .chain(ws_endpoint)
+        .map(str::trim)
+        .find(|url| !url.is_empty())
+        .map(String::from)
+        .ok_or_else(|| format!("Data source '{}' has no endpoint configured", source.alias))
+}
+
+fn resolve_endpoint(source: &DataSource) -> Result<String, String> {
+    let ws_endpoint = if source.ds_type == "websocket" {
+        operations::get_ws_provider_config(&source.provider)
+            .ok()
+            .flatten()
+            .and_then(|config| config.endpoint)
+    } else {
+        None
+    };
+    source_endpoint(source, ws_endpoint.as_deref())
+}
+
+async fn probe_endpoint(client: &reqwest::Client, endpoint: &str) -> Result<(), String> {
+    let url = url::Url::parse(endpoint).map_err(|e| format!("Invalid endpoint '{}': {}", endpoint, e))?;
+    match url.scheme() {
+        "http" | "https" => {
+            let response = client.get(url.as_str()).send().await.map_err(|e| e.to_string())?;
+            let status = response.status();
+            if status.is_server_error() {
+                Err(format!("HTTP {}", status))
+            } else {
+                Ok(())
+            }
+        }
+        "ws" | "wss" => {
+            let host = url.host_str().ok_or_else(|| format!("Endpoint '{}' has no host", endpoint))?;
+            let port = url.port_or_known_default().unwrap_or(443);
+            tokio::net::TcpStream::connect((host, port))
+                .await
+                .map(|_| ())
+                .map_err(|e| e.to_string())
+        }
+        other => Err(format!("Unsupported endpoint scheme '{}'", other)),
+    }
+}
+
+/// Probe one source, giving up after `timeout`
+pub async fn probe_source(client: &reqwest::Client, source: &DataSource, timeout: Duration) -> ProbeResult {
+    let endpoint = match resolve_endpoint(source) {
+        Ok(endpoint) => endpoint,
+        Err(e) => return ProbeResult::failed(e),
+    };
+    let started = Instant::now();
+    match tokio::time::timeout(timeout, probe_endpoint(client, &endpoint)).await {
+        Ok(Ok(())) => ProbeResult {
+            ok: true,
+            latency_ms: Some(started.elapsed().as_millis() as u64),
+            error: None,
+        },
+        Ok(Err(e)) => ProbeResult::failed(e),
+        Err(_) => ProbeResult::failed(format!("No response within {} ms", timeout.as_millis())),
+    }
+}
+
+/// Probe `sources`, at most `concurrency` at a time, keyed by source id
+pub async fn probe_sources(
+    client: &reqwest::Client,
+    sources: Vec<DataSource>,
+    timeout: Duration,
+    concurrency: usize,
+) -> BTreeMap<String, ProbeResult> {
+    stream::iter(sources)
+        .map(|source| async move {
+            let result = probe_source(client, &source, timeout).await;
+            (source.id, result)
+        })
+        .buffer_unordered(concurrency.max(1))
+        .collect()
+        .await
+}
+
+fn probe_timeout(timeout_ms: Option<u64>) -> Duration {
+    Duration::from_millis(timeout_ms.filter(|ms| *ms > 0).unwrap_or(DEFAULT_TIMEOUT_MS))
+}
+
+/// Probe every enabled source; shared by the Tauri command and the web RPC dispatcher
+pub async fn probe_all(
+    timeout_ms: Option<u64>,
+    concurrency: Option<usize>,
+    request_id: Option<String>,
+) -> Result<BTreeMap<String, ProbeResult>, String> {
+    let guard = cancellation::register(request_id).with_timeout("probe_all_data_sources");
+    let sources: Vec<DataSource> = operations::get_all_data_sources()
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .filter(|source| source.enabled)
+        .collect();
+    let client = crate::utils::http::build_client()?;
+    let concurrency = concurrency.unwrap_or(DEFAULT_CONCURRENCY).clamp(1, MAX_CONCURRENCY);
+
+    guard
+        .run(probe_sources(&client, sources, probe_timeout(timeout_ms), concurrency))
+        .await
+}
+
+/// Probe one stored source by id
+pub async fn probe_one(id: &str, timeout_ms: Option<u64>) -> Result<ProbeResult, String> {
+    let source = operations::get_all_data_sources()
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .find(|source| source.id == id)
+        .ok_or_else(|| format!("Data source '{}' not found", id))?;
+    let client = crate::utils::http::build_client()?;
+    Ok(probe_source(&client, &source, probe_timeout(timeout_ms)).await)
+}
+
+/// Check that one data source's endpoint is reachable
+#[tauri::command]
+pub async fn test_data_source(id: String, timeout_ms: Option<u64>) -> Result<ProbeResult, String> {
+    probe_one(&id, timeout_ms).await
+}
+
+/// Check every enabled data source concurrently
+/// Pass `request_id` to allow aborting via `cancel_request`
+#[tauri::command]
+pub async fn probe_all_data_sources(
+    timeout_ms: Option<u64>,
+    concurrency: Option<usize>,
+    request_id: Option<String>,
+) -> Result<BTreeMap<String, ProbeResult>, String> {
+    probe_all(timeout_ms, concurrency, request_id).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn source(id: &str, config: &str) -> DataSource {
+        DataSource {
+            id: id.to_string(),
+            alias: id.to_string(),
+            display_name: id.to_string(),
+            description: None,
+            ds_type: "rest_api".to_string(),
+            provider: "custom".to_string(),
+            category: None,
+            config: config.to_string(),
+            enabled: true,
+            tags: None,
+            created_at: String::new(),
+            updated_at: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_endpoint_from_config_or_provider() {
+        let s = source("a", r#"{"baseUrl": " https://api.example.com ", "mappingId": "m1"}"#);
+        assert_eq!(source_endpoint(&s, None).unwrap(), "https://api.example.com");
+
+        let s = source("b", r#"{"provider": "kraken", "channel": "ticker"}"#);
+        assert_eq!(source_endpoint(&s, Some("wss://ws.kraken.com/v2")).unwrap(), "wss://ws.kraken.com/v2");
+        assert!(source_endpoint(&s, None).unwrap_err().contains("no endpoint"));
+    }
+
+    #[tokio::test]
+    async fn test_probe_sources_reports_each_source() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let sources = vec![
+            source("ws", &format!(r#"{{"url": "ws://127.0.0.1:{}"}}"#, port)),
+            // Accepts the connection but never answers the request
+            source("http", &format!(r#"{{"url": "http://127.0.0.1:{}"}}"#, port)),
+            source("none", "{}"),
+            source("ftp", r#"{"url": "ftp://example.com"}"#),
+        ];
+
+        let client = reqwest::Client::new();
+        let results = probe_sources(&client, sources, Duration::from_millis(200), 2).await;
+
+        assert_eq!(results.len(), 4);
+        assert!(results["ws"].ok && results["ws"].latency_ms.is_some());
+        assert_eq!(results["http"].error.as_deref(), Some("No response within 200 ms"));
+        assert!(!results["none"].ok);
+        assert_eq!(results["ftp"].error.as_deref(), Some("Unsupported endpoint scheme 'ftp'"));
+    }
+}
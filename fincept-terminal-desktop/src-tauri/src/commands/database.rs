@@ -1,6 +1,10 @@
 // Database Tauri Commands - Expose all database operations to frontend
 
 use crate::database::*;
+use super::paper_precision::{fill_fee, normalize_fill, normalize_order};
+use super::paper_risk::{enforce_risk_limits, RiskCheck};
+use super::paper_trading::{liquidation_price, FeeSchedule};
+use crate::utils::symbol::Symbol;
 
 // ============================================================================
 // Database Health Commands
@@ -17,6 +21,107 @@ pub async fn db_check_health() -> Result<bool, String> {
     }
 }
 
+/// Database availability as recorded at init and by the last probe
+///
+/// With `retry = true` a failed initialization is attempted again first.
+#[tauri::command]
+pub async fn get_db_status(retry: Option<bool>) -> Result<DbStatus, String> {
+    if retry.unwrap_or(false) {
+        Ok(pool::retry_init())
+    } else {
+        Ok(pool::probe())
+    }
+}
+
+/// Connection pool usage and how often writes had to wait out a locked database
+#[tauri::command]
+pub async fn get_db_stats() -> Result<pool::DbStats, String> {
+    Ok(pool::stats())
+}
+
+/// Merge watchlists/portfolios from another terminal's database file
+///
+/// `strategy` decides id conflicts: "skip", "overwrite" or "rename".
+/// `tables` limits the merge to some of "watchlists", "portfolios" (default: all).
+#[tauri::command]
+pub async fn import_database(
+    path: String,
+    strategy: String,
+    tables: Option<Vec<String>>,
+) -> Result<import::ImportSummary, String> {
+    let strategy: import::MergeStrategy = strategy.parse()?;
+    import::import_database(std::path::Path::new(&path), strategy, tables.as_deref())
+        .map_err(|e| format!("{:#}", e))
+}
+
+/// Export settings, LLM/data source/WebSocket configs and watchlists as one JSON bundle
+///
+/// API keys and other secrets are left out unless `include_secrets` is true.
+#[tauri::command]
+pub async fn export_config(include_secrets: Option<bool>) -> Result<config_bundle::ConfigBundle, String> {
+    config_bundle::export_config(include_secrets.unwrap_or(false)).map_err(|e| format!("{:#}", e))
+}
+
+/// All non-secret settings as a JSON document for troubleshooting
+#[tauri::command]
+pub async fn export_settings_snapshot() -> Result<settings_snapshot::SettingsSnapshot, String> {
+    settings_snapshot::export_settings_snapshot().map_err(|e| format!("{:#}", e))
+}
+
+/// Compare current settings with a snapshot from `export_settings_snapshot` or a
+/// plain `{ key: value }` object (e.g. defaults)
+#[tauri::command]
+pub async fn diff_settings(snapshot: serde_json::Value) -> Result<settings_snapshot::SettingsDiff, String> {
+    settings_snapshot::diff_settings(&snapshot).map_err(|e| format!("{:#}", e))
+}
+
+/// Restore a bundle from `export_config`; `merge_strategy` is "skip" (default),
+/// "overwrite" or "rename"
+#[tauri::command]
+pub async fn import_config(
+    bundle: serde_json::Value,
+    merge_strategy: Option<String>,
+) -> Result<config_bundle::ConfigImportSummary, String> {
+    let strategy: import::MergeStrategy = merge_strategy.as_deref().unwrap_or("skip").parse()?;
+    let bundle = config_bundle::parse_bundle(bundle).map_err(|e| e.to_string())?;
+    config_bundle::import_config(bundle, strategy).map_err(|e| format!("{:#}", e))
+}
+
+/// Supply the database passphrase (SQLCipher builds) and retry initialization
+#[tauri::command]
+pub async fn unlock_database(passphrase: String) -> Result<DbStatus, String> {
+    if !encryption::is_supported() {
+        return Err("Database encryption is not available in this build".to_string());
+    }
+    if pool::is_initialized() {
+        // Keying new pooled connections of an open database would break them
+        return Err("Database is already open".to_string());
+    }
+    encryption::set_passphrase(Some(passphrase));
+    let status = pool::retry_init();
+    match status.state {
+        DbHealth::Failed => Err(status.reason.unwrap_or_else(|| "Database unavailable".to_string())),
+        _ => Ok(status),
+    }
+}
+
+/// Change the database passphrase; an empty `old_passphrase` encrypts a plain
+/// database and an empty `new_passphrase` decrypts it. Requires a restart.
+#[tauri::command]
+pub async fn rekey_database(old_passphrase: String, new_passphrase: String) -> Result<encryption::RekeyResult, String> {
+    let pool = pool::get_pool().map_err(|e| e.to_string())?;
+    let conn = pool.get().map_err(|e| e.to_string())?;
+    let db_path = pool::get_db_path().map_err(|e| e.to_string())?;
+
+    let result = encryption::rekey(&conn, &db_path, &old_passphrase, &new_passphrase)
+        .map_err(|e| format!("{:#}", e))?;
+    status::set(
+        DbHealth::RestartRequired,
+        Some("Database passphrase changed; restart the terminal to finish".to_string()),
+    );
+    Ok(result)
+}
+
 // ============================================================================
 // Settings Commands
 // ============================================================================
@@ -28,6 +133,12 @@ pub async fn db_save_setting(key: String, value: String, category: Option<String
     Ok("Setting saved successfully".to_string())
 }
 
+/// Save several settings atomically; nothing is written if any value is invalid
+#[tauri::command]
+pub async fn db_save_settings(entries: Vec<SettingEntry>) -> Result<Vec<SettingSaveResult>, String> {
+    operations::save_settings(&entries).map_err(|e| e.to_string())
+}
+
 #[tauri::command]
 pub async fn db_get_setting(key: String) -> Result<Option<String>, String> {
     operations::get_setting(&key).map_err(|e| e.to_string())
@@ -38,6 +149,35 @@ pub async fn db_get_all_settings() -> Result<Vec<Setting>, String> {
     operations::get_all_settings().map_err(|e| e.to_string())
 }
 
+// ============================================================================
+// Config Profile Commands
+// ============================================================================
+
+/// Profiles with their row counts; `active` marks the one in use
+#[tauri::command]
+pub async fn list_profiles() -> Result<Vec<profiles::ConfigProfile>, String> {
+    profiles::list_profiles().map_err(|e| e.to_string())
+}
+
+/// Add a profile, empty or with a copy of the settings, credentials and data
+/// sources of `copy_from`
+#[tauri::command]
+pub async fn create_profile(name: String, copy_from: Option<String>) -> Result<profiles::ConfigProfile, String> {
+    profiles::create_profile(&name, copy_from.as_deref()).map_err(|e| e.to_string())
+}
+
+/// Delete a profile and everything in it; not `default` or the active profile
+#[tauri::command]
+pub async fn delete_profile(name: String) -> Result<profiles::ConfigProfile, String> {
+    profiles::delete_profile(&name).map_err(|e| e.to_string())
+}
+
+/// Make `name` the profile that settings, credentials and data sources are read from
+#[tauri::command]
+pub async fn switch_profile(name: String) -> Result<profiles::ConfigProfile, String> {
+    profiles::switch_profile(&name).map_err(|e| e.to_string())
+}
+
 // ============================================================================
 // Credentials Commands
 // ============================================================================
@@ -112,6 +252,19 @@ pub async fn db_get_chat_messages(session_uuid: String) -> Result<Vec<ChatMessag
     operations::get_chat_messages(&session_uuid).map_err(|e| e.to_string())
 }
 
+/// Newest `limit` messages (default 50, at most 500) before the cursor; see `operations::get_chat_messages_paged`
+#[tauri::command]
+pub async fn db_get_chat_messages_paged(
+    session_uuid: String,
+    before_timestamp: Option<String>,
+    before_id: Option<String>,
+    limit: Option<usize>,
+) -> Result<ChatMessagePage, String> {
+    let limit = limit.unwrap_or(50).clamp(1, 500);
+    operations::get_chat_messages_paged(&session_uuid, before_timestamp.as_deref(), before_id.as_deref(), limit)
+        .map_err(|e| e.to_string())
+}
+
 #[tauri::command]
 pub async fn db_delete_chat_session(session_uuid: String) -> Result<String, String> {
     operations::delete_chat_session(&session_uuid).map_err(|e| e.to_string())?;
@@ -228,7 +381,7 @@ pub async fn db_get_watchlists() -> Result<Vec<Watchlist>, String> {
 }
 
 #[tauri::command]
-pub async fn db_add_watchlist_stock(watchlist_id: String, symbol: String, notes: Option<String>) -> Result<WatchlistStock, String> {
+pub async fn db_add_watchlist_stock(watchlist_id: String, symbol: Symbol, notes: Option<String>) -> Result<WatchlistStock, String> {
     queries::add_watchlist_stock(&watchlist_id, &symbol, notes.as_deref()).map_err(|e| e.to_string())
 }
 
@@ -319,6 +472,7 @@ pub async fn db_clear_market_data_cache() -> Result<String, String> {
 // ============================================================================
 
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub async fn db_create_portfolio(
     id: String,
     name: String,
@@ -327,8 +481,9 @@ pub async fn db_create_portfolio(
     currency: String,
     margin_mode: String,
     leverage: f64,
+    owner: Option<String>,
 ) -> Result<paper_trading::PaperTradingPortfolio, String> {
-    paper_trading::create_portfolio(&id, &name, &provider, initial_balance, &currency, &margin_mode, leverage)
+    paper_trading::create_portfolio(&id, &name, &provider, initial_balance, &currency, &margin_mode, leverage, owner.as_deref())
         .map_err(|e| e.to_string())
 }
 
@@ -348,6 +503,12 @@ pub async fn db_update_portfolio_balance(id: String, new_balance: f64) -> Result
     Ok("Portfolio balance updated successfully".to_string())
 }
 
+/// Add `delta` to a portfolio's balance atomically; returns the new balance
+#[tauri::command]
+pub async fn db_adjust_portfolio_balance(id: String, delta: f64) -> Result<f64, String> {
+    paper_trading::adjust_portfolio_balance(&id, delta).map_err(|e| e.to_string())
+}
+
 #[tauri::command]
 pub async fn db_create_position(
     id: String,
@@ -359,6 +520,7 @@ pub async fn db_create_position(
     leverage: f64,
     margin_mode: String,
 ) -> Result<String, String> {
+    let (quantity, entry_price) = normalize_fill(&symbol, quantity, entry_price)?;
     paper_trading::create_position(&id, &portfolio_id, &symbol, &side, entry_price, quantity, leverage, &margin_mode)
         .map_err(|e| e.to_string())?;
     Ok("Position created successfully".to_string())
@@ -380,11 +542,101 @@ pub async fn db_create_order(
     price: Option<f64>,
     time_in_force: String,
 ) -> Result<String, String> {
+    let (quantity, price) = normalize_order(&symbol, quantity, price)?;
+    let check = RiskCheck { symbol: &symbol, side: &side, quantity, price };
+    enforce_risk_limits(&portfolio_id, &check)?;
     paper_trading::create_order(&id, &portfolio_id, &symbol, &side, &order_type, quantity, price, &time_in_force)
         .map_err(|e| e.to_string())?;
     Ok("Order created successfully".to_string())
 }
 
+/// Create an entry order with linked stop-loss and take-profit legs (one OCO group)
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn create_bracket_order(
+    portfolio_id: String,
+    symbol: String,
+    side: String,
+    order_type: String,
+    quantity: f64,
+    price: Option<f64>,
+    stop_loss: f64,
+    take_profit: f64,
+    time_in_force: Option<String>,
+) -> Result<paper_trading::BracketOrder, String> {
+    let (quantity, price) = normalize_order(&symbol, quantity, price)?;
+    let (_, stop_loss) = normalize_order(&symbol, quantity, Some(stop_loss))?;
+    let (_, take_profit) = normalize_order(&symbol, quantity, Some(take_profit))?;
+    let check = RiskCheck { symbol: &symbol, side: &side, quantity, price };
+    enforce_risk_limits(&portfolio_id, &check)?;
+    let entry = paper_trading::BracketEntry {
+        portfolio_id: &portfolio_id,
+        symbol: &symbol,
+        side: &side,
+        order_type: &order_type,
+        quantity,
+        price,
+        time_in_force: time_in_force.as_deref().unwrap_or("GTC"),
+    };
+    paper_trading::create_bracket_order(&entry, stop_loss.unwrap_or_default(), take_profit.unwrap_or_default())
+        .map_err(|e| e.to_string())
+}
+
+/// Open (or add to) a position at `mark_price` with a filled market order, all in one transaction
+///
+/// `side` is long/short (buy/sell accepted). Leverage and margin mode default to the
+/// portfolio's; the trade pays the taker fee and the liquidation price is computed
+/// with it, as in `preview_order`.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn open_market_position(
+    portfolio_id: String,
+    symbol: String,
+    side: String,
+    quantity: f64,
+    mark_price: f64,
+    leverage: Option<f64>,
+    margin_mode: Option<String>,
+    fees: Option<FeeSchedule>,
+) -> Result<paper_trading::OpenedPosition, String> {
+    let side = match side.to_lowercase().as_str() {
+        "long" | "buy" => "long",
+        "short" | "sell" => "short",
+        other => return Err(format!("Invalid side '{}' (expected long or short)", other)),
+    };
+    let portfolio = paper_trading::get_portfolio(&portfolio_id).map_err(|e| e.to_string())?;
+    let leverage = leverage.unwrap_or(portfolio.leverage);
+    if !(leverage.is_finite() && leverage >= 1.0) {
+        return Err(format!("leverage must be at least 1, got {}", leverage));
+    }
+    let margin_mode = margin_mode.unwrap_or(portfolio.margin_mode);
+    if !["cross", "isolated"].contains(&margin_mode.as_str()) {
+        return Err(format!("margin_mode must be cross or isolated, got '{}'", margin_mode));
+    }
+
+    let (quantity, price) = normalize_order(&symbol, quantity, Some(mark_price))?;
+    let fill_price = price.unwrap_or(mark_price);
+    let order_side = if side == "long" { "buy" } else { "sell" };
+    let check = RiskCheck { symbol: &symbol, side: order_side, quantity, price: Some(fill_price) };
+    enforce_risk_limits(&portfolio_id, &check)?;
+
+    let fees = fees.unwrap_or_default();
+    let open = paper_trading::MarketOpen {
+        portfolio_id: &portfolio_id,
+        symbol: &symbol,
+        side,
+        quantity,
+        fill_price,
+        leverage,
+        margin_mode: &margin_mode,
+        fee_rate: fees.taker,
+    };
+    paper_trading::open_market_position(&open, |entry, qty, lev| {
+        liquidation_price(side, entry, lev, qty, fees.taker, fees.taker)
+    })
+    .map_err(|e| e.to_string())
+}
+
 #[tauri::command]
 pub async fn db_get_portfolio_orders(portfolio_id: String, status: Option<String>) -> Result<Vec<paper_trading::PaperTradingOrder>, String> {
     paper_trading::get_portfolio_orders(&portfolio_id, status.as_deref()).map_err(|e| e.to_string())
@@ -401,6 +653,17 @@ pub async fn db_delete_portfolio(id: String) -> Result<String, String> {
     Ok("Portfolio deleted successfully".to_string())
 }
 
+/// Delete a portfolio with all its positions, orders, trades and risk limits
+///
+/// `confirm` must be true; the deletion can't be undone.
+#[tauri::command]
+pub async fn purge_portfolio(portfolio_id: String, confirm: bool) -> Result<paper_trading::PurgedPortfolio, String> {
+    if !confirm {
+        return Err("Purging deletes the portfolio and all of its data; pass confirm: true to proceed".to_string());
+    }
+    paper_trading::purge_portfolio(&portfolio_id).map_err(|e| e.to_string())
+}
+
 #[tauri::command]
 pub async fn db_get_position(id: String) -> Result<paper_trading::PaperTradingPosition, String> {
     paper_trading::get_position(&id).map_err(|e| e.to_string())
@@ -451,6 +714,50 @@ pub async fn db_update_position(
     Ok("Position updated successfully".to_string())
 }
 
+/// Close part (or all) of an open position, booking realized PnL and the fee to the balance
+#[tauri::command]
+pub async fn db_reduce_position(
+    id: String,
+    close_qty: f64,
+    close_price: f64,
+    fee: Option<f64>,
+) -> Result<paper_trading::PositionReduction, String> {
+    paper_trading::reduce_position(&id, close_qty, close_price, fee.unwrap_or(0.0)).map_err(|e| e.to_string())
+}
+
+/// Mark every open position of a portfolio to `prices` (symbol -> price) in one transaction
+#[tauri::command]
+pub async fn recompute_unrealized_pnl(
+    portfolio_id: String,
+    prices: std::collections::HashMap<String, f64>,
+) -> Result<paper_trading::UnrealizedPnlUpdate, String> {
+    paper_trading::recompute_unrealized_pnl(&portfolio_id, &prices).map_err(|e| e.to_string())
+}
+
+/// Mark open positions in every portfolio to imported prices (symbol -> `{price, timestamp}`)
+/// and snapshot the portfolios affected, in one transaction
+#[tauri::command]
+pub async fn ingest_marks(
+    marks: std::collections::HashMap<String, paper_trading::PriceMark>,
+) -> Result<paper_trading::MarkIngestion, String> {
+    paper_trading::ingest_marks(&marks).map_err(|e| e.to_string())
+}
+
+/// Win rate, average win/loss, profit factor and fees of a paper portfolio
+/// `from`/`to` are inclusive YYYY-MM-DD dates; omit either for an open range
+#[tauri::command]
+pub async fn get_trade_statistics(
+    portfolio_id: String,
+    from: Option<String>,
+    to: Option<String>,
+) -> Result<paper_trading::TradeStatistics, String> {
+    for date in [&from, &to].into_iter().flatten() {
+        chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d")
+            .map_err(|_| format!("Invalid date '{}', expected YYYY-MM-DD", date))?;
+    }
+    paper_trading::get_trade_statistics(&portfolio_id, from.as_deref(), to.as_deref()).map_err(|e| e.to_string())
+}
+
 #[tauri::command]
 pub async fn db_delete_position(id: String) -> Result<String, String> {
     paper_trading::delete_position(&id).map_err(|e| e.to_string())?;
@@ -491,6 +798,14 @@ pub async fn db_delete_order(id: String) -> Result<String, String> {
     Ok("Order deleted successfully".to_string())
 }
 
+/// Cancel all working orders of a portfolio, optionally for one symbol; returns the count
+#[tauri::command]
+pub async fn db_cancel_all_orders(portfolio_id: String, symbol: Option<String>) -> Result<usize, String> {
+    paper_trading::cancel_all_orders(&portfolio_id, symbol.as_deref()).map_err(|e| e.to_string())
+}
+
+/// Store a fill rounded to the symbol's spec; returns the stored trade, whose
+/// price, quantity and fee are what the caller should account with
 #[tauri::command]
 pub async fn db_create_trade(
     id: String,
@@ -503,7 +818,11 @@ pub async fn db_create_trade(
     fee: f64,
     fee_rate: f64,
     is_maker: bool,
-) -> Result<String, String> {
+) -> Result<paper_trading::PaperTradingTrade, String> {
+    let (quantity, price) = normalize_fill(&symbol, quantity, price)?;
+    let fee = fill_fee(quantity, price, fee, fee_rate);
+    let check = RiskCheck { symbol: &symbol, side: &side, quantity, price: Some(price) };
+    enforce_risk_limits(&portfolio_id, &check)?;
     paper_trading::create_trade(
         &id,
         &portfolio_id,
@@ -516,7 +835,7 @@ pub async fn db_create_trade(
         fee_rate,
         is_maker,
     ).map_err(|e| e.to_string())?;
-    Ok("Trade created successfully".to_string())
+    paper_trading::get_trade(&id).map_err(|e| e.to_string())
 }
 
 #[tauri::command]
@@ -13,9 +13,9 @@ pub async fn execute_government_us_command(
     let mut cmd_args = vec![command];
     cmd_args.extend(args);
 
-    // Execute Python script with PyO3
+    // Execute Python script; identical concurrent requests share one run
     let script_path = get_script_path(&app, "government_us_data.py")?;
-    python_runtime::execute_python_script(&script_path, cmd_args)
+    python_runtime::execute_python_script_coalesced(&script_path, cmd_args)
 }
 
 /// Get US Treasury prices for a specific date
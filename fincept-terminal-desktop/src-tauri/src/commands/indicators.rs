@@ -0,0 +1,471 @@
+// Indicators - native technical indicators over candle series
+//
+// What: `compute_indicators` takes OHLC(V) `Candle`s (rows from the historical
+//       data commands fit as they are) and a list of `IndicatorSpec`s, and returns
+//       one series per indicator: SMA, EMA, RSI, MACD and Bollinger Bands.
+// Why:  Charts recomputed these in JavaScript on every render, which stalled the
+//       UI on multi-year intraday series; the Python technical analysis script is
+//       far too slow to call per redraw.
+// How:  Every output line has one entry per candle, `null` until the indicator
+//       has enough history, so the frontend can zip it with the candles. The
+//       formulas follow TA-Lib: EMAs are seeded with the SMA of their first
+//       `period` values, RSI uses Wilder's smoothing, the MACD signal is an EMA
+//       of the MACD line, and Bollinger Bands use the population deviation.
+//       Parameters are validated up front so a bad spec fails the whole call
+//       instead of returning an empty line.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// One bar of any source; other fields of the input (symbol, adj_close, ...) are ignored
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Candle {
+    pub timestamp: i64,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    #[serde(default)]
+    pub volume: Option<f64>,
+}
+
+/// Candle field an indicator is computed over
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PriceSource {
+    Open,
+    High,
+    Low,
+    #[default]
+    Close,
+    /// (high + low) / 2
+    Hl2,
+    /// (high + low + close) / 3
+    Hlc3,
+}
+
+impl PriceSource {
+    fn value(self, candle: &Candle) -> f64 {
+        match self {
+            Self::Open => candle.open,
+            Self::High => candle.high,
+            Self::Low => candle.low,
+            Self::Close => candle.close,
+            Self::Hl2 => (candle.high + candle.low) / 2.0,
+            Self::Hlc3 => (candle.high + candle.low + candle.close) / 3.0,
+        }
+    }
+}
+
+/// One indicator to compute, e.g. `{"type": "macd", "fast": 12, "slow": 26, "signal": 9}`
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum IndicatorSpec {
+    Sma {
+        period: usize,
+        #[serde(default)]
+        source: PriceSource,
+    },
+    Ema {
+        period: usize,
+        #[serde(default)]
+        source: PriceSource,
+    },
+    Rsi {
+        #[serde(default = "default_rsi_period")]
+        period: usize,
+        #[serde(default)]
+        source: PriceSource,
+    },
+    Macd {
+        #[serde(default = "default_macd_fast")]
+        fast: usize,
+        #[serde(default = "default_macd_slow")]
+        slow: usize,
+        #[serde(default = "default_macd_signal")]
+        signal: usize,
+        #[serde(default)]
+        source: PriceSource,
+    },
+    Bollinger {
+        #[serde(default = "default_bollinger_period")]
+        period: usize,
+        #[serde(default = "default_bollinger_std_dev")]
+        std_dev: f64,
+        #[serde(default)]
+        source: PriceSource,
+    },
+}
+
+fn default_rsi_period() -> usize {
+    14
+}
+
+fn default_macd_fast() -> usize {
+    12
+}
+
+fn default_macd_slow() -> usize {
+    26
+}
+
+fn default_macd_signal() -> usize {
+    9
+}
+
+fn default_bollinger_period() -> usize {
+    20
+}
+
+fn default_bollinger_std_dev() -> f64 {
+    2.0
+}
+
+fn check_period(name: &str, period: usize, min: usize) -> Result<(), String> {
+    if period < min {
+        return Err(format!("{} must be at least {}, got {}", name, min, period));
+    }
+    Ok(())
+}
+
+impl IndicatorSpec {
+    /// Identifier of the output series, e.g. "sma_20" or "macd_12_26_9"
+    pub fn id(&self) -> String {
+        match self {
+            Self::Sma { period, .. } => format!("sma_{}", period),
+            Self::Ema { period, .. } => format!("ema_{}", period),
+            Self::Rsi { period, .. } => format!("rsi_{}", period),
+            Self::Macd { fast, slow, signal, .. } => format!("macd_{}_{}_{}", fast, slow, signal),
+            Self::Bollinger { period, std_dev, .. } => format!("bollinger_{}_{}", period, std_dev),
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        match *self {
+            Self::Sma { period, .. } | Self::Ema { period, .. } | Self::Rsi { period, .. } => {
+                check_period("period", period, 1)
+            }
+            Self::Macd { fast, slow, signal, .. } => {
+                check_period("fast", fast, 1)?;
+                check_period("signal", signal, 1)?;
+                if slow <= fast {
+                    return Err(format!("slow period must be greater than fast ({} <= {})", slow, fast));
+                }
+                Ok(())
+            }
+            Self::Bollinger { period, std_dev, .. } => {
+                check_period("period", period, 2)?;
+                if !(std_dev.is_finite() && std_dev > 0.0) {
+                    return Err(format!("std_dev must be a positive number, got {}", std_dev));
+                }
+                Ok(())
+            }
+        }
+    }
+
+    fn source(&self) -> PriceSource {
+        match *self {
+            Self::Sma { source, .. }
+            | Self::Ema { source, .. }
+            | Self::Rsi { source, .. }
+            | Self::Macd { source, .. }
+            | Self::Bollinger { source, .. } => source,
+        }
+    }
+
+    /// Output lines by name, each aligned with `prices`
+    fn compute(&self, prices: &[f64]) -> BTreeMap<&'static str, Vec<Option<f64>>> {
+        match *self {
+            Self::Sma { period, .. } => BTreeMap::from([("value", sma(prices, period))]),
+            Self::Ema { period, .. } => BTreeMap::from([("value", ema(prices, period))]),
+            Self::Rsi { period, .. } => BTreeMap::from([("value", rsi(prices, period))]),
+            Self::Macd { fast, slow, signal, .. } => {
+                let (macd, signal, histogram) = macd(prices, fast, slow, signal);
+                BTreeMap::from([("macd", macd), ("signal", signal), ("histogram", histogram)])
+            }
+            Self::Bollinger { period, std_dev, .. } => {
+                let (upper, middle, lower) = bollinger(prices, period, std_dev);
+                BTreeMap::from([("upper", upper), ("middle", middle), ("lower", lower)])
+            }
+        }
+    }
+}
+
+/// One computed indicator; every line has one value per input candle
+#[derive(Debug, Clone, Serialize)]
+pub struct IndicatorSeries {
+    pub id: String,
+    /// "value" for single-line indicators; "macd"/"signal"/"histogram" for MACD,
+    /// "upper"/"middle"/"lower" for Bollinger Bands
+    pub lines: BTreeMap<&'static str, Vec<Option<f64>>>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct IndicatorResult {
+    /// Candle timestamps, for aligning the lines
+    pub timestamps: Vec<i64>,
+    pub indicators: Vec<IndicatorSeries>,
+}
+
+/// Mean of each trailing `period`
+fn sma(prices: &[f64], period: usize) -> Vec<Option<f64>> {
+    let mut sum = 0.0;
+    prices
+        .iter()
+        .enumerate()
+        .map(|(i, price)| {
+            sum += price;
+            if i >= period {
+                sum -= prices[i - period];
+            }
+            (i + 1 >= period).then(|| sum / period as f64)
+        })
+        .collect()
+}
+
+/// EMA with `alpha = 2 / (period + 1)`, seeded with the SMA of the first `period` values
+fn ema(prices: &[f64], period: usize) -> Vec<Option<f64>> {
+    let mut out = vec![None; prices.len()];
+    if prices.len() < period {
+        return out;
+    }
+    let alpha = 2.0 / (period as f64 + 1.0);
+    let mut value = prices[..period].iter().sum::<f64>() / period as f64;
+    out[period - 1] = Some(value);
+    for (i, price) in prices.iter().enumerate().skip(period) {
+        value += alpha * (price - value);
+        out[i] = Some(value);
+    }
+    out
+}
+
+/// EMA of a line that only has values from some index on
+fn ema_of_line(line: &[Option<f64>], period: usize) -> Vec<Option<f64>> {
+    let start = line.iter().position(Option::is_some).unwrap_or(line.len());
+    let values: Vec<f64> = line[start..].iter().map(|v| v.unwrap_or_default()).collect();
+    let mut out = vec![None; start];
+    out.extend(ema(&values, period));
+    out
+}
+
+/// Wilder's RSI: average gain and loss start as the mean of the first `period`
+/// changes and are then smoothed with `alpha = 1 / period`
+fn rsi(prices: &[f64], period: usize) -> Vec<Option<f64>> {
+    let mut out = vec![None; prices.len()];
+    if prices.len() <= period {
+        return out;
+    }
+    let changes: Vec<f64> = prices.windows(2).map(|w| w[1] - w[0]).collect();
+    let gain = |change: f64| change.max(0.0);
+    let loss = |change: f64| (-change).max(0.0);
+    let n = period as f64;
+
+    let mut avg_gain = changes[..period].iter().copied().map(gain).sum::<f64>() / n;
+    let mut avg_loss = changes[..period].iter().copied().map(loss).sum::<f64>() / n;
+    out[period] = Some(rsi_value(avg_gain, avg_loss));
+    for (i, change) in changes.iter().copied().enumerate().skip(period) {
+        avg_gain = (avg_gain * (n - 1.0) + gain(change)) / n;
+        avg_loss = (avg_loss * (n - 1.0) + loss(change)) / n;
+        out[i + 1] = Some(rsi_value(avg_gain, avg_loss));
+    }
+    out
+}
+
+fn rsi_value(avg_gain: f64, avg_loss: f64) -> f64 {
+    if avg_loss == 0.0 {
+        // No losses: 100, or neutral for a flat series
+        return if avg_gain == 0.0 { 50.0 } else { 100.0 };
+    }
+    100.0 - 100.0 / (1.0 + avg_gain / avg_loss)
+}
+
+type Lines3 = (Vec<Option<f64>>, Vec<Option<f64>>, Vec<Option<f64>>);
+
+/// MACD line (fast EMA - slow EMA), its signal EMA and the histogram between them
+fn macd(prices: &[f64], fast: usize, slow: usize, signal: usize) -> Lines3 {
+    let macd: Vec<Option<f64>> = ema(prices, fast)
+        .into_iter()
+        .zip(ema(prices, slow))
+        .map(|(fast, slow)| Some(fast? - slow?))
+        .collect();
+    let signal = ema_of_line(&macd, signal);
+    let histogram = macd.iter().zip(&signal).map(|(m, s)| Some((*m)? - (*s)?)).collect();
+    (macd, signal, histogram)
+}
+
+/// SMA middle band with bands `std_dev` population deviations above and below
+fn bollinger(prices: &[f64], period: usize, std_dev: f64) -> Lines3 {
+    let middle = sma(prices, period);
+    let (mut upper, mut lower) = (vec![None; prices.len()], vec![None; prices.len()]);
+    for (i, mean) in middle.iter().enumerate() {
+        let Some(mean) = *mean else { continue };
+        let window = &prices[i + 1 - period..=i];
+        let variance = window.iter().map(|p| (p - mean).powi(2)).sum::<f64>() / period as f64;
+        let width = std_dev * variance.sqrt();
+        upper[i] = Some(mean + width);
+        lower[i] = Some(mean - width);
+    }
+    (upper, middle, lower)
+}
+
+/// Compute every indicator in `specs` over `candles`, which must be in time order
+pub fn compute(candles: &[Candle], specs: &[IndicatorSpec]) -> Result<IndicatorResult, String> {
+    if specs.is_empty() {
+        return Err("indicators must list at least one indicator".to_string());
+    }
+    for (i, spec) in specs.iter().enumerate() {
+        spec.validate().map_err(|e| format!("Invalid indicator {} ({}): {}", i, spec.id(), e))?;
+    }
+    if let Some(i) = candles
+        .iter()
+        .position(|c| ![c.open, c.high, c.low, c.close].iter().all(|v| v.is_finite()))
+    {
+        return Err(format!("Candle at index {} has a price that is not a finite number", i));
+    }
+    if let Some(i) = candles.windows(2).position(|w| w[1].timestamp < w[0].timestamp) {
+        return Err(format!("candles must be in ascending time order (index {} is earlier than {})", i + 1, i));
+    }
+
+    let indicators = specs
+        .iter()
+        .map(|spec| {
+            let source = spec.source();
+            let prices: Vec<f64> = candles.iter().map(|c| source.value(c)).collect();
+            IndicatorSeries { id: spec.id(), lines: spec.compute(&prices) }
+        })
+        .collect();
+    Ok(IndicatorResult {
+        timestamps: candles.iter().map(|c| c.timestamp).collect(),
+        indicators,
+    })
+}
+
+#[tauri::command]
+pub async fn compute_indicators(
+    candles: Vec<Candle>,
+    indicators: Vec<IndicatorSpec>,
+) -> Result<IndicatorResult, String> {
+    compute(&candles, &indicators)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn candles(closes: &[f64]) -> Vec<Candle> {
+        closes
+            .iter()
+            .enumerate()
+            .map(|(i, &close)| Candle {
+                timestamp: i as i64 * 86_400,
+                open: close,
+                high: close + 1.0,
+                low: close - 1.0,
+                close,
+                volume: None,
+            })
+            .collect()
+    }
+
+    fn assert_close(actual: &[Option<f64>], expected: &[Option<f64>], tolerance: f64) {
+        assert_eq!(actual.len(), expected.len(), "{:?}", actual);
+        for (a, e) in actual.iter().zip(expected) {
+            match (a, e) {
+                (Some(a), Some(e)) => assert!((a - e).abs() < tolerance, "{:?} vs {:?}", actual, expected),
+                _ => assert_eq!(a, e, "{:?} vs {:?}", actual, expected),
+            }
+        }
+    }
+
+    #[test]
+    fn test_moving_averages() {
+        let prices = [1.0, 2.0, 3.0, 4.0, 5.0, 6.0];
+        assert_close(&sma(&prices, 3), &[None, None, Some(2.0), Some(3.0), Some(4.0), Some(5.0)], 1e-12);
+        // Seeded with SMA(1, 2, 3) = 2; a linear series then lags by (period - 1) / 2
+        assert_close(&ema(&prices, 3), &[None, None, Some(2.0), Some(3.0), Some(4.0), Some(5.0)], 1e-12);
+        assert_close(&ema(&[10.0, 20.0, 10.0], 2), &[None, Some(15.0), Some(35.0 / 3.0)], 1e-12);
+        assert_eq!(ema(&prices, 10), vec![None; 6]);
+    }
+
+    #[test]
+    fn test_rsi_matches_wilder_reference() {
+        // StockCharts' worked example (their table rounds intermediate averages)
+        let closes = [
+            44.34, 44.09, 44.15, 43.61, 44.33, 44.83, 45.10, 45.42, 45.84, 46.08, 45.89, 46.03, 45.61, 46.28,
+            46.28, 46.00, 46.03, 46.41, 46.22, 45.64,
+        ];
+        let values = rsi(&closes, 14);
+        let mut expected = vec![None; 14];
+        expected.extend([70.53, 66.32, 66.55, 69.41, 66.36, 57.97].map(Some));
+        assert_close(&values, &expected, 0.1);
+
+        assert_eq!(rsi(&[1.0, 2.0, 3.0], 2)[2], Some(100.0));
+        assert_eq!(rsi(&[5.0; 4], 2)[3], Some(50.0));
+    }
+
+    #[test]
+    fn test_macd_and_bollinger() {
+        // EMA(12) and EMA(26) of a linear series lag by 5.5 and 12.5, so MACD is 7
+        let linear: Vec<f64> = (0..60).map(f64::from).collect();
+        let (macd_line, signal, histogram) = macd(&linear, 12, 26, 9);
+        assert_eq!(macd_line.iter().position(Option::is_some), Some(25));
+        assert_eq!(signal.iter().position(Option::is_some), Some(33));
+        assert!((macd_line[59].unwrap() - 7.0).abs() < 1e-9);
+        assert!((signal[59].unwrap() - 7.0).abs() < 1e-9);
+        assert!(histogram[59].unwrap().abs() < 1e-9);
+
+        let (upper, middle, lower) = bollinger(&[1.0, 2.0, 3.0, 4.0, 5.0], 5, 2.0);
+        let width = 2.0 * 2.0f64.sqrt();
+        assert_close(&middle, &[None, None, None, None, Some(3.0)], 1e-12);
+        assert_close(&upper, &[None, None, None, None, Some(3.0 + width)], 1e-12);
+        assert_close(&lower, &[None, None, None, None, Some(3.0 - width)], 1e-12);
+    }
+
+    #[test]
+    fn test_compute_parses_specs_and_aligns_output() {
+        let specs: Vec<IndicatorSpec> = serde_json::from_value(json!([
+            { "type": "sma", "period": 2, "source": "hl2" },
+            { "type": "macd" },
+            { "type": "bollinger", "period": 3, "std_dev": 1.5 }
+        ]))
+        .unwrap();
+        assert_eq!(specs[1], IndicatorSpec::Macd { fast: 12, slow: 26, signal: 9, source: PriceSource::Close });
+
+        // A historical data row is accepted as a candle
+        let row: Candle = serde_json::from_value(json!({
+            "symbol": "AAPL", "timestamp": 0, "open": 1.0, "high": 2.0, "low": 0.5, "close": 1.5,
+            "volume": 100, "adj_close": 1.4
+        }))
+        .unwrap();
+        assert_eq!((row.close, row.volume), (1.5, Some(100.0)));
+
+        let result = compute(&candles(&[1.0, 2.0, 3.0, 4.0]), &specs).unwrap();
+        assert_eq!(result.timestamps.len(), 4);
+        let ids: Vec<&str> = result.indicators.iter().map(|s| s.id.as_str()).collect();
+        assert_eq!(ids, ["sma_2", "macd_12_26_9", "bollinger_3_1.5"]);
+        assert!(result.indicators.iter().flat_map(|s| s.lines.values()).all(|line| line.len() == 4));
+        assert_eq!(result.indicators[1].lines.keys().copied().collect::<Vec<_>>(), ["histogram", "macd", "signal"]);
+    }
+
+    #[test]
+    fn test_rejects_invalid_specs_and_candles() {
+        let data = candles(&[1.0, 2.0, 3.0]);
+        let rejected = |spec: IndicatorSpec| compute(&data, &[spec]).unwrap_err();
+
+        assert!(rejected(IndicatorSpec::Sma { period: 0, source: PriceSource::Close }).contains("period must be at least 1"));
+        assert!(rejected(IndicatorSpec::Macd { fast: 26, slow: 12, signal: 9, source: PriceSource::Close })
+            .contains("slow period must be greater than fast"));
+        assert!(rejected(IndicatorSpec::Bollinger { period: 20, std_dev: -1.0, source: PriceSource::Close })
+            .contains("std_dev must be a positive number"));
+        assert!(compute(&data, &[]).is_err());
+
+        let mut unordered = data.clone();
+        unordered.swap(0, 2);
+        let sma = IndicatorSpec::Sma { period: 2, source: PriceSource::Close };
+        assert!(compute(&unordered, &[sma.clone()]).unwrap_err().contains("ascending time order"));
+        let mut broken = data;
+        broken[1].close = f64::NAN;
+        assert!(compute(&broken, &[sma]).unwrap_err().contains("index 1"));
+    }
+}
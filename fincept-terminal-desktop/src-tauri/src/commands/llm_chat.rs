@@ -0,0 +1,353 @@
+// LLM Chat Streaming - provider calls from the backend with stored keys
+//
+// What: `llm_chat_stream(session_uuid, messages, request_id)` sends the conversation
+//       to the active `LLMConfig`, emits an `llm_token` event per streamed chunk and
+//       stores the finished assistant reply with `add_chat_message`.
+// Why:  Chat called providers from the frontend, so API keys had to be handed to
+//       the webview and the global temperature / max tokens / system prompt were
+//       applied (or not) by each caller.
+// How:  `LLMGlobalSettings` are applied here: the system prompt goes first, ahead of
+//       any system messages the caller sent. OpenAI-compatible providers, Anthropic
+//       and Gemini stream server-sent events; Ollama streams one JSON object per
+//       line. Both are split into lines and each line's text delta is forwarded.
+//       `cancel_request(request_id)` stops the stream; text received so far is still
+//       stored and the result is marked `cancelled`. In web server mode there is no
+//       event channel, so only the final message is returned.
+
+use super::llm_check::{api_base, auth_headers, error_message};
+use crate::database::operations;
+use crate::database::types::{ChatMessage, LLMConfig, LLMGlobalSettings};
+use crate::utils::cancellation;
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tauri::Emitter;
+
+/// One turn of the conversation sent by the caller
+#[derive(Debug, Clone, Deserialize)]
+pub struct ChatTurn {
+    /// "system", "user" or "assistant"
+    pub role: String,
+    pub content: String,
+}
+
+/// Payload of the `llm_token` event
+#[derive(Debug, Clone, Serialize)]
+pub struct LlmToken<'a> {
+    pub session_uuid: &'a str,
+    pub request_id: Option<&'a str>,
+    pub delta: &'a str,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LlmChatResult {
+    /// The stored assistant message
+    pub message: ChatMessage,
+    /// The stream was cancelled; `message` holds the text received before that
+    pub cancelled: bool,
+}
+
+/// How a provider's response stream is framed
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum StreamFormat {
+    OpenAi,
+    Anthropic,
+    Gemini,
+    Ollama,
+}
+
+impl StreamFormat {
+    fn for_provider(provider: &str) -> Self {
+        match provider {
+            "anthropic" => Self::Anthropic,
+            "gemini" | "google" => Self::Gemini,
+            "ollama" => Self::Ollama,
+            _ => Self::OpenAi,
+        }
+    }
+}
+
+/// URL and JSON body of the streaming request
+fn build_request(
+    format: StreamFormat,
+    base: &str,
+    config: &LLMConfig,
+    settings: &LLMGlobalSettings,
+    turns: &[ChatTurn],
+) -> (String, Value) {
+    let system: Vec<&str> = std::iter::once(settings.system_prompt.as_str())
+        .chain(turns.iter().filter(|t| t.role == "system").map(|t| t.content.as_str()))
+        .filter(|s| !s.trim().is_empty())
+        .collect();
+    let system = system.join("\n\n");
+    let dialog = turns.iter().filter(|t| t.role != "system");
+
+    match format {
+        StreamFormat::OpenAi | StreamFormat::Ollama => {
+            let mut messages = Vec::new();
+            if !system.is_empty() {
+                messages.push(json!({"role": "system", "content": system}));
+            }
+            messages.extend(dialog.map(|t| json!({"role": t.role, "content": t.content})));
+
+            if format == StreamFormat::Ollama {
+                let body = json!({
+                    "model": config.model,
+                    "messages": messages,
+                    "stream": true,
+                    "options": {"temperature": settings.temperature, "num_predict": settings.max_tokens},
+                });
+                (format!("{}/api/chat", base), body)
+            } else {
+                let body = json!({
+                    "model": config.model,
+                    "messages": messages,
+                    "stream": true,
+                    "temperature": settings.temperature,
+                    "max_tokens": settings.max_tokens,
+                });
+                (format!("{}/chat/completions", base), body)
+            }
+        }
+        StreamFormat::Anthropic => {
+            let mut body = json!({
+                "model": config.model,
+                "messages": dialog.map(|t| json!({"role": t.role, "content": t.content})).collect::<Vec<_>>(),
+                "stream": true,
+                "temperature": settings.temperature,
+                "max_tokens": settings.max_tokens,
+            });
+            if !system.is_empty() {
+                body["system"] = json!(system);
+            }
+            (format!("{}/v1/messages", base), body)
+        }
+        StreamFormat::Gemini => {
+            let contents: Vec<Value> = dialog
+                .map(|t| {
+                    let role = if t.role == "assistant" { "model" } else { "user" };
+                    json!({"role": role, "parts": [{"text": t.content}]})
+                })
+                .collect();
+            let mut body = json!({
+                "contents": contents,
+                "generationConfig": {"temperature": settings.temperature, "maxOutputTokens": settings.max_tokens},
+            });
+            if !system.is_empty() {
+                body["systemInstruction"] = json!({"parts": [{"text": system}]});
+            }
+            (format!("{}/models/{}:streamGenerateContent?alt=sse", base, config.model), body)
+        }
+    }
+}
+
+/// Text delta carried by one line of the response stream
+fn line_delta(format: StreamFormat, line: &str) -> Option<String> {
+    let payload = match format {
+        StreamFormat::Ollama => line.trim(),
+        _ => line.trim().strip_prefix("data:")?.trim(),
+    };
+    if payload.is_empty() || payload == "[DONE]" {
+        return None;
+    }
+    let value: Value = serde_json::from_str(payload).ok()?;
+
+    let text = match format {
+        StreamFormat::OpenAi => value.pointer("/choices/0/delta/content")?.as_str()?.to_string(),
+        StreamFormat::Anthropic => value.pointer("/delta/text")?.as_str()?.to_string(),
+        StreamFormat::Ollama => value.pointer("/message/content")?.as_str()?.to_string(),
+        StreamFormat::Gemini => value
+            .pointer("/candidates/0/content/parts")?
+            .as_array()?
+            .iter()
+            .filter_map(|p| p.get("text").and_then(|t| t.as_str()))
+            .collect(),
+    };
+    (!text.is_empty()).then_some(text)
+}
+
+/// Stream a reply for `turns`, calling `on_token` with each text delta
+pub async fn stream_chat<F>(
+    session_uuid: &str,
+    turns: &[ChatTurn],
+    request_id: Option<String>,
+    on_token: F,
+) -> Result<LlmChatResult, String>
+where
+    F: Fn(&str),
+{
+    if !turns.iter().any(|t| t.role != "system") {
+        return Err("No messages to send".to_string());
+    }
+    let config = operations::get_llm_configs()
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .find(|c| c.is_active)
+        .ok_or_else(|| "No active LLM provider configured".to_string())?;
+    let settings = operations::get_llm_global_settings().map_err(|e| e.to_string())?;
+
+    let provider = config.provider.trim().to_lowercase();
+    let format = StreamFormat::for_provider(&provider);
+    let base = api_base(&provider, config.base_url.as_deref())?;
+    let (url, body) = build_request(format, &base, &config, &settings, turns);
+
+    // The cancellation guard's timeout bounds the stream, not the client
+    let client = crate::utils::http::HttpClientConfig::load().build_streaming_client()?;
+    let mut request = client.post(&url).json(&body);
+    for (name, value) in auth_headers(&provider, config.api_key.as_deref())? {
+        request = request.header(name, value);
+    }
+
+    let guard = cancellation::register(request_id).with_timeout("llm_chat_stream");
+    let response = guard
+        .run(request.send())
+        .await?
+        .map_err(|e| format!("Request failed: {}", e))?;
+    let status = response.status();
+    if !status.is_success() {
+        let body = response.text().await.unwrap_or_default();
+        return Err(error_message(status.as_u16(), &body));
+    }
+
+    let mut stream = Box::pin(response.bytes_stream());
+    // Raw bytes, so characters split across chunks are decoded whole
+    let mut pending: Vec<u8> = Vec::new();
+    let mut reply = String::new();
+    let mut cancelled = false;
+    loop {
+        let chunk = match guard.run(stream.next()).await {
+            Ok(Some(chunk)) => chunk.map_err(|e| format!("Stream interrupted: {}", e))?,
+            Ok(None) => break,
+            // Cancelled or timed out: keep what arrived, if anything did
+            Err(e) if reply.is_empty() => return Err(e),
+            Err(_) => {
+                cancelled = true;
+                break;
+            }
+        };
+        pending.extend_from_slice(&chunk);
+
+        while let Some(end) = pending.iter().position(|&b| b == b'\n') {
+            let line: Vec<u8> = pending.drain(..=end).collect();
+            if let Some(delta) = line_delta(format, &String::from_utf8_lossy(&line)) {
+                on_token(&delta);
+                reply.push_str(&delta);
+            }
+        }
+    }
+    if !cancelled {
+        if let Some(delta) = line_delta(format, &String::from_utf8_lossy(&pending)) {
+            on_token(&delta);
+            reply.push_str(&delta);
+        }
+    }
+
+    let message = operations::add_chat_message(&ChatMessage {
+        id: uuid::Uuid::new_v4().to_string(),
+        session_uuid: session_uuid.to_string(),
+        role: "assistant".to_string(),
+        content: reply,
+        timestamp: String::new(),
+        provider: Some(config.provider.clone()),
+        model: Some(config.model.clone()),
+        tokens_used: None,
+    })
+    .map_err(|e| e.to_string())?;
+
+    Ok(LlmChatResult { message, cancelled })
+}
+
+/// Stream a chat reply from the active LLM provider as `llm_token` events
+#[tauri::command]
+pub async fn llm_chat_stream(
+    app: tauri::AppHandle,
+    session_uuid: String,
+    messages: Vec<ChatTurn>,
+    request_id: Option<String>,
+) -> Result<LlmChatResult, String> {
+    let event_request_id = request_id.clone();
+    stream_chat(&session_uuid, &messages, request_id, |delta| {
+        let _ = app.emit(
+            "llm_token",
+            LlmToken {
+                session_uuid: &session_uuid,
+                request_id: event_request_id.as_deref(),
+                delta,
+            },
+        );
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn turn(role: &str, content: &str) -> ChatTurn {
+        ChatTurn {
+            role: role.to_string(),
+            content: content.to_string(),
+        }
+    }
+
+    fn config(provider: &str) -> LLMConfig {
+        LLMConfig {
+            provider: provider.to_string(),
+            api_key: Some("key".to_string()),
+            base_url: None,
+            model: "m".to_string(),
+            is_active: true,
+            created_at: None,
+            updated_at: None,
+        }
+    }
+
+    fn settings() -> LLMGlobalSettings {
+        LLMGlobalSettings {
+            temperature: 0.2,
+            max_tokens: 64,
+            system_prompt: "Be brief.".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_requests_apply_global_settings() {
+        let turns = vec![turn("system", "Use USD."), turn("user", "Hi")];
+
+        let (url, body) = build_request(StreamFormat::OpenAi, "https://api.openai.com/v1", &config("openai"), &settings(), &turns);
+        assert_eq!(url, "https://api.openai.com/v1/chat/completions");
+        assert_eq!(body["messages"][0]["content"], "Be brief.\n\nUse USD.");
+        assert_eq!(body["messages"][1]["role"], "user");
+        assert_eq!(body["max_tokens"], 64);
+
+        let (_, body) = build_request(StreamFormat::Anthropic, "https://api.anthropic.com", &config("anthropic"), &settings(), &turns);
+        assert_eq!(body["system"], "Be brief.\n\nUse USD.");
+        assert_eq!(body["messages"].as_array().unwrap().len(), 1);
+
+        let (url, body) = build_request(StreamFormat::Gemini, "https://g", &config("gemini"), &settings(), &turns);
+        assert_eq!(url, "https://g/models/m:streamGenerateContent?alt=sse");
+        assert_eq!(body["generationConfig"]["maxOutputTokens"], 64);
+    }
+
+    #[test]
+    fn test_line_delta_per_format() {
+        assert_eq!(
+            line_delta(StreamFormat::OpenAi, r#"data: {"choices":[{"delta":{"content":"Hel"}}]}"#),
+            Some("Hel".to_string())
+        );
+        assert_eq!(line_delta(StreamFormat::OpenAi, "data: [DONE]"), None);
+        assert_eq!(
+            line_delta(StreamFormat::Anthropic, r#"data: {"type":"content_block_delta","delta":{"type":"text_delta","text":"lo"}}"#),
+            Some("lo".to_string())
+        );
+        assert_eq!(line_delta(StreamFormat::Anthropic, "event: content_block_delta"), None);
+        assert_eq!(
+            line_delta(StreamFormat::Gemini, r#"data: {"candidates":[{"content":{"parts":[{"text":"a"},{"text":"b"}]}}]}"#),
+            Some("ab".to_string())
+        );
+        assert_eq!(
+            line_delta(StreamFormat::Ollama, r#"{"message":{"role":"assistant","content":"!"},"done":false}"#),
+            Some("!".to_string())
+        );
+    }
+}
@@ -0,0 +1,306 @@
+// LLM Config Check - verify a provider's key and endpoint before chatting
+//
+// What: `test_llm_config(provider, ...)` calls the provider's model-listing endpoint
+//       with the stored config (or unsaved overrides from the settings form) and
+//       reports success, latency, HTTP status and whether the configured model is
+//       offered.
+// Why:  A wrong key or base URL used to surface only when the first chat message
+//       failed. Listing models is free, needs no tokens, and rejects bad keys the
+//       same way a completion would.
+// How:  One GET per check. OpenAI, DeepSeek, OpenRouter and any provider with a
+//       custom base URL are treated as OpenAI-compatible (`{base}/models`, Bearer
+//       auth); Anthropic, Gemini and Ollama use their own endpoints. Configs are
+//       stored one per provider, so the provider name selects the stored config.
+//       The stored key is only sent to the provider's default endpoint or the
+//       base URL saved with it; checking any other URL needs the key typed in.
+//       Failures are returned as a result with `success: false`, not as an error,
+//       so the UI can show them next to the form.
+
+use crate::database::operations;
+use serde::Serialize;
+use serde_json::Value;
+use std::time::Instant;
+
+const ANTHROPIC_VERSION: &str = "2023-06-01";
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LlmCheckResult {
+    pub provider: String,
+    pub success: bool,
+    pub latency_ms: Option<u64>,
+    pub status: Option<u16>,
+    /// Whether the configured model is in the provider's list (None if not checked)
+    pub model_available: Option<bool>,
+    pub error: Option<String>,
+}
+
+impl LlmCheckResult {
+    fn failed(provider: &str, error: impl Into<String>) -> Self {
+        Self {
+            provider: provider.to_string(),
+            success: false,
+            latency_ms: None,
+            status: None,
+            model_available: None,
+            error: Some(error.into()),
+        }
+    }
+}
+
+/// The request a check sends
+#[derive(Debug, PartialEq)]
+struct Probe {
+    url: String,
+    headers: Vec<(&'static str, String)>,
+}
+
+/// API base for a provider: the configured URL, else the provider's public endpoint
+pub(super) fn api_base(provider: &str, base_url: Option<&str>) -> Result<String, String> {
+    let default = match provider {
+        "openai" => "https://api.openai.com/v1",
+        "deepseek" => "https://api.deepseek.com",
+        "openrouter" => "https://openrouter.ai/api/v1",
+        "anthropic" => "https://api.anthropic.com",
+        "gemini" | "google" => "https://generativelanguage.googleapis.com/v1beta",
+        "ollama" => "http://localhost:11434",
+        _ => "",
+    };
+    let base = base_url
+        .map(str::trim)
+        .filter(|b| !b.is_empty())
+        .unwrap_or(default)
+        .trim_end_matches('/');
+    if base.is_empty() {
+        return Err(format!("Provider '{}' needs a base URL", provider));
+    }
+    Ok(base.to_string())
+}
+
+/// Authentication headers for a provider (Ollama needs none)
+pub(super) fn auth_headers(provider: &str, api_key: Option<&str>) -> Result<Vec<(&'static str, String)>, String> {
+    if provider == "ollama" {
+        return Ok(Vec::new());
+    }
+    let key = api_key
+        .map(str::trim)
+        .filter(|k| !k.is_empty())
+        .ok_or_else(|| "No API key configured".to_string())?
+        .to_string();
+
+    Ok(match provider {
+        "anthropic" => vec![("x-api-key", key), ("anthropic-version", ANTHROPIC_VERSION.to_string())],
+        "gemini" | "google" => vec![("x-goog-api-key", key)],
+        _ => vec![("Authorization", format!("Bearer {}", key))],
+    })
+}
+
+/// Whether the stored key may go to `base_url`: the provider's default endpoint
+/// or the base URL saved with the key, never a URL supplied only for the check
+fn stored_key_allowed(provider: &str, base_url: Option<&str>, stored_base_url: Option<&str>) -> bool {
+    let Ok(target) = api_base(provider, base_url) else {
+        return false;
+    };
+    [None, stored_base_url]
+        .into_iter()
+        .any(|trusted| api_base(provider, trusted).is_ok_and(|base| base == target))
+}
+
+/// Build the model-listing request for a provider
+fn build_probe(provider: &str, api_key: Option<&str>, base_url: Option<&str>) -> Result<Probe, String> {
+    let base = api_base(provider, base_url)?;
+    let url = match provider {
+        "ollama" => format!("{}/api/tags", base),
+        "anthropic" => format!("{}/v1/models", base),
+        _ => format!("{}/models", base),
+    };
+    Ok(Probe {
+        url,
+        headers: auth_headers(provider, api_key)?,
+    })
+}
+
+/// Model ids in a model-listing response
+fn listed_models(body: &Value) -> Vec<String> {
+    let entries = body
+        .get("data")
+        .or_else(|| body.get("models"))
+        .and_then(|v| v.as_array());
+
+    entries
+        .map(|entries| {
+            entries
+                .iter()
+                .filter_map(|m| m.get("id").or_else(|| m.get("name")).and_then(|v| v.as_str()))
+                // Gemini names are "models/<id>"
+                .map(|id| id.trim_start_matches("models/").to_string())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Whether `model` is listed; Ollama tags default to ":latest"
+fn model_listed(models: &[String], model: &str) -> bool {
+    models
+        .iter()
+        .any(|m| m == model || m.strip_suffix(":latest") == Some(model))
+}
+
+/// Readable error from a failed response body
+pub(super) fn error_message(status: u16, body: &str) -> String {
+    let detail = serde_json::from_str::<Value>(body).ok().and_then(|v| {
+        let error = v.get("error")?;
+        error
+            .get("message")
+            .and_then(|m| m.as_str())
+            .or_else(|| error.as_str())
+            .map(String::from)
+    });
+    match detail {
+        Some(detail) => format!("HTTP {}: {}", status, detail),
+        None => format!("HTTP {}: {}", status, body.chars().take(200).collect::<String>()),
+    }
+}
+
+/// Check connectivity for `provider`, overriding stored values with any given
+pub async fn check_llm_config(
+    provider: &str,
+    api_key: Option<String>,
+    base_url: Option<String>,
+    model: Option<String>,
+) -> Result<LlmCheckResult, String> {
+    let provider = provider.trim().to_lowercase();
+    let stored = operations::get_llm_configs()
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .find(|c| c.provider.eq_ignore_ascii_case(&provider));
+
+    let stored_base_url = stored.as_ref().and_then(|c| c.base_url.clone());
+    let base_url = base_url.or_else(|| stored_base_url.clone());
+    let stored_key = stored.as_ref().and_then(|c| c.api_key.clone());
+    let api_key = match api_key {
+        Some(key) => Some(key),
+        None if stored_key_allowed(&provider, base_url.as_deref(), stored_base_url.as_deref()) => stored_key,
+        None if stored_key.is_some() => {
+            return Ok(LlmCheckResult::failed(
+                &provider,
+                "The saved API key is only sent to the saved base URL; enter the key to check this one",
+            ))
+        }
+        None => None,
+    };
+    let model = model
+        .or_else(|| stored.as_ref().map(|c| c.model.clone()))
+        .filter(|m| !m.trim().is_empty());
+
+    let probe = match build_probe(&provider, api_key.as_deref(), base_url.as_deref()) {
+        Ok(probe) => probe,
+        Err(e) => return Ok(LlmCheckResult::failed(&provider, e)),
+    };
+
+    let client = crate::utils::http::build_client()?;
+    let mut request = client.get(&probe.url);
+    for (name, value) in &probe.headers {
+        request = request.header(*name, value);
+    }
+
+    let started = Instant::now();
+    let response = match request.send().await {
+        Ok(response) => response,
+        Err(e) => return Ok(LlmCheckResult::failed(&provider, format!("Request failed: {}", e))),
+    };
+    let latency_ms = started.elapsed().as_millis() as u64;
+    let status = response.status();
+    let body = response.text().await.unwrap_or_default();
+
+    if !status.is_success() {
+        return Ok(LlmCheckResult {
+            latency_ms: Some(latency_ms),
+            status: Some(status.as_u16()),
+            ..LlmCheckResult::failed(&provider, error_message(status.as_u16(), &body))
+        });
+    }
+
+    let models = serde_json::from_str::<Value>(&body)
+        .map(|v| listed_models(&v))
+        .unwrap_or_default();
+    let model_available = match (&model, models.is_empty()) {
+        (Some(model), false) => Some(model_listed(&models, model)),
+        _ => None,
+    };
+
+    Ok(LlmCheckResult {
+        provider,
+        success: true,
+        latency_ms: Some(latency_ms),
+        status: Some(status.as_u16()),
+        model_available,
+        error: None,
+    })
+}
+
+/// Test an LLM provider's key and endpoint (stored config, or unsaved overrides)
+#[tauri::command]
+pub async fn test_llm_config(
+    provider: String,
+    api_key: Option<String>,
+    base_url: Option<String>,
+    model: Option<String>,
+) -> Result<LlmCheckResult, String> {
+    check_llm_config(&provider, api_key, base_url, model).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_probe_per_provider() {
+        let openai = build_probe("openai", Some("sk-1"), None).unwrap();
+        assert_eq!(openai.url, "https://api.openai.com/v1/models");
+        assert_eq!(openai.headers, vec![("Authorization", "Bearer sk-1".to_string())]);
+
+        let custom = build_probe("lmstudio", Some("k"), Some("http://host:1234/v1/")).unwrap();
+        assert_eq!(custom.url, "http://host:1234/v1/models");
+
+        let anthropic = build_probe("anthropic", Some("k"), None).unwrap();
+        assert_eq!(anthropic.url, "https://api.anthropic.com/v1/models");
+        assert!(anthropic.headers.iter().any(|(name, _)| *name == "anthropic-version"));
+
+        let ollama = build_probe("ollama", None, None).unwrap();
+        assert_eq!(ollama.url, "http://localhost:11434/api/tags");
+
+        assert_eq!(build_probe("openai", Some("  "), None).unwrap_err(), "No API key configured");
+        assert!(build_probe("lmstudio", Some("k"), None).is_err());
+    }
+
+    #[test]
+    fn test_stored_key_only_goes_to_trusted_urls() {
+        assert!(stored_key_allowed("openai", None, None));
+        assert!(stored_key_allowed("openai", Some("https://api.openai.com/v1/"), None));
+        assert!(!stored_key_allowed("openai", Some("https://attacker.example/v1"), None));
+
+        let saved = Some("http://host:1234/v1");
+        assert!(stored_key_allowed("lmstudio", saved, saved));
+        assert!(!stored_key_allowed("lmstudio", Some("http://other:1234/v1"), saved));
+        assert!(!stored_key_allowed("lmstudio", None, None));
+    }
+
+    #[test]
+    fn test_listed_models_across_formats() {
+        let openai = serde_json::json!({"data": [{"id": "gpt-4o"}, {"id": "gpt-4o-mini"}]});
+        let gemini = serde_json::json!({"models": [{"name": "models/gemini-1.5-pro"}]});
+        let ollama = serde_json::json!({"models": [{"name": "llama3:latest"}]});
+
+        assert_eq!(listed_models(&openai), vec!["gpt-4o", "gpt-4o-mini"]);
+        assert!(model_listed(&listed_models(&gemini), "gemini-1.5-pro"));
+        assert!(model_listed(&listed_models(&ollama), "llama3"));
+        assert!(!model_listed(&listed_models(&openai), "gpt-5"));
+    }
+
+    #[test]
+    fn test_error_message_prefers_provider_detail() {
+        let body = r#"{"error": {"message": "Incorrect API key provided"}}"#;
+        assert_eq!(error_message(401, body), "HTTP 401: Incorrect API key provided");
+        assert_eq!(error_message(502, "Bad Gateway"), "HTTP 502: Bad Gateway");
+    }
+}
@@ -0,0 +1,177 @@
+// LLM Token Estimates - count prompt tokens before sending
+//
+// What: `estimate_tokens(model, text, messages)` returns a token count per message
+//       and in total, so the chat UI can show the size (and cost) of a prompt and
+//       check it against a budget before calling the provider.
+// Why:  Providers only report usage after the call, when an oversized prompt has
+//       already failed or been paid for.
+// How:  OpenAI models are counted exactly with their tiktoken encoding (o200k_base
+//       for the GPT-4o / o-series / GPT-4.1+ family, cl100k_base for older GPT-4
+//       and GPT-3.5). Other providers' tokenizers aren't public, so their count is
+//       estimated: one token per CJK character plus one per four other characters,
+//       which tracks common BPE tokenizers to within roughly 10-20% on English.
+//       Chat messages add OpenAI's framing overhead (role and separators), and the
+//       reply priming tokens are included in the total.
+
+use super::llm_chat::ChatTurn;
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use tiktoken_rs::CoreBPE;
+
+/// Framing tokens added per chat message
+const TOKENS_PER_MESSAGE: usize = 3;
+/// Tokens that prime the assistant's reply
+const REPLY_PRIMING_TOKENS: usize = 3;
+
+static O200K: Lazy<Option<CoreBPE>> = Lazy::new(|| tiktoken_rs::o200k_base().ok());
+static CL100K: Lazy<Option<CoreBPE>> = Lazy::new(|| tiktoken_rs::cl100k_base().ok());
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TokenEstimate {
+    pub model: String,
+    /// "o200k_base", "cl100k_base" or "heuristic"
+    pub method: String,
+    /// Whether the counts come from the model's own tokenizer
+    pub exact: bool,
+    /// Tokens per message (a single entry for plain text)
+    pub messages: Vec<usize>,
+    pub total: usize,
+}
+
+/// tiktoken encoding used by an OpenAI model, if it is one
+fn openai_encoding(model: &str) -> Option<&'static str> {
+    // Provider-prefixed ids, e.g. "openai/gpt-4o" on OpenRouter
+    let model = model.rsplit('/').next().unwrap_or(model).to_lowercase();
+    let is = |prefix: &str| model.starts_with(prefix);
+
+    if is("gpt-4o") || is("gpt-4.1") || is("gpt-4.5") || is("gpt-5") || is("o1") || is("o3") || is("o4") {
+        Some("o200k_base")
+    } else if is("gpt-4") || is("gpt-3.5") || is("text-embedding") {
+        Some("cl100k_base")
+    } else {
+        None
+    }
+}
+
+fn is_cjk(c: char) -> bool {
+    matches!(c,
+        '\u{3040}'..='\u{30ff}'     // Hiragana, Katakana
+        | '\u{3400}'..='\u{4dbf}'   // CJK Extension A
+        | '\u{4e00}'..='\u{9fff}'   // CJK Unified Ideographs
+        | '\u{ac00}'..='\u{d7af}'   // Hangul syllables
+        | '\u{f900}'..='\u{faff}')  // CJK Compatibility Ideographs
+}
+
+/// Estimated tokens for text without a known tokenizer
+fn heuristic_tokens(text: &str) -> usize {
+    let (cjk, other) = text
+        .chars()
+        .fold((0usize, 0usize), |(cjk, other), c| if is_cjk(c) { (cjk + 1, other) } else { (cjk, other + 1) });
+    cjk + other.div_ceil(4)
+}
+
+/// Count tokens in `text` for `model`, with the method used
+fn count_tokens(text: &str, model: &str) -> (usize, &'static str) {
+    let bpe = match openai_encoding(model) {
+        Some("o200k_base") => O200K.as_ref().map(|bpe| (bpe, "o200k_base")),
+        Some(_) => CL100K.as_ref().map(|bpe| (bpe, "cl100k_base")),
+        None => None,
+    };
+    match bpe {
+        Some((bpe, name)) => (bpe.encode_with_special_tokens(text).len(), name),
+        None => (heuristic_tokens(text), "heuristic"),
+    }
+}
+
+/// Estimate tokens for plain `text` or a list of chat `turns`
+pub fn estimate(model: &str, text: Option<&str>, turns: &[ChatTurn]) -> TokenEstimate {
+    let (messages, method, total) = match text {
+        Some(text) => {
+            let (count, method) = count_tokens(text, model);
+            (vec![count], method, count)
+        }
+        None => {
+            let mut method = "heuristic";
+            let messages: Vec<usize> = turns
+                .iter()
+                .map(|turn| {
+                    let (role, _) = count_tokens(&turn.role, model);
+                    let (content, used) = count_tokens(&turn.content, model);
+                    method = used;
+                    TOKENS_PER_MESSAGE + role + content
+                })
+                .collect();
+            let total = messages.iter().sum::<usize>() + if messages.is_empty() { 0 } else { REPLY_PRIMING_TOKENS };
+            (messages, method, total)
+        }
+    };
+
+    TokenEstimate {
+        model: model.to_string(),
+        method: method.to_string(),
+        exact: method != "heuristic",
+        messages,
+        total,
+    }
+}
+
+/// Estimate prompt tokens for `model` from plain `text` or chat `messages`
+#[tauri::command]
+pub async fn estimate_tokens(
+    model: String,
+    text: Option<String>,
+    messages: Option<Vec<ChatTurn>>,
+) -> Result<TokenEstimate, String> {
+    if text.is_none() && messages.is_none() {
+        return Err("Provide 'text' or 'messages'".to_string());
+    }
+    Ok(estimate(&model, text.as_deref(), &messages.unwrap_or_default()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn turn(role: &str, content: &str) -> ChatTurn {
+        ChatTurn {
+            role: role.to_string(),
+            content: content.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_encoding_by_model() {
+        assert_eq!(openai_encoding("gpt-4o-mini"), Some("o200k_base"));
+        assert_eq!(openai_encoding("openai/o3-mini"), Some("o200k_base"));
+        assert_eq!(openai_encoding("gpt-4-turbo"), Some("cl100k_base"));
+        assert_eq!(openai_encoding("claude-3-5-sonnet"), None);
+        assert_eq!(openai_encoding("llama3"), None);
+    }
+
+    #[test]
+    fn test_openai_models_count_exactly() {
+        let estimate = estimate("gpt-4", Some("hello world"), &[]);
+        assert_eq!(estimate.method, "cl100k_base");
+        assert!(estimate.exact);
+        assert_eq!(estimate.total, 2);
+    }
+
+    #[test]
+    fn test_heuristic_counts_cjk_per_character() {
+        assert_eq!(heuristic_tokens("abcdefgh"), 2);
+        assert_eq!(heuristic_tokens("abcde"), 2);
+        assert_eq!(heuristic_tokens("日本語"), 3);
+        assert_eq!(heuristic_tokens(""), 0);
+    }
+
+    #[test]
+    fn test_chat_messages_include_framing() {
+        let turns = vec![turn("user", "abcdefgh"), turn("assistant", "abcd")];
+        let estimate = estimate("claude-3-5-sonnet", None, &turns);
+
+        assert!(!estimate.exact);
+        // 3 framing + role + content per message, then 3 reply priming tokens
+        assert_eq!(estimate.messages, vec![3 + 1 + 2, 3 + 3 + 1]);
+        assert_eq!(estimate.total, 6 + 7 + 3);
+    }
+}
@@ -0,0 +1,321 @@
+// Log Viewer - read and follow the application log from inside the app
+//
+// What: `tail_log` returns the last lines of the log file (see `logging`);
+//       `start_log_stream` emits a `log_line` event for every line written after
+//       it starts, until `stop_log_stream` or the next `start_log_stream`.
+//       Both can be limited to a minimum level.
+// Why:  Troubleshooting meant finding `<data-dir>/logs/fincept.log` on disk.
+// How:  The level is read from each line: the `level` field of JSON lines, the
+//       level column of human-format lines. Lines without one (wrapped messages,
+//       panics) are kept only when no level filter is given. The stream polls the
+//       file's size; when the file shrinks it was rotated, and reading restarts
+//       at the top of the new file.
+
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use serde::Serialize;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::PathBuf;
+use std::time::Duration;
+use tauri::Emitter;
+
+const DEFAULT_TAIL_LINES: usize = 200;
+const MAX_TAIL_LINES: usize = 5_000;
+const TAIL_BLOCK: u64 = 64 * 1024;
+const STREAM_POLL: Duration = Duration::from_millis(500);
+
+/// Running log stream, replaced by each `start_log_stream`
+static STREAM: Lazy<Mutex<Option<tauri::async_runtime::JoinHandle<()>>>> = Lazy::new(|| Mutex::new(None));
+
+/// Log severity, least severe first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    Trace,
+    Debug,
+    Info,
+    Warn,
+    Error,
+}
+
+impl LogLevel {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "trace" => Some(LogLevel::Trace),
+            "debug" => Some(LogLevel::Debug),
+            "info" => Some(LogLevel::Info),
+            "warn" | "warning" => Some(LogLevel::Warn),
+            "error" => Some(LogLevel::Error),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LogLine {
+    pub line: String,
+    pub level: Option<LogLevel>,
+}
+
+impl LogLine {
+    fn new(line: &str) -> Self {
+        Self { line: line.to_string(), level: line_level(line) }
+    }
+
+    fn passes(&self, min_level: Option<LogLevel>) -> bool {
+        match min_level {
+            None => true,
+            Some(min) => self.level.is_some_and(|level| level >= min),
+        }
+    }
+}
+
+/// Level of a JSON or human-format log line
+fn line_level(line: &str) -> Option<LogLevel> {
+    if line.starts_with('{') {
+        let value: serde_json::Value = serde_json::from_str(line).ok()?;
+        return value.get("level").and_then(|v| v.as_str()).and_then(LogLevel::parse);
+    }
+    // "2024-05-01T10:00:00.000000Z  INFO target: message"
+    line.split_whitespace().take(2).find_map(LogLevel::parse)
+}
+
+fn parse_min_level(level: Option<&str>) -> Result<Option<LogLevel>, String> {
+    match level.map(str::trim).filter(|l| !l.is_empty()) {
+        None => Ok(None),
+        Some(l) => LogLevel::parse(l)
+            .map(Some)
+            .ok_or_else(|| format!("Invalid level '{}' (expected trace, debug, info, warn or error)", l)),
+    }
+}
+
+fn log_path() -> Result<PathBuf, String> {
+    match crate::logging::log_file_path() {
+        Some(path) => Ok(path),
+        None => Ok(crate::utils::data_dir::logs_dir()?.join(crate::logging::LOG_FILE_NAME)),
+    }
+}
+
+/// Last `count` lines of `file` at or above `min_level`, oldest first
+///
+/// Reads backwards a block at a time, so only the end of a large file is read.
+fn read_tail<R: Read + Seek>(file: &mut R, count: usize, min_level: Option<LogLevel>) -> std::io::Result<Vec<LogLine>> {
+    let mut end = file.seek(SeekFrom::End(0))?;
+    let mut carry: Vec<u8> = Vec::new();
+    let mut lines: Vec<LogLine> = Vec::new();
+
+    while end > 0 && lines.len() < count {
+        let start = end.saturating_sub(TAIL_BLOCK);
+        let mut block = vec![0; (end - start) as usize];
+        file.seek(SeekFrom::Start(start))?;
+        file.read_exact(&mut block)?;
+        block.extend_from_slice(&carry);
+
+        // Unless this block starts the file, its first piece may continue a line
+        // from the block before it, so it waits for the next round
+        let (head, body): (&[u8], &[u8]) = if start == 0 {
+            (&[], &block[..])
+        } else {
+            match block.iter().position(|b| *b == b'\n') {
+                Some(i) => (&block[..i], &block[i + 1..]),
+                None => (&block[..], &[]),
+            }
+        };
+        for raw in body.split(|b| *b == b'\n').rev() {
+            let text = String::from_utf8_lossy(raw);
+            let text = text.trim_end_matches('\r');
+            if text.is_empty() {
+                continue;
+            }
+            let line = LogLine::new(text);
+            if line.passes(min_level) {
+                lines.push(line);
+                if lines.len() == count {
+                    break;
+                }
+            }
+        }
+        carry = head.to_vec();
+        end = start;
+    }
+
+    lines.reverse();
+    Ok(lines)
+}
+
+/// Last `lines` lines of the application log (default 200, at most 5000)
+///
+/// With `level`, only lines at that level or more severe are counted and returned.
+#[tauri::command]
+pub async fn tail_log(lines: Option<usize>, level: Option<String>) -> Result<Vec<LogLine>, String> {
+    let min_level = parse_min_level(level.as_deref())?;
+    let count = lines.unwrap_or(DEFAULT_TAIL_LINES).clamp(1, MAX_TAIL_LINES);
+    let path = log_path()?;
+
+    tokio::task::spawn_blocking(move || {
+        let mut file = match std::fs::File::open(&path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(format!("Failed to open {}: {}", path.display(), e)),
+        };
+        read_tail(&mut file, count, min_level).map_err(|e| format!("Failed to read {}: {}", path.display(), e))
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Follows the log file from a byte offset, yielding complete lines
+struct LogFollower {
+    path: PathBuf,
+    offset: u64,
+    partial: Vec<u8>,
+}
+
+impl LogFollower {
+    /// Start at the current end of `path`
+    fn at_end(path: PathBuf) -> Self {
+        let offset = std::fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+        Self { path, offset, partial: Vec::new() }
+    }
+
+    /// Lines completed since the last call
+    fn poll(&mut self) -> std::io::Result<Vec<String>> {
+        let len = match std::fs::metadata(&self.path) {
+            Ok(meta) => meta.len(),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e),
+        };
+        if len < self.offset {
+            // Rotated or truncated: the new file starts from the top
+            self.offset = 0;
+            self.partial.clear();
+        }
+        if len == self.offset {
+            return Ok(Vec::new());
+        }
+
+        let mut file = std::fs::File::open(&self.path)?;
+        file.seek(SeekFrom::Start(self.offset))?;
+        let mut bytes = Vec::new();
+        file.take(len - self.offset).read_to_end(&mut bytes)?;
+        self.offset += bytes.len() as u64;
+
+        self.partial.extend_from_slice(&bytes);
+        let Some(last_newline) = self.partial.iter().rposition(|b| *b == b'\n') else {
+            return Ok(Vec::new());
+        };
+        let rest = self.partial.split_off(last_newline + 1);
+        let complete = std::mem::replace(&mut self.partial, rest);
+        Ok(complete
+            .split(|b| *b == b'\n')
+            .map(|raw| String::from_utf8_lossy(raw).trim_end_matches('\r').to_string())
+            .filter(|line| !line.is_empty())
+            .collect())
+    }
+}
+
+/// Emit a `log_line` event (a `LogLine`) for each new line of the application log
+///
+/// Replaces a stream already running, e.g. to change `level`.
+#[tauri::command]
+pub async fn start_log_stream(app: tauri::AppHandle, level: Option<String>) -> Result<String, String> {
+    let min_level = parse_min_level(level.as_deref())?;
+    let path = log_path()?;
+    let mut follower = LogFollower::at_end(path.clone());
+
+    let task = tauri::async_runtime::spawn(async move {
+        let mut ticker = tokio::time::interval(STREAM_POLL);
+        loop {
+            ticker.tick().await;
+            let lines = match follower.poll() {
+                Ok(lines) => lines,
+                Err(e) => {
+                    tracing::warn!(error = %e, "Log stream could not read the log file");
+                    continue;
+                }
+            };
+            for line in lines {
+                let line = LogLine::new(&line);
+                if line.passes(min_level) {
+                    let _ = app.emit("log_line", &line);
+                }
+            }
+        }
+    });
+    if let Some(previous) = STREAM.lock().replace(task) {
+        previous.abort();
+    }
+
+    Ok(path.to_string_lossy().to_string())
+}
+
+/// Stop the stream started by `start_log_stream`; false if none was running
+#[tauri::command]
+pub async fn stop_log_stream() -> Result<bool, String> {
+    Ok(match STREAM.lock().take() {
+        Some(task) => {
+            task.abort();
+            true
+        }
+        None => false,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Cursor, Write};
+
+    #[test]
+    fn test_levels_read_from_both_formats() {
+        let human = "2024-05-01T10:00:00.000000Z  WARN fincept::ws: reconnecting";
+        let json = r#"{"timestamp":"2024-05-01T10:00:00Z","level":"ERROR","fields":{"message":"boom"}}"#;
+        assert_eq!(line_level(human), Some(LogLevel::Warn));
+        assert_eq!(line_level(json), Some(LogLevel::Error));
+        assert_eq!(line_level("   at src/main.rs:10"), None);
+        assert!(parse_min_level(Some("loud")).is_err());
+    }
+
+    #[test]
+    fn test_tail_counts_matching_lines_across_blocks() {
+        let mut log = String::new();
+        for i in 0..5_000 {
+            let level = if i % 10 == 0 { "ERROR" } else { "INFO" };
+            log.push_str(&format!("2024-05-01T10:00:00Z  {} app: line {}\n", level, i));
+        }
+        let mut file = Cursor::new(log.into_bytes());
+
+        let lines = read_tail(&mut file, 3, None).unwrap();
+        let text: Vec<&str> = lines.iter().map(|l| l.line.rsplit(' ').next().unwrap()).collect();
+        assert_eq!(text, ["4997", "4998", "4999"]);
+
+        let errors = read_tail(&mut file, 400, Some(LogLevel::Warn)).unwrap();
+        assert_eq!(errors.len(), 400);
+        assert!(errors.iter().all(|l| l.level == Some(LogLevel::Error)));
+        assert!(errors[0].line.ends_with("line 1000"));
+        assert!(errors[399].line.ends_with("line 4990"));
+
+        // Fewer lines than asked for: the whole file
+        assert_eq!(read_tail(&mut Cursor::new(b"a\nb".to_vec()), 10, None).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_follower_yields_complete_lines_and_survives_rotation() {
+        let dir = std::env::temp_dir().join(format!("fincept_log_viewer_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("app.log");
+        std::fs::write(&path, "old line\n").unwrap();
+
+        let mut follower = LogFollower::at_end(path.clone());
+        let mut file = std::fs::OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(b"first\nsec").unwrap();
+        assert_eq!(follower.poll().unwrap(), ["first"]);
+        file.write_all(b"ond\n").unwrap();
+        assert_eq!(follower.poll().unwrap(), ["second"]);
+
+        std::fs::write(&path, "fresh\n").unwrap();
+        assert_eq!(follower.poll().unwrap(), ["fresh"]);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
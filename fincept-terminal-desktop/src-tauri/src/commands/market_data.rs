@@ -1,9 +1,16 @@
 // Market data Tauri commands
 // Frontend can call these commands to fetch live market data
 
-use crate::data_sources::yfinance::{YFinanceProvider, QuoteData, HistoricalData};
+use crate::data_sources::yfinance::{YFinanceProvider, QuoteData, HistoricalData, MultiHistoricalData};
+use crate::data_sources::fx::FxRate;
+use crate::database::historical_cache::{self, CachedHistory};
+use crate::database::{cache, queries, types::WatchlistStock};
+use crate::utils::cancellation;
+use crate::utils::command_timeout::with_timeout;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::collections::HashMap;
+use std::future::Future;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct QuoteResponse {
@@ -39,7 +46,7 @@ pub async fn get_market_quote(app: tauri::AppHandle, symbol: String) -> Result<Q
 
     let provider = YFinanceProvider::new(&app).map_err(|e| e.to_string())?;
 
-    match provider.get_quote(&symbol).await {
+    match with_timeout("get_market_quote", provider.get_quote(&symbol)).await? {
         Some(quote) => Ok(QuoteResponse {
             success: true,
             data: Some(quote),
@@ -54,11 +61,16 @@ pub async fn get_market_quote(app: tauri::AppHandle, symbol: String) -> Result<Q
 }
 
 /// Fetch multiple quotes (batch)
+/// Pass `request_id` to allow aborting via `cancel_request`
 #[tauri::command]
-pub async fn get_market_quotes(app: tauri::AppHandle, symbols: Vec<String>) -> Result<QuotesResponse, String> {
-
+pub async fn get_market_quotes(
+    app: tauri::AppHandle,
+    symbols: Vec<String>,
+    request_id: Option<String>,
+) -> Result<QuotesResponse, String> {
+    let guard = cancellation::register(request_id).with_timeout("get_market_quotes");
     let provider = YFinanceProvider::new(&app).map_err(|e| e.to_string())?;
-    let quotes = provider.get_quotes(symbols).await;
+    let quotes = guard.run(provider.get_quotes(symbols)).await?;
 
     Ok(QuotesResponse {
         success: true,
@@ -67,13 +79,111 @@ pub async fn get_market_quotes(app: tauri::AppHandle, symbols: Vec<String>) -> R
     })
 }
 
+/// Quotes cached for a watchlist are reused for this long unless the caller says otherwise
+const WATCHLIST_QUOTE_MAX_AGE_MINUTES: i64 = 10;
+
+/// A watchlist entry with its quote; `quote` is `None` when none could be fetched
+#[derive(Debug, Serialize)]
+pub struct WatchlistQuote {
+    #[serde(flatten)]
+    pub stock: WatchlistStock,
+    pub quote: Option<QuoteData>,
+}
+
+/// Pair each stock with its quote, keeping the watchlist's order
+fn join_watchlist_quotes(stocks: Vec<WatchlistStock>, quotes: &HashMap<String, QuoteData>) -> Vec<WatchlistQuote> {
+    stocks
+        .into_iter()
+        .map(|stock| {
+            let quote = quotes.get(&stock.symbol.to_uppercase()).cloned();
+            WatchlistQuote { stock, quote }
+        })
+        .collect()
+}
+
+/// Stocks of `watchlist_id` with their quotes, taking quotes from the market data cache when
+/// fresh and fetching only the rest; if the fetch fails those rows come back with no quote.
+/// The category is `watchlist_quotes_<id>`: the frontend's `watchlist_<id>` entries also
+/// carry period returns, which these quotes don't have
+pub async fn watchlist_quotes<F, Fut>(
+    watchlist_id: &str,
+    max_age_minutes: i64,
+    fetch: F,
+) -> Result<Vec<WatchlistQuote>, String>
+where
+    F: FnOnce(Vec<String>) -> Fut,
+    Fut: Future<Output = Result<Vec<QuoteData>, String>>,
+{
+    let stocks = queries::get_watchlist_stocks(watchlist_id).map_err(|e| e.to_string())?;
+    let category = format!("watchlist_quotes_{}", watchlist_id);
+
+    let mut quotes: HashMap<String, QuoteData> = HashMap::new();
+    for stock in &stocks {
+        let cached = cache::get_cached_market_data(&stock.symbol, &category, max_age_minutes)
+            .ok()
+            .flatten()
+            .and_then(|json| serde_json::from_str::<QuoteData>(&json).ok());
+        if let Some(quote) = cached {
+            quotes.insert(stock.symbol.to_uppercase(), quote);
+        }
+    }
+
+    let mut missing: Vec<String> = stocks
+        .iter()
+        .map(|stock| stock.symbol.clone())
+        .filter(|symbol| !quotes.contains_key(&symbol.to_uppercase()))
+        .collect();
+    missing.sort();
+    missing.dedup();
+    if !missing.is_empty() {
+        let fetched = fetch(missing).await.unwrap_or_else(|e| {
+            tracing::warn!(error = %e, watchlist_id, "Could not fetch watchlist quotes");
+            Vec::new()
+        });
+        for quote in fetched {
+            if let Ok(json) = serde_json::to_string(&quote) {
+                if let Err(e) = cache::save_market_data_cache(&quote.symbol, &category, &json) {
+                    tracing::warn!(error = %e, symbol = %quote.symbol, "Could not cache a watchlist quote");
+                }
+            }
+            quotes.insert(quote.symbol.to_uppercase(), quote);
+        }
+    }
+
+    Ok(join_watchlist_quotes(stocks, &quotes))
+}
+
+/// A watchlist's stocks joined with their live quotes, in one call
+/// `max_cache_age_minutes` (default 10) bounds how old a cached quote may be
+#[tauri::command]
+pub async fn get_watchlist_quotes(
+    app: tauri::AppHandle,
+    watchlist_id: String,
+    max_cache_age_minutes: Option<i64>,
+    request_id: Option<String>,
+) -> Result<Vec<WatchlistQuote>, String> {
+    let guard = cancellation::register(request_id).with_timeout("get_watchlist_quotes");
+    let provider = YFinanceProvider::new(&app).map_err(|e| e.to_string())?;
+    let max_age = max_cache_age_minutes.unwrap_or(WATCHLIST_QUOTE_MAX_AGE_MINUTES);
+    guard
+        .run(watchlist_quotes(&watchlist_id, max_age, |symbols| async move {
+            Ok(provider.get_quotes(symbols).await)
+        }))
+        .await?
+}
+
 /// Fetch period returns (7D, 30D)
+/// `adjusted` (default true) uses split/dividend-adjusted closes; false gives raw price returns
 #[tauri::command]
-pub async fn get_period_returns(app: tauri::AppHandle, symbol: String) -> Result<PeriodReturnsResponse, String> {
+pub async fn get_period_returns(
+    app: tauri::AppHandle,
+    symbol: String,
+    adjusted: Option<bool>,
+) -> Result<PeriodReturnsResponse, String> {
 
     let provider = YFinanceProvider::new(&app).map_err(|e| e.to_string())?;
 
-    match provider.get_period_returns(&symbol).await {
+    match with_timeout("get_period_returns", provider.get_period_returns(&symbol, adjusted.unwrap_or(true))).await? {
         Some((seven_day, thirty_day)) => Ok(PeriodReturnsResponse {
             success: true,
             data: Some(PeriodReturns {
@@ -95,7 +205,14 @@ pub async fn get_period_returns(app: tauri::AppHandle, symbol: String) -> Result
 #[tauri::command]
 pub async fn check_market_data_health(app: tauri::AppHandle) -> Result<bool, String> {
     let provider = YFinanceProvider::new(&app).map_err(|e| e.to_string())?;
-    Ok(provider.health_check().await)
+    with_timeout("check_market_data_health", provider.health_check()).await
+}
+
+/// Exchange rate from `base` to `quote` currency (cached for 15 minutes)
+#[tauri::command]
+pub async fn get_fx_rate(app: tauri::AppHandle, base: String, quote: String) -> Result<FxRate, String> {
+    let provider = YFinanceProvider::new(&app).map_err(|e| e.to_string())?;
+    with_timeout("get_fx_rate", provider.get_fx_rate(&base, &quote)).await?
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -106,16 +223,19 @@ pub struct HistoricalResponse {
 }
 
 /// Fetch historical data for a symbol
+/// Pass `request_id` to allow aborting via `cancel_request`
 #[tauri::command]
 pub async fn get_historical_data(
     app: tauri::AppHandle,
     symbol: String,
     start_date: String,
     end_date: String,
+    request_id: Option<String>,
 ) -> Result<HistoricalResponse, String> {
+    let guard = cancellation::register(request_id).with_timeout("get_historical_data");
     let provider = YFinanceProvider::new(&app).map_err(|e| e.to_string())?;
 
-    match provider.get_historical(&symbol, &start_date, &end_date).await {
+    match guard.run(provider.get_historical(&symbol, &start_date, &end_date)).await? {
         Some(data) => Ok(HistoricalResponse {
             success: true,
             data,
@@ -129,6 +249,81 @@ pub async fn get_historical_data(
     }
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+pub struct HistoricalMultiResponse {
+    pub success: bool,
+    pub data: MultiHistoricalData,
+    pub error: Option<String>,
+}
+
+/// Fetch historical data for several symbols (e.g. a watchlist) in one call
+/// Symbols that fail are listed in `data.errors`; `align` trims all series to a common date index
+#[tauri::command]
+pub async fn get_historical_data_multi(
+    app: tauri::AppHandle,
+    symbols: Vec<String>,
+    start_date: String,
+    end_date: String,
+    interval: Option<String>,
+    align: Option<bool>,
+    request_id: Option<String>,
+) -> Result<HistoricalMultiResponse, String> {
+    let guard = cancellation::register(request_id).with_timeout("get_historical_data_multi");
+    let interval = crate::data_sources::yfinance::normalize_interval(interval.as_deref())?;
+    let provider = YFinanceProvider::new(&app).map_err(|e| e.to_string())?;
+
+    let data = guard
+        .run(provider.get_historical_multi(symbols, &start_date, &end_date, &interval, align.unwrap_or(false)))
+        .await?;
+
+    let success = !data.data.is_empty();
+    let error = if success {
+        None
+    } else {
+        Some("Failed to fetch historical data for all symbols".to_string())
+    };
+
+    Ok(HistoricalMultiResponse { success, data, error })
+}
+
+/// Fetch historical data through the local candle cache
+/// Only date ranges missing from the cache are fetched from yfinance; `from`/`to` are inclusive
+#[tauri::command]
+pub async fn get_historical_data_cached(
+    app: tauri::AppHandle,
+    symbol: String,
+    from: String,
+    to: String,
+    interval: Option<String>,
+    request_id: Option<String>,
+) -> Result<CachedHistory, String> {
+    let guard = cancellation::register(request_id).with_timeout("get_historical_data_cached");
+    let interval = crate::data_sources::yfinance::normalize_interval(interval.as_deref())?;
+    let provider = YFinanceProvider::new(&app).map_err(|e| e.to_string())?;
+
+    let fetch = |symbol: String, start: String, end: String| {
+        let provider = &provider;
+        let interval = &interval;
+        async move {
+            provider
+                .fetch_historical(&symbol, &start, &end, interval)
+                .await
+                .map_err(|e| e.to_string())
+        }
+    };
+    guard
+        .run(historical_cache::get_cached(&symbol, &from, &to, &interval, fetch))
+        .await?
+        .map_err(|e| e.to_string())
+}
+
+/// Drop cached candles for one symbol, or for all symbols when none is given
+/// Returns the number of candles removed
+#[tauri::command]
+pub async fn clear_historical_cache(symbol: Option<String>) -> Result<usize, String> {
+    historical_cache::clear(symbol.as_deref()).map_err(|e| e.to_string())
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct StockInfoResponse {
     pub success: bool,
@@ -180,3 +375,46 @@ pub async fn get_financials(app: tauri::AppHandle, symbol: String) -> Result<Fin
         }),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_watchlist_rows_keep_their_order_and_metadata() {
+        let stock = |id: &str, symbol: &str, notes: Option<&str>| WatchlistStock {
+            id: id.to_string(),
+            watchlist_id: "w1".to_string(),
+            symbol: symbol.to_string(),
+            added_at: "2024-05-01 10:00:00".to_string(),
+            notes: notes.map(str::to_string),
+        };
+        let quote = QuoteData {
+            symbol: "AAPL".to_string(),
+            price: 190.5,
+            change: 1.5,
+            change_percent: 0.79,
+            volume: Some(1_000),
+            high: None,
+            low: None,
+            open: None,
+            previous_close: Some(189.0),
+            timestamp: 1_714_557_600,
+            currency: Some("USD".to_string()),
+        };
+        let quotes = HashMap::from([("AAPL".to_string(), quote)]);
+
+        let rows = join_watchlist_quotes(
+            vec![stock("s2", "MSFT", None), stock("s1", "aapl", Some("buy under 180"))],
+            &quotes,
+        );
+        assert_eq!(rows.len(), 2);
+        assert_eq!((rows[0].stock.symbol.as_str(), rows[0].quote.is_none()), ("MSFT", true));
+        assert_eq!(rows[1].quote.as_ref().map(|q| q.price), Some(190.5));
+
+        // Stock fields sit beside `quote`, as WatchlistStockWithQuote expects
+        let json = serde_json::to_value(&rows[1]).unwrap();
+        assert_eq!(json["notes"], "buy under 180");
+        assert_eq!(json["quote"]["symbol"], "AAPL");
+    }
+}
@@ -1,10 +1,20 @@
 // Tauri commands module
 pub mod news;
+pub mod news_scheduler;
+pub mod task_scheduler;
+pub mod system_health;
+pub mod trading_calendar;
 pub mod market_data;
 pub mod polygon;
 pub mod yfinance;
 pub mod alphavantage;
+pub mod symbol_search;
 pub mod pmdarima;
+pub mod series_analytics;
+pub mod indicators;
+pub mod log_viewer;
+pub mod process_usage;
+pub mod orphans;
 pub mod government_us;
 pub mod congress_gov;
 pub mod oecd;
@@ -63,11 +73,23 @@ pub mod jupyter;
 // Python Agents
 pub mod agents;
 
+// LLM provider check, backend chat streaming and token estimates
+pub mod llm_check;
+pub mod llm_chat;
+pub mod llm_tokens;
+
+// Reachability checks for configured data sources
+pub mod data_source_probe;
+
 // Portfolio Analytics
 pub mod portfolio;
 
 // Portfolio Management (CRUD operations)
 pub mod portfolio_management;
+pub mod paper_trading;
+pub mod paper_consolidation;
+pub mod paper_risk;
+pub mod paper_precision;
 
 // skfolio - Advanced Portfolio Optimization
 pub mod skfolio;
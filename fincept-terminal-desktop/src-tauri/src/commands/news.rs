@@ -1,10 +1,10 @@
 // RSS News Feed Fetcher - High-performance Rust implementation
 // Fetches real-time news from 20+ financial RSS feeds without CORS restrictions
 
+use futures::stream::{self, StreamExt};
 use reqwest;
 use serde::{Deserialize, Serialize};
-use std::time::{Duration, SystemTime, UNIX_EPOCH};
-use tokio::time::timeout;
+use crate::utils::retry::{get_with_retry, RetryPolicy};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NewsArticle {
@@ -23,6 +23,38 @@ pub struct NewsArticle {
     pub link: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub pub_date: Option<String>,
+    /// `pub_date` normalized to Unix seconds (RSS, Atom and common ISO variants)
+    pub published_at: Option<i64>,
+}
+
+/// Parse a feed date: RFC 2822 (RSS), RFC 3339 (Atom), or ISO 8601 without an
+/// offset (taken as UTC)
+pub fn parse_feed_date(text: &str) -> Option<chrono::DateTime<chrono::FixedOffset>> {
+    let text = text.trim();
+    if let Ok(dt) = chrono::DateTime::parse_from_rfc2822(text) {
+        return Some(dt);
+    }
+    if let Ok(dt) = chrono::DateTime::parse_from_rfc3339(text) {
+        return Some(dt);
+    }
+    let utc = chrono::FixedOffset::east_opt(0)?;
+    for format in ["%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%d %H:%M:%S%.f", "%Y-%m-%dT%H:%M"] {
+        if let Ok(naive) = chrono::NaiveDateTime::parse_from_str(text, format) {
+            return Some(naive.and_utc().with_timezone(&utc));
+        }
+    }
+    chrono::NaiveDate::parse_from_str(text, "%Y-%m-%d")
+        .ok()
+        .and_then(|date| date.and_hms_opt(0, 0, 0))
+        .map(|naive| naive.and_utc().with_timezone(&utc))
+}
+
+/// Stable id for an article, so refreshes and duplicates across feeds line up
+fn article_id(source: &str, link: Option<&str>, headline: &str) -> String {
+    use std::hash::{Hash, Hasher};
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    link.unwrap_or(headline).hash(&mut hasher);
+    format!("{}-{:016x}", source, hasher.finish())
 }
 
 #[derive(Debug, Clone)]
@@ -132,18 +164,27 @@ fn parse_rss_feed(xml_text: &str, feed: &RSSFeed) -> Vec<NewsArticle> {
 
     loop {
         match reader.read_event_into(&mut buf) {
+            // Atom links carry the URL in `href` (usually self-closing)
+            Ok(Event::Empty(e)) | Ok(Event::Start(e)) if e.name().as_ref() == b"link" => {
+                current_tag = "link".to_string();
+                if let Some(ref mut item) = current_item {
+                    if item.link.is_none() {
+                        item.link = e
+                            .attributes()
+                            .flatten()
+                            .find(|a| a.key.as_ref() == b"href")
+                            .map(|a| String::from_utf8_lossy(&a.value).to_string());
+                    }
+                }
+            }
             Ok(Event::Start(e)) => {
                 let tag_name = String::from_utf8_lossy(e.name().as_ref()).to_string();
                 current_tag = tag_name.clone();
 
                 // Start of new item/entry
                 if tag_name == "item" || tag_name == "entry" {
-                    let timestamp = SystemTime::now()
-                        .duration_since(UNIX_EPOCH)
-                        .unwrap()
-                        .as_millis();
                     current_item = Some(NewsArticle {
-                        id: format!("{}-{}", feed.source, timestamp),
+                        id: String::new(),
                         time: String::new(),
                         priority: "ROUTINE".to_string(),
                         category: feed.category.clone(),
@@ -157,12 +198,18 @@ fn parse_rss_feed(xml_text: &str, feed: &RSSFeed) -> Vec<NewsArticle> {
                         classification: "PUBLIC".to_string(),
                         link: None,
                         pub_date: None,
+                        published_at: None,
                     });
                 }
             }
-            Ok(Event::Text(e)) => {
+            // Many feeds wrap titles and descriptions in CDATA
+            Ok(event @ (Event::Text(_) | Event::CData(_))) => {
                 if let Some(ref mut item) = current_item {
-                    let text = e.unescape().unwrap_or_default().to_string();
+                    let text = match event {
+                        Event::Text(e) => e.unescape().unwrap_or_default().to_string(),
+                        Event::CData(e) => String::from_utf8_lossy(&e.into_inner()).to_string(),
+                        _ => unreachable!(),
+                    };
 
                     match current_tag.as_str() {
                         "title" => {
@@ -184,13 +231,12 @@ fn parse_rss_feed(xml_text: &str, feed: &RSSFeed) -> Vec<NewsArticle> {
                         }
                         "pubDate" | "published" | "updated" => {
                             if item.pub_date.is_none() {
-                                item.pub_date = Some(text.clone());
                                 // Extract time
-                                if let Ok(dt) = chrono::DateTime::parse_from_rfc2822(&text) {
-                                    item.time = dt.format("%H:%M:%S").to_string();
-                                } else if let Ok(dt) = chrono::DateTime::parse_from_rfc3339(&text) {
+                                if let Some(dt) = parse_feed_date(&text) {
                                     item.time = dt.format("%H:%M:%S").to_string();
+                                    item.published_at = Some(dt.timestamp());
                                 }
+                                item.pub_date = Some(text);
                             }
                         }
                         _ => {}
@@ -213,6 +259,7 @@ fn parse_rss_feed(xml_text: &str, feed: &RSSFeed) -> Vec<NewsArticle> {
                     if item.time.is_empty() {
                         item.time = chrono::Local::now().format("%H:%M:%S").to_string();
                     }
+                    item.id = article_id(&item.source, item.link.as_deref(), &item.headline);
 
                     // Analyze and enrich the article
                     enrich_article(&mut item);
@@ -311,83 +358,65 @@ fn enrich_article(article: &mut NewsArticle) {
     article.tickers = tickers;
 }
 
-// Fetch single RSS feed with timeout
-async fn fetch_rss_feed(feed: RSSFeed, client: &reqwest::Client) -> Vec<NewsArticle> {
-    // 10 second timeout per feed
-    let fetch_future = client
-        .get(&feed.url)
-        .header("Accept", "application/rss+xml, application/xml, text/xml, */*")
-        .header("User-Agent", "FinceptTerminal/3.0")
-        .send();
-
-    match timeout(Duration::from_secs(10), fetch_future).await {
-        Ok(Ok(response)) => {
-            if response.status().is_success() {
-                match response.text().await {
-                    Ok(xml_text) => {
-                        if xml_text.trim().starts_with('<') {
-                            return parse_rss_feed(&xml_text, &feed);
-                        }
-                    }
-                    Err(_) => {}
+// Fetch single RSS feed (timeouts, proxy and user-agent come from the client config)
+async fn fetch_rss_feed(feed: RSSFeed, client: &reqwest::Client, retry_policy: &RetryPolicy) -> Vec<NewsArticle> {
+    let fetch_result = get_with_retry(
+        client,
+        &feed.url,
+        &[("Accept", "application/rss+xml, application/xml, text/xml, */*")],
+        retry_policy,
+    )
+    .await;
+
+    match fetch_result {
+        Ok(response) => match response.text().await {
+            Ok(xml_text) => {
+                if xml_text.trim().starts_with('<') {
+                    return parse_rss_feed(&xml_text, &feed);
                 }
             }
+            Err(_) => {}
+        },
+        Err(e) => {
+            tracing::warn!(source = %feed.source, error = %e, "RSS feed fetch failed");
         }
-        _ => {}
     }
 
     Vec::new()
 }
 
-// Fetch all RSS feeds in parallel
-#[tauri::command]
-pub async fn fetch_all_rss_news() -> Result<Vec<NewsArticle>, String> {
-    let feeds = get_rss_feeds();
-    let client = reqwest::Client::builder()
-        .timeout(Duration::from_secs(10))
-        .build()
-        .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
-
-    // Fetch all feeds in parallel
-    let mut tasks = Vec::new();
-    for feed in feeds {
-        let client_clone = client.clone();
-        tasks.push(tokio::spawn(async move {
-            fetch_rss_feed(feed, &client_clone).await
-        }));
-    }
+// Fetch all RSS feeds, at most `concurrency` at a time
+pub async fn fetch_feeds(concurrency: usize) -> Result<Vec<NewsArticle>, String> {
+    let client = crate::utils::http::build_client()?;
+    let retry_policy = RetryPolicy::load();
 
-    let mut all_articles = Vec::new();
-    for task in tasks {
-        if let Ok(articles) = task.await {
-            all_articles.extend(articles);
-        }
-    }
+    let mut all_articles: Vec<NewsArticle> = stream::iter(get_rss_feeds())
+        .map(|feed| fetch_rss_feed(feed, &client, &retry_policy))
+        .buffer_unordered(concurrency.max(1))
+        .collect::<Vec<_>>()
+        .await
+        .into_iter()
+        .flatten()
+        .collect();
 
-    // Sort by publication date (newest first)
-    all_articles.sort_by(|a, b| {
-        let date_a = a.pub_date.as_ref().and_then(|d| {
-            chrono::DateTime::parse_from_rfc2822(d)
-                .or_else(|_| chrono::DateTime::parse_from_rfc3339(d))
-                .ok()
-        });
-        let date_b = b.pub_date.as_ref().and_then(|d| {
-            chrono::DateTime::parse_from_rfc2822(d)
-                .or_else(|_| chrono::DateTime::parse_from_rfc3339(d))
-                .ok()
-        });
-
-        match (date_b, date_a) {
-            (Some(db), Some(da)) => db.cmp(&da),
-            (Some(_), None) => std::cmp::Ordering::Less,
-            (None, Some(_)) => std::cmp::Ordering::Greater,
-            (None, None) => std::cmp::Ordering::Equal,
-        }
-    });
+    // Newest first; undated articles last
+    all_articles.sort_by(|a, b| b.published_at.cmp(&a.published_at));
 
     Ok(all_articles)
 }
 
+// Latest merged news, served from the background refresh cache
+#[tauri::command]
+pub async fn fetch_all_rss_news() -> Result<Vec<NewsArticle>, String> {
+    super::news_scheduler::cached_news().await
+}
+
+// Refresh all feeds now and return the new result
+#[tauri::command]
+pub async fn force_refresh_news() -> Result<Vec<NewsArticle>, String> {
+    super::news_scheduler::refresh().await
+}
+
 // Get RSS feed count
 #[tauri::command]
 pub fn get_rss_feed_count() -> usize {
@@ -399,3 +428,55 @@ pub fn get_rss_feed_count() -> usize {
 pub fn get_active_sources() -> Vec<String> {
     get_rss_feeds().into_iter().map(|f| f.source).collect()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_feed_dates_normalize_to_unix_seconds() {
+        // 2024-03-01 12:00:00 UTC in the formats feeds actually use
+        let expected = Some(1_709_294_400);
+        for text in [
+            "Fri, 01 Mar 2024 12:00:00 +0000",
+            "Fri, 01 Mar 2024 07:00:00 EST",
+            "01 Mar 2024 12:00:00 GMT",
+            "2024-03-01T12:00:00Z",
+            "2024-03-01T14:00:00.000+02:00",
+            "2024-03-01T12:00:00",
+            " 2024-03-01 12:00:00 ",
+        ] {
+            assert_eq!(parse_feed_date(text).map(|d| d.timestamp()), expected, "{}", text);
+        }
+        assert!(parse_feed_date("yesterday").is_none());
+    }
+
+    #[test]
+    fn test_parses_atom_entries_with_cdata() {
+        let feed = RSSFeed {
+            name: "Test".to_string(),
+            url: String::new(),
+            category: "MARKETS".to_string(),
+            region: "US".to_string(),
+            source: "TEST".to_string(),
+        };
+        let xml = r#"<feed>
+            <entry>
+                <title><![CDATA[Fed holds rates]]></title>
+                <link rel="alternate" href="https://example.com/a"/>
+                <updated>2024-03-01T12:00:00Z</updated>
+            </entry>
+            <entry>
+                <title>Undated</title>
+            </entry>
+        </feed>"#;
+
+        let articles = parse_rss_feed(xml, &feed);
+        assert_eq!(articles.len(), 2);
+        assert_eq!(articles[0].headline, "Fed holds rates");
+        assert_eq!(articles[0].link.as_deref(), Some("https://example.com/a"));
+        assert_eq!(articles[0].published_at, Some(1_709_294_400));
+        assert_eq!(articles[1].published_at, None);
+        assert_ne!(articles[0].id, articles[1].id);
+    }
+}
@@ -0,0 +1,151 @@
+// News refresh scheduler - background RSS polling with a shared cache
+//
+// What: A background task refetches every RSS feed on an interval and keeps the
+//       merged, sorted result in memory. `fetch_all_rss_news` answers from that
+//       cache, `force_refresh_news` fetches immediately, and each refresh emits
+//       `news_updated` so open news panels update without polling.
+// Why:  `fetch_all_rss_news` used to hit 20+ feeds on every call, so opening the
+//       news tab blocked for seconds and several panels multiplied the traffic.
+// How:  Refreshes are serialized by a lock; a caller that waited on it reuses the
+//       result the other refresh just stored. The interval and concurrency are
+//       re-read from settings before each cycle, so changes apply without a
+//       restart. Without the scheduler (web server mode), a cache older than
+//       twice the interval is refreshed on read instead.
+//
+// Settings (settings table, category "news"):
+//   news_refresh_interval_secs - seconds between background refreshes (default 300, min 30)
+//   news_fetch_concurrency     - feeds fetched at once (default 8, 1-32)
+
+use super::news::{fetch_feeds, NewsArticle};
+use once_cell::sync::{Lazy, OnceCell};
+use parking_lot::RwLock;
+use serde::Serialize;
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::Emitter;
+
+pub const NEWS_INTERVAL_SETTING: &str = "news_refresh_interval_secs";
+pub const NEWS_CONCURRENCY_SETTING: &str = "news_fetch_concurrency";
+
+const DEFAULT_INTERVAL_SECS: u64 = 300;
+const MIN_INTERVAL_SECS: u64 = 30;
+const DEFAULT_CONCURRENCY: usize = 8;
+const MAX_CONCURRENCY: usize = 32;
+
+/// Payload of the `news_updated` event
+#[derive(Debug, Clone, Serialize)]
+pub struct NewsUpdate<'a> {
+    /// Unix millis of the refresh
+    pub fetched_at: i64,
+    pub articles: &'a [NewsArticle],
+}
+
+#[derive(Default)]
+struct NewsCache {
+    articles: Arc<Vec<NewsArticle>>,
+    fetched_at: Option<i64>,
+}
+
+static CACHE: Lazy<RwLock<NewsCache>> = Lazy::new(|| RwLock::new(NewsCache::default()));
+static REFRESH_LOCK: Lazy<tokio::sync::Mutex<()>> = Lazy::new(|| tokio::sync::Mutex::new(()));
+static APP_HANDLE: OnceCell<tauri::AppHandle> = OnceCell::new();
+
+fn setting(key: &str) -> Option<u64> {
+    crate::database::operations::get_setting(key)
+        .ok()
+        .flatten()
+        .and_then(|v| v.trim().parse::<u64>().ok())
+}
+
+fn refresh_interval() -> Duration {
+    let secs = setting(NEWS_INTERVAL_SETTING)
+        .map(|s| s.max(MIN_INTERVAL_SECS))
+        .unwrap_or(DEFAULT_INTERVAL_SECS);
+    Duration::from_secs(secs)
+}
+
+fn fetch_concurrency() -> usize {
+    setting(NEWS_CONCURRENCY_SETTING)
+        .map(|n| (n as usize).clamp(1, MAX_CONCURRENCY))
+        .unwrap_or(DEFAULT_CONCURRENCY)
+}
+
+/// Whether a cache filled at `fetched_at` should be refreshed on read at `now`
+fn is_stale(fetched_at: Option<i64>, now: i64, interval: Duration) -> bool {
+    match fetched_at {
+        Some(at) => now - at > 2 * interval.as_millis() as i64,
+        None => true,
+    }
+}
+
+/// Fetch every feed now, store the result and notify the frontend
+pub async fn refresh() -> Result<Vec<NewsArticle>, String> {
+    let requested_at = chrono::Utc::now().timestamp_millis();
+    let _guard = REFRESH_LOCK.lock().await;
+
+    // Another refresh finished while we waited; its result is fresh enough
+    {
+        let cache = CACHE.read();
+        if cache.fetched_at.is_some_and(|at| at >= requested_at) {
+            return Ok(cache.articles.as_ref().clone());
+        }
+    }
+
+    let articles = Arc::new(fetch_feeds(fetch_concurrency()).await?);
+    let fetched_at = chrono::Utc::now().timestamp_millis();
+    {
+        let mut cache = CACHE.write();
+        cache.articles = articles.clone();
+        cache.fetched_at = Some(fetched_at);
+    }
+
+    if let Some(app) = APP_HANDLE.get() {
+        let _ = app.emit(
+            "news_updated",
+            NewsUpdate {
+                fetched_at,
+                articles: &articles,
+            },
+        );
+    }
+
+    Ok(articles.as_ref().clone())
+}
+
+/// Cached news, fetching first if nothing usable is cached yet
+pub async fn cached_news() -> Result<Vec<NewsArticle>, String> {
+    let fetched_at = CACHE.read().fetched_at;
+    if is_stale(fetched_at, chrono::Utc::now().timestamp_millis(), refresh_interval()) {
+        return refresh().await;
+    }
+    Ok(CACHE.read().articles.as_ref().clone())
+}
+
+/// Start the background refresh loop (desktop only; call once from setup)
+pub fn start(app: tauri::AppHandle) {
+    if APP_HANDLE.set(app).is_err() {
+        return;
+    }
+
+    tauri::async_runtime::spawn(async {
+        loop {
+            if let Err(e) = refresh().await {
+                tracing::warn!(error = %e, "Background news refresh failed");
+            }
+            tokio::time::sleep(refresh_interval()).await;
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cache_staleness() {
+        let interval = Duration::from_secs(300);
+        assert!(is_stale(None, 1_000, interval));
+        assert!(!is_stale(Some(1_000), 601_000, interval));
+        assert!(is_stale(Some(1_000), 601_001, interval));
+    }
+}
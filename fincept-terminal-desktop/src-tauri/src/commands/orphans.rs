@@ -0,0 +1,315 @@
+// Orphaned Processes - children a crashed session left running
+//
+// What: Every long-lived child the app spawns (MCP servers, Python pool workers,
+//       Lean CLI backtests) is recorded in `child_processes-<app pid>.json` in the
+//       data directory. On the next launch, `list_orphans` reports the children of
+//       earlier sessions that are still running, and `reap_orphans(pids)` kills
+//       them (all of them when `pids` is omitted).
+// Why:  After a crash, node/bun/python children of the previous session kept
+//       running, holding memory, ports and files until the next reboot.
+// How:  Each running instance writes only its own file. `start_session` merges the
+//       files of instances that are no longer running (and the legacy shared
+//       `child_processes.json`) once at startup and deletes them; a file whose
+//       instance still runs is left alone. A recorded pid only counts as an
+//       orphan while the process under it has the recorded name and started at
+//       the recorded time, so a pid since reused by another program is never
+//       touched. Children whose parent is another running instance of the app are
+//       left alone too. Nothing is killed until `reap_orphans` is called, and it
+//       re-checks each pid right before killing it. The recorded pid of an MCP
+//       server is often the npx/bunx launcher, so its descendants (the actual
+//       node/bun server) are killed with it, deepest first.
+
+use once_cell::sync::{Lazy, OnceCell};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use sysinfo::{Pid, System};
+
+/// Shared file written by versions that kept a single record for every instance
+const LEGACY_RECORDS_FILE: &str = "child_processes.json";
+const RECORDS_PREFIX: &str = "child_processes-";
+
+/// Children recorded by sessions that are no longer running, read once at startup
+static PREVIOUS: OnceCell<Vec<SpawnedChild>> = OnceCell::new();
+
+/// This app instance: its pid and start time
+static INSTANCE: Lazy<(u32, u64)> = Lazy::new(|| {
+    let pid = std::process::id();
+    let mut system = System::new();
+    let started_at = process_start_time(&mut system, pid).unwrap_or_default();
+    (pid, started_at)
+});
+
+/// Children spawned by this session
+static SESSION: Lazy<Mutex<Vec<SpawnedChild>>> = Lazy::new(|| Mutex::new(Vec::new()));
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SpawnedChild {
+    pub pid: u32,
+    /// "mcp", "python_worker" or "backtest"
+    pub kind: String,
+    /// What was launched, e.g. "npx" or the venv's python
+    pub command: String,
+    /// Process name as the OS reported it after spawning
+    pub name: String,
+    /// Process start time, Unix seconds
+    pub started_at: u64,
+}
+
+/// What the OS reports now for a recorded pid
+struct Observed {
+    name: String,
+    started_at: u64,
+    /// Its parent is a live app instance other than this one
+    owned_by_running_instance: bool,
+}
+
+/// One instance's record file
+#[derive(Debug, Serialize, Deserialize)]
+struct InstanceRecords {
+    pid: u32,
+    started_at: u64,
+    children: Vec<SpawnedChild>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ReapResult {
+    pub killed: Vec<u32>,
+    /// Orphans that could not be killed, e.g. for lack of permission
+    pub failed: Vec<u32>,
+}
+
+fn records_path(pid: u32) -> Result<PathBuf, String> {
+    Ok(crate::utils::data_dir::base_dir()?.join(format!("{}{}.json", RECORDS_PREFIX, pid)))
+}
+
+fn write_records(children: &[SpawnedChild]) -> Result<(), String> {
+    let (pid, started_at) = *INSTANCE;
+    let records = InstanceRecords { pid, started_at, children: children.to_vec() };
+    let json = serde_json::to_string_pretty(&records).map_err(|e| e.to_string())?;
+    std::fs::write(records_path(pid)?, json).map_err(|e| format!("Failed to record child processes: {}", e))
+}
+
+fn process_start_time(system: &mut System, pid: u32) -> Option<u64> {
+    let pid = Pid::from_u32(pid);
+    system.refresh_process(pid);
+    system.process(pid).map(|process| process.start_time())
+}
+
+/// Children recorded by instances that have exited; their files are removed once read
+fn read_stale_records() -> Vec<SpawnedChild> {
+    let Ok(dir) = crate::utils::data_dir::base_dir() else {
+        return Vec::new();
+    };
+    let Ok(entries) = std::fs::read_dir(&dir) else {
+        return Vec::new();
+    };
+    let own_pid = std::process::id();
+    let mut system = System::new();
+    let mut children = Vec::new();
+    for path in entries.flatten().map(|entry| entry.path()) {
+        let Some(file_name) = path.file_name().and_then(|name| name.to_str()) else {
+            continue;
+        };
+        let json = || std::fs::read_to_string(&path).ok();
+        if file_name == LEGACY_RECORDS_FILE {
+            children.extend(json().and_then(|json| serde_json::from_str::<Vec<SpawnedChild>>(&json).ok()).unwrap_or_default());
+        } else if file_name.starts_with(RECORDS_PREFIX) && file_name.ends_with(".json") {
+            let Some(records) = json().and_then(|json| serde_json::from_str::<InstanceRecords>(&json).ok()) else {
+                continue;
+            };
+            if records.pid == own_pid
+                || process_start_time(&mut system, records.pid) == Some(records.started_at)
+            {
+                // This instance, or another one that is still running
+                continue;
+            }
+            children.extend(records.children);
+        } else {
+            continue;
+        }
+        if let Err(e) = std::fs::remove_file(&path) {
+            tracing::warn!(error = %e, path = %path.display(), "Could not remove stale child process records");
+        }
+    }
+    children
+}
+
+/// Load the records of exited sessions and start this session's; only the first call does anything
+pub fn start_session() {
+    PREVIOUS.get_or_init(|| {
+        let previous = read_stale_records();
+        if let Err(e) = write_records(&SESSION.lock()) {
+            tracing::warn!(error = %e, "Could not reset the child process records");
+        }
+        previous
+    });
+}
+
+/// Remember a child this session spawned, so a later launch can find it if it outlives us
+pub fn record(kind: &str, command: &str, pid: u32) {
+    // Never start this session's file before the stale ones are read
+    start_session();
+    let mut system = System::new();
+    let pid_key = Pid::from_u32(pid);
+    system.refresh_process(pid_key);
+    let Some(process) = system.process(pid_key) else {
+        // Already exited
+        return;
+    };
+
+    let mut session = SESSION.lock();
+    session.retain(|child| child.pid != pid);
+    session.push(SpawnedChild {
+        pid,
+        kind: kind.to_string(),
+        command: command.to_string(),
+        name: process.name().to_string(),
+        started_at: process.start_time(),
+    });
+    if let Err(e) = write_records(&session) {
+        tracing::warn!(error = %e, pid, "Could not record a child process");
+    }
+}
+
+fn observe(system: &mut System, pid: u32) -> Option<Observed> {
+    let pid = Pid::from_u32(pid);
+    system.refresh_process(pid);
+    let process = system.process(pid)?;
+    let (name, started_at, parent) = (process.name().to_string(), process.start_time(), process.parent());
+
+    let current = sysinfo::get_current_pid().ok();
+    let own_name = current.and_then(|current| {
+        system.refresh_process(current);
+        system.process(current).map(|p| p.name().to_string())
+    });
+    let owned_by_running_instance = match (parent, own_name) {
+        (Some(parent), Some(own_name)) if Some(parent) != current => {
+            system.refresh_process(parent);
+            system.process(parent).is_some_and(|p| p.name() == own_name)
+        }
+        _ => false,
+    };
+    Some(Observed { name, started_at, owned_by_running_instance })
+}
+
+/// Whether the process now under a recorded pid is still the child that was recorded
+fn is_orphan(child: &SpawnedChild, observed: &Observed) -> bool {
+    observed.name == child.name && observed.started_at == child.started_at && !observed.owned_by_running_instance
+}
+
+fn orphans(system: &mut System) -> Vec<SpawnedChild> {
+    start_session();
+    PREVIOUS
+        .get()
+        .map(|previous| {
+            previous
+                .iter()
+                .filter(|child| observe(system, child.pid).is_some_and(|observed| is_orphan(child, &observed)))
+                .cloned()
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Children of the previous session that are still running
+pub fn orphaned_children() -> Vec<SpawnedChild> {
+    orphans(&mut System::new())
+}
+
+/// Every descendant of `root` in a (pid, parent) table, deepest first
+fn descendants(root: u32, table: &[(u32, Option<u32>)]) -> Vec<u32> {
+    let mut found = Vec::new();
+    let mut frontier = vec![root];
+    while let Some(parent) = frontier.pop() {
+        for &(pid, _) in table.iter().filter(|(pid, p)| *p == Some(parent) && *pid != root && !found.contains(pid)) {
+            found.push(pid);
+            frontier.push(pid);
+        }
+    }
+    found.reverse();
+    found
+}
+
+/// Kill orphans of the previous session and their descendants; `pids` limits it to those pids
+pub fn kill_orphans(pids: Option<&[u32]>) -> ReapResult {
+    let mut system = System::new();
+    let mut result = ReapResult::default();
+    for child in orphans(&mut system) {
+        if pids.is_some_and(|pids| !pids.contains(&child.pid)) {
+            continue;
+        }
+        system.refresh_processes();
+        let table: Vec<(u32, Option<u32>)> = system
+            .processes()
+            .iter()
+            .map(|(pid, process)| (pid.as_u32(), process.parent().map(|parent| parent.as_u32())))
+            .collect();
+        for pid in descendants(child.pid, &table) {
+            if system.process(Pid::from_u32(pid)).is_some_and(|process| process.kill()) {
+                tracing::info!(pid, parent = child.pid, "Killed descendant of an orphaned child process");
+            }
+        }
+        let killed = system.process(Pid::from_u32(child.pid)).is_some_and(|process| process.kill());
+        if killed {
+            tracing::info!(pid = child.pid, kind = %child.kind, name = %child.name, "Killed orphaned child process");
+            result.killed.push(child.pid);
+        } else {
+            result.failed.push(child.pid);
+        }
+    }
+    result
+}
+
+/// Child processes of the previous session that are still running, e.g. after a crash
+#[tauri::command]
+pub async fn list_orphans() -> Result<Vec<SpawnedChild>, String> {
+    tokio::task::spawn_blocking(orphaned_children).await.map_err(|e| e.to_string())
+}
+
+/// Kill the previous session's orphaned child processes, or only those in `pids`
+#[tauri::command]
+pub async fn reap_orphans(pids: Option<Vec<u32>>) -> Result<ReapResult, String> {
+    tokio::task::spawn_blocking(move || kill_orphans(pids.as_deref()))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reused_pids_are_not_orphans() {
+        let child = SpawnedChild {
+            pid: 4242,
+            kind: "mcp".to_string(),
+            command: "npx".to_string(),
+            name: "node".to_string(),
+            started_at: 1_700_000_000,
+        };
+        let observed = |name: &str, started_at, owned_by_running_instance| Observed {
+            name: name.to_string(),
+            started_at,
+            owned_by_running_instance,
+        };
+
+        assert!(is_orphan(&child, &observed("node", 1_700_000_000, false)));
+        // Same pid, but a different program or a later start: the pid was reused
+        assert!(!is_orphan(&child, &observed("sshd", 1_700_000_000, false)));
+        assert!(!is_orphan(&child, &observed("node", 1_700_000_500, false)));
+        // Still the child of another running instance of the app
+        assert!(!is_orphan(&child, &observed("node", 1_700_000_000, true)));
+    }
+
+    #[test]
+    fn test_descendants_are_listed_deepest_first() {
+        // npx (10) -> sh (11) -> node (12); 20 is unrelated, 13 is another child of npx
+        let table = [(10, Some(1)), (11, Some(10)), (12, Some(11)), (13, Some(10)), (20, Some(1))];
+        let found = descendants(10, &table);
+        assert_eq!(found.len(), 3);
+        assert!(found.iter().position(|&p| p == 12) < found.iter().position(|&p| p == 11));
+        assert!(!found.contains(&20) && !found.contains(&10));
+        assert!(descendants(12, &table).is_empty());
+    }
+}
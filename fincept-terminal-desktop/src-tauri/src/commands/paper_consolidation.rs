@@ -0,0 +1,213 @@
+// Consolidated Paper Positions - exposure by symbol across paper portfolios
+//
+// What: `get_consolidated_positions(owner)` sums open positions per symbol over all of
+//       an owner's paper portfolios (or every portfolio when no owner is given),
+//       reporting long, short and net quantity and exposure.
+// Why:  Users running several paper portfolios had no single view of their total
+//       exposure, or of where one portfolio's long offsets another's short.
+// How:  Read-only: loads each portfolio's open positions with
+//       `get_portfolio_positions` and groups them by upper-cased symbol. Exposure uses
+//       the position's last mark (`current_price`), falling back to the entry price.
+//       Amounts are summed as-is, so portfolios in different currencies are not
+//       converted. Portfolios created before owners were tracked have no owner and
+//       only show up when `owner` is omitted.
+
+use crate::database::paper_trading::{self, PaperTradingPortfolio, PaperTradingPosition};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// One portfolio's share of a consolidated symbol
+#[derive(Debug, Clone, Serialize)]
+pub struct PositionContribution {
+    pub portfolio_id: String,
+    pub portfolio_name: String,
+    pub side: String,
+    pub quantity: f64,
+    /// Signed market value (negative for shorts)
+    pub exposure: f64,
+}
+
+/// Net position in one symbol across portfolios
+#[derive(Debug, Clone, Serialize)]
+pub struct ConsolidatedPosition {
+    pub symbol: String,
+    pub long_quantity: f64,
+    pub short_quantity: f64,
+    /// Long minus short quantity
+    pub net_quantity: f64,
+    /// Signed market value of all contributions
+    pub net_exposure: f64,
+    /// Absolute market value of all contributions
+    pub gross_exposure: f64,
+    /// Quantity cancelled out by opposing long and short positions
+    pub offset_quantity: f64,
+    pub unrealized_pnl: f64,
+    pub contributions: Vec<PositionContribution>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ConsolidatedPositions {
+    pub owner: Option<String>,
+    pub portfolio_count: usize,
+    pub positions: Vec<ConsolidatedPosition>,
+    pub total_net_exposure: f64,
+    pub total_gross_exposure: f64,
+    /// Symbols held long in one portfolio and short in another
+    pub offsetting_symbols: Vec<String>,
+}
+
+/// Group open positions of `portfolios` by symbol
+pub fn consolidate_positions(
+    owner: Option<String>,
+    portfolios: &[PaperTradingPortfolio],
+    positions: &[PaperTradingPosition],
+) -> ConsolidatedPositions {
+    let names: BTreeMap<&str, &str> = portfolios.iter().map(|p| (p.id.as_str(), p.name.as_str())).collect();
+    let mut by_symbol: BTreeMap<String, ConsolidatedPosition> = BTreeMap::new();
+
+    for position in positions.iter().filter(|p| p.status == "open") {
+        let symbol = position.symbol.to_uppercase();
+        let is_short = position.side == "short";
+        let mark = position.current_price.unwrap_or(position.entry_price);
+        let sign = if is_short { -1.0 } else { 1.0 };
+        let exposure = sign * position.quantity * mark;
+
+        let entry = by_symbol.entry(symbol.clone()).or_insert_with(|| ConsolidatedPosition {
+            symbol,
+            long_quantity: 0.0,
+            short_quantity: 0.0,
+            net_quantity: 0.0,
+            net_exposure: 0.0,
+            gross_exposure: 0.0,
+            offset_quantity: 0.0,
+            unrealized_pnl: 0.0,
+            contributions: Vec::new(),
+        });
+        if is_short {
+            entry.short_quantity += position.quantity;
+        } else {
+            entry.long_quantity += position.quantity;
+        }
+        entry.net_exposure += exposure;
+        entry.gross_exposure += exposure.abs();
+        entry.unrealized_pnl += position.unrealized_pnl.unwrap_or(0.0);
+        entry.contributions.push(PositionContribution {
+            portfolio_id: position.portfolio_id.clone(),
+            portfolio_name: names.get(position.portfolio_id.as_str()).copied().unwrap_or_default().to_string(),
+            side: position.side.clone(),
+            quantity: position.quantity,
+            exposure,
+        });
+    }
+
+    let mut consolidated: Vec<ConsolidatedPosition> = by_symbol.into_values().collect();
+    for position in &mut consolidated {
+        position.net_quantity = position.long_quantity - position.short_quantity;
+        position.offset_quantity = position.long_quantity.min(position.short_quantity);
+    }
+
+    ConsolidatedPositions {
+        owner,
+        portfolio_count: portfolios.len(),
+        total_net_exposure: consolidated.iter().map(|p| p.net_exposure).sum(),
+        total_gross_exposure: consolidated.iter().map(|p| p.gross_exposure).sum(),
+        offsetting_symbols: consolidated
+            .iter()
+            .filter(|p| p.offset_quantity > 0.0)
+            .map(|p| p.symbol.clone())
+            .collect(),
+        positions: consolidated,
+    }
+}
+
+/// Load and consolidate open positions for `owner` (all portfolios when `None`)
+pub fn load_consolidated_positions(owner: Option<String>) -> Result<ConsolidatedPositions, String> {
+    let portfolios: Vec<PaperTradingPortfolio> = paper_trading::list_portfolios()
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .filter(|p| owner.is_none() || p.owner == owner)
+        .collect();
+
+    let mut positions = Vec::new();
+    for portfolio in &portfolios {
+        positions.extend(
+            paper_trading::get_portfolio_positions(&portfolio.id, Some("open")).map_err(|e| e.to_string())?,
+        );
+    }
+
+    Ok(consolidate_positions(owner, &portfolios, &positions))
+}
+
+/// Net exposure per symbol across an owner's paper portfolios
+#[tauri::command]
+pub async fn get_consolidated_positions(owner: Option<String>) -> Result<ConsolidatedPositions, String> {
+    load_consolidated_positions(owner.filter(|o| !o.is_empty()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn portfolio(id: &str) -> PaperTradingPortfolio {
+        PaperTradingPortfolio {
+            id: id.to_string(),
+            name: format!("Portfolio {}", id),
+            provider: "binance".to_string(),
+            initial_balance: 10000.0,
+            current_balance: 10000.0,
+            currency: "USD".to_string(),
+            margin_mode: "cross".to_string(),
+            leverage: 1.0,
+            created_at: String::new(),
+            updated_at: String::new(),
+            owner: Some("alice".to_string()),
+        }
+    }
+
+    fn position(portfolio_id: &str, symbol: &str, side: &str, quantity: f64, mark: f64) -> PaperTradingPosition {
+        PaperTradingPosition {
+            id: format!("{}-{}-{}", portfolio_id, symbol, side),
+            portfolio_id: portfolio_id.to_string(),
+            symbol: symbol.to_string(),
+            side: side.to_string(),
+            entry_price: mark,
+            quantity,
+            position_value: None,
+            current_price: Some(mark),
+            unrealized_pnl: Some(10.0),
+            realized_pnl: 0.0,
+            leverage: 1.0,
+            margin_mode: "cross".to_string(),
+            liquidation_price: None,
+            opened_at: String::new(),
+            closed_at: None,
+            status: "open".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_consolidates_and_reports_offsets() {
+        let portfolios = vec![portfolio("a"), portfolio("b")];
+        let positions = vec![
+            position("a", "BTCUSDT", "long", 2.0, 100.0),
+            position("b", "btcusdt", "short", 0.5, 100.0),
+            position("b", "ETHUSDT", "long", 3.0, 10.0),
+        ];
+
+        let result = consolidate_positions(Some("alice".to_string()), &portfolios, &positions);
+
+        assert_eq!(result.positions.len(), 2);
+        let btc = &result.positions[0];
+        assert_eq!(btc.symbol, "BTCUSDT");
+        assert_eq!(btc.net_quantity, 1.5);
+        assert_eq!(btc.offset_quantity, 0.5);
+        assert_eq!(btc.net_exposure, 150.0);
+        assert_eq!(btc.gross_exposure, 250.0);
+        assert_eq!(btc.contributions.len(), 2);
+        assert_eq!(btc.contributions[1].portfolio_name, "Portfolio b");
+
+        assert_eq!(result.offsetting_symbols, vec!["BTCUSDT".to_string()]);
+        assert_eq!(result.total_net_exposure, 180.0);
+        assert_eq!(result.total_gross_exposure, 280.0);
+    }
+}
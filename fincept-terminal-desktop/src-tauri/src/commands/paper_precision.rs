@@ -0,0 +1,232 @@
+// Paper Trading Symbol Precision - tick size, lot size and order minimums
+//
+// What: Per-symbol venue constraints (`paper_trading_symbol_specs`). Orders,
+//       positions and fills have their price rounded to the tick size before they
+//       are stored; order quantities are rounded down to the lot size, and orders
+//       below the minimum quantity or notional are rejected.
+// Why:  Prices and quantities were stored as whatever f64 the caller computed,
+//       so float noise (0.30000000000000004 BTC) ended up in the database and on
+//       screen, and the simulator accepted sizes no real venue would.
+// How:  Rounding goes through the step's decimal places, so the stored value is
+//       the shortest decimal on the grid. Lot size and minimums only apply to new
+//       orders: a partial fill or a position built from fills can legitimately be
+//       smaller, so their quantity is only cleared of float noise. A stored trade's
+//       fee is recomputed from its rounded values and the trade is returned, so
+//       the caller accounts with what was stored. Symbols without a spec are
+//       stored unchanged.
+
+use crate::database::paper_trading::{self, SymbolSpec};
+
+/// Tolerance for quantities that are a lot multiple up to float error
+const LOT_EPSILON: f64 = 1e-9;
+
+/// Decimal places of a step such as 0.01 (2) or 0.5 (1)
+fn step_decimals(step: f64) -> i32 {
+    (0..=12)
+        .find(|d| {
+            let scaled = step * 10f64.powi(*d);
+            (scaled - scaled.round()).abs() < 1e-9 * scaled.max(1.0)
+        })
+        .unwrap_or(12)
+}
+
+/// Round to `decimals` places, dropping float noise
+fn to_decimals(value: f64, decimals: i32) -> f64 {
+    let factor = 10f64.powi(decimals);
+    (value * factor).round() / factor
+}
+
+/// Nearest multiple of `tick`
+pub fn round_price(price: f64, tick: f64) -> f64 {
+    to_decimals((price / tick).round() * tick, step_decimals(tick))
+}
+
+/// Largest multiple of `lot` not above `quantity`
+pub fn round_quantity(quantity: f64, lot: f64) -> f64 {
+    to_decimals((quantity / lot + LOT_EPSILON).floor() * lot, step_decimals(lot))
+}
+
+/// `quantity` snapped to the lot grid when it is on it up to float error, else unchanged
+pub fn snap_quantity(quantity: f64, lot: f64) -> f64 {
+    let lots = quantity / lot;
+    if (lots - lots.round()).abs() < LOT_EPSILON * lots.abs().max(1.0) {
+        to_decimals(lots.round() * lot, step_decimals(lot))
+    } else {
+        quantity
+    }
+}
+
+/// `quantity` and `price` rounded to the spec's lot and tick sizes
+pub fn round_to_spec(spec: &SymbolSpec, quantity: f64, price: Option<f64>) -> Result<(f64, Option<f64>), String> {
+    let quantity = match spec.lot_size {
+        Some(lot) => {
+            let rounded = round_quantity(quantity, lot);
+            if rounded <= 0.0 {
+                return Err(format!("{} quantity {} is below the lot size {}", spec.symbol, quantity, lot));
+            }
+            rounded
+        }
+        None => quantity,
+    };
+    let price = match (price, spec.tick_size) {
+        (Some(price), Some(tick)) => {
+            let rounded = round_price(price, tick);
+            if rounded <= 0.0 {
+                return Err(format!("{} price {} is below the tick size {}", spec.symbol, price, tick));
+            }
+            Some(rounded)
+        }
+        (price, _) => price,
+    };
+    Ok((quantity, price))
+}
+
+/// Reject an order below the spec's minimum quantity or notional
+pub fn check_minimums(spec: &SymbolSpec, quantity: f64, price: Option<f64>) -> Result<(), String> {
+    if let Some(min) = spec.min_quantity {
+        if quantity < min {
+            return Err(format!("{} order quantity {} is below the minimum {}", spec.symbol, quantity, min));
+        }
+    }
+    if let (Some(min), Some(price)) = (spec.min_notional, price) {
+        let notional = quantity * price;
+        if notional < min {
+            return Err(format!("{} order value {} is below the minimum notional {}", spec.symbol, notional, min));
+        }
+    }
+    Ok(())
+}
+
+/// Round a new order to the symbol's spec and enforce its minimums
+pub fn normalize_order(symbol: &str, quantity: f64, price: Option<f64>) -> Result<(f64, Option<f64>), String> {
+    let Some(spec) = paper_trading::get_symbol_spec(symbol).map_err(|e| e.to_string())? else {
+        return Ok((quantity, price));
+    };
+    let (quantity, price) = round_to_spec(&spec, quantity, price)?;
+    check_minimums(&spec, quantity, price)?;
+    Ok((quantity, price))
+}
+
+/// Round a fill or position's price to the symbol's tick size
+///
+/// The quantity is only cleared of float noise (see `snap_quantity`): a partial
+/// fill may be smaller than a lot. Callers must account with the returned values.
+pub fn normalize_fill(symbol: &str, quantity: f64, price: f64) -> Result<(f64, f64), String> {
+    let Some(spec) = paper_trading::get_symbol_spec(symbol).map_err(|e| e.to_string())? else {
+        return Ok((quantity, price));
+    };
+    let quantity = spec.lot_size.map_or(quantity, |lot| snap_quantity(quantity, lot));
+    let (_, rounded) = round_to_spec(&SymbolSpec { lot_size: None, ..spec }, quantity, Some(price))?;
+    Ok((quantity, rounded.unwrap_or(price)))
+}
+
+/// Fee of a normalized fill: recomputed from its stored values when a rate is given
+pub fn fill_fee(quantity: f64, price: f64, fee: f64, fee_rate: f64) -> f64 {
+    if fee_rate > 0.0 {
+        quantity * price * fee_rate
+    } else {
+        fee
+    }
+}
+
+/// Validate and store a spec; each value must be positive or absent
+pub fn update_symbol_spec(spec: SymbolSpec) -> Result<SymbolSpec, String> {
+    if spec.symbol.trim().is_empty() {
+        return Err("symbol must not be empty".to_string());
+    }
+    for (name, value) in [
+        ("tick_size", spec.tick_size),
+        ("lot_size", spec.lot_size),
+        ("min_quantity", spec.min_quantity),
+        ("min_notional", spec.min_notional),
+    ] {
+        if value.is_some_and(|v| !v.is_finite() || v <= 0.0) {
+            return Err(format!("{} must be greater than 0", name));
+        }
+    }
+    paper_trading::save_symbol_spec(&spec).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_paper_symbol_spec(symbol: String) -> Result<Option<SymbolSpec>, String> {
+    paper_trading::get_symbol_spec(&symbol).map_err(|e| e.to_string())
+}
+
+/// Replace a symbol's spec; omitted values are unconstrained
+#[tauri::command]
+pub async fn set_paper_symbol_spec(
+    symbol: String,
+    tick_size: Option<f64>,
+    lot_size: Option<f64>,
+    min_quantity: Option<f64>,
+    min_notional: Option<f64>,
+) -> Result<SymbolSpec, String> {
+    update_symbol_spec(SymbolSpec {
+        symbol,
+        tick_size,
+        lot_size,
+        min_quantity,
+        min_notional,
+        updated_at: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn btc() -> SymbolSpec {
+        SymbolSpec {
+            symbol: "BTC/USDT".to_string(),
+            tick_size: Some(0.01),
+            lot_size: Some(0.001),
+            min_quantity: Some(0.002),
+            min_notional: Some(10.0),
+            updated_at: None,
+        }
+    }
+
+    #[test]
+    fn test_rounding_removes_float_noise() {
+        assert_eq!(round_price(65_432.123_456, 0.01), 65_432.12);
+        assert_eq!(round_price(101.3, 0.5), 101.5);
+        assert_eq!(round_quantity(0.1 + 0.2, 0.1), 0.3);
+        assert_eq!(round_quantity(0.0019, 0.001), 0.001);
+        assert_eq!(round_quantity(7.0, 5.0), 5.0);
+    }
+
+    #[test]
+    fn test_round_to_spec() {
+        let (quantity, price) = round_to_spec(&btc(), 0.123_456, Some(65_000.004)).unwrap();
+        assert_eq!(quantity, 0.123);
+        assert_eq!(price, Some(65_000.0));
+        // Market orders have no price to round
+        assert_eq!(round_to_spec(&btc(), 0.5, None).unwrap(), (0.5, None));
+
+        let err = round_to_spec(&btc(), 0.0004, Some(65_000.0)).unwrap_err();
+        assert!(err.contains("lot size"), "{}", err);
+    }
+
+    #[test]
+    fn test_fills_keep_sub_lot_quantities_and_reprice_the_fee() {
+        assert_eq!(snap_quantity(0.1 + 0.2, 0.1), 0.3);
+        assert_eq!(snap_quantity(0.0004, 0.001), 0.0004);
+        assert_eq!(snap_quantity(0.0015, 0.001), 0.0015);
+
+        let (_, price) = round_to_spec(&SymbolSpec { lot_size: None, ..btc() }, 0.0004, Some(65_000.004)).unwrap();
+        assert_eq!(price, Some(65_000.0));
+        assert_eq!(fill_fee(0.5, 65_000.0, 99.0, 0.001), 32.5);
+        assert_eq!(fill_fee(0.5, 65_000.0, 1.5, 0.0), 1.5);
+    }
+
+    #[test]
+    fn test_minimums() {
+        assert!(check_minimums(&btc(), 0.002, Some(65_000.0)).is_ok());
+        let err = check_minimums(&btc(), 0.001, Some(65_000.0)).unwrap_err();
+        assert!(err.contains("minimum 0.002"), "{}", err);
+
+        let cheap = SymbolSpec { min_quantity: None, ..btc() };
+        assert!(check_minimums(&cheap, 0.001, Some(5_000.0)).is_err());
+        assert!(check_minimums(&cheap, 0.001, None).is_ok());
+    }
+}
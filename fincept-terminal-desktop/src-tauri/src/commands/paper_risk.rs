@@ -0,0 +1,267 @@
+// Paper Trading Risk Limits - hard limits checked before orders and fills are stored
+//
+// What: Per-portfolio limits on position value, account leverage, number of open
+//       positions and loss per day. `db_create_order` and `db_create_trade` reject
+//       anything that would breach one, naming the rule.
+// Why:  The simulated account should refuse positions the user decided up front
+//       not to take, the same way a broker's risk engine would.
+// How:  Only the part of an order that adds exposure is checked: closing or
+//       shrinking an opposite position is always allowed, even after the daily
+//       loss limit is hit. Value and leverage need a price; an order without one
+//       (market orders) is checked on the remaining rules and fully at fill, when
+//       the trade carries its price. Daily loss is the drop from the balance at the
+//       first balance change of the local day (see `paper_trading::daily_loss`).
+
+use crate::database::paper_trading::{self, PaperTradingPortfolio, PaperTradingPosition, RiskLimits};
+
+/// Exposure an order or fill would add
+#[derive(Debug, Clone)]
+pub struct RiskCheck<'a> {
+    pub symbol: &'a str,
+    /// "buy" or "sell"
+    pub side: &'a str,
+    pub quantity: f64,
+    pub price: Option<f64>,
+}
+
+/// Reject `check` if it would breach any of `limits`
+pub fn check_risk_limits(
+    limits: &RiskLimits,
+    portfolio: &PaperTradingPortfolio,
+    positions: &[PaperTradingPosition],
+    check: &RiskCheck,
+    daily_loss: f64,
+) -> Result<(), String> {
+    let (same_side, opposite_side) = if check.side.eq_ignore_ascii_case("buy") {
+        ("long", "short")
+    } else {
+        ("short", "long")
+    };
+    let open: Vec<&PaperTradingPosition> = positions.iter().filter(|p| p.status == "open").collect();
+    let find = |side: &str| open.iter().find(|p| p.symbol == check.symbol && p.side == side).copied();
+    let existing = find(same_side);
+    let opposite = find(opposite_side);
+
+    // Quantity that closes an opposite position carries no new risk
+    let reduced = opposite.map_or(0.0, |p| p.quantity.min(check.quantity));
+    let added = check.quantity - reduced;
+    if added <= 0.0 {
+        return Ok(());
+    }
+
+    if let Some(max) = limits.max_daily_loss {
+        if daily_loss >= max {
+            return Err(format!(
+                "Risk limit max_daily_loss reached: lost {:.2} {} today (limit {:.2}); only orders that reduce positions are allowed",
+                daily_loss, portfolio.currency, max
+            ));
+        }
+    }
+
+    if let Some(max) = limits.max_open_positions {
+        // A flip replaces the opposite position, so only a new symbol/side adds one
+        let after = open.len() + usize::from(existing.is_none() && opposite.is_none());
+        if after as i64 > max {
+            return Err(format!(
+                "Risk limit max_open_positions exceeded: {} open positions (limit {})",
+                after, max
+            ));
+        }
+    }
+
+    let Some(price) = check.price.filter(|p| *p > 0.0) else {
+        return Ok(());
+    };
+
+    if let Some(max) = limits.max_position_value {
+        let value = (existing.map_or(0.0, |p| p.quantity) + added) * price;
+        if value > max {
+            return Err(format!(
+                "Risk limit max_position_value exceeded: {} position would be worth {:.2} {} (limit {:.2})",
+                check.symbol, value, portfolio.currency, max
+            ));
+        }
+    }
+
+    if let Some(max) = limits.max_leverage {
+        let notional: f64 = open
+            .iter()
+            .map(|p| p.quantity * p.current_price.unwrap_or(p.entry_price))
+            .sum::<f64>()
+            - reduced * price
+            + added * price;
+        let balance = portfolio.current_balance;
+        let leverage = if balance > 0.0 { notional / balance } else { f64::INFINITY };
+        if leverage > max {
+            return Err(format!(
+                "Risk limit max_leverage exceeded: account leverage would be {:.2}x (limit {:.2}x)",
+                leverage, max
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// Check an order or fill for `portfolio_id` against its stored limits
+pub fn enforce_risk_limits(portfolio_id: &str, check: &RiskCheck) -> Result<(), String> {
+    let limits = paper_trading::get_risk_limits(portfolio_id).map_err(|e| e.to_string())?;
+    if limits.is_empty() {
+        return Ok(());
+    }
+    let portfolio = paper_trading::get_portfolio(portfolio_id).map_err(|e| e.to_string())?;
+    let positions = paper_trading::get_portfolio_positions(portfolio_id, Some("open")).map_err(|e| e.to_string())?;
+    let daily_loss = match limits.max_daily_loss {
+        Some(_) => paper_trading::daily_loss(portfolio_id).map_err(|e| e.to_string())?,
+        None => 0.0,
+    };
+
+    check_risk_limits(&limits, &portfolio, &positions, check, daily_loss)
+}
+
+/// Validate and store limits; each must be positive or absent
+pub fn update_risk_limits(limits: RiskLimits) -> Result<RiskLimits, String> {
+    let positive = |name: &str, value: Option<f64>| match value {
+        Some(v) if !v.is_finite() || v <= 0.0 => Err(format!("{} must be greater than 0", name)),
+        _ => Ok(()),
+    };
+    positive("max_position_value", limits.max_position_value)?;
+    positive("max_leverage", limits.max_leverage)?;
+    positive("max_daily_loss", limits.max_daily_loss)?;
+    if limits.max_open_positions.is_some_and(|n| n < 1) {
+        return Err("max_open_positions must be at least 1".to_string());
+    }
+
+    paper_trading::get_portfolio(&limits.portfolio_id).map_err(|_| format!("Portfolio '{}' not found", limits.portfolio_id))?;
+    paper_trading::save_risk_limits(&limits).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_paper_risk_limits(portfolio_id: String) -> Result<RiskLimits, String> {
+    paper_trading::get_risk_limits(&portfolio_id).map_err(|e| e.to_string())
+}
+
+/// Replace a portfolio's risk limits; omitted limits are turned off
+#[tauri::command]
+pub async fn set_paper_risk_limits(
+    portfolio_id: String,
+    max_position_value: Option<f64>,
+    max_leverage: Option<f64>,
+    max_open_positions: Option<i64>,
+    max_daily_loss: Option<f64>,
+) -> Result<RiskLimits, String> {
+    update_risk_limits(RiskLimits {
+        portfolio_id,
+        max_position_value,
+        max_leverage,
+        max_open_positions,
+        max_daily_loss,
+        updated_at: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn portfolio(balance: f64) -> PaperTradingPortfolio {
+        PaperTradingPortfolio {
+            id: "p1".to_string(),
+            name: "Test".to_string(),
+            provider: "binance".to_string(),
+            initial_balance: balance,
+            current_balance: balance,
+            currency: "USD".to_string(),
+            margin_mode: "cross".to_string(),
+            leverage: 1.0,
+            created_at: String::new(),
+            updated_at: String::new(),
+            owner: None,
+        }
+    }
+
+    fn position(symbol: &str, side: &str, quantity: f64, entry_price: f64) -> PaperTradingPosition {
+        PaperTradingPosition {
+            id: format!("{}-{}", symbol, side),
+            portfolio_id: "p1".to_string(),
+            symbol: symbol.to_string(),
+            side: side.to_string(),
+            entry_price,
+            quantity,
+            position_value: None,
+            current_price: None,
+            unrealized_pnl: None,
+            realized_pnl: 0.0,
+            leverage: 1.0,
+            margin_mode: "cross".to_string(),
+            liquidation_price: None,
+            opened_at: String::new(),
+            closed_at: None,
+            status: "open".to_string(),
+        }
+    }
+
+    fn buy(symbol: &str, quantity: f64, price: Option<f64>) -> RiskCheck<'_> {
+        RiskCheck { symbol, side: "buy", quantity, price }
+    }
+
+    fn limits() -> RiskLimits {
+        RiskLimits {
+            portfolio_id: "p1".to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_position_value_and_leverage() {
+        let limits = RiskLimits {
+            max_position_value: Some(5_000.0),
+            max_leverage: Some(2.0),
+            ..limits()
+        };
+        let positions = [position("BTC", "long", 4.0, 1_000.0)];
+
+        // 4 + 1 BTC at 1000 = 5000: at the limit
+        assert!(check_risk_limits(&limits, &portfolio(10_000.0), &positions, &buy("BTC", 1.0, Some(1_000.0)), 0.0).is_ok());
+        let err = check_risk_limits(&limits, &portfolio(10_000.0), &positions, &buy("BTC", 2.0, Some(1_000.0)), 0.0)
+            .unwrap_err();
+        assert!(err.contains("max_position_value"), "{}", err);
+
+        // 4000 of BTC + 4500 of ETH on a 4000 balance = 2.125x
+        let err = check_risk_limits(&limits, &portfolio(4_000.0), &positions, &buy("ETH", 1.0, Some(4_500.0)), 0.0)
+            .unwrap_err();
+        assert!(err.contains("max_leverage"), "{}", err);
+
+        // Without a price only the other rules apply
+        assert!(check_risk_limits(&limits, &portfolio(10_000.0), &positions, &buy("BTC", 1.0, None), 0.0).is_ok());
+    }
+
+    #[test]
+    fn test_open_positions_counts_only_new_symbols() {
+        let limits = RiskLimits {
+            max_open_positions: Some(1),
+            ..limits()
+        };
+        let positions = [position("BTC", "long", 1.0, 100.0)];
+
+        assert!(check_risk_limits(&limits, &portfolio(1e6), &positions, &buy("BTC", 1.0, Some(100.0)), 0.0).is_ok());
+        let err = check_risk_limits(&limits, &portfolio(1e6), &positions, &buy("ETH", 1.0, Some(100.0)), 0.0).unwrap_err();
+        assert_eq!(err, "Risk limit max_open_positions exceeded: 2 open positions (limit 1)");
+    }
+
+    #[test]
+    fn test_daily_loss_only_blocks_added_exposure() {
+        let limits = RiskLimits {
+            max_daily_loss: Some(500.0),
+            ..limits()
+        };
+        let positions = [position("BTC", "short", 2.0, 100.0)];
+
+        let err = check_risk_limits(&limits, &portfolio(9_400.0), &[], &buy("BTC", 1.0, Some(100.0)), 600.0).unwrap_err();
+        assert!(err.contains("max_daily_loss"), "{}", err);
+        // Covering the short is still allowed; flipping past it is not
+        assert!(check_risk_limits(&limits, &portfolio(9_400.0), &positions, &buy("BTC", 2.0, Some(100.0)), 600.0).is_ok());
+        assert!(check_risk_limits(&limits, &portfolio(9_400.0), &positions, &buy("BTC", 3.0, Some(100.0)), 600.0).is_err());
+        assert!(check_risk_limits(&limits, &portfolio(9_400.0), &[], &buy("BTC", 1.0, Some(100.0)), 100.0).is_ok());
+    }
+}
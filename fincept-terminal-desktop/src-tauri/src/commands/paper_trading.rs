@@ -0,0 +1,518 @@
+// Paper Trading Preview - dry-run an order against a paper portfolio
+//
+// What: `preview_order` estimates the fill price, fee, resulting position,
+//       margin usage and liquidation price for an order without writing anything.
+// Why:  Users want a "what-if" before committing an order that mutates balances
+//       and positions.
+// How:  Mirrors the frontend engine (src/paper-trading): market/stop orders fill at
+//       the reference price plus base slippage and pay the taker fee, resting limit
+//       orders pay the maker fee. Orders reduce an opposite position first (flipping
+//       if they exceed it) or VWAP-add to a same-side one. Margin is
+//       `quantity * entry / leverage`. The liquidation formula matches
+//       `PaperTradingBalance.calculateLiquidationPrice`. Keep both sides in sync.
+
+use crate::database::paper_trading::{self, PaperTradingPortfolio, PaperTradingPosition};
+use crate::utils::money::Money;
+use serde::{Deserialize, Serialize};
+
+/// Fallback base slippage for market orders (0.1%), as in the frontend default config
+pub const DEFAULT_MARKET_SLIPPAGE: f64 = 0.001;
+
+/// Maker/taker fee rates as fractions (0.0005 = 0.05%)
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct FeeSchedule {
+    pub maker: f64,
+    pub taker: f64,
+}
+
+impl Default for FeeSchedule {
+    fn default() -> Self {
+        Self {
+            maker: 0.0002,
+            taker: 0.0005,
+        }
+    }
+}
+
+/// Liquidation price for a position, or `None` when unleveraged
+///
+/// Loss allowed before liquidation = initial margin - entry fees
+/// - maintenance margin (half the initial margin) - exit fees.
+pub fn liquidation_price(
+    side: &str,
+    entry_price: f64,
+    leverage: f64,
+    quantity: f64,
+    entry_fee_rate: f64,
+    exit_fee_rate: f64,
+) -> Option<f64> {
+    if leverage <= 1.0 || quantity <= 0.0 || entry_price <= 0.0 {
+        return None;
+    }
+
+    let position_value = entry_price * quantity;
+    let initial_margin = position_value / leverage;
+    let maintenance_margin = position_value * (0.5 / leverage);
+    let max_loss = initial_margin
+        - position_value * entry_fee_rate
+        - maintenance_margin
+        - position_value * exit_fee_rate;
+
+    let price = if side == "long" {
+        entry_price - max_loss / quantity
+    } else {
+        entry_price + max_loss / quantity
+    };
+    Some(price.max(0.0))
+}
+
+/// Order parameters being previewed
+#[derive(Debug, Clone)]
+pub struct OrderRequest {
+    pub side: String,
+    pub order_type: String,
+    pub quantity: f64,
+    /// Limit price for limit/stop_limit, trigger price for stop_market
+    pub price: Option<f64>,
+    pub leverage: Option<f64>,
+    pub reduce_only: bool,
+}
+
+/// Position as it would look after the fill
+#[derive(Debug, Clone, Serialize)]
+pub struct PositionPreview {
+    pub side: String,
+    pub quantity: f64,
+    pub entry_price: f64,
+    pub leverage: f64,
+    pub margin: f64,
+    pub liquidation_price: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct OrderPreview {
+    pub symbol: String,
+    pub side: String,
+    pub order_type: String,
+    pub quantity: f64,
+    /// Price the estimate is based on (market price, limit or stop price)
+    pub reference_price: f64,
+    pub estimated_fill_price: f64,
+    pub order_value: f64,
+    pub is_maker: bool,
+    pub fee_rate: f64,
+    pub fee: f64,
+    /// Quantity of an opposite position this order would close
+    pub closed_quantity: f64,
+    pub realized_pnl: f64,
+    pub position_before: Option<PositionPreview>,
+    /// `None` when the order leaves the symbol flat
+    pub position_after: Option<PositionPreview>,
+    pub balance_before: f64,
+    pub balance_after: f64,
+    pub margin_used_before: f64,
+    pub margin_used_after: f64,
+    pub free_margin_after: f64,
+    pub sufficient_funds: bool,
+    pub warnings: Vec<String>,
+}
+
+fn position_margin(quantity: f64, entry_price: f64, leverage: f64) -> f64 {
+    quantity * entry_price / leverage.max(1.0)
+}
+
+fn preview_position(side: &str, quantity: f64, entry_price: f64, leverage: f64, fees: &FeeSchedule) -> PositionPreview {
+    PositionPreview {
+        side: side.to_string(),
+        quantity,
+        entry_price,
+        leverage,
+        margin: position_margin(quantity, entry_price, leverage),
+        liquidation_price: liquidation_price(side, entry_price, leverage, quantity, fees.taker, fees.taker),
+    }
+}
+
+fn existing_preview(position: &PaperTradingPosition, fees: &FeeSchedule) -> PositionPreview {
+    preview_position(&position.side, position.quantity, position.entry_price, position.leverage, fees)
+}
+
+/// Compute the effect of `order` on `portfolio` given its open `positions`
+///
+/// `market_price` is the latest known price; market orders need it unless a
+/// `price` is supplied as the reference.
+pub fn compute_order_preview(
+    portfolio: &PaperTradingPortfolio,
+    positions: &[PaperTradingPosition],
+    symbol: &str,
+    order: &OrderRequest,
+    market_price: Option<f64>,
+    fees: &FeeSchedule,
+    slippage: f64,
+) -> Result<OrderPreview, String> {
+    let side = order.side.to_lowercase();
+    if side != "buy" && side != "sell" {
+        return Err(format!("Invalid side '{}' (expected buy or sell)", order.side));
+    }
+    if !order.quantity.is_finite() || order.quantity <= 0.0 {
+        return Err("Quantity must be greater than 0".to_string());
+    }
+    let order_type = order.order_type.to_lowercase();
+    let positive = |p: Option<f64>| p.filter(|p| *p > 0.0);
+
+    let (reference_price, fill_price, is_maker) = match order_type.as_str() {
+        "market" => {
+            let reference = positive(market_price)
+                .or(positive(order.price))
+                .ok_or("No market price available for symbol; pass 'price' as the reference")?;
+            let slip = if side == "buy" { 1.0 + slippage } else { 1.0 - slippage };
+            (reference, reference * slip, false)
+        }
+        "stop_market" => {
+            let stop = positive(order.price).ok_or("Stop orders need a positive 'price' (trigger)")?;
+            let slip = if side == "buy" { 1.0 + slippage } else { 1.0 - slippage };
+            (stop, stop * slip, false)
+        }
+        "limit" | "stop_limit" => {
+            let limit = positive(order.price).ok_or("Limit orders need a positive 'price'")?;
+            // A limit that crosses the market fills immediately as taker
+            let marketable = match positive(market_price) {
+                Some(market) if order_type == "limit" => {
+                    (side == "buy" && limit >= market) || (side == "sell" && limit <= market)
+                }
+                _ => false,
+            };
+            let fill = match positive(market_price) {
+                Some(market) if marketable => market,
+                _ => limit,
+            };
+            (limit, fill, !marketable)
+        }
+        other => {
+            return Err(format!(
+                "Invalid order type '{}' (expected market, limit, stop_market or stop_limit)",
+                other
+            ))
+        }
+    };
+
+    let fee_rate = if is_maker { fees.maker } else { fees.taker };
+    let order_value = Money::from_f64_lossy(fill_price) * Money::from_f64_lossy(order.quantity);
+    let fee = order_value * Money::from_f64_lossy(fee_rate);
+    let leverage = order.leverage.unwrap_or(portfolio.leverage).max(1.0);
+    let mut warnings = Vec::new();
+
+    let open = |position_side: &str| {
+        positions
+            .iter()
+            .find(|p| p.symbol == symbol && p.side == position_side && p.status == "open")
+    };
+    let (same_side, opposite_side) = if side == "buy" { ("long", "short") } else { ("short", "long") };
+    let opposite = open(opposite_side);
+    let existing = open(same_side);
+
+    if order.reduce_only {
+        match opposite {
+            None => {
+                return Err(format!(
+                    "Cannot reduce position - no {} position exists for {}",
+                    opposite_side, symbol
+                ))
+            }
+            Some(p) if order.quantity > p.quantity => {
+                return Err(format!(
+                    "Reduce-only order quantity {} exceeds position size {}",
+                    order.quantity, p.quantity
+                ))
+            }
+            _ => {}
+        }
+    }
+
+    let mut closed_quantity = 0.0;
+    let mut realized_pnl = 0.0;
+    let (position_before, position_after) = if let Some(p) = opposite {
+        closed_quantity = order.quantity.min(p.quantity);
+        realized_pnl = paper_trading::realized_pnl(&p.side, p.entry_price, fill_price, closed_quantity);
+
+        let after = if p.quantity > order.quantity {
+            Some(preview_position(&p.side, p.quantity - order.quantity, p.entry_price, p.leverage, fees))
+        } else if order.quantity > p.quantity {
+            warnings.push(format!(
+                "Order closes the {} position and opens a {} position of {}",
+                p.side,
+                same_side,
+                order.quantity - p.quantity
+            ));
+            Some(preview_position(same_side, order.quantity - p.quantity, fill_price, leverage, fees))
+        } else {
+            None
+        };
+        (Some(existing_preview(p, fees)), after)
+    } else if let Some(p) = existing {
+        let quantity = p.quantity + order.quantity;
+        let entry = (p.quantity * p.entry_price + order.quantity * fill_price) / quantity;
+        (
+            Some(existing_preview(p, fees)),
+            Some(preview_position(same_side, quantity, entry, p.leverage, fees)),
+        )
+    } else {
+        (None, Some(preview_position(same_side, order.quantity, fill_price, leverage, fees)))
+    };
+
+    let margin_used_before: f64 = positions
+        .iter()
+        .filter(|p| p.status == "open")
+        .map(|p| position_margin(p.quantity, p.entry_price, p.leverage))
+        .sum();
+    let margin_used_after = margin_used_before
+        - position_before.as_ref().map_or(0.0, |p| p.margin)
+        + position_after.as_ref().map_or(0.0, |p| p.margin);
+
+    let balance_before = portfolio.current_balance;
+    let balance_after =
+        (Money::from_f64_lossy(balance_before) + Money::from_f64_lossy(realized_pnl) - fee).to_f64();
+    let free_margin_after = balance_after - margin_used_after;
+    let sufficient_funds = free_margin_after >= 0.0;
+    if !sufficient_funds {
+        warnings.push(format!(
+            "Insufficient funds: {:.2} {} short of the required margin",
+            -free_margin_after, portfolio.currency
+        ));
+    }
+
+    if let (Some(after), Some(market)) = (&position_after, positive(market_price)) {
+        if let Some(liq) = after.liquidation_price {
+            let breached = if after.side == "long" { market <= liq } else { market >= liq };
+            if breached {
+                warnings.push(format!("Current price {} is beyond the liquidation price {:.4}", market, liq));
+            }
+        }
+    }
+
+    Ok(OrderPreview {
+        symbol: symbol.to_string(),
+        side,
+        order_type,
+        quantity: order.quantity,
+        reference_price,
+        estimated_fill_price: fill_price,
+        order_value: order_value.to_f64(),
+        is_maker,
+        fee_rate,
+        fee: fee.to_f64(),
+        closed_quantity,
+        realized_pnl,
+        position_before,
+        position_after,
+        balance_before,
+        balance_after,
+        margin_used_before,
+        margin_used_after,
+        free_margin_after,
+        sufficient_funds,
+        warnings,
+    })
+}
+
+/// Dry-run an order: same fill, fee, position and margin math as placing it, but nothing is written
+///
+/// Without `market_price` the latest tick seen for the portfolio's provider is used.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn preview_order(
+    state: tauri::State<'_, crate::WebSocketState>,
+    portfolio_id: String,
+    symbol: String,
+    side: String,
+    order_type: String,
+    quantity: f64,
+    price: Option<f64>,
+    leverage: Option<f64>,
+    reduce_only: Option<bool>,
+    market_price: Option<f64>,
+    fees: Option<FeeSchedule>,
+    slippage: Option<f64>,
+) -> Result<OrderPreview, String> {
+    let portfolio = paper_trading::get_portfolio(&portfolio_id).map_err(|e| e.to_string())?;
+    let positions = paper_trading::get_portfolio_positions(&portfolio_id, Some("open"))
+        .map_err(|e| e.to_string())?;
+
+    let market_price = match market_price {
+        Some(p) => Some(p),
+        None => state
+            .services
+            .read()
+            .await
+            .paper_trading
+            .get_price(&portfolio.provider, &symbol),
+    };
+
+    let order = OrderRequest {
+        side,
+        order_type,
+        quantity,
+        price,
+        leverage,
+        reduce_only: reduce_only.unwrap_or(false),
+    };
+    compute_order_preview(
+        &portfolio,
+        &positions,
+        &symbol,
+        &order,
+        market_price,
+        &fees.unwrap_or_default(),
+        slippage.unwrap_or(DEFAULT_MARKET_SLIPPAGE),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn portfolio(balance: f64, leverage: f64) -> PaperTradingPortfolio {
+        PaperTradingPortfolio {
+            id: "p1".to_string(),
+            name: "Test".to_string(),
+            provider: "binance".to_string(),
+            initial_balance: balance,
+            current_balance: balance,
+            currency: "USD".to_string(),
+            margin_mode: "cross".to_string(),
+            leverage,
+            created_at: String::new(),
+            updated_at: String::new(),
+            owner: None,
+        }
+    }
+
+    fn position(side: &str, quantity: f64, entry_price: f64, leverage: f64) -> PaperTradingPosition {
+        PaperTradingPosition {
+            id: "pos1".to_string(),
+            portfolio_id: "p1".to_string(),
+            symbol: "BTCUSDT".to_string(),
+            side: side.to_string(),
+            entry_price,
+            quantity,
+            position_value: None,
+            current_price: None,
+            unrealized_pnl: None,
+            realized_pnl: 0.0,
+            leverage,
+            margin_mode: "cross".to_string(),
+            liquidation_price: None,
+            opened_at: String::new(),
+            closed_at: None,
+            status: "open".to_string(),
+        }
+    }
+
+    fn order(side: &str, order_type: &str, quantity: f64, price: Option<f64>) -> OrderRequest {
+        OrderRequest {
+            side: side.to_string(),
+            order_type: order_type.to_string(),
+            quantity,
+            price,
+            leverage: None,
+            reduce_only: false,
+        }
+    }
+
+    fn assert_close(a: f64, b: f64) {
+        assert!((a - b).abs() < 1e-9, "{} != {}", a, b);
+    }
+
+    #[test]
+    fn test_liquidation_price_matches_frontend_formula() {
+        // 10x long at 100: max loss = 10 - 0.05 - 5 - 0.05 = 4.9 per unit
+        assert_close(liquidation_price("long", 100.0, 10.0, 1.0, 0.0005, 0.0005).unwrap(), 95.1);
+        assert_close(liquidation_price("short", 100.0, 10.0, 1.0, 0.0005, 0.0005).unwrap(), 104.9);
+        assert_eq!(liquidation_price("long", 100.0, 1.0, 1.0, 0.0005, 0.0005), None);
+    }
+
+    #[test]
+    fn test_market_buy_opens_position_with_slippage_and_taker_fee() {
+        let preview = compute_order_preview(
+            &portfolio(10_000.0, 5.0),
+            &[],
+            "BTCUSDT",
+            &order("buy", "market", 1.0, None),
+            Some(1000.0),
+            &FeeSchedule::default(),
+            0.001,
+        )
+        .unwrap();
+
+        assert_close(preview.estimated_fill_price, 1001.0);
+        assert!(!preview.is_maker);
+        assert_close(preview.fee, 1001.0 * 0.0005);
+        let after = preview.position_after.unwrap();
+        assert_eq!(after.side, "long");
+        assert_close(after.margin, 1001.0 / 5.0);
+        assert!(after.liquidation_price.unwrap() < 1001.0);
+        assert_close(preview.balance_after, 10_000.0 - preview.fee);
+        assert!(preview.sufficient_funds);
+    }
+
+    #[test]
+    fn test_sell_larger_than_long_flips_position() {
+        let preview = compute_order_preview(
+            &portfolio(10_000.0, 1.0),
+            &[position("long", 2.0, 100.0, 1.0)],
+            "BTCUSDT",
+            &order("sell", "limit", 3.0, Some(100.0)),
+            Some(105.0),
+            &FeeSchedule::default(),
+            0.001,
+        )
+        .unwrap();
+
+        // Sell limit below the market is marketable and fills at the market price
+        assert!(!preview.is_maker);
+        assert_close(preview.estimated_fill_price, 105.0);
+        assert_close(preview.closed_quantity, 2.0);
+        assert_close(preview.realized_pnl, 10.0);
+        let after = preview.position_after.unwrap();
+        assert_eq!(after.side, "short");
+        assert_close(after.quantity, 1.0);
+        assert_close(preview.margin_used_before, 200.0);
+        assert_close(preview.margin_used_after, 105.0);
+    }
+
+    #[test]
+    fn test_reduce_only_without_position_is_rejected() {
+        let mut request = order("sell", "market", 1.0, None);
+        request.reduce_only = true;
+        let err = compute_order_preview(
+            &portfolio(1000.0, 1.0),
+            &[],
+            "BTCUSDT",
+            &request,
+            Some(100.0),
+            &FeeSchedule::default(),
+            0.001,
+        )
+        .unwrap_err();
+        assert!(err.contains("no long position"));
+    }
+
+    #[test]
+    fn test_stop_market_fills_at_the_trigger_with_slippage() {
+        let preview = |order_type: &str| {
+            compute_order_preview(
+                &portfolio(10_000.0, 1.0),
+                &[],
+                "BTCUSDT",
+                &order("buy", order_type, 1.0, Some(110.0)),
+                Some(100.0),
+                &FeeSchedule::default(),
+                0.001,
+            )
+        };
+        let stop = preview("stop_market").unwrap();
+        assert_close(stop.estimated_fill_price, 110.0 * 1.001);
+        assert!(!stop.is_maker);
+        // "stop" is not an order type the database accepts
+        assert!(preview("stop").unwrap_err().contains("Invalid order type"));
+    }
+}
@@ -21,7 +21,7 @@ fn execute_python_script(script_name: &str, function: &str, args: &str) -> Resul
 
     if !output.status.success() {
         let error = String::from_utf8_lossy(&output.stderr);
-        return Err(format!("Python script error: {}", error));
+        return Err(crate::utils::python_errors::python_failure(&error));
     }
 
     let result = String::from_utf8_lossy(&output.stdout).to_string();
@@ -91,15 +91,21 @@ pub async fn pmdarima_forecast_arima(
 
 // ==================== PREPROCESSING COMMANDS ====================
 
+/// Same estimate as pmdarima_boxcox_auto, serialized in the `preprocessing.py` shape
 #[tauri::command]
 pub async fn pmdarima_boxcox_transform(
     data: Vec<f64>
 ) -> Result<String, String> {
-    let args = serde_json::json!({
-        "y": data
-    }).to_string();
+    let result = boxcox_auto(&data)?;
+    serde_json::to_string(&result).map_err(|e| e.to_string())
+}
 
-    execute_python_script("preprocessing.py", "apply_boxcox_transform", &args)
+/// Box-Cox transform with an estimated lambda, returned so the inverse needs no re-estimation
+#[tauri::command]
+pub async fn pmdarima_boxcox_auto(
+    data: Vec<f64>
+) -> Result<BoxCoxResult, String> {
+    boxcox_auto(&data)
 }
 
 #[tauri::command]
@@ -107,12 +113,115 @@ pub async fn pmdarima_inverse_boxcox(
     data: Vec<f64>,
     lambda: f64
 ) -> Result<String, String> {
-    let args = serde_json::json!({
-        "transformed": data,
-        "lambda": lambda
-    }).to_string();
+    let original = inverse_boxcox(&data, lambda)?;
+    Ok(serde_json::json!({
+        "original": original
+    }).to_string())
+}
+
+// ==================== BOX-COX (native) ====================
+//
+// Estimating lambda is a one-dimensional search, so both transforms and the
+// inverse run here rather than through `preprocessing.py`. Lambda maximizes the
+// Box-Cox log-likelihood, as `scipy.stats.boxcox` does, within LAMBDA_BOUNDS,
+// so pmdarima_boxcox_transform and pmdarima_boxcox_auto always agree.
+
+const LAMBDA_BOUNDS: (f64, f64) = (-5.0, 5.0);
+const LAMBDA_TOLERANCE: f64 = 1e-9;
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct BoxCoxResult {
+    pub transformed: Vec<f64>,
+    pub lambda: f64,
+}
+
+/// Box-Cox is only defined for strictly positive values, and lambda needs some spread
+fn check_boxcox_input(data: &[f64]) -> Result<(), String> {
+    if let Some(i) = data.iter().position(|v| !(v.is_finite() && *v > 0.0)) {
+        return Err(format!(
+            "Box-Cox requires strictly positive values; value at index {} is {}",
+            i, data[i]
+        ));
+    }
+    if data.len() < 2 || data.iter().all(|v| *v == data[0]) {
+        return Err("Box-Cox requires at least two distinct values".to_string());
+    }
+    Ok(())
+}
+
+fn boxcox_value(x: f64, lambda: f64) -> f64 {
+    if lambda == 0.0 {
+        x.ln()
+    } else {
+        (lambda * x.ln()).exp_m1() / lambda
+    }
+}
 
-    execute_python_script("preprocessing.py", "inverse_boxcox_transform", &args)
+/// Profile log-likelihood of `lambda`; `log_sum` is the sum of `ln(x)`
+fn boxcox_log_likelihood(data: &[f64], log_sum: f64, lambda: f64) -> f64 {
+    let n = data.len() as f64;
+    let transformed: Vec<f64> = data.iter().map(|x| boxcox_value(*x, lambda)).collect();
+    let mean = transformed.iter().sum::<f64>() / n;
+    let variance = transformed.iter().map(|t| (t - mean).powi(2)).sum::<f64>() / n;
+    let llf = (lambda - 1.0) * log_sum - n / 2.0 * variance.ln();
+    // Overflow at extreme lambdas counts as the worst fit
+    if llf.is_finite() { llf } else { f64::NEG_INFINITY }
+}
+
+/// Maximum-likelihood lambda by golden-section search
+fn estimate_lambda(data: &[f64]) -> f64 {
+    let log_sum: f64 = data.iter().map(|x| x.ln()).sum();
+    let llf = |lambda| boxcox_log_likelihood(data, log_sum, lambda);
+    let inv_phi = (5f64.sqrt() - 1.0) / 2.0;
+
+    let (mut lo, mut hi) = LAMBDA_BOUNDS;
+    let mut left = hi - inv_phi * (hi - lo);
+    let mut right = lo + inv_phi * (hi - lo);
+    let (mut f_left, mut f_right) = (llf(left), llf(right));
+    while hi - lo > LAMBDA_TOLERANCE {
+        if f_left >= f_right {
+            hi = right;
+            right = left;
+            f_right = f_left;
+            left = hi - inv_phi * (hi - lo);
+            f_left = llf(left);
+        } else {
+            lo = left;
+            left = right;
+            f_left = f_right;
+            right = lo + inv_phi * (hi - lo);
+            f_right = llf(right);
+        }
+    }
+    (lo + hi) / 2.0
+}
+
+pub fn boxcox_auto(data: &[f64]) -> Result<BoxCoxResult, String> {
+    check_boxcox_input(data)?;
+    let lambda = estimate_lambda(data);
+    Ok(BoxCoxResult {
+        transformed: data.iter().map(|x| boxcox_value(*x, lambda)).collect(),
+        lambda,
+    })
+}
+
+/// Undo a Box-Cox transform; `None` where `lambda * y + 1 <= 0` has no preimage
+pub fn inverse_boxcox(data: &[f64], lambda: f64) -> Result<Vec<Option<f64>>, String> {
+    if !lambda.is_finite() {
+        return Err("lambda must be a finite number".to_string());
+    }
+    Ok(data
+        .iter()
+        .map(|y| {
+            let x = if lambda == 0.0 {
+                Some(y.exp())
+            } else {
+                let base = lambda * y;
+                (base > -1.0).then(|| (base.ln_1p() / lambda).exp())
+            };
+            x.filter(|x| x.is_finite())
+        })
+        .collect())
 }
 
 // ==================== DIAGNOSTICS COMMANDS ====================
@@ -176,3 +285,47 @@ pub async fn pmdarima_cross_validate(
 
     execute_python_script("model_selection.py", "cross_validate_arima", &args)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_boxcox_auto_round_trips_and_rejects_non_positive_input() {
+        let data = [10.0, 12.0, 15.0, 19.0, 26.0, 35.0, 48.0, 66.0, 91.0, 125.0];
+        let result = boxcox_auto(&data).unwrap();
+
+        // The estimate is the likelihood maximum
+        let log_sum: f64 = data.iter().map(|x| x.ln()).sum();
+        let best = boxcox_log_likelihood(&data, log_sum, result.lambda);
+        for step in [-0.01, 0.01] {
+            assert!(best >= boxcox_log_likelihood(&data, log_sum, result.lambda + step));
+        }
+
+        for (y, x) in result.transformed.iter().zip(data) {
+            let restored = (result.lambda * y + 1.0).powf(1.0 / result.lambda);
+            assert!((restored - x).abs() < 1e-9 * x, "{} -> {}", x, restored);
+        }
+
+        let err = boxcox_auto(&[3.0, 0.0, 5.0]).unwrap_err();
+        assert!(err.contains("strictly positive") && err.contains("index 1"), "{}", err);
+        assert!(boxcox_auto(&[2.0, -1.0]).is_err());
+        assert!(boxcox_auto(&[4.0, 4.0, 4.0]).unwrap_err().contains("distinct"));
+    }
+
+    #[test]
+    fn test_inverse_boxcox_undoes_the_transform() {
+        let data = [0.5, 1.0, 3.0, 40.0];
+        for lambda in [-1.5, 0.0, 0.3, 2.0] {
+            let transformed: Vec<f64> = data.iter().map(|x| boxcox_value(*x, lambda)).collect();
+            let original = inverse_boxcox(&transformed, lambda).unwrap();
+            for (restored, x) in original.iter().zip(data) {
+                assert!((restored.unwrap() - x).abs() < 1e-9 * x, "lambda {}: {:?}", lambda, original);
+            }
+        }
+        assert_eq!(inverse_boxcox(&[0.0], 0.0).unwrap(), vec![Some(1.0)]);
+        // No x > 0 maps below -1/lambda
+        assert_eq!(inverse_boxcox(&[-2.0], 1.0).unwrap(), vec![None]);
+        assert!(inverse_boxcox(&[1.0], f64::NAN).is_err());
+    }
+}
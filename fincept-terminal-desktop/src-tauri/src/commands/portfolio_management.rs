@@ -1,8 +1,14 @@
 // Portfolio Management Commands - CRUD operations for portfolio tracking
+use crate::data_sources::yfinance::{QuoteData, YFinanceProvider};
 use crate::database::operations;
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::collections::{BTreeSet, HashMap};
 use uuid::Uuid;
 
+/// Default drift (absolute weight difference) tolerated before a trade is suggested
+const DEFAULT_REBALANCE_TOLERANCE: f64 = 0.01;
+
 #[tauri::command]
 pub async fn portfolio_create(
     name: String,
@@ -116,3 +122,524 @@ pub async fn portfolio_get_transactions(
 ) -> Result<Vec<Value>, String> {
     operations::get_portfolio_transactions(&portfolio_id, limit).map_err(|e| e.to_string())
 }
+
+/// Suggested trade for one symbol
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RebalanceTrade {
+    pub symbol: String,
+    /// "BUY", "SELL" or "HOLD"
+    pub action: String,
+    pub price: f64,
+    pub current_quantity: f64,
+    pub target_quantity: f64,
+    /// Signed quantity to trade (positive = buy)
+    pub delta_quantity: f64,
+    /// Signed trade value (positive = cash out)
+    pub notional: f64,
+    pub current_weight: f64,
+    pub target_weight: f64,
+}
+
+/// Rebalancing plan for a portfolio
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RebalancePlan {
+    pub total_value: f64,
+    pub cash_before: f64,
+    pub cash_after: f64,
+    /// Fraction of the desired buys that fit in available cash (1.0 = all of them)
+    pub buy_scale: f64,
+    pub trades: Vec<RebalanceTrade>,
+}
+
+/// Compute trades that move `holdings` towards `target_weights`
+///
+/// Weights are fractions of total value (holdings + cash) and may sum to less than
+/// 1; the remainder stays in cash. Held symbols without a target are sold down to
+/// zero. A symbol is left alone when its weight is within `tolerance` of target or
+/// the trade would be smaller than `min_trade_value`. Sells are assumed to settle
+/// first; if buys still exceed available cash they are scaled down pro rata.
+pub fn compute_rebalance(
+    holdings: &HashMap<String, f64>,
+    target_weights: &HashMap<String, f64>,
+    prices: &HashMap<String, f64>,
+    cash: f64,
+    tolerance: f64,
+    min_trade_value: f64,
+) -> Result<RebalancePlan, String> {
+    if cash < 0.0 {
+        return Err("Available cash cannot be negative".to_string());
+    }
+    if tolerance < 0.0 || min_trade_value < 0.0 {
+        return Err("Tolerance and minimum trade size cannot be negative".to_string());
+    }
+    for (symbol, weight) in target_weights {
+        if !weight.is_finite() || *weight < 0.0 {
+            return Err(format!("Invalid target weight for {}: {}", symbol, weight));
+        }
+    }
+    let weight_sum: f64 = target_weights.values().sum();
+    if weight_sum > 1.0 + 1e-9 {
+        return Err(format!("Target weights sum to {:.4}, which exceeds 1", weight_sum));
+    }
+
+    let symbols: BTreeSet<&String> = holdings.keys().chain(target_weights.keys()).collect();
+    let mut priced = Vec::with_capacity(symbols.len());
+    for symbol in symbols {
+        let price = match prices.get(symbol) {
+            Some(p) if p.is_finite() && *p > 0.0 => *p,
+            _ => return Err(format!("Missing or invalid price for {}", symbol)),
+        };
+        priced.push((symbol.clone(), price));
+    }
+
+    let holdings_value: f64 = priced
+        .iter()
+        .map(|(symbol, price)| holdings.get(symbol).copied().unwrap_or(0.0) * price)
+        .sum();
+    let total_value = holdings_value + cash;
+    if total_value <= 0.0 {
+        return Err("Portfolio has no value to rebalance".to_string());
+    }
+
+    let mut trades: Vec<RebalanceTrade> = priced
+        .into_iter()
+        .map(|(symbol, price)| {
+            let current_quantity = holdings.get(&symbol).copied().unwrap_or(0.0);
+            let current_weight = current_quantity * price / total_value;
+            let target_weight = target_weights.get(&symbol).copied().unwrap_or(0.0);
+
+            let mut notional = (target_weight - current_weight) * total_value;
+            if (target_weight - current_weight).abs() <= tolerance || notional.abs() < min_trade_value {
+                notional = 0.0;
+            }
+            // Never sell more than is held
+            notional = notional.max(-current_quantity * price);
+
+            RebalanceTrade {
+                symbol,
+                action: String::new(),
+                price,
+                current_quantity,
+                target_quantity: current_quantity,
+                delta_quantity: 0.0,
+                notional,
+                current_weight,
+                target_weight,
+            }
+        })
+        .collect();
+
+    let sell_proceeds: f64 = trades.iter().filter(|t| t.notional < 0.0).map(|t| -t.notional).sum();
+    let buy_cost: f64 = trades.iter().filter(|t| t.notional > 0.0).map(|t| t.notional).sum();
+    let spendable = cash + sell_proceeds;
+    let buy_scale = if buy_cost > spendable && buy_cost > 0.0 {
+        spendable / buy_cost
+    } else {
+        1.0
+    };
+
+    for trade in &mut trades {
+        if trade.notional > 0.0 {
+            trade.notional *= buy_scale;
+            if trade.notional < min_trade_value {
+                trade.notional = 0.0;
+            }
+        }
+        trade.delta_quantity = trade.notional / trade.price;
+        trade.target_quantity = trade.current_quantity + trade.delta_quantity;
+        trade.action = if trade.notional > 0.0 {
+            "BUY"
+        } else if trade.notional < 0.0 {
+            "SELL"
+        } else {
+            "HOLD"
+        }
+        .to_string();
+    }
+
+    let cash_after = cash - trades.iter().map(|t| t.notional).sum::<f64>();
+
+    Ok(RebalancePlan {
+        total_value,
+        cash_before: cash,
+        cash_after,
+        buy_scale,
+        trades,
+    })
+}
+
+/// Suggest buy/sell quantities that bring a portfolio to its target weights
+#[tauri::command]
+pub async fn portfolio_compute_rebalance(
+    portfolio_id: String,
+    target_weights: HashMap<String, f64>,
+    prices: HashMap<String, f64>,
+    available_cash: Option<f64>,
+    tolerance: Option<f64>,
+    min_trade_value: Option<f64>,
+) -> Result<RebalancePlan, String> {
+    let assets = operations::get_portfolio_assets(&portfolio_id).map_err(|e| e.to_string())?;
+
+    let mut holdings: HashMap<String, f64> = HashMap::new();
+    for asset in assets {
+        let symbol = asset.get("symbol").and_then(|v| v.as_str()).unwrap_or_default();
+        let quantity = asset.get("quantity").and_then(|v| v.as_f64()).unwrap_or(0.0);
+        if !symbol.is_empty() && quantity > 0.0 {
+            *holdings.entry(symbol.to_string()).or_insert(0.0) += quantity;
+        }
+    }
+
+    compute_rebalance(
+        &holdings,
+        &target_weights,
+        &prices,
+        available_cash.unwrap_or(0.0),
+        tolerance.unwrap_or(DEFAULT_REBALANCE_TOLERANCE),
+        min_trade_value.unwrap_or(0.0),
+    )
+}
+
+/// One holding valued at the current quote
+#[derive(Debug, Clone, Serialize)]
+pub struct HoldingValuation {
+    /// The stored asset row (id, symbol, quantity, avg_buy_price, ...)
+    #[serde(flatten)]
+    pub asset: serde_json::Map<String, Value>,
+    /// Latest price in `price_currency`
+    pub current_price: f64,
+    /// Currency the asset is quoted in; assumed to be the portfolio currency when unknown
+    pub price_currency: String,
+    /// Rate from `price_currency` to the portfolio currency, if one was available
+    pub fx_rate: Option<f64>,
+    /// Whether the values below are in the portfolio currency; if false they are in
+    /// `price_currency` and the holding is left out of the totals
+    pub converted: bool,
+    pub market_value: f64,
+    pub cost_basis: f64,
+    pub unrealized_pnl: f64,
+    pub unrealized_pnl_percent: f64,
+    pub day_change: f64,
+    pub day_change_percent: f64,
+    /// Percent of total market value (0 for unconverted holdings)
+    pub weight: f64,
+}
+
+/// Portfolio valued in its own currency
+#[derive(Debug, Clone, Serialize)]
+pub struct PortfolioSummary {
+    pub portfolio: Value,
+    pub currency: String,
+    pub holdings: Vec<HoldingValuation>,
+    pub total_market_value: f64,
+    pub total_cost_basis: f64,
+    pub total_unrealized_pnl: f64,
+    pub total_unrealized_pnl_percent: f64,
+    pub total_positions: usize,
+    pub total_day_change: f64,
+    pub total_day_change_percent: f64,
+    /// Holdings that could not be priced or converted
+    pub warnings: Vec<String>,
+    pub last_updated: String,
+}
+
+fn percent(part: f64, whole: f64) -> f64 {
+    if whole != 0.0 {
+        part / whole * 100.0
+    } else {
+        0.0
+    }
+}
+
+/// Value `assets` in `currency` using `quotes` (by symbol) and `fx_rates`
+/// (price currency -> rate into `currency`, or why it is missing)
+///
+/// Cost basis is converted at the current rate too, so P&L reflects price moves in
+/// the asset's own market, not FX moves since purchase. Holdings without a quote
+/// are valued at their average buy price.
+pub fn compute_portfolio_summary(
+    portfolio: Value,
+    currency: &str,
+    assets: Vec<Value>,
+    quotes: &HashMap<String, QuoteData>,
+    fx_rates: &HashMap<String, Result<f64, String>>,
+) -> PortfolioSummary {
+    let mut warnings = Vec::new();
+    let mut holdings = Vec::with_capacity(assets.len());
+
+    for asset in assets {
+        let asset = match asset {
+            Value::Object(map) => map,
+            _ => continue,
+        };
+        let symbol = asset.get("symbol").and_then(|v| v.as_str()).unwrap_or_default().to_string();
+        let quantity = asset.get("quantity").and_then(|v| v.as_f64()).unwrap_or(0.0);
+        let avg_buy_price = asset.get("avg_buy_price").and_then(|v| v.as_f64()).unwrap_or(0.0);
+
+        let quote = quotes.get(&symbol).filter(|q| q.price > 0.0);
+        if quote.is_none() {
+            warnings.push(format!("{}: no current quote; valued at average buy price", symbol));
+        }
+        let current_price = quote.map_or(avg_buy_price, |q| q.price);
+        let previous_close = quote.and_then(|q| q.previous_close).unwrap_or(current_price);
+        let price_currency = quote
+            .and_then(|q| q.currency.clone())
+            .unwrap_or_else(|| currency.to_string());
+
+        let fx = if price_currency == currency {
+            Ok(1.0)
+        } else {
+            fx_rates
+                .get(&price_currency)
+                .cloned()
+                .unwrap_or_else(|| Err(format!("No FX rate for {}/{}", price_currency, currency)))
+        };
+        let (fx_rate, scale) = match fx {
+            Ok(rate) => (Some(rate), rate),
+            Err(e) => {
+                warnings.push(format!(
+                    "{}: {}; shown in {} and excluded from totals",
+                    symbol, e, price_currency
+                ));
+                (None, 1.0)
+            }
+        };
+
+        let market_value = current_price * quantity * scale;
+        let cost_basis = avg_buy_price * quantity * scale;
+        let day_change = (current_price - previous_close) * quantity * scale;
+
+        holdings.push(HoldingValuation {
+            asset,
+            current_price,
+            price_currency,
+            fx_rate,
+            converted: fx_rate.is_some(),
+            market_value,
+            cost_basis,
+            unrealized_pnl: market_value - cost_basis,
+            unrealized_pnl_percent: percent(market_value - cost_basis, cost_basis),
+            day_change,
+            day_change_percent: percent(current_price - previous_close, previous_close),
+            weight: 0.0,
+        });
+    }
+
+    let converted = || holdings.iter().filter(|h| h.converted);
+    let total_market_value: f64 = converted().map(|h| h.market_value).sum();
+    let total_cost_basis: f64 = converted().map(|h| h.cost_basis).sum();
+    let total_day_change: f64 = converted().map(|h| h.day_change).sum();
+    for holding in holdings.iter_mut().filter(|h| h.converted) {
+        holding.weight = percent(holding.market_value, total_market_value);
+    }
+
+    PortfolioSummary {
+        portfolio,
+        currency: currency.to_string(),
+        total_positions: holdings.len(),
+        holdings,
+        total_market_value,
+        total_cost_basis,
+        total_unrealized_pnl: total_market_value - total_cost_basis,
+        total_unrealized_pnl_percent: percent(total_market_value - total_cost_basis, total_cost_basis),
+        total_day_change,
+        total_day_change_percent: percent(total_day_change, total_market_value - total_day_change),
+        warnings,
+        last_updated: chrono::Utc::now().to_rfc3339(),
+    }
+}
+
+/// Value a portfolio with quotes and FX rates from the given fetchers
+///
+/// Shared by the Tauri command and the web RPC, which fetch through different
+/// providers.
+pub async fn portfolio_summary<Q, QF, X, XF>(
+    portfolio_id: &str,
+    fetch_quotes: Q,
+    fx_rate: X,
+) -> Result<PortfolioSummary, String>
+where
+    Q: FnOnce(Vec<String>) -> QF,
+    QF: std::future::Future<Output = Vec<QuoteData>>,
+    X: Fn(String, String) -> XF,
+    XF: std::future::Future<Output = Result<f64, String>>,
+{
+    let portfolio = operations::get_portfolio_by_id(portfolio_id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "Portfolio not found".to_string())?;
+    let currency = portfolio
+        .get("currency")
+        .and_then(|v| v.as_str())
+        .unwrap_or("USD")
+        .to_string();
+    let assets = operations::get_portfolio_assets(portfolio_id).map_err(|e| e.to_string())?;
+
+    let symbols: Vec<String> = assets
+        .iter()
+        .filter_map(|a| a.get("symbol").and_then(|v| v.as_str()).map(String::from))
+        .collect();
+    let quotes: HashMap<String, QuoteData> = if symbols.is_empty() {
+        HashMap::new()
+    } else {
+        fetch_quotes(symbols)
+            .await
+            .into_iter()
+            .map(|q| (q.symbol.clone(), q))
+            .collect()
+    };
+
+    let price_currencies: BTreeSet<String> = quotes
+        .values()
+        .filter_map(|q| q.currency.clone())
+        .filter(|c| *c != currency)
+        .collect();
+    let mut fx_rates = HashMap::new();
+    for price_currency in price_currencies {
+        let rate = fx_rate(price_currency.clone(), currency.clone()).await;
+        fx_rates.insert(price_currency, rate);
+    }
+
+    Ok(compute_portfolio_summary(portfolio, &currency, assets, &quotes, &fx_rates))
+}
+
+/// Value a portfolio at current quotes, converted into the portfolio currency
+#[tauri::command]
+pub async fn get_portfolio_summary(app: tauri::AppHandle, portfolio_id: String) -> Result<PortfolioSummary, String> {
+    let provider = YFinanceProvider::new(&app).map_err(|e| e.to_string())?;
+    let provider = &provider;
+    portfolio_summary(
+        &portfolio_id,
+        |symbols| provider.get_quotes(symbols),
+        |base, quote| async move { provider.get_fx_rate(&base, &quote).await.map(|r| r.rate) },
+    )
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn map(entries: &[(&str, f64)]) -> HashMap<String, f64> {
+        entries.iter().map(|(k, v)| (k.to_string(), *v)).collect()
+    }
+
+    fn trade<'a>(plan: &'a RebalancePlan, symbol: &str) -> &'a RebalanceTrade {
+        plan.trades.iter().find(|t| t.symbol == symbol).unwrap()
+    }
+
+    #[test]
+    fn test_rebalance_to_equal_weights() {
+        // 100 AAPL @ 150 = 15000, 10 MSFT @ 300 = 3000, cash 2000 -> total 20000
+        let holdings = map(&[("AAPL", 100.0), ("MSFT", 10.0)]);
+        let targets = map(&[("AAPL", 0.5), ("MSFT", 0.5)]);
+        let prices = map(&[("AAPL", 150.0), ("MSFT", 300.0)]);
+
+        let plan = compute_rebalance(&holdings, &targets, &prices, 2000.0, 0.01, 0.0).unwrap();
+
+        assert_eq!(plan.total_value, 20000.0);
+        assert_eq!(trade(&plan, "AAPL").action, "SELL");
+        assert!((trade(&plan, "AAPL").delta_quantity + 100.0 / 3.0).abs() < 1e-9);
+        assert_eq!(trade(&plan, "MSFT").action, "BUY");
+        assert!((trade(&plan, "MSFT").delta_quantity - 70.0 / 3.0).abs() < 1e-9);
+        assert!(plan.cash_after.abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_tolerance_and_min_trade_skip_small_drifts() {
+        let holdings = map(&[("AAPL", 50.0), ("MSFT", 49.0)]);
+        let targets = map(&[("AAPL", 0.5), ("MSFT", 0.5)]);
+        let prices = map(&[("AAPL", 100.0), ("MSFT", 100.0)]);
+
+        let plan = compute_rebalance(&holdings, &targets, &prices, 0.0, 0.01, 0.0).unwrap();
+        assert!(plan.trades.iter().all(|t| t.action == "HOLD"));
+
+        let plan = compute_rebalance(&holdings, &targets, &prices, 0.0, 0.0, 100.0).unwrap();
+        assert!(plan.trades.iter().all(|t| t.action == "HOLD"));
+    }
+
+    #[test]
+    fn test_buys_limited_by_cash() {
+        // AAPL is within tolerance so nothing is sold; the MSFT buy must fit in 100 of cash
+        let holdings = map(&[("AAPL", 100.0)]);
+        let targets = map(&[("AAPL", 0.985), ("MSFT", 0.015)]);
+        let prices = map(&[("AAPL", 100.0), ("MSFT", 50.0)]);
+
+        let plan = compute_rebalance(&holdings, &targets, &prices, 100.0, 0.01, 0.0).unwrap();
+
+        assert_eq!(trade(&plan, "AAPL").action, "HOLD");
+        assert_eq!(trade(&plan, "MSFT").action, "BUY");
+        assert!(plan.buy_scale < 1.0);
+        assert!((trade(&plan, "MSFT").notional - 100.0).abs() < 1e-9);
+        assert!(plan.cash_after.abs() < 1e-9);
+    }
+
+    fn quote(symbol: &str, price: f64, previous_close: f64, currency: &str) -> QuoteData {
+        QuoteData {
+            symbol: symbol.to_string(),
+            price,
+            change: price - previous_close,
+            change_percent: 0.0,
+            volume: None,
+            high: None,
+            low: None,
+            open: None,
+            previous_close: Some(previous_close),
+            timestamp: 0,
+            currency: Some(currency.to_string()),
+        }
+    }
+
+    #[test]
+    fn test_summary_converts_into_portfolio_currency() {
+        let assets = vec![
+            serde_json::json!({"symbol": "SAP.DE", "quantity": 10.0, "avg_buy_price": 100.0}),
+            serde_json::json!({"symbol": "VOD.L", "quantity": 100.0, "avg_buy_price": 70.0}),
+            serde_json::json!({"symbol": "AAPL", "quantity": 5.0, "avg_buy_price": 150.0}),
+        ];
+        let quotes: HashMap<String, QuoteData> = [
+            quote("SAP.DE", 120.0, 110.0, "EUR"),
+            quote("VOD.L", 72.0, 72.0, "GBp"),
+            quote("AAPL", 200.0, 200.0, "USD"),
+        ]
+        .into_iter()
+        .map(|q| (q.symbol.clone(), q))
+        .collect();
+        let fx_rates: HashMap<String, Result<f64, String>> = [
+            ("EUR".to_string(), Ok(1.1)),
+            ("GBp".to_string(), Err("No FX rate for GBP/USD: timeout".to_string())),
+        ]
+        .into_iter()
+        .collect();
+
+        let summary = compute_portfolio_summary(serde_json::json!({}), "USD", assets, &quotes, &fx_rates);
+
+        let sap = &summary.holdings[0];
+        assert!(sap.converted);
+        assert!((sap.market_value - 1320.0).abs() < 1e-9);
+        assert!((sap.cost_basis - 1100.0).abs() < 1e-9);
+        assert!((sap.day_change - 110.0).abs() < 1e-9);
+
+        // Unconvertible holding keeps its own currency and stays out of the totals
+        let vod = &summary.holdings[1];
+        assert!(!vod.converted);
+        assert_eq!(vod.price_currency, "GBp");
+        assert!((vod.market_value - 7200.0).abs() < 1e-9);
+        assert_eq!(vod.weight, 0.0);
+        assert_eq!(summary.warnings.len(), 1);
+        assert!(summary.warnings[0].starts_with("VOD.L"));
+
+        assert!((summary.total_market_value - 2320.0).abs() < 1e-9);
+        assert!((summary.total_cost_basis - 1850.0).abs() < 1e-9);
+        assert_eq!(summary.total_positions, 3);
+        let weights: f64 = summary.holdings.iter().map(|h| h.weight).sum();
+        assert!((weights - 100.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_missing_price_and_overweight_targets_are_rejected() {
+        let holdings = map(&[("AAPL", 1.0)]);
+        let prices = map(&[("AAPL", 100.0)]);
+
+        assert!(compute_rebalance(&holdings, &map(&[("MSFT", 0.5)]), &prices, 0.0, 0.0, 0.0).is_err());
+        assert!(compute_rebalance(&holdings, &map(&[("AAPL", 1.2)]), &prices, 0.0, 0.0, 0.0).is_err());
+    }
+}
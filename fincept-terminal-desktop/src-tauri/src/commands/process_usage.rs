@@ -0,0 +1,188 @@
+// Process Usage - CPU and memory of the child processes the app spawned
+//
+// What: `get_process_resource_usage` reports CPU percentage, resident and virtual
+//       memory and run time for each running MCP server and Python pool worker,
+//       or for one of them by id (an MCP server id, or `python-worker-<n>`).
+//       `start_process_usage_stream` emits the same report as a `process_usage`
+//       event every few seconds until `stop_process_usage_stream`.
+// Why:  A runaway MCP server or a Python script stuck in a loop was only visible
+//       in the OS task manager, with nothing tying the pid back to the app.
+// How:  Pids come from the `Child` handles the app already holds (MCP) and from
+//       the pids recorded when the worker pool started. `sysinfo` samples them.
+//       CPU percentage is measured between two samples of the same process, so
+//       the shared `System` keeps the previous sample, and a process seen for the
+//       first time is sampled twice, `MINIMUM_CPU_UPDATE_INTERVAL` apart.
+
+use crate::MCPState;
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use serde::Serialize;
+use std::time::Duration;
+use sysinfo::{Pid, System, MINIMUM_CPU_UPDATE_INTERVAL};
+use tauri::{Emitter, Manager};
+
+const DEFAULT_STREAM_INTERVAL_SECS: u64 = 5;
+const WORKER_ID_PREFIX: &str = "python-worker-";
+
+/// Previous samples, needed for CPU percentages
+static SYSTEM: Lazy<Mutex<System>> = Lazy::new(|| Mutex::new(System::new()));
+
+/// Running usage stream, replaced by each `start_process_usage_stream`
+static STREAM: Lazy<Mutex<Option<tauri::async_runtime::JoinHandle<()>>>> = Lazy::new(|| Mutex::new(None));
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProcessKind {
+    Mcp,
+    PythonWorker,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ProcessUsage {
+    pub kind: ProcessKind,
+    /// MCP server id, or `python-worker-<n>`
+    pub id: String,
+    pub pid: u32,
+    /// Share of one core since the previous sample; above 100 when using several
+    pub cpu_percent: f32,
+    /// Resident set size
+    pub memory_bytes: u64,
+    pub virtual_memory_bytes: u64,
+    pub run_time_secs: u64,
+}
+
+/// Child processes the app is tracking, optionally only the one named `id`
+fn tracked_processes(mcp_state: &MCPState, id: Option<&str>) -> Vec<(ProcessKind, String, u32)> {
+    let mut tracked: Vec<(ProcessKind, String, u32)> = {
+        let processes = mcp_state.processes.lock().unwrap();
+        processes
+            .iter()
+            .map(|(server_id, mcp_process)| (ProcessKind::Mcp, server_id.clone(), mcp_process.child.id()))
+            .collect()
+    };
+    tracked.sort_by(|a, b| a.1.cmp(&b.1));
+    tracked.extend(
+        crate::worker_pool::worker_pids()
+            .into_iter()
+            .enumerate()
+            .map(|(index, pid)| (ProcessKind::PythonWorker, format!("{}{}", WORKER_ID_PREFIX, index), pid)),
+    );
+    match id {
+        Some(id) => tracked.into_iter().filter(|(_, tracked_id, _)| tracked_id == id).collect(),
+        None => tracked,
+    }
+}
+
+/// Sample every tracked process; ones that have exited are left out
+fn sample(tracked: Vec<(ProcessKind, String, u32)>) -> Vec<ProcessUsage> {
+    let mut system = SYSTEM.lock();
+    let first_seen = tracked
+        .iter()
+        .any(|(_, _, pid)| system.process(Pid::from_u32(*pid)).is_none());
+    for (_, _, pid) in &tracked {
+        system.refresh_process(Pid::from_u32(*pid));
+    }
+    if first_seen {
+        std::thread::sleep(MINIMUM_CPU_UPDATE_INTERVAL);
+        for (_, _, pid) in &tracked {
+            system.refresh_process(Pid::from_u32(*pid));
+        }
+    }
+
+    tracked
+        .into_iter()
+        .filter_map(|(kind, id, pid)| {
+            let process = system.process(Pid::from_u32(pid))?;
+            Some(ProcessUsage {
+                kind,
+                id,
+                pid,
+                cpu_percent: process.cpu_usage(),
+                memory_bytes: process.memory(),
+                virtual_memory_bytes: process.virtual_memory(),
+                run_time_secs: process.run_time(),
+            })
+        })
+        .collect()
+}
+
+/// Usage of the tracked processes, or of the one named `id`
+pub async fn process_resource_usage(mcp_state: &MCPState, id: Option<String>) -> Result<Vec<ProcessUsage>, String> {
+    let id = id.map(|id| id.trim().to_string()).filter(|id| !id.is_empty());
+    let tracked = tracked_processes(mcp_state, id.as_deref());
+    if let Some(id) = &id {
+        if tracked.is_empty() {
+            return Err(format!("No running MCP server or Python worker with id '{}'", id));
+        }
+    }
+
+    tokio::task::spawn_blocking(move || sample(tracked))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// CPU and memory of running MCP servers and Python workers
+///
+/// `id` limits the report to one MCP server id or `python-worker-<n>`.
+#[tauri::command]
+pub async fn get_process_resource_usage(
+    mcp_state: tauri::State<'_, MCPState>,
+    id: Option<String>,
+) -> Result<Vec<ProcessUsage>, String> {
+    process_resource_usage(&mcp_state, id).await
+}
+
+/// Emit a `process_usage` event (a list of `ProcessUsage`) every `interval_secs` (default 5)
+///
+/// Replaces a stream already running, e.g. to change the interval.
+#[tauri::command]
+pub async fn start_process_usage_stream(app: tauri::AppHandle, interval_secs: Option<u64>) -> Result<(), String> {
+    let interval = Duration::from_secs(interval_secs.unwrap_or(DEFAULT_STREAM_INTERVAL_SECS).max(1));
+
+    let task = tauri::async_runtime::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            let usage = match process_resource_usage(&app.state::<MCPState>(), None).await {
+                Ok(usage) => usage,
+                Err(e) => {
+                    tracing::warn!(error = %e, "Process usage stream could not sample processes");
+                    continue;
+                }
+            };
+            let _ = app.emit("process_usage", &usage);
+        }
+    });
+    if let Some(previous) = STREAM.lock().replace(task) {
+        previous.abort();
+    }
+    Ok(())
+}
+
+/// Stop the stream started by `start_process_usage_stream`; false if none was running
+#[tauri::command]
+pub async fn stop_process_usage_stream() -> Result<bool, String> {
+    Ok(match STREAM.lock().take() {
+        Some(task) => {
+            task.abort();
+            true
+        }
+        None => false,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_samples_live_processes_and_skips_missing_ones() {
+        let own = (ProcessKind::PythonWorker, "python-worker-0".to_string(), std::process::id());
+        let missing = (ProcessKind::Mcp, "gone".to_string(), u32::MAX - 1);
+
+        let usage = sample(vec![own, missing]);
+        assert_eq!(usage.len(), 1);
+        assert_eq!((usage[0].id.as_str(), usage[0].pid), ("python-worker-0", std::process::id()));
+        assert!(usage[0].memory_bytes > 0);
+    }
+}
@@ -0,0 +1,211 @@
+// Series Analytics - native transforms over numeric series
+//
+// What: Rolling mean and standard deviation, simple and log returns, z-scores and
+//       Pearson correlation over `Vec<f64>`, exposed as `series_*` commands.
+// Why:  These cheap transforms were computed by spawning Python, which cost a
+//       process start and a JSON round trip per chart redraw and tied up Python
+//       workers. Model fitting (ARIMA and friends) stays in Python.
+// How:  Outputs line up index-for-index with the input; positions without a value
+//       (the first `window - 1` rolling points, the first return, a return from a
+//       zero or negative price) are `null`, where pandas would give NaN. Rolling
+//       and z-score deviations follow pandas/scipy defaults: sample (ddof = 1) for
+//       `rolling().std()`, population (ddof = 0) for `scipy.stats.zscore`. Rolling
+//       windows keep running sums of values shifted by the first point to limit
+//       cancellation. Non-finite inputs are rejected up front.
+//       `cargo run --release --example series_analytics_bench` times these against
+//       the same numpy/pandas calls through a Python subprocess.
+
+fn check_finite(data: &[f64]) -> Result<(), String> {
+    match data.iter().position(|v| !v.is_finite()) {
+        Some(i) => Err(format!("Series value at index {} is not a finite number", i)),
+        None => Ok(()),
+    }
+}
+
+fn check_window(data: &[f64], window: usize, min: usize) -> Result<(), String> {
+    if window < min {
+        return Err(format!("window must be at least {}", min));
+    }
+    check_finite(data)
+}
+
+/// Mean of each trailing `window`; windows longer than the series give all `None`
+pub fn rolling_mean(data: &[f64], window: usize) -> Result<Vec<Option<f64>>, String> {
+    check_window(data, window, 1)?;
+    let shift = data.first().copied().unwrap_or(0.0);
+    let mut sum = 0.0;
+    Ok(data
+        .iter()
+        .enumerate()
+        .map(|(i, v)| {
+            sum += v - shift;
+            if i >= window {
+                sum -= data[i - window] - shift;
+            }
+            (i + 1 >= window).then_some(shift + sum / window as f64)
+        })
+        .collect())
+}
+
+/// Sample standard deviation of each trailing `window`
+pub fn rolling_std(data: &[f64], window: usize) -> Result<Vec<Option<f64>>, String> {
+    check_window(data, window, 2)?;
+    let shift = data.first().copied().unwrap_or(0.0);
+    let n = window as f64;
+    let (mut sum, mut sum_sq) = (0.0, 0.0);
+    Ok(data
+        .iter()
+        .enumerate()
+        .map(|(i, v)| {
+            let v = v - shift;
+            sum += v;
+            sum_sq += v * v;
+            if i >= window {
+                let old = data[i - window] - shift;
+                sum -= old;
+                sum_sq -= old * old;
+            }
+            (i + 1 >= window).then(|| ((sum_sq - sum * sum / n) / (n - 1.0)).max(0.0).sqrt())
+        })
+        .collect())
+}
+
+/// `p[i] / p[i-1] - 1`; `None` for the first point and after a zero price
+pub fn simple_returns(prices: &[f64]) -> Result<Vec<Option<f64>>, String> {
+    check_finite(prices)?;
+    Ok(std::iter::once(None)
+        .chain(prices.windows(2).map(|w| (w[0] != 0.0).then(|| w[1] / w[0] - 1.0)))
+        .take(prices.len())
+        .collect())
+}
+
+/// `ln(p[i] / p[i-1])`; `None` for the first point and around non-positive prices
+pub fn log_returns(prices: &[f64]) -> Result<Vec<Option<f64>>, String> {
+    check_finite(prices)?;
+    Ok(std::iter::once(None)
+        .chain(prices.windows(2).map(|w| (w[0] > 0.0 && w[1] > 0.0).then(|| (w[1] / w[0]).ln())))
+        .take(prices.len())
+        .collect())
+}
+
+fn mean(data: &[f64]) -> f64 {
+    data.iter().sum::<f64>() / data.len() as f64
+}
+
+/// `(x - mean) / std` with the population deviation; all `None` for a constant series
+pub fn zscore(data: &[f64]) -> Result<Vec<Option<f64>>, String> {
+    check_finite(data)?;
+    if data.is_empty() {
+        return Ok(Vec::new());
+    }
+    let mean = mean(data);
+    let std = (data.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / data.len() as f64).sqrt();
+    Ok(data.iter().map(|v| (std > 0.0).then_some((v - mean) / std)).collect())
+}
+
+/// Pearson correlation; `None` if either series is constant
+pub fn correlation(x: &[f64], y: &[f64]) -> Result<Option<f64>, String> {
+    if x.len() != y.len() {
+        return Err(format!("Series lengths differ: {} and {}", x.len(), y.len()));
+    }
+    if x.len() < 2 {
+        return Err("Correlation needs at least 2 points".to_string());
+    }
+    check_finite(x)?;
+    check_finite(y)?;
+
+    let (mean_x, mean_y) = (mean(x), mean(y));
+    let (mut cov, mut var_x, mut var_y) = (0.0, 0.0, 0.0);
+    for (a, b) in x.iter().zip(y) {
+        let (dx, dy) = (a - mean_x, b - mean_y);
+        cov += dx * dy;
+        var_x += dx * dx;
+        var_y += dy * dy;
+    }
+    if var_x == 0.0 || var_y == 0.0 {
+        return Ok(None);
+    }
+    Ok(Some((cov / (var_x * var_y).sqrt()).clamp(-1.0, 1.0)))
+}
+
+#[tauri::command]
+pub async fn series_rolling_mean(data: Vec<f64>, window: usize) -> Result<Vec<Option<f64>>, String> {
+    rolling_mean(&data, window)
+}
+
+#[tauri::command]
+pub async fn series_rolling_std(data: Vec<f64>, window: usize) -> Result<Vec<Option<f64>>, String> {
+    rolling_std(&data, window)
+}
+
+#[tauri::command]
+pub async fn series_returns(data: Vec<f64>) -> Result<Vec<Option<f64>>, String> {
+    simple_returns(&data)
+}
+
+#[tauri::command]
+pub async fn series_log_returns(data: Vec<f64>) -> Result<Vec<Option<f64>>, String> {
+    log_returns(&data)
+}
+
+#[tauri::command]
+pub async fn series_zscore(data: Vec<f64>) -> Result<Vec<Option<f64>>, String> {
+    zscore(&data)
+}
+
+#[tauri::command]
+pub async fn series_correlation(x: Vec<f64>, y: Vec<f64>) -> Result<Option<f64>, String> {
+    correlation(&x, &y)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: &[Option<f64>], expected: &[Option<f64>]) {
+        assert_eq!(actual.len(), expected.len(), "{:?}", actual);
+        for (a, e) in actual.iter().zip(expected) {
+            match (a, e) {
+                (Some(a), Some(e)) => assert!((a - e).abs() < 1e-9, "{:?} vs {:?}", actual, expected),
+                _ => assert_eq!(a, e, "{:?} vs {:?}", actual, expected),
+            }
+        }
+    }
+
+    #[test]
+    fn test_rolling_windows_match_pandas() {
+        let data = [1.0, 2.0, 4.0, 7.0, 11.0];
+        assert_close(&rolling_mean(&data, 3).unwrap(), &[None, None, Some(7.0 / 3.0), Some(13.0 / 3.0), Some(22.0 / 3.0)]);
+        // pd.Series(data).rolling(2).std()
+        let half = 0.5f64.sqrt();
+        assert_close(&rolling_std(&data, 2).unwrap(), &[None, Some(half), Some(2.0 * half), Some(3.0 * half), Some(4.0 * half)]);
+        assert_eq!(rolling_mean(&data, 10).unwrap(), vec![None; 5]);
+
+        // Large offsets don't lose the deviation to cancellation
+        let prices = [1e9 + 1.0, 1e9 + 2.0, 1e9 + 3.0];
+        assert_close(&rolling_std(&prices, 3).unwrap(), &[None, None, Some(1.0)]);
+
+        assert!(rolling_std(&data, 1).is_err());
+        assert!(rolling_mean(&[1.0, f64::NAN], 1).unwrap_err().contains("index 1"));
+    }
+
+    #[test]
+    fn test_returns() {
+        let prices = [100.0, 110.0, 0.0, 50.0];
+        assert_close(&simple_returns(&prices).unwrap(), &[None, Some(0.1), Some(-1.0), None]);
+        assert_close(&log_returns(&prices).unwrap(), &[None, Some(1.1f64.ln()), None, None]);
+        assert!(simple_returns(&[]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_zscore_and_correlation() {
+        assert_close(&zscore(&[2.0, 4.0, 6.0]).unwrap(), &[Some(-(1.5f64.sqrt())), Some(0.0), Some(1.5f64.sqrt())]);
+        assert_eq!(zscore(&[3.0, 3.0]).unwrap(), vec![None, None]);
+
+        let x = [1.0, 2.0, 3.0, 4.0];
+        assert!((correlation(&x, &[2.0, 4.0, 6.0, 8.0]).unwrap().unwrap() - 1.0).abs() < 1e-12);
+        assert!((correlation(&x, &[4.0, 3.0, 2.0, 1.0]).unwrap().unwrap() + 1.0).abs() < 1e-12);
+        assert_eq!(correlation(&x, &[5.0; 4]).unwrap(), None);
+        assert!(correlation(&x, &[1.0]).is_err());
+    }
+}
@@ -0,0 +1,341 @@
+// Symbol Search - provider-agnostic ticker autocomplete
+//
+// What: `search_symbols(query, limit)` asks every available provider for matching
+//       tickers and returns one merged list of `SymbolMatch { symbol, name,
+//       exchange, type, provider }` for the watchlist and quote inputs.
+// Why:  `search_alphavantage_symbols` was the only search: one provider, needs a
+//       key, and five requests a minute on the free tier, which typing exhausts.
+// How:  Yahoo's search endpoint (no key) and Alpha Vantage's SYMBOL_SEARCH (only
+//       when an `alphavantage` credential or the `ALPHA_VANTAGE_API_KEY` setting
+//       from the API keys screen holds a key) are queried concurrently. Each
+//       provider has a request budget per minute; a provider over budget is skipped
+//       for that query rather than delaying the keystroke. Results are deduped by
+//       upper-cased symbol (earlier providers win, later ones fill gaps), ranked
+//       exact match, then symbol prefix, then provider order, and cached per
+//       (query, limit) for `CACHE_TTL` unless every provider failed.
+
+use crate::database::operations;
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
+
+const DEFAULT_LIMIT: usize = 10;
+const MAX_LIMIT: usize = 50;
+const CACHE_TTL: Duration = Duration::from_secs(10 * 60);
+const CACHE_CAPACITY: usize = 256;
+const RATE_WINDOW: Duration = Duration::from_secs(60);
+const YAHOO_SEARCH_URL: &str = "https://query2.finance.yahoo.com/v1/finance/search";
+const ALPHAVANTAGE_URL: &str = "https://www.alphavantage.co/query";
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SymbolMatch {
+    pub symbol: String,
+    pub name: Option<String>,
+    pub exchange: Option<String>,
+    /// Asset type as the provider reports it ("EQUITY", "ETF", ...)
+    #[serde(rename = "type")]
+    pub asset_type: Option<String>,
+    pub provider: String,
+}
+
+/// Requests per `RATE_WINDOW` each provider may receive
+fn rate_budget(provider: &str) -> usize {
+    match provider {
+        "alphavantage" => 5,
+        _ => 60,
+    }
+}
+
+static RATE_LIMITS: Lazy<Mutex<HashMap<&'static str, VecDeque<Instant>>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+type CacheKey = (String, usize);
+static CACHE: Lazy<Mutex<HashMap<CacheKey, (Instant, Vec<SymbolMatch>)>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Record a request in `hits` if fewer than `budget` happened in the last window
+fn try_acquire(hits: &mut VecDeque<Instant>, budget: usize, now: Instant) -> bool {
+    while hits.front().is_some_and(|t| now.duration_since(*t) >= RATE_WINDOW) {
+        hits.pop_front();
+    }
+    if hits.len() >= budget {
+        return false;
+    }
+    hits.push_back(now);
+    true
+}
+
+fn acquire(provider: &'static str) -> bool {
+    let mut limits = RATE_LIMITS.lock();
+    try_acquire(limits.entry(provider).or_default(), rate_budget(provider), Instant::now())
+}
+
+fn text(value: &Value, key: &str) -> Option<String> {
+    value
+        .get(key)
+        .and_then(|v| v.as_str())
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(String::from)
+}
+
+/// Matches from Yahoo's `/v1/finance/search` response
+pub fn parse_yahoo(body: &Value) -> Vec<SymbolMatch> {
+    body.get("quotes")
+        .and_then(|q| q.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|quote| {
+            Some(SymbolMatch {
+                symbol: text(quote, "symbol")?,
+                name: text(quote, "longname").or_else(|| text(quote, "shortname")),
+                exchange: text(quote, "exchDisp").or_else(|| text(quote, "exchange")),
+                asset_type: text(quote, "quoteType"),
+                provider: "yahoo".to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Matches from Alpha Vantage's SYMBOL_SEARCH response
+pub fn parse_alphavantage(body: &Value) -> Result<Vec<SymbolMatch>, String> {
+    // Rate limit and key errors come back as 200 with a message instead of matches
+    if let Some(message) = ["Note", "Information", "Error Message"].iter().find_map(|k| text(body, k)) {
+        return Err(message);
+    }
+    Ok(body
+        .get("bestMatches")
+        .and_then(|m| m.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|m| {
+            Some(SymbolMatch {
+                symbol: text(m, "1. symbol")?,
+                name: text(m, "2. name"),
+                exchange: text(m, "4. region"),
+                asset_type: text(m, "3. type"),
+                provider: "alphavantage".to_string(),
+            })
+        })
+        .collect())
+}
+
+/// Dedupe by symbol and rank; `lists` are in provider priority order
+pub fn merge(query: &str, lists: Vec<Vec<SymbolMatch>>, limit: usize) -> Vec<SymbolMatch> {
+    let mut merged: Vec<SymbolMatch> = Vec::new();
+    let mut index: HashMap<String, usize> = HashMap::new();
+    for m in lists.into_iter().flatten() {
+        match index.get(&m.symbol.to_uppercase()) {
+            Some(&i) => {
+                let existing = &mut merged[i];
+                existing.name = existing.name.take().or(m.name);
+                existing.exchange = existing.exchange.take().or(m.exchange);
+                existing.asset_type = existing.asset_type.take().or(m.asset_type);
+            }
+            None => {
+                index.insert(m.symbol.to_uppercase(), merged.len());
+                merged.push(m);
+            }
+        }
+    }
+
+    let query = query.to_uppercase();
+    let rank = |m: &SymbolMatch| {
+        let symbol = m.symbol.to_uppercase();
+        if symbol == query {
+            0
+        } else if symbol.starts_with(&query) {
+            1
+        } else {
+            2
+        }
+    };
+    // Stable sort keeps provider order within each rank
+    merged.sort_by_key(rank);
+    merged.truncate(limit);
+    merged
+}
+
+async fn search_yahoo(client: &reqwest::Client, query: &str, limit: usize) -> Result<Vec<SymbolMatch>, String> {
+    let count = limit.to_string();
+    let body: Value = client
+        .get(YAHOO_SEARCH_URL)
+        .query(&[("q", query), ("quotesCount", count.as_str()), ("newsCount", "0")])
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| e.to_string())?
+        .json()
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(parse_yahoo(&body))
+}
+
+/// Setting the API keys screen saves the Alpha Vantage key under
+const ALPHAVANTAGE_KEY_SETTING: &str = "ALPHA_VANTAGE_API_KEY";
+
+fn alphavantage_key() -> Option<String> {
+    let usable = |key: &String| !key.trim().is_empty();
+    ["alphavantage", "alpha_vantage"]
+        .iter()
+        .find_map(|service| {
+            operations::get_credential_by_service(service)
+                .ok()
+                .flatten()
+                .and_then(|c| c.api_key)
+                .filter(usable)
+        })
+        .or_else(|| operations::get_setting(ALPHAVANTAGE_KEY_SETTING).ok().flatten().filter(usable))
+}
+
+async fn search_alphavantage(client: &reqwest::Client, query: &str, api_key: &str) -> Result<Vec<SymbolMatch>, String> {
+    let body: Value = client
+        .get(ALPHAVANTAGE_URL)
+        .query(&[("function", "SYMBOL_SEARCH"), ("keywords", query), ("apikey", api_key)])
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        // The URL carries the API key; keep it out of errors and logs
+        .map_err(|e| e.without_url().to_string())?
+        .json()
+        .await
+        .map_err(|e| e.without_url().to_string())?;
+    parse_alphavantage(&body)
+}
+
+/// Query `provider` unless it is out of budget; `None` means it was not asked
+async fn query_provider<Fut>(provider: &'static str, fut: Fut) -> Option<Result<Vec<SymbolMatch>, String>>
+where
+    Fut: std::future::Future<Output = Result<Vec<SymbolMatch>, String>>,
+{
+    if !acquire(provider) {
+        tracing::debug!(provider, "Symbol search skipped: provider over its request budget");
+        return None;
+    }
+    let result = fut.await;
+    if let Err(e) = &result {
+        tracing::warn!(provider, error = %e, "Symbol search failed");
+    }
+    Some(result)
+}
+
+fn cached(key: &CacheKey) -> Option<Vec<SymbolMatch>> {
+    CACHE
+        .lock()
+        .get(key)
+        .filter(|(at, _)| at.elapsed() < CACHE_TTL)
+        .map(|(_, matches)| matches.clone())
+}
+
+fn store(key: CacheKey, matches: Vec<SymbolMatch>) {
+    let mut cache = CACHE.lock();
+    cache.retain(|_, (at, _)| at.elapsed() < CACHE_TTL);
+    if cache.len() >= CACHE_CAPACITY {
+        if let Some(oldest) = cache.iter().min_by_key(|(_, (at, _))| *at).map(|(k, _)| k.clone()) {
+            cache.remove(&oldest);
+        }
+    }
+    cache.insert(key, (Instant::now(), matches));
+}
+
+/// Merged matches for `query` from every available provider
+pub async fn search(query: &str, limit: Option<usize>) -> Result<Vec<SymbolMatch>, String> {
+    let query = query.trim();
+    if query.is_empty() {
+        return Ok(Vec::new());
+    }
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+    let key = (query.to_lowercase(), limit);
+    if let Some(matches) = cached(&key) {
+        return Ok(matches);
+    }
+
+    let client = crate::utils::http::build_client()?;
+    let api_key = alphavantage_key();
+    let alphavantage = async {
+        match &api_key {
+            Some(api_key) => query_provider("alphavantage", search_alphavantage(&client, query, api_key)).await,
+            None => None,
+        }
+    };
+    let (yahoo, alphavantage) = tokio::join!(query_provider("yahoo", search_yahoo(&client, query, limit)), alphavantage);
+
+    let answered: Vec<Result<Vec<SymbolMatch>, String>> = [yahoo, alphavantage].into_iter().flatten().collect();
+    if answered.is_empty() {
+        return Err("Symbol search is rate limited, try again shortly".to_string());
+    }
+    if answered.iter().all(|r| r.is_err()) {
+        let errors: Vec<&str> = answered.iter().filter_map(|r| r.as_ref().err().map(String::as_str)).collect();
+        return Err(format!("Symbol search failed: {}", errors.join("; ")));
+    }
+
+    let matches = merge(query, answered.into_iter().filter_map(Result::ok).collect(), limit);
+    store(key, matches.clone());
+    Ok(matches)
+}
+
+/// Ticker autocomplete across providers
+#[tauri::command]
+pub async fn search_symbols(query: String, limit: Option<usize>) -> Result<Vec<SymbolMatch>, String> {
+    search(&query, limit).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn symbol(symbol: &str, name: Option<&str>, provider: &str) -> SymbolMatch {
+        SymbolMatch {
+            symbol: symbol.to_string(),
+            name: name.map(str::to_string),
+            exchange: None,
+            asset_type: None,
+            provider: provider.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_parse_provider_responses() {
+        let yahoo = serde_json::json!({"quotes": [
+            {"symbol": "AAPL", "shortname": "Apple Inc.", "exchDisp": "NASDAQ", "quoteType": "EQUITY"},
+            {"shortname": "no symbol"}
+        ]});
+        let matches = parse_yahoo(&yahoo);
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].exchange.as_deref(), Some("NASDAQ"));
+        assert_eq!(matches[0].asset_type.as_deref(), Some("EQUITY"));
+
+        let av = serde_json::json!({"bestMatches": [
+            {"1. symbol": "TSCO.LON", "2. name": "Tesco PLC", "3. type": "Equity", "4. region": "United Kingdom"}
+        ]});
+        assert_eq!(parse_alphavantage(&av).unwrap()[0].symbol, "TSCO.LON");
+        let limited = serde_json::json!({"Note": "Thank you for using Alpha Vantage! Our standard API rate limit is 5 requests per minute."});
+        assert!(parse_alphavantage(&limited).unwrap_err().contains("rate limit"));
+    }
+
+    #[test]
+    fn test_merge_dedupes_and_ranks() {
+        let yahoo = vec![symbol("APLE", Some("Apple Hospitality"), "yahoo"), symbol("AAPL", None, "yahoo")];
+        let av = vec![symbol("aapl", Some("Apple Inc"), "alphavantage"), symbol("AAPL.LON", None, "alphavantage")];
+
+        let merged = merge("aapl", vec![yahoo, av], 10);
+        let symbols: Vec<&str> = merged.iter().map(|m| m.symbol.as_str()).collect();
+        assert_eq!(symbols, vec!["AAPL", "AAPL.LON", "APLE"]);
+        // Yahoo's entry wins; Alpha Vantage fills in the missing name
+        assert_eq!(merged[0].provider, "yahoo");
+        assert_eq!(merged[0].name.as_deref(), Some("Apple Inc"));
+
+        assert_eq!(merge("aapl", vec![merged.clone()], 1).len(), 1);
+    }
+
+    #[test]
+    fn test_rate_budget_window() {
+        let start = Instant::now();
+        let mut hits = VecDeque::new();
+        assert!(try_acquire(&mut hits, 2, start));
+        assert!(try_acquire(&mut hits, 2, start + Duration::from_secs(1)));
+        assert!(!try_acquire(&mut hits, 2, start + Duration::from_secs(2)));
+        assert!(try_acquire(&mut hits, 2, start + RATE_WINDOW));
+    }
+}
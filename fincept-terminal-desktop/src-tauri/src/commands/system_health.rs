@@ -0,0 +1,281 @@
+// System Health - one status report covering every subsystem
+//
+// What: `system_health` returns the database status, each Python venv, the
+//       WebSocket provider connections, worker pool utilization and MCP server
+//       liveness, each as a status ("n/a", "ok", "unknown", "degraded", "down")
+//       with a human-readable detail, plus an overall status.
+// Why:  Diagnosing a problem meant calling get_db_status, ws_get_all_metrics and
+//       ping_mcp_server one by one, and the Python runtime had no status at all.
+// How:  Every check reads state the subsystem already keeps and never waits on
+//       it: the database runs its usual `probe()`, venvs are checked for their
+//       interpreter on disk, and the worker pool is inspected with `try_lock` so a
+//       long-running script shows up as busy rather than blocking the report.
+//       The web server runs scripts as subprocesses and never starts the pool,
+//       so there it is reported as "n/a". The overall status is the worst
+//       subsystem status.
+
+use crate::database::status::{DbHealth, DbStatus};
+use crate::websocket::types::{ConnectionMetrics, ConnectionStatus};
+use crate::worker_pool::WorkerPoolStats;
+use crate::{MCPState, WebSocketState};
+use serde::Serialize;
+use std::path::Path;
+
+const VENVS: [&str; 2] = ["venv-numpy1", "venv-numpy2"];
+
+/// Ordered from best to worst, so the overall status is the maximum
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HealthStatus {
+    /// The subsystem is not used in this mode, e.g. the worker pool in the web server
+    #[serde(rename = "n/a")]
+    NotApplicable,
+    Ok,
+    /// Not checked yet, e.g. Python before setup has run
+    Unknown,
+    Degraded,
+    Down,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SubsystemHealth {
+    pub name: String,
+    pub status: HealthStatus,
+    pub detail: String,
+    /// Subsystem-specific fields (per-provider states, pool counters, ...)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SystemHealth {
+    pub overall: HealthStatus,
+    /// Unix milliseconds
+    pub checked_at: i64,
+    pub subsystems: Vec<SubsystemHealth>,
+}
+
+fn subsystem(name: &str, status: HealthStatus, detail: impl Into<String>) -> SubsystemHealth {
+    SubsystemHealth {
+        name: name.to_string(),
+        status,
+        detail: detail.into(),
+        data: None,
+    }
+}
+
+pub fn database_health(db: &DbStatus) -> SubsystemHealth {
+    let status = match db.state {
+        DbHealth::Healthy => HealthStatus::Ok,
+        DbHealth::Uninitialized => HealthStatus::Unknown,
+        DbHealth::Degraded => HealthStatus::Degraded,
+        DbHealth::Failed | DbHealth::RestartRequired => HealthStatus::Down,
+    };
+    let detail = match &db.reason {
+        Some(reason) => format!("{}: {}", db.state, reason),
+        None => db.state.to_string(),
+    };
+    subsystem("database", status, detail)
+}
+
+/// Whether `venv` under `base` has its interpreter; `None` before setup has run
+pub fn python_venv_health(venv: &str, base: Option<&Path>) -> SubsystemHealth {
+    let name = format!("python:{}", venv);
+    let Some(base) = base else {
+        return subsystem(&name, HealthStatus::Unknown, "Python runtime not initialized");
+    };
+    let python_exe = if cfg!(target_os = "windows") {
+        base.join(venv).join("Scripts").join("python.exe")
+    } else {
+        base.join(venv).join("bin").join("python3")
+    };
+    if python_exe.exists() {
+        subsystem(&name, HealthStatus::Ok, python_exe.to_string_lossy())
+    } else {
+        subsystem(&name, HealthStatus::Down, format!("Interpreter not found at {}", python_exe.display()))
+    }
+}
+
+/// Providers that are failing or reconnecting degrade the subsystem; an
+/// explicitly disconnected provider does not
+pub fn websocket_health(metrics: &[ConnectionMetrics]) -> SubsystemHealth {
+    let count = |status: ConnectionStatus| metrics.iter().filter(|m| m.status == status).count();
+    let connected = count(ConnectionStatus::Connected);
+    let failing = count(ConnectionStatus::Error) + count(ConnectionStatus::Reconnecting);
+
+    let status = if failing > 0 { HealthStatus::Degraded } else { HealthStatus::Ok };
+    let detail = if metrics.is_empty() {
+        "No providers configured".to_string()
+    } else {
+        format!("{} of {} providers connected, {} failing", connected, metrics.len(), failing)
+    };
+    let providers: Vec<serde_json::Value> = metrics
+        .iter()
+        .map(|m| {
+            serde_json::json!({
+                "provider": m.provider,
+                "status": m.status,
+                "last_error": m.last_error,
+            })
+        })
+        .collect();
+
+    SubsystemHealth {
+        data: Some(serde_json::json!({ "providers": providers })),
+        ..subsystem("websocket", status, detail)
+    }
+}
+
+/// `None` when this process runs scripts without the pool (the web server)
+pub fn worker_pool_health(stats: Option<&WorkerPoolStats>) -> SubsystemHealth {
+    let Some(stats) = stats else {
+        return subsystem("worker_pool", HealthStatus::NotApplicable, "Scripts run as subprocesses in web mode");
+    };
+    let (status, detail) = if !stats.initialized {
+        (HealthStatus::Down, "Worker pool not initialized".to_string())
+    } else if stats.exited_workers >= stats.workers && stats.workers > 0 {
+        (HealthStatus::Down, "All worker processes have exited".to_string())
+    } else if stats.exited_workers > 0 {
+        (HealthStatus::Degraded, format!("{} of {} worker processes have exited", stats.exited_workers, stats.workers))
+    } else {
+        (
+            HealthStatus::Ok,
+            format!("{} of {} workers busy, {} tasks queued", stats.busy_workers, stats.workers, stats.queued_tasks),
+        )
+    };
+
+    SubsystemHealth {
+        data: serde_json::to_value(stats).ok(),
+        ..subsystem("worker_pool", status, detail)
+    }
+}
+
+/// `servers` pairs each spawned server id with whether its process is running
+pub fn mcp_health(servers: &[(String, bool)]) -> SubsystemHealth {
+    let dead: Vec<&str> = servers.iter().filter(|(_, alive)| !alive).map(|(id, _)| id.as_str()).collect();
+    let status = if dead.is_empty() {
+        HealthStatus::Ok
+    } else if dead.len() == servers.len() {
+        HealthStatus::Down
+    } else {
+        HealthStatus::Degraded
+    };
+    let detail = if servers.is_empty() {
+        "No MCP servers running".to_string()
+    } else if dead.is_empty() {
+        format!("{} MCP servers running", servers.len())
+    } else {
+        format!("{} of {} MCP servers exited: {}", dead.len(), servers.len(), dead.join(", "))
+    };
+    let data = servers
+        .iter()
+        .map(|(id, alive)| serde_json::json!({ "server_id": id, "alive": alive }))
+        .collect();
+
+    SubsystemHealth {
+        data: Some(serde_json::Value::Array(data)),
+        ..subsystem("mcp", status, detail)
+    }
+}
+
+/// Combine subsystem reports; the overall status is the worst one
+pub fn aggregate(subsystems: Vec<SubsystemHealth>) -> SystemHealth {
+    SystemHealth {
+        overall: subsystems.iter().map(|s| s.status).max().unwrap_or(HealthStatus::Ok),
+        checked_at: chrono::Utc::now().timestamp_millis(),
+        subsystems,
+    }
+}
+
+/// Check every subsystem; shared by the Tauri command and the web RPC dispatcher,
+/// which passes `uses_worker_pool: false`
+pub async fn check_system_health(
+    ws_state: &WebSocketState,
+    mcp_state: &MCPState,
+    uses_worker_pool: bool,
+) -> SystemHealth {
+    let mut subsystems = vec![database_health(&crate::database::pool::probe())];
+
+    let python_base = crate::worker_pool::python_base_path();
+    subsystems.extend(VENVS.iter().map(|venv| python_venv_health(venv, python_base.as_deref())));
+
+    let metrics = ws_state.manager.read().await.get_all_metrics();
+    subsystems.push(websocket_health(&metrics));
+    let pool_stats = uses_worker_pool.then(crate::worker_pool::pool_stats);
+    subsystems.push(worker_pool_health(pool_stats.as_ref()));
+    subsystems.push(mcp_health(&crate::mcp_server_liveness(mcp_state)));
+
+    aggregate(subsystems)
+}
+
+/// Composite health of the database, Python, WebSocket, worker pool and MCP subsystems
+#[tauri::command]
+pub async fn system_health(
+    ws_state: tauri::State<'_, WebSocketState>,
+    mcp_state: tauri::State<'_, MCPState>,
+) -> Result<SystemHealth, String> {
+    Ok(check_system_health(&ws_state, &mcp_state, true).await)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_overall_is_worst_subsystem() {
+        let health = aggregate(vec![
+            subsystem("a", HealthStatus::Ok, ""),
+            subsystem("b", HealthStatus::Degraded, ""),
+            subsystem("c", HealthStatus::Unknown, ""),
+        ]);
+        assert_eq!(health.overall, HealthStatus::Degraded);
+        assert_eq!(aggregate(vec![]).overall, HealthStatus::Ok);
+    }
+
+    #[test]
+    fn test_database_states() {
+        let db = |state, reason: Option<&str>| DbStatus {
+            state,
+            reason: reason.map(str::to_string),
+            updated_at: 0,
+        };
+        assert_eq!(database_health(&db(DbHealth::Healthy, None)).status, HealthStatus::Ok);
+        let failed = database_health(&db(DbHealth::Failed, Some("disk full")));
+        assert_eq!(failed.status, HealthStatus::Down);
+        assert_eq!(failed.detail, "failed: disk full");
+    }
+
+    #[test]
+    fn test_python_venv_before_and_after_setup() {
+        assert_eq!(python_venv_health("venv-numpy2", None).status, HealthStatus::Unknown);
+
+        let missing = std::env::temp_dir().join(format!("fincept-health-{}", uuid::Uuid::new_v4()));
+        let health = python_venv_health("venv-numpy2", Some(&missing));
+        assert_eq!(health.name, "python:venv-numpy2");
+        assert_eq!(health.status, HealthStatus::Down);
+    }
+
+    #[test]
+    fn test_worker_pool_and_mcp() {
+        let stats = WorkerPoolStats {
+            initialized: true,
+            workers: 1,
+            threads_per_worker: 4,
+            busy_workers: 1,
+            ..Default::default()
+        };
+        assert_eq!(worker_pool_health(Some(&stats)).status, HealthStatus::Ok);
+        assert_eq!(worker_pool_health(Some(&WorkerPoolStats::default())).status, HealthStatus::Down);
+
+        let web = worker_pool_health(None);
+        assert_eq!(web.status, HealthStatus::NotApplicable);
+        assert_eq!(serde_json::to_value(web.status).unwrap(), "n/a");
+        assert_eq!(aggregate(vec![web, subsystem("a", HealthStatus::Ok, "")]).overall, HealthStatus::Ok);
+
+        assert_eq!(mcp_health(&[]).status, HealthStatus::Ok);
+        let servers = vec![("a".to_string(), true), ("b".to_string(), false)];
+        let health = mcp_health(&servers);
+        assert_eq!(health.status, HealthStatus::Degraded);
+        assert_eq!(health.detail, "1 of 2 MCP servers exited: b");
+    }
+}
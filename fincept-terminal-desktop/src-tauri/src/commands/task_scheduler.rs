@@ -0,0 +1,681 @@
+// Task Scheduler - run commands periodically in the background
+//
+// What: `schedule_task(command, args, schedule)` stores a command invocation with
+//       an interval ("30s", "5m", "every 2h") or a 5-field cron expression
+//       ("*/15 9-16 * * 1-5"). A background runner dispatches due tasks and
+//       records each run's status, error and duration on the task row.
+// Why:  Refreshing news, snapshotting portfolios or pulling quotes on a timer
+//       otherwise needs the UI open and a `setInterval` per panel.
+// How:  Tasks live in `scheduled_tasks` with a precomputed `next_run_at`. The
+//       runner polls once a second and hands due tasks to a `TaskDispatcher`:
+//       the web server wraps the RPC dispatcher, the desktop app a curated set
+//       of commands that need the AppHandle. `next_run_at` advances as soon as a
+//       run starts, and a task that comes due while its previous run is still in
+//       flight is skipped (counted in `skipped_count`) rather than run twice.
+//       Cron expressions are evaluated in local time.
+
+use crate::database::pool::get_pool;
+use chrono::{DateTime, Datelike, NaiveDate, TimeZone};
+use futures::future::BoxFuture;
+use once_cell::sync::{Lazy, OnceCell};
+use parking_lot::Mutex;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+const TICK: Duration = Duration::from_secs(1);
+const MIN_INTERVAL_SECS: u64 = 10;
+/// How far ahead a cron expression is searched (covers Feb 29)
+const CRON_LOOKAHEAD_DAYS: u32 = 4 * 366;
+
+/// Commands that manage the scheduler can't be scheduled themselves
+const SCHEDULER_COMMANDS: &[&str] = &[
+    "schedule_task",
+    "list_scheduled_tasks",
+    "set_scheduled_task_enabled",
+    "delete_scheduled_task",
+];
+
+// ============================================================================
+// Schedules
+// ============================================================================
+
+/// Parsed 5-field cron expression (minute hour day-of-month month day-of-week)
+#[derive(Debug, Clone, PartialEq)]
+pub struct CronSchedule {
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    months: u64,
+    weekdays: u64,
+    /// Day-of-month / day-of-week fields were restricted (not `*`)
+    days_restricted: bool,
+    weekdays_restricted: bool,
+}
+
+fn has(mask: u64, n: u32) -> bool {
+    mask & (1 << n) != 0
+}
+
+/// Bitmask of the values a cron field selects: `*`, `*/n`, `a`, `a-b`, `a-b/n`, comma lists
+fn parse_cron_field(field: &str, min: u32, max: u32) -> Result<u64, String> {
+    let invalid = || format!("Invalid cron field '{}' (expected {}-{})", field, min, max);
+    let mut mask = 0u64;
+
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => (range, step.parse::<u32>().map_err(|_| invalid())?),
+            None => (part, 1),
+        };
+        if step == 0 {
+            return Err(invalid());
+        }
+        let (start, end) = if range == "*" {
+            (min, max)
+        } else if let Some((a, b)) = range.split_once('-') {
+            (a.parse().map_err(|_| invalid())?, b.parse().map_err(|_| invalid())?)
+        } else {
+            let value: u32 = range.parse().map_err(|_| invalid())?;
+            // "5/15" means every 15 from 5
+            (value, if part.contains('/') { max } else { value })
+        };
+        if start < min || end > max || start > end {
+            return Err(invalid());
+        }
+        for n in (start..=end).step_by(step as usize) {
+            mask |= 1 << n;
+        }
+    }
+    Ok(mask)
+}
+
+impl CronSchedule {
+    pub fn parse(expr: &str) -> Result<Self, String> {
+        let fields: Vec<&str> = expr.split_whitespace().collect();
+        if fields.len() != 5 {
+            return Err(format!("Cron expression '{}' needs 5 fields", expr));
+        }
+
+        let mut weekdays = parse_cron_field(fields[4], 0, 7)?;
+        // Both 0 and 7 are Sunday
+        if has(weekdays, 7) {
+            weekdays = (weekdays | 1) & !(1 << 7);
+        }
+
+        Ok(Self {
+            minutes: parse_cron_field(fields[0], 0, 59)?,
+            hours: parse_cron_field(fields[1], 0, 23)?,
+            days: parse_cron_field(fields[2], 1, 31)?,
+            months: parse_cron_field(fields[3], 1, 12)?,
+            weekdays,
+            days_restricted: fields[2] != "*",
+            weekdays_restricted: fields[4] != "*",
+        })
+    }
+
+    /// Standard cron: when both day fields are restricted, either may match
+    fn matches_date(&self, date: NaiveDate) -> bool {
+        if !has(self.months, date.month()) {
+            return false;
+        }
+        let day = has(self.days, date.day());
+        let weekday = has(self.weekdays, date.weekday().num_days_from_sunday());
+        match (self.days_restricted, self.weekdays_restricted) {
+            (true, true) => day || weekday,
+            _ => day && weekday,
+        }
+    }
+
+    /// First matching minute strictly after `after`, in `after`'s time zone
+    pub fn next_after<Tz: TimeZone>(&self, after: &DateTime<Tz>) -> Option<DateTime<Tz>> {
+        let tz = after.timezone();
+        let start = after.naive_local();
+        let mut date = start.date();
+
+        for _ in 0..CRON_LOOKAHEAD_DAYS {
+            if self.matches_date(date) {
+                for hour in (0..24).filter(|h| has(self.hours, *h)) {
+                    for minute in (0..60).filter(|m| has(self.minutes, *m)) {
+                        let naive = date.and_hms_opt(hour, minute, 0)?;
+                        if naive <= start {
+                            continue;
+                        }
+                        // Times skipped by a DST change don't exist locally
+                        if let Some(at) = tz.from_local_datetime(&naive).earliest() {
+                            return Some(at);
+                        }
+                    }
+                }
+            }
+            date = date.succ_opt()?;
+        }
+        None
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Schedule {
+    Interval(Duration),
+    Cron(CronSchedule),
+}
+
+/// Interval like "300", "30s", "5m", "2h", "1d" or "every 5m"
+fn parse_interval(spec: &str) -> Option<Duration> {
+    let spec = spec.strip_prefix("every").unwrap_or(spec).trim();
+    let split = spec.find(|c: char| !c.is_ascii_digit()).unwrap_or(spec.len());
+    let (value, unit) = spec.split_at(split);
+    let value: u64 = value.parse().ok()?;
+    let multiplier = match unit.trim() {
+        "" | "s" | "sec" | "secs" => 1,
+        "m" | "min" | "mins" => 60,
+        "h" | "hr" | "hrs" => 3600,
+        "d" | "day" | "days" => 86400,
+        _ => return None,
+    };
+    Some(Duration::from_secs(value.checked_mul(multiplier)?))
+}
+
+impl Schedule {
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let spec = spec.trim().to_lowercase();
+        if spec.split_whitespace().count() == 5 {
+            return CronSchedule::parse(&spec).map(Schedule::Cron);
+        }
+        let interval = parse_interval(&spec)
+            .ok_or_else(|| format!("Invalid schedule '{}': use an interval like '5m' or a cron expression", spec))?;
+        if interval.as_secs() < MIN_INTERVAL_SECS {
+            return Err(format!("Interval must be at least {} seconds", MIN_INTERVAL_SECS));
+        }
+        Ok(Schedule::Interval(interval))
+    }
+
+    /// Next run (Unix millis) after `after_ms`; None if a cron expression never matches
+    pub fn next_run(&self, after_ms: i64) -> Option<i64> {
+        match self {
+            Schedule::Interval(interval) => Some(after_ms + interval.as_millis() as i64),
+            Schedule::Cron(cron) => {
+                let after = chrono::Local.timestamp_millis_opt(after_ms).single()?;
+                cron.next_after(&after).map(|at| at.timestamp_millis())
+            }
+        }
+    }
+}
+
+// ============================================================================
+// Storage
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ScheduledTask {
+    pub id: i64,
+    pub command: String,
+    pub args: Value,
+    pub schedule: String,
+    pub enabled: bool,
+    /// Unix millis of the next run (None when disabled or the schedule never matches)
+    pub next_run_at: Option<i64>,
+    pub last_run_at: Option<i64>,
+    /// "success" or "error"
+    pub last_status: Option<String>,
+    pub last_error: Option<String>,
+    pub last_duration_ms: Option<i64>,
+    pub run_count: i64,
+    /// Runs skipped because the previous run was still in flight
+    pub skipped_count: i64,
+    /// A run is in flight right now
+    pub running: bool,
+    pub created_at: String,
+}
+
+const TASK_COLUMNS: &str = "id, command, args, schedule, enabled, next_run_at, last_run_at, last_status,
+     last_error, last_duration_ms, run_count, skipped_count, created_at";
+
+fn task_from_row(row: &rusqlite::Row) -> rusqlite::Result<ScheduledTask> {
+    let id: i64 = row.get(0)?;
+    let args: String = row.get(2)?;
+    Ok(ScheduledTask {
+        id,
+        command: row.get(1)?,
+        args: serde_json::from_str(&args).unwrap_or(Value::Null),
+        schedule: row.get(3)?,
+        enabled: row.get::<_, i64>(4)? != 0,
+        next_run_at: row.get(5)?,
+        last_run_at: row.get(6)?,
+        last_status: row.get(7)?,
+        last_error: row.get(8)?,
+        last_duration_ms: row.get(9)?,
+        run_count: row.get(10)?,
+        skipped_count: row.get(11)?,
+        running: IN_FLIGHT.lock().contains(&id),
+        created_at: row.get(12)?,
+    })
+}
+
+fn insert_task(conn: &Connection, command: &str, args: &Value, schedule: &str, next_run_at: Option<i64>) -> rusqlite::Result<i64> {
+    conn.execute(
+        "INSERT INTO scheduled_tasks (command, args, schedule, enabled, next_run_at) VALUES (?1, ?2, ?3, 1, ?4)",
+        params![command, args.to_string(), schedule, next_run_at],
+    )?;
+    Ok(conn.last_insert_rowid())
+}
+
+fn get_task(conn: &Connection, id: i64) -> rusqlite::Result<Option<ScheduledTask>> {
+    conn.query_row(
+        &format!("SELECT {} FROM scheduled_tasks WHERE id = ?1", TASK_COLUMNS),
+        params![id],
+        task_from_row,
+    )
+    .optional()
+}
+
+fn list_tasks(conn: &Connection) -> rusqlite::Result<Vec<ScheduledTask>> {
+    let mut stmt = conn.prepare(&format!("SELECT {} FROM scheduled_tasks ORDER BY id", TASK_COLUMNS))?;
+    let tasks = stmt.query_map([], task_from_row)?.collect();
+    tasks
+}
+
+fn due_tasks(conn: &Connection, now: i64) -> rusqlite::Result<Vec<ScheduledTask>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM scheduled_tasks WHERE enabled = 1 AND next_run_at IS NOT NULL AND next_run_at <= ?1
+         ORDER BY next_run_at, id",
+        TASK_COLUMNS
+    ))?;
+    let tasks = stmt.query_map(params![now], task_from_row)?.collect();
+    tasks
+}
+
+fn set_enabled(conn: &Connection, id: i64, enabled: bool, next_run_at: Option<i64>) -> rusqlite::Result<usize> {
+    conn.execute(
+        "UPDATE scheduled_tasks SET enabled = ?2, next_run_at = ?3 WHERE id = ?1",
+        params![id, enabled, next_run_at],
+    )
+}
+
+fn set_next_run(conn: &Connection, id: i64, next_run_at: Option<i64>, skipped: bool) -> rusqlite::Result<usize> {
+    conn.execute(
+        "UPDATE scheduled_tasks SET next_run_at = ?2, skipped_count = skipped_count + ?3 WHERE id = ?1",
+        params![id, next_run_at, skipped as i64],
+    )
+}
+
+fn record_run(conn: &Connection, run: &TaskRun) -> rusqlite::Result<usize> {
+    conn.execute(
+        "UPDATE scheduled_tasks
+         SET last_run_at = ?2, last_status = ?3, last_error = ?4, last_duration_ms = ?5, run_count = run_count + 1
+         WHERE id = ?1",
+        params![
+            run.task_id,
+            run.started_at,
+            if run.success { "success" } else { "error" },
+            run.error,
+            run.duration_ms,
+        ],
+    )
+}
+
+fn delete_task(conn: &Connection, id: i64) -> rusqlite::Result<bool> {
+    Ok(conn.execute("DELETE FROM scheduled_tasks WHERE id = ?1", params![id])? > 0)
+}
+
+fn with_conn<T>(f: impl FnOnce(&Connection) -> rusqlite::Result<T>) -> Result<T, String> {
+    let pool = get_pool().map_err(|e| e.to_string())?;
+    let conn = pool.get().map_err(|e| e.to_string())?;
+    f(&conn).map_err(|e| e.to_string())
+}
+
+// ============================================================================
+// Runner
+// ============================================================================
+
+/// Result of one task run, passed to `TaskDispatcher::completed`
+#[derive(Debug, Clone, Serialize)]
+pub struct TaskRun {
+    pub task_id: i64,
+    pub command: String,
+    pub success: bool,
+    pub result: Option<Value>,
+    pub error: Option<String>,
+    /// Unix millis
+    pub started_at: i64,
+    pub duration_ms: i64,
+}
+
+/// Executes scheduled commands on behalf of the runner
+pub trait TaskDispatcher: Send + Sync {
+    /// Whether `command` can be dispatched
+    fn supports(&self, command: &str) -> bool;
+
+    fn dispatch(&self, command: String, args: Value) -> BoxFuture<'static, Result<Value, String>>;
+
+    /// Called after every run (e.g. to notify the frontend)
+    fn completed(&self, _run: &TaskRun) {}
+}
+
+static DISPATCHER: OnceCell<Arc<dyn TaskDispatcher>> = OnceCell::new();
+static IN_FLIGHT: Lazy<Mutex<HashSet<i64>>> = Lazy::new(|| Mutex::new(HashSet::new()));
+/// The polling loop, kept so `stop` can end it
+static RUNNER: Lazy<Mutex<Option<tauri::async_runtime::JoinHandle<()>>>> = Lazy::new(|| Mutex::new(None));
+
+fn now_ms() -> i64 {
+    chrono::Utc::now().timestamp_millis()
+}
+
+async fn run_task(dispatcher: Arc<dyn TaskDispatcher>, task: ScheduledTask) {
+    let started_at = now_ms();
+    let started = Instant::now();
+    // Tasks stored before a command was withdrawn from scheduling don't run either
+    let result = if dispatcher.supports(&task.command) {
+        dispatcher.dispatch(task.command.clone(), task.args.clone()).await
+    } else {
+        Err(format!("'{}' can't be scheduled in this mode", task.command))
+    };
+    IN_FLIGHT.lock().remove(&task.id);
+
+    let run = TaskRun {
+        task_id: task.id,
+        command: task.command,
+        success: result.is_ok(),
+        error: result.as_ref().err().cloned(),
+        result: result.ok(),
+        started_at,
+        duration_ms: started.elapsed().as_millis() as i64,
+    };
+    if let Some(error) = &run.error {
+        tracing::warn!(task_id = run.task_id, command = %run.command, error = %error, "Scheduled task failed");
+    }
+    if let Err(e) = with_conn(|conn| record_run(conn, &run)) {
+        tracing::warn!(task_id = run.task_id, error = %e, "Failed to record scheduled task run");
+    }
+    dispatcher.completed(&run);
+}
+
+/// Start every task that is due; skip those whose previous run is in flight
+fn run_due_tasks(dispatcher: &Arc<dyn TaskDispatcher>) -> Result<(), String> {
+    let now = now_ms();
+    for task in with_conn(|conn| due_tasks(conn, now))? {
+        let next_run_at = Schedule::parse(&task.schedule).ok().and_then(|s| s.next_run(now));
+        let skipped = !IN_FLIGHT.lock().insert(task.id);
+        with_conn(|conn| set_next_run(conn, task.id, next_run_at, skipped))?;
+
+        if skipped {
+            tracing::debug!(task_id = task.id, command = %task.command, "Previous run still in flight, skipping");
+            continue;
+        }
+        tauri::async_runtime::spawn(run_task(dispatcher.clone(), task));
+    }
+    Ok(())
+}
+
+/// Start the runner with the dispatcher for this mode (call once at startup)
+pub fn start(dispatcher: Arc<dyn TaskDispatcher>) {
+    if DISPATCHER.set(dispatcher.clone()).is_err() {
+        return;
+    }
+
+    let runner = tauri::async_runtime::spawn(async move {
+        loop {
+            if let Err(e) = run_due_tasks(&dispatcher) {
+                tracing::warn!(error = %e, "Scheduled task poll failed");
+            }
+            tokio::time::sleep(TICK).await;
+        }
+    });
+    *RUNNER.lock() = Some(runner);
+}
+
+/// Stop polling for due tasks (on shutdown); runs already in flight are left to finish
+pub fn stop() {
+    if let Some(runner) = RUNNER.lock().take() {
+        runner.abort();
+    }
+}
+
+// ============================================================================
+// Operations (shared by Tauri commands and the web RPC)
+// ============================================================================
+
+pub fn create_task(command: &str, args: Option<Value>, schedule: &str) -> Result<ScheduledTask, String> {
+    let command = command.trim();
+    if command.is_empty() {
+        return Err("Command is required".to_string());
+    }
+    if SCHEDULER_COMMANDS.contains(&command) {
+        return Err(format!("'{}' can't be scheduled", command));
+    }
+    if let Some(dispatcher) = DISPATCHER.get() {
+        if !dispatcher.supports(command) {
+            return Err(format!("'{}' can't be scheduled in this mode", command));
+        }
+    }
+    let args = args.unwrap_or_else(|| Value::Object(Default::default()));
+    if !args.is_object() {
+        return Err("Args must be a JSON object".to_string());
+    }
+
+    let schedule = schedule.trim();
+    let next_run_at = Schedule::parse(schedule)?.next_run(now_ms());
+    with_conn(|conn| {
+        let id = insert_task(conn, command, &args, schedule, next_run_at)?;
+        get_task(conn, id)
+    })?
+    .ok_or_else(|| "Scheduled task not found after insert".to_string())
+}
+
+pub fn get_tasks() -> Result<Vec<ScheduledTask>, String> {
+    with_conn(list_tasks)
+}
+
+/// Enable or disable a task; enabling schedules the next run from now
+pub fn update_enabled(id: i64, enabled: bool) -> Result<ScheduledTask, String> {
+    let task = with_conn(|conn| get_task(conn, id))?.ok_or_else(|| format!("Scheduled task {} not found", id))?;
+    let next_run_at = if enabled {
+        Schedule::parse(&task.schedule)?.next_run(now_ms())
+    } else {
+        None
+    };
+    with_conn(|conn| {
+        set_enabled(conn, id, enabled, next_run_at)?;
+        get_task(conn, id)
+    })?
+    .ok_or_else(|| format!("Scheduled task {} not found", id))
+}
+
+pub fn remove_task(id: i64) -> Result<bool, String> {
+    with_conn(|conn| delete_task(conn, id))
+}
+
+// ============================================================================
+// Desktop dispatcher
+// ============================================================================
+
+/// Argument by camelCase or snake_case name
+fn arg<'a>(args: &'a Value, camel: &str, snake: &str) -> Option<&'a Value> {
+    args.get(camel).or_else(|| args.get(snake)).filter(|v| !v.is_null())
+}
+
+fn to_value<T: Serialize>(result: Result<T, String>) -> Result<Value, String> {
+    result.and_then(|v| serde_json::to_value(v).map_err(|e| e.to_string()))
+}
+
+/// Commands the desktop runner can call directly
+const DESKTOP_COMMANDS: &[&str] = &[
+    "force_refresh_news",
+    "get_market_quotes",
+    "get_portfolio_summary",
+    "get_consolidated_positions",
+];
+
+/// Dispatches the desktop commands worth scheduling; emits `scheduled_task_completed`
+pub struct DesktopDispatcher {
+    app: tauri::AppHandle,
+}
+
+impl DesktopDispatcher {
+    pub fn new(app: tauri::AppHandle) -> Self {
+        Self { app }
+    }
+}
+
+impl TaskDispatcher for DesktopDispatcher {
+    fn supports(&self, command: &str) -> bool {
+        DESKTOP_COMMANDS.contains(&command)
+    }
+
+    fn dispatch(&self, command: String, args: Value) -> BoxFuture<'static, Result<Value, String>> {
+        let app = self.app.clone();
+        Box::pin(async move {
+            match command.as_str() {
+                "force_refresh_news" => to_value(super::news::force_refresh_news().await),
+                "get_market_quotes" => {
+                    let symbols: Vec<String> = arg(&args, "symbols", "symbols")
+                        .map(|v| serde_json::from_value(v.clone()))
+                        .transpose()
+                        .map_err(|e| format!("Invalid symbols: {}", e))?
+                        .ok_or_else(|| "Missing 'symbols' argument".to_string())?;
+                    to_value(super::market_data::get_market_quotes(app, symbols, None).await)
+                }
+                "get_portfolio_summary" => {
+                    let portfolio_id = arg(&args, "portfolioId", "portfolio_id")
+                        .and_then(|v| v.as_str())
+                        .ok_or_else(|| "Missing 'portfolioId' argument".to_string())?
+                        .to_string();
+                    to_value(super::portfolio_management::get_portfolio_summary(app, portfolio_id).await)
+                }
+                "get_consolidated_positions" => {
+                    let owner = arg(&args, "owner", "owner").and_then(|v| v.as_str()).map(String::from);
+                    to_value(super::paper_consolidation::get_consolidated_positions(owner).await)
+                }
+                other => Err(format!("'{}' can't be scheduled in the desktop app", other)),
+            }
+        })
+    }
+
+    fn completed(&self, run: &TaskRun) {
+        use tauri::Emitter;
+        let _ = self.app.emit("scheduled_task_completed", run);
+    }
+}
+
+// ============================================================================
+// Tauri commands
+// ============================================================================
+
+/// Schedule `command` with `args` on an interval ("5m", "every 1h") or cron expression
+#[tauri::command]
+pub async fn schedule_task(command: String, args: Option<Value>, schedule: String) -> Result<ScheduledTask, String> {
+    create_task(&command, args, &schedule)
+}
+
+#[tauri::command]
+pub async fn list_scheduled_tasks() -> Result<Vec<ScheduledTask>, String> {
+    get_tasks()
+}
+
+#[tauri::command]
+pub async fn set_scheduled_task_enabled(id: i64, enabled: bool) -> Result<ScheduledTask, String> {
+    update_enabled(id, enabled)
+}
+
+#[tauri::command]
+pub async fn delete_scheduled_task(id: i64) -> Result<bool, String> {
+    remove_task(id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn utc(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    fn next(expr: &str, after: &str) -> String {
+        CronSchedule::parse(expr)
+            .unwrap()
+            .next_after(&utc(after))
+            .unwrap()
+            .to_rfc3339()
+    }
+
+    #[test]
+    fn test_parse_intervals() {
+        assert_eq!(Schedule::parse("300").unwrap(), Schedule::Interval(Duration::from_secs(300)));
+        assert_eq!(Schedule::parse("5m").unwrap(), Schedule::Interval(Duration::from_secs(300)));
+        assert_eq!(Schedule::parse("every 2h").unwrap(), Schedule::Interval(Duration::from_secs(7200)));
+        assert_eq!(Schedule::parse("1 day").unwrap(), Schedule::Interval(Duration::from_secs(86400)));
+        assert!(Schedule::parse("5s").is_err());
+        assert!(Schedule::parse("5 weeks").is_err());
+        assert!(Schedule::parse("").is_err());
+    }
+
+    #[test]
+    fn test_parse_cron_fields() {
+        assert_eq!(parse_cron_field("*/15", 0, 59).unwrap(), 1 | 1 << 15 | 1 << 30 | 1 << 45);
+        assert_eq!(parse_cron_field("1-3,5", 0, 59).unwrap(), 0b101110);
+        assert_eq!(parse_cron_field("50/5", 0, 59).unwrap(), 1 << 50 | 1 << 55);
+        assert!(parse_cron_field("60", 0, 59).is_err());
+        assert!(parse_cron_field("*/0", 0, 59).is_err());
+        assert!(parse_cron_field("5-1", 0, 59).is_err());
+        assert!(CronSchedule::parse("* * * *").is_err());
+        // 7 is Sunday
+        assert_eq!(CronSchedule::parse("0 0 * * 7").unwrap().weekdays, 1);
+    }
+
+    #[test]
+    fn test_cron_next_run() {
+        // Strictly after: a matching minute with seconds elapsed moves on
+        assert_eq!(next("*/15 * * * *", "2024-03-01T10:15:30+00:00"), "2024-03-01T10:30:00+00:00");
+        assert_eq!(next("0 9 * * *", "2024-03-01T09:00:00+00:00"), "2024-03-02T09:00:00+00:00");
+        // Weekdays only: Friday evening -> Monday morning
+        assert_eq!(next("30 9 * * 1-5", "2024-03-01T18:00:00+00:00"), "2024-03-04T09:30:00+00:00");
+        // Both day fields restricted: the 15th or any Sunday
+        assert_eq!(next("0 0 15 * 0", "2024-03-01T00:00:00+00:00"), "2024-03-03T00:00:00+00:00");
+        // Leap day is found years ahead
+        assert_eq!(next("0 0 29 2 *", "2024-03-01T00:00:00+00:00"), "2028-02-29T00:00:00+00:00");
+        assert!(CronSchedule::parse("0 0 31 2 *").unwrap().next_after(&utc("2024-03-01T00:00:00+00:00")).is_none());
+    }
+
+    #[test]
+    fn test_task_storage_and_due_selection() {
+        let conn = Connection::open_in_memory().unwrap();
+        crate::database::schema::create_schema(&conn).unwrap();
+
+        let args = serde_json::json!({"symbols": ["AAPL"]});
+        let due = insert_task(&conn, "get_market_quotes", &args, "5m", Some(1_000)).unwrap();
+        let later = insert_task(&conn, "force_refresh_news", &Value::Null, "1h", Some(5_000)).unwrap();
+
+        let ids: Vec<i64> = due_tasks(&conn, 2_000).unwrap().iter().map(|t| t.id).collect();
+        assert_eq!(ids, vec![due]);
+
+        set_next_run(&conn, due, Some(301_000), true).unwrap();
+        record_run(
+            &conn,
+            &TaskRun {
+                task_id: due,
+                command: "get_market_quotes".to_string(),
+                success: false,
+                result: None,
+                error: Some("timeout".to_string()),
+                started_at: 1_000,
+                duration_ms: 42,
+            },
+        )
+        .unwrap();
+        let task = get_task(&conn, due).unwrap().unwrap();
+        assert_eq!(task.args, args);
+        assert_eq!(task.skipped_count, 1);
+        assert_eq!(task.run_count, 1);
+        assert_eq!(task.last_status.as_deref(), Some("error"));
+        assert_eq!(task.last_error.as_deref(), Some("timeout"));
+
+        // Disabled tasks are never due
+        set_enabled(&conn, later, false, None).unwrap();
+        assert!(due_tasks(&conn, 10_000).unwrap().is_empty());
+        assert!(delete_task(&conn, later).unwrap());
+        assert_eq!(list_tasks(&conn).unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,621 @@
+// Trading Calendar - exchange sessions, holidays and time zones
+//
+// What: `is_market_open(exchange, at)` says whether an exchange is trading at an
+//       instant (and why not), `next_session(exchange)` returns the session in
+//       progress or the next one to open. Seeded exchanges: NYSE, NASDAQ, LSE,
+//       XETRA and CRYPTO (always open).
+// Why:  DAY order expiry, "is the market open" badges and end-of-session
+//       snapshots all need to know when an exchange trades, and there was no
+//       calendar to ask.
+// How:  Each exchange has regular hours in its local time, a list of full-day
+//       holidays and early closes, and a DST rule. Rows in
+//       `trading_calendar_overrides` win over the seeded data for a date: they can
+//       close a day, open one, or set an early close, which is how holidays past
+//       the seeded years or unscheduled closures get added. The DST rules (US and
+//       EU switch dates) are implemented here rather than with a tz database; all
+//       seeded sessions sit well away from the 01:00-02:00 switch times, so the
+//       local-to-UTC conversion is never ambiguous for them.
+
+use crate::database::pool::get_pool;
+use chrono::{DateTime, Datelike, Duration, FixedOffset, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Timelike, Utc, Weekday};
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// How far ahead `next_session` looks before giving up
+const SESSION_LOOKAHEAD_DAYS: i64 = 31;
+
+/// Override kinds, see `CalendarOverride::kind`
+const OVERRIDE_KINDS: &[&str] = &["closed", "open", "early_close"];
+
+/// Daylight saving rule of an exchange's time zone
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TzRule {
+    Utc,
+    /// America/New_York: second Sunday of March to first Sunday of November
+    UsEastern,
+    /// Europe/London: last Sunday of March to last Sunday of October
+    London,
+    /// Europe/Berlin: same switch instants as London
+    CentralEurope,
+}
+
+impl TzRule {
+    pub fn name(self) -> &'static str {
+        match self {
+            TzRule::Utc => "UTC",
+            TzRule::UsEastern => "America/New_York",
+            TzRule::London => "Europe/London",
+            TzRule::CentralEurope => "Europe/Berlin",
+        }
+    }
+
+    fn standard_offset_secs(self) -> i32 {
+        match self {
+            TzRule::Utc | TzRule::London => 0,
+            TzRule::UsEastern => -5 * 3600,
+            TzRule::CentralEurope => 3600,
+        }
+    }
+
+    fn in_dst(self, utc: NaiveDateTime) -> bool {
+        let year = utc.year();
+        let (start, end) = match self {
+            TzRule::Utc => return false,
+            // 02:00 local standard time, 02:00 local daylight time
+            TzRule::UsEastern => (at_hour(nth_sunday(year, 3, 2), 7), at_hour(nth_sunday(year, 11, 1), 6)),
+            // 01:00 UTC both ways
+            TzRule::London | TzRule::CentralEurope => (at_hour(last_sunday(year, 3), 1), at_hour(last_sunday(year, 10), 1)),
+        };
+        utc >= start && utc < end
+    }
+
+    /// UTC offset in effect at `utc`
+    pub fn offset_at(self, utc: DateTime<Utc>) -> FixedOffset {
+        let dst = if self.in_dst(utc.naive_utc()) { 3600 } else { 0 };
+        FixedOffset::east_opt(self.standard_offset_secs() + dst).expect("offset within a day")
+    }
+
+    /// UTC instant of a local wall-clock time
+    fn to_utc(self, local: NaiveDateTime) -> DateTime<Utc> {
+        let guess = local - Duration::seconds(self.standard_offset_secs() as i64);
+        let offset = self.offset_at(Utc.from_utc_datetime(&guess));
+        Utc.from_utc_datetime(&(local - Duration::seconds(offset.local_minus_utc() as i64)))
+    }
+
+    fn local_date(self, utc: DateTime<Utc>) -> NaiveDate {
+        utc.with_timezone(&self.offset_at(utc)).date_naive()
+    }
+}
+
+fn at_hour(date: NaiveDate, hour: u32) -> NaiveDateTime {
+    date.and_hms_opt(hour, 0, 0).expect("valid hour")
+}
+
+fn first_sunday_from(date: NaiveDate) -> NaiveDate {
+    date + Duration::days(((7 - date.weekday().num_days_from_sunday()) % 7) as i64)
+}
+
+fn nth_sunday(year: i32, month: u32, n: i64) -> NaiveDate {
+    let first = NaiveDate::from_ymd_opt(year, month, 1).expect("valid month");
+    first_sunday_from(first) + Duration::weeks(n - 1)
+}
+
+/// Last Sunday of a 31-day month
+fn last_sunday(year: i32, month: u32) -> NaiveDate {
+    first_sunday_from(NaiveDate::from_ymd_opt(year, month, 25).expect("valid month"))
+}
+
+/// Seeded calendar of one exchange
+#[derive(Debug)]
+pub struct Exchange {
+    pub code: &'static str,
+    pub name: &'static str,
+    pub tz: TzRule,
+    /// Minutes after local midnight; a close of 1440 is the next midnight
+    open: u32,
+    close: u32,
+    early_close: u32,
+    weekends_closed: bool,
+    holidays: &'static [&'static str],
+    early_closes: &'static [&'static str],
+}
+
+const US_HOLIDAYS: &[&str] = &[
+    "2025-01-01", "2025-01-09", "2025-01-20", "2025-02-17", "2025-04-18", "2025-05-26", "2025-06-19",
+    "2025-07-04", "2025-09-01", "2025-11-27", "2025-12-25",
+    "2026-01-01", "2026-01-19", "2026-02-16", "2026-04-03", "2026-05-25", "2026-06-19", "2026-07-03",
+    "2026-09-07", "2026-11-26", "2026-12-25",
+    "2027-01-01", "2027-01-18", "2027-02-15", "2027-03-26", "2027-05-31", "2027-06-18", "2027-07-05",
+    "2027-09-06", "2027-11-25", "2027-12-24",
+];
+const US_EARLY_CLOSES: &[&str] = &["2025-07-03", "2025-11-28", "2025-12-24", "2026-11-27", "2026-12-24", "2027-11-26"];
+
+const LSE_HOLIDAYS: &[&str] = &[
+    "2025-01-01", "2025-04-18", "2025-04-21", "2025-05-05", "2025-05-26", "2025-08-25", "2025-12-25", "2025-12-26",
+    "2026-01-01", "2026-04-03", "2026-04-06", "2026-05-04", "2026-05-25", "2026-08-31", "2026-12-25", "2026-12-28",
+    "2027-01-01", "2027-03-26", "2027-03-29", "2027-05-03", "2027-05-31", "2027-08-30", "2027-12-27", "2027-12-28",
+];
+const LSE_EARLY_CLOSES: &[&str] = &["2025-12-24", "2025-12-31", "2026-12-24", "2026-12-31", "2027-12-24", "2027-12-31"];
+
+const XETRA_HOLIDAYS: &[&str] = &[
+    "2025-01-01", "2025-04-18", "2025-04-21", "2025-05-01", "2025-12-24", "2025-12-25", "2025-12-26", "2025-12-31",
+    "2026-01-01", "2026-04-03", "2026-04-06", "2026-05-01", "2026-12-24", "2026-12-25", "2026-12-31",
+    "2027-01-01", "2027-03-26", "2027-03-29", "2027-12-24", "2027-12-31",
+];
+
+static EXCHANGES: &[Exchange] = &[
+    Exchange {
+        code: "NYSE",
+        name: "New York Stock Exchange",
+        tz: TzRule::UsEastern,
+        open: 9 * 60 + 30,
+        close: 16 * 60,
+        early_close: 13 * 60,
+        weekends_closed: true,
+        holidays: US_HOLIDAYS,
+        early_closes: US_EARLY_CLOSES,
+    },
+    Exchange {
+        code: "NASDAQ",
+        name: "Nasdaq",
+        tz: TzRule::UsEastern,
+        open: 9 * 60 + 30,
+        close: 16 * 60,
+        early_close: 13 * 60,
+        weekends_closed: true,
+        holidays: US_HOLIDAYS,
+        early_closes: US_EARLY_CLOSES,
+    },
+    Exchange {
+        code: "LSE",
+        name: "London Stock Exchange",
+        tz: TzRule::London,
+        open: 8 * 60,
+        close: 16 * 60 + 30,
+        early_close: 12 * 60 + 30,
+        weekends_closed: true,
+        holidays: LSE_HOLIDAYS,
+        early_closes: LSE_EARLY_CLOSES,
+    },
+    Exchange {
+        code: "XETRA",
+        name: "Xetra (Deutsche Börse)",
+        tz: TzRule::CentralEurope,
+        open: 9 * 60,
+        close: 17 * 60 + 30,
+        early_close: 14 * 60,
+        weekends_closed: true,
+        holidays: XETRA_HOLIDAYS,
+        early_closes: &[],
+    },
+    Exchange {
+        code: "CRYPTO",
+        name: "Crypto (24/7)",
+        tz: TzRule::Utc,
+        open: 0,
+        close: 24 * 60,
+        early_close: 24 * 60,
+        weekends_closed: false,
+        holidays: &[],
+        early_closes: &[],
+    },
+];
+
+/// Seeded exchange by code, case-insensitive
+pub fn exchange(code: &str) -> Result<&'static Exchange, String> {
+    EXCHANGES
+        .iter()
+        .find(|e| e.code.eq_ignore_ascii_case(code.trim()))
+        .ok_or_else(|| {
+            let known: Vec<&str> = EXCHANGES.iter().map(|e| e.code).collect();
+            format!("Unknown exchange '{}'. Known exchanges: {}", code, known.join(", "))
+        })
+}
+
+/// A date-specific change to an exchange's seeded calendar
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CalendarOverride {
+    pub exchange: String,
+    /// Exchange-local date, YYYY-MM-DD
+    pub date: String,
+    /// "closed", "open" (regular hours) or "early_close"
+    pub kind: String,
+    /// Local close time "HH:MM" for early_close; defaults to the exchange's early close
+    pub close_time: Option<String>,
+    pub note: Option<String>,
+}
+
+/// Trading hours of one exchange day
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Session {
+    pub exchange: String,
+    /// Exchange-local date
+    pub date: String,
+    pub open_at: DateTime<Utc>,
+    pub close_at: DateTime<Utc>,
+    pub early_close: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MarketStatus {
+    pub exchange: String,
+    pub timezone: &'static str,
+    pub at: DateTime<Utc>,
+    /// Exchange-local wall-clock time of `at`
+    pub local_time: String,
+    pub open: bool,
+    /// Why the market is closed: "weekend", "holiday", "before_open", "after_close"
+    /// or the note of a closing override
+    pub reason: Option<String>,
+    /// The day's session, if the exchange trades that day
+    pub session: Option<Session>,
+}
+
+type Overrides = HashMap<NaiveDate, CalendarOverride>;
+
+fn parse_date(date: &str) -> Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(date.trim(), "%Y-%m-%d").map_err(|_| format!("Invalid date '{}', expected YYYY-MM-DD", date))
+}
+
+fn parse_minutes(time: &str) -> Result<u32, String> {
+    let time = NaiveTime::parse_from_str(time.trim(), "%H:%M").map_err(|_| format!("Invalid time '{}', expected HH:MM", time))?;
+    Ok(time.hour() * 60 + time.minute())
+}
+
+impl Exchange {
+    fn session(&self, date: NaiveDate, close: u32) -> Session {
+        let midnight = at_hour(date, 0);
+        Session {
+            exchange: self.code.to_string(),
+            date: date.format("%Y-%m-%d").to_string(),
+            open_at: self.tz.to_utc(midnight + Duration::minutes(self.open as i64)),
+            close_at: self.tz.to_utc(midnight + Duration::minutes(close as i64)),
+            early_close: close < self.close,
+        }
+    }
+
+    /// The session on `date`, or why there is none
+    fn day_schedule(&self, overrides: &Overrides, date: NaiveDate) -> Result<Session, String> {
+        if let Some(o) = overrides.get(&date) {
+            return match o.kind.as_str() {
+                "closed" => Err(o.note.clone().unwrap_or_else(|| "closed".to_string())),
+                "early_close" => {
+                    let close = o.close_time.as_deref().map(parse_minutes).transpose()?.unwrap_or(self.early_close);
+                    Ok(self.session(date, close))
+                }
+                _ => Ok(self.session(date, self.close)),
+            };
+        }
+
+        let day = date.format("%Y-%m-%d").to_string();
+        if self.weekends_closed && matches!(date.weekday(), Weekday::Sat | Weekday::Sun) {
+            Err("weekend".to_string())
+        } else if self.holidays.contains(&day.as_str()) {
+            Err("holiday".to_string())
+        } else if self.early_closes.contains(&day.as_str()) {
+            Ok(self.session(date, self.early_close))
+        } else {
+            Ok(self.session(date, self.close))
+        }
+    }
+
+    pub fn status_at(&self, overrides: &Overrides, at: DateTime<Utc>) -> MarketStatus {
+        let offset = self.tz.offset_at(at);
+        let date = self.tz.local_date(at);
+        let (open, reason, session) = match self.day_schedule(overrides, date) {
+            Ok(session) if at < session.open_at => (false, Some("before_open".to_string()), Some(session)),
+            Ok(session) if at >= session.close_at => (false, Some("after_close".to_string()), Some(session)),
+            Ok(session) => (true, None, Some(session)),
+            Err(reason) => (false, Some(reason), None),
+        };
+
+        MarketStatus {
+            exchange: self.code.to_string(),
+            timezone: self.tz.name(),
+            at,
+            local_time: at.with_timezone(&offset).to_rfc3339(),
+            open,
+            reason,
+            session,
+        }
+    }
+
+    /// The session in progress at `after`, or the next one to open
+    pub fn next_session(&self, overrides: &Overrides, after: DateTime<Utc>) -> Result<Session, String> {
+        let start = self.tz.local_date(after);
+        (0..=SESSION_LOOKAHEAD_DAYS)
+            .filter_map(|days| self.day_schedule(overrides, start + Duration::days(days)).ok())
+            .find(|session| session.close_at > after)
+            .ok_or_else(|| format!("{} has no session in the next {} days", self.code, SESSION_LOOKAHEAD_DAYS))
+    }
+}
+
+// ============================================================================
+// Overrides storage
+// ============================================================================
+
+fn load_overrides(conn: &Connection, exchange: Option<&str>) -> rusqlite::Result<Vec<CalendarOverride>> {
+    let mut stmt = conn.prepare(
+        "SELECT exchange, date, kind, close_time, note FROM trading_calendar_overrides
+         WHERE ?1 IS NULL OR exchange = ?1 ORDER BY exchange, date",
+    )?;
+    let overrides = stmt
+        .query_map(params![exchange], |row| {
+            Ok(CalendarOverride {
+                exchange: row.get(0)?,
+                date: row.get(1)?,
+                kind: row.get(2)?,
+                close_time: row.get(3)?,
+                note: row.get(4)?,
+            })
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(overrides)
+}
+
+/// Overrides for one exchange; without a database the seeded calendar still answers
+fn overrides_for(exchange: &Exchange) -> Overrides {
+    let loaded = get_pool().and_then(|pool| {
+        let conn = pool.get()?;
+        Ok(load_overrides(&conn, Some(exchange.code))?)
+    });
+    match loaded {
+        Ok(rows) => rows
+            .into_iter()
+            .filter_map(|o| NaiveDate::parse_from_str(&o.date, "%Y-%m-%d").ok().map(|d| (d, o)))
+            .collect(),
+        Err(e) => {
+            tracing::warn!(exchange = exchange.code, error = %e, "Trading calendar overrides unavailable");
+            Overrides::new()
+        }
+    }
+}
+
+fn save_override(conn: &Connection, o: CalendarOverride) -> Result<CalendarOverride, String> {
+    let exchange = exchange(&o.exchange)?;
+    let date = parse_date(&o.date)?;
+    if !OVERRIDE_KINDS.contains(&o.kind.as_str()) {
+        return Err(format!("Invalid kind '{}'. Expected one of: {}", o.kind, OVERRIDE_KINDS.join(", ")));
+    }
+    let close_time = match (o.kind.as_str(), o.close_time.as_deref()) {
+        ("early_close", Some(time)) => {
+            let minutes = parse_minutes(time)?;
+            if minutes <= exchange.open || minutes >= exchange.close {
+                return Err(format!("close_time {} is outside {}'s regular hours", time, exchange.code));
+            }
+            Some(format!("{:02}:{:02}", minutes / 60, minutes % 60))
+        }
+        (_, Some(_)) => return Err("close_time only applies to early_close overrides".to_string()),
+        (_, None) => None,
+    };
+
+    let saved = CalendarOverride {
+        exchange: exchange.code.to_string(),
+        date: date.format("%Y-%m-%d").to_string(),
+        kind: o.kind,
+        close_time,
+        note: o.note.filter(|n| !n.trim().is_empty()),
+    };
+    conn.execute(
+        "INSERT INTO trading_calendar_overrides (exchange, date, kind, close_time, note)
+         VALUES (?1, ?2, ?3, ?4, ?5)
+         ON CONFLICT(exchange, date) DO UPDATE SET
+             kind = excluded.kind, close_time = excluded.close_time, note = excluded.note",
+        params![saved.exchange, saved.date, saved.kind, saved.close_time, saved.note],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(saved)
+}
+
+// ============================================================================
+// Entry points shared by the Tauri commands and the web RPC dispatcher
+// ============================================================================
+
+fn parse_instant(value: Option<&str>, name: &str) -> Result<DateTime<Utc>, String> {
+    match value {
+        Some(s) => DateTime::parse_from_rfc3339(s.trim())
+            .map(|t| t.with_timezone(&Utc))
+            .map_err(|_| format!("Invalid '{}' timestamp '{}', expected RFC 3339", name, s)),
+        None => Ok(Utc::now()),
+    }
+}
+
+/// Whether `exchange` is trading at `at` (RFC 3339, default now)
+pub fn market_status(exchange_code: &str, at: Option<&str>) -> Result<MarketStatus, String> {
+    let exchange = exchange(exchange_code)?;
+    let at = parse_instant(at, "at")?;
+    Ok(exchange.status_at(&overrides_for(exchange), at))
+}
+
+/// Session in progress at `after` (RFC 3339, default now) or the next one
+pub fn upcoming_session(exchange_code: &str, after: Option<&str>) -> Result<Session, String> {
+    let exchange = exchange(exchange_code)?;
+    let after = parse_instant(after, "after")?;
+    exchange.next_session(&overrides_for(exchange), after)
+}
+
+pub fn set_override(o: CalendarOverride) -> Result<CalendarOverride, String> {
+    let pool = get_pool().map_err(|e| e.to_string())?;
+    let conn = pool.get().map_err(|e| e.to_string())?;
+    save_override(&conn, o)
+}
+
+pub fn remove_override(exchange_code: &str, date: &str) -> Result<bool, String> {
+    let exchange = exchange(exchange_code)?;
+    let date = parse_date(date)?;
+    let pool = get_pool().map_err(|e| e.to_string())?;
+    let conn = pool.get().map_err(|e| e.to_string())?;
+    conn.execute(
+        "DELETE FROM trading_calendar_overrides WHERE exchange = ?1 AND date = ?2",
+        params![exchange.code, date.format("%Y-%m-%d").to_string()],
+    )
+    .map(|n| n > 0)
+    .map_err(|e| e.to_string())
+}
+
+pub fn get_overrides(exchange_code: Option<&str>) -> Result<Vec<CalendarOverride>, String> {
+    let code = exchange_code.map(exchange).transpose()?.map(|e| e.code);
+    let pool = get_pool().map_err(|e| e.to_string())?;
+    let conn = pool.get().map_err(|e| e.to_string())?;
+    load_overrides(&conn, code).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn is_market_open(exchange: String, at: Option<String>) -> Result<MarketStatus, String> {
+    market_status(&exchange, at.as_deref())
+}
+
+#[tauri::command]
+pub async fn next_session(exchange: String, after: Option<String>) -> Result<Session, String> {
+    upcoming_session(&exchange, after.as_deref())
+}
+
+/// Close, open or shorten one exchange day; replaces any override for that date
+#[tauri::command]
+pub async fn set_calendar_override(
+    exchange: String,
+    date: String,
+    kind: String,
+    close_time: Option<String>,
+    note: Option<String>,
+) -> Result<CalendarOverride, String> {
+    set_override(CalendarOverride { exchange, date, kind, close_time, note })
+}
+
+#[tauri::command]
+pub async fn delete_calendar_override(exchange: String, date: String) -> Result<bool, String> {
+    remove_override(&exchange, &date)
+}
+
+#[tauri::command]
+pub async fn list_calendar_overrides(exchange: Option<String>) -> Result<Vec<CalendarOverride>, String> {
+    get_overrides(exchange.as_deref())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utc(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    fn nyse() -> &'static Exchange {
+        exchange("nyse").unwrap()
+    }
+
+    #[test]
+    fn test_dst_switches() {
+        // US: 2026-03-08 07:00 UTC and 2026-11-01 06:00 UTC
+        assert_eq!(TzRule::UsEastern.offset_at(utc("2026-03-08T06:59:00Z")).local_minus_utc(), -5 * 3600);
+        assert_eq!(TzRule::UsEastern.offset_at(utc("2026-03-08T07:00:00Z")).local_minus_utc(), -4 * 3600);
+        assert_eq!(TzRule::UsEastern.offset_at(utc("2026-11-01T06:00:00Z")).local_minus_utc(), -5 * 3600);
+        // EU: 2026-03-29 and 2026-10-25 at 01:00 UTC
+        assert_eq!(TzRule::London.offset_at(utc("2026-03-29T01:00:00Z")).local_minus_utc(), 3600);
+        assert_eq!(TzRule::CentralEurope.offset_at(utc("2026-10-25T00:59:00Z")).local_minus_utc(), 2 * 3600);
+        assert_eq!(TzRule::CentralEurope.offset_at(utc("2026-10-25T01:00:00Z")).local_minus_utc(), 3600);
+    }
+
+    #[test]
+    fn test_nyse_regular_day_in_both_offsets() {
+        let none = Overrides::new();
+        // Summer: 09:30 EDT = 13:30 UTC
+        let status = nyse().status_at(&none, utc("2026-07-15T13:30:00Z"));
+        assert!(status.open);
+        assert_eq!(status.session.unwrap().close_at, utc("2026-07-15T20:00:00Z"));
+        // Winter: 09:30 EST = 14:30 UTC
+        let status = nyse().status_at(&none, utc("2026-01-15T14:00:00Z"));
+        assert!(!status.open);
+        assert_eq!(status.reason.as_deref(), Some("before_open"));
+        assert!(nyse().status_at(&none, utc("2026-01-15T14:30:00Z")).open);
+    }
+
+    #[test]
+    fn test_closed_days_and_early_close() {
+        let none = Overrides::new();
+        let status = nyse().status_at(&none, utc("2026-10-17T15:00:00Z"));
+        assert_eq!(status.reason.as_deref(), Some("weekend"));
+        let status = nyse().status_at(&none, utc("2026-11-26T15:00:00Z"));
+        assert_eq!(status.reason.as_deref(), Some("holiday"));
+
+        // Day after Thanksgiving closes at 13:00 EST
+        let status = nyse().status_at(&none, utc("2026-11-27T18:30:00Z"));
+        assert!(!status.open);
+        assert_eq!(status.reason.as_deref(), Some("after_close"));
+        assert!(status.session.unwrap().early_close);
+    }
+
+    #[test]
+    fn test_next_session_skips_holiday_weekend() {
+        let none = Overrides::new();
+        // Friday 2026-04-03 is Good Friday: after Thursday's close, next is Monday
+        let session = nyse().next_session(&none, utc("2026-04-02T21:00:00Z")).unwrap();
+        assert_eq!(session.date, "2026-04-06");
+        assert_eq!(session.open_at, utc("2026-04-06T13:30:00Z"));
+        // While open, the current session is returned
+        let session = nyse().next_session(&none, utc("2026-04-06T15:00:00Z")).unwrap();
+        assert_eq!(session.date, "2026-04-06");
+    }
+
+    #[test]
+    fn test_overrides_win_over_seeded_calendar() {
+        let date = |d: &str| NaiveDate::parse_from_str(d, "%Y-%m-%d").unwrap();
+        let entry = |d: &str, kind: &str, close_time: Option<&str>| {
+            (
+                date(d),
+                CalendarOverride {
+                    exchange: "NYSE".to_string(),
+                    date: d.to_string(),
+                    kind: kind.to_string(),
+                    close_time: close_time.map(str::to_string),
+                    note: Some("Test".to_string()),
+                },
+            )
+        };
+        let overrides: Overrides = [
+            entry("2026-10-19", "closed", None),
+            entry("2026-10-20", "early_close", Some("12:00")),
+        ]
+        .into_iter()
+        .collect();
+
+        let status = nyse().status_at(&overrides, utc("2026-10-19T15:00:00Z"));
+        assert_eq!(status.reason.as_deref(), Some("Test"));
+        let session = nyse().next_session(&overrides, utc("2026-10-17T12:00:00Z")).unwrap();
+        assert_eq!(session.date, "2026-10-20");
+        assert_eq!(session.close_at, utc("2026-10-20T16:00:00Z"));
+    }
+
+    #[test]
+    fn test_crypto_always_open() {
+        let crypto = exchange("CRYPTO").unwrap();
+        let status = crypto.status_at(&Overrides::new(), utc("2026-10-18T23:59:00Z"));
+        assert!(status.open);
+        assert_eq!(status.session.unwrap().close_at, utc("2026-10-19T00:00:00Z"));
+    }
+
+    #[test]
+    fn test_save_override_validates_and_upserts() {
+        let conn = Connection::open_in_memory().unwrap();
+        crate::database::schema::create_schema(&conn).unwrap();
+        let o = |kind: &str, close_time: Option<&str>| CalendarOverride {
+            exchange: "lse".to_string(),
+            date: "2026-12-23".to_string(),
+            kind: kind.to_string(),
+            close_time: close_time.map(str::to_string),
+            note: None,
+        };
+
+        assert!(save_override(&conn, o("holiday", None)).is_err());
+        assert!(save_override(&conn, o("closed", Some("12:00"))).is_err());
+        assert!(save_override(&conn, o("early_close", Some("18:00"))).is_err());
+
+        assert_eq!(save_override(&conn, o("closed", None)).unwrap().exchange, "LSE");
+        save_override(&conn, o("early_close", Some("12:30"))).unwrap();
+        let stored = load_overrides(&conn, Some("LSE")).unwrap();
+        assert_eq!(stored.len(), 1);
+        assert_eq!(stored[0].kind, "early_close");
+        assert_eq!(stored[0].close_time.as_deref(), Some("12:30"));
+    }
+}
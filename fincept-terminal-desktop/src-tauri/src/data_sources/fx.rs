@@ -0,0 +1,143 @@
+// FX rates - currency conversion for multi-currency portfolios
+//
+// What: `get_rate(base, quote, fetch)` returns how many units of `quote` one unit of
+//       `base` buys, cached in memory for `FX_CACHE_TTL_SECS`.
+// Why:  Portfolios have a currency, but holdings are priced in whatever currency their
+//       exchange trades in; summing them unconverted mis-values the portfolio.
+// How:  Rates come from Yahoo FX pairs (`EURUSD=X`) via the yfinance quote script.
+//       The caller supplies the fetch function so desktop (AppHandle script paths) and
+//       web mode share the logic. Minor units Yahoo reports for some exchanges (GBp,
+//       ZAc, ILA) are mapped to their major currency plus a scale factor.
+
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::future::Future;
+
+/// How long a fetched rate is reused before asking Yahoo again
+pub const FX_CACHE_TTL_SECS: i64 = 15 * 60;
+
+/// (base, quote) in major units -> (rate, fetched at unix seconds)
+static RATE_CACHE: Lazy<RwLock<HashMap<(String, String), (f64, i64)>>> =
+    Lazy::new(|| RwLock::new(HashMap::new()));
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FxRate {
+    pub base: String,
+    pub quote: String,
+    /// Units of `quote` per one unit of `base`
+    pub rate: f64,
+    /// Unix seconds when the underlying rate was fetched
+    pub as_of: i64,
+    pub cached: bool,
+}
+
+/// Map a currency code to its major currency and the size of one unit in it
+///
+/// `"GBp"` (pence) -> `("GBP", 0.01)`, `"usd"` -> `("USD", 1.0)`.
+pub fn normalize_currency(code: &str) -> Result<(String, f64), String> {
+    let code = code.trim();
+    match code {
+        "GBp" | "GBX" => return Ok(("GBP".to_string(), 0.01)),
+        "ZAc" | "ZAC" => return Ok(("ZAR".to_string(), 0.01)),
+        "ILA" => return Ok(("ILS".to_string(), 0.01)),
+        _ => {}
+    }
+    if code.len() == 3 && code.chars().all(|c| c.is_ascii_alphabetic()) {
+        Ok((code.to_ascii_uppercase(), 1.0))
+    } else {
+        Err(format!("Invalid currency code '{}'", code))
+    }
+}
+
+/// Yahoo symbol for a currency pair
+fn pair_symbol(base: &str, quote: &str) -> String {
+    format!("{}{}=X", base, quote)
+}
+
+fn cached_rate(base: &str, quote: &str, now: i64) -> Option<(f64, i64)> {
+    let cache = RATE_CACHE.read();
+    cache
+        .get(&(base.to_string(), quote.to_string()))
+        .copied()
+        .filter(|(_, fetched_at)| now - fetched_at < FX_CACHE_TTL_SECS)
+}
+
+/// Rate from `base` to `quote`, using `fetch(yahoo_symbol)` on a cache miss
+pub async fn get_rate<F, Fut>(base: &str, quote: &str, fetch: F) -> Result<FxRate, String>
+where
+    F: FnOnce(String) -> Fut,
+    Fut: Future<Output = Result<f64, String>>,
+{
+    let (base_major, base_scale) = normalize_currency(base)?;
+    let (quote_major, quote_scale) = normalize_currency(quote)?;
+    let now = chrono::Utc::now().timestamp();
+
+    let (major_rate, as_of, cached) = if base_major == quote_major {
+        (1.0, now, false)
+    } else if let Some((rate, fetched_at)) = cached_rate(&base_major, &quote_major, now) {
+        (rate, fetched_at, true)
+    } else {
+        let rate = fetch(pair_symbol(&base_major, &quote_major))
+            .await
+            .map_err(|e| format!("No FX rate for {}/{}: {}", base_major, quote_major, e))?;
+        if !rate.is_finite() || rate <= 0.0 {
+            return Err(format!("Invalid FX rate for {}/{}: {}", base_major, quote_major, rate));
+        }
+        RATE_CACHE.write().insert((base_major, quote_major), (rate, now));
+        (rate, now, false)
+    };
+
+    Ok(FxRate {
+        base: base.trim().to_string(),
+        quote: quote.trim().to_string(),
+        rate: major_rate * base_scale / quote_scale,
+        as_of,
+        cached,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn test_normalize_currency() {
+        assert_eq!(normalize_currency("usd"), Ok(("USD".to_string(), 1.0)));
+        assert_eq!(normalize_currency("GBp"), Ok(("GBP".to_string(), 0.01)));
+        assert_eq!(normalize_currency("GBP"), Ok(("GBP".to_string(), 1.0)));
+        assert!(normalize_currency("EURO").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_rate_is_cached_and_scaled() {
+        let calls = AtomicUsize::new(0);
+        let fetch = |symbol: String| {
+            calls.fetch_add(1, Ordering::SeqCst);
+            assert_eq!(symbol, "CHFSEK=X");
+            async { Ok(12.0) }
+        };
+
+        let first = get_rate("CHF", "SEK", fetch).await.unwrap();
+        assert_eq!(first.rate, 12.0);
+        assert!(!first.cached);
+
+        let second = get_rate("CHF", "SEK", fetch).await.unwrap();
+        assert!(second.cached);
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        // Same currency never fetches; minor units scale the rate
+        let pence = get_rate("GBp", "GBP", |_| async { Err("unused".to_string()) }).await.unwrap();
+        assert!((pence.rate - 0.01).abs() < 1e-12);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_failure_is_reported() {
+        let err = get_rate("NOK", "DKK", |_| async { Err("timeout".to_string()) })
+            .await
+            .unwrap_err();
+        assert_eq!(err, "No FX rate for NOK/DKK: timeout");
+    }
+}
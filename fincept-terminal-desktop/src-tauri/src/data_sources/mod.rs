@@ -1,2 +1,3 @@
 // Data sources module - modular architecture for different market data providers
 pub mod yfinance;
+pub mod fx;
@@ -3,8 +3,12 @@
 
 use serde::{Deserialize, Serialize};
 use anyhow::{Result, Context};
+use std::collections::{BTreeSet, HashMap};
+use std::future::Future;
 use std::process::Command;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use futures::stream::{self, StreamExt};
+use crate::utils::retry::{classify_stderr, retry_async, RetryPolicy};
 
 // Windows-specific imports to hide console windows
 #[cfg(target_os = "windows")]
@@ -13,6 +17,47 @@ use std::os::windows::process::CommandExt;
 #[cfg(target_os = "windows")]
 const CREATE_NO_WINDOW: u32 = 0x08000000;
 
+/// Create a Python command with the user's network settings (proxy) applied
+fn python_command(python_path: &Path) -> Command {
+    let mut cmd = Command::new(python_path);
+    crate::utils::http::HttpClientConfig::load().apply_to_command(&mut cmd);
+
+    // Hide console window on Windows
+    #[cfg(target_os = "windows")]
+    cmd.creation_flags(CREATE_NO_WINDOW);
+
+    cmd
+}
+
+/// Run one yfinance script action and return its stdout
+///
+/// Yahoo rate-limits (429) and flaky 5xx/timeouts show up in the script's stderr;
+/// those attempts are retried with backoff, anything else fails immediately.
+async fn run_script(python_path: &Path, script_path: &Path, args: &[&str]) -> std::result::Result<String, String> {
+    let policy = RetryPolicy::load();
+    let action = args.first().copied().unwrap_or("");
+    let fetch = retry_async(&policy, |e: &String| classify_stderr(e), move || async move {
+        let mut cmd = python_command(python_path);
+        cmd.arg(script_path).args(args);
+
+        // Async process so concurrent fetches (e.g. multi-symbol history) don't block a worker.
+        // kill_on_drop: a cancelled request (utils::cancellation) kills the Python process.
+        let output = tokio::process::Command::from(cmd)
+            .kill_on_drop(true)
+            .output()
+            .await
+            .map_err(|e| format!("Failed to execute Python script: {}", e))?;
+
+        if !output.status.success() {
+            let error = String::from_utf8_lossy(&output.stderr);
+            return Err(crate::utils::python_errors::python_failure(&error));
+        }
+
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    });
+    crate::utils::timing::timed_async("yfinance", action, fetch).await
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct QuoteData {
     pub symbol: String,
@@ -25,6 +70,9 @@ pub struct QuoteData {
     pub open: Option<f64>,
     pub previous_close: Option<f64>,
     pub timestamp: i64,
+    /// Currency the price is quoted in, as reported by Yahoo (may be a minor unit like "GBp")
+    #[serde(default)]
+    pub currency: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -37,6 +85,169 @@ pub struct HistoricalData {
     pub close: f64,
     pub volume: u64,
     pub adj_close: f64,
+    /// Close as traded, before the dividend adjustment applied to OHLC (absent in cached candles)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub raw_close: Option<f64>,
+}
+
+/// Intervals accepted by yfinance's `history()`
+pub const HISTORICAL_INTERVALS: [&str; 13] = [
+    "1m", "2m", "5m", "15m", "30m", "60m", "90m", "1h", "1d", "5d", "1wk", "1mo", "3mo",
+];
+
+/// Parallel script invocations for multi-symbol history
+const MULTI_HISTORICAL_CONCURRENCY: usize = 4;
+
+/// Historical candles for several symbols, with per-symbol failures
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MultiHistoricalData {
+    pub data: HashMap<String, Vec<HistoricalData>>,
+    /// symbol -> error for symbols that could not be fetched
+    pub errors: HashMap<String, String>,
+    /// Common timestamp index when alignment was requested; every series in
+    /// `data` then has exactly one candle per entry, in the same order
+    pub index: Option<Vec<i64>>,
+}
+
+/// Validate an interval string, defaulting to daily
+pub fn normalize_interval(interval: Option<&str>) -> std::result::Result<String, String> {
+    let interval = interval.map(str::trim).filter(|i| !i.is_empty()).unwrap_or("1d");
+    if HISTORICAL_INTERVALS.contains(&interval) {
+        Ok(interval.to_string())
+    } else {
+        Err(format!(
+            "Unsupported interval '{}'. Expected one of: {}",
+            interval,
+            HISTORICAL_INTERVALS.join(", ")
+        ))
+    }
+}
+
+/// Fetch history for many symbols with bounded concurrency
+///
+/// Duplicate symbols are fetched once. Failures and empty series are reported in
+/// `errors` instead of failing the whole request.
+async fn fetch_historical_multi<F, Fut>(symbols: Vec<String>, interval: &str, align: bool, fetch: F) -> MultiHistoricalData
+where
+    F: Fn(String) -> Fut,
+    Fut: Future<Output = (String, std::result::Result<Vec<HistoricalData>, String>)>,
+{
+    let unique: BTreeSet<String> = symbols
+        .into_iter()
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .collect();
+
+    let results: Vec<_> = stream::iter(unique.into_iter().map(fetch))
+        .buffer_unordered(MULTI_HISTORICAL_CONCURRENCY)
+        .collect()
+        .await;
+
+    let mut out = MultiHistoricalData::default();
+    for (symbol, result) in results {
+        match result {
+            Ok(candles) if !candles.is_empty() => {
+                out.data.insert(symbol, candles);
+            }
+            Ok(_) => {
+                out.errors.insert(symbol, "No historical data returned".to_string());
+            }
+            Err(e) => {
+                out.errors.insert(symbol, e);
+            }
+        }
+    }
+
+    if align {
+        out.index = Some(align_to_common_index(&mut out.data, interval));
+    }
+    out
+}
+
+/// Key used to match candles across symbols
+///
+/// Daily and longer bars are stamped at local midnight of each exchange, so they
+/// are matched on the nearest UTC day rather than the exact second.
+fn alignment_key(timestamp: i64, interval: &str) -> i64 {
+    const DAY: i64 = 86_400;
+    match interval {
+        "1d" | "5d" | "1wk" | "1mo" | "3mo" => (timestamp + DAY / 2).div_euclid(DAY) * DAY,
+        _ => timestamp,
+    }
+}
+
+/// Restrict every series to the keys present in all of them
+///
+/// Returns the common index (sorted ascending); each series is rewritten to one
+/// candle per index entry, in index order.
+pub fn align_to_common_index(data: &mut HashMap<String, Vec<HistoricalData>>, interval: &str) -> Vec<i64> {
+    let mut common: Option<BTreeSet<i64>> = None;
+    for candles in data.values() {
+        let keys: BTreeSet<i64> = candles.iter().map(|c| alignment_key(c.timestamp, interval)).collect();
+        common = Some(match common {
+            Some(existing) => existing.intersection(&keys).copied().collect(),
+            None => keys,
+        });
+    }
+    let index: Vec<i64> = common.unwrap_or_default().into_iter().collect();
+
+    for candles in data.values_mut() {
+        let mut by_key: HashMap<i64, HistoricalData> = HashMap::new();
+        for candle in candles.drain(..) {
+            by_key.insert(alignment_key(candle.timestamp, interval), candle);
+        }
+        *candles = index.iter().filter_map(|key| by_key.remove(key)).collect();
+    }
+
+    index
+}
+
+/// 7-day and 30-day percentage returns from daily candles (oldest first)
+///
+/// With `adjusted`, returns are computed from `adj_close`, which Yahoo back-adjusts
+/// for splits and dividends, so a period spanning a split or ex-dividend date
+/// measures what a holder actually earned. Without it, `raw_close` is used: a
+/// plain price return that ignores dividends. Candles missing either field fall
+/// back to `close`. Returns `None` with fewer than two candles.
+pub fn compute_period_returns(hist_data: &[HistoricalData], adjusted: bool) -> Option<(f64, f64)> {
+    if hist_data.len() < 2 {
+        return None;
+    }
+
+    let price = |candle: &HistoricalData| {
+        if adjusted && candle.adj_close > 0.0 {
+            candle.adj_close
+        } else if adjusted {
+            candle.close
+        } else {
+            candle.raw_close.filter(|close| *close > 0.0).unwrap_or(candle.close)
+        }
+    };
+    let pct_change = |old: f64, current: f64| {
+        if old > 0.0 {
+            ((current - old) / old) * 100.0
+        } else {
+            0.0
+        }
+    };
+
+    let current_price = price(hist_data.last()?);
+
+    // Calculate 7-day return
+    let seven_day_return = if hist_data.len() >= 7 {
+        pct_change(price(&hist_data[hist_data.len() - 7]), current_price)
+    } else {
+        0.0
+    };
+
+    // Calculate 30-day return
+    let thirty_day_return = if hist_data.len() >= 20 {
+        pct_change(price(hist_data.first()?), current_price)
+    } else {
+        0.0
+    };
+
+    Some((seven_day_return, thirty_day_return))
 }
 
 pub struct YFinanceProvider {
@@ -70,6 +281,14 @@ impl YFinanceProvider {
         }
     }
 
+    /// Rate converting `base` into `quote` currency (cached, see `data_sources::fx`)
+    pub async fn get_fx_rate(&self, base: &str, quote: &str) -> std::result::Result<super::fx::FxRate, String> {
+        super::fx::get_rate(base, quote, |symbol| async move {
+            self.fetch_quote(&symbol).await.map(|q| q.price).map_err(|e| e.to_string())
+        })
+        .await
+    }
+
     /// Fetch real-time quotes for multiple symbols
     /// Returns only successful fetches (partial success)
     pub async fn get_quotes(&self, symbols: Vec<String>) -> Vec<QuoteData> {
@@ -84,27 +303,12 @@ impl YFinanceProvider {
 
     /// Internal batch fetch method - calls Python yfinance script with multiple symbols
     async fn fetch_batch_quotes(&self, symbols: Vec<String>) -> Result<Vec<QuoteData>> {
-        let mut cmd = Command::new(&self.python_path);
-        cmd.arg(&self.script_path).arg("batch_quotes");
-
-        for symbol in symbols {
-            cmd.arg(symbol);
-        }
-
-        // Hide console window on Windows
-        #[cfg(target_os = "windows")]
-        cmd.creation_flags(CREATE_NO_WINDOW);
+        let mut args = vec!["batch_quotes"];
+        args.extend(symbols.iter().map(String::as_str));
 
-        let output = cmd
-            .output()
-            .context("Failed to execute Python script")?;
-
-        if !output.status.success() {
-            let error = String::from_utf8_lossy(&output.stderr);
-            anyhow::bail!("Python script failed: {}", error);
-        }
-
-        let json_output = String::from_utf8_lossy(&output.stdout);
+        let json_output = run_script(&self.python_path, &self.script_path, &args)
+            .await
+            .map_err(anyhow::Error::msg)?;
         let quotes: Vec<QuoteData> = serde_json::from_str(&json_output)
             .context("Failed to parse JSON from Python script")?;
 
@@ -113,25 +317,9 @@ impl YFinanceProvider {
 
     /// Internal fetch method - calls Python yfinance script
     async fn fetch_quote(&self, symbol: &str) -> Result<QuoteData> {
-        let mut cmd = Command::new(&self.python_path);
-        cmd.arg(&self.script_path)
-            .arg("quote")
-            .arg(symbol);
-
-        // Hide console window on Windows
-        #[cfg(target_os = "windows")]
-        cmd.creation_flags(CREATE_NO_WINDOW);
-
-        let output = cmd
-            .output()
-            .context("Failed to execute Python script")?;
-
-        if !output.status.success() {
-            let error = String::from_utf8_lossy(&output.stderr);
-            anyhow::bail!("Python script failed: {}", error);
-        }
-
-        let json_output = String::from_utf8_lossy(&output.stdout);
+        let json_output = run_script(&self.python_path, &self.script_path, &["quote", symbol])
+            .await
+            .map_err(anyhow::Error::msg)?;
         let quote: QuoteData = serde_json::from_str(&json_output)
             .context("Failed to parse JSON from Python script")?;
 
@@ -146,7 +334,7 @@ impl YFinanceProvider {
         start_date: &str,
         end_date: &str,
     ) -> Option<Vec<HistoricalData>> {
-        match self.fetch_historical(symbol, start_date, end_date).await {
+        match self.fetch_historical(symbol, start_date, end_date, "1d").await {
             Ok(data) => Some(data),
             Err(_e) => {
                 // Silent fail for production
@@ -156,74 +344,54 @@ impl YFinanceProvider {
     }
 
     /// Internal historical fetch method - calls Python yfinance script
-    async fn fetch_historical(
+    pub(crate) async fn fetch_historical(
         &self,
         symbol: &str,
         start_date: &str,
         end_date: &str,
+        interval: &str,
     ) -> Result<Vec<HistoricalData>> {
-        let mut cmd = Command::new(&self.python_path);
-        cmd.arg(&self.script_path)
-            .arg("historical")
-            .arg(symbol)
-            .arg(start_date)
-            .arg(end_date);
-
-        // Hide console window on Windows
-        #[cfg(target_os = "windows")]
-        cmd.creation_flags(CREATE_NO_WINDOW);
-
-        let output = cmd
-            .output()
-            .context("Failed to execute Python script")?;
-
-        if !output.status.success() {
-            let error = String::from_utf8_lossy(&output.stderr);
-            anyhow::bail!("Python script failed: {}", error);
-        }
-
-        let json_output = String::from_utf8_lossy(&output.stdout);
+        let args = ["historical", symbol, start_date, end_date, interval];
+        let json_output = run_script(&self.python_path, &self.script_path, &args)
+            .await
+            .map_err(anyhow::Error::msg)?;
         let historical: Vec<HistoricalData> = serde_json::from_str(&json_output)
             .context("Failed to parse JSON from Python script")?;
 
         Ok(historical)
     }
 
+    /// Fetch historical data for several symbols at once
+    /// Partial failures are reported per symbol; optionally aligned to a common date index
+    pub async fn get_historical_multi(
+        &self,
+        symbols: Vec<String>,
+        start_date: &str,
+        end_date: &str,
+        interval: &str,
+        align: bool,
+    ) -> MultiHistoricalData {
+        fetch_historical_multi(symbols, interval, align, move |symbol| async move {
+            let result = self
+                .fetch_historical(&symbol, start_date, end_date, interval)
+                .await
+                .map_err(|e| e.to_string());
+            (symbol, result)
+        })
+        .await
+    }
+
     /// Calculate period returns (7D, 30D) for a symbol
-    /// Uses historical data to calculate returns
-    pub async fn get_period_returns(&self, symbol: &str) -> Option<(f64, f64)> {
+    /// `adjusted` selects total return (adjusted close) over price return (close)
+    pub async fn get_period_returns(&self, symbol: &str, adjusted: bool) -> Option<(f64, f64)> {
         // Get current date and past dates
         let today = chrono::Utc::now().format("%Y-%m-%d").to_string();
-        let _seven_days_ago = (chrono::Utc::now() - chrono::Duration::days(10)).format("%Y-%m-%d").to_string();
         let thirty_days_ago = (chrono::Utc::now() - chrono::Duration::days(35)).format("%Y-%m-%d").to_string();
 
         // Fetch historical data
         let hist_data = self.get_historical(symbol, &thirty_days_ago, &today).await?;
 
-        if hist_data.len() < 2 {
-            return None;
-        }
-
-        let current_price = hist_data.last()?.close;
-
-        // Calculate 7-day return
-        let seven_day_return = if hist_data.len() >= 7 {
-            let idx = hist_data.len() - 7;
-            let old_price = hist_data.get(idx)?.close;
-            ((current_price - old_price) / old_price) * 100.0
-        } else {
-            0.0
-        };
-
-        // Calculate 30-day return
-        let thirty_day_return = if hist_data.len() >= 20 {
-            let old_price = hist_data.first()?.close;
-            ((current_price - old_price) / old_price) * 100.0
-        } else {
-            0.0
-        };
-
-        Some((seven_day_return, thirty_day_return))
+        compute_period_returns(&hist_data, adjusted)
     }
 
     /// Health check - tests if the provider is working
@@ -241,25 +409,9 @@ impl YFinanceProvider {
 
     /// Internal info fetch method - calls Python yfinance script
     async fn fetch_info(&self, symbol: &str) -> Result<serde_json::Value> {
-        let mut cmd = Command::new(&self.python_path);
-        cmd.arg(&self.script_path)
-            .arg("info")
-            .arg(symbol);
-
-        // Hide console window on Windows
-        #[cfg(target_os = "windows")]
-        cmd.creation_flags(CREATE_NO_WINDOW);
-
-        let output = cmd
-            .output()
-            .context("Failed to execute Python script")?;
-
-        if !output.status.success() {
-            let error = String::from_utf8_lossy(&output.stderr);
-            anyhow::bail!("Python script failed: {}", error);
-        }
-
-        let json_output = String::from_utf8_lossy(&output.stdout);
+        let json_output = run_script(&self.python_path, &self.script_path, &["info", symbol])
+            .await
+            .map_err(anyhow::Error::msg)?;
         let info: serde_json::Value = serde_json::from_str(&json_output)
             .context("Failed to parse JSON from Python script")?;
 
@@ -276,25 +428,9 @@ impl YFinanceProvider {
 
     /// Internal financials fetch method - calls Python yfinance script
     async fn fetch_financials(&self, symbol: &str) -> Result<serde_json::Value> {
-        let mut cmd = Command::new(&self.python_path);
-        cmd.arg(&self.script_path)
-            .arg("financials")
-            .arg(symbol);
-
-        // Hide console window on Windows
-        #[cfg(target_os = "windows")]
-        cmd.creation_flags(CREATE_NO_WINDOW);
-
-        let output = cmd
-            .output()
-            .context("Failed to execute Python script")?;
-
-        if !output.status.success() {
-            let error = String::from_utf8_lossy(&output.stderr);
-            anyhow::bail!("Python script failed: {}", error);
-        }
-
-        let json_output = String::from_utf8_lossy(&output.stdout);
+        let json_output = run_script(&self.python_path, &self.script_path, &["financials", symbol])
+            .await
+            .map_err(anyhow::Error::msg)?;
         let financials: serde_json::Value = serde_json::from_str(&json_output)
             .context("Failed to parse JSON from Python script")?;
 
@@ -368,127 +504,83 @@ impl YFinanceProviderWeb {
         let python_path = Self::get_python_path();
         let script_path = Self::get_script_path("yfinance_data.py");
 
-        let mut cmd = Command::new(&python_path);
-        cmd.arg(&script_path)
-            .arg("quote")
-            .arg(symbol);
-
-        #[cfg(target_os = "windows")]
-        cmd.creation_flags(CREATE_NO_WINDOW);
-
-        let output = cmd.output()
-            .map_err(|e| format!("Failed to execute Python script: {}", e))?;
-
-        if !output.status.success() {
-            let error = String::from_utf8_lossy(&output.stderr);
-            return Err(format!("Python script failed: {}", error));
-        }
-
-        let json_output = String::from_utf8_lossy(&output.stdout);
+        let json_output = run_script(&python_path, &script_path, &["quote", symbol]).await?;
         serde_json::from_str(&json_output)
             .map_err(|e| format!("Failed to parse JSON: {}", e))
     }
 
+    /// Rate converting `base` into `quote` currency (cached, see `data_sources::fx`)
+    pub async fn get_fx_rate(base: &str, quote: &str) -> Result<super::fx::FxRate, String> {
+        super::fx::get_rate(base, quote, |symbol| async move {
+            Self::get_quote(&symbol).await.map(|q| q.price)
+        })
+        .await
+    }
+
     /// Fetch multiple quotes
     pub async fn get_quotes(symbols: &[String]) -> Result<Vec<QuoteData>, String> {
         let python_path = Self::get_python_path();
         let script_path = Self::get_script_path("yfinance_data.py");
 
-        let mut cmd = Command::new(&python_path);
-        cmd.arg(&script_path).arg("batch_quotes");
-
-        for symbol in symbols {
-            cmd.arg(symbol);
-        }
-
-        #[cfg(target_os = "windows")]
-        cmd.creation_flags(CREATE_NO_WINDOW);
-
-        let output = cmd.output()
-            .map_err(|e| format!("Failed to execute Python script: {}", e))?;
+        let mut args = vec!["batch_quotes"];
+        args.extend(symbols.iter().map(String::as_str));
 
-        if !output.status.success() {
-            let error = String::from_utf8_lossy(&output.stderr);
-            return Err(format!("Python script failed: {}", error));
-        }
-
-        let json_output = String::from_utf8_lossy(&output.stdout);
+        let json_output = run_script(&python_path, &script_path, &args).await?;
         serde_json::from_str(&json_output)
             .map_err(|e| format!("Failed to parse JSON: {}", e))
     }
 
     /// Fetch historical data
     pub async fn get_historical(symbol: &str, start_date: &str, end_date: &str) -> Result<Vec<HistoricalData>, String> {
-        let python_path = Self::get_python_path();
-        let script_path = Self::get_script_path("yfinance_data.py");
-
-        let mut cmd = Command::new(&python_path);
-        cmd.arg(&script_path)
-            .arg("historical")
-            .arg(symbol)
-            .arg(start_date)
-            .arg(end_date);
-
-        #[cfg(target_os = "windows")]
-        cmd.creation_flags(CREATE_NO_WINDOW);
+        Self::get_historical_interval(symbol, start_date, end_date, "1d").await
+    }
 
-        let output = cmd.output()
-            .map_err(|e| format!("Failed to execute Python script: {}", e))?;
+    /// Fetch historical data for several symbols at once
+    pub async fn get_historical_multi(
+        symbols: Vec<String>,
+        start_date: &str,
+        end_date: &str,
+        interval: &str,
+        align: bool,
+    ) -> MultiHistoricalData {
+        fetch_historical_multi(symbols, interval, align, move |symbol| async move {
+            let result = Self::get_historical_interval(&symbol, start_date, end_date, interval).await;
+            (symbol, result)
+        })
+        .await
+    }
 
-        if !output.status.success() {
-            let error = String::from_utf8_lossy(&output.stderr);
-            return Err(format!("Python script failed: {}", error));
-        }
+    pub(crate) async fn get_historical_interval(
+        symbol: &str,
+        start_date: &str,
+        end_date: &str,
+        interval: &str,
+    ) -> Result<Vec<HistoricalData>, String> {
+        let python_path = Self::get_python_path();
+        let script_path = Self::get_script_path("yfinance_data.py");
 
-        let json_output = String::from_utf8_lossy(&output.stdout);
+        let args = ["historical", symbol, start_date, end_date, interval];
+        let json_output = run_script(&python_path, &script_path, &args).await?;
         serde_json::from_str(&json_output)
             .map_err(|e| format!("Failed to parse JSON: {}", e))
     }
 
     /// Get period returns (7D, 30D)
-    pub async fn get_period_returns(symbol: &str) -> Result<serde_json::Value, String> {
+    /// `adjusted` selects total return (adjusted close) over price return (close)
+    pub async fn get_period_returns(symbol: &str, adjusted: bool) -> Result<serde_json::Value, String> {
         let today = chrono::Utc::now().format("%Y-%m-%d").to_string();
         let thirty_days_ago = (chrono::Utc::now() - chrono::Duration::days(35)).format("%Y-%m-%d").to_string();
 
         let hist_data = Self::get_historical(symbol, &thirty_days_ago, &today).await?;
 
-        if hist_data.len() < 2 {
-            return Ok(serde_json::json!({
-                "symbol": symbol,
-                "seven_day": 0.0,
-                "thirty_day": 0.0
-            }));
-        }
-
-        let current_price = hist_data.last().map(|d| d.close).unwrap_or(0.0);
-
-        let seven_day_return = if hist_data.len() >= 7 {
-            let idx = hist_data.len() - 7;
-            let old_price = hist_data.get(idx).map(|d| d.close).unwrap_or(current_price);
-            if old_price > 0.0 {
-                ((current_price - old_price) / old_price) * 100.0
-            } else {
-                0.0
-            }
-        } else {
-            0.0
-        };
-
-        let thirty_day_return = if hist_data.len() >= 20 {
-            let old_price = hist_data.first().map(|d| d.close).unwrap_or(current_price);
-            if old_price > 0.0 {
-                ((current_price - old_price) / old_price) * 100.0
-            } else {
-                0.0
-            }
-        } else {
-            0.0
-        };
+        let (seven_day_return, thirty_day_return) =
+            compute_period_returns(&hist_data, adjusted).unwrap_or((0.0, 0.0));
 
         Ok(serde_json::json!({
             "symbol": symbol,
             "seven_day": seven_day_return,
-            "thirty_day": thirty_day_return
+            "thirty_day": thirty_day_return,
+            "adjusted": adjusted
         }))
     }
 
@@ -502,23 +594,7 @@ impl YFinanceProviderWeb {
         let python_path = Self::get_python_path();
         let script_path = Self::get_script_path("yfinance_data.py");
 
-        let mut cmd = Command::new(&python_path);
-        cmd.arg(&script_path)
-            .arg("info")
-            .arg(symbol);
-
-        #[cfg(target_os = "windows")]
-        cmd.creation_flags(CREATE_NO_WINDOW);
-
-        let output = cmd.output()
-            .map_err(|e| format!("Failed to execute Python script: {}", e))?;
-
-        if !output.status.success() {
-            let error = String::from_utf8_lossy(&output.stderr);
-            return Err(format!("Python script failed: {}", error));
-        }
-
-        let json_output = String::from_utf8_lossy(&output.stdout);
+        let json_output = run_script(&python_path, &script_path, &["info", symbol]).await?;
         serde_json::from_str(&json_output)
             .map_err(|e| format!("Failed to parse JSON: {}", e))
     }
@@ -528,26 +604,76 @@ impl YFinanceProviderWeb {
         let python_path = Self::get_python_path();
         let script_path = Self::get_script_path("yfinance_data.py");
 
-        let mut cmd = Command::new(&python_path);
-        cmd.arg(&script_path)
-            .arg("financials")
-            .arg(symbol);
+        let json_output = run_script(&python_path, &script_path, &["financials", symbol]).await?;
+        serde_json::from_str(&json_output)
+            .map_err(|e| format!("Failed to parse JSON: {}", e))
+    }
+}
 
-        #[cfg(target_os = "windows")]
-        cmd.creation_flags(CREATE_NO_WINDOW);
+// Provider tests removed - require AppHandle which isn't available in unit tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candle(symbol: &str, timestamp: i64, close: f64) -> HistoricalData {
+        HistoricalData {
+            symbol: symbol.to_string(),
+            timestamp,
+            open: close,
+            high: close,
+            low: close,
+            close,
+            volume: 0,
+            adj_close: close,
+            raw_close: None,
+        }
+    }
 
-        let output = cmd.output()
-            .map_err(|e| format!("Failed to execute Python script: {}", e))?;
+    #[test]
+    fn test_align_daily_series_across_timezones() {
+        // 2024-01-02/03/04 at New York midnight (05:00 UTC) vs Tokyo midnight (15:00 UTC prev day)
+        let ny = [1_704_171_600, 1_704_258_000, 1_704_344_400];
+        let tokyo = [1_704_207_600 - 86_400, 1_704_207_600];
 
-        if !output.status.success() {
-            let error = String::from_utf8_lossy(&output.stderr);
-            return Err(format!("Python script failed: {}", error));
-        }
+        let mut data = HashMap::new();
+        data.insert("AAPL".to_string(), ny.iter().map(|t| candle("AAPL", *t, 1.0)).collect());
+        data.insert("7203.T".to_string(), tokyo.iter().map(|t| candle("7203.T", *t, 2.0)).collect());
 
-        let json_output = String::from_utf8_lossy(&output.stdout);
-        serde_json::from_str(&json_output)
-            .map_err(|e| format!("Failed to parse JSON: {}", e))
+        let index = align_to_common_index(&mut data, "1d");
+
+        assert_eq!(index.len(), 2);
+        assert_eq!(data["AAPL"].len(), 2);
+        assert_eq!(data["7203.T"].len(), 2);
+        assert_eq!(data["AAPL"][0].timestamp, ny[0]);
+        assert_eq!(data["7203.T"][1].timestamp, tokyo[1]);
     }
-}
 
-// Tests removed - require AppHandle which isn't available in unit tests
+    #[test]
+    fn test_adjusted_returns_are_continuous_across_split() {
+        // 4:1 split after day 10: the traded close drops 400 -> 100, adjusted OHLC is 100 throughout
+        let hist: Vec<HistoricalData> = (0..22)
+            .map(|day| {
+                let raw = if day < 10 { 400.0 } else { 100.0 };
+                HistoricalData {
+                    raw_close: Some(raw),
+                    ..candle("AAPL", 1_704_153_600 + day * 86_400, 100.0)
+                }
+            })
+            .collect();
+
+        let (_, raw_30d) = compute_period_returns(&hist, false).unwrap();
+        let (adj_7d, adj_30d) = compute_period_returns(&hist, true).unwrap();
+
+        assert!((raw_30d + 75.0).abs() < 1e-9);
+        assert!(adj_7d.abs() < 1e-9);
+        assert!(adj_30d.abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_normalize_interval() {
+        assert_eq!(normalize_interval(None), Ok("1d".to_string()));
+        assert_eq!(normalize_interval(Some("1wk")), Ok("1wk".to_string()));
+        assert!(normalize_interval(Some("7d")).is_err());
+    }
+}
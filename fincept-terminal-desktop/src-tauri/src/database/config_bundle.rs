@@ -0,0 +1,387 @@
+// Config Bundle - export and import the app configuration as one JSON document
+//
+// What: `export_config(include_secrets)` collects settings, LLM configs and global
+//       LLM settings, data sources, WebSocket provider configs and watchlists into a
+//       versioned `ConfigBundle`; `import_config(bundle, strategy)` restores one.
+// Why:  Setting up a second machine meant re-entering every provider, key and
+//       watchlist by hand. `import_database` merges whole databases, which also
+//       carries portfolios and history the user may not want.
+// How:  Secrets (API keys and secrets, settings and data source config fields whose
+//       name looks like a key, token or password) are left out unless
+//       `include_secrets` is set. On import, a secret missing from the bundle never
+//       clears the local one. Items match by natural key (setting key, provider
+//       name, data source id, watchlist name) and conflicts follow the same
+//       `MergeStrategy` as `import_database`:
+//         skip      - keep the local item
+//         overwrite - replace it (a watchlist keeps its id; its symbols are replaced)
+//         rename    - watchlists and data sources are added as copies named
+//                     "<name> (imported)"; keyed items without a name are skipped
+//       The bundle is fully parsed and its `schema_version` checked before anything
+//       is written. Bundles from a newer app version are rejected.
+
+use super::import::{MergeStrategy, TableMergeSummary};
+use super::types::{DataSource, LLMConfig, LLMGlobalSettings, Setting, WSProviderConfig, Watchlist, WatchlistStock};
+use super::{operations, queries};
+use crate::utils::symbol::Symbol;
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// Bumped when the bundle layout changes incompatibly
+pub const CONFIG_BUNDLE_VERSION: u32 = 1;
+
+const RENAME_SUFFIX: &str = " (imported)";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WatchlistBundle {
+    pub name: String,
+    pub description: Option<String>,
+    pub color: String,
+    pub symbols: Vec<WatchlistSymbol>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WatchlistSymbol {
+    pub symbol: String,
+    pub notes: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfigBundle {
+    pub schema_version: u32,
+    #[serde(default)]
+    pub app_version: String,
+    #[serde(default)]
+    pub exported_at: String,
+    #[serde(default)]
+    pub includes_secrets: bool,
+    #[serde(default)]
+    pub settings: Vec<Setting>,
+    #[serde(default)]
+    pub llm_configs: Vec<LLMConfig>,
+    #[serde(default)]
+    pub llm_global_settings: Option<LLMGlobalSettings>,
+    #[serde(default)]
+    pub data_sources: Vec<DataSource>,
+    #[serde(default)]
+    pub ws_provider_configs: Vec<WSProviderConfig>,
+    #[serde(default)]
+    pub watchlists: Vec<WatchlistBundle>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ConfigImportSummary {
+    pub schema_version: u32,
+    pub strategy: MergeStrategy,
+    pub sections: Vec<TableMergeSummary>,
+}
+
+/// Whether a field or setting name holds a credential
+pub(crate) fn is_secret_name(name: &str) -> bool {
+    let name = name.to_lowercase().replace(['-', '_'], "");
+    ["apikey", "secret", "token", "password", "passphrase", "privatekey"]
+        .iter()
+        .any(|marker| name.contains(marker))
+}
+
+/// Remove credential fields from a data source's JSON config
+fn redact_config(config: &str) -> String {
+    match serde_json::from_str::<Value>(config) {
+        Ok(Value::Object(mut map)) => {
+            map.retain(|key, _| !is_secret_name(key));
+            Value::Object(map).to_string()
+        }
+        _ => config.to_string(),
+    }
+}
+
+/// Imported config with credential fields the bundle lacks taken from `local`
+fn restore_config_secrets(imported: &str, local: &str) -> String {
+    match (serde_json::from_str::<Value>(imported), serde_json::from_str::<Value>(local)) {
+        (Ok(Value::Object(mut imported)), Ok(Value::Object(local))) => {
+            for (key, value) in local {
+                if is_secret_name(&key) && !imported.contains_key(&key) {
+                    imported.insert(key, value);
+                }
+            }
+            Value::Object(imported).to_string()
+        }
+        _ => imported.to_string(),
+    }
+}
+
+/// Collect the current configuration
+pub fn export_config(include_secrets: bool) -> Result<ConfigBundle> {
+    let mut settings = operations::get_all_settings()?;
+    let mut llm_configs = operations::get_llm_configs()?;
+    let mut data_sources = operations::get_all_data_sources()?;
+    let mut ws_provider_configs = operations::get_ws_provider_configs()?;
+
+    if !include_secrets {
+        settings.retain(|s| !is_secret_name(&s.setting_key));
+        for config in &mut llm_configs {
+            config.api_key = None;
+        }
+        for source in &mut data_sources {
+            source.config = redact_config(&source.config);
+        }
+        for config in &mut ws_provider_configs {
+            config.api_key = None;
+            config.api_secret = None;
+        }
+    }
+    for config in &mut ws_provider_configs {
+        config.id = None;
+    }
+
+    let mut watchlists = Vec::new();
+    for watchlist in queries::get_watchlists()? {
+        let symbols = queries::get_watchlist_stocks(&watchlist.id)?
+            .into_iter()
+            .map(|s| WatchlistSymbol {
+                symbol: s.symbol,
+                notes: s.notes,
+            })
+            .collect();
+        watchlists.push(WatchlistBundle {
+            name: watchlist.name,
+            description: watchlist.description,
+            color: watchlist.color,
+            symbols,
+        });
+    }
+
+    Ok(ConfigBundle {
+        schema_version: CONFIG_BUNDLE_VERSION,
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        exported_at: chrono::Utc::now().to_rfc3339(),
+        includes_secrets: include_secrets,
+        settings,
+        llm_configs,
+        llm_global_settings: Some(operations::get_llm_global_settings()?),
+        data_sources,
+        ws_provider_configs,
+        watchlists,
+    })
+}
+
+/// Parse a bundle and check that this version can import it
+pub fn parse_bundle(bundle: Value) -> Result<ConfigBundle> {
+    let version = bundle
+        .get("schema_version")
+        .and_then(|v| v.as_u64())
+        .ok_or_else(|| anyhow::anyhow!("Not a config bundle: missing 'schema_version'"))?;
+    if version == 0 || version > CONFIG_BUNDLE_VERSION as u64 {
+        bail!(
+            "Config bundle version {} is not supported (this app reads up to version {})",
+            version,
+            CONFIG_BUNDLE_VERSION
+        );
+    }
+    serde_json::from_value(bundle).map_err(|e| anyhow::anyhow!("Invalid config bundle: {}", e))
+}
+
+/// Restore a bundle into the current configuration
+pub fn import_config(bundle: ConfigBundle, strategy: MergeStrategy) -> Result<ConfigImportSummary> {
+    let sections = vec![
+        import_settings(&bundle.settings, strategy)?,
+        import_llm(&bundle, strategy)?,
+        import_data_sources(&bundle.data_sources, strategy)?,
+        import_ws_providers(&bundle.ws_provider_configs, strategy)?,
+        import_watchlists(&bundle.watchlists, strategy)?,
+    ];
+
+    Ok(ConfigImportSummary {
+        schema_version: bundle.schema_version,
+        strategy,
+        sections,
+    })
+}
+
+fn import_settings(settings: &[Setting], strategy: MergeStrategy) -> Result<TableMergeSummary> {
+    let mut summary = TableMergeSummary::new("settings");
+    for setting in settings {
+        let exists = operations::get_setting(&setting.setting_key)?.is_some();
+        if exists && strategy != MergeStrategy::Overwrite {
+            summary.skipped += 1;
+            continue;
+        }
+        operations::save_setting(&setting.setting_key, &setting.setting_value, setting.category.as_deref())?;
+        if exists {
+            summary.overwritten += 1;
+        } else {
+            summary.inserted += 1;
+        }
+    }
+    Ok(summary)
+}
+
+fn import_llm(bundle: &ConfigBundle, strategy: MergeStrategy) -> Result<TableMergeSummary> {
+    let mut summary = TableMergeSummary::new("llm_configs");
+    let local: HashMap<String, LLMConfig> = operations::get_llm_configs()?
+        .into_iter()
+        .map(|c| (c.provider.clone(), c))
+        .collect();
+
+    for config in &bundle.llm_configs {
+        let existing = local.get(&config.provider);
+        if existing.is_some() && strategy != MergeStrategy::Overwrite {
+            summary.skipped += 1;
+            continue;
+        }
+        let mut config = config.clone();
+        if config.api_key.is_none() {
+            config.api_key = existing.and_then(|c| c.api_key.clone());
+        }
+        operations::save_llm_config(&config)?;
+        if existing.is_some() {
+            summary.overwritten += 1;
+        } else {
+            summary.inserted += 1;
+        }
+    }
+
+    // A single row that always exists locally
+    if let Some(settings) = &bundle.llm_global_settings {
+        if strategy == MergeStrategy::Overwrite {
+            operations::save_llm_global_settings(settings)?;
+            summary.overwritten += 1;
+        } else {
+            summary.skipped += 1;
+        }
+    }
+    Ok(summary)
+}
+
+fn import_data_sources(sources: &[DataSource], strategy: MergeStrategy) -> Result<TableMergeSummary> {
+    let mut summary = TableMergeSummary::new("data_sources");
+    let local: HashMap<String, DataSource> = operations::get_all_data_sources()?
+        .into_iter()
+        .map(|s| (s.id.clone(), s))
+        .collect();
+
+    for source in sources {
+        let mut source = source.clone();
+        match (local.get(&source.id), strategy) {
+            (None, _) => summary.inserted += 1,
+            (Some(_), MergeStrategy::Skip) => {
+                summary.skipped += 1;
+                continue;
+            }
+            (Some(existing), MergeStrategy::Overwrite) => {
+                source.config = restore_config_secrets(&source.config, &existing.config);
+                summary.overwritten += 1;
+            }
+            (Some(_), MergeStrategy::Rename) => {
+                source.id = uuid::Uuid::new_v4().to_string();
+                source.alias = format!("{}_imported", source.alias);
+                source.display_name.push_str(RENAME_SUFFIX);
+                summary.renamed += 1;
+            }
+        }
+        operations::save_data_source(&source)?;
+    }
+    Ok(summary)
+}
+
+fn import_ws_providers(configs: &[WSProviderConfig], strategy: MergeStrategy) -> Result<TableMergeSummary> {
+    let mut summary = TableMergeSummary::new("ws_provider_configs");
+    for config in configs {
+        let existing = operations::get_ws_provider_config(&config.provider_name)?;
+        if existing.is_some() && strategy != MergeStrategy::Overwrite {
+            summary.skipped += 1;
+            continue;
+        }
+        let mut config = config.clone();
+        if let Some(existing) = &existing {
+            config.api_key = config.api_key.or_else(|| existing.api_key.clone());
+            config.api_secret = config.api_secret.or_else(|| existing.api_secret.clone());
+        }
+        operations::save_ws_provider_config(&config)?;
+        if existing.is_some() {
+            summary.overwritten += 1;
+        } else {
+            summary.inserted += 1;
+        }
+    }
+    Ok(summary)
+}
+
+fn import_watchlists(watchlists: &[WatchlistBundle], strategy: MergeStrategy) -> Result<TableMergeSummary> {
+    let mut summary = TableMergeSummary::new("watchlists");
+    let local: HashMap<String, Watchlist> = queries::get_watchlists()?
+        .into_iter()
+        .map(|w| (w.name.clone(), w))
+        .collect();
+
+    for bundle in watchlists {
+        let target = match (local.get(&bundle.name), strategy) {
+            (None, _) => {
+                summary.inserted += 1;
+                queries::create_watchlist(&bundle.name, bundle.description.as_deref(), &bundle.color)?
+            }
+            (Some(_), MergeStrategy::Skip) => {
+                summary.skipped += 1;
+                continue;
+            }
+            (Some(existing), MergeStrategy::Overwrite) => {
+                let stocks: Vec<WatchlistStock> = queries::get_watchlist_stocks(&existing.id)?;
+                for stock in stocks {
+                    queries::remove_watchlist_stock(&existing.id, &stock.symbol)?;
+                }
+                summary.overwritten += 1;
+                existing.clone()
+            }
+            (Some(_), MergeStrategy::Rename) => {
+                summary.renamed += 1;
+                let name = format!("{}{}", bundle.name, RENAME_SUFFIX);
+                queries::create_watchlist(&name, bundle.description.as_deref(), &bundle.color)?
+            }
+        };
+
+        for symbol in &bundle.symbols {
+            let ticker = Symbol::new(&symbol.symbol)
+                .map_err(|e| anyhow::anyhow!("Watchlist '{}': {}", bundle.name, e))?;
+            queries::add_watchlist_stock(&target.id, &ticker, symbol.notes.as_deref())?;
+        }
+    }
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_secret_names() {
+        assert!(is_secret_name("fred_api_key"));
+        assert!(is_secret_name("apiSecret"));
+        assert!(is_secret_name("access-token"));
+        assert!(!is_secret_name("theme"));
+        assert!(!is_secret_name("news_refresh_interval_secs"));
+    }
+
+    #[test]
+    fn test_data_source_secrets_round_trip() {
+        let local = r#"{"host":"db.local","apiKey":"k1","password":"p"}"#;
+        let redacted = redact_config(local);
+        assert_eq!(serde_json::from_str::<Value>(&redacted).unwrap(), serde_json::json!({"host": "db.local"}));
+
+        let imported = r#"{"host":"db.remote"}"#;
+        let restored: Value = serde_json::from_str(&restore_config_secrets(imported, local)).unwrap();
+        assert_eq!(restored, serde_json::json!({"host": "db.remote", "apiKey": "k1", "password": "p"}));
+
+        assert_eq!(redact_config("not json"), "not json");
+    }
+
+    #[test]
+    fn test_bundle_version_is_checked() {
+        assert!(parse_bundle(serde_json::json!({"settings": []})).is_err());
+        assert!(parse_bundle(serde_json::json!({"schema_version": CONFIG_BUNDLE_VERSION + 1})).is_err());
+
+        let bundle = parse_bundle(serde_json::json!({"schema_version": 1})).unwrap();
+        assert!(bundle.settings.is_empty());
+        assert!(!bundle.includes_secrets);
+    }
+}
@@ -0,0 +1,241 @@
+// Database Encryption - optional SQLCipher encryption of the whole database
+//
+// What: When built with the `sqlcipher` feature and a passphrase is available, every
+//       pooled connection is opened with `PRAGMA key`. `rekey_database` changes,
+//       adds or removes the passphrase.
+// Why:  Credential fields are already encrypted individually, but some users want
+//       the whole database (portfolios, chats, notes) encrypted at rest.
+// How:  Passphrase sources, highest first: `unlock_database` (prompt in the UI),
+//       `FINCEPT_DB_PASSPHRASE`, then the OS keychain entry written by
+//       `rekey_database`. `check_key` opens the file once before the pool is built,
+//       so a wrong passphrase fails with a clear error instead of an r2d2 timeout.
+//       Rekeying uses `sqlcipher_export` into `<db>.rekeyed`, because plaintext files
+//       can't be rekeyed in place and pooled connections still hold the old key.
+//       The database then reports `restart_required`, blocking writes that would be
+//       lost, and the new file is swapped in on the next start.
+//
+// Performance: SQLCipher encrypts every page (AES-256 plus an HMAC per page). Expect
+// roughly 5-15% slower reads/writes and a slower first open (key derivation runs
+// 256k PBKDF2 iterations). Bulk caches (market data, forum) pay the most.
+
+use anyhow::{bail, Context, Result};
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
+use rusqlite::Connection;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+pub const PASSPHRASE_ENV: &str = "FINCEPT_DB_PASSPHRASE";
+#[cfg(feature = "sqlcipher")]
+const KEYCHAIN_SERVICE: &str = "fincept-terminal";
+#[cfg(feature = "sqlcipher")]
+const KEYCHAIN_USER: &str = "database";
+
+/// Passphrase supplied through `unlock_database`
+static PASSPHRASE: Lazy<RwLock<Option<String>>> = Lazy::new(|| RwLock::new(None));
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RekeyResult {
+    pub encrypted: bool,
+    /// The re-keyed copy; it replaces the database on the next start
+    pub pending_path: String,
+    pub restart_required: bool,
+    /// Whether the new passphrase was saved to the OS keychain
+    pub stored_in_keychain: bool,
+}
+
+/// Whether this build can open encrypted databases
+pub fn is_supported() -> bool {
+    cfg!(feature = "sqlcipher")
+}
+
+/// Use `passphrase` for connections opened from now on
+pub fn set_passphrase(passphrase: Option<String>) {
+    *PASSPHRASE.write() = passphrase.filter(|p| !p.is_empty());
+}
+
+/// The passphrase connections should be keyed with, if any
+pub fn current_passphrase() -> Option<String> {
+    if let Some(p) = PASSPHRASE.read().clone() {
+        return Some(p);
+    }
+    if let Some(p) = std::env::var(PASSPHRASE_ENV).ok().filter(|p| !p.is_empty()) {
+        return Some(p);
+    }
+    keychain_get()
+}
+
+/// Key a freshly opened connection; must run before any other statement
+pub fn apply_key(conn: &Connection) -> rusqlite::Result<()> {
+    if !is_supported() {
+        return Ok(());
+    }
+    match current_passphrase() {
+        Some(passphrase) => conn.pragma_update(None, "key", passphrase),
+        None => Ok(()),
+    }
+}
+
+/// Open the database once and make sure the passphrase (or lack of one) fits
+pub fn check_key(db_path: &Path) -> Result<()> {
+    if !is_supported() {
+        if std::env::var(PASSPHRASE_ENV).is_ok_and(|p| !p.is_empty()) {
+            tracing::warn!(
+                "{} is set but this build has no SQLCipher support; opening the database unencrypted",
+                PASSPHRASE_ENV
+            );
+        }
+        return Ok(());
+    }
+
+    let conn = Connection::open(db_path).context("Failed to open database")?;
+    apply_key(&conn)?;
+    match conn.query_row("SELECT count(*) FROM sqlite_master", [], |row| row.get::<_, i64>(0)) {
+        Ok(_) => Ok(()),
+        Err(e) if is_not_a_database(&e) => {
+            if current_passphrase().is_some() {
+                bail!("Wrong database passphrase (or the database is not encrypted)")
+            } else {
+                bail!("Database is encrypted; a passphrase is required (unlock_database or {})", PASSPHRASE_ENV)
+            }
+        }
+        Err(e) => Err(e).context("Failed to read database"),
+    }
+}
+
+fn is_not_a_database(error: &rusqlite::Error) -> bool {
+    matches!(
+        error,
+        rusqlite::Error::SqliteFailure(e, _) if e.code == rusqlite::ErrorCode::NotADatabase
+    )
+}
+
+fn pending_path(db_path: &Path) -> PathBuf {
+    let mut name = db_path.as_os_str().to_owned();
+    name.push(".rekeyed");
+    PathBuf::from(name)
+}
+
+/// Swap in a database re-keyed during the previous run; call before opening the pool
+pub fn apply_pending_rekey(db_path: &Path) -> Result<()> {
+    let pending = pending_path(db_path);
+    if !pending.exists() {
+        return Ok(());
+    }
+
+    for suffix in ["-wal", "-shm"] {
+        let mut sidecar = db_path.as_os_str().to_owned();
+        sidecar.push(suffix);
+        let _ = std::fs::remove_file(PathBuf::from(sidecar));
+    }
+    std::fs::rename(&pending, db_path).context("Failed to replace database with re-keyed copy")?;
+    tracing::info!(path = %db_path.display(), "Applied re-keyed database");
+    Ok(())
+}
+
+/// Export the database under `new_passphrase` (empty = decrypt) to `<db>.rekeyed`
+///
+/// `conn` must be a connection keyed with the current passphrase.
+pub fn rekey(conn: &Connection, db_path: &Path, old_passphrase: &str, new_passphrase: &str) -> Result<RekeyResult> {
+    if !is_supported() {
+        bail!("Database encryption is not available in this build (enable the 'sqlcipher' feature)");
+    }
+    if current_passphrase().unwrap_or_default() != old_passphrase {
+        bail!("Wrong database passphrase");
+    }
+    if old_passphrase == new_passphrase {
+        bail!("New passphrase must differ from the current one");
+    }
+
+    let pending = pending_path(db_path);
+    let _ = std::fs::remove_file(&pending);
+
+    let user_version: i64 = conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+    conn.execute(
+        "ATTACH DATABASE ?1 AS rekeyed KEY ?2",
+        [pending.to_string_lossy().as_ref(), new_passphrase],
+    )
+    .context("Failed to create re-keyed database")?;
+    let exported = conn
+        .query_row("SELECT sqlcipher_export('rekeyed')", [], |_| Ok(()))
+        .and_then(|_| conn.execute_batch(&format!("PRAGMA rekeyed.user_version = {}", user_version)));
+    let _ = conn.execute_batch("DETACH DATABASE rekeyed");
+    if let Err(e) = exported {
+        let _ = std::fs::remove_file(&pending);
+        return Err(e).context("Failed to export database");
+    }
+
+    let new_passphrase = (!new_passphrase.is_empty()).then(|| new_passphrase.to_string());
+    let stored_in_keychain = keychain_set(new_passphrase.as_deref());
+    set_passphrase(new_passphrase.clone());
+
+    Ok(RekeyResult {
+        encrypted: new_passphrase.is_some(),
+        pending_path: pending.display().to_string(),
+        restart_required: true,
+        stored_in_keychain,
+    })
+}
+
+#[cfg(feature = "sqlcipher")]
+fn keychain_entry() -> Option<keyring::Entry> {
+    keyring::Entry::new(KEYCHAIN_SERVICE, KEYCHAIN_USER).ok()
+}
+
+#[cfg(feature = "sqlcipher")]
+fn keychain_get() -> Option<String> {
+    keychain_entry()?.get_password().ok().filter(|p| !p.is_empty())
+}
+
+#[cfg(not(feature = "sqlcipher"))]
+fn keychain_get() -> Option<String> {
+    None
+}
+
+/// Store (or with `None`, remove) the passphrase in the OS keychain
+#[cfg(feature = "sqlcipher")]
+fn keychain_set(passphrase: Option<&str>) -> bool {
+    let Some(entry) = keychain_entry() else {
+        return false;
+    };
+    let result = match passphrase {
+        Some(p) => entry.set_password(p),
+        None => entry.delete_credential(),
+    };
+    match result {
+        Ok(()) => passphrase.is_some(),
+        Err(e) => {
+            tracing::warn!(error = %e, "Could not update database passphrase in OS keychain");
+            false
+        }
+    }
+}
+
+#[cfg(not(feature = "sqlcipher"))]
+fn keychain_set(_passphrase: Option<&str>) -> bool {
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply_pending_rekey_swaps_file() {
+        let dir = std::env::temp_dir().join(format!("fincept_rekey_test_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let db = dir.join("fincept_terminal.db");
+        std::fs::write(&db, b"old").unwrap();
+        std::fs::write(dir.join("fincept_terminal.db-wal"), b"wal").unwrap();
+        std::fs::write(pending_path(&db), b"new").unwrap();
+
+        apply_pending_rekey(&db).unwrap();
+
+        assert_eq!(std::fs::read(&db).unwrap(), b"new");
+        assert!(!pending_path(&db).exists());
+        assert!(!dir.join("fincept_terminal.db-wal").exists());
+        // Nothing pending is a no-op
+        apply_pending_rekey(&db).unwrap();
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
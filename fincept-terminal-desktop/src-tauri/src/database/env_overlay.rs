@@ -0,0 +1,149 @@
+// Environment Overlay - settings and credentials injected through env vars
+//
+// What: `get_setting` and `get_credential_by_service` consult environment variables
+//       before the database:
+//         FINCEPT_SETTING_<KEY>               -> setting <key>
+//         FINCEPT_CRED_<SERVICE>_API_KEY      -> credential field api_key
+//         FINCEPT_CRED_<SERVICE>_API_SECRET   -> api_secret
+//         FINCEPT_CRED_<SERVICE>_USERNAME     -> username
+//         FINCEPT_CRED_<SERVICE>_PASSWORD     -> password
+//         FINCEPT_CRED_<SERVICE>_ADDITIONAL_DATA -> additional_data
+//       <KEY> and <SERVICE> are upper-cased with every other non-alphanumeric
+//       character turned into `_` (`alpha_vantage` -> FINCEPT_CRED_ALPHA_VANTAGE_API_KEY).
+// Why:  Containerized web deployments get secrets from the orchestrator and want
+//       to run the same binary without writing them into the database.
+// How:  Precedence is env, then database. A set setting variable wins outright,
+//       so the database isn't read. Credential fields are overlaid one by one:
+//       each set variable replaces that field and the rest come from the database
+//       row. With no row, the set fields alone form the credential (`id: None`).
+//       Empty variables count as unset. Listings (`get_all_settings`,
+//       `get_credentials`) and exports show only what the database stores.
+
+use crate::database::types::Credential;
+
+pub const SETTING_PREFIX: &str = "FINCEPT_SETTING_";
+pub const CREDENTIAL_PREFIX: &str = "FINCEPT_CRED_";
+
+/// Env var name part for a setting key or service name
+fn env_segment(name: &str) -> String {
+    name.trim()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_uppercase() } else { '_' })
+        .collect()
+}
+
+fn env_value(name: &str) -> Option<String> {
+    std::env::var(name).ok().filter(|value| !value.is_empty())
+}
+
+/// Env var that overrides setting `key`
+pub fn setting_var(key: &str) -> String {
+    format!("{}{}", SETTING_PREFIX, env_segment(key))
+}
+
+/// Env var that overrides `field` of the credential for `service`
+pub fn credential_var(service: &str, field: &str) -> String {
+    format!("{}{}_{}", CREDENTIAL_PREFIX, env_segment(service), env_segment(field))
+}
+
+/// Setting value from the environment, if one is set
+pub fn setting(key: &str) -> Option<String> {
+    setting_from(key, env_value)
+}
+
+fn setting_from(key: &str, lookup: impl Fn(&str) -> Option<String>) -> Option<String> {
+    lookup(&setting_var(key))
+}
+
+/// Apply the environment's credential fields for `service` over the database row
+pub fn overlay_credential(service: &str, stored: Option<Credential>) -> Option<Credential> {
+    overlay_credential_from(service, stored, env_value)
+}
+
+fn overlay_credential_from(
+    service: &str,
+    stored: Option<Credential>,
+    lookup: impl Fn(&str) -> Option<String>,
+) -> Option<Credential> {
+    let field = |name: &str| lookup(&credential_var(service, name));
+    let (username, password, api_key, api_secret, additional_data) = (
+        field("username"),
+        field("password"),
+        field("api_key"),
+        field("api_secret"),
+        field("additional_data"),
+    );
+    let any_set = [&username, &password, &api_key, &api_secret, &additional_data]
+        .iter()
+        .any(|value| value.is_some());
+    if !any_set {
+        return stored;
+    }
+
+    let mut credential = stored.unwrap_or_else(|| Credential {
+        id: None,
+        service_name: service.to_string(),
+        username: None,
+        password: None,
+        api_key: None,
+        api_secret: None,
+        additional_data: None,
+        created_at: None,
+        updated_at: None,
+    });
+    credential.username = username.or(credential.username);
+    credential.password = password.or(credential.password);
+    credential.api_key = api_key.or(credential.api_key);
+    credential.api_secret = api_secret.or(credential.api_secret);
+    credential.additional_data = additional_data.or(credential.additional_data);
+    Some(credential)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn env(vars: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+        let vars: HashMap<String, String> = vars.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+        move |name| vars.get(name).cloned()
+    }
+
+    #[test]
+    fn test_variable_names() {
+        assert_eq!(setting_var("log_format"), "FINCEPT_SETTING_LOG_FORMAT");
+        assert_eq!(credential_var("alpha-vantage", "api_key"), "FINCEPT_CRED_ALPHA_VANTAGE_API_KEY");
+        assert_eq!(setting_from("log_format", env(&[("FINCEPT_SETTING_LOG_FORMAT", "json")])), Some("json".into()));
+        assert_eq!(setting_from("log_format", env(&[])), None);
+    }
+
+    #[test]
+    fn test_credential_fields_override_stored_row() {
+        let stored = Credential {
+            id: Some(3),
+            service_name: "fyers".to_string(),
+            username: Some("db-user".to_string()),
+            password: None,
+            api_key: Some("db-key".to_string()),
+            api_secret: Some("db-secret".to_string()),
+            additional_data: None,
+            created_at: None,
+            updated_at: None,
+        };
+        let vars = env(&[("FINCEPT_CRED_FYERS_API_KEY", "env-key")]);
+
+        let merged = overlay_credential_from("fyers", Some(stored.clone()), &vars).unwrap();
+        assert_eq!(merged.id, Some(3));
+        assert_eq!(merged.api_key.as_deref(), Some("env-key"));
+        assert_eq!(merged.api_secret.as_deref(), Some("db-secret"));
+        assert_eq!(merged.username.as_deref(), Some("db-user"));
+
+        // Env alone is enough; nothing set leaves the database answer untouched
+        let env_only = overlay_credential_from("fyers", None, &vars).unwrap();
+        assert_eq!((env_only.id, env_only.service_name.as_str()), (None, "fyers"));
+        assert_eq!(env_only.api_key.as_deref(), Some("env-key"));
+        assert!(overlay_credential_from("other", None, &vars).is_none());
+        let untouched = overlay_credential_from("other", Some(stored), &vars).unwrap();
+        assert_eq!(untouched.api_key.as_deref(), Some("db-key"));
+    }
+}
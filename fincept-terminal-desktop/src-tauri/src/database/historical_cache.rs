@@ -0,0 +1,337 @@
+// Historical Cache - daily/intraday candles persisted per (symbol, interval, date)
+//
+// What: `get_cached` answers a historical request from the `historical_cache`
+//       table and only asks upstream for the date ranges it has not seen yet,
+//       storing what comes back. `clear` drops a symbol's (or every) cached candle.
+// Why:  Every chart open re-ran the yfinance script for years of candles that
+//       never change, which is slow and gets the app rate limited.
+// How:  Fetched date ranges are recorded in `historical_cache_ranges`, so days
+//       without candles (weekends, holidays) count as covered and are not asked
+//       for again. Coverage stops at yesterday (UTC): today's candle is still
+//       forming and is fetched again on every request. Adjacent and overlapping
+//       ranges are merged when stored, keeping the gap computation cheap.
+
+use crate::data_sources::yfinance::HistoricalData;
+use crate::database::pool::get_pool;
+use crate::utils::symbol::Symbol;
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use rusqlite::{params, Connection};
+use serde::Serialize;
+use std::future::Future;
+
+const DATE_FORMAT: &str = "%Y-%m-%d";
+
+/// Inclusive range of dates
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DateSpan {
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+}
+
+/// Inclusive date range in the `YYYY-MM-DD` form used by the commands
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DateRange {
+    pub start: String,
+    pub end: String,
+}
+
+impl From<DateSpan> for DateRange {
+    fn from(span: DateSpan) -> Self {
+        Self {
+            start: span.start.format(DATE_FORMAT).to_string(),
+            end: span.end.format(DATE_FORMAT).to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CachedHistory {
+    pub symbol: String,
+    pub interval: String,
+    pub data: Vec<HistoricalData>,
+    /// Ranges that were missing from the cache and fetched upstream
+    pub fetched_ranges: Vec<DateRange>,
+}
+
+pub fn parse_date(raw: &str) -> Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(raw.trim(), DATE_FORMAT)
+        .map_err(|_| format!("Invalid date '{}', expected YYYY-MM-DD", raw))
+}
+
+/// Trading date a candle belongs to
+///
+/// Daily and longer candles are stamped at the exchange's local midnight, which
+/// is the previous UTC day east of Greenwich; shifting by half a day lands on
+/// the right date for every exchange.
+pub fn candle_date(timestamp: i64, interval: &str) -> NaiveDate {
+    let intraday = interval.ends_with('m') || interval.ends_with('h');
+    let timestamp = if intraday { timestamp } else { timestamp + 12 * 3600 };
+    DateTime::<Utc>::from_timestamp(timestamp, 0)
+        .unwrap_or_default()
+        .date_naive()
+}
+
+/// Parts of `from..=to` not covered by any of `covered`
+pub fn missing_ranges(covered: &[DateSpan], from: NaiveDate, to: NaiveDate) -> Vec<DateSpan> {
+    let mut covered = covered.to_vec();
+    covered.sort_by_key(|span| span.start);
+
+    let mut gaps = Vec::new();
+    let mut cursor = from;
+    for span in covered {
+        if cursor > to {
+            break;
+        }
+        if span.end < cursor {
+            continue;
+        }
+        if span.start > to {
+            break;
+        }
+        if span.start > cursor {
+            gaps.push(DateSpan { start: cursor, end: span.start - Duration::days(1) });
+        }
+        cursor = span.end + Duration::days(1);
+    }
+    if cursor <= to {
+        gaps.push(DateSpan { start: cursor, end: to });
+    }
+    gaps
+}
+
+/// Date ranges already fetched for `symbol` at `interval`
+pub fn covered_ranges(conn: &Connection, symbol: &Symbol, interval: &str) -> Result<Vec<DateSpan>> {
+    let mut stmt = conn.prepare(
+        "SELECT start_date, end_date FROM historical_cache_ranges
+         WHERE symbol = ?1 AND interval = ?2 ORDER BY start_date",
+    )?;
+    let rows = stmt.query_map(params![symbol, interval], |row| {
+        Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+    })?;
+    rows.map(|row| {
+        let (start, end) = row?;
+        Ok(DateSpan {
+            start: parse_date(&start).map_err(|e| anyhow!(e))?,
+            end: parse_date(&end).map_err(|e| anyhow!(e))?,
+        })
+    })
+    .collect()
+}
+
+/// Store `candles` and mark `span` (if any) as fetched, in one transaction
+pub fn store(
+    conn: &mut Connection,
+    symbol: &Symbol,
+    interval: &str,
+    candles: &[HistoricalData],
+    span: Option<DateSpan>,
+) -> Result<()> {
+    let existing = match span {
+        Some(_) => covered_ranges(conn, symbol, interval)?,
+        None => Vec::new(),
+    };
+    let tx = conn.transaction()?;
+    {
+        let mut insert = tx.prepare(
+            "INSERT OR REPLACE INTO historical_cache
+             (symbol, interval, date, timestamp, open, high, low, close, volume, adj_close)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+        )?;
+        for candle in candles {
+            insert.execute(params![
+                symbol,
+                interval,
+                candle_date(candle.timestamp, interval).format(DATE_FORMAT).to_string(),
+                candle.timestamp,
+                candle.open,
+                candle.high,
+                candle.low,
+                candle.close,
+                candle.volume as i64,
+                candle.adj_close,
+            ])?;
+        }
+    }
+    if let Some(span) = span {
+        // Fold every range that touches the new one into a single row
+        let mut merged = span;
+        for other in existing {
+            let touches = other.start <= span.end + Duration::days(1) && other.end + Duration::days(1) >= span.start;
+            if touches {
+                merged.start = merged.start.min(other.start);
+                merged.end = merged.end.max(other.end);
+                tx.execute(
+                    "DELETE FROM historical_cache_ranges WHERE symbol = ?1 AND interval = ?2 AND start_date = ?3",
+                    params![symbol, interval, other.start.format(DATE_FORMAT).to_string()],
+                )?;
+            }
+        }
+        let range = DateRange::from(merged);
+        tx.execute(
+            "INSERT OR REPLACE INTO historical_cache_ranges (symbol, interval, start_date, end_date, fetched_at)
+             VALUES (?1, ?2, ?3, ?4, CURRENT_TIMESTAMP)",
+            params![symbol, interval, range.start, range.end],
+        )?;
+    }
+    tx.commit()?;
+    Ok(())
+}
+
+/// Cached candles for `symbol` dated within `span`, oldest first
+pub fn load(conn: &Connection, symbol: &Symbol, interval: &str, span: DateSpan) -> Result<Vec<HistoricalData>> {
+    let range = DateRange::from(span);
+    let mut stmt = conn.prepare(
+        "SELECT timestamp, open, high, low, close, volume, adj_close FROM historical_cache
+         WHERE symbol = ?1 AND interval = ?2 AND date BETWEEN ?3 AND ?4
+         ORDER BY timestamp",
+    )?;
+    let rows = stmt.query_map(params![symbol, interval, range.start, range.end], |row| {
+        Ok(HistoricalData {
+            symbol: symbol.to_string(),
+            timestamp: row.get(0)?,
+            open: row.get(1)?,
+            high: row.get(2)?,
+            low: row.get(3)?,
+            close: row.get(4)?,
+            volume: row.get::<_, i64>(5)?.max(0) as u64,
+            adj_close: row.get(6)?,
+            raw_close: None,
+        })
+    })?;
+    Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+}
+
+/// Candles for `symbol` between `from` and `to` (inclusive), fetching only uncached ranges
+///
+/// `fetch(symbol, start, end)` is called once per gap with an exclusive `end`,
+/// as yfinance expects.
+pub async fn get_cached<F, Fut>(symbol: &str, from: &str, to: &str, interval: &str, fetch: F) -> Result<CachedHistory>
+where
+    F: Fn(String, String, String) -> Fut,
+    Fut: Future<Output = std::result::Result<Vec<HistoricalData>, String>>,
+{
+    let symbol = Symbol::new(symbol).map_err(|e| anyhow!(e))?;
+    let span = DateSpan {
+        start: parse_date(from).map_err(|e| anyhow!(e))?,
+        end: parse_date(to).map_err(|e| anyhow!(e))?,
+    };
+    if span.start > span.end {
+        return Err(anyhow!("Start date {} is after end date {}", from, to));
+    }
+
+    let covered = covered_ranges(&get_pool()?.get()?, &symbol, interval)?;
+    let gaps = missing_ranges(&covered, span.start, span.end);
+    let last_settled = Utc::now().date_naive() - Duration::days(1);
+
+    for gap in &gaps {
+        let start = gap.start.format(DATE_FORMAT).to_string();
+        let end = (gap.end + Duration::days(1)).format(DATE_FORMAT).to_string();
+        let candles = fetch(symbol.to_string(), start, end).await.map_err(|e| anyhow!(e))?;
+
+        let settled = DateSpan { start: gap.start, end: gap.end.min(last_settled) };
+        let settled = (settled.start <= settled.end).then_some(settled);
+        let pool = get_pool()?;
+        let mut conn = pool.get()?;
+        crate::database::retry::with_busy_retry(|| store(&mut conn, &symbol, interval, &candles, settled))?;
+    }
+
+    let data = load(&get_pool()?.get()?, &symbol, interval, span)?;
+    Ok(CachedHistory {
+        symbol: symbol.into_string(),
+        interval: interval.to_string(),
+        data,
+        fetched_ranges: gaps.into_iter().map(DateRange::from).collect(),
+    })
+}
+
+/// Delete cached candles and coverage for `symbol`, or for every symbol
+pub fn clear_with(conn: &Connection, symbol: Option<&Symbol>) -> Result<usize> {
+    let deleted = match symbol {
+        Some(symbol) => {
+            conn.execute("DELETE FROM historical_cache_ranges WHERE symbol = ?1", params![symbol])?;
+            conn.execute("DELETE FROM historical_cache WHERE symbol = ?1", params![symbol])?
+        }
+        None => {
+            conn.execute("DELETE FROM historical_cache_ranges", [])?;
+            conn.execute("DELETE FROM historical_cache", [])?
+        }
+    };
+    Ok(deleted)
+}
+
+/// Number of candles removed
+pub fn clear(symbol: Option<&str>) -> Result<usize> {
+    let symbol = symbol.map(Symbol::new).transpose().map_err(|e| anyhow!(e))?;
+    crate::database::pool::write(|conn| clear_with(conn, symbol.as_ref()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(raw: &str) -> NaiveDate {
+        parse_date(raw).unwrap()
+    }
+
+    fn span(start: &str, end: &str) -> DateSpan {
+        DateSpan { start: date(start), end: date(end) }
+    }
+
+    fn candle(day: &str, close: f64) -> HistoricalData {
+        // New York midnight, as yfinance stamps daily candles
+        let timestamp = date(day).and_hms_opt(4, 0, 0).unwrap().and_utc().timestamp();
+        HistoricalData {
+            symbol: "AAPL".to_string(),
+            timestamp,
+            open: close,
+            high: close,
+            low: close,
+            close,
+            volume: 100,
+            adj_close: close,
+            raw_close: None,
+        }
+    }
+
+    #[test]
+    fn test_missing_ranges_skips_covered_spans() {
+        let covered = [span("2024-01-10", "2024-01-20"), span("2024-01-01", "2024-01-05")];
+        assert_eq!(
+            missing_ranges(&covered, date("2024-01-01"), date("2024-01-31")),
+            vec![span("2024-01-06", "2024-01-09"), span("2024-01-21", "2024-01-31")]
+        );
+        assert!(missing_ranges(&covered, date("2024-01-11"), date("2024-01-15")).is_empty());
+        assert_eq!(
+            missing_ranges(&[], date("2024-01-01"), date("2024-01-01")),
+            vec![span("2024-01-01", "2024-01-01")]
+        );
+    }
+
+    #[test]
+    fn test_candle_date_uses_exchange_day() {
+        // Tokyo midnight is 15:00 UTC the day before
+        let tokyo = date("2024-03-04").and_hms_opt(0, 0, 0).unwrap().and_utc().timestamp() - 9 * 3600;
+        assert_eq!(candle_date(tokyo, "1d"), date("2024-03-04"));
+        assert_eq!(candle_date(tokyo, "1h"), date("2024-03-03"));
+    }
+
+    #[test]
+    fn test_store_merges_ranges_and_loads_by_date() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        crate::database::schema::create_schema(&conn).unwrap();
+        let symbol = Symbol::new("aapl").unwrap();
+
+        store(&mut conn, &symbol, "1d", &[candle("2024-01-02", 1.0)], Some(span("2024-01-01", "2024-01-03"))).unwrap();
+        store(&mut conn, &symbol, "1d", &[candle("2024-01-08", 2.0)], Some(span("2024-01-06", "2024-01-09"))).unwrap();
+        store(&mut conn, &symbol, "1d", &[candle("2024-01-05", 3.0)], Some(span("2024-01-04", "2024-01-05"))).unwrap();
+        assert_eq!(covered_ranges(&conn, &symbol, "1d").unwrap(), vec![span("2024-01-01", "2024-01-09")]);
+        assert!(covered_ranges(&conn, &symbol, "1h").unwrap().is_empty());
+
+        let loaded = load(&conn, &symbol, "1d", span("2024-01-03", "2024-01-09")).unwrap();
+        assert_eq!(loaded.iter().map(|c| c.close).collect::<Vec<_>>(), vec![3.0, 2.0]);
+
+        assert_eq!(clear_with(&conn, Some(&symbol)).unwrap(), 3);
+        assert!(covered_ranges(&conn, &symbol, "1d").unwrap().is_empty());
+    }
+}
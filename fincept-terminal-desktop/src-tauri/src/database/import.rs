@@ -0,0 +1,530 @@
+// Database Import - merge another terminal's watchlists and portfolios
+//
+// What: `import_database(path, strategy, groups)` attaches an external
+//       fincept_terminal.db and merges whole table groups (a parent table and the
+//       child tables that reference it) into the current database.
+// Why:  Users moving between machines or recovering from a backup want to combine
+//       data, not replace it the way a restore does.
+// How:  The merge runs in one transaction on a pooled connection; the source is
+//       attached as `import` and always detached afterwards. Only columns present
+//       in both schemas are copied, so older databases import cleanly. Parent id
+//       conflicts follow the strategy:
+//         skip      - keep the local parent, ignore the imported one and its children
+//         overwrite - replace the local parent and all its children with the imported ones
+//         rename    - import under a fresh id with " (imported)" appended to the name
+//       Child rows follow the parent's new id. A child whose own id already exists
+//       is skipped, upserted or re-keyed the same way.
+
+use anyhow::{bail, Context, Result};
+use rusqlite::types::Value;
+use rusqlite::{params_from_iter, Connection, OptionalExtension, Transaction};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+const SOURCE_SCHEMA: &str = "import";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MergeStrategy {
+    Skip,
+    Overwrite,
+    Rename,
+}
+
+impl std::str::FromStr for MergeStrategy {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "skip" => Ok(MergeStrategy::Skip),
+            "overwrite" => Ok(MergeStrategy::Overwrite),
+            "rename" => Ok(MergeStrategy::Rename),
+            other => Err(format!(
+                "Invalid strategy '{}' (expected skip, overwrite or rename)",
+                other
+            )),
+        }
+    }
+}
+
+/// A parent table plus the tables whose rows belong to it
+struct MergeGroup {
+    key: &'static str,
+    parent: &'static str,
+    name_column: &'static str,
+    /// (child table, foreign key column referencing parent.id)
+    children: &'static [(&'static str, &'static str)],
+}
+
+const MERGE_GROUPS: &[MergeGroup] = &[
+    MergeGroup {
+        key: "watchlists",
+        parent: "watchlists",
+        name_column: "name",
+        children: &[("watchlist_stocks", "watchlist_id")],
+    },
+    MergeGroup {
+        key: "portfolios",
+        parent: "portfolios",
+        name_column: "name",
+        children: &[
+            ("portfolio_assets", "portfolio_id"),
+            ("portfolio_transactions", "portfolio_id"),
+            ("portfolio_snapshots", "portfolio_id"),
+        ],
+    },
+];
+
+/// Names accepted in the `tables` selection
+pub fn mergeable_groups() -> Vec<&'static str> {
+    MERGE_GROUPS.iter().map(|g| g.key).collect()
+}
+
+/// Per-table outcome of an import
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct TableMergeSummary {
+    pub table: String,
+    pub inserted: usize,
+    pub overwritten: usize,
+    pub renamed: usize,
+    pub skipped: usize,
+}
+
+impl TableMergeSummary {
+    pub(super) fn new(table: &str) -> Self {
+        Self {
+            table: table.to_string(),
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportSummary {
+    pub source: String,
+    pub strategy: MergeStrategy,
+    pub tables: Vec<TableMergeSummary>,
+}
+
+/// Merge the selected groups (default: all) from the database at `path`
+pub fn import_database(
+    path: &Path,
+    strategy: MergeStrategy,
+    groups: Option<&[String]>,
+) -> Result<ImportSummary> {
+    if !path.is_file() {
+        bail!("Import file not found: {}", path.display());
+    }
+    let current = super::pool::get_db_path()?;
+    if let (Ok(a), Ok(b)) = (path.canonicalize(), current.canonicalize()) {
+        if a == b {
+            bail!("Cannot import the active database into itself");
+        }
+    }
+
+    let pool = super::pool::get_pool()?;
+    let mut conn = pool.get().context("Failed to get connection")?;
+    attach_and_merge(&mut conn, path, strategy, groups)
+}
+
+/// Attach `path`, merge, and detach again whatever the outcome
+fn attach_and_merge(
+    conn: &mut Connection,
+    path: &Path,
+    strategy: MergeStrategy,
+    groups: Option<&[String]>,
+) -> Result<ImportSummary> {
+    let selected = select_groups(groups)?;
+
+    conn.execute(
+        &format!("ATTACH DATABASE ?1 AS {}", SOURCE_SCHEMA),
+        [path.to_string_lossy().as_ref()],
+    )
+    .with_context(|| format!("Failed to open {}", path.display()))?;
+
+    let result = merge_attached(conn, &selected, strategy);
+
+    // Pooled connections are reused; never leave the source attached
+    if let Err(e) = conn.execute_batch(&format!("DETACH DATABASE {}", SOURCE_SCHEMA)) {
+        tracing::warn!(error = %e, "Failed to detach imported database");
+    }
+
+    result.map(|tables| ImportSummary {
+        source: path.display().to_string(),
+        strategy,
+        tables,
+    })
+}
+
+fn select_groups(groups: Option<&[String]>) -> Result<Vec<&'static MergeGroup>> {
+    let Some(keys) = groups else {
+        return Ok(MERGE_GROUPS.iter().collect());
+    };
+
+    keys.iter()
+        .map(|key| {
+            MERGE_GROUPS
+                .iter()
+                .find(|g| g.key.eq_ignore_ascii_case(key.trim()))
+                .ok_or_else(|| {
+                    anyhow::anyhow!(
+                        "Unknown table '{}' (supported: {})",
+                        key,
+                        mergeable_groups().join(", ")
+                    )
+                })
+        })
+        .collect()
+}
+
+fn merge_attached(
+    conn: &mut Connection,
+    groups: &[&MergeGroup],
+    strategy: MergeStrategy,
+) -> Result<Vec<TableMergeSummary>> {
+    let tx = conn.transaction()?;
+    let mut summaries = Vec::new();
+
+    for group in groups {
+        if !source_has_table(&tx, group.parent)? {
+            continue;
+        }
+        summaries.extend(merge_group(&tx, group, strategy)?);
+    }
+
+    tx.commit()?;
+    Ok(summaries)
+}
+
+/// A table to copy, restricted to the columns both schemas share
+struct TableCopy {
+    table: &'static str,
+    columns: Vec<String>,
+}
+
+impl TableCopy {
+    fn load(tx: &Transaction, table: &'static str) -> Result<Option<Self>> {
+        if !source_has_table(tx, table)? {
+            return Ok(None);
+        }
+        let target = table_columns(tx, "main", table)?;
+        let columns: Vec<String> = table_columns(tx, SOURCE_SCHEMA, table)?
+            .into_iter()
+            .filter(|c| target.contains(c))
+            .collect();
+        if !columns.iter().any(|c| c == "id") {
+            bail!("Table '{}' in the import has no id column", table);
+        }
+        Ok(Some(Self { table, columns }))
+    }
+
+    fn index_of(&self, column: &str) -> Option<usize> {
+        self.columns.iter().position(|c| c == column)
+    }
+
+    fn select_sql(&self, filter: Option<&str>) -> String {
+        let mut sql = format!(
+            "SELECT {} FROM {}.{}",
+            quote_columns(&self.columns),
+            SOURCE_SCHEMA,
+            self.table
+        );
+        if let Some(column) = filter {
+            sql.push_str(&format!(" WHERE \"{}\" = ?1", column));
+        }
+        sql
+    }
+
+    fn read(&self, tx: &Transaction, filter: Option<(&str, &str)>) -> Result<Vec<Vec<Value>>> {
+        let mut stmt = tx.prepare(&self.select_sql(filter.map(|(c, _)| c)))?;
+        let width = self.columns.len();
+        let map = |row: &rusqlite::Row| {
+            (0..width)
+                .map(|i| row.get::<_, Value>(i))
+                .collect::<rusqlite::Result<Vec<Value>>>()
+        };
+        let rows = match filter {
+            Some((_, value)) => stmt.query_map([value], map)?.collect::<rusqlite::Result<Vec<_>>>()?,
+            None => stmt.query_map([], map)?.collect::<rusqlite::Result<Vec<_>>>()?,
+        };
+        Ok(rows)
+    }
+
+    fn exists(&self, tx: &Transaction, id: &str) -> Result<bool> {
+        Ok(tx
+            .query_row(
+                &format!("SELECT 1 FROM main.{} WHERE id = ?1", self.table),
+                [id],
+                |_| Ok(()),
+            )
+            .optional()?
+            .is_some())
+    }
+
+    /// Insert `row`; with `upsert` an existing id is updated in place
+    fn write(&self, tx: &Transaction, row: &[Value], upsert: bool) -> Result<()> {
+        let placeholders = vec!["?"; self.columns.len()].join(", ");
+        let mut sql = format!(
+            "INSERT INTO main.{} ({}) VALUES ({})",
+            self.table,
+            quote_columns(&self.columns),
+            placeholders
+        );
+        if upsert {
+            let updates: Vec<String> = self
+                .columns
+                .iter()
+                .filter(|c| c.as_str() != "id")
+                .map(|c| format!("\"{0}\" = excluded.\"{0}\"", c))
+                .collect();
+            if updates.is_empty() {
+                sql.push_str(" ON CONFLICT(id) DO NOTHING");
+            } else {
+                sql.push_str(&format!(" ON CONFLICT(id) DO UPDATE SET {}", updates.join(", ")));
+            }
+        }
+        tx.execute(&sql, params_from_iter(row.iter()))?;
+        Ok(())
+    }
+}
+
+fn merge_group(
+    tx: &Transaction,
+    group: &MergeGroup,
+    strategy: MergeStrategy,
+) -> Result<Vec<TableMergeSummary>> {
+    let parent = TableCopy::load(tx, group.parent)?
+        .ok_or_else(|| anyhow::anyhow!("Table '{}' missing from import", group.parent))?;
+    let parent_id = parent.index_of("id").unwrap_or(0);
+    let parent_name = parent.index_of(group.name_column);
+
+    let mut children = Vec::new();
+    for (table, fk) in group.children {
+        if let Some(copy) = TableCopy::load(tx, table)? {
+            let Some(fk_index) = copy.index_of(fk) else {
+                continue;
+            };
+            children.push((copy, *fk, fk_index, TableMergeSummary::new(table)));
+        }
+    }
+
+    let mut summary = TableMergeSummary::new(group.parent);
+
+    for mut row in parent.read(tx, None)? {
+        let source_id = value_text(&row[parent_id]);
+
+        let target_id = if !parent.exists(tx, &source_id)? {
+            parent.write(tx, &row, false)?;
+            summary.inserted += 1;
+            source_id.clone()
+        } else {
+            match strategy {
+                MergeStrategy::Skip => {
+                    summary.skipped += 1;
+                    for (child, fk, _, child_summary) in children.iter_mut() {
+                        child_summary.skipped += child.read(tx, Some((*fk, source_id.as_str())))?.len();
+                    }
+                    continue;
+                }
+                MergeStrategy::Overwrite => {
+                    for (child, fk, _, _) in &children {
+                        tx.execute(
+                            &format!("DELETE FROM main.{} WHERE \"{}\" = ?1", child.table, fk),
+                            [&source_id],
+                        )?;
+                    }
+                    parent.write(tx, &row, true)?;
+                    summary.overwritten += 1;
+                    source_id.clone()
+                }
+                MergeStrategy::Rename => {
+                    let new_id = uuid::Uuid::new_v4().to_string();
+                    row[parent_id] = Value::Text(new_id.clone());
+                    if let Some(i) = parent_name {
+                        row[i] = Value::Text(format!("{} (imported)", value_text(&row[i])));
+                    }
+                    parent.write(tx, &row, false)?;
+                    summary.renamed += 1;
+                    new_id
+                }
+            }
+        };
+
+        for (child, fk, fk_index, child_summary) in children.iter_mut() {
+            let child_id = child.index_of("id").unwrap_or(0);
+            for mut child_row in child.read(tx, Some((*fk, source_id.as_str())))? {
+                child_row[*fk_index] = Value::Text(target_id.clone());
+
+                let id = value_text(&child_row[child_id]);
+                if !child.exists(tx, &id)? {
+                    child.write(tx, &child_row, false)?;
+                    child_summary.inserted += 1;
+                    continue;
+                }
+                match strategy {
+                    MergeStrategy::Skip => child_summary.skipped += 1,
+                    MergeStrategy::Overwrite => {
+                        child.write(tx, &child_row, true)?;
+                        child_summary.overwritten += 1;
+                    }
+                    MergeStrategy::Rename => {
+                        child_row[child_id] = Value::Text(uuid::Uuid::new_v4().to_string());
+                        child.write(tx, &child_row, false)?;
+                        child_summary.renamed += 1;
+                    }
+                }
+            }
+        }
+    }
+
+    let mut summaries = vec![summary];
+    summaries.extend(children.into_iter().map(|(_, _, _, s)| s));
+    Ok(summaries)
+}
+
+fn source_has_table(conn: &Connection, table: &str) -> Result<bool> {
+    Ok(conn
+        .query_row(
+            &format!(
+                "SELECT 1 FROM {}.sqlite_master WHERE type = 'table' AND name = ?1",
+                SOURCE_SCHEMA
+            ),
+            [table],
+            |_| Ok(()),
+        )
+        .optional()?
+        .is_some())
+}
+
+fn table_columns(conn: &Connection, schema: &str, table: &str) -> Result<Vec<String>> {
+    let mut stmt = conn.prepare(&format!("PRAGMA {}.table_info({})", schema, table))?;
+    let columns = stmt
+        .query_map([], |row| row.get::<_, String>(1))?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(columns)
+}
+
+fn quote_columns(columns: &[String]) -> String {
+    columns
+        .iter()
+        .map(|c| format!("\"{}\"", c))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn value_text(value: &Value) -> String {
+    match value {
+        Value::Text(s) => s.clone(),
+        Value::Integer(i) => i.to_string(),
+        Value::Real(f) => f.to_string(),
+        Value::Null | Value::Blob(_) => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn database_with(path: Option<&Path>, watchlists: &[(&str, &str)], stocks: &[(&str, &str, &str)]) -> Connection {
+        let conn = match path {
+            Some(p) => Connection::open(p).unwrap(),
+            None => Connection::open_in_memory().unwrap(),
+        };
+        crate::database::schema::create_schema(&conn).unwrap();
+        for (id, name) in watchlists {
+            conn.execute("INSERT INTO watchlists (id, name) VALUES (?1, ?2)", [id, name]).unwrap();
+        }
+        for (id, watchlist_id, symbol) in stocks {
+            conn.execute(
+                "INSERT INTO watchlist_stocks (id, watchlist_id, symbol) VALUES (?1, ?2, ?3)",
+                [id, watchlist_id, symbol],
+            )
+            .unwrap();
+        }
+        conn
+    }
+
+    fn source_file(name: &str) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(format!("fincept_import_{}_{}.db", name, std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        database_with(
+            Some(&path),
+            &[("w1", "Tech"), ("w2", "Energy")],
+            &[("s1", "w1", "AAPL"), ("s2", "w1", "MSFT"), ("s3", "w2", "XOM")],
+        );
+        path
+    }
+
+    fn count(conn: &Connection, sql: &str) -> i64 {
+        conn.query_row(sql, [], |row| row.get(0)).unwrap()
+    }
+
+    fn table<'a>(summary: &'a ImportSummary, name: &str) -> &'a TableMergeSummary {
+        summary.tables.iter().find(|t| t.table == name).unwrap()
+    }
+
+    #[test]
+    fn test_skip_keeps_local_rows() {
+        let path = source_file("skip");
+        let mut conn = database_with(None, &[("w1", "Local Tech")], &[("s1", "w1", "NVDA")]);
+
+        let summary = attach_and_merge(&mut conn, &path, MergeStrategy::Skip, None).unwrap();
+
+        assert_eq!(table(&summary, "watchlists").inserted, 1);
+        assert_eq!(table(&summary, "watchlists").skipped, 1);
+        assert_eq!(table(&summary, "watchlist_stocks").skipped, 2);
+        assert_eq!(table(&summary, "watchlist_stocks").inserted, 1);
+        assert_eq!(count(&conn, "SELECT COUNT(*) FROM watchlist_stocks WHERE watchlist_id = 'w1'"), 1);
+        assert_eq!(count(&conn, "SELECT COUNT(*) FROM pragma_database_list WHERE name = 'import'"), 0);
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_overwrite_replaces_parent_and_children() {
+        let path = source_file("overwrite");
+        let mut conn = database_with(None, &[("w1", "Local Tech")], &[("local", "w1", "NVDA")]);
+
+        let summary = attach_and_merge(&mut conn, &path, MergeStrategy::Overwrite, None).unwrap();
+
+        assert_eq!(table(&summary, "watchlists").overwritten, 1);
+        let name: String = conn
+            .query_row("SELECT name FROM watchlists WHERE id = 'w1'", [], |r| r.get(0))
+            .unwrap();
+        assert_eq!(name, "Tech");
+        assert_eq!(count(&conn, "SELECT COUNT(*) FROM watchlist_stocks WHERE watchlist_id = 'w1'"), 2);
+        assert_eq!(count(&conn, "SELECT COUNT(*) FROM watchlist_stocks WHERE id = 'local'"), 0);
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_rename_imports_conflicts_under_new_ids() {
+        let path = source_file("rename");
+        let mut conn = database_with(None, &[("w1", "Tech")], &[("s1", "w1", "AAPL")]);
+
+        let summary = attach_and_merge(&mut conn, &path, MergeStrategy::Rename, Some(&["watchlists".to_string()])).unwrap();
+
+        assert_eq!(table(&summary, "watchlists").renamed, 1);
+        assert_eq!(table(&summary, "watchlist_stocks").renamed, 1);
+        assert_eq!(count(&conn, "SELECT COUNT(*) FROM watchlists"), 3);
+        let renamed_id: String = conn
+            .query_row("SELECT id FROM watchlists WHERE name = 'Tech (imported)'", [], |r| r.get(0))
+            .unwrap();
+        assert_ne!(renamed_id, "w1");
+        assert_eq!(
+            count(&conn, &format!("SELECT COUNT(*) FROM watchlist_stocks WHERE watchlist_id = '{}'", renamed_id)),
+            2
+        );
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_unknown_table_selection_is_rejected() {
+        let path = source_file("unknown");
+        let mut conn = database_with(None, &[], &[]);
+        let err = attach_and_merge(&mut conn, &path, MergeStrategy::Skip, Some(&["credentials".to_string()]))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("Unknown table 'credentials'"));
+        let _ = std::fs::remove_file(&path);
+    }
+}
@@ -9,13 +9,27 @@ pub mod queries;
 pub mod cache;
 pub mod paper_trading;
 pub mod notes_excel;
+pub mod status;
+pub mod import;
+pub mod config_bundle;
+pub mod settings_snapshot;
+pub mod settings_schema;
+pub mod encryption;
+pub mod env_overlay;
+pub mod retry;
+pub mod historical_cache;
+pub mod profiles;
 
 pub use pool::init_database;
+pub use status::{DbHealth, DbStatus};
 pub use types::*;
 
 use anyhow::Result;
 
 /// Initialize all databases and connection pools
+///
+/// Safe to call from several places (desktop `run()`, web `run_server`); only
+/// the first call does any work.
 pub async fn initialize() -> Result<()> {
     init_database().await?;
     Ok(())
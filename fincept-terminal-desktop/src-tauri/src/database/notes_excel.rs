@@ -103,9 +103,9 @@ pub fn get_all_notes(include_archived: bool) -> Result<Vec<Note>> {
     let conn = conn_lock.as_ref().unwrap();
 
     let query = if include_archived {
-        "SELECT * FROM financial_notes ORDER BY updated_at DESC"
+        "SELECT * FROM financial_notes ORDER BY updated_at DESC, id DESC"
     } else {
-        "SELECT * FROM financial_notes WHERE is_archived = 0 ORDER BY updated_at DESC"
+        "SELECT * FROM financial_notes WHERE is_archived = 0 ORDER BY updated_at DESC, id DESC"
     };
 
     let mut stmt = conn.prepare(query)?;
@@ -189,7 +189,7 @@ pub fn search_notes(query: &str) -> Result<Vec<Note>> {
         "SELECT * FROM financial_notes
          WHERE (title LIKE ?1 OR content LIKE ?1 OR tags LIKE ?1 OR tickers LIKE ?1)
          AND is_archived = 0
-         ORDER BY updated_at DESC"
+         ORDER BY updated_at DESC, id DESC"
     )?;
 
     let notes = stmt
@@ -323,7 +323,7 @@ pub fn get_recent_excel_files(limit: i64) -> Result<Vec<ExcelFile>> {
     let conn = conn_lock.as_ref().unwrap();
 
     let mut stmt = conn.prepare(
-        "SELECT * FROM excel_files ORDER BY last_opened DESC LIMIT ?1"
+        "SELECT * FROM excel_files ORDER BY last_opened DESC, id DESC LIMIT ?1"
     )?;
 
     let files = stmt
@@ -363,7 +363,7 @@ pub fn get_excel_snapshots(file_id: i64) -> Result<Vec<ExcelSnapshot>> {
     let conn = conn_lock.as_ref().unwrap();
 
     let mut stmt = conn.prepare(
-        "SELECT * FROM excel_snapshots WHERE file_id = ?1 ORDER BY created_at DESC"
+        "SELECT * FROM excel_snapshots WHERE file_id = ?1 ORDER BY created_at DESC, id DESC"
     )?;
 
     let snapshots = stmt
@@ -1,7 +1,8 @@
 // Database Operations - All CRUD operations with optimized queries
 // Part 1: Core operations (Settings, Credentials, LLM, Chat, DataSources)
 
-use crate::database::{pool::get_pool, types::*};
+use crate::database::{env_overlay, pool::{self, get_pool}, profiles::active_profile, settings_schema, types::*};
+use crate::utils::command_timeout;
 use anyhow::Result;
 use rusqlite::{params, OptionalExtension};
 
@@ -9,27 +10,74 @@ use rusqlite::{params, OptionalExtension};
 // Settings Operations
 // ============================================================================
 
+/// Upsert one setting; the value is checked against `settings_schema` first
 pub fn save_setting(key: &str, value: &str, category: Option<&str>) -> Result<()> {
-    let pool = get_pool()?;
-    let conn = pool.get()?;
+    settings_schema::validate_setting(key, value).map_err(anyhow::Error::msg)?;
+    pool::write(|conn| {
+        conn.execute(
+            "INSERT OR REPLACE INTO settings (profile, setting_key, setting_value, category, updated_at)
+             VALUES (?1, ?2, ?3, ?4, CURRENT_TIMESTAMP)",
+            params![active_profile(conn)?, key, value, category],
+        )?;
+        Ok(())
+    })?;
+    command_timeout::invalidate();
+    Ok(())
+}
 
-    conn.execute(
-        "INSERT OR REPLACE INTO settings (setting_key, setting_value, category, updated_at)
-         VALUES (?1, ?2, ?3, CURRENT_TIMESTAMP)",
-        params![key, value, category],
-    )?;
+/// Upsert several settings in one transaction
+///
+/// Every value is checked against `settings_schema` first; if any fails, nothing is
+/// written and each entry reports `saved: false`, the failing ones with their error.
+pub fn save_settings(entries: &[SettingEntry]) -> Result<Vec<SettingSaveResult>> {
+    let errors: Vec<Option<String>> = entries
+        .iter()
+        .map(|entry| settings_schema::validate_setting(&entry.key, &entry.value).err())
+        .collect();
+    let valid = errors.iter().all(Option::is_none);
+
+    if valid {
+        pool::write(|conn| {
+            let tx = conn.unchecked_transaction()?;
+            upsert_settings(&tx, entries)?;
+            tx.commit()?;
+            Ok(())
+        })?;
+        command_timeout::invalidate();
+    }
 
+    Ok(entries
+        .iter()
+        .zip(errors)
+        .map(|(entry, error)| SettingSaveResult { key: entry.key.clone(), saved: valid, error })
+        .collect())
+}
+
+fn upsert_settings(conn: &rusqlite::Connection, entries: &[SettingEntry]) -> Result<()> {
+    let profile = active_profile(conn)?;
+    let mut stmt = conn.prepare(
+        "INSERT OR REPLACE INTO settings (profile, setting_key, setting_value, category, updated_at)
+         VALUES (?1, ?2, ?3, ?4, CURRENT_TIMESTAMP)",
+    )?;
+    for entry in entries {
+        stmt.execute(params![profile, entry.key, entry.value, entry.category])?;
+    }
     Ok(())
 }
 
+/// Setting value; `FINCEPT_SETTING_<KEY>` takes precedence (see `env_overlay`)
 pub fn get_setting(key: &str) -> Result<Option<String>> {
+    if let Some(value) = env_overlay::setting(key) {
+        return Ok(Some(value));
+    }
+
     let pool = get_pool()?;
     let conn = pool.get()?;
 
     let result = conn
         .query_row(
-            "SELECT setting_value FROM settings WHERE setting_key = ?1",
-            params![key],
+            "SELECT setting_value FROM settings WHERE profile = ?1 AND setting_key = ?2",
+            params![active_profile(&conn)?, key],
             |row| row.get(0),
         )
         .optional()?;
@@ -41,9 +89,10 @@ pub fn get_all_settings() -> Result<Vec<Setting>> {
     let pool = get_pool()?;
     let conn = pool.get()?;
 
-    let mut stmt = conn.prepare("SELECT setting_key, setting_value, category, updated_at FROM settings")?;
+    let mut stmt =
+        conn.prepare("SELECT setting_key, setting_value, category, updated_at FROM settings WHERE profile = ?1")?;
     let settings = stmt
-        .query_map([], |row| {
+        .query_map(params![active_profile(&conn)?], |row| {
             Ok(Setting {
                 setting_key: row.get(0)?,
                 setting_value: row.get(1)?,
@@ -66,9 +115,10 @@ pub fn save_credential(cred: &Credential) -> Result<OperationResult> {
 
     conn.execute(
         "INSERT OR REPLACE INTO credentials
-         (service_name, username, password, api_key, api_secret, additional_data, updated_at)
-         VALUES (?1, ?2, ?3, ?4, ?5, ?6, CURRENT_TIMESTAMP)",
+         (profile, service_name, username, password, api_key, api_secret, additional_data, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, CURRENT_TIMESTAMP)",
         params![
+            active_profile(&conn)?,
             cred.service_name,
             cred.username,
             cred.password,
@@ -90,11 +140,11 @@ pub fn get_credentials() -> Result<Vec<Credential>> {
 
     let mut stmt = conn.prepare(
         "SELECT id, service_name, username, password, api_key, api_secret, additional_data, created_at, updated_at
-         FROM credentials ORDER BY service_name"
+         FROM credentials WHERE profile = ?1 ORDER BY service_name"
     )?;
 
     let credentials = stmt
-        .query_map([], |row| {
+        .query_map(params![active_profile(&conn)?], |row| {
             Ok(Credential {
                 id: row.get(0)?,
                 service_name: row.get(1)?,
@@ -112,6 +162,7 @@ pub fn get_credentials() -> Result<Vec<Credential>> {
     Ok(credentials)
 }
 
+/// Stored credential with any `FINCEPT_CRED_<SERVICE>_*` fields applied over it (see `env_overlay`)
 pub fn get_credential_by_service(service_name: &str) -> Result<Option<Credential>> {
     let pool = get_pool()?;
     let conn = pool.get()?;
@@ -119,8 +170,8 @@ pub fn get_credential_by_service(service_name: &str) -> Result<Option<Credential
     let result = conn
         .query_row(
             "SELECT id, service_name, username, password, api_key, api_secret, additional_data, created_at, updated_at
-             FROM credentials WHERE service_name = ?1",
-            params![service_name],
+             FROM credentials WHERE profile = ?1 AND service_name = ?2",
+            params![active_profile(&conn)?, service_name],
             |row| {
                 Ok(Credential {
                     id: row.get(0)?,
@@ -137,7 +188,7 @@ pub fn get_credential_by_service(service_name: &str) -> Result<Option<Credential
         )
         .optional()?;
 
-    Ok(result)
+    Ok(env_overlay::overlay_credential(service_name, result))
 }
 
 pub fn delete_credential(id: i64) -> Result<OperationResult> {
@@ -268,16 +319,20 @@ pub fn create_chat_session(title: &str) -> Result<ChatSession> {
 pub fn get_chat_sessions(limit: Option<i64>) -> Result<Vec<ChatSession>> {
     let pool = get_pool()?;
     let conn = pool.get()?;
+    chat_sessions(&conn, limit)
+}
 
+/// Most recently updated first; sessions updated together keep their creation order, newest first
+fn chat_sessions(conn: &rusqlite::Connection, limit: Option<i64>) -> Result<Vec<ChatSession>> {
     let query = if let Some(lim) = limit {
         format!(
             "SELECT session_uuid, title, message_count, created_at, updated_at
-             FROM chat_sessions ORDER BY updated_at DESC LIMIT {}",
+             FROM chat_sessions ORDER BY updated_at DESC, rowid DESC LIMIT {}",
             lim
         )
     } else {
         "SELECT session_uuid, title, message_count, created_at, updated_at
-         FROM chat_sessions ORDER BY updated_at DESC"
+         FROM chat_sessions ORDER BY updated_at DESC, rowid DESC"
             .to_string()
     };
 
@@ -349,27 +404,93 @@ pub fn get_chat_messages(session_uuid: &str) -> Result<Vec<ChatMessage>> {
 
     let mut stmt = conn.prepare(
         "SELECT id, session_uuid, role, content, timestamp, provider, model, tokens_used
-         FROM chat_messages WHERE session_uuid = ?1 ORDER BY timestamp ASC"
+         FROM chat_messages WHERE session_uuid = ?1 ORDER BY timestamp ASC, rowid ASC"
     )?;
 
     let messages = stmt
-        .query_map(params![session_uuid], |row| {
-            Ok(ChatMessage {
-                id: row.get(0)?,
-                session_uuid: row.get(1)?,
-                role: row.get(2)?,
-                content: row.get(3)?,
-                timestamp: row.get(4)?,
-                provider: row.get(5)?,
-                model: row.get(6)?,
-                tokens_used: row.get(7)?,
-            })
-        })?
+        .query_map(params![session_uuid], chat_message_from_row)?
         .collect::<std::result::Result<Vec<_>, _>>()?;
 
     Ok(messages)
 }
 
+fn chat_message_from_row(row: &rusqlite::Row) -> rusqlite::Result<ChatMessage> {
+    Ok(ChatMessage {
+        id: row.get(0)?,
+        session_uuid: row.get(1)?,
+        role: row.get(2)?,
+        content: row.get(3)?,
+        timestamp: row.get(4)?,
+        provider: row.get(5)?,
+        model: row.get(6)?,
+        tokens_used: row.get(7)?,
+    })
+}
+
+/// The newest `limit` messages older than the cursor, for loading history on scroll-up
+///
+/// The cursor is the first message of the page already shown, `before_id`; its
+/// timestamp and rowid are looked up. Messages are ordered by timestamp and then
+/// rowid (insertion order, unlike the random UUID ids), so a message and its reply
+/// from the same second keep their order and are neither skipped nor repeated
+/// across pages. Without a cursor the newest page is returned; with only
+/// `before_timestamp`, everything at that timestamp counts as already shown.
+pub fn get_chat_messages_paged(
+    session_uuid: &str,
+    before_timestamp: Option<&str>,
+    before_id: Option<&str>,
+    limit: usize,
+) -> Result<ChatMessagePage> {
+    let pool = get_pool()?;
+    let conn = pool.get()?;
+    chat_messages_page(&conn, session_uuid, before_timestamp, before_id, limit)
+}
+
+fn chat_messages_page(
+    conn: &rusqlite::Connection,
+    session_uuid: &str,
+    before_timestamp: Option<&str>,
+    before_id: Option<&str>,
+    limit: usize,
+) -> Result<ChatMessagePage> {
+    let mut stmt = conn.prepare(
+        "SELECT id, session_uuid, role, content, timestamp, provider, model, tokens_used
+         FROM chat_messages
+         WHERE session_uuid = ?1
+           AND (?2 IS NULL OR timestamp < ?2 OR (timestamp = ?2 AND rowid < ?3))
+         ORDER BY timestamp DESC, rowid DESC
+         LIMIT ?4"
+    )?;
+
+    let cursor: Option<(String, i64)> = match before_id {
+        Some(id) => conn
+            .query_row(
+                "SELECT timestamp, rowid FROM chat_messages WHERE id = ?1 AND session_uuid = ?2",
+                params![id, session_uuid],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()?,
+        None => None,
+    };
+    let (before_timestamp, before_rowid) = match cursor {
+        Some((timestamp, rowid)) => (Some(timestamp), Some(rowid)),
+        None => (before_timestamp.map(str::to_string), None),
+    };
+
+    // One extra row tells whether anything older is left
+    let mut messages = stmt
+        .query_map(
+            params![session_uuid, before_timestamp, before_rowid, limit as i64 + 1],
+            chat_message_from_row,
+        )?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    let has_more = messages.len() > limit;
+    messages.truncate(limit);
+    messages.reverse();
+
+    Ok(ChatMessagePage { messages, has_more })
+}
+
 pub fn delete_chat_session(session_uuid: &str) -> Result<()> {
     let pool = get_pool()?;
     let conn = pool.get()?;
@@ -392,10 +513,11 @@ pub fn save_data_source(source: &DataSource) -> Result<OperationResultWithId> {
 
     conn.execute(
         "INSERT OR REPLACE INTO data_sources
-         (id, alias, display_name, description, type, provider, category, config, enabled, tags, updated_at)
-         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, CURRENT_TIMESTAMP)",
+         (id, profile, alias, display_name, description, type, provider, category, config, enabled, tags, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, CURRENT_TIMESTAMP)",
         params![
             source.id,
+            active_profile(&conn)?,
             source.alias,
             source.display_name,
             source.description,
@@ -421,11 +543,11 @@ pub fn get_all_data_sources() -> Result<Vec<DataSource>> {
 
     let mut stmt = conn.prepare(
         "SELECT id, alias, display_name, description, type, provider, category, config, enabled, tags, created_at, updated_at
-         FROM data_sources ORDER BY display_name"
+         FROM data_sources WHERE profile = ?1 ORDER BY display_name"
     )?;
 
     let sources = stmt
-        .query_map([], |row| {
+        .query_map(params![active_profile(&conn)?], |row| {
             Ok(DataSource {
                 id: row.get(0)?,
                 alias: row.get(1)?,
@@ -631,7 +753,7 @@ pub fn get_all_portfolios() -> Result<Vec<serde_json::Value>> {
 
     let mut stmt = conn.prepare(
         "SELECT id, name, owner, currency, description, created_at, updated_at
-         FROM portfolios ORDER BY created_at DESC"
+         FROM portfolios ORDER BY created_at DESC, rowid DESC"
     )?;
 
     let portfolios = stmt
@@ -826,12 +948,12 @@ pub fn get_portfolio_transactions(portfolio_id: &str, limit: Option<i32>) -> Res
     let query = if let Some(lim) = limit {
         format!(
             "SELECT id, portfolio_id, symbol, transaction_type, quantity, price, total_value, transaction_date, notes
-             FROM portfolio_transactions WHERE portfolio_id = ?1 ORDER BY transaction_date DESC LIMIT {}",
+             FROM portfolio_transactions WHERE portfolio_id = ?1 ORDER BY transaction_date DESC, rowid DESC LIMIT {}",
             lim
         )
     } else {
         "SELECT id, portfolio_id, symbol, transaction_type, quantity, price, total_value, transaction_date, notes
-         FROM portfolio_transactions WHERE portfolio_id = ?1 ORDER BY transaction_date DESC".to_string()
+         FROM portfolio_transactions WHERE portfolio_id = ?1 ORDER BY transaction_date DESC, rowid DESC".to_string()
     };
 
     let mut stmt = conn.prepare(&query)?;
@@ -854,3 +976,78 @@ pub fn get_portfolio_transactions(portfolio_id: &str, limit: Option<i32>) -> Res
 
     Ok(transactions)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_single_save_rejects_invalid_value() {
+        // Validation runs before the database is touched
+        let err = save_setting(crate::utils::http::HTTP_TIMEOUT_SETTING, "soon", None).unwrap_err();
+        assert!(err.to_string().contains("must be a whole number above 0"), "{}", err);
+    }
+
+    #[test]
+    fn test_chat_pages_walk_back_through_shared_timestamps() {
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        crate::database::schema::create_schema(&conn).unwrap();
+        conn.execute("INSERT INTO chat_sessions (session_uuid, title) VALUES ('s1', 'chat')", []).unwrap();
+        // Ids are random like the real UUIDs; within a second, insertion order wins
+        for (id, timestamp) in [
+            ("a", "2024-05-01 10:00:00"),
+            ("z", "2024-05-01 10:00:01"),
+            ("c", "2024-05-01 10:00:01"),
+            ("b", "2024-05-01 10:00:01"),
+            ("e", "2024-05-01 10:00:02"),
+        ] {
+            conn.execute(
+                "INSERT INTO chat_messages (id, session_uuid, role, content, timestamp) VALUES (?1, 's1', 'user', 'hi', ?2)",
+                params![id, timestamp],
+            )
+            .unwrap();
+        }
+        let ids = |page: &ChatMessagePage| page.messages.iter().map(|m| m.id.clone()).collect::<Vec<_>>();
+
+        let newest = chat_messages_page(&conn, "s1", None, None, 2).unwrap();
+        assert_eq!((ids(&newest), newest.has_more), (vec!["b".to_string(), "e".to_string()], true));
+
+        let first = &newest.messages[0];
+        let older = chat_messages_page(&conn, "s1", Some(&first.timestamp), Some(&first.id), 2).unwrap();
+        assert_eq!((ids(&older), older.has_more), (vec!["z".to_string(), "c".to_string()], true));
+
+        let first = &older.messages[0];
+        let oldest = chat_messages_page(&conn, "s1", None, Some(&first.id), 2).unwrap();
+        assert_eq!((ids(&oldest), oldest.has_more), (vec!["a".to_string()], false));
+
+        // A timestamp alone skips everything at that second
+        let before_second = chat_messages_page(&conn, "s1", Some("2024-05-01 10:00:01"), None, 5).unwrap();
+        assert_eq!(ids(&before_second), vec!["a".to_string()]);
+    }
+
+    #[test]
+    fn test_sessions_with_the_same_timestamp_keep_a_stable_order() {
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        crate::database::schema::create_schema(&conn).unwrap();
+        for (uuid, updated_at) in [
+            ("zeta", "2024-05-01 10:00:00"),
+            ("alpha", "2024-05-01 10:00:00"),
+            ("mid", "2024-05-01 10:00:00"),
+            ("newest", "2024-05-01 10:00:05"),
+        ] {
+            conn.execute(
+                "INSERT INTO chat_sessions (session_uuid, title, updated_at) VALUES (?1, 'chat', ?2)",
+                params![uuid, updated_at],
+            )
+            .unwrap();
+        }
+        let uuids = |limit| {
+            chat_sessions(&conn, limit).unwrap().into_iter().map(|s| s.session_uuid).collect::<Vec<_>>()
+        };
+
+        // Ties fall back to insertion order, newest first, on every call and under a limit
+        assert_eq!(uuids(None), vec!["newest", "mid", "alpha", "zeta"]);
+        assert_eq!(uuids(None), uuids(None));
+        assert_eq!(uuids(Some(2)), vec!["newest", "mid"]);
+    }
+}
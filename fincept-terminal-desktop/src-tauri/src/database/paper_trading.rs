@@ -1,9 +1,11 @@
 // Paper Trading Operations - Portfolio, positions, orders, trades
 
-use crate::database::pool::get_pool;
+use crate::database::pool::{get_pool, get_read_pool, write};
+use crate::utils::money::Money;
 use anyhow::Result;
-use rusqlite::params;
+use rusqlite::{params, OptionalExtension};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 // ============================================================================
 // Types
@@ -21,6 +23,9 @@ pub struct PaperTradingPortfolio {
     pub leverage: f64,
     pub created_at: String,
     pub updated_at: String,
+    /// User the portfolio belongs to; None for portfolios created before owners were tracked
+    #[serde(default)]
+    pub owner: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -62,6 +67,47 @@ pub struct PaperTradingOrder {
     pub created_at: String,
     pub filled_at: Option<String>,
     pub updated_at: String,
+    /// Order that must fill before this one becomes active (bracket legs)
+    pub parent_order_id: Option<String>,
+    /// Orders sharing a group are one-cancels-other: a fill of one cancels the rest
+    pub oco_group_id: Option<String>,
+}
+
+/// Per-portfolio risk limits; `None` disables a rule
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RiskLimits {
+    pub portfolio_id: String,
+    /// Largest notional value of a single position
+    pub max_position_value: Option<f64>,
+    /// Largest open notional across positions divided by the balance
+    pub max_leverage: Option<f64>,
+    pub max_open_positions: Option<i64>,
+    /// Largest drop in balance since the start of the (local) day
+    pub max_daily_loss: Option<f64>,
+    pub updated_at: Option<String>,
+}
+
+impl RiskLimits {
+    pub fn is_empty(&self) -> bool {
+        self.max_position_value.is_none()
+            && self.max_leverage.is_none()
+            && self.max_open_positions.is_none()
+            && self.max_daily_loss.is_none()
+    }
+}
+
+/// Venue constraints for a symbol; `None` leaves that value unconstrained
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SymbolSpec {
+    pub symbol: String,
+    /// Prices are rounded to a multiple of this
+    pub tick_size: Option<f64>,
+    /// Quantities are rounded down to a multiple of this
+    pub lot_size: Option<f64>,
+    pub min_quantity: Option<f64>,
+    /// Smallest price * quantity an order may have
+    pub min_notional: Option<f64>,
+    pub updated_at: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -83,6 +129,7 @@ pub struct PaperTradingTrade {
 // Portfolio Operations
 // ============================================================================
 
+#[allow(clippy::too_many_arguments)]
 pub fn create_portfolio(
     id: &str,
     name: &str,
@@ -91,15 +138,17 @@ pub fn create_portfolio(
     currency: &str,
     margin_mode: &str,
     leverage: f64,
+    owner: Option<&str>,
 ) -> Result<PaperTradingPortfolio> {
+    let initial_balance = money(initial_balance)?;
     let pool = get_pool()?;
     let conn = pool.get()?;
 
     conn.execute(
         "INSERT INTO paper_trading_portfolios
-         (id, name, provider, initial_balance, current_balance, currency, margin_mode, leverage)
-         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
-        params![id, name, provider, initial_balance, initial_balance, currency, margin_mode, leverage],
+         (id, name, provider, initial_balance, current_balance, currency, margin_mode, leverage, owner)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+        params![id, name, provider, initial_balance, initial_balance, currency, margin_mode, leverage, owner],
     )?;
 
     get_portfolio(id)
@@ -110,7 +159,7 @@ pub fn get_portfolio(id: &str) -> Result<PaperTradingPortfolio> {
     let conn = pool.get()?;
 
     let portfolio = conn.query_row(
-        "SELECT id, name, provider, initial_balance, current_balance, currency, margin_mode, leverage, created_at, updated_at
+        "SELECT id, name, provider, initial_balance, current_balance, currency, margin_mode, leverage, created_at, updated_at, owner
          FROM paper_trading_portfolios WHERE id = ?1",
         params![id],
         |row| {
@@ -118,13 +167,14 @@ pub fn get_portfolio(id: &str) -> Result<PaperTradingPortfolio> {
                 id: row.get(0)?,
                 name: row.get(1)?,
                 provider: row.get(2)?,
-                initial_balance: row.get(3)?,
-                current_balance: row.get(4)?,
+                initial_balance: row.get::<_, Money>(3)?.to_f64(),
+                current_balance: row.get::<_, Money>(4)?.to_f64(),
                 currency: row.get(5)?,
                 margin_mode: row.get(6)?,
                 leverage: row.get(7)?,
                 created_at: row.get(8)?,
                 updated_at: row.get(9)?,
+                owner: row.get(10)?,
             })
         },
     )?;
@@ -133,24 +183,55 @@ pub fn get_portfolio(id: &str) -> Result<PaperTradingPortfolio> {
 }
 
 pub fn update_portfolio_balance(id: &str, new_balance: f64) -> Result<()> {
-    let pool = get_pool()?;
-    let conn = pool.get()?;
+    let new_balance = money(new_balance)?;
+    write(|conn| {
+        // The first change of a day fixes the balance daily loss is measured from
+        roll_daily_baseline(conn, id)?;
+        conn.execute(
+            "UPDATE paper_trading_portfolios SET current_balance = ?1, updated_at = CURRENT_TIMESTAMP WHERE id = ?2",
+            params![new_balance, id],
+        )?;
+        Ok(())
+    })
+}
+
+/// Add `delta` (negative for fees and losses) to a portfolio's balance; returns the new balance
+///
+/// Read and write happen in one transaction with exact decimal arithmetic, so
+/// concurrent fills can't overwrite each other's update and many small fees
+/// don't drift the balance.
+pub fn adjust_portfolio_balance(id: &str, delta: f64) -> Result<f64> {
+    let delta = money(delta)?;
+    let balance = write(|conn| {
+        let tx = conn.unchecked_transaction()?;
+        let balance = apply_balance_delta(&tx, id, delta)?;
+        tx.commit()?;
+        Ok(balance)
+    })?;
+    Ok(balance.to_f64())
+}
 
+fn apply_balance_delta(conn: &rusqlite::Connection, id: &str, delta: Money) -> Result<Money> {
+    roll_daily_baseline(conn, id)?;
+    let balance: Money = conn
+        .query_row("SELECT current_balance FROM paper_trading_portfolios WHERE id = ?1", params![id], |row| row.get(0))
+        .optional()?
+        .ok_or_else(|| anyhow::anyhow!("Portfolio {} not found", id))?;
+    let balance = balance + delta;
     conn.execute(
         "UPDATE paper_trading_portfolios SET current_balance = ?1, updated_at = CURRENT_TIMESTAMP WHERE id = ?2",
-        params![new_balance, id],
+        params![balance, id],
     )?;
-
-    Ok(())
+    Ok(balance)
 }
 
 pub fn list_portfolios() -> Result<Vec<PaperTradingPortfolio>> {
-    let pool = get_pool()?;
+    let pool = get_read_pool()?;
     let conn = pool.get()?;
 
     let mut stmt = conn.prepare(
-        "SELECT id, name, provider, initial_balance, current_balance, currency, margin_mode, leverage, created_at, updated_at
-         FROM paper_trading_portfolios ORDER BY created_at DESC"
+        "SELECT id, name, provider, initial_balance, current_balance, currency, margin_mode, leverage, created_at, updated_at, owner
+         FROM paper_trading_portfolios ORDER BY created_at DESC, rowid DESC"
     )?;
 
     let portfolios = stmt
@@ -159,13 +240,14 @@ pub fn list_portfolios() -> Result<Vec<PaperTradingPortfolio>> {
                 id: row.get(0)?,
                 name: row.get(1)?,
                 provider: row.get(2)?,
-                initial_balance: row.get(3)?,
-                current_balance: row.get(4)?,
+                initial_balance: row.get::<_, Money>(3)?.to_f64(),
+                current_balance: row.get::<_, Money>(4)?.to_f64(),
                 currency: row.get(5)?,
                 margin_mode: row.get(6)?,
                 leverage: row.get(7)?,
                 created_at: row.get(8)?,
                 updated_at: row.get(9)?,
+                owner: row.get(10)?,
             })
         })?
         .collect::<std::result::Result<Vec<_>, _>>()?;
@@ -185,6 +267,196 @@ pub fn delete_portfolio(id: &str) -> Result<()> {
     Ok(())
 }
 
+/// Rows removed by `purge_portfolio`, per table
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct PurgedPortfolio {
+    pub portfolios: usize,
+    pub positions: usize,
+    pub orders: usize,
+    pub trades: usize,
+    pub risk_limits: usize,
+    pub snapshots: usize,
+}
+
+/// Delete a portfolio and everything recorded for it, in one transaction
+///
+/// Dependent rows are deleted explicitly, trades first, rather than left to
+/// `ON DELETE CASCADE`: the counts come back exact, and rows written while
+/// foreign keys weren't enforced go too.
+pub fn purge_portfolio(id: &str) -> Result<PurgedPortfolio> {
+    write(|conn| {
+        let tx = conn.unchecked_transaction()?;
+        let purged = purge_portfolio_rows(&tx, id)?;
+        tx.commit()?;
+        Ok(purged)
+    })
+}
+
+fn purge_portfolio_rows(conn: &rusqlite::Connection, id: &str) -> Result<PurgedPortfolio> {
+    let exists = conn
+        .query_row("SELECT 1 FROM paper_trading_portfolios WHERE id = ?1", params![id], |_| Ok(()))
+        .optional()?
+        .is_some();
+    if !exists {
+        anyhow::bail!("Portfolio {} not found", id);
+    }
+
+    let delete = |table: &str| conn.execute(&format!("DELETE FROM {} WHERE portfolio_id = ?1", table), params![id]);
+    Ok(PurgedPortfolio {
+        trades: delete("paper_trading_trades")?,
+        orders: delete("paper_trading_orders")?,
+        positions: delete("paper_trading_positions")?,
+        risk_limits: delete("paper_trading_risk_limits")?,
+        snapshots: delete("paper_trading_snapshots")?,
+        portfolios: conn.execute("DELETE FROM paper_trading_portfolios WHERE id = ?1", params![id])?,
+    })
+}
+
+// ============================================================================
+// Risk Limit Operations
+// ============================================================================
+
+pub fn get_risk_limits(portfolio_id: &str) -> Result<RiskLimits> {
+    let pool = get_pool()?;
+    let conn = pool.get()?;
+
+    let limits = conn
+        .query_row(
+            "SELECT portfolio_id, max_position_value, max_leverage, max_open_positions, max_daily_loss, updated_at
+             FROM paper_trading_risk_limits WHERE portfolio_id = ?1",
+            params![portfolio_id],
+            |row| {
+                Ok(RiskLimits {
+                    portfolio_id: row.get(0)?,
+                    max_position_value: row.get(1)?,
+                    max_leverage: row.get(2)?,
+                    max_open_positions: row.get(3)?,
+                    max_daily_loss: row.get(4)?,
+                    updated_at: row.get(5)?,
+                })
+            },
+        )
+        .optional()?;
+
+    Ok(limits.unwrap_or_else(|| RiskLimits {
+        portfolio_id: portfolio_id.to_string(),
+        ..Default::default()
+    }))
+}
+
+pub fn save_risk_limits(limits: &RiskLimits) -> Result<RiskLimits> {
+    let pool = get_pool()?;
+    let conn = pool.get()?;
+
+    conn.execute(
+        "INSERT INTO paper_trading_risk_limits
+         (portfolio_id, max_position_value, max_leverage, max_open_positions, max_daily_loss, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, CURRENT_TIMESTAMP)
+         ON CONFLICT(portfolio_id) DO UPDATE SET
+             max_position_value = excluded.max_position_value,
+             max_leverage = excluded.max_leverage,
+             max_open_positions = excluded.max_open_positions,
+             max_daily_loss = excluded.max_daily_loss,
+             updated_at = CURRENT_TIMESTAMP",
+        params![
+            limits.portfolio_id,
+            limits.max_position_value,
+            limits.max_leverage,
+            limits.max_open_positions,
+            limits.max_daily_loss,
+        ],
+    )?;
+
+    get_risk_limits(&limits.portfolio_id)
+}
+
+/// Start a new day's loss baseline at the portfolio's current balance if the day changed
+fn roll_daily_baseline(conn: &rusqlite::Connection, portfolio_id: &str) -> Result<()> {
+    let today = chrono::Local::now().format("%Y-%m-%d").to_string();
+    conn.execute(
+        "INSERT INTO paper_trading_risk_limits (portfolio_id, day_start_balance, day_start_date)
+         SELECT id, current_balance, ?2 FROM paper_trading_portfolios WHERE id = ?1
+         ON CONFLICT(portfolio_id) DO UPDATE SET
+             day_start_balance = excluded.day_start_balance,
+             day_start_date = excluded.day_start_date
+         WHERE day_start_date IS NOT excluded.day_start_date",
+        params![portfolio_id, today],
+    )?;
+    Ok(())
+}
+
+/// Balance lost since the start of today (0 when up on the day)
+pub fn daily_loss(portfolio_id: &str) -> Result<f64> {
+    let pool = get_pool()?;
+    let conn = pool.get()?;
+
+    roll_daily_baseline(&conn, portfolio_id)?;
+    let balances: Option<(Option<Money>, Money)> = conn
+        .query_row(
+            "SELECT r.day_start_balance, p.current_balance
+             FROM paper_trading_risk_limits r JOIN paper_trading_portfolios p ON p.id = r.portfolio_id
+             WHERE r.portfolio_id = ?1",
+            params![portfolio_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()?;
+
+    let loss = match balances {
+        Some((Some(day_start), current)) => day_start - current,
+        _ => Money::ZERO,
+    };
+    Ok(loss.max(Money::ZERO).to_f64())
+}
+
+// ============================================================================
+// Symbol Spec Operations
+// ============================================================================
+
+pub fn get_symbol_spec(symbol: &str) -> Result<Option<SymbolSpec>> {
+    let pool = get_pool()?;
+    let conn = pool.get()?;
+
+    let spec = conn
+        .query_row(
+            "SELECT symbol, tick_size, lot_size, min_quantity, min_notional, updated_at
+             FROM paper_trading_symbol_specs WHERE symbol = ?1",
+            params![symbol],
+            |row| {
+                Ok(SymbolSpec {
+                    symbol: row.get(0)?,
+                    tick_size: row.get(1)?,
+                    lot_size: row.get(2)?,
+                    min_quantity: row.get(3)?,
+                    min_notional: row.get(4)?,
+                    updated_at: row.get(5)?,
+                })
+            },
+        )
+        .optional()?;
+
+    Ok(spec)
+}
+
+pub fn save_symbol_spec(spec: &SymbolSpec) -> Result<SymbolSpec> {
+    let pool = get_pool()?;
+    let conn = pool.get()?;
+
+    conn.execute(
+        "INSERT INTO paper_trading_symbol_specs
+         (symbol, tick_size, lot_size, min_quantity, min_notional, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, CURRENT_TIMESTAMP)
+         ON CONFLICT(symbol) DO UPDATE SET
+             tick_size = excluded.tick_size,
+             lot_size = excluded.lot_size,
+             min_quantity = excluded.min_quantity,
+             min_notional = excluded.min_notional,
+             updated_at = CURRENT_TIMESTAMP",
+        params![spec.symbol, spec.tick_size, spec.lot_size, spec.min_quantity, spec.min_notional],
+    )?;
+
+    Ok(get_symbol_spec(&spec.symbol)?.unwrap_or_else(|| spec.clone()))
+}
+
 // ============================================================================
 // Position Operations
 // ============================================================================
@@ -199,36 +471,36 @@ pub fn create_position(
     leverage: f64,
     margin_mode: &str,
 ) -> Result<()> {
-    let pool = get_pool()?;
-    let conn = pool.get()?;
-
     let position_value = entry_price * quantity;
 
-    conn.execute(
-        "INSERT INTO paper_trading_positions
-         (id, portfolio_id, symbol, side, entry_price, quantity, position_value, leverage, margin_mode, status)
-         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, 'open')",
-        params![id, portfolio_id, symbol, side, entry_price, quantity, position_value, leverage, margin_mode],
-    )?;
+    write(|conn| {
+        conn.execute(
+            "INSERT INTO paper_trading_positions
+             (id, portfolio_id, symbol, side, entry_price, quantity, position_value, leverage, margin_mode, status)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, 'open')",
+            params![id, portfolio_id, symbol, side, entry_price, quantity, position_value, leverage, margin_mode],
+        )?;
+        Ok(())
+    })?;
 
     Ok(())
 }
 
 pub fn get_portfolio_positions(portfolio_id: &str, status: Option<&str>) -> Result<Vec<PaperTradingPosition>> {
-    let pool = get_pool()?;
+    let pool = get_read_pool()?;
     let conn = pool.get()?;
 
     let query = if let Some(st) = status {
         format!(
             "SELECT id, portfolio_id, symbol, side, entry_price, quantity, position_value, current_price,
                     unrealized_pnl, realized_pnl, leverage, margin_mode, liquidation_price, opened_at, closed_at, status
-             FROM paper_trading_positions WHERE portfolio_id = ?1 AND status = '{}' ORDER BY opened_at DESC",
+             FROM paper_trading_positions WHERE portfolio_id = ?1 AND status = '{}' ORDER BY opened_at DESC, rowid DESC",
             st
         )
     } else {
         "SELECT id, portfolio_id, symbol, side, entry_price, quantity, position_value, current_price,
                 unrealized_pnl, realized_pnl, leverage, margin_mode, liquidation_price, opened_at, closed_at, status
-         FROM paper_trading_positions WHERE portfolio_id = ?1 ORDER BY opened_at DESC"
+         FROM paper_trading_positions WHERE portfolio_id = ?1 ORDER BY opened_at DESC, rowid DESC"
             .to_string()
     };
 
@@ -245,7 +517,7 @@ pub fn get_portfolio_positions(portfolio_id: &str, status: Option<&str>) -> Resu
                 position_value: row.get(6)?,
                 current_price: row.get(7)?,
                 unrealized_pnl: row.get(8)?,
-                realized_pnl: row.get(9)?,
+                realized_pnl: row.get::<_, Money>(9)?.to_f64(),
                 leverage: row.get(10)?,
                 margin_mode: row.get(11)?,
                 liquidation_price: row.get(12)?,
@@ -262,7 +534,10 @@ pub fn get_portfolio_positions(portfolio_id: &str, status: Option<&str>) -> Resu
 pub fn get_position(id: &str) -> Result<PaperTradingPosition> {
     let pool = get_pool()?;
     let conn = pool.get()?;
+    load_position(&conn, id)
+}
 
+fn load_position(conn: &rusqlite::Connection, id: &str) -> Result<PaperTradingPosition> {
     let position = conn.query_row(
         "SELECT id, portfolio_id, symbol, side, entry_price, quantity, position_value, current_price,
                 unrealized_pnl, realized_pnl, leverage, margin_mode, liquidation_price, opened_at, closed_at, status
@@ -279,7 +554,7 @@ pub fn get_position(id: &str) -> Result<PaperTradingPosition> {
                 position_value: row.get(6)?,
                 current_price: row.get(7)?,
                 unrealized_pnl: row.get(8)?,
-                realized_pnl: row.get(9)?,
+                realized_pnl: row.get::<_, Money>(9)?.to_f64(),
                 leverage: row.get(10)?,
                 margin_mode: row.get(11)?,
                 liquidation_price: row.get(12)?,
@@ -301,7 +576,7 @@ pub fn get_position_by_symbol(portfolio_id: &str, symbol: &str, status: &str) ->
         "SELECT id, portfolio_id, symbol, side, entry_price, quantity, position_value, current_price,
                 unrealized_pnl, realized_pnl, leverage, margin_mode, liquidation_price, opened_at, closed_at, status
          FROM paper_trading_positions WHERE portfolio_id = ?1 AND symbol = ?2 AND status = ?3
-         ORDER BY opened_at DESC LIMIT 1",
+         ORDER BY opened_at DESC, rowid DESC LIMIT 1",
         params![portfolio_id, symbol, status],
         |row| {
             Ok(PaperTradingPosition {
@@ -314,7 +589,7 @@ pub fn get_position_by_symbol(portfolio_id: &str, symbol: &str, status: &str) ->
                 position_value: row.get(6)?,
                 current_price: row.get(7)?,
                 unrealized_pnl: row.get(8)?,
-                realized_pnl: row.get(9)?,
+                realized_pnl: row.get::<_, Money>(9)?.to_f64(),
                 leverage: row.get(10)?,
                 margin_mode: row.get(11)?,
                 liquidation_price: row.get(12)?,
@@ -345,7 +620,7 @@ pub fn get_position_by_symbol_and_side(
         "SELECT id, portfolio_id, symbol, side, entry_price, quantity, position_value, current_price,
                 unrealized_pnl, realized_pnl, leverage, margin_mode, liquidation_price, opened_at, closed_at, status
          FROM paper_trading_positions WHERE portfolio_id = ?1 AND symbol = ?2 AND side = ?3 AND status = ?4
-         ORDER BY opened_at DESC LIMIT 1",
+         ORDER BY opened_at DESC, rowid DESC LIMIT 1",
         params![portfolio_id, symbol, side, status],
         |row| {
             Ok(PaperTradingPosition {
@@ -358,7 +633,7 @@ pub fn get_position_by_symbol_and_side(
                 position_value: row.get(6)?,
                 current_price: row.get(7)?,
                 unrealized_pnl: row.get(8)?,
-                realized_pnl: row.get(9)?,
+                realized_pnl: row.get::<_, Money>(9)?.to_f64(),
                 leverage: row.get(10)?,
                 margin_mode: row.get(11)?,
                 liquidation_price: row.get(12)?,
@@ -387,9 +662,6 @@ pub fn update_position(
     status: Option<&str>,
     closed_at: Option<&str>,
 ) -> Result<()> {
-    let pool = get_pool()?;
-    let conn = pool.get()?;
-
     let mut updates = Vec::new();
     let mut params_vec: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
 
@@ -411,7 +683,7 @@ pub fn update_position(
     }
     if let Some(rpnl) = realized_pnl {
         updates.push("realized_pnl = ?".to_string());
-        params_vec.push(Box::new(rpnl));
+        params_vec.push(Box::new(money(rpnl)?));
     }
     if let Some(lp) = liquidation_price {
         updates.push("liquidation_price = ?".to_string());
@@ -434,21 +706,548 @@ pub fn update_position(
     let sql = format!("UPDATE paper_trading_positions SET {} WHERE id = ?", updates.join(", "));
 
     let params_refs: Vec<&dyn rusqlite::ToSql> = params_vec.iter().map(|p| p.as_ref()).collect();
-    conn.execute(&sql, params_refs.as_slice())?;
+    write(|conn| Ok(conn.execute(&sql, params_refs.as_slice())?))?;
 
     Ok(())
 }
 
 pub fn delete_position(id: &str) -> Result<()> {
+    write(|conn| {
+        conn.execute(
+            "DELETE FROM paper_trading_positions WHERE id = ?1",
+            params![id],
+        )?;
+        Ok(())
+    })?;
+
+    Ok(())
+}
+
+/// Outcome of `reduce_position`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PositionReduction {
+    /// The position after the reduction; status "closed" when nothing is left
+    pub position: PaperTradingPosition,
+    pub closed_quantity: f64,
+    /// PnL of the closed portion before fees
+    pub realized_pnl: f64,
+    pub fee: f64,
+    pub balance: f64,
+}
+
+/// Remaining quantities below this count as a fully closed position
+const QUANTITY_EPSILON: f64 = 1e-9;
+
+/// `value` as `Money`, rejecting NaN and infinities
+fn money(value: f64) -> Result<Money> {
+    Money::new(value).map_err(|e| anyhow::anyhow!(e))
+}
+
+fn exact(value: f64) -> Money {
+    Money::from_f64_lossy(value)
+}
+
+/// PnL of closing `quantity` of a `side` position opened at `entry_price`
+pub fn realized_pnl(side: &str, entry_price: f64, close_price: f64, quantity: f64) -> f64 {
+    exact_pnl(side, exact(entry_price), exact(close_price), exact(quantity)).to_f64()
+}
+
+fn exact_pnl(side: &str, entry_price: Money, close_price: Money, quantity: Money) -> Money {
+    if side == "short" {
+        (entry_price - close_price) * quantity
+    } else {
+        (close_price - entry_price) * quantity
+    }
+}
+
+/// Close `close_qty` of an open position at `close_price`
+///
+/// In one transaction: the realized PnL of the closed portion is added to the
+/// position's `realized_pnl`, the remaining quantity (or a full close) is written,
+/// and the portfolio balance moves by the PnL minus `fee`.
+pub fn reduce_position(id: &str, close_qty: f64, close_price: f64, fee: f64) -> Result<PositionReduction> {
+    if !(close_qty.is_finite() && close_qty > 0.0) {
+        anyhow::bail!("Close quantity must be positive, got {}", close_qty);
+    }
+    if !(close_price.is_finite() && close_price > 0.0) {
+        anyhow::bail!("Close price must be positive, got {}", close_price);
+    }
+    if !(fee.is_finite() && fee >= 0.0) {
+        anyhow::bail!("Fee must not be negative, got {}", fee);
+    }
+
     let pool = get_pool()?;
-    let conn = pool.get()?;
+    let mut conn = pool.get()?;
+    crate::database::retry::with_busy_retry(|| apply_reduction(&mut conn, id, close_qty, close_price, fee))
+}
 
-    conn.execute(
-        "DELETE FROM paper_trading_positions WHERE id = ?1",
-        params![id],
+fn apply_reduction(
+    conn: &mut rusqlite::Connection,
+    id: &str,
+    close_qty: f64,
+    close_price: f64,
+    fee: f64,
+) -> Result<PositionReduction> {
+    let tx = conn.transaction_with_behavior(rusqlite::TransactionBehavior::Immediate)?;
+    let position = load_position(&tx, id)?;
+    if position.status != "open" {
+        anyhow::bail!("Position {} is {}, not open", id, position.status);
+    }
+    if close_qty > position.quantity + QUANTITY_EPSILON {
+        anyhow::bail!("Close quantity {} exceeds position size {}", close_qty, position.quantity);
+    }
+
+    let entry_price = exact(position.entry_price);
+    let close_price = exact(close_price);
+    let fee = exact(fee);
+    let closed_quantity = exact(close_qty.min(position.quantity));
+    let pnl = exact_pnl(&position.side, entry_price, close_price, closed_quantity);
+    let remaining = exact(position.quantity) - closed_quantity;
+    let position_realized = exact(position.realized_pnl) + pnl;
+
+    if remaining.to_f64() <= QUANTITY_EPSILON {
+        tx.execute(
+            "UPDATE paper_trading_positions
+             SET quantity = 0, position_value = 0, unrealized_pnl = 0, realized_pnl = ?1,
+                 current_price = ?2, status = 'closed', closed_at = CURRENT_TIMESTAMP
+             WHERE id = ?3",
+            params![position_realized, close_price, id],
+        )?;
+    } else {
+        tx.execute(
+            "UPDATE paper_trading_positions
+             SET quantity = ?1, position_value = ?2, realized_pnl = ?3, current_price = ?4,
+                 unrealized_pnl = ?5
+             WHERE id = ?6",
+            params![
+                remaining,
+                remaining * entry_price,
+                position_realized,
+                close_price,
+                exact_pnl(&position.side, entry_price, close_price, remaining),
+                id
+            ],
+        )?;
+    }
+
+    let balance = apply_balance_delta(&tx, &position.portfolio_id, pnl - fee)?;
+    let position = load_position(&tx, id)?;
+    tx.commit()?;
+
+    Ok(PositionReduction {
+        position,
+        closed_quantity: closed_quantity.to_f64(),
+        realized_pnl: pnl.to_f64(),
+        fee: fee.to_f64(),
+        balance: balance.to_f64(),
+    })
+}
+
+/// Market fill that opens a position, or adds to an open one on the same side
+#[derive(Debug, Clone)]
+pub struct MarketOpen<'a> {
+    pub portfolio_id: &'a str,
+    pub symbol: &'a str,
+    /// "long" or "short"
+    pub side: &'a str,
+    pub quantity: f64,
+    pub fill_price: f64,
+    pub leverage: f64,
+    pub margin_mode: &'a str,
+    pub fee_rate: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct OpenedPosition {
+    pub position: PaperTradingPosition,
+    pub order_id: String,
+    pub trade_id: String,
+    pub fee: f64,
+    /// Margin the fill ties up: `quantity * fill_price / leverage`
+    pub margin: f64,
+    pub balance: f64,
+}
+
+/// Open (or add to) a position with a filled market order, in one transaction
+///
+/// Writes the filled order, the trade with its taker fee and the position (VWAP
+/// entry when adding), and debits the fee from the balance. Margin stays in the
+/// balance as everywhere else - it is the open positions' claim on it - so the
+/// fill is rejected when the free balance can't cover its margin plus the fee.
+/// `liquidation` maps (entry price, quantity, leverage) to a liquidation price.
+/// An open position on the opposite side is rejected rather than netted; adding
+/// to one on the same side uses that position's leverage, not `open.leverage`.
+pub fn open_market_position(
+    open: &MarketOpen,
+    liquidation: impl Fn(f64, f64, f64) -> Option<f64>,
+) -> Result<OpenedPosition> {
+    if !["long", "short"].contains(&open.side) {
+        anyhow::bail!("Invalid side '{}' (expected long or short)", open.side);
+    }
+    if !(open.quantity.is_finite() && open.quantity > 0.0) {
+        anyhow::bail!("Quantity must be positive, got {}", open.quantity);
+    }
+    if !(open.fill_price.is_finite() && open.fill_price > 0.0) {
+        anyhow::bail!("Fill price must be positive, got {}", open.fill_price);
+    }
+    if !(open.leverage.is_finite() && open.leverage >= 1.0) {
+        anyhow::bail!("Leverage must be at least 1, got {}", open.leverage);
+    }
+    if !(open.fee_rate.is_finite() && open.fee_rate >= 0.0) {
+        anyhow::bail!("Fee rate must not be negative, got {}", open.fee_rate);
+    }
+
+    // Generated once so a busy retry writes the same rows
+    let ids = [(); 3].map(|_| uuid::Uuid::new_v4().to_string());
+    let pool = get_pool()?;
+    let mut conn = pool.get()?;
+    crate::database::retry::with_busy_retry(|| apply_market_open(&mut conn, open, &ids, &liquidation))
+}
+
+/// `ids` are the order, trade and (if one is created) position ids
+fn apply_market_open(
+    conn: &mut rusqlite::Connection,
+    open: &MarketOpen,
+    ids: &[String; 3],
+    liquidation: &dyn Fn(f64, f64, f64) -> Option<f64>,
+) -> Result<OpenedPosition> {
+    let [order_id, trade_id, new_position_id] = ids;
+    let tx = conn.transaction_with_behavior(rusqlite::TransactionBehavior::Immediate)?;
+
+    let balance: Money = tx
+        .query_row(
+            "SELECT current_balance FROM paper_trading_portfolios WHERE id = ?1",
+            params![open.portfolio_id],
+            |row| row.get(0),
+        )
+        .optional()?
+        .ok_or_else(|| anyhow::anyhow!("Portfolio {} not found", open.portfolio_id))?;
+
+    let mut margin_used = Money::ZERO;
+    let mut existing = None;
+    {
+        let mut stmt = tx.prepare(
+            "SELECT id, symbol, side, quantity, entry_price, leverage
+             FROM paper_trading_positions WHERE portfolio_id = ?1 AND status = 'open'",
+        )?;
+        let mut rows = stmt.query(params![open.portfolio_id])?;
+        while let Some(row) = rows.next()? {
+            let (id, symbol, side): (String, String, String) = (row.get(0)?, row.get(1)?, row.get(2)?);
+            let (quantity, entry_price, leverage): (f64, f64, f64) = (row.get(3)?, row.get(4)?, row.get(5)?);
+            margin_used += exact(quantity * entry_price / leverage.max(1.0));
+            if symbol == open.symbol {
+                if side != open.side {
+                    anyhow::bail!(
+                        "{} has an open {} position; close it before opening a {} position",
+                        open.symbol,
+                        side,
+                        open.side
+                    );
+                }
+                existing = Some((id, leverage));
+            }
+        }
+    }
+
+    // Adding to a position keeps its leverage, so the fill is margined at that leverage too
+    let leverage = existing.as_ref().map_or(open.leverage, |(_, leverage)| leverage.max(1.0));
+    let price = exact(open.fill_price);
+    let quantity = exact(open.quantity);
+    let value = price * quantity;
+    let fee = value * exact(open.fee_rate);
+    let margin = exact(open.quantity * open.fill_price / leverage);
+    let free = balance - margin_used;
+    if free < margin + fee {
+        anyhow::bail!("Insufficient funds: {} needed for margin and fees, {} free", margin + fee, free);
+    }
+
+    let order_side = if open.side == "long" { "buy" } else { "sell" };
+    tx.execute(
+        "INSERT INTO paper_trading_orders
+         (id, portfolio_id, symbol, side, type, quantity, filled_quantity, avg_fill_price, status,
+          time_in_force, leverage, margin_mode, filled_at)
+         VALUES (?1, ?2, ?3, ?4, 'market', ?5, ?5, ?6, 'filled', 'GTC', ?7, ?8, CURRENT_TIMESTAMP)",
+        params![order_id, open.portfolio_id, open.symbol, order_side, quantity, price, leverage, open.margin_mode],
+    )?;
+    tx.execute(
+        "INSERT INTO paper_trading_trades
+         (id, portfolio_id, order_id, symbol, side, price, quantity, fee, fee_rate, is_maker)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, 0)",
+        params![trade_id, open.portfolio_id, order_id, open.symbol, order_side, price, quantity, fee, open.fee_rate],
     )?;
 
-    Ok(())
+    let position_id = match existing {
+        Some((id, _)) => {
+            let current = load_position(&tx, &id)?;
+            let total = exact(current.quantity) + quantity;
+            let cost = exact(current.entry_price) * exact(current.quantity) + value;
+            let entry = Money::from_decimal(cost.decimal() / total.decimal());
+            tx.execute(
+                "UPDATE paper_trading_positions
+                 SET quantity = ?1, entry_price = ?2, position_value = ?3, liquidation_price = ?4
+                 WHERE id = ?5",
+                params![
+                    total,
+                    entry,
+                    entry * total,
+                    liquidation(entry.to_f64(), total.to_f64(), current.leverage),
+                    id
+                ],
+            )?;
+            id
+        }
+        None => {
+            tx.execute(
+                "INSERT INTO paper_trading_positions
+                 (id, portfolio_id, symbol, side, entry_price, quantity, position_value, leverage,
+                  margin_mode, liquidation_price, status)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, 'open')",
+                params![
+                    new_position_id,
+                    open.portfolio_id,
+                    open.symbol,
+                    open.side,
+                    price,
+                    quantity,
+                    value,
+                    open.leverage,
+                    open.margin_mode,
+                    liquidation(open.fill_price, open.quantity, open.leverage)
+                ],
+            )?;
+            new_position_id.clone()
+        }
+    };
+
+    let balance = apply_balance_delta(&tx, open.portfolio_id, -fee)?;
+    let position = load_position(&tx, &position_id)?;
+    tx.commit()?;
+
+    Ok(OpenedPosition {
+        position,
+        order_id: order_id.clone(),
+        trade_id: trade_id.clone(),
+        fee: fee.to_f64(),
+        margin: margin.to_f64(),
+        balance: balance.to_f64(),
+    })
+}
+
+/// Mark-to-market result of one open position
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PositionMark {
+    pub position_id: String,
+    pub symbol: String,
+    pub side: String,
+    pub current_price: f64,
+    pub unrealized_pnl: f64,
+    /// Unrealized PnL as a percentage of the margin posted (notional / leverage)
+    pub return_on_margin: Option<f64>,
+}
+
+/// Outcome of `recompute_unrealized_pnl`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UnrealizedPnlUpdate {
+    pub portfolio_id: String,
+    pub positions: Vec<PositionMark>,
+    /// Open positions' symbols with no mark supplied; their stored PnL is kept
+    pub missing_prices: Vec<String>,
+    /// Unrealized PnL of every open position after the update
+    pub total_unrealized_pnl: f64,
+}
+
+/// Mark every open position of a portfolio to `prices` (symbol -> mark) in one transaction
+///
+/// Leverage does not change the PnL of a given quantity, only the margin behind
+/// it, so it shows up in `return_on_margin`.
+pub fn recompute_unrealized_pnl(portfolio_id: &str, prices: &HashMap<String, f64>) -> Result<UnrealizedPnlUpdate> {
+    if let Some((symbol, price)) = prices.iter().find(|(_, price)| !(price.is_finite() && **price > 0.0)) {
+        anyhow::bail!("Price for {} must be positive, got {}", symbol, price);
+    }
+
+    let pool = get_pool()?;
+    let mut conn = pool.get()?;
+    crate::database::retry::with_busy_retry(|| apply_marks(&mut conn, portfolio_id, prices))
+}
+
+fn apply_marks(
+    conn: &mut rusqlite::Connection,
+    portfolio_id: &str,
+    prices: &HashMap<String, f64>,
+) -> Result<UnrealizedPnlUpdate> {
+    let tx = conn.transaction_with_behavior(rusqlite::TransactionBehavior::Immediate)?;
+    let exists = tx
+        .query_row("SELECT 1 FROM paper_trading_portfolios WHERE id = ?1", params![portfolio_id], |_| Ok(()))
+        .optional()?;
+    if exists.is_none() {
+        anyhow::bail!("Portfolio {} not found", portfolio_id);
+    }
+
+    let update = mark_positions(&tx, portfolio_id, prices)?;
+    tx.commit()?;
+    Ok(update)
+}
+
+/// Mark the open positions of one portfolio inside the caller's transaction
+fn mark_positions(
+    tx: &rusqlite::Connection,
+    portfolio_id: &str,
+    prices: &HashMap<String, f64>,
+) -> Result<UnrealizedPnlUpdate> {
+    let open_ids: Vec<String> = tx
+        .prepare("SELECT id FROM paper_trading_positions WHERE portfolio_id = ?1 AND status = 'open' ORDER BY opened_at, rowid")?
+        .query_map(params![portfolio_id], |row| row.get(0))?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+
+    let mut update = UnrealizedPnlUpdate {
+        portfolio_id: portfolio_id.to_string(),
+        positions: Vec::new(),
+        missing_prices: Vec::new(),
+        total_unrealized_pnl: 0.0,
+    };
+    let mut total = Money::ZERO;
+    for id in open_ids {
+        let position = load_position(tx, &id)?;
+        let Some(&mark) = prices.get(&position.symbol) else {
+            total += exact(position.unrealized_pnl.unwrap_or(0.0));
+            if !update.missing_prices.contains(&position.symbol) {
+                update.missing_prices.push(position.symbol);
+            }
+            continue;
+        };
+        let pnl = exact_pnl(&position.side, exact(position.entry_price), exact(mark), exact(position.quantity));
+        tx.execute(
+            "UPDATE paper_trading_positions SET current_price = ?1, unrealized_pnl = ?2 WHERE id = ?3",
+            params![mark, pnl, id],
+        )?;
+        total += pnl;
+        let pnl = pnl.to_f64();
+        let margin = position.entry_price * position.quantity / position.leverage.max(1.0);
+        update.positions.push(PositionMark {
+            position_id: id,
+            symbol: position.symbol,
+            side: position.side,
+            current_price: mark,
+            unrealized_pnl: pnl,
+            return_on_margin: (margin > 0.0).then(|| pnl / margin * 100.0),
+        });
+    }
+
+    update.total_unrealized_pnl = total.to_f64();
+    Ok(update)
+}
+
+/// Imported price for one symbol, e.g. an end-of-day close
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PriceMark {
+    pub price: f64,
+    /// When the price was observed; becomes the `as_of` of the snapshots it goes into
+    pub timestamp: String,
+}
+
+/// Balance and open PnL of a paper portfolio at a point in time
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PortfolioSnapshot {
+    pub id: i64,
+    pub portfolio_id: String,
+    pub balance: f64,
+    pub unrealized_pnl: f64,
+    /// `balance + unrealized_pnl`
+    pub equity: f64,
+    pub open_positions: usize,
+    /// Latest timestamp among the marks applied to the portfolio
+    pub as_of: String,
+}
+
+/// Outcome of `ingest_marks`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MarkIngestion {
+    pub positions_updated: usize,
+    /// One per portfolio holding an open position in a marked symbol
+    pub snapshots: Vec<PortfolioSnapshot>,
+    /// Marked symbols no open position holds
+    pub unmatched_symbols: Vec<String>,
+}
+
+/// Mark every open position in any portfolio to `marks` (symbol -> price) and
+/// snapshot each portfolio touched, all in one transaction
+///
+/// For loading end-of-day prices without a live feed. Positions in symbols
+/// without a mark keep their stored PnL and count towards their snapshot as is.
+pub fn ingest_marks(marks: &HashMap<String, PriceMark>) -> Result<MarkIngestion> {
+    if let Some((symbol, mark)) = marks.iter().find(|(_, mark)| !(mark.price.is_finite() && mark.price > 0.0)) {
+        anyhow::bail!("Price for {} must be positive, got {}", symbol, mark.price);
+    }
+
+    let pool = get_pool()?;
+    let mut conn = pool.get()?;
+    crate::database::retry::with_busy_retry(|| apply_ingested_marks(&mut conn, marks))
+}
+
+fn apply_ingested_marks(conn: &mut rusqlite::Connection, marks: &HashMap<String, PriceMark>) -> Result<MarkIngestion> {
+    let tx = conn.transaction_with_behavior(rusqlite::TransactionBehavior::Immediate)?;
+
+    let held: Vec<(String, String)> = tx
+        .prepare("SELECT DISTINCT portfolio_id, symbol FROM paper_trading_positions WHERE status = 'open' ORDER BY portfolio_id")?
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    let mut portfolio_ids: Vec<String> = held
+        .iter()
+        .filter(|(_, symbol)| marks.contains_key(symbol))
+        .map(|(portfolio_id, _)| portfolio_id.clone())
+        .collect();
+    portfolio_ids.dedup();
+
+    let prices: HashMap<String, f64> = marks.iter().map(|(symbol, mark)| (symbol.clone(), mark.price)).collect();
+    let mut ingestion = MarkIngestion {
+        positions_updated: 0,
+        snapshots: Vec::new(),
+        unmatched_symbols: marks
+            .keys()
+            .filter(|symbol| !held.iter().any(|(_, held_symbol)| held_symbol == *symbol))
+            .cloned()
+            .collect(),
+    };
+    ingestion.unmatched_symbols.sort();
+
+    for portfolio_id in portfolio_ids {
+        let update = mark_positions(&tx, &portfolio_id, &prices)?;
+        ingestion.positions_updated += update.positions.len();
+
+        let (balance, open_positions): (Money, i64) = tx.query_row(
+            "SELECT current_balance,
+                    (SELECT COUNT(*) FROM paper_trading_positions WHERE portfolio_id = p.id AND status = 'open')
+             FROM paper_trading_portfolios p WHERE id = ?1",
+            params![portfolio_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )?;
+        let as_of = update
+            .positions
+            .iter()
+            .filter_map(|mark| marks.get(&mark.symbol))
+            .map(|mark| mark.timestamp.clone())
+            .max()
+            .unwrap_or_default();
+        let equity = (balance + exact(update.total_unrealized_pnl)).to_f64();
+        let balance = balance.to_f64();
+        tx.execute(
+            "INSERT INTO paper_trading_snapshots (portfolio_id, balance, unrealized_pnl, equity, open_positions, as_of)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![portfolio_id, balance, update.total_unrealized_pnl, equity, open_positions, as_of],
+        )?;
+        ingestion.snapshots.push(PortfolioSnapshot {
+            id: tx.last_insert_rowid(),
+            portfolio_id,
+            balance,
+            unrealized_pnl: update.total_unrealized_pnl,
+            equity,
+            open_positions: open_positions as usize,
+            as_of,
+        });
+    }
+    tx.commit()?;
+
+    Ok(ingestion)
 }
 
 // ============================================================================
@@ -465,34 +1264,137 @@ pub fn create_order(
     price: Option<f64>,
     time_in_force: &str,
 ) -> Result<()> {
-    let pool = get_pool()?;
-    let conn = pool.get()?;
+    write(|conn| {
+        conn.execute(
+            "INSERT INTO paper_trading_orders
+             (id, portfolio_id, symbol, side, type, quantity, price, status, time_in_force, filled_quantity)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, 'pending', ?8, 0)",
+            params![id, portfolio_id, symbol, side, order_type, quantity, price, time_in_force],
+        )?;
+        Ok(())
+    })?;
 
-    conn.execute(
+    Ok(())
+}
+
+/// Entry order of a bracket
+#[derive(Debug, Clone)]
+pub struct BracketEntry<'a> {
+    pub portfolio_id: &'a str,
+    pub symbol: &'a str,
+    pub side: &'a str,
+    /// "market" or "limit"
+    pub order_type: &'a str,
+    pub quantity: f64,
+    pub price: Option<f64>,
+    pub time_in_force: &'a str,
+}
+
+/// Orders created by `create_bracket_order`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BracketOrder {
+    pub entry: PaperTradingOrder,
+    pub stop_loss: PaperTradingOrder,
+    pub take_profit: PaperTradingOrder,
+}
+
+/// Check that the stop-loss sits on the losing and the take-profit on the winning side
+pub fn validate_bracket(entry: &BracketEntry, stop_loss: f64, take_profit: f64) -> std::result::Result<(), String> {
+    if entry.quantity <= 0.0 || stop_loss <= 0.0 || take_profit <= 0.0 {
+        return Err("Quantity, stop-loss and take-profit must be positive".to_string());
+    }
+    match (entry.order_type, entry.price) {
+        ("market", _) | ("limit", Some(_)) => {}
+        ("limit", None) => return Err("A limit entry must have a price".to_string()),
+        (other, _) => return Err(format!("Bracket entry must be a market or limit order, not '{}'", other)),
+    }
+    let (low, high) = match entry.side {
+        "buy" => (stop_loss, take_profit),
+        "sell" => (take_profit, stop_loss),
+        other => return Err(format!("Invalid side '{}'", other)),
+    };
+    // Without a limit price only the order of the two legs can be checked
+    let ordered = match entry.price {
+        Some(price) => low < price && price < high,
+        None => low < high,
+    };
+    if !ordered {
+        return Err(format!(
+            "For a {} entry the stop-loss ({}) and take-profit ({}) must lie on either side of the entry price",
+            entry.side, stop_loss, take_profit
+        ));
+    }
+    Ok(())
+}
+
+/// Create an entry order with a stop-loss and a take-profit leg, all or nothing
+///
+/// Both legs close the entry (opposite side, reduce-only), stay dormant until
+/// the entry fills and form one OCO group, so whichever fills first cancels the
+/// other.
+pub fn create_bracket_order(entry: &BracketEntry, stop_loss: f64, take_profit: f64) -> Result<BracketOrder> {
+    validate_bracket(entry, stop_loss, take_profit).map_err(|e| anyhow::anyhow!(e))?;
+    // Generated once, so a busy retry re-inserts the same orders
+    let ids = [(); 4].map(|_| uuid::Uuid::new_v4().to_string());
+    write(|conn| insert_bracket(conn, entry, stop_loss, take_profit, &ids))
+}
+
+/// `ids`: entry, stop-loss and take-profit order ids, then the OCO group id
+fn insert_bracket(
+    conn: &rusqlite::Connection,
+    entry: &BracketEntry,
+    stop_loss: f64,
+    take_profit: f64,
+    ids: &[String; 4],
+) -> Result<BracketOrder> {
+    let [entry_id, stop_id, take_profit_id, group_id] = ids;
+    let exit_side = if entry.side == "buy" { "sell" } else { "buy" };
+
+    let tx = conn.unchecked_transaction()?;
+    tx.execute(
         "INSERT INTO paper_trading_orders
          (id, portfolio_id, symbol, side, type, quantity, price, status, time_in_force, filled_quantity)
          VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, 'pending', ?8, 0)",
-        params![id, portfolio_id, symbol, side, order_type, quantity, price, time_in_force],
+        params![entry_id, entry.portfolio_id, entry.symbol, entry.side, entry.order_type, entry.quantity, entry.price, entry.time_in_force],
     )?;
-
-    Ok(())
+    for (id, order_type, price, stop_price) in [
+        (stop_id, "stop_market", None, Some(stop_loss)),
+        (take_profit_id, "limit", Some(take_profit), None),
+    ] {
+        tx.execute(
+            "INSERT INTO paper_trading_orders
+             (id, portfolio_id, symbol, side, type, quantity, price, stop_price, status, time_in_force,
+              filled_quantity, reduce_only, parent_order_id, oco_group_id)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, 'pending', 'GTC', 0, 1, ?9, ?10)",
+            params![id, entry.portfolio_id, entry.symbol, exit_side, order_type, entry.quantity, price, stop_price, entry_id, group_id],
+        )?;
+    }
+    let bracket = BracketOrder {
+        entry: load_order(&tx, entry_id)?,
+        stop_loss: load_order(&tx, stop_id)?,
+        take_profit: load_order(&tx, take_profit_id)?,
+    };
+    tx.commit()?;
+    Ok(bracket)
 }
 
 pub fn get_portfolio_orders(portfolio_id: &str, status: Option<&str>) -> Result<Vec<PaperTradingOrder>> {
-    let pool = get_pool()?;
+    let pool = get_read_pool()?;
     let conn = pool.get()?;
 
     let query = if let Some(st) = status {
         format!(
             "SELECT id, portfolio_id, symbol, side, type, quantity, price, stop_price, filled_quantity,
-                    avg_fill_price, status, time_in_force, post_only, reduce_only, created_at, filled_at, updated_at
-             FROM paper_trading_orders WHERE portfolio_id = ?1 AND status = '{}' ORDER BY created_at DESC",
+                    avg_fill_price, status, time_in_force, post_only, reduce_only, created_at, filled_at, updated_at,
+                    parent_order_id, oco_group_id
+             FROM paper_trading_orders WHERE portfolio_id = ?1 AND status = '{}' ORDER BY created_at DESC, rowid DESC",
             st
         )
     } else {
         "SELECT id, portfolio_id, symbol, side, type, quantity, price, stop_price, filled_quantity,
-                avg_fill_price, status, time_in_force, post_only, reduce_only, created_at, filled_at, updated_at
-         FROM paper_trading_orders WHERE portfolio_id = ?1 ORDER BY created_at DESC"
+                avg_fill_price, status, time_in_force, post_only, reduce_only, created_at, filled_at, updated_at,
+                parent_order_id, oco_group_id
+         FROM paper_trading_orders WHERE portfolio_id = ?1 ORDER BY created_at DESC, rowid DESC"
             .to_string()
     };
 
@@ -517,6 +1419,8 @@ pub fn get_portfolio_orders(portfolio_id: &str, status: Option<&str>) -> Result<
                 created_at: row.get(14)?,
                 filled_at: row.get(15)?,
                 updated_at: row.get(16)?,
+                parent_order_id: row.get(17)?,
+                oco_group_id: row.get(18)?,
             })
         })?
         .collect::<std::result::Result<Vec<_>, _>>()?;
@@ -526,15 +1430,18 @@ pub fn get_portfolio_orders(portfolio_id: &str, status: Option<&str>) -> Result<
 
 #[allow(dead_code)]
 pub fn update_order_status(id: &str, status: &str, filled_quantity: f64, avg_fill_price: Option<f64>) -> Result<()> {
-    let pool = get_pool()?;
-    let conn = pool.get()?;
-
-    conn.execute(
-        "UPDATE paper_trading_orders
-         SET status = ?1, filled_quantity = ?2, avg_fill_price = ?3, updated_at = CURRENT_TIMESTAMP
-         WHERE id = ?4",
-        params![status, filled_quantity, avg_fill_price, id],
-    )?;
+    write(|conn| {
+        let tx = conn.unchecked_transaction()?;
+        tx.execute(
+            "UPDATE paper_trading_orders
+             SET status = ?1, filled_quantity = ?2, avg_fill_price = ?3, updated_at = CURRENT_TIMESTAMP
+             WHERE id = ?4",
+            params![status, filled_quantity, avg_fill_price, id],
+        )?;
+        cancel_linked_orders(&tx, id, status)?;
+        tx.commit()?;
+        Ok(())
+    })?;
 
     Ok(())
 }
@@ -542,10 +1449,14 @@ pub fn update_order_status(id: &str, status: &str, filled_quantity: f64, avg_fil
 pub fn get_order(id: &str) -> Result<PaperTradingOrder> {
     let pool = get_pool()?;
     let conn = pool.get()?;
+    load_order(&conn, id)
+}
 
+fn load_order(conn: &rusqlite::Connection, id: &str) -> Result<PaperTradingOrder> {
     let order = conn.query_row(
         "SELECT id, portfolio_id, symbol, side, type, quantity, price, stop_price, filled_quantity,
-                avg_fill_price, status, time_in_force, post_only, reduce_only, created_at, filled_at, updated_at
+                avg_fill_price, status, time_in_force, post_only, reduce_only, created_at, filled_at, updated_at,
+                parent_order_id, oco_group_id
          FROM paper_trading_orders WHERE id = ?1",
         params![id],
         |row| {
@@ -567,6 +1478,8 @@ pub fn get_order(id: &str) -> Result<PaperTradingOrder> {
                 created_at: row.get(14)?,
                 filled_at: row.get(15)?,
                 updated_at: row.get(16)?,
+                parent_order_id: row.get(17)?,
+                oco_group_id: row.get(18)?,
             })
         },
     )?;
@@ -574,51 +1487,27 @@ pub fn get_order(id: &str) -> Result<PaperTradingOrder> {
     Ok(order)
 }
 
+/// Working orders for the matcher; bracket legs wait until their entry order has filled
 pub fn get_pending_orders(portfolio_id: Option<&str>) -> Result<Vec<PaperTradingOrder>> {
     let pool = get_pool()?;
     let conn = pool.get()?;
+    load_pending_orders(&conn, portfolio_id)
+}
 
-    if let Some(pid) = portfolio_id {
-        let mut stmt = conn.prepare(
-            "SELECT id, portfolio_id, symbol, side, type, quantity, price, stop_price, filled_quantity,
-                    avg_fill_price, status, time_in_force, post_only, reduce_only, created_at, filled_at, updated_at
-             FROM paper_trading_orders WHERE status IN ('pending', 'triggered', 'partial') AND portfolio_id = ?1
-             ORDER BY created_at ASC"
-        )?;
-
-        let orders = stmt.query_map(params![pid], |row| {
-            Ok(PaperTradingOrder {
-                id: row.get(0)?,
-                portfolio_id: row.get(1)?,
-                symbol: row.get(2)?,
-                side: row.get(3)?,
-                order_type: row.get(4)?,
-                quantity: row.get(5)?,
-                price: row.get(6)?,
-                stop_price: row.get(7)?,
-                filled_quantity: row.get(8)?,
-                avg_fill_price: row.get(9)?,
-                status: row.get(10)?,
-                time_in_force: row.get(11)?,
-                post_only: row.get::<_, i32>(12)? != 0,
-                reduce_only: row.get::<_, i32>(13)? != 0,
-                created_at: row.get(14)?,
-                filled_at: row.get(15)?,
-                updated_at: row.get(16)?,
-            })
-        })?
-        .collect::<std::result::Result<Vec<_>, _>>()?;
-
-        Ok(orders)
-    } else {
-        let mut stmt = conn.prepare(
-            "SELECT id, portfolio_id, symbol, side, type, quantity, price, stop_price, filled_quantity,
-                    avg_fill_price, status, time_in_force, post_only, reduce_only, created_at, filled_at, updated_at
-             FROM paper_trading_orders WHERE status IN ('pending', 'triggered', 'partial')
-             ORDER BY created_at ASC"
-        )?;
+fn load_pending_orders(conn: &rusqlite::Connection, portfolio_id: Option<&str>) -> Result<Vec<PaperTradingOrder>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, portfolio_id, symbol, side, type, quantity, price, stop_price, filled_quantity,
+                avg_fill_price, status, time_in_force, post_only, reduce_only, created_at, filled_at, updated_at,
+                parent_order_id, oco_group_id
+         FROM paper_trading_orders o
+         WHERE status IN ('pending', 'triggered', 'partial') AND (?1 IS NULL OR portfolio_id = ?1)
+           AND (parent_order_id IS NULL OR EXISTS (
+               SELECT 1 FROM paper_trading_orders p WHERE p.id = o.parent_order_id AND p.status = 'filled'))
+         ORDER BY created_at ASC, o.rowid",
+    )?;
 
-        let orders = stmt.query_map([], |row| {
+    let orders = stmt
+        .query_map(params![portfolio_id], |row| {
             Ok(PaperTradingOrder {
                 id: row.get(0)?,
                 portfolio_id: row.get(1)?,
@@ -637,12 +1526,13 @@ pub fn get_pending_orders(portfolio_id: Option<&str>) -> Result<Vec<PaperTrading
                 created_at: row.get(14)?,
                 filled_at: row.get(15)?,
                 updated_at: row.get(16)?,
+                parent_order_id: row.get(17)?,
+                oco_group_id: row.get(18)?,
             })
         })?
         .collect::<std::result::Result<Vec<_>, _>>()?;
 
-        Ok(orders)
-    }
+    Ok(orders)
 }
 
 pub fn update_order(
@@ -652,9 +1542,6 @@ pub fn update_order(
     status: Option<&str>,
     filled_at: Option<&str>,
 ) -> Result<()> {
-    let pool = get_pool()?;
-    let conn = pool.get()?;
-
     let mut updates = vec!["updated_at = CURRENT_TIMESTAMP".to_string()];
     let mut params_vec: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
 
@@ -679,23 +1566,75 @@ pub fn update_order(
     let sql = format!("UPDATE paper_trading_orders SET {} WHERE id = ?", updates.join(", "));
 
     let params_refs: Vec<&dyn rusqlite::ToSql> = params_vec.iter().map(|p| p.as_ref()).collect();
-    conn.execute(&sql, params_refs.as_slice())?;
+    write(|conn| {
+        let tx = conn.unchecked_transaction()?;
+        tx.execute(&sql, params_refs.as_slice())?;
+        if let Some(st) = status {
+            cancel_linked_orders(&tx, id, st)?;
+        }
+        tx.commit()?;
+        Ok(())
+    })?;
 
     Ok(())
 }
 
+/// Cancel the working orders that order `id` moving to `status` makes obsolete
+///
+/// Any fill of an OCO leg cancels the other legs of its group; a cancelled or
+/// rejected parent takes its (not yet active) bracket legs with it. Returns how
+/// many orders were cancelled.
+fn cancel_linked_orders(conn: &rusqlite::Connection, id: &str, status: &str) -> Result<usize> {
+    let cancelled = match status {
+        "filled" | "partial" => conn.execute(
+            "UPDATE paper_trading_orders SET status = 'cancelled', updated_at = CURRENT_TIMESTAMP
+             WHERE id != ?1 AND status IN ('pending', 'triggered', 'partial')
+               AND oco_group_id = (SELECT oco_group_id FROM paper_trading_orders WHERE id = ?1)",
+            params![id],
+        )?,
+        "cancelled" | "rejected" => conn.execute(
+            "UPDATE paper_trading_orders SET status = 'cancelled', updated_at = CURRENT_TIMESTAMP
+             WHERE parent_order_id = ?1 AND status IN ('pending', 'triggered', 'partial')",
+            params![id],
+        )?,
+        _ => 0,
+    };
+    Ok(cancelled)
+}
+
+/// Delete an order together with any bracket legs hanging off it
 pub fn delete_order(id: &str) -> Result<()> {
     let pool = get_pool()?;
     let conn = pool.get()?;
 
     conn.execute(
-        "DELETE FROM paper_trading_orders WHERE id = ?1",
+        "DELETE FROM paper_trading_orders WHERE id = ?1 OR parent_order_id = ?1",
         params![id],
     )?;
 
     Ok(())
 }
 
+/// Cancel every working order of a portfolio, optionally only for `symbol`
+///
+/// Returns how many orders were cancelled.
+pub fn cancel_all_orders(portfolio_id: &str, symbol: Option<&str>) -> Result<usize> {
+    let pool = get_pool()?;
+    let conn = pool.get()?;
+    cancel_orders(&conn, portfolio_id, symbol)
+}
+
+/// A single UPDATE, so either every matching order is cancelled or none is
+fn cancel_orders(conn: &rusqlite::Connection, portfolio_id: &str, symbol: Option<&str>) -> Result<usize> {
+    let cancelled = conn.execute(
+        "UPDATE paper_trading_orders SET status = 'cancelled', updated_at = CURRENT_TIMESTAMP
+         WHERE portfolio_id = ?1 AND status IN ('pending', 'triggered', 'partial')
+           AND (?2 IS NULL OR symbol = ?2)",
+        params![portfolio_id, symbol],
+    )?;
+    Ok(cancelled)
+}
+
 // ============================================================================
 // Trade Operations
 // ============================================================================
@@ -712,32 +1651,33 @@ pub fn create_trade(
     fee_rate: f64,
     is_maker: bool,
 ) -> Result<()> {
-    let pool = get_pool()?;
-    let conn = pool.get()?;
-
-    conn.execute(
-        "INSERT INTO paper_trading_trades
-         (id, portfolio_id, order_id, symbol, side, price, quantity, fee, fee_rate, is_maker)
-         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
-        params![id, portfolio_id, order_id, symbol, side, price, quantity, fee, fee_rate, if is_maker { 1 } else { 0 }],
-    )?;
+    let (price, quantity, fee) = (money(price)?, money(quantity)?, money(fee)?);
+    write(|conn| {
+        conn.execute(
+            "INSERT INTO paper_trading_trades
+             (id, portfolio_id, order_id, symbol, side, price, quantity, fee, fee_rate, is_maker)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+            params![id, portfolio_id, order_id, symbol, side, price, quantity, fee, fee_rate, if is_maker { 1 } else { 0 }],
+        )?;
+        Ok(())
+    })?;
 
     Ok(())
 }
 
 pub fn get_portfolio_trades(portfolio_id: &str, limit: Option<i64>) -> Result<Vec<PaperTradingTrade>> {
-    let pool = get_pool()?;
+    let pool = get_read_pool()?;
     let conn = pool.get()?;
 
     let query = if let Some(lim) = limit {
         format!(
             "SELECT id, portfolio_id, order_id, symbol, side, price, quantity, fee, fee_rate, is_maker, timestamp
-             FROM paper_trading_trades WHERE portfolio_id = ?1 ORDER BY timestamp DESC LIMIT {}",
+             FROM paper_trading_trades WHERE portfolio_id = ?1 ORDER BY timestamp DESC, rowid DESC LIMIT {}",
             lim
         )
     } else {
         "SELECT id, portfolio_id, order_id, symbol, side, price, quantity, fee, fee_rate, is_maker, timestamp
-         FROM paper_trading_trades WHERE portfolio_id = ?1 ORDER BY timestamp DESC"
+         FROM paper_trading_trades WHERE portfolio_id = ?1 ORDER BY timestamp DESC, rowid DESC"
             .to_string()
     };
 
@@ -792,12 +1732,12 @@ pub fn get_trade(id: &str) -> Result<PaperTradingTrade> {
 }
 
 pub fn get_order_trades(order_id: &str) -> Result<Vec<PaperTradingTrade>> {
-    let pool = get_pool()?;
+    let pool = get_read_pool()?;
     let conn = pool.get()?;
 
     let mut stmt = conn.prepare(
         "SELECT id, portfolio_id, order_id, symbol, side, price, quantity, fee, fee_rate, is_maker, timestamp
-         FROM paper_trading_trades WHERE order_id = ?1 ORDER BY timestamp ASC"
+         FROM paper_trading_trades WHERE order_id = ?1 ORDER BY timestamp ASC, rowid"
     )?;
 
     let trades = stmt
@@ -832,3 +1772,542 @@ pub fn delete_trade(id: &str) -> Result<()> {
 
     Ok(())
 }
+
+// ============================================================================
+// Trade Statistics
+// ============================================================================
+
+/// Win/loss figures for closed positions on one side
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SideStatistics {
+    pub closed_positions: usize,
+    pub winning: usize,
+    pub losing: usize,
+    pub realized_pnl: f64,
+}
+
+/// Performance summary of a portfolio over a date range
+///
+/// Wins and losses count closed positions by their realized P&L (before fees);
+/// open positions only contribute their partial-close P&L and unrealized P&L.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TradeStatistics {
+    pub portfolio_id: String,
+    pub from: Option<String>,
+    pub to: Option<String>,
+    /// Fills in the range
+    pub total_trades: usize,
+    /// Traded notional (price x quantity) of those fills
+    pub total_volume: f64,
+    pub total_fees: f64,
+    pub closed_positions: usize,
+    pub winning_positions: usize,
+    pub losing_positions: usize,
+    pub breakeven_positions: usize,
+    /// Winning / (winning + losing); None without decided positions
+    pub win_rate: Option<f64>,
+    pub average_win: Option<f64>,
+    pub average_loss: Option<f64>,
+    pub largest_win: Option<f64>,
+    pub largest_loss: Option<f64>,
+    pub gross_profit: f64,
+    pub gross_loss: f64,
+    /// Gross profit / gross loss; None when nothing was lost
+    pub profit_factor: Option<f64>,
+    /// Realized P&L of closed positions
+    pub realized_pnl: f64,
+    /// Realized P&L minus all fees in the range
+    pub net_pnl: f64,
+    pub open_positions: usize,
+    /// Realized by partial closes of positions that are still open
+    pub open_realized_pnl: f64,
+    pub unrealized_pnl: f64,
+    pub long: SideStatistics,
+    pub short: SideStatistics,
+}
+
+/// Whether a "YYYY-MM-DD..." timestamp falls on a day within `from..=to`
+fn within_dates(timestamp: &str, from: Option<&str>, to: Option<&str>) -> bool {
+    let day = timestamp.get(..10).unwrap_or(timestamp);
+    from.is_none_or(|from| day >= from) && to.is_none_or(|to| day <= to)
+}
+
+/// Statistics over already loaded positions and trades
+///
+/// Closed positions count when they were closed within the range, trades when
+/// they were filled within it; open positions are taken as they are now.
+pub fn compute_trade_statistics(
+    portfolio_id: &str,
+    positions: &[PaperTradingPosition],
+    trades: &[PaperTradingTrade],
+    from: Option<&str>,
+    to: Option<&str>,
+) -> TradeStatistics {
+    let mut stats = TradeStatistics {
+        portfolio_id: portfolio_id.to_string(),
+        from: from.map(str::to_string),
+        to: to.map(str::to_string),
+        ..Default::default()
+    };
+
+    let (mut volume, mut fees) = (Money::ZERO, Money::ZERO);
+    for trade in trades.iter().filter(|t| within_dates(&t.timestamp, from, to)) {
+        stats.total_trades += 1;
+        volume += exact(trade.price) * exact(trade.quantity);
+        fees += exact(trade.fee);
+    }
+
+    let (mut open_realized, mut unrealized) = (Money::ZERO, Money::ZERO);
+    let (mut long_pnl, mut short_pnl) = (Money::ZERO, Money::ZERO);
+    let mut wins = Vec::new();
+    let mut losses = Vec::new();
+    for position in positions {
+        if position.status != "closed" {
+            stats.open_positions += 1;
+            open_realized += exact(position.realized_pnl);
+            unrealized += exact(position.unrealized_pnl.unwrap_or(0.0));
+            continue;
+        }
+        let closed_in_range = position
+            .closed_at
+            .as_deref()
+            .is_some_and(|closed_at| within_dates(closed_at, from, to));
+        if !closed_in_range {
+            continue;
+        }
+
+        let pnl = exact(position.realized_pnl);
+        let (side, side_pnl) = if position.side == "short" {
+            (&mut stats.short, &mut short_pnl)
+        } else {
+            (&mut stats.long, &mut long_pnl)
+        };
+        side.closed_positions += 1;
+        *side_pnl += pnl;
+        stats.closed_positions += 1;
+        if pnl > Money::ZERO {
+            side.winning += 1;
+            wins.push(pnl);
+        } else if pnl < Money::ZERO {
+            side.losing += 1;
+            losses.push(pnl);
+        } else {
+            stats.breakeven_positions += 1;
+        }
+    }
+
+    let gross_profit: Money = wins.iter().copied().sum();
+    let gross_loss = -losses.iter().copied().sum::<Money>();
+    stats.total_volume = volume.to_f64();
+    stats.total_fees = fees.to_f64();
+    stats.open_realized_pnl = open_realized.to_f64();
+    stats.unrealized_pnl = unrealized.to_f64();
+    stats.long.realized_pnl = long_pnl.to_f64();
+    stats.short.realized_pnl = short_pnl.to_f64();
+    stats.realized_pnl = (gross_profit - gross_loss).to_f64();
+    stats.gross_profit = gross_profit.to_f64();
+    stats.gross_loss = gross_loss.to_f64();
+    stats.net_pnl = (gross_profit - gross_loss - fees).to_f64();
+    stats.winning_positions = wins.len();
+    stats.losing_positions = losses.len();
+    let decided = wins.len() + losses.len();
+    stats.win_rate = (decided > 0).then(|| wins.len() as f64 / decided as f64);
+    stats.average_win = (!wins.is_empty()).then(|| stats.gross_profit / wins.len() as f64);
+    stats.average_loss = (!losses.is_empty()).then(|| -stats.gross_loss / losses.len() as f64);
+    stats.largest_win = wins.iter().max().map(|pnl| pnl.to_f64());
+    stats.largest_loss = losses.iter().min().map(|pnl| pnl.to_f64());
+    stats.profit_factor = (stats.gross_loss > 0.0).then(|| stats.gross_profit / stats.gross_loss);
+    stats
+}
+
+/// Statistics for a portfolio; `from`/`to` are inclusive YYYY-MM-DD dates
+pub fn get_trade_statistics(portfolio_id: &str, from: Option<&str>, to: Option<&str>) -> Result<TradeStatistics> {
+    // Existence check, so a typo doesn't look like an empty portfolio
+    get_portfolio(portfolio_id)?;
+    let positions = get_portfolio_positions(portfolio_id, None)?;
+    let trades = get_portfolio_trades(portfolio_id, None)?;
+    Ok(compute_trade_statistics(portfolio_id, &positions, &trades, from, to))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cancel_orders_only_touches_working_orders() {
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        crate::database::schema::create_schema(&conn).unwrap();
+        for (id, portfolio, symbol, status) in [
+            ("o1", "p1", "BTC", "pending"),
+            ("o2", "p1", "ETH", "partial"),
+            ("o3", "p1", "BTC", "filled"),
+            ("o4", "p2", "BTC", "pending"),
+            ("o5", "p1", "BTC", "triggered"),
+        ] {
+            conn.execute(
+                "INSERT INTO paper_trading_orders (id, portfolio_id, symbol, side, type, quantity, status)
+                 VALUES (?1, ?2, ?3, 'buy', 'limit', 1, ?4)",
+                params![id, portfolio, symbol, status],
+            )
+            .unwrap();
+        }
+        let status = |id: &str| -> String {
+            conn.query_row("SELECT status FROM paper_trading_orders WHERE id = ?1", params![id], |row| row.get(0))
+                .unwrap()
+        };
+
+        assert_eq!(cancel_orders(&conn, "p1", Some("BTC")).unwrap(), 2);
+        assert_eq!(status("o1"), "cancelled");
+        assert_eq!(status("o2"), "partial");
+        assert_eq!(status("o3"), "filled");
+        assert_eq!(status("o4"), "pending");
+
+        assert_eq!(cancel_orders(&conn, "p1", None).unwrap(), 1);
+        assert_eq!(status("o2"), "cancelled");
+        assert_eq!(cancel_orders(&conn, "p1", None).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_purge_removes_only_the_portfolios_rows() {
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        crate::database::schema::create_schema(&conn).unwrap();
+        conn.execute_batch(
+            "INSERT INTO paper_trading_portfolios (id, name, provider, current_balance) VALUES ('p1', 'test', 'x', 1000), ('p2', 'keep', 'x', 1000);
+             INSERT INTO paper_trading_orders (id, portfolio_id, symbol, side, type, quantity, status)
+                 VALUES ('o1', 'p1', 'BTC', 'buy', 'market', 1, 'filled'), ('o2', 'p1', 'BTC', 'sell', 'limit', 1, 'pending'),
+                        ('o3', 'p2', 'BTC', 'buy', 'market', 1, 'filled');
+             INSERT INTO paper_trading_trades (id, portfolio_id, order_id, symbol, side, price, quantity)
+                 VALUES ('t1', 'p1', 'o1', 'BTC', 'buy', 100, 1), ('t2', 'p2', 'o3', 'BTC', 'buy', 100, 1);
+             INSERT INTO paper_trading_positions (id, portfolio_id, symbol, side, entry_price, quantity, status)
+                 VALUES ('pos1', 'p1', 'BTC', 'long', 100, 1, 'open');
+             INSERT INTO paper_trading_risk_limits (portfolio_id, max_leverage) VALUES ('p1', 5);",
+        )
+        .unwrap();
+
+        let purged = purge_portfolio_rows(&conn, "p1").unwrap();
+        assert_eq!(
+            purged,
+            PurgedPortfolio { portfolios: 1, positions: 1, orders: 2, trades: 1, risk_limits: 1, snapshots: 0 }
+        );
+        let count = |table: &str| -> i64 {
+            conn.query_row(&format!("SELECT COUNT(*) FROM {}", table), [], |row| row.get(0)).unwrap()
+        };
+        assert_eq!((count("paper_trading_portfolios"), count("paper_trading_orders"), count("paper_trading_trades")), (1, 1, 1));
+        assert!(purge_portfolio_rows(&conn, "p1").is_err());
+    }
+
+    #[test]
+    fn test_bracket_legs_wait_for_entry_and_cancel_each_other() {
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        crate::database::schema::create_schema(&conn).unwrap();
+        let entry = BracketEntry {
+            portfolio_id: "p1",
+            symbol: "BTC",
+            side: "buy",
+            order_type: "limit",
+            quantity: 2.0,
+            price: Some(100.0),
+            time_in_force: "GTC",
+        };
+        let ids = |prefix: &str| ["entry", "stop", "tp", "group"].map(|name| format!("{}-{}", prefix, name));
+        let pending = |conn: &rusqlite::Connection| -> Vec<String> {
+            let mut ids: Vec<String> = load_pending_orders(conn, Some("p1")).unwrap().into_iter().map(|o| o.id).collect();
+            ids.sort();
+            ids
+        };
+        let set_status = |conn: &rusqlite::Connection, id: &str, status: &str| {
+            conn.execute("UPDATE paper_trading_orders SET status = ?1 WHERE id = ?2", params![status, id]).unwrap();
+            cancel_linked_orders(conn, id, status).unwrap()
+        };
+
+        let bracket = insert_bracket(&conn, &entry, 95.0, 110.0, &ids("a")).unwrap();
+        assert_eq!((bracket.stop_loss.side.as_str(), bracket.stop_loss.stop_price), ("sell", Some(95.0)));
+        assert_eq!((bracket.take_profit.order_type.as_str(), bracket.take_profit.price), ("limit", Some(110.0)));
+        assert!(bracket.take_profit.reduce_only);
+        assert_eq!(bracket.stop_loss.parent_order_id.as_deref(), Some("a-entry"));
+        assert_eq!(pending(&conn), vec!["a-entry"]);
+
+        // The entry fills: both legs go live; the take-profit fills and cancels the stop
+        assert_eq!(set_status(&conn, "a-entry", "filled"), 0);
+        assert_eq!(pending(&conn), vec!["a-stop", "a-tp"]);
+        assert_eq!(set_status(&conn, "a-tp", "filled"), 1);
+        assert_eq!(load_order(&conn, "a-stop").unwrap().status, "cancelled");
+        assert!(pending(&conn).is_empty());
+
+        // Cancelling an unfilled entry cancels its legs
+        insert_bracket(&conn, &entry, 95.0, 110.0, &ids("b")).unwrap();
+        assert_eq!(set_status(&conn, "b-entry", "cancelled"), 2);
+        assert_eq!(load_order(&conn, "b-tp").unwrap().status, "cancelled");
+
+        assert!(validate_bracket(&entry, 105.0, 110.0).is_err());
+        assert!(validate_bracket(&BracketEntry { side: "sell", price: None, order_type: "market", ..entry.clone() }, 110.0, 95.0).is_ok());
+        assert!(validate_bracket(&BracketEntry { price: None, ..entry }, 95.0, 110.0).is_err());
+    }
+
+    #[test]
+    fn test_reduce_position_realizes_pnl_and_closes() {
+        let mut conn = rusqlite::Connection::open_in_memory().unwrap();
+        crate::database::schema::create_schema(&conn).unwrap();
+        conn.execute_batch(
+            "INSERT INTO paper_trading_portfolios (id, name, provider, initial_balance, current_balance)
+             VALUES ('p1', 'Test', 'paper', 1000, 1000);
+             INSERT INTO paper_trading_positions (id, portfolio_id, symbol, side, entry_price, quantity, status)
+             VALUES ('long', 'p1', 'BTC', 'long', 100, 4, 'open'),
+                    ('short', 'p1', 'ETH', 'short', 50, 2, 'open');",
+        )
+        .unwrap();
+
+        let r = apply_reduction(&mut conn, "long", 1.5, 110.0, 0.5).unwrap();
+        assert_eq!(r.realized_pnl, 15.0);
+        assert_eq!(r.balance, 1014.5);
+        assert_eq!((r.position.quantity, r.position.status.as_str()), (2.5, "open"));
+        assert_eq!(r.position.realized_pnl, 15.0);
+        assert_eq!(r.position.position_value, Some(250.0));
+
+        // Shorts gain when the price falls; closing the rest closes the position
+        let r = apply_reduction(&mut conn, "short", 2.0, 45.0, 0.0).unwrap();
+        assert_eq!(r.realized_pnl, 10.0);
+        assert_eq!(r.balance, 1024.5);
+        assert_eq!(r.position.status, "closed");
+        assert!(r.position.closed_at.is_some());
+
+        let err = apply_reduction(&mut conn, "long", 3.0, 90.0, 0.0).unwrap_err().to_string();
+        assert!(err.contains("exceeds position size"), "{}", err);
+        assert!(apply_reduction(&mut conn, "short", 1.0, 45.0, 0.0).is_err());
+        let balance: Money = conn
+            .query_row("SELECT current_balance FROM paper_trading_portfolios WHERE id = 'p1'", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(balance, exact(1024.5));
+    }
+
+    #[test]
+    fn test_market_open_writes_order_trade_and_position_together() {
+        let mut conn = rusqlite::Connection::open_in_memory().unwrap();
+        crate::database::schema::create_schema(&conn).unwrap();
+        conn.execute_batch(
+            "INSERT INTO paper_trading_portfolios (id, name, provider, initial_balance, current_balance)
+             VALUES ('p1', 'Test', 'paper', 1000, 1000);",
+        )
+        .unwrap();
+        let ids = |n: u32| [0, 1, 2].map(|i| format!("{}-{}", n, i));
+        let liquidation = |entry: f64, _qty: f64, leverage: f64| Some(entry * (1.0 - 1.0 / leverage));
+        let mut open = MarketOpen {
+            portfolio_id: "p1",
+            symbol: "BTC",
+            side: "long",
+            quantity: 2.0,
+            fill_price: 100.0,
+            leverage: 4.0,
+            margin_mode: "isolated",
+            fee_rate: 0.001,
+        };
+
+        let r = apply_market_open(&mut conn, &open, &ids(1), &liquidation).unwrap();
+        assert_eq!((r.fee, r.margin, r.balance), (0.2, 50.0, 999.8));
+        assert_eq!((r.position.id.as_str(), r.position.quantity), ("1-2", 2.0));
+        assert_eq!(r.position.liquidation_price, Some(75.0));
+        let order: (String, String, f64, f64) = conn
+            .query_row(
+                "SELECT side, status, filled_quantity, avg_fill_price FROM paper_trading_orders WHERE id = '1-0'",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+            )
+            .unwrap();
+        assert_eq!(order, ("buy".to_string(), "filled".to_string(), 2.0, 100.0));
+        let fee: f64 = conn
+            .query_row("SELECT fee FROM paper_trading_trades WHERE order_id = '1-0'", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(fee, 0.2);
+
+        // Same side adds at the volume-weighted entry, keeping the position's leverage
+        // for the position and the margin check alike
+        open.quantity = 2.0;
+        open.fill_price = 200.0;
+        open.leverage = 10.0;
+        let r = apply_market_open(&mut conn, &open, &ids(2), &liquidation).unwrap();
+        assert_eq!((r.position.id.as_str(), r.position.quantity, r.position.entry_price), ("1-2", 4.0, 150.0));
+        assert_eq!((r.margin, r.position.leverage), (100.0, 4.0));
+        assert_eq!(r.position.liquidation_price, Some(112.5));
+        assert_eq!(r.balance, 999.4);
+
+        // The opposite side and fills the free balance can't margin write nothing
+        open.side = "short";
+        let err = apply_market_open(&mut conn, &open, &ids(3), &liquidation).unwrap_err().to_string();
+        assert!(err.contains("close it before"), "{}", err);
+        open.symbol = "ETH";
+        open.quantity = 100.0;
+        open.leverage = 1.0;
+        let err = apply_market_open(&mut conn, &open, &ids(4), &liquidation).unwrap_err().to_string();
+        assert!(err.starts_with("Insufficient funds"), "{}", err);
+        let orders: i64 = conn.query_row("SELECT COUNT(*) FROM paper_trading_orders", [], |row| row.get(0)).unwrap();
+        assert_eq!(orders, 2);
+    }
+
+    #[test]
+    fn test_balance_stays_exact_over_many_fills() {
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        crate::database::schema::create_schema(&conn).unwrap();
+        conn.execute_batch(
+            "INSERT INTO paper_trading_portfolios (id, name, provider, initial_balance, current_balance)
+             VALUES ('p1', 'Test', 'paper', 10000, 10000);",
+        )
+        .unwrap();
+
+        // 5,000 round trips: +0.3 PnL, -0.1 and -0.07 fees, each written back to SQLite
+        let mut float = 10_000.0_f64;
+        for _ in 0..5_000 {
+            for delta in [0.3, -0.1, -0.07] {
+                apply_balance_delta(&conn, "p1", exact(delta)).unwrap();
+                float += delta;
+            }
+        }
+        let (balance, stored): (Money, String) = conn
+            .query_row("SELECT current_balance, typeof(current_balance) FROM paper_trading_portfolios WHERE id = 'p1'", [], |row| {
+                Ok((row.get(0)?, row.get(1)?))
+            })
+            .unwrap();
+        assert_eq!((balance, stored.as_str()), (exact(10_650.0), "text"));
+        assert_ne!(float, 10_650.0);
+        assert!(apply_balance_delta(&conn, "missing", exact(1.0)).is_err());
+    }
+
+    #[test]
+    fn test_marks_open_positions_in_one_pass() {
+        let mut conn = rusqlite::Connection::open_in_memory().unwrap();
+        crate::database::schema::create_schema(&conn).unwrap();
+        conn.execute_batch(
+            "INSERT INTO paper_trading_portfolios (id, name, provider, initial_balance, current_balance)
+             VALUES ('p1', 'Test', 'paper', 1000, 1000);
+             INSERT INTO paper_trading_positions (id, portfolio_id, symbol, side, entry_price, quantity, leverage, unrealized_pnl, status)
+             VALUES ('btc', 'p1', 'BTC', 'long', 100, 2, 1, NULL, 'open'),
+                    ('eth', 'p1', 'ETH', 'short', 50, 4, 5, NULL, 'open'),
+                    ('sol', 'p1', 'SOL', 'long', 10, 1, 1, 3, 'open'),
+                    ('old', 'p1', 'BTC', 'long', 80, 1, 1, 0, 'closed');",
+        )
+        .unwrap();
+        let prices = HashMap::from([("BTC".to_string(), 110.0), ("ETH".to_string(), 45.0)]);
+
+        let update = apply_marks(&mut conn, "p1", &prices).unwrap();
+        let marks: Vec<_> = update
+            .positions
+            .iter()
+            .map(|m| (m.position_id.as_str(), m.unrealized_pnl, m.return_on_margin))
+            .collect();
+        assert!(marks.contains(&("btc", 20.0, Some(10.0))));
+        assert!(marks.contains(&("eth", 20.0, Some(50.0))));
+        assert_eq!(marks.len(), 2);
+        assert_eq!(update.missing_prices, vec!["SOL"]);
+        assert_eq!(update.total_unrealized_pnl, 43.0);
+
+        let eth = load_position(&conn, "eth").unwrap();
+        assert_eq!((eth.current_price, eth.unrealized_pnl), (Some(45.0), Some(20.0)));
+        assert_eq!(load_position(&conn, "old").unwrap().current_price, None);
+        assert!(apply_marks(&mut conn, "missing", &prices).unwrap_err().to_string().contains("not found"));
+    }
+
+    #[test]
+    fn test_ingested_marks_span_portfolios_and_snapshot_them() {
+        let mut conn = rusqlite::Connection::open_in_memory().unwrap();
+        crate::database::schema::create_schema(&conn).unwrap();
+        conn.execute_batch(
+            "INSERT INTO paper_trading_portfolios (id, name, provider, current_balance)
+             VALUES ('p1', 'a', 'paper', 1000), ('p2', 'b', 'paper', 500), ('p3', 'c', 'paper', 100);
+             INSERT INTO paper_trading_positions (id, portfolio_id, symbol, side, entry_price, quantity, unrealized_pnl, status)
+             VALUES ('a1', 'p1', 'AAPL', 'long', 100, 2, NULL, 'open'),
+                    ('a2', 'p1', 'MSFT', 'short', 50, 1, 4, 'open'),
+                    ('b1', 'p2', 'AAPL', 'short', 120, 1, NULL, 'open'),
+                    ('c1', 'p3', 'TSLA', 'long', 10, 1, NULL, 'open'),
+                    ('old', 'p3', 'AAPL', 'long', 80, 1, 0, 'closed');",
+        )
+        .unwrap();
+        let mark = |price: f64, timestamp: &str| PriceMark { price, timestamp: timestamp.to_string() };
+        let marks = HashMap::from([
+            ("AAPL".to_string(), mark(110.0, "2024-05-01")),
+            ("NVDA".to_string(), mark(900.0, "2024-05-01")),
+        ]);
+
+        let ingestion = apply_ingested_marks(&mut conn, &marks).unwrap();
+        assert_eq!(ingestion.positions_updated, 2);
+        assert_eq!(ingestion.unmatched_symbols, vec!["NVDA"]);
+        let snapshots: Vec<_> = ingestion
+            .snapshots
+            .iter()
+            .map(|s| (s.portfolio_id.as_str(), s.unrealized_pnl, s.equity, s.open_positions, s.as_of.as_str()))
+            .collect();
+        assert_eq!(
+            snapshots,
+            vec![("p1", 24.0, 1024.0, 2, "2024-05-01"), ("p2", 10.0, 510.0, 1, "2024-05-01")]
+        );
+
+        assert_eq!(load_position(&conn, "b1").unwrap().current_price, Some(110.0));
+        assert_eq!(load_position(&conn, "old").unwrap().current_price, None);
+        let stored: i64 = conn
+            .query_row("SELECT COUNT(*) FROM paper_trading_snapshots", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(stored, 2);
+    }
+
+    fn position(side: &str, status: &str, realized_pnl: f64, closed_at: Option<&str>) -> PaperTradingPosition {
+        PaperTradingPosition {
+            id: uuid::Uuid::new_v4().to_string(),
+            portfolio_id: "p1".to_string(),
+            symbol: "BTC".to_string(),
+            side: side.to_string(),
+            entry_price: 100.0,
+            quantity: 1.0,
+            position_value: None,
+            current_price: None,
+            unrealized_pnl: Some(if status == "open" { 7.0 } else { 0.0 }),
+            realized_pnl,
+            leverage: 1.0,
+            margin_mode: "cross".to_string(),
+            liquidation_price: None,
+            opened_at: "2024-01-01 09:00:00".to_string(),
+            closed_at: closed_at.map(str::to_string),
+            status: status.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_trade_statistics_split_wins_losses_and_sides() {
+        let positions = [
+            position("long", "closed", 30.0, Some("2024-01-02 10:00:00")),
+            position("short", "closed", -10.0, Some("2024-01-03 10:00:00")),
+            position("short", "closed", 10.0, Some("2024-01-03 11:00:00")),
+            position("long", "closed", 0.0, Some("2024-01-04 10:00:00")),
+            position("long", "closed", 50.0, Some("2023-12-31 10:00:00")),
+            position("long", "open", 5.0, None),
+        ];
+        let trade = |timestamp: &str, fee: f64| PaperTradingTrade {
+            id: uuid::Uuid::new_v4().to_string(),
+            portfolio_id: "p1".to_string(),
+            order_id: "o1".to_string(),
+            symbol: "BTC".to_string(),
+            side: "buy".to_string(),
+            price: 100.0,
+            quantity: 2.0,
+            fee,
+            fee_rate: 0.001,
+            is_maker: false,
+            timestamp: timestamp.to_string(),
+        };
+        let trades = [trade("2024-01-02 10:00:00", 1.0), trade("2024-01-03 10:00:00", 2.0), trade("2023-12-31 10:00:00", 4.0)];
+
+        let stats = compute_trade_statistics("p1", &positions, &trades, Some("2024-01-01"), Some("2024-01-31"));
+
+        assert_eq!((stats.total_trades, stats.total_volume, stats.total_fees), (2, 400.0, 3.0));
+        assert_eq!(stats.closed_positions, 4);
+        assert_eq!((stats.winning_positions, stats.losing_positions, stats.breakeven_positions), (2, 1, 1));
+        assert_eq!(stats.win_rate, Some(2.0 / 3.0));
+        assert_eq!((stats.average_win, stats.average_loss), (Some(20.0), Some(-10.0)));
+        assert_eq!((stats.largest_win, stats.largest_loss), (Some(30.0), Some(-10.0)));
+        assert_eq!(stats.profit_factor, Some(4.0));
+        assert_eq!((stats.realized_pnl, stats.net_pnl), (30.0, 27.0));
+        assert_eq!((stats.open_positions, stats.open_realized_pnl, stats.unrealized_pnl), (1, 5.0, 7.0));
+        assert_eq!(stats.long, SideStatistics { closed_positions: 2, winning: 1, losing: 0, realized_pnl: 30.0 });
+        assert_eq!(stats.short, SideStatistics { closed_positions: 2, winning: 1, losing: 1, realized_pnl: 0.0 });
+
+        let empty = compute_trade_statistics("p1", &[], &[], None, None);
+        assert_eq!((empty.win_rate, empty.profit_factor), (None, None));
+    }
+}
@@ -1,67 +1,272 @@
 // Connection Pool Management - Singleton with r2d2 for concurrent access
+//
+// Two pools share the database file. The read-write pool owns the schema and all
+// writes; the read-only pool (`get_read_pool`) serves the large list queries
+// (trades, positions, orders, backtest runs). In WAL mode readers never block the
+// writer, but on one pool a burst of heavy reads could use up the connections a
+// write transaction is waiting for. Read connections are opened read-only with
+// `PRAGMA query_only`, so a write routed there by mistake fails instead of
+// silently competing. Cache, mmap and temp-store pragmas apply to both pools and
+// can be tuned with the FINCEPT_SQLITE_* environment variables. Writes that can
+// race the monitoring service go through `write`, which retries on a locked
+// database (see retry.rs).
 
+use super::retry::{self, BusyStats};
+use super::status::{self, DbHealth, DbStatus};
 use anyhow::{Context, Result};
 use once_cell::sync::OnceCell;
-use parking_lot::RwLock;
 use r2d2::Pool;
 use r2d2_sqlite::SqliteConnectionManager;
-use rusqlite::OpenFlags;
+use rusqlite::{Connection, OpenFlags};
+use serde::Serialize;
+use std::path::Path;
 use std::sync::Arc;
 
 pub type DbPool = Arc<Pool<SqliteConnectionManager>>;
 
-static POOL: OnceCell<RwLock<Option<DbPool>>> = OnceCell::new();
+pub const CACHE_SIZE_ENV: &str = "FINCEPT_SQLITE_CACHE_SIZE";
+pub const MMAP_SIZE_ENV: &str = "FINCEPT_SQLITE_MMAP_SIZE";
+pub const TEMP_STORE_ENV: &str = "FINCEPT_SQLITE_TEMP_STORE";
 
-/// Get database connection pool (lazy initialized)
-pub fn get_pool() -> Result<DbPool> {
-    let pool_lock = POOL.get_or_init(|| RwLock::new(None));
-    let pool_read = pool_lock.read();
+const READ_POOL_SIZE: u32 = 8;
+
+/// Tuning pragmas run on every new connection of both pools
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConnectionPragmas {
+    /// Pages when positive, KiB when negative (SQLite's convention)
+    pub cache_size: i64,
+    /// Bytes of the file to memory-map; 0 disables mmap
+    pub mmap_size: i64,
+    /// "default", "file" or "memory"
+    pub temp_store: String,
+}
+
+impl Default for ConnectionPragmas {
+    fn default() -> Self {
+        Self {
+            cache_size: -64000,
+            mmap_size: 30_000_000_000,
+            temp_store: "memory".to_string(),
+        }
+    }
+}
+
+impl ConnectionPragmas {
+    /// Defaults overridden by FINCEPT_SQLITE_CACHE_SIZE / _MMAP_SIZE / _TEMP_STORE
+    pub fn from_env() -> Self {
+        Self::from_lookup(|name| std::env::var(name).ok())
+    }
+
+    /// Invalid values are logged and replaced by the default
+    fn from_lookup(lookup: impl Fn(&str) -> Option<String>) -> Self {
+        let mut pragmas = Self::default();
+        let value = |name: &str| lookup(name).map(|v| v.trim().to_lowercase()).filter(|v| !v.is_empty());
+        let ignore = |var: &str, value: &str| tracing::warn!(var, value, "Ignoring invalid SQLite pragma override");
+
+        if let Some(raw) = value(CACHE_SIZE_ENV) {
+            match raw.parse() {
+                Ok(cache_size) => pragmas.cache_size = cache_size,
+                Err(_) => ignore(CACHE_SIZE_ENV, &raw),
+            }
+        }
+        if let Some(raw) = value(MMAP_SIZE_ENV) {
+            match raw.parse::<i64>() {
+                Ok(mmap_size) if mmap_size >= 0 => pragmas.mmap_size = mmap_size,
+                _ => ignore(MMAP_SIZE_ENV, &raw),
+            }
+        }
+        if let Some(raw) = value(TEMP_STORE_ENV) {
+            match raw.as_str() {
+                "default" | "file" | "memory" => pragmas.temp_store = raw,
+                _ => ignore(TEMP_STORE_ENV, &raw),
+            }
+        }
+        pragmas
+    }
+
+    fn apply(&self, conn: &Connection) -> rusqlite::Result<()> {
+        conn.busy_timeout(retry::BUSY_TIMEOUT)?;
+        conn.execute_batch(&format!(
+            "PRAGMA cache_size = {};
+             PRAGMA temp_store = {};
+             PRAGMA mmap_size = {};",
+            self.cache_size, self.temp_store, self.mmap_size
+        ))
+    }
+}
 
-    if let Some(pool) = pool_read.as_ref() {
+// Set exactly once. `get_or_try_init` blocks concurrent callers until the first
+// one finishes, so the pool and schema are never created twice; a failed attempt
+// leaves the cell empty and the next caller retries.
+static POOL: OnceCell<DbPool> = OnceCell::new();
+static READ_POOL: OnceCell<DbPool> = OnceCell::new();
+
+/// Get database connection pool, initializing it on first use
+///
+/// After a failed initialization this returns the "Database unavailable" error
+/// straight away; `retry_init` is the way back.
+pub fn get_pool() -> Result<DbPool> {
+    let current = status::current();
+    if current.state == DbHealth::RestartRequired {
+        return Err(status::unavailable_error(&current));
+    }
+    if let Some(pool) = POOL.get() {
         return Ok(Arc::clone(pool));
     }
+    if current.state == DbHealth::Failed {
+        return Err(status::unavailable_error(&current));
+    }
+    init_pool()
+}
 
-    drop(pool_read);
-    Err(anyhow::anyhow!("Database not initialized. Call init_database first."))
+/// Get the read-only pool for large pure-read queries
+///
+/// Subject to the same availability checks as `get_pool`, whose initialization
+/// (file creation, rekey, schema) has to happen first.
+pub fn get_read_pool() -> Result<DbPool> {
+    get_pool()?;
+    READ_POOL
+        .get_or_try_init(|| {
+            let manager = read_only_manager(&get_db_path()?, ConnectionPragmas::from_env());
+            let pool = Pool::builder()
+                .max_size(READ_POOL_SIZE)
+                .min_idle(Some(1))
+                .connection_timeout(std::time::Duration::from_secs(5))
+                .build(manager)
+                .context("Failed to create read-only connection pool")?;
+            Ok::<_, anyhow::Error>(Arc::new(pool))
+        })
+        .map(Arc::clone)
 }
 
 /// Initialize database connection pool with optimal settings
+///
+/// Idempotent: repeated or concurrent calls return the same pool.
 pub async fn init_database() -> Result<DbPool> {
-    let pool_lock = POOL.get_or_init(|| RwLock::new(None));
+    init_pool()
+}
 
-    // Check if already initialized
-    {
-        let pool_read = pool_lock.read();
-        if let Some(pool) = pool_read.as_ref() {
-            return Ok(Arc::clone(pool));
+fn init_pool() -> Result<DbPool> {
+    let result = init_once(&POOL, || create_pool(&get_db_path()?));
+    match &result {
+        Ok(_) => {
+            // Don't let an idempotent re-init mask a degraded pool
+            if !matches!(status::current().state, DbHealth::Degraded | DbHealth::RestartRequired) {
+                status::set(DbHealth::Healthy, None);
+            }
         }
+        Err(e) => status::set(DbHealth::Failed, Some(format!("{:#}", e))),
+    }
+    result
+}
+
+/// The pool in `cell`, created by `create` on the first call
+fn init_once(cell: &OnceCell<DbPool>, create: impl FnOnce() -> Result<DbPool>) -> Result<DbPool> {
+    cell.get_or_try_init(create).map(Arc::clone)
+}
+
+/// Whether the pool has been created
+pub fn is_initialized() -> bool {
+    POOL.get().is_some()
+}
+
+/// Run a write on a pooled connection, retrying while the database is locked
+///
+/// `op` may run more than once, so it must be a single statement or its own
+/// transaction.
+pub fn write<T>(mut op: impl FnMut(&Connection) -> Result<T>) -> Result<T> {
+    let pool = get_pool()?;
+    let conn = pool.get()?;
+    retry::with_busy_retry(|| op(&conn))
+}
+
+/// Connection pool usage and lock contention counters
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DbStats {
+    pub connections: u32,
+    pub idle_connections: u32,
+    pub read_connections: u32,
+    pub read_idle_connections: u32,
+    #[serde(flatten)]
+    pub busy: BusyStats,
+}
+
+/// Current stats; pools that don't exist yet report zero connections
+pub fn stats() -> DbStats {
+    let mut stats = DbStats {
+        busy: retry::stats(),
+        ..Default::default()
+    };
+    if let Some(pool) = POOL.get() {
+        let state = pool.state();
+        stats.connections = state.connections;
+        stats.idle_connections = state.idle_connections;
+    }
+    if let Some(pool) = READ_POOL.get() {
+        let state = pool.state();
+        stats.read_connections = state.connections;
+        stats.read_idle_connections = state.idle_connections;
+    }
+    stats
+}
+
+/// Re-attempt initialization if it failed, then probe the pool
+pub fn retry_init() -> DbStatus {
+    if POOL.get().is_none() {
+        let _ = init_pool();
+    }
+    probe()
+}
+
+/// Check that the pool can hand out a working connection and update the status
+pub fn probe() -> DbStatus {
+    let Some(pool) = POOL.get() else {
+        return status::current();
+    };
+    if status::current().state == DbHealth::RestartRequired {
+        return status::current();
     }
 
-    // Initialize pool
-    let mut pool_write = pool_lock.write();
+    let check = pool
+        .get()
+        .context("Failed to get connection")
+        .and_then(|conn| {
+            conn.query_row("SELECT 1", [], |_| Ok(()))
+                .context("Test query failed")
+        });
 
-    // Get database path
-    let db_path = get_db_path()?;
+    match check {
+        Ok(()) => status::set(DbHealth::Healthy, None),
+        Err(e) => status::set(DbHealth::Degraded, Some(format!("{:#}", e))),
+    }
+    status::current()
+}
+
+fn create_pool(db_path: &Path) -> Result<DbPool> {
+    // Finish a pending rekey and fail early on a wrong/missing passphrase
+    super::encryption::apply_pending_rekey(db_path)?;
+    super::encryption::check_key(db_path)?;
 
     // Create connection manager with optimizations
-    let manager = SqliteConnectionManager::file(&db_path)
+    let pragmas = ConnectionPragmas::from_env();
+    let manager = SqliteConnectionManager::file(db_path)
         .with_flags(
             OpenFlags::SQLITE_OPEN_READ_WRITE
                 | OpenFlags::SQLITE_OPEN_CREATE
                 | OpenFlags::SQLITE_OPEN_NO_MUTEX, // Faster with connection pool
         )
-        .with_init(|conn| {
+        .with_init(move |conn| {
+            // SQLCipher key must be the first statement on the connection
+            super::encryption::apply_key(conn)?;
+
             // Performance optimizations
             conn.execute_batch(
                 "PRAGMA journal_mode = WAL;
                  PRAGMA synchronous = NORMAL;
-                 PRAGMA cache_size = -64000;
-                 PRAGMA temp_store = MEMORY;
-                 PRAGMA mmap_size = 30000000000;
                  PRAGMA page_size = 4096;
                  PRAGMA foreign_keys = ON;",
             )?;
-            Ok(())
+            pragmas.apply(conn)
         });
 
     // Create pool with optimal settings
@@ -80,11 +285,20 @@ pub async fn init_database() -> Result<DbPool> {
         crate::database::schema::create_schema(&conn)?;
     }
 
-    *pool_write = Some(Arc::clone(&pool_arc));
-
     Ok(pool_arc)
 }
 
+/// Connections that can only read: opened read-only and with `query_only` set
+fn read_only_manager(db_path: &Path, pragmas: ConnectionPragmas) -> SqliteConnectionManager {
+    SqliteConnectionManager::file(db_path)
+        .with_flags(OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX)
+        .with_init(move |conn| {
+            super::encryption::apply_key(conn)?;
+            conn.execute_batch("PRAGMA query_only = ON;")?;
+            pragmas.apply(conn)
+        })
+}
+
 /// Get database file path - platform-specific app data directories
 pub fn get_db_path() -> Result<std::path::PathBuf> {
     let db_dir = get_data_dir()?;
@@ -103,8 +317,9 @@ pub fn get_excel_db_path() -> Result<std::path::PathBuf> {
 }
 
 fn get_data_dir() -> Result<std::path::PathBuf> {
-    let db_dir = if let Ok(path) = std::env::var("FINCEPT_DATA_DIR") {
-        std::path::PathBuf::from(path)
+    // Portable mode (--data-dir / FINCEPT_DATA_DIR) takes precedence
+    let db_dir = if let Some(path) = crate::utils::data_dir::portable_data_dir() {
+        path
     } else if cfg!(target_os = "windows") {
         // Windows: %APPDATA%\fincept-terminal
         let appdata = std::env::var("APPDATA")
@@ -130,3 +345,102 @@ fn get_data_dir() -> Result<std::path::PathBuf> {
     std::fs::create_dir_all(&db_dir).context("Failed to create database directory")?;
     Ok(db_dir)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_concurrent_initialize_creates_pool_once() {
+        // A pool of its own in a temp dir, leaving the data dir and `POOL` alone
+        let dir = std::env::temp_dir().join(format!("fincept_db_init_test_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let db_path = dir.join("fincept_terminal.db");
+        let cell = OnceCell::new();
+        let creations = std::sync::atomic::AtomicUsize::new(0);
+
+        let pools: Vec<DbPool> = std::thread::scope(|scope| {
+            let handles: Vec<_> = (0..16)
+                .map(|_| {
+                    scope.spawn(|| {
+                        init_once(&cell, || {
+                            creations.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                            create_pool(&db_path)
+                        })
+                        .map_err(|e| e.to_string())
+                    })
+                })
+                .collect();
+            handles.into_iter().map(|handle| handle.join().unwrap().unwrap()).collect()
+        });
+
+        assert!(pools.iter().all(|p| Arc::ptr_eq(p, &pools[0])));
+        assert_eq!(creations.load(std::sync::atomic::Ordering::SeqCst), 1);
+        let tables: i64 = pools[0]
+            .get()
+            .unwrap()
+            .query_row("SELECT COUNT(*) FROM sqlite_master WHERE name = 'settings'", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(tables, 1);
+
+        drop(pools);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_pragma_overrides() {
+        let env = |vars: &'static [(&'static str, &'static str)]| {
+            move |name: &str| vars.iter().find(|(k, _)| *k == name).map(|(_, v)| v.to_string())
+        };
+
+        assert_eq!(ConnectionPragmas::from_lookup(env(&[])), ConnectionPragmas::default());
+
+        let pragmas = ConnectionPragmas::from_lookup(env(&[
+            (CACHE_SIZE_ENV, "-128000"),
+            (MMAP_SIZE_ENV, "0"),
+            (TEMP_STORE_ENV, " FILE "),
+        ]));
+        assert_eq!(pragmas.cache_size, -128000);
+        assert_eq!(pragmas.mmap_size, 0);
+        assert_eq!(pragmas.temp_store, "file");
+
+        // Invalid values fall back to the defaults
+        let pragmas = ConnectionPragmas::from_lookup(env(&[
+            (MMAP_SIZE_ENV, "-1"),
+            (TEMP_STORE_ENV, "memory; DROP TABLE settings"),
+        ]));
+        assert_eq!(pragmas, ConnectionPragmas::default());
+    }
+
+    #[test]
+    fn test_read_only_pool_rejects_writes() {
+        let path = std::env::temp_dir().join(format!("fincept_read_pool_test_{}.db", uuid::Uuid::new_v4()));
+        // Kept open like the read-write pool is in the app
+        let writer = Connection::open(&path).unwrap();
+        writer
+            .execute_batch(
+                "PRAGMA journal_mode = WAL;
+                 CREATE TABLE t (v INTEGER);
+                 INSERT INTO t VALUES (1);",
+            )
+            .unwrap();
+
+        let pool = Pool::builder()
+            .max_size(1)
+            .build(read_only_manager(&path, ConnectionPragmas::default()))
+            .unwrap();
+        let conn = pool.get().unwrap();
+        let v: i64 = conn.query_row("SELECT v FROM t", [], |row| row.get(0)).unwrap();
+        assert_eq!(v, 1);
+        assert!(conn.execute("INSERT INTO t VALUES (2)", []).is_err());
+        let query_only: i64 = conn.query_row("PRAGMA query_only", [], |row| row.get(0)).unwrap();
+        assert_eq!(query_only, 1);
+
+        drop(conn);
+        drop(pool);
+        drop(writer);
+        for suffix in ["", "-wal", "-shm"] {
+            let _ = std::fs::remove_file(format!("{}{}", path.display(), suffix));
+        }
+    }
+}
@@ -0,0 +1,263 @@
+// Config Profiles - named sets of settings, credentials and data sources
+//
+// What: Every row of `settings`, `credentials` and `data_sources` belongs to a
+//       profile (`default` unless created otherwise). Reads and writes through
+//       `operations` only see the active profile, so switching from `dev` to
+//       `prod` swaps API keys, endpoints and preferences in one step.
+//       `create_profile` can start from a copy of another profile.
+// Why:  Keeping a paper account and a live account, or a staging and production
+//       backend, meant re-entering credentials every time.
+// How:  Profiles are listed in `config_profiles`. The active one is the
+//       `active_profile` setting, which always lives in the `default` profile so
+//       it can be read before the active profile is known. A name that no longer
+//       exists reads as `default`. `default` itself can't be deleted, and neither
+//       can the active profile.
+
+use super::pool::{self, get_pool};
+use anyhow::{bail, Result};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+
+pub const DEFAULT_PROFILE: &str = "default";
+
+/// Setting naming the active profile, stored in the `default` profile
+pub const ACTIVE_PROFILE_SETTING: &str = "active_profile";
+
+const MAX_NAME_LEN: usize = 64;
+
+/// Tables whose rows are scoped to a profile
+const PROFILE_TABLES: &[&str] = &["settings", "credentials", "data_sources"];
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ConfigProfile {
+    pub name: String,
+    pub active: bool,
+    pub settings: i64,
+    pub credentials: i64,
+    pub data_sources: i64,
+    pub created_at: Option<String>,
+}
+
+/// Name of the active profile
+pub fn active_profile(conn: &Connection) -> Result<String> {
+    let active = conn
+        .query_row(
+            "SELECT p.name FROM config_profiles p
+             JOIN settings s ON s.setting_value = p.name
+             WHERE s.profile = ?1 AND s.setting_key = ?2",
+            params![DEFAULT_PROFILE, ACTIVE_PROFILE_SETTING],
+            |row| row.get(0),
+        )
+        .optional()?;
+    Ok(active.unwrap_or_else(|| DEFAULT_PROFILE.to_string()))
+}
+
+fn validate_name(name: &str) -> Result<&str> {
+    let name = name.trim();
+    if name.is_empty() || name.len() > MAX_NAME_LEN {
+        bail!("Profile name must be 1 to {} characters", MAX_NAME_LEN);
+    }
+    if !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+        bail!("Profile name '{}' may only contain letters, digits, '-' and '_'", name);
+    }
+    Ok(name)
+}
+
+fn profile_exists(conn: &Connection, name: &str) -> Result<bool> {
+    Ok(conn.query_row(
+        "SELECT COUNT(*) FROM config_profiles WHERE name = ?1",
+        params![name],
+        |row| row.get::<_, i64>(0),
+    )? > 0)
+}
+
+pub fn list_profiles() -> Result<Vec<ConfigProfile>> {
+    let pool = get_pool()?;
+    let conn = pool.get()?;
+    list_profile_rows(&conn)
+}
+
+fn list_profile_rows(conn: &Connection) -> Result<Vec<ConfigProfile>> {
+    let active = active_profile(conn)?;
+    let mut stmt = conn.prepare(
+        "SELECT name, created_at,
+                (SELECT COUNT(*) FROM settings WHERE profile = p.name),
+                (SELECT COUNT(*) FROM credentials WHERE profile = p.name),
+                (SELECT COUNT(*) FROM data_sources WHERE profile = p.name)
+         FROM config_profiles p ORDER BY name",
+    )?;
+    let profiles = stmt
+        .query_map([], |row| {
+            let name: String = row.get(0)?;
+            Ok(ConfigProfile {
+                active: name == active,
+                name,
+                created_at: row.get(1)?,
+                settings: row.get(2)?,
+                credentials: row.get(3)?,
+                data_sources: row.get(4)?,
+            })
+        })?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    Ok(profiles)
+}
+
+/// Add a profile, empty or with a copy of every row of `copy_from`
+pub fn create_profile(name: &str, copy_from: Option<&str>) -> Result<ConfigProfile> {
+    pool::write(|conn| {
+        let tx = conn.unchecked_transaction()?;
+        let profile = create_profile_rows(&tx, name, copy_from)?;
+        tx.commit()?;
+        Ok(profile)
+    })
+}
+
+fn create_profile_rows(conn: &Connection, name: &str, copy_from: Option<&str>) -> Result<ConfigProfile> {
+    let name = validate_name(name)?;
+    if profile_exists(conn, name)? {
+        bail!("Profile '{}' already exists", name);
+    }
+    conn.execute("INSERT INTO config_profiles (name) VALUES (?1)", params![name])?;
+
+    if let Some(source) = copy_from {
+        if !profile_exists(conn, source)? {
+            bail!("Profile '{}' not found", source);
+        }
+        conn.execute(
+            "INSERT INTO settings (profile, setting_key, setting_value, category, updated_at)
+             SELECT ?1, setting_key, setting_value, category, CURRENT_TIMESTAMP
+             FROM settings WHERE profile = ?2 AND setting_key != ?3",
+            params![name, source, ACTIVE_PROFILE_SETTING],
+        )?;
+        conn.execute(
+            "INSERT INTO credentials
+             (profile, service_name, username, password, api_key, api_secret, additional_data)
+             SELECT ?1, service_name, username, password, api_key, api_secret, additional_data
+             FROM credentials WHERE profile = ?2",
+            params![name, source],
+        )?;
+        // Data source ids are global, so the copies get new ones
+        conn.execute(
+            "INSERT INTO data_sources
+             (id, profile, alias, display_name, description, type, provider, category, config, enabled, tags)
+             SELECT lower(hex(randomblob(16))), ?1, alias, display_name, description, type, provider,
+                    category, config, enabled, tags
+             FROM data_sources WHERE profile = ?2",
+            params![name, source],
+        )?;
+    }
+
+    list_profile_rows(conn)?
+        .into_iter()
+        .find(|p| p.name == name)
+        .ok_or_else(|| anyhow::anyhow!("Profile '{}' not found", name))
+}
+
+/// Remove a profile and every row that belongs to it
+pub fn delete_profile(name: &str) -> Result<ConfigProfile> {
+    pool::write(|conn| {
+        let tx = conn.unchecked_transaction()?;
+        let profile = delete_profile_rows(&tx, name)?;
+        tx.commit()?;
+        Ok(profile)
+    })
+}
+
+fn delete_profile_rows(conn: &Connection, name: &str) -> Result<ConfigProfile> {
+    if name == DEFAULT_PROFILE {
+        bail!("The default profile can't be deleted");
+    }
+    if name == active_profile(conn)? {
+        bail!("Profile '{}' is active; switch to another profile first", name);
+    }
+    let profile = list_profile_rows(conn)?
+        .into_iter()
+        .find(|p| p.name == name)
+        .ok_or_else(|| anyhow::anyhow!("Profile '{}' not found", name))?;
+
+    for table in PROFILE_TABLES {
+        conn.execute(&format!("DELETE FROM {} WHERE profile = ?1", table), params![name])?;
+    }
+    conn.execute("DELETE FROM config_profiles WHERE name = ?1", params![name])?;
+    Ok(profile)
+}
+
+/// Make `name` the active profile
+pub fn switch_profile(name: &str) -> Result<ConfigProfile> {
+    let profile = pool::write(|conn| switch_profile_row(conn, name))?;
+    crate::utils::command_timeout::invalidate();
+    Ok(profile)
+}
+
+fn switch_profile_row(conn: &Connection, name: &str) -> Result<ConfigProfile> {
+    if !profile_exists(conn, name)? {
+        bail!("Profile '{}' not found", name);
+    }
+    conn.execute(
+        "INSERT OR REPLACE INTO settings (profile, setting_key, setting_value, category, updated_at)
+         VALUES (?1, ?2, ?3, 'profiles', CURRENT_TIMESTAMP)",
+        params![DEFAULT_PROFILE, ACTIVE_PROFILE_SETTING, name],
+    )?;
+    list_profile_rows(conn)?
+        .into_iter()
+        .find(|p| p.name == name)
+        .ok_or_else(|| anyhow::anyhow!("Profile '{}' not found", name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setting(conn: &Connection, key: &str) -> Option<String> {
+        let profile = active_profile(conn).unwrap();
+        conn.query_row(
+            "SELECT setting_value FROM settings WHERE profile = ?1 AND setting_key = ?2",
+            params![profile, key],
+            |row| row.get(0),
+        )
+        .optional()
+        .unwrap()
+    }
+
+    #[test]
+    fn test_profiles_scope_rows_and_guard_deletes() {
+        let conn = Connection::open_in_memory().unwrap();
+        crate::database::schema::create_schema(&conn).unwrap();
+        conn.execute(
+            "INSERT INTO settings (setting_key, setting_value) VALUES ('api_base', 'https://dev')",
+            [],
+        )
+        .unwrap();
+        conn.execute("INSERT INTO credentials (service_name, api_key) VALUES ('broker', 'dev-key')", [])
+            .unwrap();
+
+        create_profile_rows(&conn, "prod", Some(DEFAULT_PROFILE)).unwrap();
+        assert!(create_profile_rows(&conn, "prod", None).is_err());
+        assert!(create_profile_rows(&conn, "no spaces", None).is_err());
+        switch_profile_row(&conn, "prod").unwrap();
+        assert_eq!(active_profile(&conn).unwrap(), "prod");
+
+        // The copy is independent of the profile it came from
+        conn.execute(
+            "UPDATE settings SET setting_value = 'https://prod' WHERE profile = 'prod' AND setting_key = 'api_base'",
+            [],
+        )
+        .unwrap();
+        assert_eq!(setting(&conn, "api_base").as_deref(), Some("https://prod"));
+        let prod = list_profile_rows(&conn).unwrap().into_iter().find(|p| p.active).unwrap();
+        assert_eq!((prod.name.as_str(), prod.settings, prod.credentials), ("prod", 1, 1));
+
+        assert!(delete_profile_rows(&conn, "prod").is_err());
+        assert!(delete_profile_rows(&conn, DEFAULT_PROFILE).is_err());
+        switch_profile_row(&conn, DEFAULT_PROFILE).unwrap();
+        assert_eq!(setting(&conn, "api_base").as_deref(), Some("https://dev"));
+
+        let deleted = delete_profile_rows(&conn, "prod").unwrap();
+        assert_eq!(deleted.credentials, 1);
+        let left: i64 = conn
+            .query_row("SELECT COUNT(*) FROM credentials WHERE profile = 'prod'", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(left, 0);
+        assert!(switch_profile_row(&conn, "prod").is_err());
+    }
+}
@@ -1,6 +1,7 @@
 // Specialized Query Operations - Complex queries, MCP, Backtesting, Context Recording
 
-use crate::database::{pool::get_pool, types::*};
+use crate::database::{pool::{get_pool, get_read_pool}, types::*};
+use crate::utils::symbol::Symbol;
 use anyhow::Result;
 use rusqlite::params;
 
@@ -214,18 +215,18 @@ pub fn save_backtest_run(run: &BacktestRun) -> Result<OperationResult> {
 }
 
 pub fn get_backtest_runs(limit: Option<i64>) -> Result<Vec<BacktestRun>> {
-    let pool = get_pool()?;
+    let pool = get_read_pool()?;
     let conn = pool.get()?;
 
     let query = if let Some(lim) = limit {
         format!(
             "SELECT id, strategy_id, provider_name, config, results, status, performance_metrics, error_message, created_at, completed_at, duration_seconds
-             FROM backtest_runs ORDER BY created_at DESC LIMIT {}",
+             FROM backtest_runs ORDER BY created_at DESC, rowid DESC LIMIT {}",
             lim
         )
     } else {
         "SELECT id, strategy_id, provider_name, config, results, status, performance_metrics, error_message, created_at, completed_at, duration_seconds
-         FROM backtest_runs ORDER BY created_at DESC"
+         FROM backtest_runs ORDER BY created_at DESC, rowid DESC"
             .to_string()
     };
 
@@ -279,28 +280,28 @@ pub fn save_recorded_context(context: &RecordedContext) -> Result<()> {
 }
 
 pub fn get_recorded_contexts(tab_name: Option<&str>, limit: Option<i64>) -> Result<Vec<RecordedContext>> {
-    let pool = get_pool()?;
+    let pool = get_read_pool()?;
     let conn = pool.get()?;
 
     let query = match (tab_name, limit) {
         (Some(_), Some(lim)) => format!(
             "SELECT id, tab_name, data_type, label, raw_data, metadata, data_size, created_at, tags
-             FROM recorded_contexts WHERE tab_name = ?1 ORDER BY created_at DESC LIMIT {}",
+             FROM recorded_contexts WHERE tab_name = ?1 ORDER BY created_at DESC, rowid DESC LIMIT {}",
             lim
         ),
         (Some(_), None) => {
             "SELECT id, tab_name, data_type, label, raw_data, metadata, data_size, created_at, tags
-             FROM recorded_contexts WHERE tab_name = ?1 ORDER BY created_at DESC"
+             FROM recorded_contexts WHERE tab_name = ?1 ORDER BY created_at DESC, rowid DESC"
                 .to_string()
         }
         (None, Some(lim)) => format!(
             "SELECT id, tab_name, data_type, label, raw_data, metadata, data_size, created_at, tags
-             FROM recorded_contexts ORDER BY created_at DESC LIMIT {}",
+             FROM recorded_contexts ORDER BY created_at DESC, rowid DESC LIMIT {}",
             lim
         ),
         (None, None) => {
             "SELECT id, tab_name, data_type, label, raw_data, metadata, data_size, created_at, tags
-             FROM recorded_contexts ORDER BY created_at DESC"
+             FROM recorded_contexts ORDER BY created_at DESC, rowid DESC"
                 .to_string()
         }
     };
@@ -390,7 +391,7 @@ pub fn get_watchlists() -> Result<Vec<Watchlist>> {
 
     let mut stmt = conn.prepare(
         "SELECT id, name, description, color, created_at, updated_at
-         FROM watchlists ORDER BY created_at DESC"
+         FROM watchlists ORDER BY created_at DESC, rowid DESC"
     )?;
 
     let watchlists = stmt
@@ -409,7 +410,7 @@ pub fn get_watchlists() -> Result<Vec<Watchlist>> {
     Ok(watchlists)
 }
 
-pub fn add_watchlist_stock(watchlist_id: &str, symbol: &str, notes: Option<&str>) -> Result<WatchlistStock> {
+pub fn add_watchlist_stock(watchlist_id: &str, symbol: &Symbol, notes: Option<&str>) -> Result<WatchlistStock> {
     let pool = get_pool()?;
     let conn = pool.get()?;
 
@@ -417,7 +418,7 @@ pub fn add_watchlist_stock(watchlist_id: &str, symbol: &str, notes: Option<&str>
 
     conn.execute(
         "INSERT INTO watchlist_stocks (id, watchlist_id, symbol, notes) VALUES (?1, ?2, ?3, ?4)",
-        params![id, watchlist_id, symbol.to_uppercase(), notes],
+        params![id, watchlist_id, symbol, notes],
     )?;
 
     let stock = conn.query_row(
@@ -443,7 +444,7 @@ pub fn get_watchlist_stocks(watchlist_id: &str) -> Result<Vec<WatchlistStock>> {
 
     let mut stmt = conn.prepare(
         "SELECT id, watchlist_id, symbol, added_at, notes
-         FROM watchlist_stocks WHERE watchlist_id = ?1 ORDER BY added_at DESC"
+         FROM watchlist_stocks WHERE watchlist_id = ?1 ORDER BY added_at DESC, rowid DESC"
     )?;
 
     let stocks = stmt
@@ -0,0 +1,146 @@
+// Busy Retry - retry writes that hit SQLITE_BUSY / SQLITE_LOCKED
+//
+// What: `with_busy_retry` re-runs a database operation with exponential backoff
+//       while it fails with "database is locked", and counts the retries for
+//       `get_db_stats`.
+// Why:  The monitoring service inserting alerts and RPC/UI writes (trades,
+//       orders, settings) run on separate connections. When they collided one of
+//       them failed with an opaque "database is locked" and the write was lost.
+// How:  Every connection also gets `busy_timeout` (`BUSY_TIMEOUT`), so SQLite
+//       itself waits for the lock first; the retry covers the cases the timeout
+//       does not (a deferred transaction upgrading to a write lock gets BUSY at
+//       once). Only busy/locked errors are retried, anything else is returned
+//       straight away. Operations must be safe to repeat: a single statement or
+//       a whole transaction.
+
+use anyhow::Result;
+use rusqlite::ErrorCode;
+use serde::Serialize;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+/// How long SQLite waits on a lock before returning SQLITE_BUSY
+pub const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+const MAX_ATTEMPTS: u32 = 5;
+const INITIAL_BACKOFF: Duration = Duration::from_millis(25);
+
+static BUSY_RETRIES: AtomicU64 = AtomicU64::new(0);
+static BUSY_FAILURES: AtomicU64 = AtomicU64::new(0);
+
+/// Lock contention counters since startup
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct BusyStats {
+    /// Attempts repeated after a busy/locked error
+    pub busy_retries: u64,
+    /// Operations that were still busy after the last attempt
+    pub busy_failures: u64,
+}
+
+pub fn stats() -> BusyStats {
+    BusyStats {
+        busy_retries: BUSY_RETRIES.load(Ordering::Relaxed),
+        busy_failures: BUSY_FAILURES.load(Ordering::Relaxed),
+    }
+}
+
+/// Whether `err` (or anything it wraps) is SQLite's busy or locked error
+pub fn is_busy(err: &anyhow::Error) -> bool {
+    err.chain().any(|cause| {
+        matches!(
+            cause.downcast_ref::<rusqlite::Error>(),
+            Some(rusqlite::Error::SqliteFailure(e, _))
+                if matches!(e.code, ErrorCode::DatabaseBusy | ErrorCode::DatabaseLocked)
+        )
+    })
+}
+
+/// Run `op`, retrying with backoff while it fails because the database is locked
+pub fn with_busy_retry<T>(mut op: impl FnMut() -> Result<T>) -> Result<T> {
+    let mut backoff = INITIAL_BACKOFF;
+    let mut attempt = 1;
+    loop {
+        match op() {
+            Err(e) if is_busy(&e) => {
+                if attempt >= MAX_ATTEMPTS {
+                    BUSY_FAILURES.fetch_add(1, Ordering::Relaxed);
+                    tracing::warn!(attempts = attempt, "Database still locked, giving up");
+                    return Err(e);
+                }
+                BUSY_RETRIES.fetch_add(1, Ordering::Relaxed);
+                tracing::debug!(attempt, backoff_ms = backoff.as_millis() as u64, "Database locked, retrying");
+                std::thread::sleep(backoff);
+                backoff *= 2;
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rusqlite::{Connection, TransactionBehavior};
+
+    fn temp_db() -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("fincept_busy_test_{}.db", uuid::Uuid::new_v4()))
+    }
+
+    #[test]
+    fn test_retries_until_writer_releases_lock() {
+        let path = temp_db();
+        let setup = Connection::open(&path).unwrap();
+        setup
+            .execute_batch("PRAGMA journal_mode = WAL; CREATE TABLE t (v INTEGER);")
+            .unwrap();
+        drop(setup);
+
+        // Holds the write lock for a while, like a long monitoring insert
+        let holder_path = path.clone();
+        let (locked_tx, locked_rx) = std::sync::mpsc::channel();
+        let holder = std::thread::spawn(move || {
+            let mut conn = Connection::open(&holder_path).unwrap();
+            let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate).unwrap();
+            tx.execute("INSERT INTO t VALUES (1)", []).unwrap();
+            locked_tx.send(()).unwrap();
+            std::thread::sleep(Duration::from_millis(60));
+            tx.commit().unwrap();
+        });
+        locked_rx.recv().unwrap();
+
+        // Without a busy_timeout every attempt made while the lock is held fails at once
+        let conn = Connection::open(&path).unwrap();
+        conn.busy_timeout(Duration::ZERO).unwrap();
+        let before = stats();
+        let mut attempts = 0;
+        with_busy_retry(|| {
+            attempts += 1;
+            conn.execute("INSERT INTO t VALUES (2)", [])?;
+            Ok(())
+        })
+        .unwrap();
+        holder.join().unwrap();
+
+        assert!(attempts > 1);
+        assert!(stats().busy_retries >= before.busy_retries + (attempts - 1) as u64);
+        let count: i64 = conn.query_row("SELECT COUNT(*) FROM t", [], |row| row.get(0)).unwrap();
+        assert_eq!(count, 2);
+
+        drop(conn);
+        for suffix in ["", "-wal", "-shm"] {
+            let _ = std::fs::remove_file(format!("{}{}", path.display(), suffix));
+        }
+    }
+
+    #[test]
+    fn test_other_errors_are_not_retried() {
+        let mut attempts = 0;
+        let result: Result<()> = with_busy_retry(|| {
+            attempts += 1;
+            anyhow::bail!("no such table: t")
+        });
+        assert!(result.is_err());
+        assert_eq!(attempts, 1);
+    }
+}
@@ -2,6 +2,7 @@
 
 use anyhow::Result;
 use rusqlite::Connection;
+use crate::utils::symbol::Symbol;
 
 pub fn create_schema(conn: &Connection) -> Result<()> {
     // Execute all schema statements
@@ -10,24 +11,36 @@ pub fn create_schema(conn: &Connection) -> Result<()> {
         -- Credentials table
         CREATE TABLE IF NOT EXISTS credentials (
             id INTEGER PRIMARY KEY AUTOINCREMENT,
-            service_name TEXT NOT NULL UNIQUE,
+            profile TEXT NOT NULL DEFAULT 'default',
+            service_name TEXT NOT NULL,
             username TEXT,
             password TEXT,
             api_key TEXT,
             api_secret TEXT,
             additional_data TEXT,
             created_at TEXT DEFAULT CURRENT_TIMESTAMP,
-            updated_at TEXT DEFAULT CURRENT_TIMESTAMP
+            updated_at TEXT DEFAULT CURRENT_TIMESTAMP,
+            UNIQUE (profile, service_name)
         );
 
         -- Settings table
         CREATE TABLE IF NOT EXISTS settings (
-            setting_key TEXT PRIMARY KEY,
+            profile TEXT NOT NULL DEFAULT 'default',
+            setting_key TEXT NOT NULL,
             setting_value TEXT NOT NULL,
             category TEXT,
-            updated_at TEXT DEFAULT CURRENT_TIMESTAMP
+            updated_at TEXT DEFAULT CURRENT_TIMESTAMP,
+            PRIMARY KEY (profile, setting_key)
         );
 
+        -- Config profiles (see database::profiles)
+        CREATE TABLE IF NOT EXISTS config_profiles (
+            name TEXT PRIMARY KEY,
+            created_at TEXT DEFAULT CURRENT_TIMESTAMP
+        );
+
+        INSERT OR IGNORE INTO config_profiles (name) VALUES ('default');
+
         -- LLM configurations table
         CREATE TABLE IF NOT EXISTS llm_configs (
             provider TEXT PRIMARY KEY,
@@ -72,10 +85,13 @@ pub fn create_schema(conn: &Connection) -> Result<()> {
             FOREIGN KEY (session_uuid) REFERENCES chat_sessions(session_uuid) ON DELETE CASCADE
         );
 
+        CREATE INDEX IF NOT EXISTS idx_chat_messages_session ON chat_messages(session_uuid, timestamp, id);
+
         -- Data sources table
         CREATE TABLE IF NOT EXISTS data_sources (
             id TEXT PRIMARY KEY,
-            alias TEXT NOT NULL UNIQUE,
+            profile TEXT NOT NULL DEFAULT 'default',
+            alias TEXT NOT NULL,
             display_name TEXT NOT NULL,
             description TEXT,
             type TEXT NOT NULL CHECK (type IN ('websocket', 'rest_api')),
@@ -85,7 +101,8 @@ pub fn create_schema(conn: &Connection) -> Result<()> {
             enabled INTEGER DEFAULT 1,
             tags TEXT,
             created_at TEXT DEFAULT CURRENT_TIMESTAMP,
-            updated_at TEXT DEFAULT CURRENT_TIMESTAMP
+            updated_at TEXT DEFAULT CURRENT_TIMESTAMP,
+            UNIQUE (profile, alias)
         );
 
         -- WebSocket provider configs table
@@ -128,8 +145,9 @@ pub fn create_schema(conn: &Connection) -> Result<()> {
             id TEXT PRIMARY KEY,
             name TEXT NOT NULL,
             provider TEXT NOT NULL,
-            initial_balance REAL NOT NULL DEFAULT 100000,
-            current_balance REAL NOT NULL,
+            owner TEXT,
+            initial_balance TEXT NOT NULL DEFAULT '100000',
+            current_balance TEXT NOT NULL,
             currency TEXT DEFAULT 'USD',
             margin_mode TEXT DEFAULT 'cross',
             leverage REAL DEFAULT 1,
@@ -148,7 +166,7 @@ pub fn create_schema(conn: &Connection) -> Result<()> {
             position_value REAL,
             current_price REAL,
             unrealized_pnl REAL,
-            realized_pnl REAL DEFAULT 0,
+            realized_pnl TEXT DEFAULT '0',
             leverage REAL DEFAULT 1,
             margin_mode TEXT DEFAULT 'cross',
             liquidation_price REAL,
@@ -179,6 +197,8 @@ pub fn create_schema(conn: &Connection) -> Result<()> {
             iceberg_qty REAL,
             leverage REAL,
             margin_mode TEXT,
+            parent_order_id TEXT,
+            oco_group_id TEXT,
             created_at TEXT DEFAULT CURRENT_TIMESTAMP,
             filled_at TEXT,
             updated_at TEXT DEFAULT CURRENT_TIMESTAMP,
@@ -202,12 +222,48 @@ pub fn create_schema(conn: &Connection) -> Result<()> {
             FOREIGN KEY (order_id) REFERENCES paper_trading_orders(id) ON DELETE CASCADE
         );
 
+        -- Paper trading risk limits (NULL limit = rule off) and the daily loss baseline
+        CREATE TABLE IF NOT EXISTS paper_trading_risk_limits (
+            portfolio_id TEXT PRIMARY KEY,
+            max_position_value REAL,
+            max_leverage REAL,
+            max_open_positions INTEGER,
+            max_daily_loss REAL,
+            day_start_balance TEXT,
+            day_start_date TEXT,
+            updated_at TEXT DEFAULT CURRENT_TIMESTAMP,
+            FOREIGN KEY (portfolio_id) REFERENCES paper_trading_portfolios(id) ON DELETE CASCADE
+        );
+
+        CREATE TABLE IF NOT EXISTS paper_trading_symbol_specs (
+            symbol TEXT PRIMARY KEY,
+            tick_size REAL,
+            lot_size REAL,
+            min_quantity REAL,
+            min_notional REAL,
+            updated_at TEXT DEFAULT CURRENT_TIMESTAMP
+        );
+
+        -- Paper portfolio balance and open PnL over time, e.g. after each end-of-day mark
+        CREATE TABLE IF NOT EXISTS paper_trading_snapshots (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            portfolio_id TEXT NOT NULL,
+            balance REAL NOT NULL,
+            unrealized_pnl REAL NOT NULL,
+            equity REAL NOT NULL,
+            open_positions INTEGER NOT NULL,
+            as_of TEXT NOT NULL,
+            created_at TEXT DEFAULT CURRENT_TIMESTAMP,
+            FOREIGN KEY (portfolio_id) REFERENCES paper_trading_portfolios(id) ON DELETE CASCADE
+        );
+
         -- Indexes for paper trading tables
         CREATE INDEX IF NOT EXISTS idx_paper_positions_portfolio ON paper_trading_positions(portfolio_id);
         CREATE INDEX IF NOT EXISTS idx_paper_positions_status ON paper_trading_positions(status);
         CREATE INDEX IF NOT EXISTS idx_paper_orders_portfolio ON paper_trading_orders(portfolio_id);
         CREATE INDEX IF NOT EXISTS idx_paper_orders_status ON paper_trading_orders(status);
         CREATE INDEX IF NOT EXISTS idx_paper_trades_portfolio ON paper_trading_trades(portfolio_id);
+        CREATE INDEX IF NOT EXISTS idx_paper_snapshots_portfolio ON paper_trading_snapshots(portfolio_id, as_of);
 
         -- MCP servers table
         CREATE TABLE IF NOT EXISTS mcp_servers (
@@ -421,16 +477,31 @@ pub fn create_schema(conn: &Connection) -> Result<()> {
         CREATE INDEX IF NOT EXISTS idx_optimization_runs_provider ON optimization_runs(provider_name);
         CREATE INDEX IF NOT EXISTS idx_optimization_runs_status ON optimization_runs(status);
 
+        -- Monitor condition groups (members are monitor_conditions rows with group_id set)
+        CREATE TABLE IF NOT EXISTS monitor_condition_groups (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT,
+            provider TEXT NOT NULL,
+            symbol TEXT NOT NULL,
+            logic TEXT NOT NULL CHECK (logic IN ('and', 'or')),
+            enabled INTEGER DEFAULT 1,
+            notify INTEGER DEFAULT 0,
+            created_at TEXT DEFAULT CURRENT_TIMESTAMP,
+            updated_at TEXT DEFAULT CURRENT_TIMESTAMP
+        );
+
         -- Monitor conditions table
         CREATE TABLE IF NOT EXISTS monitor_conditions (
             id INTEGER PRIMARY KEY AUTOINCREMENT,
             provider TEXT NOT NULL,
             symbol TEXT NOT NULL,
-            field TEXT NOT NULL CHECK (field IN ('price', 'volume', 'change_percent', 'spread')),
+            field TEXT NOT NULL,
             operator TEXT NOT NULL CHECK (operator IN ('>', '<', '>=', '<=', '==', 'between')),
             value REAL NOT NULL,
             value2 REAL,
             enabled INTEGER DEFAULT 1,
+            notify INTEGER DEFAULT 0,
+            group_id INTEGER REFERENCES monitor_condition_groups(id) ON DELETE CASCADE,
             created_at TEXT DEFAULT CURRENT_TIMESTAMP,
             updated_at TEXT DEFAULT CURRENT_TIMESTAMP
         );
@@ -447,6 +518,10 @@ pub fn create_schema(conn: &Connection) -> Result<()> {
             field TEXT NOT NULL,
             triggered_value REAL NOT NULL,
             triggered_at INTEGER NOT NULL,
+            group_id INTEGER,
+            details TEXT,
+            acknowledged INTEGER DEFAULT 0,
+            acknowledged_at INTEGER,
             FOREIGN KEY(condition_id) REFERENCES monitor_conditions(id) ON DELETE CASCADE
         );
 
@@ -506,8 +581,442 @@ pub fn create_schema(conn: &Connection) -> Result<()> {
         CREATE INDEX IF NOT EXISTS idx_portfolio_transactions_date ON portfolio_transactions(transaction_date DESC);
         CREATE INDEX IF NOT EXISTS idx_portfolio_snapshots_portfolio ON portfolio_snapshots(portfolio_id);
         CREATE INDEX IF NOT EXISTS idx_portfolio_snapshots_date ON portfolio_snapshots(snapshot_date DESC);
+
+        -- Commands run in the background on an interval or cron schedule
+        CREATE TABLE IF NOT EXISTS scheduled_tasks (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            command TEXT NOT NULL,
+            args TEXT NOT NULL DEFAULT '{}',
+            schedule TEXT NOT NULL,
+            enabled INTEGER DEFAULT 1,
+            next_run_at INTEGER,
+            last_run_at INTEGER,
+            last_status TEXT,
+            last_error TEXT,
+            last_duration_ms INTEGER,
+            run_count INTEGER DEFAULT 0,
+            skipped_count INTEGER DEFAULT 0,
+            created_at TEXT DEFAULT CURRENT_TIMESTAMP
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_scheduled_tasks_due ON scheduled_tasks(enabled, next_run_at);
+
+        -- Date-specific changes to the seeded exchange trading calendars
+        CREATE TABLE IF NOT EXISTS trading_calendar_overrides (
+            exchange TEXT NOT NULL,
+            date TEXT NOT NULL,
+            kind TEXT NOT NULL CHECK (kind IN ('closed', 'open', 'early_close')),
+            close_time TEXT,
+            note TEXT,
+            created_at TEXT DEFAULT CURRENT_TIMESTAMP,
+            PRIMARY KEY (exchange, date)
+        );
+
+        -- Historical candles kept so repeated chart loads skip yfinance
+        CREATE TABLE IF NOT EXISTS historical_cache (
+            symbol TEXT NOT NULL,
+            interval TEXT NOT NULL,
+            date TEXT NOT NULL,
+            timestamp INTEGER NOT NULL,
+            open REAL NOT NULL,
+            high REAL NOT NULL,
+            low REAL NOT NULL,
+            close REAL NOT NULL,
+            volume INTEGER NOT NULL,
+            adj_close REAL NOT NULL,
+            PRIMARY KEY (symbol, interval, timestamp)
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_historical_cache_date ON historical_cache(symbol, interval, date);
+
+        -- Date ranges already fetched, including days without candles
+        CREATE TABLE IF NOT EXISTS historical_cache_ranges (
+            symbol TEXT NOT NULL,
+            interval TEXT NOT NULL,
+            start_date TEXT NOT NULL,
+            end_date TEXT NOT NULL,
+            fetched_at TEXT DEFAULT CURRENT_TIMESTAMP,
+            PRIMARY KEY (symbol, interval, start_date)
+        );
+
+        -- Web RPC tokens limited to a few commands (see server::scoped_tokens)
+        CREATE TABLE IF NOT EXISTS scoped_tokens (
+            token_hash TEXT PRIMARY KEY,
+            commands TEXT NOT NULL,
+            expires_at INTEGER NOT NULL,
+            created_at TEXT DEFAULT CURRENT_TIMESTAMP
+        );
+
+        -- Persisted tickers and the pairs being recorded (see websocket::tick_recorder)
+        CREATE TABLE IF NOT EXISTS tick_history (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            provider TEXT NOT NULL,
+            symbol TEXT NOT NULL,
+            price REAL NOT NULL,
+            bid REAL,
+            ask REAL,
+            bid_size REAL,
+            ask_size REAL,
+            volume REAL,
+            high REAL,
+            low REAL,
+            open REAL,
+            close REAL,
+            change REAL,
+            change_percent REAL,
+            timestamp INTEGER NOT NULL
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_tick_history_pair ON tick_history(provider, symbol, timestamp);
+        CREATE INDEX IF NOT EXISTS idx_tick_history_timestamp ON tick_history(timestamp);
+
+        CREATE TABLE IF NOT EXISTS tick_recorder_targets (
+            provider TEXT NOT NULL,
+            symbol TEXT NOT NULL,
+            enabled_at TEXT DEFAULT CURRENT_TIMESTAMP,
+            PRIMARY KEY (provider, symbol)
+        );
         ",
     )?;
 
+    run_migrations(conn)?;
+
+    Ok(())
+}
+
+/// Apply additive migrations for databases created by older versions
+///
+/// `CREATE TABLE IF NOT EXISTS` never alters an existing table, so columns
+/// added after a table first shipped are back-filled here.
+fn run_migrations(conn: &Connection) -> Result<()> {
+    add_column_if_missing(conn, "monitor_conditions", "notify", "INTEGER DEFAULT 0")?;
+    add_column_if_missing(conn, "paper_trading_portfolios", "owner", "TEXT")?;
+    add_column_if_missing(conn, "monitor_conditions", "group_id", "INTEGER")?;
+    add_column_if_missing(conn, "monitor_alerts", "group_id", "INTEGER")?;
+    add_column_if_missing(conn, "monitor_alerts", "details", "TEXT")?;
+    add_column_if_missing(conn, "monitor_alerts", "acknowledged", "INTEGER DEFAULT 0")?;
+    add_column_if_missing(conn, "monitor_alerts", "acknowledged_at", "INTEGER")?;
+    add_column_if_missing(conn, "paper_trading_orders", "parent_order_id", "TEXT")?;
+    add_column_if_missing(conn, "paper_trading_orders", "oco_group_id", "TEXT")?;
+    drop_monitor_field_check(conn)?;
+    scope_to_profiles(conn)?;
+    purge_invalid_monitor_symbols(conn)?;
+    store_money_as_text(conn)?;
+    // Indexed here rather than in the schema: older tables only gain the columns above
+    conn.execute_batch(
+        "CREATE INDEX IF NOT EXISTS idx_monitor_conditions_group ON monitor_conditions(group_id);
+         CREATE INDEX IF NOT EXISTS idx_monitor_alerts_acknowledged ON monitor_alerts(acknowledged);
+         CREATE INDEX IF NOT EXISTS idx_paper_trading_orders_parent ON paper_trading_orders(parent_order_id);
+         CREATE INDEX IF NOT EXISTS idx_paper_trading_orders_oco ON paper_trading_orders(oco_group_id);",
+    )?;
+    Ok(())
+}
+
+/// Rebuild `monitor_conditions` without the CHECK on `field`
+///
+/// Fields are validated by `MonitorField::from_str`; the constraint only kept
+/// older databases from storing new fields such as `mid_price`. SQLite can't
+/// drop a constraint, so the table is copied into a new one (with foreign keys
+/// off, so alerts referencing conditions are left alone).
+fn drop_monitor_field_check(conn: &Connection) -> Result<()> {
+    let sql: String = conn.query_row(
+        "SELECT sql FROM sqlite_master WHERE type = 'table' AND name = 'monitor_conditions'",
+        [],
+        |row| row.get(0),
+    )?;
+    if !sql.contains("CHECK (field IN") {
+        return Ok(());
+    }
+
+    conn.execute_batch("PRAGMA foreign_keys = OFF;")?;
+    let result = conn.execute_batch(
+        "
+        BEGIN;
+        CREATE TABLE monitor_conditions_new (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            provider TEXT NOT NULL,
+            symbol TEXT NOT NULL,
+            field TEXT NOT NULL,
+            operator TEXT NOT NULL CHECK (operator IN ('>', '<', '>=', '<=', '==', 'between')),
+            value REAL NOT NULL,
+            value2 REAL,
+            enabled INTEGER DEFAULT 1,
+            notify INTEGER DEFAULT 0,
+            group_id INTEGER REFERENCES monitor_condition_groups(id) ON DELETE CASCADE,
+            created_at TEXT DEFAULT CURRENT_TIMESTAMP,
+            updated_at TEXT DEFAULT CURRENT_TIMESTAMP
+        );
+        INSERT INTO monitor_conditions_new
+            (id, provider, symbol, field, operator, value, value2, enabled, notify, group_id, created_at, updated_at)
+        SELECT id, provider, symbol, field, operator, value, value2, enabled, notify, group_id, created_at, updated_at
+        FROM monitor_conditions;
+        DROP TABLE monitor_conditions;
+        ALTER TABLE monitor_conditions_new RENAME TO monitor_conditions;
+        CREATE INDEX IF NOT EXISTS idx_monitor_conditions_provider_symbol ON monitor_conditions(provider, symbol);
+        CREATE INDEX IF NOT EXISTS idx_monitor_conditions_enabled ON monitor_conditions(enabled);
+        CREATE INDEX IF NOT EXISTS idx_monitor_conditions_group ON monitor_conditions(group_id);
+        COMMIT;
+        ",
+    );
+    if result.is_err() {
+        let _ = conn.execute_batch("ROLLBACK;");
+    }
+    conn.execute_batch("PRAGMA foreign_keys = ON;")?;
+    result?;
     Ok(())
 }
+
+/// Rebuild the profile-scoped tables of older databases with a `profile` column
+///
+/// Their keys were unique on their own (`setting_key`, `service_name`, `alias`);
+/// now they are unique per profile, which needs new constraints, so like
+/// `drop_monitor_field_check` each table is copied into a new one. Existing rows
+/// land in the `default` profile.
+fn scope_to_profiles(conn: &Connection) -> Result<()> {
+    let tables = [
+        (
+            "settings",
+            "profile TEXT NOT NULL DEFAULT 'default',
+            setting_key TEXT NOT NULL,
+            setting_value TEXT NOT NULL,
+            category TEXT,
+            updated_at TEXT DEFAULT CURRENT_TIMESTAMP,
+            PRIMARY KEY (profile, setting_key)",
+            "setting_key, setting_value, category, updated_at",
+        ),
+        (
+            "credentials",
+            "id INTEGER PRIMARY KEY AUTOINCREMENT,
+            profile TEXT NOT NULL DEFAULT 'default',
+            service_name TEXT NOT NULL,
+            username TEXT,
+            password TEXT,
+            api_key TEXT,
+            api_secret TEXT,
+            additional_data TEXT,
+            created_at TEXT DEFAULT CURRENT_TIMESTAMP,
+            updated_at TEXT DEFAULT CURRENT_TIMESTAMP,
+            UNIQUE (profile, service_name)",
+            "id, service_name, username, password, api_key, api_secret, additional_data, created_at, updated_at",
+        ),
+        (
+            "data_sources",
+            "id TEXT PRIMARY KEY,
+            profile TEXT NOT NULL DEFAULT 'default',
+            alias TEXT NOT NULL,
+            display_name TEXT NOT NULL,
+            description TEXT,
+            type TEXT NOT NULL CHECK (type IN ('websocket', 'rest_api')),
+            provider TEXT NOT NULL,
+            category TEXT,
+            config TEXT NOT NULL,
+            enabled INTEGER DEFAULT 1,
+            tags TEXT,
+            created_at TEXT DEFAULT CURRENT_TIMESTAMP,
+            updated_at TEXT DEFAULT CURRENT_TIMESTAMP,
+            UNIQUE (profile, alias)",
+            "id, alias, display_name, description, type, provider, category, config, enabled, tags, created_at, updated_at",
+        ),
+    ];
+
+    for (table, definition, columns) in tables {
+        let scoped: bool = conn.query_row(
+            &format!("SELECT COUNT(*) FROM pragma_table_info('{}') WHERE name = 'profile'", table),
+            [],
+            |row| row.get::<_, i64>(0),
+        )? > 0;
+        if scoped {
+            continue;
+        }
+
+        let result = conn.execute_batch(&format!(
+            "
+            BEGIN;
+            CREATE TABLE {table}_new ({definition});
+            INSERT INTO {table}_new ({columns}) SELECT {columns} FROM {table};
+            DROP TABLE {table};
+            ALTER TABLE {table}_new RENAME TO {table};
+            COMMIT;
+            ",
+        ));
+        if result.is_err() {
+            let _ = conn.execute_batch("ROLLBACK;");
+        }
+        result?;
+    }
+    Ok(())
+}
+
+/// Delete monitor rows whose symbol `Symbol` can't read back
+///
+/// Rows written before symbols were validated can hold a blank symbol (or one
+/// with control characters). They could never match a tick, and one of them made
+/// every monitor loader fail, disabling all conditions. A deleted group takes its
+/// member conditions with it.
+fn purge_invalid_monitor_symbols(conn: &Connection) -> Result<()> {
+    let invalid_ids = |table: &str| -> Result<Vec<i64>> {
+        let mut stmt = conn.prepare(&format!("SELECT id, CAST(symbol AS TEXT) FROM {}", table))?;
+        let rows = stmt
+            .query_map([], |row| Ok((row.get::<_, i64>(0)?, row.get::<_, Option<String>>(1)?)))?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(rows
+            .into_iter()
+            .filter(|(_, symbol)| symbol.as_deref().is_none_or(|s| Symbol::new(s).is_err()))
+            .map(|(id, _)| id)
+            .collect())
+    };
+
+    for (table, also_delete) in [
+        ("monitor_condition_groups", Some("DELETE FROM monitor_conditions WHERE group_id = ?1")),
+        ("monitor_conditions", None),
+        ("monitor_alerts", None),
+    ] {
+        let ids = invalid_ids(table)?;
+        for id in &ids {
+            if let Some(sql) = also_delete {
+                conn.execute(sql, [id])?;
+            }
+            conn.execute(&format!("DELETE FROM {} WHERE id = ?1", table), [id])?;
+        }
+        if !ids.is_empty() {
+            tracing::warn!(table, count = ids.len(), "Deleted monitor rows with an invalid symbol");
+        }
+    }
+    Ok(())
+}
+
+/// Columns holding running money totals, stored as exact decimal text (see `utils::money`)
+const MONEY_TEXT_COLUMNS: &[(&str, &[&str])] = &[
+    ("paper_trading_portfolios", &["initial_balance", "current_balance"]),
+    ("paper_trading_positions", &["realized_pnl"]),
+    ("paper_trading_risk_limits", &["day_start_balance"]),
+];
+
+/// Rebuild older paper-trading tables whose money totals are REAL columns
+///
+/// Like `drop_monitor_field_check`, each table is copied into a new one, here
+/// created from its own stored SQL with the money columns declared TEXT; the copy
+/// turns each REAL into its decimal text. Indexes are recreated afterwards.
+fn store_money_as_text(conn: &Connection) -> Result<()> {
+    for (table, columns) in MONEY_TEXT_COLUMNS {
+        let mut sql: String = conn.query_row(
+            "SELECT sql FROM sqlite_master WHERE type = 'table' AND name = ?1",
+            [table],
+            |row| row.get(0),
+        )?;
+        let before = sql.clone();
+        for column in *columns {
+            sql = sql.replacen(&format!("{} REAL", column), &format!("{} TEXT", column), 1);
+        }
+        if sql == before {
+            continue;
+        }
+        let sql = sql.replacen(table, &format!("{}_new", table), 1);
+        let indexes: Vec<String> = conn
+            .prepare("SELECT sql FROM sqlite_master WHERE type = 'index' AND tbl_name = ?1 AND sql IS NOT NULL")?
+            .query_map([table], |row| row.get(0))?
+            .collect::<std::result::Result<_, _>>()?;
+
+        conn.execute_batch("PRAGMA foreign_keys = OFF;")?;
+        let result = conn.execute_batch(&format!(
+            "BEGIN;
+            {sql};
+            INSERT INTO {table}_new SELECT * FROM {table};
+            DROP TABLE {table};
+            ALTER TABLE {table}_new RENAME TO {table};
+            {indexes}
+            COMMIT;",
+            indexes = indexes.iter().map(|index| format!("{};", index)).collect::<String>(),
+        ));
+        if result.is_err() {
+            let _ = conn.execute_batch("ROLLBACK;");
+        }
+        conn.execute_batch("PRAGMA foreign_keys = ON;")?;
+        result?;
+    }
+    Ok(())
+}
+
+/// Add a column to a table unless it already exists
+fn add_column_if_missing(conn: &Connection, table: &str, column: &str, definition: &str) -> Result<()> {
+    let exists: bool = conn.query_row(
+        &format!("SELECT COUNT(*) FROM pragma_table_info('{}') WHERE name = ?1", table),
+        [column],
+        |row| row.get::<_, i64>(0),
+    )? > 0;
+
+    if !exists {
+        conn.execute_batch(&format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition))?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_invalid_monitor_symbols_are_purged() {
+        let conn = Connection::open_in_memory().unwrap();
+        create_schema(&conn).unwrap();
+        conn.execute_batch(
+            "INSERT INTO monitor_condition_groups (id, name, provider, symbol, logic) VALUES (1, 'ok', 'binance', 'BTCUSDT', 'and');
+             INSERT INTO monitor_condition_groups (id, name, provider, symbol, logic) VALUES (2, 'blank', 'binance', '  ', 'and');
+             INSERT INTO monitor_conditions (id, provider, symbol, field, operator, value, group_id) VALUES (1, 'binance', 'BTCUSDT', 'price', '>', 1, 1);
+             INSERT INTO monitor_conditions (id, provider, symbol, field, operator, value, group_id) VALUES (2, 'binance', '  ', 'price', '>', 1, 2);
+             INSERT INTO monitor_conditions (id, provider, symbol, field, operator, value) VALUES (3, 'binance', '', 'price', '>', 1);
+             INSERT INTO monitor_conditions (id, provider, symbol, field, operator, value) VALUES (4, 'binance', 'ethusdt', 'price', '>', 1);",
+        )
+        .unwrap();
+
+        purge_invalid_monitor_symbols(&conn).unwrap();
+
+        let ids = |table: &str| -> Vec<i64> {
+            let mut stmt = conn.prepare(&format!("SELECT id FROM {} ORDER BY id", table)).unwrap();
+            stmt.query_map([], |row| row.get(0)).unwrap().map(|r| r.unwrap()).collect()
+        };
+        assert_eq!(ids("monitor_condition_groups"), vec![1]);
+        assert_eq!(ids("monitor_conditions"), vec![1, 4]);
+    }
+
+    #[test]
+    fn test_money_totals_move_to_text_columns() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE paper_trading_portfolios (
+                id TEXT PRIMARY KEY,
+                name TEXT NOT NULL,
+                provider TEXT NOT NULL,
+                initial_balance REAL NOT NULL DEFAULT 100000,
+                current_balance REAL NOT NULL
+            );
+            CREATE INDEX idx_portfolios_name ON paper_trading_portfolios(name);
+            INSERT INTO paper_trading_portfolios (id, name, provider, current_balance) VALUES ('p1', 'old', 'x', 9999.95);
+            CREATE TABLE paper_trading_positions (id TEXT PRIMARY KEY, realized_pnl REAL DEFAULT 0);
+            CREATE TABLE paper_trading_risk_limits (portfolio_id TEXT PRIMARY KEY, day_start_balance TEXT);",
+        )
+        .unwrap();
+
+        store_money_as_text(&conn).unwrap();
+        // A second run finds nothing left to convert
+        store_money_as_text(&conn).unwrap();
+
+        let (balance, kind): (String, String) = conn
+            .query_row("SELECT current_balance, typeof(current_balance) FROM paper_trading_portfolios", [], |row| {
+                Ok((row.get(0)?, row.get(1)?))
+            })
+            .unwrap();
+        assert_eq!((balance.as_str(), kind.as_str()), ("9999.95", "text"));
+        let declared = |table: &str, column: &str| -> String {
+            conn.query_row(&format!("SELECT type FROM pragma_table_info('{}') WHERE name = ?1", table), [column], |row| row.get(0))
+                .unwrap()
+        };
+        assert_eq!(declared("paper_trading_portfolios", "initial_balance"), "TEXT");
+        assert_eq!(declared("paper_trading_positions", "realized_pnl"), "TEXT");
+        let index: i64 = conn
+            .query_row("SELECT COUNT(*) FROM sqlite_master WHERE name = 'idx_portfolios_name'", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(index, 1);
+    }
+}
@@ -0,0 +1,116 @@
+// Settings Schema - value types of the settings the backend reads
+//
+// What: `validate_setting` checks a value against the type of its key: flags,
+//       positive whole numbers and fixed choices.
+// Why:  The readers fall back to defaults on values they can't parse, so a typo
+//       saved from the settings page silently did nothing. `operations::save_setting`
+//       rejects an invalid value; bulk saves (`operations::save_settings`) check
+//       every value before writing any.
+// How:  Keys not listed here belong to the frontend and accept any value. Add a
+//       key when a backend module starts reading it.
+
+use crate::commands::news_scheduler::{NEWS_CONCURRENCY_SETTING, NEWS_INTERVAL_SETTING};
+use crate::logging::LOG_FORMAT_SETTING;
+use crate::utils::command_timeout::{COMMAND_TIMEOUTS_SETTING, COMMAND_TIMEOUT_SETTING};
+use crate::utils::http::{HTTP_CONNECT_TIMEOUT_SETTING, HTTP_TIMEOUT_SETTING};
+use crate::utils::retry::{RETRY_BASE_DELAY_SETTING, RETRY_MAX_ATTEMPTS_SETTING};
+use crate::websocket::metrics_cache::METRICS_CACHE_SETTING;
+use crate::websocket::raw_capture::RAW_CAPTURE_SETTING;
+use crate::websocket::services::monitoring::NOTIFICATIONS_SETTING_KEY;
+use crate::websocket::tick_recorder::RETENTION_SETTING as TICK_RETENTION_SETTING;
+
+/// `server::command_log::COMMAND_LOG_SETTING`; the server module is only built with `web`
+const COMMAND_LOG_SETTING: &str = "debug_command_log";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SettingType {
+    /// "true"/"false" (also 1/0, on/off)
+    Flag,
+    /// Whole number greater than zero
+    PositiveInteger,
+    /// One of the listed values, case-insensitive
+    OneOf(&'static [&'static str]),
+    /// JSON object of names to whole numbers greater than zero
+    PositiveIntegerMap,
+}
+
+pub const SETTINGS_SCHEMA: &[(&str, SettingType)] = &[
+    (LOG_FORMAT_SETTING, SettingType::OneOf(&["human", "text", "pretty", "json"])),
+    (COMMAND_LOG_SETTING, SettingType::Flag),
+    (RAW_CAPTURE_SETTING, SettingType::Flag),
+    (NOTIFICATIONS_SETTING_KEY, SettingType::Flag),
+    (HTTP_CONNECT_TIMEOUT_SETTING, SettingType::PositiveInteger),
+    (HTTP_TIMEOUT_SETTING, SettingType::PositiveInteger),
+    (RETRY_MAX_ATTEMPTS_SETTING, SettingType::PositiveInteger),
+    (RETRY_BASE_DELAY_SETTING, SettingType::PositiveInteger),
+    (NEWS_INTERVAL_SETTING, SettingType::PositiveInteger),
+    (NEWS_CONCURRENCY_SETTING, SettingType::PositiveInteger),
+    (METRICS_CACHE_SETTING, SettingType::PositiveInteger),
+    (TICK_RETENTION_SETTING, SettingType::PositiveInteger),
+    (COMMAND_TIMEOUT_SETTING, SettingType::PositiveInteger),
+    (COMMAND_TIMEOUTS_SETTING, SettingType::PositiveIntegerMap),
+];
+
+/// Type of `key`, or `None` for settings the backend doesn't read
+pub fn setting_type(key: &str) -> Option<SettingType> {
+    SETTINGS_SCHEMA.iter().find(|(k, _)| *k == key).map(|(_, kind)| *kind)
+}
+
+/// Reject a value its setting's reader would ignore
+pub fn validate_setting(key: &str, value: &str) -> Result<(), String> {
+    if key.trim().is_empty() {
+        return Err("Setting key must not be empty".to_string());
+    }
+    let value = value.trim();
+    match setting_type(key) {
+        None => Ok(()),
+        Some(SettingType::Flag) => match value {
+            "true" | "false" | "1" | "0" | "on" | "off" => Ok(()),
+            _ => Err(format!("{} must be true or false, got '{}'", key, value)),
+        },
+        Some(SettingType::PositiveInteger) => match value.parse::<u64>() {
+            Ok(n) if n > 0 => Ok(()),
+            _ => Err(format!("{} must be a whole number above 0, got '{}'", key, value)),
+        },
+        Some(SettingType::PositiveIntegerMap) => {
+            match serde_json::from_str::<std::collections::HashMap<String, u64>>(value) {
+                Ok(map) if map.values().all(|n| *n > 0) => Ok(()),
+                _ => Err(format!(
+                    "{} must be a JSON object of names to whole numbers above 0, got '{}'",
+                    key, value
+                )),
+            }
+        }
+        Some(SettingType::OneOf(choices)) => {
+            if choices.contains(&value.to_lowercase().as_str()) {
+                Ok(())
+            } else {
+                Err(format!("{} must be one of {}, got '{}'", key, choices.join(", "), value))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_values_checked_by_key_type() {
+        assert!(validate_setting(COMMAND_LOG_SETTING, "on").is_ok());
+        assert!(validate_setting(COMMAND_LOG_SETTING, "yes please").is_err());
+        assert!(validate_setting(HTTP_TIMEOUT_SETTING, " 30 ").is_ok());
+        assert!(validate_setting(HTTP_TIMEOUT_SETTING, "0").is_err());
+        assert!(validate_setting(HTTP_TIMEOUT_SETTING, "-5").is_err());
+        assert!(validate_setting(LOG_FORMAT_SETTING, "JSON").is_ok());
+        let err = validate_setting(LOG_FORMAT_SETTING, "xml").unwrap_err();
+        assert!(err.contains(" must be one of "), "{}", err);
+        assert!(validate_setting(COMMAND_TIMEOUTS_SETTING, r#"{"get_market_quotes": 30}"#).is_ok());
+        assert!(validate_setting(COMMAND_TIMEOUTS_SETTING, r#"{"get_market_quotes": 0}"#).is_err());
+        assert!(validate_setting(COMMAND_TIMEOUTS_SETTING, "30").is_err());
+
+        // Frontend-only keys take anything; an empty key never does
+        assert!(validate_setting("theme", "").is_ok());
+        assert!(validate_setting(" ", "x").is_err());
+    }
+}
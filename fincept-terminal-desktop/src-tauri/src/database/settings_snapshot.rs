@@ -0,0 +1,213 @@
+// Settings Snapshot - shareable copy of the settings table and a diff against a baseline
+//
+// What: `export_settings_snapshot()` returns every setting as a JSON document a
+//       user can paste into a support ticket; `diff_settings(baseline)` lists the
+//       settings added, removed or changed relative to a snapshot (or a plain
+//       `{ key: value }` map such as a set of defaults).
+// Why:  Troubleshooting started with the user reading settings out one by one.
+// How:  Settings in a secret category (API keys, credentials) or whose key looks
+//       like a credential (`config_bundle::is_secret_name`) never leave the
+//       database: the snapshot lists only their keys under `redacted`, and the
+//       diff skips them on both sides so a baseline can't be used to probe a value.
+
+use super::config_bundle::is_secret_name;
+use super::operations;
+use super::types::Setting;
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+/// Setting categories whose values are credentials
+const SECRET_CATEGORIES: &[&str] = &["api_keys", "credentials", "secrets", "auth"];
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SnapshotSetting {
+    pub value: String,
+    pub category: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SettingsSnapshot {
+    pub app_version: String,
+    pub taken_at: String,
+    pub settings: BTreeMap<String, SnapshotSetting>,
+    /// Keys of settings left out because they hold credentials
+    pub redacted: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SettingChange {
+    pub key: String,
+    pub baseline: Option<String>,
+    pub current: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SettingsDiff {
+    /// Set now but not in the baseline
+    pub added: Vec<SettingChange>,
+    /// In the baseline but not set now
+    pub removed: Vec<SettingChange>,
+    pub changed: Vec<SettingChange>,
+    pub unchanged: usize,
+}
+
+fn is_secret(key: &str, category: Option<&str>) -> bool {
+    is_secret_name(key)
+        || category.is_some_and(|c| SECRET_CATEGORIES.iter().any(|s| c.eq_ignore_ascii_case(s)))
+}
+
+/// Snapshot of `settings` with secrets left out
+pub fn build_snapshot(settings: Vec<Setting>) -> SettingsSnapshot {
+    let mut snapshot = SettingsSnapshot {
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        taken_at: chrono::Utc::now().to_rfc3339(),
+        settings: BTreeMap::new(),
+        redacted: Vec::new(),
+    };
+    for setting in settings {
+        if is_secret(&setting.setting_key, setting.category.as_deref()) {
+            snapshot.redacted.push(setting.setting_key);
+        } else {
+            snapshot.settings.insert(
+                setting.setting_key,
+                SnapshotSetting {
+                    value: setting.setting_value,
+                    category: setting.category,
+                },
+            );
+        }
+    }
+    snapshot.redacted.sort();
+    snapshot
+}
+
+pub fn export_settings_snapshot() -> Result<SettingsSnapshot> {
+    Ok(build_snapshot(operations::get_all_settings()?))
+}
+
+/// Key/value pairs of a snapshot, or of a plain `{ key: value }` object
+fn parse_baseline(baseline: &Value) -> Result<BTreeMap<String, String>> {
+    let entries = match baseline.get("settings") {
+        Some(settings) => settings,
+        None => baseline,
+    };
+    let Some(entries) = entries.as_object() else {
+        bail!("Baseline must be a settings snapshot or an object of key/value pairs");
+    };
+
+    Ok(entries
+        .iter()
+        .map(|(key, entry)| {
+            // Snapshot entries are { value, category }; plain maps hold the value itself
+            let value = entry.get("value").unwrap_or(entry);
+            let value = match value {
+                Value::String(s) => s.clone(),
+                other => other.to_string(),
+            };
+            (key.clone(), value)
+        })
+        .collect())
+}
+
+/// How `current` differs from `baseline`, ignoring secret settings
+pub fn diff(baseline: &BTreeMap<String, String>, current: &[Setting]) -> SettingsDiff {
+    let (secret, current): (Vec<&Setting>, Vec<&Setting>) =
+        current.iter().partition(|s| is_secret(&s.setting_key, s.category.as_deref()));
+    let secret: Vec<&str> = secret.iter().map(|s| s.setting_key.as_str()).collect();
+    let current: BTreeMap<&str, &str> = current
+        .iter()
+        .map(|s| (s.setting_key.as_str(), s.setting_value.as_str()))
+        .collect();
+    let change = |key: &str, baseline: Option<&String>, current: Option<&&str>| SettingChange {
+        key: key.to_string(),
+        baseline: baseline.cloned(),
+        current: current.map(|v| v.to_string()),
+    };
+
+    let mut result = SettingsDiff::default();
+    let skip = |key: &str| is_secret_name(key) || secret.contains(&key);
+    for (key, value) in baseline.iter().filter(|(key, _)| !skip(key)) {
+        match current.get(key.as_str()) {
+            None => result.removed.push(change(key, Some(value), None)),
+            Some(now) if now != value => result.changed.push(change(key, Some(value), Some(now))),
+            Some(_) => result.unchanged += 1,
+        }
+    }
+    for (key, now) in &current {
+        if !baseline.contains_key(*key) {
+            result.added.push(change(*key, None, Some(now)));
+        }
+    }
+    result
+}
+
+pub fn diff_settings(baseline: &Value) -> Result<SettingsDiff> {
+    let baseline = parse_baseline(baseline)?;
+    Ok(diff(&baseline, &operations::get_all_settings()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setting(key: &str, value: &str, category: Option<&str>) -> Setting {
+        Setting {
+            setting_key: key.to_string(),
+            setting_value: value.to_string(),
+            category: category.map(str::to_string),
+            updated_at: None,
+        }
+    }
+
+    #[test]
+    fn test_snapshot_redacts_secrets() {
+        let snapshot = build_snapshot(vec![
+            setting("theme", "dark", Some("ui")),
+            setting("fred", "abc123", Some("api_keys")),
+            setting("openai_api_key", "sk-1", None),
+        ]);
+
+        assert_eq!(snapshot.settings.len(), 1);
+        assert_eq!(snapshot.settings["theme"].value, "dark");
+        assert_eq!(snapshot.redacted, vec!["fred", "openai_api_key"]);
+        let json = serde_json::to_string(&snapshot).unwrap();
+        assert!(!json.contains("abc123") && !json.contains("sk-1"));
+    }
+
+    #[test]
+    fn test_diff_against_snapshot_and_plain_map() {
+        let current = vec![
+            setting("theme", "dark", None),
+            setting("refresh_secs", "30", None),
+            setting("language", "en", None),
+            setting("fred", "abc123", Some("api_keys")),
+        ];
+
+        let snapshot = serde_json::to_value(build_snapshot(vec![
+            setting("theme", "light", None),
+            setting("refresh_secs", "30", None),
+            setting("http_proxy", "http://proxy:8080", None),
+        ]))
+        .unwrap();
+        let result = diff(&parse_baseline(&snapshot).unwrap(), &current);
+        assert_eq!(result.changed, vec![SettingChange {
+            key: "theme".to_string(),
+            baseline: Some("light".to_string()),
+            current: Some("dark".to_string()),
+        }]);
+        assert_eq!(result.removed[0].key, "http_proxy");
+        // The secret setting is never reported as added
+        assert_eq!(result.added.iter().map(|c| c.key.as_str()).collect::<Vec<_>>(), vec!["language"]);
+        assert_eq!(result.unchanged, 1);
+
+        let defaults = serde_json::json!({ "refresh_secs": 60, "fred_api_key": "x", "fred": "guess" });
+        let result = diff(&parse_baseline(&defaults).unwrap(), &current);
+        assert_eq!(result.changed[0].baseline.as_deref(), Some("60"));
+        assert!(result.removed.is_empty());
+        assert_eq!(result.changed.len(), 1);
+
+        assert!(parse_baseline(&serde_json::json!([1, 2])).is_err());
+    }
+}
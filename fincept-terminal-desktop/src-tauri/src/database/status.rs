@@ -0,0 +1,109 @@
+// Database Status - single source of truth for database availability
+//
+// What: A process-wide `DbStatus` (uninitialized / healthy / degraded / failed,
+//       plus the reason) exposed through the `get_db_status` command.
+// Why:  `run()` keeps going when database init fails so the UI can show an error,
+//       but every DB-backed command then failed with its own unrelated message
+//       and the frontend had nothing global to look at.
+// How:  `pool::init_pool` records healthy/failed on every init attempt. Once init
+//       has failed, `get_pool()` short-circuits with `unavailable_error()`, so every
+//       DB-backed command returns the same "Database unavailable" error. `probe()`
+//       in pool.rs marks a live pool degraded when it cannot hand out connections,
+//       and `get_db_status(retry = true)` re-attempts initialization.
+
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
+use serde::Serialize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DbHealth {
+    /// No initialization attempted yet
+    Uninitialized,
+    Healthy,
+    /// Pool exists but connections or queries are failing
+    Degraded,
+    /// Initialization failed; DB-backed commands are short-circuited
+    Failed,
+    /// Database was re-keyed; commands are blocked until the app restarts
+    RestartRequired,
+}
+
+impl std::fmt::Display for DbHealth {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            DbHealth::Uninitialized => "uninitialized",
+            DbHealth::Healthy => "healthy",
+            DbHealth::Degraded => "degraded",
+            DbHealth::Failed => "failed",
+            DbHealth::RestartRequired => "restart_required",
+        };
+        f.write_str(name)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DbStatus {
+    pub state: DbHealth,
+    pub reason: Option<String>,
+    /// Unix milliseconds of the last state change
+    pub updated_at: i64,
+}
+
+static STATUS: Lazy<RwLock<DbStatus>> = Lazy::new(|| {
+    RwLock::new(DbStatus {
+        state: DbHealth::Uninitialized,
+        reason: None,
+        updated_at: chrono::Utc::now().timestamp_millis(),
+    })
+});
+
+/// Current database status
+pub fn current() -> DbStatus {
+    STATUS.read().clone()
+}
+
+/// Record a state change, logging transitions
+pub fn set(state: DbHealth, reason: Option<String>) {
+    let mut status = STATUS.write();
+    if status.state == state && status.reason == reason {
+        return;
+    }
+    match state {
+        DbHealth::Healthy => tracing::info!(previous = %status.state, "Database healthy"),
+        _ => tracing::warn!(state = %state, reason = reason.as_deref().unwrap_or(""), "Database status changed"),
+    }
+    *status = DbStatus {
+        state,
+        reason,
+        updated_at: chrono::Utc::now().timestamp_millis(),
+    };
+}
+
+/// The error DB-backed commands return while the database is unavailable
+pub fn unavailable_error(status: &DbStatus) -> anyhow::Error {
+    anyhow::anyhow!(
+        "Database unavailable (status: {}): {}. See get_db_status for details",
+        status.state,
+        status.reason.as_deref().unwrap_or("unknown reason")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unavailable_error_references_status() {
+        let status = DbStatus {
+            state: DbHealth::Failed,
+            reason: Some("Failed to create database directory".to_string()),
+            updated_at: 0,
+        };
+        assert_eq!(
+            unavailable_error(&status).to_string(),
+            "Database unavailable (status: failed): Failed to create database directory. See get_db_status for details"
+        );
+        assert_eq!(serde_json::to_value(DbHealth::Degraded).unwrap(), "degraded");
+    }
+}
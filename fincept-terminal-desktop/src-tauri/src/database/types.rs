@@ -55,6 +55,22 @@ pub struct Setting {
     pub updated_at: Option<String>,
 }
 
+/// One setting in a bulk save
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SettingEntry {
+    pub key: String,
+    pub value: String,
+    pub category: Option<String>,
+}
+
+/// Outcome of one entry of a bulk save
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SettingSaveResult {
+    pub key: String,
+    pub saved: bool,
+    pub error: Option<String>,
+}
+
 // ============================================================================
 // LLM Configuration
 // ============================================================================
@@ -102,6 +118,14 @@ pub struct ChatMessage {
     pub tokens_used: Option<i64>,
 }
 
+/// One page of a session's history, oldest message first
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChatMessagePage {
+    pub messages: Vec<ChatMessage>,
+    /// Older messages exist; fetch them with the first message's id as the cursor
+    pub has_more: bool,
+}
+
 // ============================================================================
 // Data Sources
 // ============================================================================
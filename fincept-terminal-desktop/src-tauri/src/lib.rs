@@ -9,7 +9,8 @@ use std::time::Duration;
 use std::sync::mpsc::{channel, Sender, Receiver};
 use serde::Serialize;
 use sha2::{Sha256, Digest};
-use tauri::{Manager, Listener};
+use tauri::Listener;
+use utils::mcp_framing::{self, McpFraming};
 
 // Data sources and commands modules
 mod data_sources;
@@ -22,6 +23,7 @@ mod python_runtime;
 mod worker_pool;
 pub mod websocket;
 pub mod barter_integration;
+pub mod logging;
 
 // Web server module (feature-gated)
 #[cfg(feature = "web")]
@@ -34,6 +36,7 @@ pub struct MCPProcess {
     child: Child,
     stdin: Arc<Mutex<ChildStdin>>,
     response_rx: Receiver<String>,
+    framing: McpFraming,
 }
 
 // Global state to manage MCP server processes
@@ -46,6 +49,7 @@ pub struct WebSocketState {
     pub manager: Arc<tokio::sync::RwLock<websocket::WebSocketManager>>,
     pub router: Arc<tokio::sync::RwLock<websocket::MessageRouter>>,
     pub services: Arc<tokio::sync::RwLock<WebSocketServices>>,
+    pub metrics_cache: Arc<websocket::metrics_cache::MetricsCache>,
 }
 
 pub struct WebSocketServices {
@@ -55,6 +59,40 @@ pub struct WebSocketServices {
     pub monitoring: websocket::services::MonitoringService,
 }
 
+impl WebSocketServices {
+    /// Stop the background tasks the services run
+    pub fn shutdown(&self) {
+        self.monitoring.stop_monitoring();
+    }
+}
+
+impl WebSocketState {
+    /// Metrics of every provider; `fresh` skips the short-lived snapshot (see `metrics_cache`)
+    pub async fn all_metrics(&self, fresh: bool) -> Vec<websocket::types::ConnectionMetrics> {
+        self.metrics_cache.get(&self.manager, fresh).await
+    }
+
+    /// Evaluate a monitor condition against the last `last_n` routed ticks (default 100)
+    /// and the current book, without saving it or raising alerts
+    pub async fn test_monitor_condition(
+        &self,
+        condition: &websocket::services::monitoring::MonitorCondition,
+        last_n: Option<usize>,
+    ) -> websocket::services::monitoring::ConditionTest {
+        let ticks = self.router.read().await.recent_tickers(&condition.provider, &condition.symbol);
+        let last_n = last_n.unwrap_or(100).min(websocket::replay::REPLAY_TICKS);
+        let ticks = &ticks[ticks.len().saturating_sub(last_n)..];
+        let book = self.services.read().await.monitoring.book_metrics(&condition.provider, &condition.symbol);
+        websocket::services::monitoring::test_condition(condition, ticks, book)
+    }
+
+    /// Close all upstream connections and stop the services' tasks, e.g. when the web server stops
+    pub async fn shutdown(&self) {
+        self.manager.read().await.shutdown().await;
+        self.services.read().await.shutdown();
+    }
+}
+
 #[derive(Debug, Serialize)]
 struct SpawnResult {
     pid: u32,
@@ -84,6 +122,7 @@ fn spawn_mcp_server(
     command: String,
     args: Vec<String>,
     env: HashMap<String, String>,
+    framing: Option<String>,
 ) -> Result<SpawnResult, String> {
     spawn_mcp_server_internal(
         Some(&app),
@@ -92,6 +131,7 @@ fn spawn_mcp_server(
         command,
         args,
         env,
+        McpFraming::parse(framing.as_deref())?,
     )
 }
 
@@ -111,6 +151,8 @@ fn spawn_mcp_server(
 /// * `command` - The command to execute (e.g., "npx", "bunx", "node", or a direct path).
 /// * `args` - Command-line arguments to pass to the spawned process.
 /// * `env` - Environment variables to set for the spawned process.
+/// * `framing` - How messages are delimited on stdin/stdout: newline-delimited JSON
+///   or `Content-Length` headers (see [`McpFraming`]).
 ///
 /// # Bun/npx Substitution
 ///
@@ -130,6 +172,7 @@ pub(crate) fn spawn_mcp_server_internal(
     command: String,
     args: Vec<String>,
     env: HashMap<String, String>,
+    framing: McpFraming,
 ) -> Result<SpawnResult, String> {
     // Determine if we should use bundled Bun (for npx/bunx commands)
     let (fixed_command, fixed_args) = if command == "npx" || command == "bunx" {
@@ -187,6 +230,7 @@ pub(crate) fn spawn_mcp_server_internal(
     match cmd.spawn() {
         Ok(mut child) => {
             let pid = child.id();
+            commands::orphans::record("mcp", &fixed_command, pid);
 
             // Extract stdin and stdout
             let stdin = child.stdin.take().ok_or("Failed to get stdin")?;
@@ -197,19 +241,20 @@ pub(crate) fn spawn_mcp_server_internal(
             let (response_tx, response_rx): (Sender<String>, Receiver<String>) = channel();
 
             // Spawn background thread to read stdout
+            let reader_server_id = server_id.clone();
             thread::spawn(move || {
-                let reader = BufReader::new(stdout);
+                let mut reader = BufReader::new(stdout);
 
-                for line in reader.lines() {
-                    match line {
-                        Ok(content) => {
-                            if !content.trim().is_empty() {
-                                if response_tx.send(content).is_err() {
-                                    break;
-                                }
+                loop {
+                    match mcp_framing::read_message(&mut reader, framing) {
+                        Ok(Some(content)) => {
+                            if response_tx.send(content).is_err() {
+                                break;
                             }
                         }
-                        Err(_) => {
+                        Ok(None) => break,
+                        Err(e) => {
+                            tracing::warn!(server_id = %reader_server_id, error = %e, "Stopped reading MCP server output");
                             break;
                         }
                     }
@@ -217,25 +262,39 @@ pub(crate) fn spawn_mcp_server_internal(
             });
 
             // Spawn background thread to read stderr (for debugging)
+            // Output is also captured to a size-capped per-server file (mcp-<id>.log)
             if let Some(stderr) = stderr {
-                let _server_id_clone = server_id.clone();
+                let server_id_clone = server_id.clone();
+                let mut capture = match logging::open_mcp_stderr_log(&server_id) {
+                    Ok(file) => Some(file),
+                    Err(e) => {
+                        tracing::warn!(server_id = %server_id, error = %e, "Failed to open MCP stderr log");
+                        None
+                    }
+                };
                 thread::spawn(move || {
                     let reader = BufReader::new(stderr);
                     for line in reader.lines() {
                         if let Ok(content) = line {
                             if !content.trim().is_empty() {
-                                eprintln!("[MCP] {}", content);
+                                tracing::debug!(target: "mcp", server_id = %server_id_clone, "{}", content);
+                                if let Some(file) = capture.as_mut() {
+                                    let _ = writeln!(file, "{}", content);
+                                }
                             }
                         }
                     }
                 });
             }
 
+            tracing::info!(server_id = %server_id, pid, command = %fixed_command, ?framing, "Spawned MCP server");
+
             // Store process with communication channels
             let mcp_process = MCPProcess {
                 child,
                 stdin: Arc::new(Mutex::new(stdin)),
                 response_rx,
+                framing,
             };
 
             let mut processes = state.processes.lock().unwrap();
@@ -248,7 +307,7 @@ pub(crate) fn spawn_mcp_server_internal(
             })
         }
         Err(e) => {
-            eprintln!("[Tauri] Failed to spawn MCP server: {}", e);
+            tracing::error!(server_id = %server_id, command = %fixed_command, error = %e, "Failed to spawn MCP server");
             Ok(SpawnResult {
                 pid: 0,
                 success: false,
@@ -273,7 +332,7 @@ pub(crate) fn send_mcp_request_internal(
     server_id: String,
     request: String,
 ) -> Result<String, String> {
-    println!("[Tauri] Sending request to server {}: {}", server_id, request);
+    tracing::debug!(server_id = %server_id, request = %request, "Sending MCP request");
 
     let mut processes = state.processes.lock().unwrap();
 
@@ -281,10 +340,8 @@ pub(crate) fn send_mcp_request_internal(
         // Write request to stdin
         {
             let mut stdin = mcp_process.stdin.lock().unwrap();
-            writeln!(stdin, "{}", request)
+            mcp_framing::write_message(&mut *stdin, mcp_process.framing, &request)
                 .map_err(|e| format!("Failed to write to stdin: {}", e))?;
-            stdin.flush()
-                .map_err(|e| format!("Failed to flush stdin: {}", e))?;
         }
 
         // Wait for response with timeout (30 seconds for initial package download)
@@ -323,10 +380,8 @@ pub(crate) fn send_mcp_notification_internal(
 
     if let Some(mcp_process) = processes.get_mut(&server_id) {
         let mut stdin = mcp_process.stdin.lock().unwrap();
-        writeln!(stdin, "{}", notification)
+        mcp_framing::write_message(&mut *stdin, mcp_process.framing, &notification)
             .map_err(|e| format!("Failed to write notification: {}", e))?;
-        stdin.flush()
-            .map_err(|e| format!("Failed to flush: {}", e))?;
         Ok(())
     } else {
         Err(format!("Server {} not found", server_id))
@@ -360,6 +415,17 @@ pub(crate) fn ping_mcp_server_internal(
     }
 }
 
+/// Liveness of every spawned MCP server, by server id
+pub(crate) fn mcp_server_liveness(state: &MCPState) -> Vec<(String, bool)> {
+    let mut processes = state.processes.lock().unwrap();
+    let mut servers: Vec<(String, bool)> = processes
+        .iter_mut()
+        .map(|(id, mcp_process)| (id.clone(), matches!(mcp_process.child.try_wait(), Ok(None))))
+        .collect();
+    servers.sort();
+    servers
+}
+
 // Kill MCP server
 #[tauri::command]
 fn kill_mcp_server(
@@ -387,6 +453,46 @@ pub(crate) fn kill_mcp_server_internal(
     }
 }
 
+/// Get the path of the application log file (for "Open logs folder" in the UI)
+#[tauri::command]
+fn get_log_file_path() -> Result<String, String> {
+    match logging::log_file_path() {
+        Some(path) => Ok(path.to_string_lossy().to_string()),
+        None => utils::data_dir::logs_dir()
+            .map(|dir| dir.join(logging::LOG_FILE_NAME).to_string_lossy().to_string()),
+    }
+}
+
+/// Recent backend timings (Python scripts, yfinance, RPC) for the built-in profiler
+#[tauri::command]
+fn get_command_timings(limit: Option<usize>, clear: Option<bool>) -> utils::timing::CommandTimingsReport {
+    let report = utils::timing::report(limit);
+    if clear.unwrap_or(false) {
+        utils::timing::clear();
+    }
+    report
+}
+
+/// Abort an in-flight data request started with this `request_id`
+/// Returns false if it already finished or was never registered
+#[tauri::command]
+fn cancel_request(request_id: String) -> bool {
+    utils::cancellation::cancel(&request_id)
+}
+
+/// Fail every Python task waiting for the worker pool as cancelled; with
+/// `cancel_running`, also kill the worker running one (it restarts on the next task)
+#[tauri::command]
+fn drain_worker_pool(cancel_running: Option<bool>) -> worker_pool::DrainResult {
+    worker_pool::drain_worker_pool(cancel_running.unwrap_or(false))
+}
+
+/// Registered commands split into web-available, desktop-only and web-only
+#[tauri::command]
+fn get_command_availability() -> command_registry::CommandAvailability {
+    command_registry::command_availability()
+}
+
 // SHA256 hash for Fyers authentication
 #[tauri::command]
 fn sha256_hash(input: String) -> String {
@@ -396,6 +502,19 @@ fn sha256_hash(input: String) -> String {
     format!("{:x}", result)
 }
 
+// HMAC-SHA256 signature (hex) for exchange request signing
+#[tauri::command]
+fn hmac_sha256(key: String, message: String) -> Result<String, String> {
+    utils::hashing::hmac_sha256_hex(key.as_bytes(), message.as_bytes())
+}
+
+// Hex digest with a selectable algorithm ("sha256" or "sha512")
+#[tauri::command]
+fn hash(algorithm: String, input: String) -> Result<String, String> {
+    let algorithm: utils::hashing::HashAlgorithm = algorithm.parse()?;
+    Ok(utils::hashing::hash_hex(algorithm, input.as_bytes()))
+}
+
 // ============================================================================
 // WEBSOCKET COMMANDS
 // ============================================================================
@@ -407,7 +526,10 @@ async fn ws_set_config(
     config: websocket::types::ProviderConfig,
 ) -> Result<(), String> {
     let manager = state.manager.read().await;
-    manager.set_config(config.clone());
+    manager.set_config(config.clone()).map_err(|e| e.to_string())?;
+    state.router.read().await.set_ticker_throttle(&config.name, config.ticker_throttle_ms);
+    database::operations::save_ws_provider_config(&config.to_db_config())
+        .map_err(|e| format!("Config applied but not saved: {}", e))?;
     Ok(())
 }
 
@@ -417,15 +539,16 @@ async fn ws_connect(
     state: tauri::State<'_, WebSocketState>,
     provider: String,
 ) -> Result<(), String> {
-    eprintln!("[ws_connect] Called for provider: {}", provider);
+    let cmd_id = logging::next_command_id();
+    tracing::info!(cmd_id, command = "ws_connect", provider = %provider, "Connecting");
 
     let manager = state.manager.read().await;
     let result = manager.connect(&provider).await
         .map_err(|e| e.to_string());
 
     match &result {
-        Ok(_) => eprintln!("[ws_connect] ✓ Successfully connected to {}", provider),
-        Err(e) => eprintln!("[ws_connect] ✗ Failed to connect to {}: {}", provider, e),
+        Ok(_) => tracing::info!(cmd_id, command = "ws_connect", provider = %provider, "Connected"),
+        Err(e) => tracing::error!(cmd_id, command = "ws_connect", provider = %provider, error = %e, "Connect failed"),
     }
 
     result
@@ -451,27 +574,114 @@ async fn ws_subscribe(
     channel: String,
     params: Option<serde_json::Value>,
 ) -> Result<(), String> {
-    eprintln!("[ws_subscribe] Called: provider={}, symbol={}, channel={}", provider, symbol, channel);
+    let cmd_id = logging::next_command_id();
+    let symbol = utils::symbol::Symbol::exchange(&symbol)?.into_string();
+    tracing::info!(cmd_id, command = "ws_subscribe", provider = %provider, symbol = %symbol, channel = %channel, "Subscribing");
 
     // Register frontend subscriber
     let topic = format!("{}.{}.{}", provider, channel, symbol);
-    eprintln!("[ws_subscribe] Registering frontend subscriber for topic: {}", topic);
-    state.router.write().await.subscribe_frontend(&topic);
+    let subscribers = state.router.write().await.subscribe_frontend(&topic);
+    tracing::debug!(cmd_id, topic = %topic, subscribers, "Registered frontend subscriber");
 
     // Subscribe via manager
-    eprintln!("[ws_subscribe] Calling manager.subscribe...");
     let manager = state.manager.read().await;
     let result = manager.subscribe(&provider, &symbol, &channel, params).await
         .map_err(|e| e.to_string());
 
     match &result {
-        Ok(_) => eprintln!("[ws_subscribe] ✓ Successfully subscribed to {} {} {}", provider, symbol, channel),
-        Err(e) => eprintln!("[ws_subscribe] ✗ Failed to subscribe: {}", e),
+        Ok(_) => tracing::info!(cmd_id, command = "ws_subscribe", topic = %topic, "Subscribed"),
+        Err(e) => {
+            tracing::error!(cmd_id, command = "ws_subscribe", topic = %topic, error = %e, "Subscribe failed");
+            // Release the frontend reference taken above
+            state.router.read().await.unsubscribe_frontend(&topic);
+        }
     }
 
     result
 }
 
+/// Register frontend topics for `symbols` and subscribe them upstream as one batch
+///
+/// One outcome per distinct symbol, in input order; invalid symbols fail without
+/// reaching the provider.
+pub(crate) async fn subscribe_many_internal(
+    state: &WebSocketState,
+    provider: &str,
+    symbols: &[String],
+    channel: &str,
+    params: Option<serde_json::Value>,
+) -> Vec<websocket::types::SubscribeOutcome> {
+    use websocket::types::SubscribeOutcome;
+
+    let validated: Vec<Result<String, SubscribeOutcome>> = symbols
+        .iter()
+        .map(|raw| {
+            utils::symbol::Symbol::exchange(raw)
+                .map(|symbol| symbol.into_string())
+                .map_err(|e| SubscribeOutcome::failed(raw, e))
+        })
+        .collect();
+    let valid: Vec<String> = validated.iter().filter_map(|v| v.as_ref().ok().cloned()).collect();
+
+    let topic = |symbol: &str| format!("{}.{}.{}", provider, channel, symbol);
+    let mut seen = std::collections::HashSet::new();
+    {
+        let mut router = state.router.write().await;
+        for symbol in valid.iter().filter(|s| seen.insert(s.as_str())) {
+            router.subscribe_frontend(&topic(symbol));
+        }
+    }
+
+    let manager = state.manager.read().await;
+    let mut outcomes: HashMap<String, SubscribeOutcome> = manager
+        .subscribe_many(provider, &valid, channel, params)
+        .await
+        .into_iter()
+        .map(|outcome| (outcome.symbol.clone(), outcome))
+        .collect();
+    drop(manager);
+
+    {
+        // Release the frontend references taken above for failed symbols
+        let router = state.router.read().await;
+        for outcome in outcomes.values().filter(|o| !o.subscribed) {
+            router.unsubscribe_frontend(&topic(&outcome.symbol));
+        }
+    }
+
+    let mut seen = std::collections::HashSet::new();
+    validated
+        .into_iter()
+        .filter_map(|v| match v {
+            Ok(symbol) if seen.insert(symbol.clone()) => outcomes.remove(&symbol),
+            Ok(_) => None,
+            Err(outcome) => seen.insert(outcome.symbol.clone()).then_some(outcome),
+        })
+        .collect()
+}
+
+/// Subscribe several symbols to one channel
+///
+/// Sends a single upstream request where the provider accepts a symbol list.
+#[tauri::command]
+async fn ws_subscribe_many(
+    state: tauri::State<'_, WebSocketState>,
+    provider: String,
+    symbols: Vec<String>,
+    channel: String,
+    params: Option<serde_json::Value>,
+) -> Result<Vec<websocket::types::SubscribeOutcome>, String> {
+    let cmd_id = logging::next_command_id();
+    tracing::info!(cmd_id, command = "ws_subscribe_many", provider = %provider, symbols = symbols.len(), channel = %channel, "Subscribing");
+
+    let outcomes = subscribe_many_internal(&state, &provider, &symbols, &channel, params).await;
+    let failed = outcomes.iter().filter(|o| !o.subscribed).count();
+    if failed > 0 {
+        tracing::warn!(cmd_id, command = "ws_subscribe_many", failed, "Some subscriptions failed");
+    }
+    Ok(outcomes)
+}
+
 /// Unsubscribe from WebSocket channel
 #[tauri::command]
 async fn ws_unsubscribe(
@@ -480,15 +690,28 @@ async fn ws_unsubscribe(
     symbol: String,
     channel: String,
 ) -> Result<(), String> {
-    // Unregister frontend subscriber
+    let symbol = utils::symbol::Symbol::exchange(&symbol)?.into_string();
+    // Unregister frontend subscriber (topic stays live while other views use it)
     state.router.write().await.unsubscribe_frontend(&format!("{}.{}.{}", provider, channel, symbol));
 
-    // Unsubscribe via manager
+    // Unsubscribe via manager (upstream is only dropped for the last subscriber)
     let manager = state.manager.read().await;
     manager.unsubscribe(&provider, &symbol, &channel).await
         .map_err(|e| e.to_string())
 }
 
+/// Channels, auth and batch-subscribe support and symbol format of a provider
+#[tauri::command]
+async fn get_provider_capabilities(
+    state: tauri::State<'_, WebSocketState>,
+    provider: String,
+) -> Result<websocket::adapters::ProviderCapabilities, String> {
+    let manager = state.manager.read().await;
+    manager
+        .capabilities(&provider)
+        .ok_or_else(|| format!("Unknown provider: {}", provider))
+}
+
 /// Get connection metrics for a provider
 #[tauri::command]
 async fn ws_get_metrics(
@@ -503,9 +726,9 @@ async fn ws_get_metrics(
 #[tauri::command]
 async fn ws_get_all_metrics(
     state: tauri::State<'_, WebSocketState>,
+    fresh: Option<bool>,
 ) -> Result<Vec<websocket::types::ConnectionMetrics>, String> {
-    let manager = state.manager.read().await;
-    Ok(manager.get_all_metrics())
+    Ok(state.all_metrics(fresh.unwrap_or(false)).await)
 }
 
 /// Reconnect to provider
@@ -519,6 +742,93 @@ async fn ws_reconnect(
         .map_err(|e| e.to_string())
 }
 
+/// Turn on/off keeping the last raw frame per provider symbol and channel (debug aid;
+/// persisted so it survives restarts)
+#[tauri::command]
+async fn ws_set_raw_capture(
+    state: tauri::State<'_, WebSocketState>,
+    enabled: bool,
+) -> Result<(), String> {
+    use websocket::raw_capture::RAW_CAPTURE_SETTING;
+
+    database::operations::save_setting(
+        RAW_CAPTURE_SETTING,
+        if enabled { "true" } else { "false" },
+        Some("debug"),
+    ).map_err(|e| e.to_string())?;
+
+    state.manager.read().await.set_raw_capture_enabled(enabled);
+    Ok(())
+}
+
+/// Last raw frame a provider sent per symbol and channel, narrowed to `symbol`
+/// and/or `channel` when given (empty unless raw capture is enabled)
+#[tauri::command]
+async fn ws_get_last_raw(
+    state: tauri::State<'_, WebSocketState>,
+    provider: String,
+    symbol: Option<String>,
+    channel: Option<String>,
+) -> Result<Vec<websocket::raw_capture::RawFrame>, String> {
+    let manager = state.manager.read().await;
+    Ok(manager.last_raw(&provider, symbol.as_deref(), channel.as_deref()))
+}
+
+/// Start or stop persisting the tickers of `provider` / `symbol` to the tick history
+/// (persisted so it survives restarts); returns the pairs now recorded
+#[tauri::command]
+async fn set_tick_recording(
+    state: tauri::State<'_, WebSocketState>,
+    provider: String,
+    symbol: utils::symbol::Symbol,
+    enabled: bool,
+) -> Result<Vec<websocket::tick_recorder::RecordingTarget>, String> {
+    let router = state.router.read().await;
+    router.tick_recorder().set_recording(&provider, &symbol, enabled).map_err(|e| e.to_string())
+}
+
+/// Recorded pairs, written and dropped tick counts, and the retention window
+#[tauri::command]
+async fn tick_recorder_status(
+    state: tauri::State<'_, WebSocketState>,
+) -> Result<websocket::tick_recorder::TickRecorderStatus, String> {
+    Ok(state.router.read().await.tick_recorder().status())
+}
+
+/// Recorded ticks between `from` and `to` (Unix ms), oldest first
+#[tauri::command]
+async fn get_tick_history(
+    provider: String,
+    symbol: utils::symbol::Symbol,
+    from: Option<u64>,
+    to: Option<u64>,
+    limit: Option<usize>,
+) -> Result<Vec<websocket::types::TickerData>, String> {
+    let limit = limit.unwrap_or(10_000).min(websocket::tick_recorder::MAX_HISTORY_TICKS);
+    websocket::tick_recorder::get_tick_history(&provider, &symbol, from, to, limit).map_err(|e| e.to_string())
+}
+
+/// Route recorded ticks through the router again at `speed` times real time
+/// (0 = no pauses); returns how many ticks the replay sends
+#[tauri::command]
+async fn replay_ticks(
+    state: tauri::State<'_, WebSocketState>,
+    provider: String,
+    symbol: utils::symbol::Symbol,
+    from: Option<u64>,
+    to: Option<u64>,
+    speed: Option<f64>,
+) -> Result<usize, String> {
+    websocket::tick_recorder::replay_ticks(state.router.clone(), &provider, &symbol, from, to, speed.unwrap_or(1.0))
+        .map_err(|e| e.to_string())
+}
+
+/// Stop a running tick replay
+#[tauri::command]
+async fn stop_tick_replay() -> Result<bool, String> {
+    Ok(websocket::tick_recorder::stop_replay())
+}
+
 // ============================================================================
 // MONITORING COMMANDS
 // ============================================================================
@@ -536,28 +846,17 @@ async fn monitor_add_condition(
     let conn = pool.get().map_err(|e| e.to_string())?;
 
     conn.execute(
-        "INSERT INTO monitor_conditions (provider, symbol, field, operator, value, value2, enabled)
-         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        "INSERT INTO monitor_conditions (provider, symbol, field, operator, value, value2, enabled, notify)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
         params![
             &condition.provider,
             &condition.symbol,
-            match condition.field {
-                websocket::services::monitoring::MonitorField::Price => "price",
-                websocket::services::monitoring::MonitorField::Volume => "volume",
-                websocket::services::monitoring::MonitorField::ChangePercent => "change_percent",
-                websocket::services::monitoring::MonitorField::Spread => "spread",
-            },
-            match condition.operator {
-                websocket::services::monitoring::MonitorOperator::GreaterThan => ">",
-                websocket::services::monitoring::MonitorOperator::LessThan => "<",
-                websocket::services::monitoring::MonitorOperator::GreaterThanOrEqual => ">=",
-                websocket::services::monitoring::MonitorOperator::LessThanOrEqual => "<=",
-                websocket::services::monitoring::MonitorOperator::Equal => "==",
-                websocket::services::monitoring::MonitorOperator::Between => "between",
-            },
+            condition.field.as_str(),
+            condition.operator.as_str(),
             condition.value,
             condition.value2,
             if condition.enabled { 1 } else { 0 },
+            if condition.notify { 1 } else { 0 },
         ],
     ).map_err(|e| e.to_string())?;
 
@@ -570,6 +869,18 @@ async fn monitor_add_condition(
     Ok(id)
 }
 
+/// Whether a condition holds now and how often it held over the last `last_n` ticks
+///
+/// For checking an alert before saving it; nothing is stored or emitted.
+#[tauri::command]
+async fn test_monitor_condition(
+    state: tauri::State<'_, WebSocketState>,
+    condition: websocket::services::monitoring::MonitorCondition,
+    last_n: Option<usize>,
+) -> Result<websocket::services::monitoring::ConditionTest, String> {
+    Ok(state.test_monitor_condition(&condition, last_n).await)
+}
+
 /// Get all monitoring conditions
 #[tauri::command]
 async fn monitor_get_conditions(
@@ -579,9 +890,10 @@ async fn monitor_get_conditions(
     let conn = pool.get().map_err(|e| e.to_string())?;
 
     let mut stmt = conn.prepare(
-        "SELECT id, provider, symbol, field, operator, value, value2, enabled
+        "SELECT id, provider, symbol, field, operator, value, value2, enabled, notify
          FROM monitor_conditions
-         ORDER BY created_at DESC"
+         WHERE group_id IS NULL
+         ORDER BY created_at DESC, id DESC"
     ).map_err(|e| e.to_string())?;
 
     let conditions = stmt
@@ -595,6 +907,7 @@ async fn monitor_get_conditions(
                 value: row.get(5)?,
                 value2: row.get(6)?,
                 enabled: row.get::<_, i32>(7)? == 1,
+                notify: row.get::<_, Option<i32>>(8)?.unwrap_or(0) == 1,
             })
         })
         .map_err(|e| e.to_string())?
@@ -626,41 +939,105 @@ async fn monitor_delete_condition(
     Ok(())
 }
 
-/// Get recent alerts
+/// Alert history, newest first, with optional filters and paging
+///
+/// Passing only `limit` returns the latest alerts; `total` is the
+/// number of alerts matching the filters, for paging with `offset`.
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 async fn monitor_get_alerts(
     _app: tauri::AppHandle,
-    limit: i64,
-) -> Result<Vec<websocket::services::monitoring::MonitorAlert>, String> {
-    use rusqlite::params;
+    limit: Option<i64>,
+    acknowledged: Option<bool>,
+    offset: Option<i64>,
+    provider: Option<String>,
+    symbol: Option<String>,
+    field: Option<String>,
+    since: Option<i64>,
+    until: Option<i64>,
+) -> Result<websocket::services::monitoring::AlertPage, String> {
+    let query = websocket::services::monitoring::AlertQuery {
+        limit,
+        offset,
+        acknowledged,
+        provider,
+        symbol,
+        field,
+        since,
+        until,
+    };
 
     let pool = database::pool::get_pool().map_err(|e| e.to_string())?;
     let conn = pool.get().map_err(|e| e.to_string())?;
+    websocket::services::monitoring::query_alerts(&conn, &query).map_err(|e| e.to_string())
+}
 
-    let mut stmt = conn.prepare(
-        "SELECT id, condition_id, provider, symbol, field, triggered_value, triggered_at
-         FROM monitor_alerts
-         ORDER BY triggered_at DESC
-         LIMIT ?1"
-    ).map_err(|e| e.to_string())?;
+/// Mark one alert as acknowledged, returning whether it changed
+#[tauri::command]
+async fn monitor_acknowledge_alert(id: i64) -> Result<bool, String> {
+    let pool = database::pool::get_pool().map_err(|e| e.to_string())?;
+    let conn = pool.get().map_err(|e| e.to_string())?;
 
-    let alerts = stmt
-        .query_map(params![limit], |row| {
-            Ok(websocket::services::monitoring::MonitorAlert {
-                id: Some(row.get(0)?),
-                condition_id: row.get(1)?,
-                provider: row.get(2)?,
-                symbol: row.get(3)?,
-                field: websocket::services::monitoring::MonitorField::from_str(&row.get::<_, String>(4)?).unwrap(),
-                triggered_value: row.get(5)?,
-                triggered_at: row.get::<_, i64>(6)? as u64,
-            })
-        })
-        .map_err(|e| e.to_string())?
-        .collect::<Result<Vec<_>, _>>()
+    websocket::services::monitoring::acknowledge_alerts(&conn, Some(id))
+        .map(|changed| changed > 0)
+        .map_err(|e| e.to_string())
+}
+
+/// Mark every unacknowledged alert as acknowledged, returning how many changed
+#[tauri::command]
+async fn monitor_acknowledge_all_alerts() -> Result<usize, String> {
+    let pool = database::pool::get_pool().map_err(|e| e.to_string())?;
+    let conn = pool.get().map_err(|e| e.to_string())?;
+
+    websocket::services::monitoring::acknowledge_alerts(&conn, None).map_err(|e| e.to_string())
+}
+
+/// Add a group of conditions evaluated together with AND/OR logic
+#[tauri::command]
+async fn monitor_add_condition_group(
+    state: tauri::State<'_, WebSocketState>,
+    group: websocket::services::monitoring::MonitorConditionGroup,
+) -> Result<i64, String> {
+    let pool = database::pool::get_pool().map_err(|e| e.to_string())?;
+    let conn = pool.get().map_err(|e| e.to_string())?;
+
+    let id = websocket::services::monitoring::insert_condition_group(&conn, &group)
         .map_err(|e| e.to_string())?;
 
-    Ok(alerts)
+    // Reload conditions
+    let services = state.services.read().await;
+    services.monitoring.load_conditions().await.map_err(|e| {
+        format!("Failed to reload monitor conditions after inserting group with id {}: {}", id, e)
+    })?;
+
+    Ok(id)
+}
+
+/// Get all condition groups with their member conditions
+#[tauri::command]
+async fn monitor_get_condition_groups() -> Result<Vec<websocket::services::monitoring::MonitorConditionGroup>, String> {
+    let pool = database::pool::get_pool().map_err(|e| e.to_string())?;
+    let conn = pool.get().map_err(|e| e.to_string())?;
+
+    websocket::services::monitoring::load_condition_groups(&conn, false).map_err(|e| e.to_string())
+}
+
+/// Delete a condition group and its member conditions; false when no such group existed
+#[tauri::command]
+async fn monitor_delete_condition_group(
+    state: tauri::State<'_, WebSocketState>,
+    id: i64,
+) -> Result<bool, String> {
+    let pool = database::pool::get_pool().map_err(|e| e.to_string())?;
+    let conn = pool.get().map_err(|e| e.to_string())?;
+
+    let deleted = websocket::services::monitoring::delete_condition_group(&conn, id).map_err(|e| e.to_string())?;
+
+    // Reload conditions
+    let services = state.services.read().await;
+    services.monitoring.load_conditions().await.map_err(|e| e.to_string())?;
+
+    Ok(deleted)
 }
 
 /// Load monitoring conditions on startup
@@ -672,6 +1049,25 @@ async fn monitor_load_conditions(
     services.monitoring.load_conditions().await.map_err(|e| e.to_string())
 }
 
+/// Enable or disable native notifications for every monitoring alert
+/// (individual conditions can still opt in via their `notify` flag)
+#[tauri::command]
+async fn monitor_set_notifications_enabled(
+    state: tauri::State<'_, WebSocketState>,
+    enabled: bool,
+) -> Result<(), String> {
+    use websocket::services::monitoring::NOTIFICATIONS_SETTING_KEY;
+
+    database::operations::save_setting(
+        NOTIFICATIONS_SETTING_KEY,
+        if enabled { "true" } else { "false" },
+        Some("monitoring"),
+    ).map_err(|e| e.to_string())?;
+
+    state.services.read().await.monitoring.set_notifications_enabled(enabled);
+    Ok(())
+}
+
 // Windows-specific imports to hide console windows
 #[cfg(target_os = "windows")]
 use std::os::windows::process::CommandExt;
@@ -724,7 +1120,7 @@ fn execute_python_script(
                     .map_err(|e| format!("Failed to parse output: {}", e))
             } else {
                 let error = String::from_utf8_lossy(&output.stderr);
-                Err(format!("Python script failed: {}", error))
+                Err(utils::python_errors::python_failure(&error))
             }
         }
         Err(e) => Err(format!("Failed to execute Python script: {}", e)),
@@ -735,25 +1131,51 @@ fn execute_python_script(
 pub fn run() {
     use crate::generate_handler_from_list;
 
+    // Validate portable data directory (--data-dir / FINCEPT_DATA_DIR) before anything writes to it
+    // (logging is not up yet, so this is the one place that still prints directly)
+    let data_dir = match utils::data_dir::validate_data_dir() {
+        Ok(dir) => dir,
+        Err(e) => {
+            eprintln!("CRITICAL ERROR: Invalid data directory: {}", e);
+            std::process::exit(1);
+        }
+    };
+
     // Initialize high-performance Rust SQLite database
     // CRITICAL: Database is required for paper trading and other core features
-    if let Err(e) = tokio::runtime::Runtime::new().unwrap().block_on(database::initialize()) {
-        eprintln!("========================================");
-        eprintln!("CRITICAL ERROR: Failed to initialize database!");
-        eprintln!("Error: {}", e);
-        eprintln!("The application cannot function without the database.");
-        eprintln!("Please ensure you have write permissions to:");
-        eprintln!("  Windows: %APPDATA%\\fincept-terminal");
-        eprintln!("  macOS: ~/Library/Application Support/fincept-terminal");
-        eprintln!("  Linux: ~/.local/share/fincept-terminal");
-        eprintln!("========================================");
-        // Note: We don't panic here to allow the app to show an error UI
-        // The frontend will detect database failures via health checks
+    let db_result = tokio::runtime::Runtime::new().unwrap().block_on(database::initialize());
+
+    // Logging reads its format from settings, so it comes up after the database
+    if let Err(e) = logging::init_logging() {
+        eprintln!("Failed to initialize logging: {}", e);
+    }
+    if let Some(dir) = data_dir {
+        tracing::info!(data_dir = %dir.display(), "Using data directory");
     }
 
+    if let Err(e) = db_result {
+        tracing::error!(
+            error = %e,
+            "CRITICAL: Failed to initialize database. The application cannot function without it. \
+             Please ensure you have write permissions to the data directory \
+             (Windows: %APPDATA%\\fincept-terminal, macOS: ~/Library/Application Support/fincept-terminal, \
+             Linux: ~/.local/share/fincept-terminal)"
+        );
+        // Note: We don't panic here to allow the app to show an error UI.
+        // The failure is recorded in database::status; DB-backed commands return
+        // "Database unavailable" and the frontend reads details via get_db_status.
+    }
+
+    // Read the child processes a crashed previous session may have left running
+    commands::orphans::start_session();
+
     // Initialize WebSocket system
-    let router = Arc::new(tokio::sync::RwLock::new(websocket::MessageRouter::new()));
-    let manager = Arc::new(tokio::sync::RwLock::new(websocket::WebSocketManager::new(router.clone())));
+    let message_router = websocket::MessageRouter::new();
+    message_router.tick_recorder().load_targets();
+    let router = Arc::new(tokio::sync::RwLock::new(message_router));
+    let ws_manager = websocket::WebSocketManager::new(router.clone());
+    ws_manager.set_raw_capture_enabled(websocket::raw_capture::enabled_in_settings());
+    let manager = Arc::new(tokio::sync::RwLock::new(ws_manager));
 
     // Initialize services with default monitoring (will be configured in setup)
     let services = Arc::new(tokio::sync::RwLock::new(WebSocketServices {
@@ -767,6 +1189,7 @@ pub fn run() {
         manager: manager.clone(),
         router: router.clone(),
         services: services.clone(),
+        metrics_cache: Arc::new(websocket::metrics_cache::MetricsCache::from_settings()),
     };
 
     // Initialize Barter trading system (Paper mode by default)
@@ -783,6 +1206,7 @@ pub fn run() {
         .plugin(tauri_plugin_fs::init())
         .plugin(tauri_plugin_updater::Builder::new().build())
         .plugin(tauri_plugin_process::init())
+        .plugin(tauri_plugin_notification::init())
         .manage(MCPState {
             processes: Mutex::new(HashMap::new()),
         })
@@ -795,11 +1219,19 @@ pub fn run() {
             let router_clone = router.clone();
             let services_clone = services.clone();
 
-            // Get database path
-            let db_path = app.path().app_data_dir()
-                .map(|p| p.join("fincept_terminal.db").to_string_lossy().to_string())
+            // Get database path (same resolution as the connection pool, honours --data-dir)
+            let db_path = database::pool::get_db_path()
+                .map(|p| p.to_string_lossy().to_string())
                 .unwrap_or_else(|_| "fincept_terminal.db".to_string());
 
+            // Keep the RSS news cache warm and push `news_updated` to the UI
+            commands::news_scheduler::start(app_handle.clone());
+
+            // Run scheduled commands (news refresh, quote pulls, portfolio summaries)
+            commands::task_scheduler::start(std::sync::Arc::new(
+                commands::task_scheduler::DesktopDispatcher::new(app_handle.clone()),
+            ));
+
             // Use tauri::async_runtime to spawn task in Tauri's runtime
             tauri::async_runtime::spawn(async move {
                 // Set router app handle
@@ -812,22 +1244,33 @@ pub fn run() {
 
                 // Subscribe to ticker stream and start monitoring
                 let ticker_rx = router_clone.read().await.subscribe_ticker();
-                services_guard.monitoring.start_monitoring(ticker_rx);
+                let orderbook_rx = router_clone.read().await.subscribe_orderbook();
+                services_guard.monitoring.start_monitoring(ticker_rx, orderbook_rx);
 
                 // Load existing conditions from database
                 let _ = services_guard.monitoring.load_conditions().await;
 
                 drop(services_guard); // Release the lock before listening
 
-                // Listen for Fyers ticker events from frontend
-                let router_clone_for_event = router_clone.clone();
+                // Listen for Fyers ticker events from frontend. Ticks go through the
+                // batcher: every tick reaches monitoring, the UI gets `ticker_batch`
+                let ticker_tx = websocket::batcher::spawn_ticker_batcher(
+                    app_handle.clone(),
+                    router_clone.clone(),
+                    websocket::batcher::DEFAULT_BATCH_INTERVAL,
+                );
                 let _ = app_handle.listen("fyers_ticker", move |event: tauri::Event| {
                     if let Ok(payload_str) = serde_json::from_str::<serde_json::Value>(event.payload()) {
                         if let Some(payload) = payload_str.as_object() {
+                            // A tick without a usable symbol can't match any topic or condition
+                            let symbol = match payload.get("symbol").and_then(|v| v.as_str()).map(utils::symbol::Symbol::exchange) {
+                                Some(Ok(symbol)) => symbol,
+                                _ => return,
+                            };
                             // Parse ticker data from frontend event
                             let ticker = websocket::types::TickerData {
                                 provider: payload.get("provider").and_then(|v| v.as_str()).unwrap_or("fyers").to_string(),
-                                symbol: payload.get("symbol").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+                                symbol: symbol.into_string(),
                                 price: payload.get("price").and_then(|v| v.as_f64()).unwrap_or(0.0),
                                 volume: payload.get("volume").and_then(|v| v.as_f64()),
                                 bid: payload.get("bid").and_then(|v| v.as_f64()),
@@ -841,13 +1284,10 @@ pub fn run() {
                                 change: payload.get("change").and_then(|v| v.as_f64()),
                                 change_percent: payload.get("change_percent").and_then(|v| v.as_f64()),
                                 timestamp: payload.get("timestamp").and_then(|v| v.as_u64()).unwrap_or(0),
+                                replayed: false,
                             };
 
-                            // Route to monitoring service
-                            let router = router_clone_for_event.clone();
-                            tauri::async_runtime::spawn(async move {
-                                router.read().await.route(websocket::types::MarketMessage::Ticker(ticker)).await;
-                            });
+                            let _ = ticker_tx.send(ticker);
                         }
                     }
                 });
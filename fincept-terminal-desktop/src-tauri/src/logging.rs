@@ -0,0 +1,276 @@
+// Logging facade - tracing based application logs
+//
+// What: Installs a global `tracing` subscriber that writes human-readable logs to
+//       stderr and either human-readable or JSON-structured logs to a file under
+//       the data directory (`<data-dir>/logs/fincept.log`).
+// Why:  Ad-hoc `eprintln!`/`println!` output is unparseable and gets lost when the
+//       app is launched from a desktop shortcut. A single log file with command ids
+//       is something a support user can attach to a ticket.
+// How:  Format is chosen by `FINCEPT_LOG_FORMAT` (env) or the `log_format` setting
+//       (`human` | `json`), env taking precedence. Verbosity follows `RUST_LOG`
+//       (default `info`). Commands tag their events with `cmd_id` from
+//       `next_command_id()` so related lines can be grepped together.
+//
+// Rotation: the app log and each MCP server's stderr capture are written through
+// `RotatingFile`, which rolls `name.log` -> `name.log.1` -> ... once a size cap is
+// hit and keeps a bounded number of old files, so long sessions cannot fill the
+// disk. Caps are tunable via FINCEPT_LOG_MAX_SIZE_MB / FINCEPT_LOG_MAX_FILES.
+
+use once_cell::sync::OnceCell;
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{EnvFilter, Layer};
+
+pub const LOG_FORMAT_ENV: &str = "FINCEPT_LOG_FORMAT";
+pub const LOG_FORMAT_SETTING: &str = "log_format";
+pub const LOG_FILE_NAME: &str = "fincept.log";
+pub const LOG_MAX_SIZE_ENV: &str = "FINCEPT_LOG_MAX_SIZE_MB";
+pub const LOG_MAX_FILES_ENV: &str = "FINCEPT_LOG_MAX_FILES";
+
+const DEFAULT_LOG_MAX_BYTES: u64 = 10 * 1024 * 1024;
+const DEFAULT_LOG_MAX_FILES: usize = 5;
+const MCP_LOG_MAX_BYTES: u64 = 2 * 1024 * 1024;
+const MCP_LOG_MAX_FILES: usize = 3;
+
+static LOG_FILE_PATH: OnceCell<PathBuf> = OnceCell::new();
+static COMMAND_ID: AtomicU64 = AtomicU64::new(1);
+
+/// Output format for the log file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogFormat {
+    Human,
+    Json,
+}
+
+impl LogFormat {
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "human" | "text" | "pretty" => Some(LogFormat::Human),
+            "json" => Some(LogFormat::Json),
+            _ => None,
+        }
+    }
+
+    /// Resolve format from env, then the persisted setting, defaulting to human
+    pub fn resolve() -> Self {
+        if let Some(format) = std::env::var(LOG_FORMAT_ENV).ok().and_then(|v| Self::from_str(&v)) {
+            return format;
+        }
+
+        // Database may not be initialized yet; treat that as "no setting"
+        crate::database::operations::get_setting(LOG_FORMAT_SETTING)
+            .ok()
+            .flatten()
+            .and_then(|v| Self::from_str(&v))
+            .unwrap_or(LogFormat::Human)
+    }
+}
+
+/// Install the global tracing subscriber
+///
+/// Safe to call more than once; only the first call takes effect. Returns the
+/// path of the log file.
+pub fn init_logging() -> Result<PathBuf, String> {
+    if let Some(path) = LOG_FILE_PATH.get() {
+        return Ok(path.clone());
+    }
+
+    let format = LogFormat::resolve();
+    let log_path = crate::utils::data_dir::logs_dir()?.join(LOG_FILE_NAME);
+
+    let max_bytes = std::env::var(LOG_MAX_SIZE_ENV)
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .filter(|mb| *mb > 0)
+        .map(|mb| mb * 1024 * 1024)
+        .unwrap_or(DEFAULT_LOG_MAX_BYTES);
+    let max_files = std::env::var(LOG_MAX_FILES_ENV)
+        .ok()
+        .and_then(|v| v.parse::<usize>().ok())
+        .unwrap_or(DEFAULT_LOG_MAX_FILES);
+
+    let file = RotatingFile::open(&log_path, max_bytes, max_files)
+        .map_err(|e| format!("Failed to open log file {}: {}", log_path.display(), e))?;
+
+    let file_layer = match format {
+        LogFormat::Human => tracing_subscriber::fmt::layer()
+            .with_ansi(false)
+            .with_writer(Mutex::new(file))
+            .boxed(),
+        LogFormat::Json => tracing_subscriber::fmt::layer()
+            .json()
+            .with_current_span(true)
+            .with_writer(Mutex::new(file))
+            .boxed(),
+    };
+
+    let stderr_layer = tracing_subscriber::fmt::layer()
+        .with_writer(std::io::stderr)
+        .boxed();
+
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
+
+    tracing_subscriber::registry()
+        .with(filter)
+        .with(file_layer)
+        .with(stderr_layer)
+        .try_init()
+        .map_err(|e| format!("Failed to install log subscriber: {}", e))?;
+
+    let _ = LOG_FILE_PATH.set(log_path.clone());
+    tracing::info!(path = %log_path.display(), format = ?format, "Logging initialized");
+
+    Ok(log_path)
+}
+
+/// Path of the active log file, if logging has been initialized
+pub fn log_file_path() -> Option<PathBuf> {
+    LOG_FILE_PATH.get().cloned()
+}
+
+/// Open the bounded stderr capture file for an MCP server
+///
+/// Lives next to the app log as `mcp-<server_id>.log`. The reader thread owns the
+/// returned writer, so rotation swaps the underlying file without the thread
+/// ever holding a stale handle.
+pub fn open_mcp_stderr_log(server_id: &str) -> io::Result<RotatingFile> {
+    let dir = crate::utils::data_dir::logs_dir()
+        .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+    let safe_id: String = server_id
+        .chars()
+        .map(|c| if c.is_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect();
+    RotatingFile::open(&dir.join(format!("mcp-{}.log", safe_id)), MCP_LOG_MAX_BYTES, MCP_LOG_MAX_FILES)
+}
+
+/// Size-capped log file that rolls over to numbered backups
+pub struct RotatingFile {
+    path: PathBuf,
+    max_bytes: u64,
+    max_files: usize,
+    file: File,
+    written: u64,
+}
+
+impl RotatingFile {
+    /// Open (append) `path`, rotating once it exceeds `max_bytes` and keeping at
+    /// most `max_files` rotated backups
+    pub fn open(path: &Path, max_bytes: u64, max_files: usize) -> io::Result<Self> {
+        let file = Self::open_append(path)?;
+        let written = file.metadata().map(|m| m.len()).unwrap_or(0);
+        Ok(Self {
+            path: path.to_path_buf(),
+            max_bytes,
+            max_files,
+            file,
+            written,
+        })
+    }
+
+    fn open_append(path: &Path) -> io::Result<File> {
+        std::fs::OpenOptions::new().create(true).append(true).open(path)
+    }
+
+    fn backup_path(&self, index: usize) -> PathBuf {
+        let mut name = self.path.as_os_str().to_os_string();
+        name.push(format!(".{}", index));
+        PathBuf::from(name)
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        self.file.flush()?;
+
+        if self.max_files == 0 {
+            // No backups requested: just truncate in place
+            self.file = std::fs::OpenOptions::new()
+                .create(true)
+                .write(true)
+                .truncate(true)
+                .open(&self.path)?;
+            self.written = 0;
+            return Ok(());
+        }
+
+        let _ = std::fs::remove_file(self.backup_path(self.max_files));
+        for index in (1..self.max_files).rev() {
+            let from = self.backup_path(index);
+            if from.exists() {
+                let _ = std::fs::rename(&from, self.backup_path(index + 1));
+            }
+        }
+        if std::fs::rename(&self.path, self.backup_path(1)).is_err() {
+            // Windows refuses to rename a file we still hold open: copy + truncate instead
+            std::fs::copy(&self.path, self.backup_path(1))?;
+            self.file.set_len(0)?;
+        }
+
+        self.file = Self::open_append(&self.path)?;
+        self.written = 0;
+        Ok(())
+    }
+}
+
+impl Write for RotatingFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.written > 0 && self.written + buf.len() as u64 > self.max_bytes {
+            self.rotate()?;
+        }
+        let n = self.file.write(buf)?;
+        self.written += n as u64;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+/// Allocate a process-unique id used to correlate log lines for one command
+pub fn next_command_id() -> u64 {
+    COMMAND_ID.fetch_add(1, Ordering::Relaxed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_log_format_from_str() {
+        assert_eq!(LogFormat::from_str("json"), Some(LogFormat::Json));
+        assert_eq!(LogFormat::from_str(" JSON "), Some(LogFormat::Json));
+        assert_eq!(LogFormat::from_str("human"), Some(LogFormat::Human));
+        assert_eq!(LogFormat::from_str("xml"), None);
+    }
+
+    #[test]
+    fn test_rotating_file_caps_size_and_backups() {
+        let dir = std::env::temp_dir().join(format!("fincept_log_test_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("test.log");
+
+        let mut file = RotatingFile::open(&path, 16, 2).unwrap();
+        for _ in 0..10 {
+            file.write_all(b"0123456789\n").unwrap();
+        }
+        file.flush().unwrap();
+
+        assert!(std::fs::metadata(&path).unwrap().len() <= 16);
+        assert!(file.backup_path(1).exists());
+        assert!(file.backup_path(2).exists());
+        assert!(!file.backup_path(3).exists());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_command_ids_are_unique() {
+        let a = next_command_id();
+        let b = next_command_id();
+        assert_ne!(a, b);
+    }
+}
@@ -1,8 +1,14 @@
 // Python Runtime Module - Worker Pool Integration
 // Provides backward-compatible API using worker pool instead of PyO3
 
+use crate::utils::coalesce::Coalescer;
+use once_cell::sync::Lazy;
 use std::path::PathBuf;
 
+/// In-flight Python calls keyed by (script, args) - args[0] is the command name
+static INFLIGHT_SCRIPTS: Lazy<Coalescer<(PathBuf, Vec<String>), Result<String, String>>> =
+    Lazy::new(Coalescer::new);
+
 /// Execute a Python script using the worker pool (async version)
 /// This is the primary execution method - fast, persistent workers, no subprocess spawning
 pub async fn execute_python_script_async(
@@ -12,12 +18,20 @@ pub async fn execute_python_script_async(
     // Determine venv based on script path or library requirements
     let venv = determine_venv_for_script(script_path);
 
+    let script_name = script_path.file_name().map(|n| n.to_string_lossy()).unwrap_or_default();
+    // Scripts are keyed by file name in `command_timeouts`
+    let timeout = crate::utils::command_timeout::timeout_for(&script_name);
+
+    // e.g. "yfinance_data.py quote" - the first arg is the script's command
+    let label = format!("{} {}", script_name, args.first().map(String::as_str).unwrap_or(""));
+
     // Use worker pool
-    crate::worker_pool::execute_python_script(
-        script_path.clone(),
-        args,
-        venv,
-    ).await
+    crate::utils::timing::timed_async(
+        "python",
+        label.trim_end(),
+        crate::worker_pool::execute_python_script(script_path.clone(), args, venv, timeout),
+    )
+    .await
 }
 
 /// Execute a Python script using the worker pool (blocking sync wrapper)
@@ -46,6 +60,19 @@ pub fn execute_python_script(
     }
 }
 
+/// Execute a read-only Python data command, sharing the result with identical
+/// concurrent calls instead of running the script once per caller
+///
+/// Only use for idempotent fetches (macro/market data); commands with side
+/// effects must keep using `execute_python_script`.
+pub fn execute_python_script_coalesced(
+    script_path: &PathBuf,
+    args: Vec<String>,
+) -> Result<String, String> {
+    let key = (script_path.clone(), args.clone());
+    INFLIGHT_SCRIPTS.run(key, || execute_python_script(script_path, args))
+}
+
 /// Execute Python code directly (for simple calculations)
 /// Note: This spawns a temporary Python process since workers are script-based
 pub async fn execute_python_code(code: &str) -> Result<String, String> {
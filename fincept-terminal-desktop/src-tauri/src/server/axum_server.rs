@@ -5,13 +5,27 @@
 // - POST /api/rpc - JSON-RPC endpoint for all commands
 // - GET /api/health - Health check endpoint
 // - GET /api/ready - Readiness check endpoint
-// - WS /ws - WebSocket endpoint for real-time data
+// - GET /api/commands - Commands available in web mode vs desktop only
+// - GET /api/docs - API documentation page (also at `/` without a frontend)
+// - WS /ws - WebSocket endpoint for real-time data (`hello` frame first, then
+//   `websocket::types::WsEvent` frames)
+// - Everything else - the built frontend when `ServerConfig::static_dir` is set
+//   (env `FINCEPT_STATIC_DIR`), with client-side routes answered by index.html
 //
 // Production Features:
 // - Request tracing with unique request IDs
 // - Structured logging
 // - CORS configuration
 // - Health and readiness checks
+// - API key: with `ServerConfig::api_key` set (env `FINCEPT_API_KEY`), `/rpc`
+//   requests must carry it or a scoped token; without one the API is open to
+//   anyone who can reach the server
+// - Scoped tokens: a request carrying one (`x-fincept-token`) may only call the
+//   commands it was issued for (see `scoped_tokens`). A shared link passes it
+//   to the frontend as `/#token=<token>`, which the frontend sends as that header
+// - Graceful shutdown on Ctrl+C / SIGTERM: in-flight requests finish, then the
+//   task scheduler stops and the upstream WebSocket connections and their tasks
+//   are closed
 //
 // Usage:
 // Run with: cargo run --bin fincept-server --features web
@@ -21,7 +35,7 @@ use axum::{
         ws::{Message, WebSocket, WebSocketUpgrade},
         State,
     },
-    http::{HeaderValue, Method, Request, StatusCode},
+    http::{HeaderMap, HeaderValue, Method, Request, StatusCode},
     middleware::{self, Next},
     response::{IntoResponse, Response},
     routing::{get, post, any},
@@ -29,30 +43,57 @@ use axum::{
 };
 use futures::{SinkExt, StreamExt};
 use std::collections::HashMap;
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::{Arc, Mutex};
-use std::time::Instant;
+use std::time::{Duration, Instant};
 use tower_http::cors::{AllowOrigin, Any, CorsLayer};
+use tower_http::services::{ServeDir, ServeFile};
 use tower_http::trace::TraceLayer;
 use tower_http::request_id::{MakeRequestUuid, PropagateRequestIdLayer, SetRequestIdLayer};
 
 use super::rpc::dispatch;
-use super::types::{HealthResponse, RpcRequest, ServerConfig, ServerState};
+use crate::websocket::types::WsEvent;
+use super::types::{ErrorKind, HealthResponse, RpcRequest, RpcResponse, ServerConfig, ServerState};
 
 /// Start the Axum web server
 pub async fn run_server(config: ServerConfig) -> Result<(), Box<dyn std::error::Error>> {
+    // Validate portable data directory before the database is created in it
+    let data_dir = crate::utils::data_dir::validate_data_dir()?;
+    if let Some(dir) = &config.static_dir {
+        check_static_dir(dir)?;
+    }
+
     // Initialize the database
     crate::database::initialize().await?;
 
+    // Install log subscriber (format may come from the settings table)
+    if let Err(e) = crate::logging::init_logging() {
+        eprintln!("Failed to initialize logging: {}", e);
+    }
+    if let Some(dir) = data_dir {
+        tracing::info!(data_dir = %dir.display(), "Using data directory");
+    }
+    super::command_log::init_from_settings();
+
     let server_state = Arc::new(ServerState {
         start_time: Instant::now(),
         config: config.clone(),
         request_count: std::sync::atomic::AtomicU64::new(0),
+        command_stats: Default::default(),
+        ws_connections: AtomicU64::new(0),
         ws_state: init_websocket_state().await?,
         mcp_state: Arc::new(crate::MCPState {
             processes: Mutex::new(HashMap::new()),
         }),
     });
 
+    // Scheduled tasks run through the same dispatcher as /api/rpc
+    crate::commands::task_scheduler::start(Arc::new(super::rpc::RpcTaskDispatcher::new(server_state.clone())));
+
+    // Upstream sockets are closed once the server has stopped
+    let shutdown_state = server_state.clone();
+
     // Request ID layer for tracing
     let x_request_id = axum::http::HeaderName::from_static("x-request-id");
 
@@ -61,9 +102,15 @@ pub async fn run_server(config: ServerConfig) -> Result<(), Box<dyn std::error::
         .route("/api/rpc", post(rpc_handler))
         .route("/api/health", get(health_handler))
         .route("/api/ready", get(ready_handler))
+        .route("/api/commands", get(commands_handler))
         .route("/api/forum/*path", any(forum_handler))
-        .route("/", get(index_handler))
-        .route("/ws", get(ws_handler))
+        .route("/api/docs", get(index_handler))
+        .route("/ws", get(ws_handler));
+    let app = match &config.static_dir {
+        Some(dir) => with_frontend(app, dir),
+        None => app.route("/", get(index_handler)),
+    };
+    let app = app
         .layer(middleware::from_fn_with_state(server_state.clone(), request_logging_middleware))
         .layer(PropagateRequestIdLayer::new(x_request_id.clone()))
         .layer(SetRequestIdLayer::new(x_request_id.clone(), MakeRequestUuid))
@@ -117,7 +164,12 @@ pub async fn run_server(config: ServerConfig) -> Result<(), Box<dyn std::error::
     println!("║  • POST /api/rpc    - JSON-RPC commands                   ║");
     println!("║  • GET  /api/health - Health check                        ║");
     println!("║  • GET  /api/ready  - Readiness check                     ║");
-    println!("║  • GET  /           - API documentation                   ║");
+    println!("║  • GET  /api/commands - Web vs desktop command list       ║");
+    println!("║  • GET  /api/docs   - API documentation                   ║");
+    match &config.static_dir {
+        Some(dir) => println!("║  • GET  /           - Web UI from {:<23} ║", dir.display()),
+        None => println!("║  • GET  /           - API documentation                   ║"),
+    }
     println!("║  • WS   /ws         - Real-time data stream               ║");
     println!("╠═══════════════════════════════════════════════════════════╣");
     println!("║  Features:                                                ║");
@@ -129,11 +181,77 @@ pub async fn run_server(config: ServerConfig) -> Result<(), Box<dyn std::error::
     println!();
 
     let listener = tokio::net::TcpListener::bind(&addr).await?;
-    axum::serve(listener, app).await?;
+    axum::serve(listener, app).with_graceful_shutdown(shutdown_signal()).await?;
+
+    tracing::info!("Server stopped, stopping scheduled tasks and closing upstream WebSocket connections");
+    crate::commands::task_scheduler::stop();
+    shutdown_state.ws_state.shutdown().await;
 
     Ok(())
 }
 
+/// Resolves on Ctrl+C, or SIGTERM on Unix (`docker stop`, systemd)
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        if tokio::signal::ctrl_c().await.is_err() {
+            std::future::pending::<()>().await;
+        }
+    };
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut signal) => {
+                signal.recv().await;
+            }
+            Err(_) => std::future::pending::<()>().await,
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {}
+        _ = terminate => {}
+    }
+    tracing::info!("Shutdown signal received");
+}
+
+/// Fail fast when the frontend directory can't be served
+fn check_static_dir(dir: &Path) -> Result<(), String> {
+    if !dir.join("index.html").is_file() {
+        return Err(format!(
+            "Static directory {} has no index.html; point FINCEPT_STATIC_DIR at the built frontend (dist)",
+            dir.display()
+        ));
+    }
+    Ok(())
+}
+
+/// Serve the built frontend from `dir` for every path no route claims
+///
+/// Existing files are returned as they are; anything else gets index.html so
+/// client-side routes survive a reload. Paths under `/api` and `/ws` are never
+/// answered with the app: an unknown API path stays a 404.
+fn with_frontend<S: Clone + Send + Sync + 'static>(router: Router<S>, dir: &Path) -> Router<S> {
+    let files = ServeDir::new(dir).fallback(ServeFile::new(dir.join("index.html")));
+    router.fallback(move |request: Request<axum::body::Body>| {
+        let mut files = files.clone();
+        async move {
+            let path = request.uri().path();
+            let reserved = ["/api", "/ws"]
+                .iter()
+                .any(|prefix| path == *prefix || path.starts_with(&format!("{}/", prefix)));
+            if reserved {
+                return StatusCode::NOT_FOUND.into_response();
+            }
+            match tower::Service::call(&mut files, request).await {
+                Ok(response) => response.map(axum::body::Body::new),
+                Err(never) => match never {},
+            }
+        }
+    })
+}
+
 /// Request logging middleware
 async fn request_logging_middleware(
     State(state): State<Arc<ServerState>>,
@@ -188,37 +306,185 @@ async fn request_logging_middleware(
 /// Accepts JSON-RPC style requests and dispatches to command handlers
 async fn rpc_handler(
     State(state): State<Arc<ServerState>>,
+    headers: HeaderMap,
     Json(request): Json<RpcRequest>,
 ) -> impl IntoResponse {
     let cmd = request.cmd.clone();
     tracing::debug!(command = %cmd, "Processing RPC command");
-    
-    let response = dispatch(state, request).await;
+
+    let header = |name: &str| headers.get(name).and_then(|v| v.to_str().ok());
+    let token = header(super::scoped_tokens::TOKEN_HEADER);
+    if let (None, Some(api_key)) = (token, state.config.api_key.as_deref()) {
+        let presented = header(super::scoped_tokens::API_KEY_HEADER)
+            .or_else(|| header("authorization").and_then(|v| v.strip_prefix("Bearer ")));
+        if !presented.is_some_and(|presented| super::scoped_tokens::api_key_matches(api_key, presented)) {
+            tracing::warn!(command = %cmd, "RPC command refused: missing or invalid API key");
+            let response = RpcResponse::err("Missing or invalid API key").with_kind(ErrorKind::Unauthorized);
+            return (StatusCode::UNAUTHORIZED, Json(response));
+        }
+    }
+    if let Some(token) = token {
+        if let Err(denial) = super::scoped_tokens::authorize(token, &cmd) {
+            tracing::warn!(command = %cmd, ?denial, "RPC command refused by scoped token");
+            let kind = match denial {
+                super::scoped_tokens::TokenDenial::OutOfScope => ErrorKind::Forbidden,
+                _ => ErrorKind::Unauthorized,
+            };
+            let response = RpcResponse::err(denial.message(&cmd)).with_kind(kind);
+            let status = StatusCode::from_u16(response.status_code()).unwrap_or(StatusCode::UNAUTHORIZED);
+            return (status, Json(response));
+        }
+    }
+
+    let started = std::time::Instant::now();
+    let logged_args = super::command_log::is_enabled().then(|| request.args.clone());
+    let response = dispatch(state.clone(), request).await;
+    if let Some(args) = logged_args {
+        super::command_log::record(&cmd, &args, response.success, response.error.as_deref());
+    }
+    let error = if response.success {
+        None
+    } else {
+        Some(response.error.as_deref().unwrap_or("failed"))
+    };
+    crate::utils::timing::record("rpc", &cmd, started, error);
+
+    // Unrecognized names share one counter so clients can't grow the map
+    let known = crate::command_registry::WEB_RPC_COMMANDS.contains(&cmd.as_str())
+        || crate::command_registry::is_known_command(&cmd);
+    state
+        .command_stats
+        .record(if known { cmd.as_str() } else { "<unknown>" }, response.success);
     
     if response.success {
         tracing::debug!(command = %cmd, "RPC command succeeded");
     } else {
         tracing::warn!(command = %cmd, error = ?response.error, "RPC command failed");
     }
-    
-    Json(response)
+
+    // The JSON body is the same either way; only the status line differs
+    let status = if state.config.rpc_status_codes {
+        StatusCode::from_u16(response.status_code()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR)
+    } else {
+        StatusCode::OK
+    };
+    (status, Json(response))
 }
 
 /// WebSocket handler for real-time data streaming
+///
+/// Beyond `max_ws_connections` open clients the upgrade is refused with 503.
 async fn ws_handler(
     ws: WebSocketUpgrade,
     State(state): State<Arc<ServerState>>,
-) -> impl IntoResponse {
-    ws.on_upgrade(move |socket| handle_ws(socket, state))
+) -> Response {
+    let Some(slot) = WsSlot::acquire(&state) else {
+        tracing::warn!(limit = state.config.max_ws_connections, "WebSocket connection limit reached");
+        return (StatusCode::SERVICE_UNAVAILABLE, "Too many WebSocket connections").into_response();
+    };
+    ws.on_upgrade(move |socket| handle_ws(socket, state, slot)).into_response()
+}
+
+/// A reserved `/ws` connection, released when dropped (also if the upgrade never completes)
+struct WsSlot(Arc<ServerState>);
+
+impl WsSlot {
+    fn acquire(state: &Arc<ServerState>) -> Option<Self> {
+        let max = state.config.max_ws_connections as u64;
+        state
+            .ws_connections
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |n| (max == 0 || n < max).then_some(n + 1))
+            .ok()
+            .map(|_| WsSlot(state.clone()))
+    }
+}
+
+impl Drop for WsSlot {
+    fn drop(&mut self) {
+        self.0.ws_connections.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// Evicts a client whose send buffer stays full, i.e. one that stopped reading
+struct SlowClientGuard {
+    epoch: Instant,
+    /// Millis since `epoch` (plus one) when the buffer filled up; 0 while draining
+    full_since_ms: AtomicU64,
+    timeout: Duration,
+    evict: tokio::sync::Notify,
+}
+
+impl SlowClientGuard {
+    fn new(timeout: Duration) -> Self {
+        Self {
+            epoch: Instant::now(),
+            full_since_ms: AtomicU64::new(0),
+            timeout,
+            evict: tokio::sync::Notify::new(),
+        }
+    }
+
+    /// Queue `message` for the client; false once the connection is closed
+    fn try_send(&self, tx: &tokio::sync::mpsc::Sender<Message>, message: Message, kind: &str) -> bool {
+        match tx.try_send(message) {
+            Ok(_) => {
+                self.full_since_ms.store(0, Ordering::Relaxed);
+                true
+            }
+            Err(tokio::sync::mpsc::error::TrySendError::Full(_)) => {
+                let now = self.epoch.elapsed().as_millis() as u64 + 1;
+                let since = self
+                    .full_since_ms
+                    .compare_exchange(0, now, Ordering::Relaxed, Ordering::Relaxed)
+                    .unwrap_or_else(|since| since);
+                if now - since >= self.timeout.as_millis() as u64 {
+                    self.evict.notify_one();
+                } else {
+                    tracing::warn!("WebSocket channel full, dropping {} message", kind);
+                }
+                true
+            }
+            Err(tokio::sync::mpsc::error::TrySendError::Closed(_)) => false,
+        }
+    }
+}
+
+/// Forward a broadcast stream to the client as typed `WsEvent` frames
+fn spawn_forwarder<T>(
+    mut rx: tokio::sync::broadcast::Receiver<T>,
+    wrap: fn(T) -> WsEvent,
+    tx: tokio::sync::mpsc::Sender<Message>,
+    guard: Arc<SlowClientGuard>,
+) -> tokio::task::JoinHandle<()>
+where
+    T: Clone + Send + 'static,
+{
+    tokio::spawn(async move {
+        while let Ok(data) = rx.recv().await {
+            let event = wrap(data);
+            let text = match serde_json::to_string(&event) {
+                Ok(text) => text,
+                Err(e) => {
+                    tracing::warn!(event = event.name(), error = %e, "Failed to serialize WebSocket event");
+                    continue;
+                }
+            };
+            if !guard.try_send(&tx, Message::Text(text), event.name().trim_start_matches("ws_")) {
+                break;
+            }
+        }
+    })
 }
 
-async fn handle_ws(socket: WebSocket, state: Arc<ServerState>) {
+async fn handle_ws(socket: WebSocket, state: Arc<ServerState>, _slot: WsSlot) {
     let (mut sender, mut receiver) = socket.split();
     // Use bounded channel with reasonable buffer size (1000 messages)
-    // If client is slow and channel becomes full, new messages will be dropped to prevent memory growth
+    // If client is slow and channel becomes full, new messages are dropped; if it
+    // stays full for `ws_slow_client_timeout` the client is disconnected
     let (tx, mut rx) = tokio::sync::mpsc::channel::<Message>(1000);
+    let guard = Arc::new(SlowClientGuard::new(state.config.ws_slow_client_timeout));
 
-    let (mut ticker_rx, mut orderbook_rx, mut trade_rx, mut candle_rx, mut status_rx) = {
+    let (ticker_rx, orderbook_rx, trade_rx, candle_rx, status_rx) = {
         let router = state.ws_state.router.read().await;
         (
             router.subscribe_ticker(),
@@ -237,127 +503,56 @@ async fn handle_ws(socket: WebSocket, state: Arc<ServerState>) {
         }
     });
 
-    let tx_clone = tx.clone();
-    let ticker_task = tokio::spawn(async move {
-        while let Ok(data) = ticker_rx.recv().await {
-            let payload = serde_json::json!({
-                "event": "ws_ticker",
-                "data": data,
-            });
-            let message_text = payload.to_string();
-            match tx_clone.try_send(Message::Text(message_text)) {
-                Ok(_) => {}
-                Err(tokio::sync::mpsc::error::TrySendError::Full(_)) => {
-                    tracing::warn!("WebSocket channel full, dropping ticker message");
-                }
-                Err(tokio::sync::mpsc::error::TrySendError::Closed(_)) => break,
-            }
-        }
-    });
-
-    let tx_clone = tx.clone();
-    let orderbook_task = tokio::spawn(async move {
-        while let Ok(data) = orderbook_rx.recv().await {
-            let payload = serde_json::json!({
-                "event": "ws_orderbook",
-                "data": data,
-            });
-            let message_text = payload.to_string();
-            match tx_clone.try_send(Message::Text(message_text)) {
-                Ok(_) => {}
-                Err(tokio::sync::mpsc::error::TrySendError::Full(_)) => {
-                    tracing::warn!("WebSocket channel full, dropping orderbook message");
-                }
-                Err(tokio::sync::mpsc::error::TrySendError::Closed(_)) => break,
-            }
-        }
-    });
-
-    let tx_clone = tx.clone();
-    let trade_task = tokio::spawn(async move {
-        while let Ok(data) = trade_rx.recv().await {
-            let payload = serde_json::json!({
-                "event": "ws_trade",
-                "data": data,
-            });
-            let message_text = payload.to_string();
-            match tx_clone.try_send(Message::Text(message_text)) {
-                Ok(_) => {}
-                Err(tokio::sync::mpsc::error::TrySendError::Full(_)) => {
-                    tracing::warn!("WebSocket channel full, dropping trade message");
-                }
-                Err(tokio::sync::mpsc::error::TrySendError::Closed(_)) => break,
-            }
-        }
-    });
-
-    let tx_clone = tx.clone();
-    let candle_task = tokio::spawn(async move {
-        while let Ok(data) = candle_rx.recv().await {
-            let payload = serde_json::json!({
-                "event": "ws_candle",
-                "data": data,
-            });
-            let message_text = payload.to_string();
-            match tx_clone.try_send(Message::Text(message_text)) {
-                Ok(_) => {}
-                Err(tokio::sync::mpsc::error::TrySendError::Full(_)) => {
-                    tracing::warn!("WebSocket channel full, dropping candle message");
-                }
-                Err(tokio::sync::mpsc::error::TrySendError::Closed(_)) => break,
-            }
-        }
-    });
+    // Announce the event schema before any data
+    if let Ok(hello) = serde_json::to_string(&WsEvent::hello()) {
+        guard.try_send(&tx, Message::Text(hello), "hello");
+    }
 
-    let tx_clone = tx.clone();
-    let status_task = tokio::spawn(async move {
-        while let Ok(data) = status_rx.recv().await {
-            let payload = serde_json::json!({
-                "event": "ws_status",
-                "data": data,
-            });
-            let message_text = payload.to_string();
-            match tx_clone.try_send(Message::Text(message_text)) {
-                Ok(_) => {}
-                Err(tokio::sync::mpsc::error::TrySendError::Full(_)) => {
-                    tracing::warn!("WebSocket channel full, dropping status message");
-                }
-                Err(tokio::sync::mpsc::error::TrySendError::Closed(_)) => break,
+    let forwarders = [
+        spawn_forwarder(ticker_rx, |data| WsEvent::Ticker { data }, tx.clone(), guard.clone()),
+        spawn_forwarder(orderbook_rx, |data| WsEvent::OrderBook { data }, tx.clone(), guard.clone()),
+        spawn_forwarder(trade_rx, |data| WsEvent::Trade { data }, tx.clone(), guard.clone()),
+        spawn_forwarder(candle_rx, |data| WsEvent::Candle { data }, tx.clone(), guard.clone()),
+        spawn_forwarder(status_rx, |data| WsEvent::Status { data }, tx.clone(), guard.clone()),
+    ];
+
+    loop {
+        let message = tokio::select! {
+            message = receiver.next() => message,
+            _ = guard.evict.notified() => {
+                tracing::warn!(
+                    timeout_secs = state.config.ws_slow_client_timeout.as_secs(),
+                    "Disconnecting WebSocket client that stopped reading"
+                );
+                break;
             }
-        }
-    });
-
-    while let Some(message) = receiver.next().await {
+        };
         match message {
-            Ok(Message::Close(_)) | Err(_) => break,
-            Ok(Message::Ping(data)) => {
+            None | Some(Ok(Message::Close(_))) | Some(Err(_)) => break,
+            Some(Ok(Message::Ping(data))) => {
                 // Respond to ping with pong to keep connection alive
                 // Use try_send to avoid blocking like other message handlers
-                match tx.try_send(Message::Pong(data)) {
-                    Ok(_) => {}
-                    Err(tokio::sync::mpsc::error::TrySendError::Full(_)) => {
-                        tracing::warn!("WebSocket channel full, dropping ping/pong message");
-                    }
-                    Err(tokio::sync::mpsc::error::TrySendError::Closed(_)) => break,
+                if !guard.try_send(&tx, Message::Pong(data), "ping/pong") {
+                    break;
                 }
             }
-            Ok(Message::Pong(_)) => {
+            Some(Ok(Message::Pong(_))) => {
                 // Pong received, connection is alive
             }
             _ => {}
         }
     }
 
-    ticker_task.abort();
-    orderbook_task.abort();
-    trade_task.abort();
-    candle_task.abort();
-    status_task.abort();
+    for task in forwarders {
+        task.abort();
+    }
     send_task.abort();
 }
 
 async fn init_websocket_state() -> Result<crate::WebSocketState, Box<dyn std::error::Error>> {
-    let router = Arc::new(tokio::sync::RwLock::new(crate::websocket::MessageRouter::new()));
+    let message_router = crate::websocket::MessageRouter::new();
+    message_router.tick_recorder().load_targets();
+    let router = Arc::new(tokio::sync::RwLock::new(message_router));
     let manager = Arc::new(tokio::sync::RwLock::new(crate::websocket::WebSocketManager::new(router.clone())));
     
     let db_path = crate::database::pool::get_db_path()?
@@ -376,11 +571,13 @@ async fn init_websocket_state() -> Result<crate::WebSocketState, Box<dyn std::er
         manager: manager.clone(),
         router: router.clone(),
         services: services.clone(),
+        metrics_cache: Arc::new(crate::websocket::metrics_cache::MetricsCache::from_settings()),
     };
 
     let mut services_guard = services.write().await;
     let ticker_rx = router.read().await.subscribe_ticker();
-    services_guard.monitoring.start_monitoring(ticker_rx);
+    let orderbook_rx = router.read().await.subscribe_orderbook();
+    services_guard.monitoring.start_monitoring(ticker_rx, orderbook_rx);
     if let Err(err) = services_guard.monitoring.load_conditions().await {
         tracing::warn!(error = %err, "Failed to load monitoring conditions");
     }
@@ -388,11 +585,16 @@ async fn init_websocket_state() -> Result<crate::WebSocketState, Box<dyn std::er
     Ok(ws_state)
 }
 
+/// Command listing - which registered commands the RPC endpoint implements
+async fn commands_handler() -> impl IntoResponse {
+    Json(crate::command_registry::command_availability())
+}
+
 /// Health check endpoint - always returns healthy if server is running
 async fn health_handler(State(state): State<Arc<ServerState>>) -> impl IntoResponse {
     let uptime = state.start_time.elapsed().as_secs();
-    // Note: request_count is tracked for potential future metrics but not included in health response
-    // to keep the response minimal. Use /api/ready for more detailed status.
+    // Request counts are left out to keep the response minimal; the `server_stats`
+    // RPC command reports them along with per-command counts.
     
     Json(HealthResponse {
         status: "healthy".to_string(),
@@ -506,6 +708,11 @@ async fn index_handler() -> impl IntoResponse {
         <p><span class="method">GET</span> <code>/api/health</code></p>
         <p>Health check endpoint. Returns server status and uptime.</p>
     </div>
+
+    <div class="endpoint">
+        <p><span class="method">GET</span> <code>/api/commands</code></p>
+        <p>Registered commands split into <code>web</code>, <code>desktop_only</code> and <code>web_only</code>.</p>
+    </div>
     
     <h2>Available Commands</h2>
     
@@ -513,8 +720,14 @@ async fn index_handler() -> impl IntoResponse {
         <p class="category-title">📊 Market Data</p>
         <ul>
             <li><code>get_market_quote</code> - Get real-time stock quote</li>
+            <li><code>get_fx_rate</code> - Exchange rate between two currencies (cached)</li>
             <li><code>get_market_quotes</code> - Get multiple stock quotes</li>
+            <li><code>get_watchlist_quotes</code> - Get a watchlist's stocks with their quotes</li>
             <li><code>get_historical_data</code> - Get historical price data</li>
+            <li><code>get_historical_data_multi</code> - Get historical data for many symbols</li>
+            <li><code>get_historical_data_cached</code> - Get historical data through the local candle cache</li>
+            <li><code>clear_historical_cache</code> - Drop cached candles</li>
+            <li><code>compute_indicators</code> - SMA/EMA/RSI/MACD/Bollinger over candles</li>
             <li><code>get_stock_info</code> - Get company information</li>
             <li><code>get_financials</code> - Get financial statements</li>
             <li><code>get_period_returns</code> - Get period returns (7D, 30D)</li>
@@ -526,9 +739,22 @@ async fn index_handler() -> impl IntoResponse {
         <p class="category-title">⚙️ Settings & Database</p>
         <ul>
             <li><code>db_check_health</code> - Check database status</li>
+            <li><code>cancel_request</code> - Abort an in-flight request started with a <code>requestId</code></li>
+            <li><code>drain_worker_pool</code> - Cancel queued Python tasks, optionally killing the running one</li>
+            <li><code>get_db_status</code> - Database status (healthy/degraded/failed) and reason</li>
+            <li><code>import_database</code> - Merge watchlists/portfolios from another database file</li>
+            <li><code>export_config</code> - Export settings, provider configs and watchlists as a JSON bundle</li>
+            <li><code>import_config</code> - Restore a config bundle</li>
+            <li><code>schedule_task</code> - Run a command on an interval or cron schedule</li>
+            <li><code>list_scheduled_tasks</code> - Scheduled tasks with their last run status</li>
             <li><code>db_get_all_settings</code> - Get all settings</li>
             <li><code>db_get_setting</code> - Get a specific setting</li>
             <li><code>db_save_setting</code> - Save a setting</li>
+            <li><code>db_save_settings</code> - Save several settings in one transaction</li>
+            <li><code>list_profiles</code> - Config profiles and which one is active</li>
+            <li><code>create_profile</code> - Add a config profile, optionally copying another</li>
+            <li><code>delete_profile</code> - Delete a config profile and its rows</li>
+            <li><code>switch_profile</code> - Change the active config profile</li>
             <li><code>check_setup_status</code> - Check system setup status</li>
         </ul>
     </div>
@@ -560,6 +786,7 @@ async fn index_handler() -> impl IntoResponse {
             <li><code>db_get_chat_sessions</code> - Get chat session history</li>
             <li><code>db_add_chat_message</code> - Add message to session</li>
             <li><code>db_get_chat_messages</code> - Get messages for session</li>
+            <li><code>db_get_chat_messages_paged</code> - Newest messages before the beforeId message (in insertion order), with has_more</li>
             <li><code>db_delete_chat_session</code> - Delete a chat session</li>
         </ul>
     </div>
@@ -578,9 +805,12 @@ async fn index_handler() -> impl IntoResponse {
         <ul>
             <li><code>db_list_portfolios</code> - List all portfolios</li>
             <li><code>db_get_portfolio</code> - Get portfolio by ID</li>
+            <li><code>get_portfolio_summary</code> - Value an investment portfolio at current quotes in its currency</li>
             <li><code>db_create_portfolio</code> - Create new portfolio</li>
             <li><code>db_delete_portfolio</code> - Delete a portfolio</li>
+            <li><code>purge_portfolio</code> - Delete a portfolio with its positions, orders, trades and risk limits (requires confirm: true); returns counts per table</li>
             <li><code>db_update_portfolio_balance</code> - Update portfolio balance</li>
+            <li><code>db_adjust_portfolio_balance</code> - Add a fee, PnL or transfer to the balance atomically</li>
         </ul>
     </div>
 
@@ -592,7 +822,12 @@ async fn index_handler() -> impl IntoResponse {
             <li><code>db_get_position_by_symbol</code> - Get open/closed position by symbol</li>
             <li><code>db_get_position_by_symbol_and_side</code> - Get position by symbol and side</li>
             <li><code>db_get_portfolio_positions</code> - List positions by portfolio</li>
+            <li><code>get_consolidated_positions</code> - Net exposure per symbol across an owner's portfolios</li>
             <li><code>db_update_position</code> - Update position fields</li>
+            <li><code>db_reduce_position</code> - Partially or fully close a position with realized PnL</li>
+            <li><code>recompute_unrealized_pnl</code> - Mark all open positions of a portfolio to a price map</li>
+            <li><code>ingest_marks</code> - Mark open positions in every portfolio to imported prices and snapshot them</li>
+            <li><code>get_trade_statistics</code> - Win rate, profit factor and fees of a paper portfolio</li>
             <li><code>db_delete_position</code> - Delete a position</li>
         </ul>
     </div>
@@ -601,6 +836,8 @@ async fn index_handler() -> impl IntoResponse {
         <p class="category-title">🧾 Paper Trading Orders</p>
         <ul>
             <li><code>db_create_order</code> - Create an order</li>
+            <li><code>create_bracket_order</code> - Entry order with linked stop-loss and take-profit (OCO) legs</li>
+            <li><code>open_market_position</code> - Filled market order, trade, position and fee in one transaction</li>
             <li><code>db_get_order</code> - Get order by ID</li>
             <li><code>db_get_portfolio_orders</code> - List orders by portfolio</li>
             <li><code>db_get_pending_orders</code> - List pending/triggered orders</li>
@@ -636,7 +873,15 @@ async fn index_handler() -> impl IntoResponse {
         <p class="category-title">🔧 Utilities</p>
         <ul>
             <li><code>greet</code> - Test endpoint</li>
+            <li><code>server_stats</code> - Request totals, uptime, per-command counts and open WebSocket connections</li>
+            <li><code>create_scoped_token</code> - Expiring token limited to the listed commands, for shareable links (needs <code>FINCEPT_API_KEY</code>)</li>
+            <li><code>get_process_resource_usage</code> - CPU and memory of running MCP servers and Python workers (optional id)</li>
+            <li><code>list_orphans</code> - Child processes a crashed previous session left running</li>
+            <li><code>reap_orphans</code> - Kill those orphaned child processes (optional pids)</li>
             <li><code>sha256_hash</code> - Compute SHA256 hash</li>
+            <li><code>hmac_sha256</code> - HMAC-SHA256 signature (hex)</li>
+            <li><code>hash</code> - SHA256/SHA512 digest (hex)</li>
+            <li><code>tail_log</code> - Last lines of the application log, optionally from a minimum level</li>
         </ul>
     </div>
     
@@ -670,3 +915,67 @@ async fn index_handler() -> impl IntoResponse {
     let html = html.replace("{{VERSION}}", env!("CARGO_PKG_VERSION"));
     (axum::http::StatusCode::OK, [("content-type", "text/html")], html)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_slow_client_evicted_after_buffer_stays_full() {
+        let (tx, mut rx) = tokio::sync::mpsc::channel::<Message>(1);
+        let guard = SlowClientGuard::new(Duration::from_millis(50));
+        let evicted = |guard: &SlowClientGuard| {
+            futures::FutureExt::now_or_never(guard.evict.notified()).is_some()
+        };
+
+        assert!(guard.try_send(&tx, Message::Text("a".into()), "test"));
+        // Full, but not for long enough
+        assert!(guard.try_send(&tx, Message::Text("b".into()), "test"));
+        assert!(!evicted(&guard));
+
+        // Draining resets the clock
+        rx.recv().await.unwrap();
+        assert!(guard.try_send(&tx, Message::Text("c".into()), "test"));
+        assert_eq!(guard.full_since_ms.load(Ordering::Relaxed), 0);
+
+        assert!(guard.try_send(&tx, Message::Text("d".into()), "test"));
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        assert!(guard.try_send(&tx, Message::Text("e".into()), "test"));
+        assert!(evicted(&guard));
+
+        drop(rx);
+        assert!(!guard.try_send(&tx, Message::Text("f".into()), "test"));
+    }
+
+    #[tokio::test]
+    async fn test_frontend_served_without_shadowing_api() {
+        let dir = std::env::temp_dir().join(format!("fincept_static_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(dir.join("assets")).unwrap();
+        std::fs::write(dir.join("index.html"), "<html>app</html>").unwrap();
+        std::fs::write(dir.join("assets/app.js"), "console.log(1)").unwrap();
+        assert!(check_static_dir(&dir).is_ok());
+        assert!(check_static_dir(&dir.join("assets")).is_err());
+
+        let app: Router = with_frontend(Router::new().route("/api/health", get(|| async { "ok" })), &dir);
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        let fetch = |path: &str| {
+            let url = format!("{}{}", base, path);
+            async move {
+                let response = reqwest::get(url).await.unwrap();
+                (response.status().as_u16(), response.text().await.unwrap())
+            }
+        };
+
+        let app_page = (200, "<html>app</html>".to_string());
+        assert_eq!(fetch("/assets/app.js").await, (200, "console.log(1)".to_string()));
+        assert_eq!(fetch("/").await, app_page);
+        // Client-side route
+        assert_eq!(fetch("/portfolio/123").await, app_page);
+        assert_eq!(fetch("/api/health").await, (200, "ok".to_string()));
+        assert_eq!(fetch("/api/unknown").await.0, 404);
+        assert_eq!(fetch("/ws/extra").await.0, 404);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,280 @@
+// Command Log - opt-in record of RPC commands for replaying a user's session
+//
+// What: While enabled, every command handled by the web RPC endpoint is appended
+//       to `logs/command_log.jsonl` as (command, redacted args, timestamp, result
+//       status). `replay_command_log` reads such a file and dispatches the
+//       commands again, in order, reporting where the outcome differs.
+// Why:  Intermittent bugs reported by users could not be reproduced because
+//       nobody knew which commands, with which arguments, led up to them.
+// How:  Off by default and gated by the `debug_command_log` setting, so a
+//       disabled recorder costs one atomic load per request. Arguments whose key
+//       looks like a credential (`config_bundle::is_secret_name`) are replaced
+//       by `REDACTED` at any depth before they reach the file, as is the value
+//       of a setting entry whose key or category does (`api_keys`); replayed
+//       commands receive the placeholder, so steps that needed a secret can fail
+//       on replay. The file rotates like the MCP stderr logs. The recorder's own
+//       commands are neither recorded nor replayed. Replay only reads logs from
+//       the logs directory and skips commands that aren't read-only
+//       (`server::read_only`) unless the caller confirms them.
+
+use crate::database::config_bundle::is_secret_name;
+use crate::logging::RotatingFile;
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Setting that turns recording on at startup
+pub const COMMAND_LOG_SETTING: &str = "debug_command_log";
+pub const COMMAND_LOG_FILE: &str = "command_log.jsonl";
+/// Placeholder written instead of a secret argument
+pub const REDACTED: &str = "[REDACTED]";
+
+const MAX_BYTES: u64 = 10 * 1024 * 1024;
+const MAX_FILES: usize = 2;
+
+/// Commands that manage the log itself
+const UNLOGGED_COMMANDS: &[&str] = &["command_log_set_enabled", "command_log_status", "replay_command_log"];
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static WRITER: Lazy<Mutex<Option<RotatingFile>>> = Lazy::new(|| Mutex::new(None));
+
+/// One recorded command
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CommandLogEntry {
+    pub command: String,
+    pub args: Value,
+    /// Unix milliseconds
+    pub timestamp: i64,
+    pub success: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CommandLogStatus {
+    pub enabled: bool,
+    pub path: Option<String>,
+}
+
+/// Path of the log file in the logs directory
+pub fn log_path() -> Result<PathBuf, String> {
+    Ok(crate::utils::data_dir::logs_dir()?.join(COMMAND_LOG_FILE))
+}
+
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Start or stop recording; the file is opened when recording starts
+pub fn set_enabled(enabled: bool) -> Result<(), String> {
+    let mut writer = WRITER.lock();
+    if enabled && writer.is_none() {
+        let path = log_path()?;
+        let file = RotatingFile::open(&path, MAX_BYTES, MAX_FILES)
+            .map_err(|e| format!("Failed to open command log {}: {}", path.display(), e))?;
+        *writer = Some(file);
+    } else if !enabled {
+        *writer = None;
+    }
+    ENABLED.store(enabled, Ordering::Relaxed);
+    Ok(())
+}
+
+/// Turn recording on if the debug setting asks for it
+pub fn init_from_settings() {
+    let enabled = crate::database::operations::get_setting(COMMAND_LOG_SETTING)
+        .ok()
+        .flatten()
+        .is_some_and(|v| matches!(v.trim(), "true" | "1" | "on"));
+    if enabled {
+        if let Err(e) = set_enabled(true) {
+            tracing::warn!(error = %e, "Command log could not be enabled");
+        }
+    }
+}
+
+pub fn status() -> CommandLogStatus {
+    CommandLogStatus {
+        enabled: is_enabled(),
+        path: log_path().ok().map(|p| p.display().to_string()),
+    }
+}
+
+/// Setting fields holding a setting's value
+const SETTING_VALUE_FIELDS: &[&str] = &["value", "settingValue", "setting_value"];
+
+/// A `{key, value, category}` setting entry whose value is a credential
+fn is_secret_setting(map: &Map<String, Value>) -> bool {
+    let field = |names: &[&str]| names.iter().find_map(|name| map.get(*name)).and_then(Value::as_str);
+    let secret_category = |category: &str| {
+        let category = category.to_lowercase();
+        is_secret_name(&category) || category.contains("key") || category.contains("credential")
+    };
+    field(&["key", "settingKey", "setting_key"]).is_some_and(is_secret_name)
+        || field(&["category"]).is_some_and(secret_category)
+}
+
+/// `args` with every credential-like field replaced by `REDACTED`
+pub fn redact_args(args: &Value) -> Value {
+    match args {
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .map(|(key, value)| {
+                    let secret_value = SETTING_VALUE_FIELDS.contains(&key.as_str()) && is_secret_setting(map);
+                    let value = if secret_value || is_secret_name(key) {
+                        Value::String(REDACTED.to_string())
+                    } else {
+                        redact_args(value)
+                    };
+                    (key.clone(), value)
+                })
+                .collect(),
+        ),
+        Value::Array(items) => Value::Array(items.iter().map(redact_args).collect()),
+        other => other.clone(),
+    }
+}
+
+/// Append a handled command; no-op while disabled
+pub fn record(command: &str, args: &Value, success: bool, error: Option<&str>) {
+    if !is_enabled() || UNLOGGED_COMMANDS.contains(&command) {
+        return;
+    }
+    let entry = CommandLogEntry {
+        command: command.to_string(),
+        args: redact_args(args),
+        timestamp: chrono::Utc::now().timestamp_millis(),
+        success,
+        error: error.map(str::to_string),
+    };
+    let Ok(line) = serde_json::to_string(&entry) else {
+        return;
+    };
+    if let Some(writer) = WRITER.lock().as_mut() {
+        if let Err(e) = writeln!(writer, "{}", line).and_then(|_| writer.flush()) {
+            tracing::warn!(error = %e, "Failed to write command log");
+        }
+    }
+}
+
+/// Entries of a command log file in recorded order
+pub fn read_log(path: &Path) -> Result<Vec<CommandLogEntry>, String> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read command log {}: {}", path.display(), e))?;
+    content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| {
+            serde_json::from_str(line).map_err(|e| format!("Invalid command log entry on line {}: {}", i + 1, e))
+        })
+        .collect()
+}
+
+/// Whether a recorded command is dispatched again on replay
+pub fn is_replayable(command: &str) -> bool {
+    !UNLOGGED_COMMANDS.contains(&command)
+}
+
+/// The log to replay: `path` (a file name or path in the logs directory) or the current log
+pub fn replay_path(path: Option<&str>) -> Result<PathBuf, String> {
+    match path {
+        Some(path) => resolve_in_dir(&crate::utils::data_dir::logs_dir()?, path),
+        None => log_path(),
+    }
+}
+
+fn resolve_in_dir(dir: &Path, path: &str) -> Result<PathBuf, String> {
+    let dir = dir.canonicalize().map_err(|e| format!("Failed to resolve {}: {}", dir.display(), e))?;
+    let resolved = dir
+        .join(path)
+        .canonicalize()
+        .map_err(|e| format!("Failed to read command log {}: {}", path, e))?;
+    if resolved.parent() != Some(dir.as_path()) {
+        return Err(format!("Command logs can only be replayed from {}", dir.display()));
+    }
+    Ok(resolved)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_redacts_secrets_at_any_depth() {
+        let args = json!({
+            "provider": "kraken",
+            "apiKey": "abc",
+            "config": { "api_secret": "def", "symbols": ["BTC"], "nested": [{ "password": "p" }] }
+        });
+        let settings = json!({
+            "key": "ALPHA_VANTAGE_API_KEY",
+            "value": "av-123",
+            "entries": [
+                { "key": "fred_key_primary", "value": "f-1", "category": "api_keys" },
+                { "key": "theme", "value": "dark", "category": "appearance" }
+            ]
+        });
+        assert_eq!(
+            redact_args(&settings),
+            json!({
+                "key": "ALPHA_VANTAGE_API_KEY",
+                "value": REDACTED,
+                "entries": [
+                    { "key": "fred_key_primary", "value": REDACTED, "category": "api_keys" },
+                    { "key": "theme", "value": "dark", "category": "appearance" }
+                ]
+            })
+        );
+        assert_eq!(
+            redact_args(&args),
+            json!({
+                "provider": "kraken",
+                "apiKey": REDACTED,
+                "config": { "api_secret": REDACTED, "symbols": ["BTC"], "nested": [{ "password": REDACTED }] }
+            })
+        );
+    }
+
+    #[test]
+    fn test_reads_entries_and_reports_bad_lines() {
+        let path = std::env::temp_dir().join(format!("fincept_command_log_{}.jsonl", uuid::Uuid::new_v4()));
+        let entry = CommandLogEntry {
+            command: "db_get_setting".to_string(),
+            args: json!({ "key": "theme" }),
+            timestamp: 1,
+            success: false,
+            error: Some("boom".to_string()),
+        };
+        std::fs::write(&path, format!("{}\n\n", serde_json::to_string(&entry).unwrap())).unwrap();
+        assert_eq!(read_log(&path).unwrap(), vec![entry]);
+
+        std::fs::write(&path, "{\"command\": \"x\"}\n").unwrap();
+        assert!(read_log(&path).unwrap_err().contains("line 1"));
+        let _ = std::fs::remove_file(&path);
+
+        assert!(!is_replayable("replay_command_log"));
+        assert!(is_replayable("db_get_setting"));
+    }
+
+    #[test]
+    fn test_replay_paths_stay_in_the_logs_directory() {
+        let root = std::env::temp_dir().join(format!("fincept_replay_dir_{}", uuid::Uuid::new_v4()));
+        let logs = root.join("logs");
+        std::fs::create_dir_all(&logs).unwrap();
+        std::fs::write(logs.join(COMMAND_LOG_FILE), "").unwrap();
+        std::fs::write(root.join("elsewhere.jsonl"), "").unwrap();
+
+        let inside = resolve_in_dir(&logs, COMMAND_LOG_FILE).unwrap();
+        assert_eq!(inside, logs.canonicalize().unwrap().join(COMMAND_LOG_FILE));
+        assert!(resolve_in_dir(&logs, "../elsewhere.jsonl").unwrap_err().contains("only be replayed"));
+        let absolute = root.join("elsewhere.jsonl").display().to_string();
+        assert!(resolve_in_dir(&logs, &absolute).unwrap_err().contains("only be replayed"));
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
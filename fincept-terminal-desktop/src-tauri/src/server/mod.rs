@@ -10,8 +10,12 @@
 // This enables running Fincept Terminal as a web service while reusing
 // all 930+ existing Rust commands without modification.
 
+pub mod command_log;
+pub mod read_only;
 pub mod rpc;
+pub mod scoped_tokens;
 pub mod types;
+pub mod validation;
 
 #[cfg(feature = "web")]
 pub mod axum_server;
@@ -0,0 +1,151 @@
+// Read-Only Commands - RPC commands that can run again without side effects
+//
+// What: An explicit list of web RPC commands that only read: they fetch data,
+//       compute over their arguments or report state, and change nothing
+//       stored or running.
+// Why:  Commands that re-run other commands on the caller's behalf (a replayed
+//       command log, scheduled tasks) must not be able to delete a portfolio,
+//       overwrite settings or spawn processes behind the user's back.
+// How:  Listed by name rather than guessed from a prefix, so a new command is
+//       excluded until someone adds it here. Reads that return credentials or
+//       keys (`db_get_credentials`, `get_shared_session`, ...) are left out too.
+
+pub const READ_ONLY_COMMANDS: &[&str] = &[
+    // Market data and upstream sources
+    "get_market_quote",
+    "get_market_quotes",
+    "get_watchlist_quotes",
+    "get_fx_rate",
+    "get_period_returns",
+    "check_market_data_health",
+    "get_historical_data",
+    "get_historical_data_multi",
+    "get_historical_data_cached",
+    "get_stock_info",
+    "get_financials",
+    "db_get_cached_market_data",
+    "fetch_all_rss_news",
+    "get_rss_feed_count",
+    "get_active_sources",
+    "get_alphavantage_quote",
+    "get_alphavantage_daily",
+    "get_alphavantage_intraday",
+    "get_alphavantage_overview",
+    "get_alphavantage_comprehensive",
+    "get_alphavantage_market_movers",
+    "search_alphavantage_symbols",
+    "search_symbols",
+    "get_treasury_prices",
+    "get_treasury_auctions",
+    "get_comprehensive_treasury_data",
+    "get_treasury_summary",
+    "get_congress_bills",
+    "get_bill_info",
+    "get_bill_text",
+    "get_comprehensive_bill_data",
+    "get_bill_summary_by_congress",
+    "get_oecd_gdp_real",
+    "get_oecd_consumer_price_index",
+    "get_oecd_gdp_forecast",
+    "get_oecd_unemployment",
+    "get_oecd_economic_summary",
+    "get_oecd_country_list",
+    "get_imf_economic_indicators",
+    "get_imf_direction_of_trade",
+    "get_imf_available_indicators",
+    "get_imf_comprehensive_economic_data",
+    "get_imf_reserves_data",
+    "get_imf_trade_summary",
+    // Analytics over the arguments
+    "pmdarima_fit_auto_arima",
+    "pmdarima_forecast_auto_arima",
+    "pmdarima_forecast_arima",
+    "pmdarima_boxcox_transform",
+    "pmdarima_boxcox_auto",
+    "pmdarima_inverse_boxcox",
+    "pmdarima_calculate_acf",
+    "pmdarima_calculate_pacf",
+    "pmdarima_decompose_timeseries",
+    "pmdarima_cross_validate",
+    "series_rolling_mean",
+    "series_rolling_std",
+    "series_returns",
+    "series_log_returns",
+    "series_zscore",
+    "series_correlation",
+    "compute_indicators",
+    "estimate_tokens",
+    "sha256_hash",
+    "hash",
+    // Application state
+    "greet",
+    "system_health",
+    "server_stats",
+    "get_process_resource_usage",
+    "list_orphans",
+    "db_check_health",
+    "get_db_status",
+    "get_db_stats",
+    "check_setup_status",
+    "get_log_file_path",
+    "tail_log",
+    "get_command_timings",
+    "get_command_availability",
+    "command_log_status",
+    "get_provider_capabilities",
+    "tick_recorder_status",
+    "get_tick_history",
+    "ws_get_metrics",
+    "ws_get_all_metrics",
+    "list_scheduled_tasks",
+    "is_market_open",
+    "next_session",
+    "list_calendar_overrides",
+    "list_profiles",
+    "db_get_chat_sessions",
+    "db_get_chat_messages",
+    "db_get_chat_messages_paged",
+    "db_get_watchlists",
+    "db_get_watchlist_stocks",
+    "monitor_get_conditions",
+    "monitor_get_alerts",
+    "monitor_get_condition_groups",
+    // Paper trading reads
+    "db_list_portfolios",
+    "db_get_portfolio",
+    "db_get_portfolio_positions",
+    "get_consolidated_positions",
+    "db_get_position",
+    "db_get_position_by_symbol",
+    "db_get_position_by_symbol_and_side",
+    "get_paper_risk_limits",
+    "get_paper_symbol_spec",
+    "preview_order",
+    "db_get_order",
+    "db_get_portfolio_orders",
+    "db_get_pending_orders",
+    "db_get_trade",
+    "db_get_portfolio_trades",
+    "db_get_order_trades",
+    "get_trade_statistics",
+    "get_portfolio_summary",
+];
+
+pub fn is_read_only(command: &str) -> bool {
+    READ_ONLY_COMMANDS.contains(&command)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_listed_commands_exist_and_exclude_writes_and_secrets() {
+        for command in READ_ONLY_COMMANDS {
+            assert!(crate::command_registry::WEB_RPC_COMMANDS.contains(command), "{} is not a web RPC command", command);
+        }
+        for command in ["db_delete_portfolio", "db_save_setting", "replay_command_log", "schedule_task", "db_get_credentials", "get_shared_session"] {
+            assert!(!is_read_only(command), "{}", command);
+        }
+    }
+}
@@ -3,6 +3,10 @@
 // It allows reusing all existing command logic without modification.
 
 use super::types::{RpcRequest, RpcResponse, ServerState};
+use super::validation::{check_date_order, get_arg, normalize_date, ArgValidator};
+use crate::commands::paper_precision::{fill_fee, normalize_fill, normalize_order};
+use crate::commands::paper_risk::{enforce_risk_limits, RiskCheck};
+use crate::utils::symbol::Symbol;
 use serde_json::Value;
 use std::collections::HashMap;
 use std::sync::Arc;
@@ -17,19 +21,31 @@ pub async fn dispatch(state: Arc<ServerState>, request: RpcRequest) -> RpcRespon
     match request.cmd.as_str() {
         // BASIC COMMANDS
         "greet" => {
-            let name = args.get("name")
+            let name = get_arg(&args, &["name"])
                 .and_then(|v| v.as_str())
                 .unwrap_or("World");
             RpcResponse::ok(format!("Hello, {}! You've been greeted from Rust Web Server!", name))
         }
+        "server_stats" => RpcResponse::ok(state.stats()),
+        "system_health" => {
+            RpcResponse::ok(crate::commands::system_health::check_system_health(&state.ws_state, &state.mcp_state, false).await)
+        }
+        "get_process_resource_usage" => dispatch_process_resource_usage(&state.mcp_state, args).await,
+        "list_orphans" => dispatch_list_orphans().await,
+        "reap_orphans" => dispatch_reap_orphans(args).await,
         "get_shared_session" => dispatch_get_shared_session().await,
 
         // MARKET DATA COMMANDS
         "get_market_quote" => dispatch_market_quote(args).await,
         "get_market_quotes" => dispatch_market_quotes(args).await,
+        "get_watchlist_quotes" => dispatch_watchlist_quotes(args).await,
+        "get_fx_rate" => dispatch_fx_rate(args).await,
         "get_period_returns" => dispatch_period_returns(args).await,
         "check_market_data_health" => dispatch_market_health().await,
         "get_historical_data" => dispatch_historical_data(args).await,
+        "get_historical_data_multi" => dispatch_historical_data_multi(args).await,
+        "get_historical_data_cached" => dispatch_historical_data_cached(args).await,
+        "clear_historical_cache" => dispatch_clear_historical_cache(args),
         "get_stock_info" => dispatch_stock_info(args).await,
         "get_financials" => dispatch_financials(args).await,
         "db_get_cached_market_data" => dispatch_db_get_cached_market_data(args).await,
@@ -38,6 +54,7 @@ pub async fn dispatch(state: Arc<ServerState>, request: RpcRequest) -> RpcRespon
 
         // NEWS COMMANDS
         "fetch_all_rss_news" => dispatch_fetch_all_rss_news().await,
+        "force_refresh_news" => dispatch_force_refresh_news().await,
         "get_rss_feed_count" => dispatch_get_rss_feed_count().await,
         "get_active_sources" => dispatch_get_active_sources().await,
 
@@ -51,6 +68,7 @@ pub async fn dispatch(state: Arc<ServerState>, request: RpcRequest) -> RpcRespon
         "get_alphavantage_intraday" => dispatch_get_alphavantage_intraday(args).await,
         "get_alphavantage_overview" => dispatch_get_alphavantage_overview(args).await,
         "search_alphavantage_symbols" => dispatch_search_alphavantage_symbols(args).await,
+        "search_symbols" => dispatch_search_symbols(args).await,
         "get_alphavantage_comprehensive" => dispatch_get_alphavantage_comprehensive(args).await,
         "get_alphavantage_market_movers" => dispatch_get_alphavantage_market_movers().await,
 
@@ -59,12 +77,22 @@ pub async fn dispatch(state: Arc<ServerState>, request: RpcRequest) -> RpcRespon
         "pmdarima_forecast_auto_arima" => dispatch_pmdarima_forecast_auto_arima(args).await,
         "pmdarima_forecast_arima" => dispatch_pmdarima_forecast_arima(args).await,
         "pmdarima_boxcox_transform" => dispatch_pmdarima_boxcox_transform(args).await,
+        "pmdarima_boxcox_auto" => dispatch_pmdarima_boxcox_auto(args),
         "pmdarima_inverse_boxcox" => dispatch_pmdarima_inverse_boxcox(args).await,
         "pmdarima_calculate_acf" => dispatch_pmdarima_calculate_acf(args).await,
         "pmdarima_calculate_pacf" => dispatch_pmdarima_calculate_pacf(args).await,
         "pmdarima_decompose_timeseries" => dispatch_pmdarima_decompose_timeseries(args).await,
         "pmdarima_cross_validate" => dispatch_pmdarima_cross_validate(args).await,
 
+        // NATIVE SERIES ANALYTICS
+        "series_rolling_mean" => dispatch_series_rolling(args, crate::commands::series_analytics::rolling_mean),
+        "series_rolling_std" => dispatch_series_rolling(args, crate::commands::series_analytics::rolling_std),
+        "series_returns" => dispatch_series(args, crate::commands::series_analytics::simple_returns),
+        "series_log_returns" => dispatch_series(args, crate::commands::series_analytics::log_returns),
+        "series_zscore" => dispatch_series(args, crate::commands::series_analytics::zscore),
+        "series_correlation" => dispatch_series_correlation(args),
+        "compute_indicators" => dispatch_compute_indicators(args),
+
         // GOVERNMENT & MACRO COMMANDS
         "execute_government_us_command" => dispatch_execute_government_us_command(args).await,
         "get_treasury_prices" => dispatch_get_treasury_prices(args).await,
@@ -95,9 +123,34 @@ pub async fn dispatch(state: Arc<ServerState>, request: RpcRequest) -> RpcRespon
 
         // DATABASE HEALTH & SETTINGS COMMANDS
         "db_check_health" => dispatch_db_health().await,
+        "get_db_status" => dispatch_get_db_status(args),
+        "get_db_stats" => RpcResponse::ok(crate::database::pool::stats()),
+        "import_database" => dispatch_import_database(args),
+        "export_config" => dispatch_export_config(args),
+        "import_config" => dispatch_import_config(args),
+        "export_settings_snapshot" => dispatch_export_settings_snapshot(),
+        "diff_settings" => dispatch_diff_settings(args),
+
+        // SCHEDULED TASK COMMANDS
+        "schedule_task" => dispatch_schedule_task(args),
+        "list_scheduled_tasks" => dispatch_list_scheduled_tasks(),
+        "set_scheduled_task_enabled" => dispatch_set_scheduled_task_enabled(args),
+        "delete_scheduled_task" => dispatch_delete_scheduled_task(args),
+
+        // TRADING CALENDAR COMMANDS
+        "is_market_open" => dispatch_is_market_open(args),
+        "next_session" => dispatch_next_session(args),
+        "set_calendar_override" => dispatch_set_calendar_override(args),
+        "delete_calendar_override" => dispatch_delete_calendar_override(args),
+        "list_calendar_overrides" => dispatch_list_calendar_overrides(args),
         "db_get_all_settings" => dispatch_db_get_all_settings().await,
         "db_get_setting" => dispatch_db_get_setting(args).await,
         "db_save_setting" => dispatch_db_save_setting(args).await,
+        "db_save_settings" => dispatch_db_save_settings(args).await,
+        "list_profiles" => dispatch_list_profiles(),
+        "create_profile" => dispatch_create_profile(args),
+        "delete_profile" => dispatch_delete_profile(args),
+        "switch_profile" => dispatch_switch_profile(args),
 
         // CREDENTIALS COMMANDS
         "db_get_credentials" => dispatch_db_get_credentials().await,
@@ -108,6 +161,9 @@ pub async fn dispatch(state: Arc<ServerState>, request: RpcRequest) -> RpcRespon
         // LLM CONFIG COMMANDS
         "db_get_llm_configs" => dispatch_db_get_llm_configs().await,
         "db_save_llm_config" => dispatch_db_save_llm_config(args).await,
+        "test_llm_config" => dispatch_test_llm_config(args).await,
+        "llm_chat_stream" => dispatch_llm_chat_stream(args).await,
+        "estimate_tokens" => dispatch_estimate_tokens(args),
         "db_get_llm_global_settings" => dispatch_db_get_llm_global_settings().await,
         "db_save_llm_global_settings" => dispatch_db_save_llm_global_settings(args).await,
 
@@ -116,12 +172,15 @@ pub async fn dispatch(state: Arc<ServerState>, request: RpcRequest) -> RpcRespon
         "db_get_chat_sessions" => dispatch_db_get_chat_sessions(args).await,
         "db_add_chat_message" => dispatch_db_add_chat_message(args).await,
         "db_get_chat_messages" => dispatch_db_get_chat_messages(args).await,
+        "db_get_chat_messages_paged" => dispatch_db_get_chat_messages_paged(args).await,
         "db_delete_chat_session" => dispatch_db_delete_chat_session(args).await,
 
         // DATA SOURCE COMMANDS
         "db_get_all_data_sources" => dispatch_db_get_all_data_sources().await,
         "db_save_data_source" => dispatch_db_save_data_source(args).await,
         "db_delete_data_source" => dispatch_db_delete_data_source(args).await,
+        "test_data_source" => dispatch_test_data_source(args).await,
+        "probe_all_data_sources" => dispatch_probe_all_data_sources(args).await,
 
         // WEBSOCKET PROVIDER COMMANDS
         "db_get_ws_provider_configs" => dispatch_db_get_ws_provider_configs().await,
@@ -135,24 +194,39 @@ pub async fn dispatch(state: Arc<ServerState>, request: RpcRequest) -> RpcRespon
         "db_get_portfolio" => dispatch_db_get_portfolio(args).await,
         "db_create_portfolio" => dispatch_db_create_portfolio(args).await,
         "db_delete_portfolio" => dispatch_db_delete_portfolio(args).await,
+        "purge_portfolio" => dispatch_purge_portfolio(args).await,
         "db_update_portfolio_balance" => dispatch_db_update_portfolio_balance(args).await,
+        "db_adjust_portfolio_balance" => dispatch_db_adjust_portfolio_balance(args).await,
+        "get_portfolio_summary" => dispatch_get_portfolio_summary(args).await,
 
         // PAPER TRADING - POSITIONS
         "db_create_position" => dispatch_db_create_position(args).await,
         "db_get_portfolio_positions" => dispatch_db_get_portfolio_positions(args).await,
+        "get_consolidated_positions" => dispatch_get_consolidated_positions(args),
         "db_get_position" => dispatch_db_get_position(args).await,
         "db_get_position_by_symbol" => dispatch_db_get_position_by_symbol(args).await,
         "db_get_position_by_symbol_and_side" => dispatch_db_get_position_by_symbol_and_side(args).await,
         "db_update_position" => dispatch_db_update_position(args).await,
+        "db_reduce_position" => dispatch_db_reduce_position(args).await,
+        "recompute_unrealized_pnl" => dispatch_recompute_unrealized_pnl(args).await,
+        "ingest_marks" => dispatch_ingest_marks(args).await,
         "db_delete_position" => dispatch_db_delete_position(args).await,
 
         // PAPER TRADING - ORDERS
         "db_create_order" => dispatch_db_create_order(args).await,
+        "create_bracket_order" => dispatch_create_bracket_order(args).await,
+        "open_market_position" => dispatch_open_market_position(args).await,
+        "get_paper_risk_limits" => dispatch_get_paper_risk_limits(args),
+        "set_paper_risk_limits" => dispatch_set_paper_risk_limits(args),
+        "get_paper_symbol_spec" => dispatch_get_paper_symbol_spec(args),
+        "set_paper_symbol_spec" => dispatch_set_paper_symbol_spec(args),
+        "preview_order" => dispatch_preview_order(&state.ws_state, args).await,
         "db_get_order" => dispatch_db_get_order(args).await,
         "db_get_portfolio_orders" => dispatch_db_get_portfolio_orders(args).await,
         "db_get_pending_orders" => dispatch_db_get_pending_orders(args).await,
         "db_update_order" => dispatch_db_update_order(args).await,
         "db_delete_order" => dispatch_db_delete_order(args).await,
+        "db_cancel_all_orders" => dispatch_db_cancel_all_orders(args).await,
 
         // PAPER TRADING - TRADES
         "db_create_trade" => dispatch_db_create_trade(args).await,
@@ -160,6 +234,7 @@ pub async fn dispatch(state: Arc<ServerState>, request: RpcRequest) -> RpcRespon
         "db_get_portfolio_trades" => dispatch_db_get_portfolio_trades(args).await,
         "db_get_order_trades" => dispatch_db_get_order_trades(args).await,
         "db_delete_trade" => dispatch_db_delete_trade(args).await,
+        "get_trade_statistics" => dispatch_get_trade_statistics(args).await,
 
         // WATCHLIST COMMANDS
         "db_get_watchlists" => dispatch_db_get_watchlists().await,
@@ -172,8 +247,21 @@ pub async fn dispatch(state: Arc<ServerState>, request: RpcRequest) -> RpcRespon
         // SETUP & UTILITY COMMANDS
         "check_setup_status" => dispatch_check_setup_status().await,
         "cleanup_running_workflows" => dispatch_cleanup_running_workflows().await,
+        "get_log_file_path" => dispatch_get_log_file_path().await,
+        "tail_log" => dispatch_tail_log(args).await,
+        "get_command_timings" => dispatch_get_command_timings(args).await,
+        "get_command_availability" => RpcResponse::ok(crate::command_registry::command_availability()),
+        "command_log_set_enabled" => dispatch_command_log_set_enabled(args),
+        "create_scoped_token" => dispatch_create_scoped_token(&state, args),
+        "command_log_status" => RpcResponse::ok(super::command_log::status()),
+        "replay_command_log" => dispatch_replay_command_log(state.clone(), args).await,
+        "cancel_request" => dispatch_cancel_request(args),
+        "drain_worker_pool" => {
+            let cancel_running = get_arg(&args, &["cancelRunning"]).and_then(|v| v.as_bool()).unwrap_or(false);
+            RpcResponse::ok(crate::worker_pool::drain_worker_pool(cancel_running))
+        }
         "sha256_hash" => {
-            let input = args.get("input")
+            let input = get_arg(&args, &["input"])
                 .and_then(|v| v.as_str())
                 .unwrap_or("");
             use sha2::{Sha256, Digest};
@@ -182,23 +270,58 @@ pub async fn dispatch(state: Arc<ServerState>, request: RpcRequest) -> RpcRespon
             let result = hasher.finalize();
             RpcResponse::ok(format!("{:x}", result))
         }
+        "hmac_sha256" => {
+            let key = get_arg(&args, &["key"]).and_then(|v| v.as_str()).unwrap_or("");
+            let message = get_arg(&args, &["message"]).and_then(|v| v.as_str()).unwrap_or("");
+            match crate::utils::hashing::hmac_sha256_hex(key.as_bytes(), message.as_bytes()) {
+                Ok(signature) => RpcResponse::ok(signature),
+                Err(e) => RpcResponse::err(e),
+            }
+        }
+        "hash" => {
+            let input = get_arg(&args, &["input"]).and_then(|v| v.as_str()).unwrap_or("");
+            match get_arg(&args, &["algorithm"])
+                .and_then(|v| v.as_str())
+                .unwrap_or("sha256")
+                .parse::<crate::utils::hashing::HashAlgorithm>()
+            {
+                Ok(algorithm) => RpcResponse::ok(crate::utils::hashing::hash_hex(algorithm, input.as_bytes())),
+                Err(e) => RpcResponse::err(e),
+            }
+        }
 
         // WEBSOCKET COMMANDS
         "ws_set_config" => dispatch_ws_set_config(&state.ws_state, args).await,
         "ws_connect" => dispatch_ws_connect(&state.ws_state, args).await,
         "ws_disconnect" => dispatch_ws_disconnect(&state.ws_state, args).await,
         "ws_subscribe" => dispatch_ws_subscribe(&state.ws_state, args).await,
+        "ws_subscribe_many" => dispatch_ws_subscribe_many(&state.ws_state, args).await,
         "ws_unsubscribe" => dispatch_ws_unsubscribe(&state.ws_state, args).await,
         "ws_get_metrics" => dispatch_ws_get_metrics(&state.ws_state, args).await,
-        "ws_get_all_metrics" => dispatch_ws_get_all_metrics(&state.ws_state).await,
+        "get_provider_capabilities" => dispatch_get_provider_capabilities(&state.ws_state, args).await,
+        "ws_get_all_metrics" => dispatch_ws_get_all_metrics(&state.ws_state, args).await,
         "ws_reconnect" => dispatch_ws_reconnect(&state.ws_state, args).await,
+        "ws_set_raw_capture" => dispatch_ws_set_raw_capture(&state.ws_state, args).await,
+        "ws_get_last_raw" => dispatch_ws_get_last_raw(&state.ws_state, args).await,
+        "set_tick_recording" => dispatch_set_tick_recording(&state.ws_state, args).await,
+        "tick_recorder_status" => RpcResponse::ok(state.ws_state.router.read().await.tick_recorder().status()),
+        "get_tick_history" => dispatch_get_tick_history(args).await,
+        "replay_ticks" => dispatch_replay_ticks(&state.ws_state, args).await,
+        "stop_tick_replay" => RpcResponse::ok(crate::websocket::tick_recorder::stop_replay()),
 
         // MONITORING COMMANDS
         "monitor_add_condition" => dispatch_monitor_add_condition(&state.ws_state, args).await,
         "monitor_get_conditions" => dispatch_monitor_get_conditions().await,
+        "test_monitor_condition" => dispatch_test_monitor_condition(&state.ws_state, args).await,
         "monitor_delete_condition" => dispatch_monitor_delete_condition(&state.ws_state, args).await,
         "monitor_get_alerts" => dispatch_monitor_get_alerts(args).await,
+        "monitor_acknowledge_alert" => dispatch_monitor_acknowledge_alert(args),
+        "monitor_acknowledge_all_alerts" => dispatch_monitor_acknowledge_all_alerts(),
+        "monitor_add_condition_group" => dispatch_monitor_add_condition_group(&state.ws_state, args).await,
+        "monitor_get_condition_groups" => dispatch_monitor_get_condition_groups().await,
+        "monitor_delete_condition_group" => dispatch_monitor_delete_condition_group(&state.ws_state, args).await,
         "monitor_load_conditions" => dispatch_monitor_load_conditions(&state.ws_state).await,
+        "monitor_set_notifications_enabled" => dispatch_monitor_set_notifications_enabled(&state.ws_state, args).await,
 
         // MCP COMMANDS
         "spawn_mcp_server" => dispatch_spawn_mcp_server(&state.mcp_state, args).await,
@@ -225,12 +348,14 @@ pub async fn dispatch(state: Arc<ServerState>, request: RpcRequest) -> RpcRespon
                     See / for API documentation and available commands.",
                     request.cmd
                 ))
+                .with_kind(crate::server::ErrorKind::NotImplemented)
             } else {
                 RpcResponse::err(format!(
                     "Command '{}' is not recognized. \
                     See / for API documentation and available commands.",
                     request.cmd
                 ))
+                .with_kind(crate::server::ErrorKind::NotFound)
             }
         }
     }
@@ -242,28 +367,34 @@ async fn dispatch_spawn_mcp_server(
     mcp_state: &Arc<crate::MCPState>,
     args: Value,
 ) -> RpcResponse {
-    let server_id = match args.get("serverId").or(args.get("server_id")).and_then(|v| v.as_str()) {
+    let server_id = match get_arg(&args, &["serverId"]).and_then(|v| v.as_str()) {
         Some(value) => value.to_string(),
         None => return RpcResponse::err("Missing 'serverId' parameter"),
     };
-    let command = match args.get("command").and_then(|v| v.as_str()) {
+    let command = match get_arg(&args, &["command"]).and_then(|v| v.as_str()) {
         Some(value) => value.to_string(),
         None => return RpcResponse::err("Missing 'command' parameter"),
     };
-    let command_args: Vec<String> = match args.get("args") {
+    let command_args: Vec<String> = match get_arg(&args, &["args"]) {
         Some(value) => match serde_json::from_value(value.clone()) {
             Ok(v) => v,
             Err(e) => return RpcResponse::err(format!("Invalid 'args' parameter: {}", e)),
         },
         None => Vec::new(),
     };
-    let env: HashMap<String, String> = match args.get("env") {
+    let env: HashMap<String, String> = match get_arg(&args, &["env"]) {
         Some(value) => match serde_json::from_value(value.clone()) {
             Ok(v) => v,
             Err(e) => return RpcResponse::err(format!("Invalid 'env' parameter: {}", e)),
         },
         None => HashMap::new(),
     };
+    let framing = match crate::utils::mcp_framing::McpFraming::parse(
+        get_arg(&args, &["framing"]).and_then(|v| v.as_str()),
+    ) {
+        Ok(framing) => framing,
+        Err(e) => return RpcResponse::err(e),
+    };
 
     match crate::spawn_mcp_server_internal(
         None,
@@ -272,6 +403,7 @@ async fn dispatch_spawn_mcp_server(
         command,
         command_args,
         env,
+        framing,
     ) {
         Ok(result) => RpcResponse::ok(result),
         Err(e) => RpcResponse::err(e),
@@ -282,11 +414,11 @@ async fn dispatch_send_mcp_request(
     mcp_state: &Arc<crate::MCPState>,
     args: Value,
 ) -> RpcResponse {
-    let server_id = match args.get("serverId").or(args.get("server_id")).and_then(|v| v.as_str()) {
+    let server_id = match get_arg(&args, &["serverId"]).and_then(|v| v.as_str()) {
         Some(value) => value.to_string(),
         None => return RpcResponse::err("Missing 'serverId' parameter"),
     };
-    let request = match args.get("request").and_then(|v| v.as_str()) {
+    let request = match get_arg(&args, &["request"]).and_then(|v| v.as_str()) {
         Some(value) => value.to_string(),
         None => return RpcResponse::err("Missing 'request' parameter"),
     };
@@ -301,11 +433,11 @@ async fn dispatch_send_mcp_notification(
     mcp_state: &Arc<crate::MCPState>,
     args: Value,
 ) -> RpcResponse {
-    let server_id = match args.get("serverId").or(args.get("server_id")).and_then(|v| v.as_str()) {
+    let server_id = match get_arg(&args, &["serverId"]).and_then(|v| v.as_str()) {
         Some(value) => value.to_string(),
         None => return RpcResponse::err("Missing 'serverId' parameter"),
     };
-    let notification = match args.get("notification").and_then(|v| v.as_str()) {
+    let notification = match get_arg(&args, &["notification"]).and_then(|v| v.as_str()) {
         Some(value) => value.to_string(),
         None => return RpcResponse::err("Missing 'notification' parameter"),
     };
@@ -320,7 +452,7 @@ async fn dispatch_ping_mcp_server(
     mcp_state: &Arc<crate::MCPState>,
     args: Value,
 ) -> RpcResponse {
-    let server_id = match args.get("serverId").or(args.get("server_id")).and_then(|v| v.as_str()) {
+    let server_id = match get_arg(&args, &["serverId"]).and_then(|v| v.as_str()) {
         Some(value) => value.to_string(),
         None => return RpcResponse::err("Missing 'serverId' parameter"),
     };
@@ -335,7 +467,7 @@ async fn dispatch_kill_mcp_server(
     mcp_state: &Arc<crate::MCPState>,
     args: Value,
 ) -> RpcResponse {
-    let server_id = match args.get("serverId").or(args.get("server_id")).and_then(|v| v.as_str()) {
+    let server_id = match get_arg(&args, &["serverId"]).and_then(|v| v.as_str()) {
         Some(value) => value.to_string(),
         None => return RpcResponse::err("Missing 'serverId' parameter"),
     };
@@ -471,7 +603,7 @@ async fn dispatch_execute_agent_team(args: Value) -> RpcResponse {
 // MARKET DATA DISPATCH FUNCTIONS
 
 async fn dispatch_market_quote(args: Value) -> RpcResponse {
-    let symbol = match args.get("symbol").and_then(|v| v.as_str()) {
+    let symbol = match get_arg(&args, &["symbol"]).and_then(|v| v.as_str()) {
         Some(s) => s.to_string(),
         None => return RpcResponse::err("Missing 'symbol' parameter"),
     };
@@ -482,8 +614,24 @@ async fn dispatch_market_quote(args: Value) -> RpcResponse {
     }
 }
 
+async fn dispatch_fx_rate(args: Value) -> RpcResponse {
+    let base = match get_arg(&args, &["base"]).and_then(|v| v.as_str()) {
+        Some(s) => s.to_string(),
+        None => return RpcResponse::err("Missing 'base' parameter"),
+    };
+    let quote = match get_arg(&args, &["quote"]).and_then(|v| v.as_str()) {
+        Some(s) => s.to_string(),
+        None => return RpcResponse::err("Missing 'quote' parameter"),
+    };
+
+    match crate::data_sources::yfinance::YFinanceProviderWeb::get_fx_rate(&base, &quote).await {
+        Ok(rate) => RpcResponse::ok(rate),
+        Err(e) => RpcResponse::err(e),
+    }
+}
+
 async fn dispatch_market_quotes(args: Value) -> RpcResponse {
-    let symbols: Vec<String> = match args.get("symbols") {
+    let symbols: Vec<String> = match get_arg(&args, &["symbols"]) {
         Some(v) => match serde_json::from_value(v.clone()) {
             Ok(parsed) => parsed,
             Err(e) => return RpcResponse::err(format!("Invalid 'symbols' parameter: {}", e)),
@@ -497,13 +645,35 @@ async fn dispatch_market_quotes(args: Value) -> RpcResponse {
     }
 }
 
+async fn dispatch_watchlist_quotes(args: Value) -> RpcResponse {
+    let watchlist_id = match get_arg(&args, &["watchlistId"]).and_then(|v| v.as_str()) {
+        Some(id) => id.to_string(),
+        None => return RpcResponse::err("Missing 'watchlistId' parameter"),
+    };
+    let max_age = get_arg(&args, &["maxCacheAgeMinutes"]).and_then(|v| v.as_i64()).unwrap_or(10);
+
+    let guard = crate::utils::cancellation::register(request_id_arg(&args)).with_timeout("get_watchlist_quotes");
+    match guard
+        .run(crate::commands::market_data::watchlist_quotes(&watchlist_id, max_age, |symbols| async move {
+            crate::data_sources::yfinance::YFinanceProviderWeb::get_quotes(&symbols).await
+        }))
+        .await
+        .and_then(|r| r)
+    {
+        Ok(rows) => RpcResponse::ok(rows),
+        Err(e) => RpcResponse::err(e),
+    }
+}
+
 async fn dispatch_period_returns(args: Value) -> RpcResponse {
-    let symbol = match args.get("symbol").and_then(|v| v.as_str()) {
+    let symbol = match get_arg(&args, &["symbol"]).and_then(|v| v.as_str()) {
         Some(s) => s.to_string(),
         None => return RpcResponse::err("Missing 'symbol' parameter"),
     };
 
-    match crate::data_sources::yfinance::YFinanceProviderWeb::get_period_returns(&symbol).await {
+    let adjusted = get_arg(&args, &["adjusted"]).and_then(|v| v.as_bool()).unwrap_or(true);
+
+    match crate::data_sources::yfinance::YFinanceProviderWeb::get_period_returns(&symbol, adjusted).await {
         Ok(returns) => RpcResponse::ok(returns),
         Err(e) => RpcResponse::err(e),
     }
@@ -528,9 +698,7 @@ async fn dispatch_db_get_cached_market_data(args: Value) -> RpcResponse {
     // what: accept both snake_case and camelCase cache age parameters
     // why: the frontend previously sent maxAgeMinutes and hit missing-parameter errors
     // how: read the snake_case key first, then fall back to the camelCase variant
-    let max_age_minutes = match args
-        .get("max_age_minutes")
-        .or_else(|| args.get("maxAgeMinutes"))
+    let max_age_minutes = match get_arg(&args, &["max_age_minutes"])
         .and_then(|v| v.as_i64()) {
         Some(value) => value,
         None => return RpcResponse::err("Missing 'max_age_minutes' parameter"),
@@ -554,9 +722,7 @@ async fn dispatch_db_save_market_data_cache(args: Value) -> RpcResponse {
         Ok(value) => value,
         Err(e) => return RpcResponse::err(e),
     };
-    let quote_data = match args
-        .get("quote_data")
-        .or_else(|| args.get("quoteData"))
+    let quote_data = match get_arg(&args, &["quote_data"])
         .and_then(|v| v.as_str())
         .map(str::to_string) {
         Some(value) => value,
@@ -580,27 +746,123 @@ async fn dispatch_db_clear_market_data_cache() -> RpcResponse {
 }
 
 async fn dispatch_historical_data(args: Value) -> RpcResponse {
-    let symbol = match args.get("symbol").and_then(|v| v.as_str()) {
+    let symbol = match get_arg(&args, &["symbol"]).and_then(|v| v.as_str()) {
         Some(s) => s.to_string(),
         None => return RpcResponse::err("Missing 'symbol' parameter"),
     };
-    let start_date = args.get("startDate").or(args.get("start_date"))
+    let start_date = get_arg(&args, &["startDate"])
+        .and_then(|v| v.as_str())
+        .unwrap_or("")
+        .to_string();
+    let end_date = get_arg(&args, &["endDate"])
+        .and_then(|v| v.as_str())
+        .unwrap_or("")
+        .to_string();
+
+    let guard = crate::utils::cancellation::register(request_id_arg(&args)).with_timeout("get_historical_data");
+    match guard
+        .run(crate::data_sources::yfinance::YFinanceProviderWeb::get_historical(&symbol, &start_date, &end_date))
+        .await
+        .and_then(|r| r)
+    {
+        Ok(data) => RpcResponse::ok(data),
+        Err(e) => RpcResponse::err(e),
+    }
+}
+
+fn request_id_arg(args: &Value) -> Option<String> {
+    get_arg(args, &["requestId"])
+        .and_then(|v| v.as_str())
+        .map(String::from)
+}
+
+fn dispatch_cancel_request(args: Value) -> RpcResponse {
+    match request_id_arg(&args) {
+        Some(id) => RpcResponse::ok(serde_json::json!({"cancelled": crate::utils::cancellation::cancel(&id)})),
+        None => RpcResponse::err("Missing 'requestId' parameter"),
+    }
+}
+
+async fn dispatch_historical_data_multi(args: Value) -> RpcResponse {
+    let symbols: Vec<String> = match get_arg(&args, &["symbols"]).and_then(|v| v.as_array()) {
+        Some(arr) => arr.iter().filter_map(|v| v.as_str().map(String::from)).collect(),
+        None => return RpcResponse::err("Missing 'symbols' parameter"),
+    };
+    let start_date = get_arg(&args, &["startDate"])
         .and_then(|v| v.as_str())
         .unwrap_or("")
         .to_string();
-    let end_date = args.get("endDate").or(args.get("end_date"))
+    let end_date = get_arg(&args, &["endDate"])
         .and_then(|v| v.as_str())
         .unwrap_or("")
         .to_string();
+    let interval = match crate::data_sources::yfinance::normalize_interval(
+        get_arg(&args, &["interval"]).and_then(|v| v.as_str()),
+    ) {
+        Ok(i) => i,
+        Err(e) => return RpcResponse::err(e),
+    };
+    let align = get_arg(&args, &["align"]).and_then(|v| v.as_bool()).unwrap_or(false);
 
-    match crate::data_sources::yfinance::YFinanceProviderWeb::get_historical(&symbol, &start_date, &end_date).await {
+    let guard = crate::utils::cancellation::register(request_id_arg(&args)).with_timeout("get_historical_data_multi");
+    match guard
+        .run(crate::data_sources::yfinance::YFinanceProviderWeb::get_historical_multi(
+            symbols, &start_date, &end_date, &interval, align,
+        ))
+        .await
+    {
         Ok(data) => RpcResponse::ok(data),
         Err(e) => RpcResponse::err(e),
     }
 }
 
+async fn dispatch_historical_data_cached(args: Value) -> RpcResponse {
+    let symbol = match get_arg(&args, &["symbol"]).and_then(|v| v.as_str()) {
+        Some(s) => s.to_string(),
+        None => return RpcResponse::err("Missing 'symbol' parameter"),
+    };
+    let (from, to) = match (
+        get_arg(&args, &["from"]).and_then(|v| v.as_str()),
+        get_arg(&args, &["to"]).and_then(|v| v.as_str()),
+    ) {
+        (Some(from), Some(to)) => (from.to_string(), to.to_string()),
+        _ => return RpcResponse::err("Missing 'from' or 'to' parameter"),
+    };
+    let interval = match crate::data_sources::yfinance::normalize_interval(
+        get_arg(&args, &["interval"]).and_then(|v| v.as_str()),
+    ) {
+        Ok(i) => i,
+        Err(e) => return RpcResponse::err(e),
+    };
+
+    let fetch = |symbol: String, start: String, end: String| {
+        let interval = &interval;
+        async move {
+            crate::data_sources::yfinance::YFinanceProviderWeb::get_historical_interval(&symbol, &start, &end, interval)
+                .await
+        }
+    };
+    let guard = crate::utils::cancellation::register(request_id_arg(&args)).with_timeout("get_historical_data_cached");
+    match guard
+        .run(crate::database::historical_cache::get_cached(&symbol, &from, &to, &interval, fetch))
+        .await
+    {
+        Ok(Ok(data)) => RpcResponse::ok(data),
+        Ok(Err(e)) => RpcResponse::err(e.to_string()),
+        Err(e) => RpcResponse::err(e),
+    }
+}
+
+fn dispatch_clear_historical_cache(args: Value) -> RpcResponse {
+    let symbol = get_arg(&args, &["symbol"]).and_then(|v| v.as_str());
+    match crate::database::historical_cache::clear(symbol) {
+        Ok(deleted) => RpcResponse::ok(deleted),
+        Err(e) => RpcResponse::err(e.to_string()),
+    }
+}
+
 async fn dispatch_stock_info(args: Value) -> RpcResponse {
-    let symbol = match args.get("symbol").and_then(|v| v.as_str()) {
+    let symbol = match get_arg(&args, &["symbol"]).and_then(|v| v.as_str()) {
         Some(s) => s.to_string(),
         None => return RpcResponse::err("Missing 'symbol' parameter"),
     };
@@ -612,7 +874,7 @@ async fn dispatch_stock_info(args: Value) -> RpcResponse {
 }
 
 async fn dispatch_financials(args: Value) -> RpcResponse {
-    let symbol = match args.get("symbol").and_then(|v| v.as_str()) {
+    let symbol = match get_arg(&args, &["symbol"]).and_then(|v| v.as_str()) {
         Some(s) => s.to_string(),
         None => return RpcResponse::err("Missing 'symbol' parameter"),
     };
@@ -632,6 +894,13 @@ async fn dispatch_fetch_all_rss_news() -> RpcResponse {
     }
 }
 
+async fn dispatch_force_refresh_news() -> RpcResponse {
+    match crate::commands::news::force_refresh_news().await {
+        Ok(articles) => RpcResponse::ok(articles),
+        Err(e) => RpcResponse::err(e),
+    }
+}
+
 async fn dispatch_get_rss_feed_count() -> RpcResponse {
     RpcResponse::ok(crate::commands::news::get_rss_feed_count())
 }
@@ -644,7 +913,11 @@ async fn dispatch_get_active_sources() -> RpcResponse {
 
 fn execute_python_script_runtime(script_name: &str, args: Vec<String>) -> Result<String, String> {
     let script_path = crate::utils::python::get_script_path_for_runtime(None, script_name)?;
-    
+    validate_python_args(&args)?;
+    crate::python_runtime::execute_python_script(&script_path, args)
+}
+
+fn validate_python_args(args: &[String]) -> Result<(), String> {
     // SECURITY: Basic input validation for command-line arguments
     // Validate argument count to prevent abuse
     if args.len() > 100 {
@@ -671,8 +944,8 @@ fn execute_python_script_runtime(script_name: &str, args: Vec<String>) -> Result
             ));
         }
     }
-    
-    crate::python_runtime::execute_python_script(&script_path, args)
+
+    Ok(())
 }
 
 fn execute_python_command_runtime(
@@ -691,29 +964,49 @@ fn execute_python_command_runtime(
     
     let mut cmd_args = vec![command.to_string()];
     cmd_args.extend(args);
-    execute_python_script_runtime(script_name, cmd_args)
+
+    // Identical concurrent data requests share one Python run
+    let script_path = crate::utils::python::get_script_path_for_runtime(None, script_name)?;
+    validate_python_args(&cmd_args)?;
+    crate::python_runtime::execute_python_script_coalesced(&script_path, cmd_args)
 }
 
 fn get_required_string(args: &Value, key: &str) -> Result<String, String> {
-    args.get(key)
+    get_arg(args, &[key])
         .and_then(|v| v.as_str())
         .map(|s| s.to_string())
         .ok_or_else(|| format!("Missing '{}' parameter", key))
 }
 
 fn get_optional_string(args: &Value, key: &str) -> Option<String> {
-    args.get(key).and_then(|v| v.as_str()).map(|s| s.to_string())
+    get_arg(args, &[key]).and_then(|v| v.as_str()).map(|s| s.to_string())
+}
+
+/// Optional date argument normalized to `YYYY-MM-DD`; empty strings pass through
+/// so positional Python arguments keep their places
+fn get_optional_date(args: &Value, key: &str) -> Result<Option<String>, String> {
+    match get_optional_string(args, key) {
+        Some(value) if !value.trim().is_empty() => normalize_date(key, &value).map(Some),
+        other => Ok(other),
+    }
+}
+
+/// Optional `start_date` and `end_date`, normalized and checked to be in order
+fn get_optional_date_range(args: &Value) -> Result<(Option<String>, Option<String>), String> {
+    let start = get_optional_date(args, "start_date")?;
+    let end = get_optional_date(args, "end_date")?;
+    check_date_order(start.as_deref(), end.as_deref())?;
+    Ok((start, end))
 }
 
 fn get_optional_i32(args: &Value, key: &str) -> Option<i32> {
-    args.get(key)
+    get_arg(args, &[key])
         .and_then(|v| v.as_i64())
         .and_then(|v| i32::try_from(v).ok())
 }
 
 fn get_required_i32(args: &Value, key: &str) -> Result<i32, String> {
-    let value = args
-        .get(key)
+    let value = get_arg(args, &[key])
         .ok_or_else(|| format!("Missing '{}' parameter", key))?;
 
     let int_value = value
@@ -725,8 +1018,7 @@ fn get_required_i32(args: &Value, key: &str) -> Result<i32, String> {
 }
 
 fn get_required_i64(args: &Value, key: &str) -> Result<i64, String> {
-    let value = args
-        .get(key)
+    let value = get_arg(args, &[key])
         .ok_or_else(|| format!("Missing '{}' parameter", key))?;
 
     value
@@ -735,11 +1027,11 @@ fn get_required_i64(args: &Value, key: &str) -> Result<i64, String> {
 }
 
 fn get_optional_bool(args: &Value, key: &str) -> Option<bool> {
-    args.get(key).and_then(|v| v.as_bool())
+    get_arg(args, &[key]).and_then(|v| v.as_bool())
 }
 
 fn get_string_list(args: &Value, key: &str) -> Result<Vec<String>, String> {
-    match args.get(key) {
+    match get_arg(args, &[key]) {
         Some(value) => serde_json::from_value(value.clone())
             .map_err(|e| format!("Invalid '{}' parameter: {}", key, e)),
         None => Ok(Vec::new()),
@@ -748,15 +1040,29 @@ fn get_string_list(args: &Value, key: &str) -> Result<Vec<String>, String> {
 
 /// Helper function to reduce code duplication in Python command dispatch functions.
 /// Extracts optional string parameters from args and calls the Python command.
+/// Parameters named `*_date` are validated and normalized to `YYYY-MM-DD`.
 fn dispatch_python_with_optional_params(
     script_name: &str,
     command: &str,
     args: &Value,
     param_names: &[&str],
 ) -> RpcResponse {
+    if param_names.contains(&"start_date") && param_names.contains(&"end_date") {
+        if let Err(e) = get_optional_date_range(args) {
+            return RpcResponse::err(e);
+        }
+    }
     let mut command_args = Vec::new();
     for param_name in param_names {
-        if let Some(value) = get_optional_string(args, param_name) {
+        let value = if param_name.ends_with("_date") {
+            match get_optional_date(args, param_name) {
+                Ok(value) => value,
+                Err(e) => return RpcResponse::err(e),
+            }
+        } else {
+            get_optional_string(args, param_name)
+        };
+        if let Some(value) = value {
             command_args.push(value);
         }
     }
@@ -785,7 +1091,7 @@ async fn dispatch_execute_polygon_command(args: Value) -> RpcResponse {
     // 2. Race conditions where one request's API key could be used for another request
     // 3. Potential exposure to other code in the process
     // Configure POLYGON_API_KEY before starting the server instead.
-    if args.get("apiKey").is_some() || args.get("api_key").is_some() {
+    if get_arg(&args, &["apiKey"]).is_some() {
         return RpcResponse::err(
             "API key configuration via request parameters is not supported for security reasons. \
              Please set POLYGON_API_KEY environment variable before starting the server."
@@ -914,6 +1220,19 @@ async fn dispatch_search_alphavantage_symbols(args: Value) -> RpcResponse {
     }
 }
 
+async fn dispatch_search_symbols(args: Value) -> RpcResponse {
+    let query = match get_required_string(&args, "query") {
+        Ok(value) => value,
+        Err(e) => return RpcResponse::err(e),
+    };
+    let limit = get_arg(&args, &["limit"]).and_then(|v| v.as_u64()).map(|n| n as usize);
+
+    match crate::commands::symbol_search::search(&query, limit).await {
+        Ok(matches) => RpcResponse::ok(matches),
+        Err(e) => RpcResponse::err(e),
+    }
+}
+
 async fn dispatch_get_alphavantage_comprehensive(args: Value) -> RpcResponse {
     let symbol = match get_required_string(&args, "symbol") {
         Ok(value) => value,
@@ -936,7 +1255,7 @@ async fn dispatch_get_alphavantage_market_movers() -> RpcResponse {
 // PMDARIMA DISPATCH FUNCTIONS
 
 async fn dispatch_pmdarima_fit_auto_arima(args: Value) -> RpcResponse {
-    let data: Vec<f64> = match args.get("data").cloned() {
+    let data: Vec<f64> = match get_arg(&args, &["data"]).cloned() {
         Some(value) => match serde_json::from_value(value) {
             Ok(parsed) => parsed,
             Err(e) => return RpcResponse::err(format!("Invalid 'data' parameter: {}", e)),
@@ -959,7 +1278,7 @@ async fn dispatch_pmdarima_fit_auto_arima(args: Value) -> RpcResponse {
 }
 
 async fn dispatch_pmdarima_forecast_auto_arima(args: Value) -> RpcResponse {
-    let data: Vec<f64> = match args.get("data").cloned() {
+    let data: Vec<f64> = match get_arg(&args, &["data"]).cloned() {
         Some(value) => match serde_json::from_value(value) {
             Ok(parsed) => parsed,
             Err(e) => return RpcResponse::err(format!("Invalid 'data' parameter: {}", e)),
@@ -976,7 +1295,7 @@ async fn dispatch_pmdarima_forecast_auto_arima(args: Value) -> RpcResponse {
         n_periods,
         get_optional_bool(&args, "seasonal"),
         get_optional_bool(&args, "return_conf_int"),
-        args.get("alpha").and_then(|v| v.as_f64()),
+        get_arg(&args, &["alpha"]).and_then(|v| v.as_f64()),
     )
     .await
     {
@@ -986,7 +1305,7 @@ async fn dispatch_pmdarima_forecast_auto_arima(args: Value) -> RpcResponse {
 }
 
 async fn dispatch_pmdarima_forecast_arima(args: Value) -> RpcResponse {
-    let data: Vec<f64> = match args.get("data").cloned() {
+    let data: Vec<f64> = match get_arg(&args, &["data"]).cloned() {
         Some(value) => match serde_json::from_value(value) {
             Ok(parsed) => parsed,
             Err(e) => return RpcResponse::err(format!("Invalid 'data' parameter: {}", e)),
@@ -1017,7 +1336,7 @@ async fn dispatch_pmdarima_forecast_arima(args: Value) -> RpcResponse {
         q,
         n_periods,
         get_optional_bool(&args, "return_conf_int"),
-        args.get("alpha").and_then(|v| v.as_f64()),
+        get_arg(&args, &["alpha"]).and_then(|v| v.as_f64()),
     )
     .await
     {
@@ -1027,7 +1346,7 @@ async fn dispatch_pmdarima_forecast_arima(args: Value) -> RpcResponse {
 }
 
 async fn dispatch_pmdarima_boxcox_transform(args: Value) -> RpcResponse {
-    let data: Vec<f64> = match args.get("data").cloned() {
+    let data: Vec<f64> = match get_arg(&args, &["data"]).cloned() {
         Some(value) => match serde_json::from_value(value) {
             Ok(parsed) => parsed,
             Err(e) => return RpcResponse::err(format!("Invalid 'data' parameter: {}", e)),
@@ -1041,15 +1360,22 @@ async fn dispatch_pmdarima_boxcox_transform(args: Value) -> RpcResponse {
     }
 }
 
+fn dispatch_pmdarima_boxcox_auto(args: Value) -> RpcResponse {
+    match get_series(&args, "data").and_then(|data| crate::commands::pmdarima::boxcox_auto(&data)) {
+        Ok(result) => RpcResponse::ok(result),
+        Err(e) => RpcResponse::err(e),
+    }
+}
+
 async fn dispatch_pmdarima_inverse_boxcox(args: Value) -> RpcResponse {
-    let data: Vec<f64> = match args.get("data").cloned() {
+    let data: Vec<f64> = match get_arg(&args, &["data"]).cloned() {
         Some(value) => match serde_json::from_value(value) {
             Ok(parsed) => parsed,
             Err(e) => return RpcResponse::err(format!("Invalid 'data' parameter: {}", e)),
         },
         None => return RpcResponse::err("Missing 'data' parameter"),
     };
-    let lambda = match args.get("lambda").and_then(|v| v.as_f64()) {
+    let lambda = match get_arg(&args, &["lambda"]).and_then(|v| v.as_f64()) {
         Some(value) => value,
         None => return RpcResponse::err("Missing 'lambda' parameter"),
     };
@@ -1061,7 +1387,7 @@ async fn dispatch_pmdarima_inverse_boxcox(args: Value) -> RpcResponse {
 }
 
 async fn dispatch_pmdarima_calculate_acf(args: Value) -> RpcResponse {
-    let data: Vec<f64> = match args.get("data").cloned() {
+    let data: Vec<f64> = match get_arg(&args, &["data"]).cloned() {
         Some(value) => match serde_json::from_value(value) {
             Ok(parsed) => parsed,
             Err(e) => return RpcResponse::err(format!("Invalid 'data' parameter: {}", e)),
@@ -1076,7 +1402,7 @@ async fn dispatch_pmdarima_calculate_acf(args: Value) -> RpcResponse {
 }
 
 async fn dispatch_pmdarima_calculate_pacf(args: Value) -> RpcResponse {
-    let data: Vec<f64> = match args.get("data").cloned() {
+    let data: Vec<f64> = match get_arg(&args, &["data"]).cloned() {
         Some(value) => match serde_json::from_value(value) {
             Ok(parsed) => parsed,
             Err(e) => return RpcResponse::err(format!("Invalid 'data' parameter: {}", e)),
@@ -1091,7 +1417,7 @@ async fn dispatch_pmdarima_calculate_pacf(args: Value) -> RpcResponse {
 }
 
 async fn dispatch_pmdarima_decompose_timeseries(args: Value) -> RpcResponse {
-    let data: Vec<f64> = match args.get("data").cloned() {
+    let data: Vec<f64> = match get_arg(&args, &["data"]).cloned() {
         Some(value) => match serde_json::from_value(value) {
             Ok(parsed) => parsed,
             Err(e) => return RpcResponse::err(format!("Invalid 'data' parameter: {}", e)),
@@ -1114,7 +1440,7 @@ async fn dispatch_pmdarima_decompose_timeseries(args: Value) -> RpcResponse {
 }
 
 async fn dispatch_pmdarima_cross_validate(args: Value) -> RpcResponse {
-    let data: Vec<f64> = match args.get("data").cloned() {
+    let data: Vec<f64> = match get_arg(&args, &["data"]).cloned() {
         Some(value) => match serde_json::from_value(value) {
             Ok(parsed) => parsed,
             Err(e) => return RpcResponse::err(format!("Invalid 'data' parameter: {}", e)),
@@ -1144,6 +1470,69 @@ async fn dispatch_pmdarima_cross_validate(args: Value) -> RpcResponse {
     }
 }
 
+// NATIVE SERIES ANALYTICS DISPATCH FUNCTIONS
+
+fn get_series(args: &Value, key: &str) -> Result<Vec<f64>, String> {
+    match get_arg(args, &[key]) {
+        Some(value) => serde_json::from_value(value.clone())
+            .map_err(|e| format!("Invalid '{}' parameter: {}", key, e)),
+        None => Err(format!("Missing '{}' parameter", key)),
+    }
+}
+
+fn dispatch_series(args: Value, transform: fn(&[f64]) -> Result<Vec<Option<f64>>, String>) -> RpcResponse {
+    match get_series(&args, "data").and_then(|data| transform(&data)) {
+        Ok(result) => RpcResponse::ok(result),
+        Err(e) => RpcResponse::err(e),
+    }
+}
+
+fn dispatch_series_rolling(
+    args: Value,
+    transform: fn(&[f64], usize) -> Result<Vec<Option<f64>>, String>,
+) -> RpcResponse {
+    let window = match get_arg(&args, &["window"]).and_then(|v| v.as_u64()) {
+        Some(window) => window as usize,
+        None => return RpcResponse::err("Missing 'window' parameter"),
+    };
+    match get_series(&args, "data").and_then(|data| transform(&data, window)) {
+        Ok(result) => RpcResponse::ok(result),
+        Err(e) => RpcResponse::err(e),
+    }
+}
+
+fn dispatch_series_correlation(args: Value) -> RpcResponse {
+    let result = get_series(&args, "x").and_then(|x| {
+        let y = get_series(&args, "y")?;
+        crate::commands::series_analytics::correlation(&x, &y)
+    });
+    match result {
+        Ok(correlation) => RpcResponse::ok(correlation),
+        Err(e) => RpcResponse::err(e),
+    }
+}
+
+fn dispatch_compute_indicators(args: Value) -> RpcResponse {
+    let parse = |key: &str| match get_arg(&args, &[key]) {
+        Some(value) => Ok(value.clone()),
+        None => Err(format!("Missing '{}' parameter", key)),
+    };
+    let result = parse("candles")
+        .and_then(|v| serde_json::from_value::<Vec<crate::commands::indicators::Candle>>(v)
+            .map_err(|e| format!("Invalid 'candles' parameter: {}", e)))
+        .and_then(|candles| {
+            let specs = parse("indicators").and_then(|v| {
+                serde_json::from_value::<Vec<crate::commands::indicators::IndicatorSpec>>(v)
+                    .map_err(|e| format!("Invalid 'indicators' parameter: {}", e))
+            })?;
+            crate::commands::indicators::compute(&candles, &specs)
+        });
+    match result {
+        Ok(result) => RpcResponse::ok(result),
+        Err(e) => RpcResponse::err(e),
+    }
+}
+
 // GOVERNMENT & MACRO DISPATCH FUNCTIONS
 
 async fn dispatch_execute_government_us_command(args: Value) -> RpcResponse {
@@ -1181,11 +1570,15 @@ async fn dispatch_get_treasury_prices(args: Value) -> RpcResponse {
 }
 
 async fn dispatch_get_treasury_auctions(args: Value) -> RpcResponse {
+    let (start_date, end_date) = match get_optional_date_range(&args) {
+        Ok(range) => range,
+        Err(e) => return RpcResponse::err(e),
+    };
     let mut command_args = Vec::new();
-    if let Some(value) = get_optional_string(&args, "start_date") {
+    if let Some(value) = start_date {
         command_args.push(value);
     }
-    if let Some(value) = get_optional_string(&args, "end_date") {
+    if let Some(value) = end_date {
         command_args.push(value);
     }
     if let Some(value) = get_optional_string(&args, "security_type") {
@@ -1248,6 +1641,10 @@ async fn dispatch_execute_congress_gov_command(args: Value) -> RpcResponse {
 }
 
 async fn dispatch_get_congress_bills(args: Value) -> RpcResponse {
+    let (start_date, end_date) = match get_optional_date_range(&args) {
+        Ok(range) => range,
+        Err(e) => return RpcResponse::err(e),
+    };
     let mut command_args = Vec::new();
     if let Some(value) = get_optional_i32(&args, "congress") {
         command_args.push(value.to_string());
@@ -1255,10 +1652,10 @@ async fn dispatch_get_congress_bills(args: Value) -> RpcResponse {
     if let Some(value) = get_optional_string(&args, "bill_type") {
         command_args.push(value);
     }
-    if let Some(value) = get_optional_string(&args, "start_date") {
+    if let Some(value) = start_date {
         command_args.push(value);
     }
-    if let Some(value) = get_optional_string(&args, "end_date") {
+    if let Some(value) = end_date {
         command_args.push(value);
     }
     if let Some(value) = get_optional_i32(&args, "limit") {
@@ -1369,6 +1766,10 @@ async fn dispatch_get_oecd_gdp_real(args: Value) -> RpcResponse {
 }
 
 async fn dispatch_get_oecd_consumer_price_index(args: Value) -> RpcResponse {
+    let (start_date, end_date) = match get_optional_date_range(&args) {
+        Ok(range) => range,
+        Err(e) => return RpcResponse::err(e),
+    };
     let mut command_args = Vec::new();
     if let Some(value) = get_optional_string(&args, "countries") {
         command_args.push(value);
@@ -1385,10 +1786,10 @@ async fn dispatch_get_oecd_consumer_price_index(args: Value) -> RpcResponse {
     if let Some(value) = get_optional_bool(&args, "harmonized") {
         command_args.push(value.to_string());
     }
-    if let Some(value) = get_optional_string(&args, "start_date") {
+    if let Some(value) = start_date {
         command_args.push(value);
     }
-    if let Some(value) = get_optional_string(&args, "end_date") {
+    if let Some(value) = end_date {
         command_args.push(value);
     }
 
@@ -1502,6 +1903,242 @@ async fn dispatch_get_imf_trade_summary(args: Value) -> RpcResponse {
 
 // DATABASE HEALTH & SETTINGS DISPATCH FUNCTIONS
 
+fn dispatch_get_db_status(args: Value) -> RpcResponse {
+    let retry = get_arg(&args, &["retry"]).and_then(|v| v.as_bool()).unwrap_or(false);
+    let status = if retry {
+        crate::database::pool::retry_init()
+    } else {
+        crate::database::pool::probe()
+    };
+    RpcResponse::ok(status)
+}
+
+fn dispatch_import_database(args: Value) -> RpcResponse {
+    let path = match get_arg(&args, &["path"]).and_then(|v| v.as_str()) {
+        Some(path) => path.to_string(),
+        None => return RpcResponse::err("Missing 'path' parameter"),
+    };
+    let strategy = match get_arg(&args, &["strategy"])
+        .and_then(|v| v.as_str())
+        .unwrap_or("skip")
+        .parse::<crate::database::import::MergeStrategy>()
+    {
+        Ok(strategy) => strategy,
+        Err(e) => return RpcResponse::err(e),
+    };
+    let tables: Option<Vec<String>> = get_arg(&args, &["tables"])
+        .and_then(|v| serde_json::from_value(v.clone()).ok());
+
+    match crate::database::import::import_database(std::path::Path::new(&path), strategy, tables.as_deref()) {
+        Ok(summary) => RpcResponse::ok(summary),
+        Err(e) => RpcResponse::err(format!("{:#}", e)),
+    }
+}
+
+fn dispatch_export_config(args: Value) -> RpcResponse {
+    let include_secrets = get_arg(&args, &["includeSecrets"]).and_then(|v| v.as_bool()).unwrap_or(false);
+    match crate::database::config_bundle::export_config(include_secrets) {
+        Ok(bundle) => RpcResponse::ok(bundle),
+        Err(e) => RpcResponse::err(format!("{:#}", e)),
+    }
+}
+
+fn dispatch_export_settings_snapshot() -> RpcResponse {
+    match crate::database::settings_snapshot::export_settings_snapshot() {
+        Ok(snapshot) => RpcResponse::ok(snapshot),
+        Err(e) => RpcResponse::err(format!("{:#}", e)),
+    }
+}
+
+fn dispatch_diff_settings(args: Value) -> RpcResponse {
+    let snapshot = match get_arg(&args, &["snapshot"]) {
+        Some(snapshot) => snapshot,
+        None => return RpcResponse::err("Missing 'snapshot' parameter"),
+    };
+    match crate::database::settings_snapshot::diff_settings(snapshot) {
+        Ok(diff) => RpcResponse::ok(diff),
+        Err(e) => RpcResponse::err(format!("{:#}", e)),
+    }
+}
+
+fn dispatch_import_config(args: Value) -> RpcResponse {
+    use crate::database::config_bundle;
+
+    let bundle = match get_arg(&args, &["bundle"]) {
+        Some(bundle) => match config_bundle::parse_bundle(bundle.clone()) {
+            Ok(bundle) => bundle,
+            Err(e) => return RpcResponse::err(e.to_string()),
+        },
+        None => return RpcResponse::err("Missing 'bundle' parameter"),
+    };
+    let strategy = match get_arg(&args, &["mergeStrategy"])
+        .and_then(|v| v.as_str())
+        .unwrap_or("skip")
+        .parse::<crate::database::import::MergeStrategy>()
+    {
+        Ok(strategy) => strategy,
+        Err(e) => return RpcResponse::err(e),
+    };
+
+    match config_bundle::import_config(bundle, strategy) {
+        Ok(summary) => RpcResponse::ok(summary),
+        Err(e) => RpcResponse::err(format!("{:#}", e)),
+    }
+}
+
+/// Commands a scheduled task may run besides the read-only ones; they only replace cached data
+const SCHEDULABLE_REFRESHES: &[&str] = &["force_refresh_news"];
+
+/// Runs scheduled tasks through this dispatcher, limited to read-only commands and
+/// cache refreshes so a stored task can't delete, overwrite or spawn anything
+pub struct RpcTaskDispatcher {
+    state: Arc<ServerState>,
+}
+
+impl RpcTaskDispatcher {
+    pub fn new(state: Arc<ServerState>) -> Self {
+        Self { state }
+    }
+}
+
+impl crate::commands::task_scheduler::TaskDispatcher for RpcTaskDispatcher {
+    fn supports(&self, command: &str) -> bool {
+        super::read_only::is_read_only(command) || SCHEDULABLE_REFRESHES.contains(&command)
+    }
+
+    fn dispatch(&self, cmd: String, args: Value) -> futures::future::BoxFuture<'static, Result<Value, String>> {
+        let state = self.state.clone();
+        Box::pin(async move {
+            let response = dispatch(state, RpcRequest { cmd, args }).await;
+            if response.success {
+                Ok(response.data.unwrap_or(Value::Null))
+            } else {
+                Err(response.error.unwrap_or_else(|| "Command failed".to_string()))
+            }
+        })
+    }
+}
+
+fn scheduled_task_id(args: &Value) -> Option<i64> {
+    get_arg(args, &["id"]).and_then(|v| v.as_i64())
+}
+
+fn dispatch_schedule_task(args: Value) -> RpcResponse {
+    let command = match get_arg(&args, &["command"]).and_then(|v| v.as_str()) {
+        Some(c) => c,
+        None => return RpcResponse::err("Missing 'command' parameter"),
+    };
+    let schedule = match get_arg(&args, &["schedule"]).and_then(|v| v.as_str()) {
+        Some(s) => s,
+        None => return RpcResponse::err("Missing 'schedule' parameter"),
+    };
+    let task_args = get_arg(&args, &["args"]).cloned();
+
+    match crate::commands::task_scheduler::create_task(command, task_args, schedule) {
+        Ok(task) => RpcResponse::ok(task),
+        Err(e) => RpcResponse::err(e),
+    }
+}
+
+fn dispatch_list_scheduled_tasks() -> RpcResponse {
+    match crate::commands::task_scheduler::get_tasks() {
+        Ok(tasks) => RpcResponse::ok(tasks),
+        Err(e) => RpcResponse::err(e),
+    }
+}
+
+fn dispatch_set_scheduled_task_enabled(args: Value) -> RpcResponse {
+    let id = match scheduled_task_id(&args) {
+        Some(id) => id,
+        None => return RpcResponse::err("Missing 'id' parameter"),
+    };
+    let enabled = match get_arg(&args, &["enabled"]).and_then(|v| v.as_bool()) {
+        Some(e) => e,
+        None => return RpcResponse::err("Missing 'enabled' parameter"),
+    };
+
+    match crate::commands::task_scheduler::update_enabled(id, enabled) {
+        Ok(task) => RpcResponse::ok(task),
+        Err(e) => RpcResponse::err(e),
+    }
+}
+
+fn dispatch_delete_scheduled_task(args: Value) -> RpcResponse {
+    let id = match scheduled_task_id(&args) {
+        Some(id) => id,
+        None => return RpcResponse::err("Missing 'id' parameter"),
+    };
+    match crate::commands::task_scheduler::remove_task(id) {
+        Ok(deleted) => RpcResponse::ok(serde_json::json!({ "deleted": deleted })),
+        Err(e) => RpcResponse::err(e),
+    }
+}
+
+fn dispatch_is_market_open(args: Value) -> RpcResponse {
+    let mut v = ArgValidator::new(&args);
+    let exchange = v.required_str(&["exchange"]);
+    if let Err(errors) = v.finish() {
+        return RpcResponse::invalid(errors);
+    }
+    let at = get_arg(&args, &["at"]).and_then(|v| v.as_str());
+    match crate::commands::trading_calendar::market_status(&exchange, at) {
+        Ok(status) => RpcResponse::ok(status),
+        Err(e) => RpcResponse::err(e),
+    }
+}
+
+fn dispatch_next_session(args: Value) -> RpcResponse {
+    let mut v = ArgValidator::new(&args);
+    let exchange = v.required_str(&["exchange"]);
+    if let Err(errors) = v.finish() {
+        return RpcResponse::invalid(errors);
+    }
+    let after = get_arg(&args, &["after"]).and_then(|v| v.as_str());
+    match crate::commands::trading_calendar::upcoming_session(&exchange, after) {
+        Ok(session) => RpcResponse::ok(session),
+        Err(e) => RpcResponse::err(e),
+    }
+}
+
+fn dispatch_set_calendar_override(args: Value) -> RpcResponse {
+    let mut v = ArgValidator::new(&args);
+    let exchange = v.required_str(&["exchange"]);
+    let date = v.required_str(&["date"]);
+    let kind = v.required_one_of(&["kind"], &["closed", "open", "early_close"]);
+    if let Err(errors) = v.finish() {
+        return RpcResponse::invalid(errors);
+    }
+    let close_time = get_arg(&args, &["closeTime"]).and_then(|v| v.as_str()).map(|s| s.to_string());
+    let note = get_arg(&args, &["note"]).and_then(|v| v.as_str()).map(|s| s.to_string());
+
+    let entry = crate::commands::trading_calendar::CalendarOverride { exchange, date, kind, close_time, note };
+    match crate::commands::trading_calendar::set_override(entry) {
+        Ok(saved) => RpcResponse::ok(saved),
+        Err(e) => RpcResponse::err(e),
+    }
+}
+
+fn dispatch_delete_calendar_override(args: Value) -> RpcResponse {
+    let mut v = ArgValidator::new(&args);
+    let exchange = v.required_str(&["exchange"]);
+    let date = v.required_str(&["date"]);
+    if let Err(errors) = v.finish() {
+        return RpcResponse::invalid(errors);
+    }
+    match crate::commands::trading_calendar::remove_override(&exchange, &date) {
+        Ok(deleted) => RpcResponse::ok(deleted),
+        Err(e) => RpcResponse::err(e),
+    }
+}
+
+fn dispatch_list_calendar_overrides(args: Value) -> RpcResponse {
+    let exchange = get_arg(&args, &["exchange"]).and_then(|v| v.as_str());
+    match crate::commands::trading_calendar::get_overrides(exchange) {
+        Ok(overrides) => RpcResponse::ok(overrides),
+        Err(e) => RpcResponse::err(e),
+    }
+}
+
 async fn dispatch_db_health() -> RpcResponse {
     match crate::database::pool::get_pool() {
         Ok(pool) => {
@@ -1525,7 +2162,7 @@ async fn dispatch_db_get_all_settings() -> RpcResponse {
 }
 
 async fn dispatch_db_get_setting(args: Value) -> RpcResponse {
-    let key = match args.get("key").and_then(|v| v.as_str()) {
+    let key = match get_arg(&args, &["key"]).and_then(|v| v.as_str()) {
         Some(k) => k.to_string(),
         None => return RpcResponse::err("Missing 'key' parameter"),
     };
@@ -1537,15 +2174,15 @@ async fn dispatch_db_get_setting(args: Value) -> RpcResponse {
 }
 
 async fn dispatch_db_save_setting(args: Value) -> RpcResponse {
-    let key = match args.get("key").and_then(|v| v.as_str()) {
+    let key = match get_arg(&args, &["key"]).and_then(|v| v.as_str()) {
         Some(k) => k.to_string(),
         None => return RpcResponse::err("Missing 'key' parameter"),
     };
-    let value = match args.get("value").and_then(|v| v.as_str()) {
+    let value = match get_arg(&args, &["value"]).and_then(|v| v.as_str()) {
         Some(v) => v.to_string(),
         None => return RpcResponse::err("Missing 'value' parameter"),
     };
-    let category = args.get("category").and_then(|v| v.as_str()).map(|s| s.to_string());
+    let category = get_arg(&args, &["category"]).and_then(|v| v.as_str()).map(|s| s.to_string());
 
     match crate::database::operations::save_setting(&key, &value, category.as_deref()) {
         Ok(_) => RpcResponse::ok(serde_json::json!({"saved": true})),
@@ -1553,9 +2190,70 @@ async fn dispatch_db_save_setting(args: Value) -> RpcResponse {
     }
 }
 
-// CREDENTIALS DISPATCH FUNCTIONS
-
-async fn dispatch_db_get_credentials() -> RpcResponse {
+async fn dispatch_db_save_settings(args: Value) -> RpcResponse {
+    let entries: Vec<crate::database::SettingEntry> = match get_arg(&args, &["entries"]) {
+        Some(v) => match serde_json::from_value(v.clone()) {
+            Ok(entries) => entries,
+            Err(e) => return RpcResponse::err(format!("Invalid 'entries' parameter: {}", e)),
+        },
+        None => return RpcResponse::err("Missing 'entries' parameter"),
+    };
+
+    match crate::database::operations::save_settings(&entries) {
+        Ok(results) => RpcResponse::ok(results),
+        Err(e) => RpcResponse::err(e.to_string()),
+    }
+}
+
+// CONFIG PROFILE DISPATCH FUNCTIONS
+
+fn dispatch_list_profiles() -> RpcResponse {
+    match crate::database::profiles::list_profiles() {
+        Ok(profiles) => RpcResponse::ok(profiles),
+        Err(e) => RpcResponse::err(e.to_string()),
+    }
+}
+
+fn dispatch_create_profile(args: Value) -> RpcResponse {
+    let name = match get_arg(&args, &["name"]).and_then(|v| v.as_str()) {
+        Some(n) => n.to_string(),
+        None => return RpcResponse::err("Missing 'name' parameter"),
+    };
+    let copy_from = get_arg(&args, &["copyFrom"]).and_then(|v| v.as_str());
+
+    match crate::database::profiles::create_profile(&name, copy_from) {
+        Ok(profile) => RpcResponse::ok(profile),
+        Err(e) => RpcResponse::err(e.to_string()),
+    }
+}
+
+fn dispatch_delete_profile(args: Value) -> RpcResponse {
+    let name = match get_arg(&args, &["name"]).and_then(|v| v.as_str()) {
+        Some(n) => n.to_string(),
+        None => return RpcResponse::err("Missing 'name' parameter"),
+    };
+
+    match crate::database::profiles::delete_profile(&name) {
+        Ok(profile) => RpcResponse::ok(profile),
+        Err(e) => RpcResponse::err(e.to_string()),
+    }
+}
+
+fn dispatch_switch_profile(args: Value) -> RpcResponse {
+    let name = match get_arg(&args, &["name"]).and_then(|v| v.as_str()) {
+        Some(n) => n.to_string(),
+        None => return RpcResponse::err("Missing 'name' parameter"),
+    };
+
+    match crate::database::profiles::switch_profile(&name) {
+        Ok(profile) => RpcResponse::ok(profile),
+        Err(e) => RpcResponse::err(e.to_string()),
+    }
+}
+
+// CREDENTIALS DISPATCH FUNCTIONS
+
+async fn dispatch_db_get_credentials() -> RpcResponse {
     match crate::database::operations::get_credentials() {
         Ok(creds) => RpcResponse::ok(creds),
         Err(e) => RpcResponse::err(e.to_string()),
@@ -1566,7 +2264,7 @@ async fn dispatch_db_save_credential(args: Value) -> RpcResponse {
     // what: accept credentials nested under a 'credential' key
     // why: the frontend sends the Tauri-style payload `{ credential: {...} }`
     // how: unwrap the credential field before deserializing, falling back to flat args
-    let cred_value = args.get("credential").cloned().unwrap_or_else(|| args.clone());
+    let cred_value = get_arg(&args, &["credential"]).cloned().unwrap_or_else(|| args.clone());
     let cred: crate::database::types::Credential = match serde_json::from_value(cred_value) {
         Ok(c) => c,
         Err(e) => return RpcResponse::err(format!("Invalid credential data: {}", e)),
@@ -1579,7 +2277,7 @@ async fn dispatch_db_save_credential(args: Value) -> RpcResponse {
 }
 
 async fn dispatch_db_get_credential_by_service(args: Value) -> RpcResponse {
-    let service_name = match args.get("serviceName").or(args.get("service_name")).and_then(|v| v.as_str()) {
+    let service_name = match get_arg(&args, &["serviceName"]).and_then(|v| v.as_str()) {
         Some(s) => s.to_string(),
         None => return RpcResponse::err("Missing 'serviceName' parameter"),
     };
@@ -1591,7 +2289,7 @@ async fn dispatch_db_get_credential_by_service(args: Value) -> RpcResponse {
 }
 
 async fn dispatch_db_delete_credential(args: Value) -> RpcResponse {
-    let id = match args.get("id").and_then(|v| v.as_i64()) {
+    let id = match get_arg(&args, &["id"]).and_then(|v| v.as_i64()) {
         Some(i) => i,
         None => return RpcResponse::err("Missing 'id' parameter"),
     };
@@ -1615,7 +2313,7 @@ async fn dispatch_db_save_llm_config(args: Value) -> RpcResponse {
     // what: support both wrapped and flat payloads for LLM configs
     // why: the frontend sends `{ config: ... }` for Tauri parity, which previously failed web RPC deserialization
     // how: try to unwrap a `config` field first, falling back to the raw args
-    let config_value = args.get("config").cloned().unwrap_or_else(|| args.clone());
+    let config_value = get_arg(&args, &["config"]).cloned().unwrap_or_else(|| args.clone());
     let config: crate::database::types::LLMConfig = match serde_json::from_value(config_value) {
         Ok(c) => c,
         Err(e) => return RpcResponse::err(format!("Invalid LLM config data: {}", e)),
@@ -1627,6 +2325,57 @@ async fn dispatch_db_save_llm_config(args: Value) -> RpcResponse {
     }
 }
 
+async fn dispatch_test_llm_config(args: Value) -> RpcResponse {
+    let provider = match get_arg(&args, &["provider"]).and_then(|v| v.as_str()) {
+        Some(p) => p.to_string(),
+        None => return RpcResponse::err("Missing 'provider' parameter"),
+    };
+    let text = |name: &str| get_arg(&args, &[name]).and_then(|v| v.as_str()).map(String::from);
+
+    match crate::commands::llm_check::check_llm_config(&provider, text("apiKey"), text("baseUrl"), text("model")).await {
+        Ok(result) => RpcResponse::ok(result),
+        Err(e) => RpcResponse::err(e),
+    }
+}
+
+fn dispatch_estimate_tokens(args: Value) -> RpcResponse {
+    let model = match get_arg(&args, &["model"]).and_then(|v| v.as_str()) {
+        Some(m) => m.to_string(),
+        None => return RpcResponse::err("Missing 'model' parameter"),
+    };
+    let text = get_arg(&args, &["text"]).and_then(|v| v.as_str());
+    let messages: Vec<crate::commands::llm_chat::ChatTurn> = match get_arg(&args, &["messages"])
+        .map(|v| serde_json::from_value(v.clone()))
+    {
+        Some(Ok(messages)) => messages,
+        Some(Err(e)) => return RpcResponse::err(format!("Invalid messages: {}", e)),
+        None if text.is_some() => Vec::new(),
+        None => return RpcResponse::err("Provide 'text' or 'messages'"),
+    };
+
+    RpcResponse::ok(crate::commands::llm_tokens::estimate(&model, text, &messages))
+}
+
+// No event channel over HTTP: the reply is returned whole once the stream ends
+async fn dispatch_llm_chat_stream(args: Value) -> RpcResponse {
+    let session_uuid = match get_arg(&args, &["sessionUuid"]).and_then(|v| v.as_str()) {
+        Some(s) => s.to_string(),
+        None => return RpcResponse::err("Missing 'sessionUuid' parameter"),
+    };
+    let messages: Vec<crate::commands::llm_chat::ChatTurn> = match get_arg(&args, &["messages"])
+        .map(|v| serde_json::from_value(v.clone()))
+    {
+        Some(Ok(messages)) => messages,
+        Some(Err(e)) => return RpcResponse::err(format!("Invalid messages: {}", e)),
+        None => return RpcResponse::err("Missing 'messages' parameter"),
+    };
+
+    match crate::commands::llm_chat::stream_chat(&session_uuid, &messages, request_id_arg(&args), |_| {}).await {
+        Ok(result) => RpcResponse::ok(result),
+        Err(e) => RpcResponse::err(e),
+    }
+}
+
 async fn dispatch_db_get_llm_global_settings() -> RpcResponse {
     match crate::database::operations::get_llm_global_settings() {
         Ok(settings) => RpcResponse::ok(settings),
@@ -1638,7 +2387,7 @@ async fn dispatch_db_save_llm_global_settings(args: Value) -> RpcResponse {
     // what: deserialize LLM global settings from either a wrapped or flat payload
     // why: mirrors the config handler so web RPC accepts the Tauri-style `{ settings: {...} }` shape
     // how: pick the nested `settings` value when present, otherwise parse the full args map
-    let settings_value = args.get("settings").cloned().unwrap_or_else(|| args.clone());
+    let settings_value = get_arg(&args, &["settings"]).cloned().unwrap_or_else(|| args.clone());
     let settings: crate::database::types::LLMGlobalSettings = match serde_json::from_value(settings_value) {
         Ok(s) => s,
         Err(e) => return RpcResponse::err(format!("Invalid LLM global settings: {}", e)),
@@ -1653,7 +2402,7 @@ async fn dispatch_db_save_llm_global_settings(args: Value) -> RpcResponse {
 // CHAT SESSION DISPATCH FUNCTIONS
 
 async fn dispatch_db_create_chat_session(args: Value) -> RpcResponse {
-    let title = args.get("title").and_then(|v| v.as_str()).unwrap_or("New Chat").to_string();
+    let title = get_arg(&args, &["title"]).and_then(|v| v.as_str()).unwrap_or("New Chat").to_string();
 
     match crate::database::operations::create_chat_session(&title) {
         Ok(session) => RpcResponse::ok(session),
@@ -1662,7 +2411,7 @@ async fn dispatch_db_create_chat_session(args: Value) -> RpcResponse {
 }
 
 async fn dispatch_db_get_chat_sessions(args: Value) -> RpcResponse {
-    let limit = args.get("limit").and_then(|v| v.as_i64());
+    let limit = get_arg(&args, &["limit"]).and_then(|v| v.as_i64());
 
     match crate::database::operations::get_chat_sessions(limit) {
         Ok(sessions) => RpcResponse::ok(sessions),
@@ -1674,7 +2423,7 @@ async fn dispatch_db_add_chat_message(args: Value) -> RpcResponse {
     // what: support wrapped chat messages
     // why: the frontend posts `{ msg: {...} }` to mirror Tauri invoke semantics
     // how: unwrap `msg` when present, otherwise parse the raw map
-    let message_value = args.get("msg").cloned().or_else(|| args.get("message").cloned()).unwrap_or_else(|| args.clone());
+    let message_value = get_arg(&args, &["msg"]).cloned().or_else(|| get_arg(&args, &["message"]).cloned()).unwrap_or_else(|| args.clone());
     let message: crate::database::types::ChatMessage = match serde_json::from_value(message_value) {
         Ok(m) => m,
         Err(e) => return RpcResponse::err(format!("Invalid chat message: {}", e)),
@@ -1687,7 +2436,7 @@ async fn dispatch_db_add_chat_message(args: Value) -> RpcResponse {
 }
 
 async fn dispatch_db_get_chat_messages(args: Value) -> RpcResponse {
-    let session_uuid = match args.get("sessionUuid").or(args.get("session_uuid")).and_then(|v| v.as_str()) {
+    let session_uuid = match get_arg(&args, &["sessionUuid"]).and_then(|v| v.as_str()) {
         Some(s) => s.to_string(),
         None => return RpcResponse::err("Missing 'sessionUuid' parameter"),
     };
@@ -1698,8 +2447,23 @@ async fn dispatch_db_get_chat_messages(args: Value) -> RpcResponse {
     }
 }
 
+async fn dispatch_db_get_chat_messages_paged(args: Value) -> RpcResponse {
+    let session_uuid = match get_arg(&args, &["sessionUuid"]).and_then(|v| v.as_str()) {
+        Some(s) => s.to_string(),
+        None => return RpcResponse::err("Missing 'sessionUuid' parameter"),
+    };
+    let before_timestamp = get_arg(&args, &["beforeTimestamp"]).and_then(|v| v.as_str()).map(|s| s.to_string());
+    let before_id = get_arg(&args, &["beforeId"]).and_then(|v| v.as_str()).map(|s| s.to_string());
+    let limit = get_arg(&args, &["limit"]).and_then(|v| v.as_u64()).map(|v| v as usize);
+
+    match crate::commands::database::db_get_chat_messages_paged(session_uuid, before_timestamp, before_id, limit).await {
+        Ok(page) => RpcResponse::ok(page),
+        Err(e) => RpcResponse::err(e),
+    }
+}
+
 async fn dispatch_db_delete_chat_session(args: Value) -> RpcResponse {
-    let session_uuid = match args.get("sessionUuid").or(args.get("session_uuid")).and_then(|v| v.as_str()) {
+    let session_uuid = match get_arg(&args, &["sessionUuid"]).and_then(|v| v.as_str()) {
         Some(s) => s.to_string(),
         None => return RpcResponse::err("Missing 'sessionUuid' parameter"),
     };
@@ -1723,7 +2487,7 @@ async fn dispatch_db_save_data_source(args: Value) -> RpcResponse {
     // what: deserialize data sources from either wrapped or flat payloads
     // why: the JS client currently wraps the object under `source` for Tauri compatibility
     // how: peel off the wrapper before passing to serde
-    let source_value = args.get("source").cloned().unwrap_or_else(|| args.clone());
+    let source_value = get_arg(&args, &["source"]).cloned().unwrap_or_else(|| args.clone());
     let source: crate::database::types::DataSource = match serde_json::from_value(source_value) {
         Ok(s) => s,
         Err(e) => return RpcResponse::err(format!("Invalid data source: {}", e)),
@@ -1735,8 +2499,33 @@ async fn dispatch_db_save_data_source(args: Value) -> RpcResponse {
     }
 }
 
+async fn dispatch_test_data_source(args: Value) -> RpcResponse {
+    let id = match get_arg(&args, &["id"]).and_then(|v| v.as_str()) {
+        Some(i) => i.to_string(),
+        None => return RpcResponse::err("Missing 'id' parameter"),
+    };
+    let timeout_ms = get_arg(&args, &["timeoutMs"]).and_then(|v| v.as_u64());
+
+    match crate::commands::data_source_probe::probe_one(&id, timeout_ms).await {
+        Ok(result) => RpcResponse::ok(result),
+        Err(e) => RpcResponse::err(e),
+    }
+}
+
+async fn dispatch_probe_all_data_sources(args: Value) -> RpcResponse {
+    let timeout_ms = get_arg(&args, &["timeoutMs"]).and_then(|v| v.as_u64());
+    let concurrency = get_arg(&args, &["concurrency"])
+        .and_then(|v| v.as_u64())
+        .map(|n| n as usize);
+
+    match crate::commands::data_source_probe::probe_all(timeout_ms, concurrency, request_id_arg(&args)).await {
+        Ok(results) => RpcResponse::ok(results),
+        Err(e) => RpcResponse::err(e),
+    }
+}
+
 async fn dispatch_db_delete_data_source(args: Value) -> RpcResponse {
-    let id = match args.get("id").and_then(|v| v.as_str()) {
+    let id = match get_arg(&args, &["id"]).and_then(|v| v.as_str()) {
         Some(i) => i.to_string(),
         None => return RpcResponse::err("Missing 'id' parameter"),
     };
@@ -1757,7 +2546,7 @@ async fn dispatch_db_get_ws_provider_configs() -> RpcResponse {
 }
 
 async fn dispatch_db_get_ws_provider_config(args: Value) -> RpcResponse {
-    let provider_name = match args.get("provider_name").or(args.get("providerName")).and_then(|v| v.as_str()) {
+    let provider_name = match get_arg(&args, &["provider_name"]).and_then(|v| v.as_str()) {
         Some(p) => p.to_string(),
         None => return RpcResponse::err("Missing 'provider_name' parameter"),
     };
@@ -1772,7 +2561,7 @@ async fn dispatch_db_save_ws_provider_config(args: Value) -> RpcResponse {
     // what: accept provider configs in either wrapped or flat form
     // why: the frontend mirrors Tauri's `{ config }` payload shape
     // how: unwrap the config field when present before deserializing
-    let config_value = args.get("config").cloned().unwrap_or_else(|| args.clone());
+    let config_value = get_arg(&args, &["config"]).cloned().unwrap_or_else(|| args.clone());
     let config: crate::database::types::WSProviderConfig = match serde_json::from_value(config_value) {
         Ok(c) => c,
         Err(e) => return RpcResponse::err(format!("Invalid WS provider config data: {}", e)),
@@ -1785,7 +2574,7 @@ async fn dispatch_db_save_ws_provider_config(args: Value) -> RpcResponse {
 }
 
 async fn dispatch_db_delete_ws_provider_config(args: Value) -> RpcResponse {
-    let provider_name = match args.get("provider_name").or(args.get("providerName")).and_then(|v| v.as_str()) {
+    let provider_name = match get_arg(&args, &["provider_name"]).and_then(|v| v.as_str()) {
         Some(p) => p.to_string(),
         None => return RpcResponse::err("Missing 'provider_name' parameter"),
     };
@@ -1797,7 +2586,7 @@ async fn dispatch_db_delete_ws_provider_config(args: Value) -> RpcResponse {
 }
 
 async fn dispatch_db_toggle_ws_provider_enabled(args: Value) -> RpcResponse {
-    let provider_name = match args.get("provider_name").or(args.get("providerName")).and_then(|v| v.as_str()) {
+    let provider_name = match get_arg(&args, &["provider_name"]).and_then(|v| v.as_str()) {
         Some(p) => p.to_string(),
         None => return RpcResponse::err("Missing 'provider_name' parameter"),
     };
@@ -1818,7 +2607,7 @@ async fn dispatch_db_list_portfolios() -> RpcResponse {
 }
 
 async fn dispatch_db_get_portfolio(args: Value) -> RpcResponse {
-    let portfolio_id = match args.get("id").or(args.get("portfolioId")).or(args.get("portfolio_id")).and_then(|v| v.as_str()) {
+    let portfolio_id = match get_arg(&args, &["id", "portfolioId"]).and_then(|v| v.as_str()) {
         Some(i) => i.to_string(),
         None => return RpcResponse::err("Missing 'id' or 'portfolioId' parameter"),
     };
@@ -1829,36 +2618,49 @@ async fn dispatch_db_get_portfolio(args: Value) -> RpcResponse {
     }
 }
 
-async fn dispatch_db_create_portfolio(args: Value) -> RpcResponse {
-    let id = args
-        .get("id")
-        .and_then(|v| v.as_str())
-        .map(String::from)
-        .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
-    let name = match args.get("name").and_then(|v| v.as_str()) {
-        Some(n) => n.to_string(),
-        None => return RpcResponse::err("Missing 'name' parameter"),
-    };
-    let provider = match args.get("provider").and_then(|v| v.as_str()) {
-        Some(p) => p.to_string(),
-        None => return RpcResponse::err("Missing 'provider' parameter"),
-    };
-    let initial_balance = match args
-        .get("initialBalance")
-        .or(args.get("initial_balance"))
-        .and_then(|v| v.as_f64())
-    {
-        Some(value) => value,
-        None => return RpcResponse::err("Missing 'initialBalance' parameter"),
+async fn dispatch_get_portfolio_summary(args: Value) -> RpcResponse {
+    let portfolio_id = match get_arg(&args, &["portfolioId", "id"]).and_then(|v| v.as_str()) {
+        Some(id) => id.to_string(),
+        None => return RpcResponse::err("Missing 'portfolioId' parameter"),
     };
-    let currency = args.get("currency").and_then(|v| v.as_str()).unwrap_or("USD").to_string();
-    let margin_mode = args
-        .get("marginMode")
-        .or(args.get("margin_mode"))
+    let summary = crate::commands::portfolio_management::portfolio_summary(
+        &portfolio_id,
+        |symbols| async move {
+            match crate::data_sources::yfinance::YFinanceProviderWeb::get_quotes(&symbols).await {
+                Ok(quotes) => quotes,
+                Err(e) => {
+                    tracing::warn!(error = %e, "Portfolio summary quotes failed; valuing at cost");
+                    Vec::new()
+                }
+            }
+        },
+        |base, quote| async move {
+            crate::data_sources::yfinance::YFinanceProviderWeb::get_fx_rate(&base, &quote).await.map(|r| r.rate)
+        },
+    )
+    .await;
+    match summary {
+        Ok(summary) => RpcResponse::ok(summary),
+        Err(e) => RpcResponse::err(e),
+    }
+}
+
+async fn dispatch_db_create_portfolio(args: Value) -> RpcResponse {
+    let mut v = ArgValidator::new(&args);
+    let id = v.id_or_generated();
+    let name = v.required_str(&["name"]);
+    let provider = v.required_str(&["provider"]);
+    let initial_balance = v.required_f64(&["initialBalance"]);
+    if let Err(errors) = v.finish() {
+        return RpcResponse::invalid(errors);
+    }
+    let currency = get_arg(&args, &["currency"]).and_then(|v| v.as_str()).unwrap_or("USD").to_string();
+    let margin_mode = get_arg(&args, &["marginMode"])
         .and_then(|v| v.as_str())
         .unwrap_or("cross")
         .to_string();
-    let leverage = args.get("leverage").and_then(|v| v.as_f64()).unwrap_or(1.0);
+    let leverage = get_arg(&args, &["leverage"]).and_then(|v| v.as_f64()).unwrap_or(1.0);
+    let owner = get_arg(&args, &["owner"]).and_then(|v| v.as_str());
 
     match crate::database::paper_trading::create_portfolio(
         &id,
@@ -1868,6 +2670,7 @@ async fn dispatch_db_create_portfolio(args: Value) -> RpcResponse {
         &currency,
         &margin_mode,
         leverage,
+        owner,
     ) {
         Ok(portfolio) => RpcResponse::ok(portfolio),
         Err(e) => RpcResponse::err(e.to_string()),
@@ -1875,10 +2678,7 @@ async fn dispatch_db_create_portfolio(args: Value) -> RpcResponse {
 }
 
 async fn dispatch_db_delete_portfolio(args: Value) -> RpcResponse {
-    let portfolio_id = match args
-        .get("id")
-        .or(args.get("portfolioId"))
-        .or(args.get("portfolio_id"))
+    let portfolio_id = match get_arg(&args, &["id", "portfolioId"])
         .and_then(|v| v.as_str())
     {
         Some(i) => i.to_string(),
@@ -1891,6 +2691,19 @@ async fn dispatch_db_delete_portfolio(args: Value) -> RpcResponse {
     }
 }
 
+async fn dispatch_purge_portfolio(args: Value) -> RpcResponse {
+    let portfolio_id = match get_arg(&args, &["portfolioId", "id"]).and_then(|v| v.as_str()) {
+        Some(id) => id.to_string(),
+        None => return RpcResponse::err("Missing 'portfolioId' parameter"),
+    };
+    let confirm = get_arg(&args, &["confirm"]).and_then(|v| v.as_bool()).unwrap_or(false);
+
+    match crate::commands::database::purge_portfolio(portfolio_id, confirm).await {
+        Ok(purged) => RpcResponse::ok(purged),
+        Err(e) => RpcResponse::err(e),
+    }
+}
+
 // WATCHLIST DISPATCH FUNCTIONS
 
 async fn dispatch_db_get_watchlists() -> RpcResponse {
@@ -1901,12 +2714,12 @@ async fn dispatch_db_get_watchlists() -> RpcResponse {
 }
 
 async fn dispatch_db_create_watchlist(args: Value) -> RpcResponse {
-    let name = match args.get("name").and_then(|v| v.as_str()) {
+    let name = match get_arg(&args, &["name"]).and_then(|v| v.as_str()) {
         Some(n) => n.to_string(),
         None => return RpcResponse::err("Missing 'name' parameter"),
     };
-    let description = args.get("description").and_then(|v| v.as_str()).map(|s| s.to_string());
-    let color = args.get("color").and_then(|v| v.as_str()).unwrap_or("#3b82f6").to_string();
+    let description = get_arg(&args, &["description"]).and_then(|v| v.as_str()).map(|s| s.to_string());
+    let color = get_arg(&args, &["color"]).and_then(|v| v.as_str()).unwrap_or("#3b82f6").to_string();
 
     match crate::database::queries::create_watchlist(&name, description.as_deref(), &color) {
         Ok(watchlist) => RpcResponse::ok(watchlist),
@@ -1915,7 +2728,7 @@ async fn dispatch_db_create_watchlist(args: Value) -> RpcResponse {
 }
 
 async fn dispatch_db_get_watchlist_stocks(args: Value) -> RpcResponse {
-    let watchlist_id = match args.get("watchlistId").or(args.get("watchlist_id")).and_then(|v| v.as_str()) {
+    let watchlist_id = match get_arg(&args, &["watchlistId"]).and_then(|v| v.as_str()) {
         Some(i) => i.to_string(),
         None => return RpcResponse::err("Missing 'watchlistId' parameter"),
     };
@@ -1927,15 +2740,18 @@ async fn dispatch_db_get_watchlist_stocks(args: Value) -> RpcResponse {
 }
 
 async fn dispatch_db_add_watchlist_stock(args: Value) -> RpcResponse {
-    let watchlist_id = match args.get("watchlistId").or(args.get("watchlist_id")).and_then(|v| v.as_str()) {
+    let watchlist_id = match get_arg(&args, &["watchlistId"]).and_then(|v| v.as_str()) {
         Some(i) => i.to_string(),
         None => return RpcResponse::err("Missing 'watchlistId' parameter"),
     };
-    let symbol = match args.get("symbol").and_then(|v| v.as_str()) {
-        Some(s) => s.to_string(),
+    let symbol = match get_arg(&args, &["symbol"]).and_then(|v| v.as_str()) {
+        Some(s) => match Symbol::new(s) {
+            Ok(symbol) => symbol,
+            Err(e) => return RpcResponse::err(e),
+        },
         None => return RpcResponse::err("Missing 'symbol' parameter"),
     };
-    let notes = args.get("notes").and_then(|v| v.as_str()).map(|s| s.to_string());
+    let notes = get_arg(&args, &["notes"]).and_then(|v| v.as_str()).map(|s| s.to_string());
 
     match crate::database::queries::add_watchlist_stock(&watchlist_id, &symbol, notes.as_deref()) {
         Ok(stock) => RpcResponse::ok(stock),
@@ -1944,11 +2760,11 @@ async fn dispatch_db_add_watchlist_stock(args: Value) -> RpcResponse {
 }
 
 async fn dispatch_db_remove_watchlist_stock(args: Value) -> RpcResponse {
-    let watchlist_id = match args.get("watchlistId").or(args.get("watchlist_id")).and_then(|v| v.as_str()) {
+    let watchlist_id = match get_arg(&args, &["watchlistId"]).and_then(|v| v.as_str()) {
         Some(i) => i.to_string(),
         None => return RpcResponse::err("Missing 'watchlistId' parameter"),
     };
-    let symbol = match args.get("symbol").and_then(|v| v.as_str()) {
+    let symbol = match get_arg(&args, &["symbol"]).and_then(|v| v.as_str()) {
         Some(s) => s.to_string(),
         None => return RpcResponse::err("Missing 'symbol' parameter"),
     };
@@ -1960,7 +2776,7 @@ async fn dispatch_db_remove_watchlist_stock(args: Value) -> RpcResponse {
 }
 
 async fn dispatch_db_delete_watchlist(args: Value) -> RpcResponse {
-    let watchlist_id = match args.get("watchlistId").or(args.get("watchlist_id")).or(args.get("id")).and_then(|v| v.as_str()) {
+    let watchlist_id = match get_arg(&args, &["watchlistId", "id"]).and_then(|v| v.as_str()) {
         Some(i) => i.to_string(),
         None => return RpcResponse::err("Missing 'watchlistId' or 'id' parameter"),
     };
@@ -1991,11 +2807,11 @@ async fn dispatch_check_setup_status() -> RpcResponse {
 // PAPER TRADING DISPATCH FUNCTIONS
 
 async fn dispatch_db_update_portfolio_balance(args: Value) -> RpcResponse {
-    let id = match args.get("id").and_then(|v| v.as_str()) {
+    let id = match get_arg(&args, &["id"]).and_then(|v| v.as_str()) {
         Some(i) => i.to_string(),
         None => return RpcResponse::err("Missing 'id' parameter"),
     };
-    let new_balance = match args.get("newBalance").or(args.get("new_balance")).and_then(|v| v.as_f64()) {
+    let new_balance = match get_arg(&args, &["newBalance"]).and_then(|v| v.as_f64()) {
         Some(b) => b,
         None => return RpcResponse::err("Missing 'newBalance' parameter"),
     };
@@ -2006,31 +2822,40 @@ async fn dispatch_db_update_portfolio_balance(args: Value) -> RpcResponse {
     }
 }
 
-async fn dispatch_db_create_position(args: Value) -> RpcResponse {
-    let id = args.get("id").and_then(|v| v.as_str()).map(|s| s.to_string()).unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
-    let portfolio_id = match args.get("portfolioId").or(args.get("portfolio_id")).and_then(|v| v.as_str()) {
-        Some(s) => s.to_string(),
-        None => return RpcResponse::err("Missing 'portfolioId' parameter"),
-    };
-    let symbol = match args.get("symbol").and_then(|v| v.as_str()) {
-        Some(s) => s.to_string(),
-        None => return RpcResponse::err("Missing 'symbol' parameter"),
-    };
-    let side = match args.get("side").and_then(|v| v.as_str()) {
-        Some(s) => s.to_string(),
-        None => return RpcResponse::err("Missing 'side' parameter"),
+async fn dispatch_db_adjust_portfolio_balance(args: Value) -> RpcResponse {
+    let id = match get_arg(&args, &["id"]).and_then(|v| v.as_str()) {
+        Some(i) => i.to_string(),
+        None => return RpcResponse::err("Missing 'id' parameter"),
     };
-    let entry_price = match args.get("entryPrice").or(args.get("entry_price")).and_then(|v| v.as_f64()) {
-        Some(f) => f,
-        None => return RpcResponse::err("Missing 'entryPrice' parameter"),
+    let delta = match get_arg(&args, &["delta"]).and_then(|v| v.as_f64()) {
+        Some(d) => d,
+        None => return RpcResponse::err("Missing 'delta' parameter"),
     };
-    let quantity = match args.get("quantity").and_then(|v| v.as_f64()) {
-        Some(f) => f,
-        None => return RpcResponse::err("Missing 'quantity' parameter"),
-    };
-    let leverage = args.get("leverage").and_then(|v| v.as_f64()).unwrap_or(1.0);
-    let margin_mode = args.get("marginMode").or(args.get("margin_mode")).and_then(|v| v.as_str()).unwrap_or("cross").to_string();
 
+    match crate::database::paper_trading::adjust_portfolio_balance(&id, delta) {
+        Ok(balance) => RpcResponse::ok(balance),
+        Err(e) => RpcResponse::err(e.to_string()),
+    }
+}
+
+async fn dispatch_db_create_position(args: Value) -> RpcResponse {
+    let mut v = ArgValidator::new(&args);
+    let id = v.id_or_generated();
+    let portfolio_id = v.required_str(&["portfolioId"]);
+    let symbol = v.required_str(&["symbol"]);
+    let side = v.required_one_of(&["side"], &["long", "short"]);
+    let entry_price = v.required_positive(&["entryPrice"]);
+    let quantity = v.required_positive(&["quantity"]);
+    if let Err(errors) = v.finish() {
+        return RpcResponse::invalid(errors);
+    }
+    let leverage = get_arg(&args, &["leverage"]).and_then(|v| v.as_f64()).unwrap_or(1.0);
+    let margin_mode = get_arg(&args, &["marginMode"]).and_then(|v| v.as_str()).unwrap_or("cross").to_string();
+
+    let (quantity, entry_price) = match normalize_fill(&symbol, quantity, entry_price) {
+        Ok(rounded) => rounded,
+        Err(e) => return RpcResponse::err(e),
+    };
     match crate::database::paper_trading::create_position(&id, &portfolio_id, &symbol, &side, entry_price, quantity, leverage, &margin_mode) {
          Ok(_) => RpcResponse::ok(serde_json::json!({"created": true})),
          Err(e) => RpcResponse::err(e.to_string()),
@@ -2038,11 +2863,11 @@ async fn dispatch_db_create_position(args: Value) -> RpcResponse {
 }
 
 async fn dispatch_db_get_portfolio_positions(args: Value) -> RpcResponse {
-    let portfolio_id = match args.get("portfolioId").or(args.get("portfolio_id")).and_then(|v| v.as_str()) {
+    let portfolio_id = match get_arg(&args, &["portfolioId"]).and_then(|v| v.as_str()) {
         Some(s) => s.to_string(),
         None => return RpcResponse::err("Missing 'portfolioId' parameter"),
     };
-    let status = args.get("status").and_then(|v| v.as_str()).map(|s| s.to_string());
+    let status = get_arg(&args, &["status"]).and_then(|v| v.as_str()).map(|s| s.to_string());
 
     match crate::database::paper_trading::get_portfolio_positions(&portfolio_id, status.as_deref()) {
         Ok(positions) => RpcResponse::ok(positions),
@@ -2051,7 +2876,7 @@ async fn dispatch_db_get_portfolio_positions(args: Value) -> RpcResponse {
 }
 
 async fn dispatch_db_get_position(args: Value) -> RpcResponse {
-    let id = match args.get("id").and_then(|v| v.as_str()) {
+    let id = match get_arg(&args, &["id"]).and_then(|v| v.as_str()) {
         Some(s) => s.to_string(),
         None => return RpcResponse::err("Missing 'id' parameter"),
     };
@@ -2062,15 +2887,15 @@ async fn dispatch_db_get_position(args: Value) -> RpcResponse {
 }
 
 async fn dispatch_db_get_position_by_symbol(args: Value) -> RpcResponse {
-    let portfolio_id = match args.get("portfolioId").or(args.get("portfolio_id")).and_then(|v| v.as_str()) {
+    let portfolio_id = match get_arg(&args, &["portfolioId"]).and_then(|v| v.as_str()) {
         Some(s) => s.to_string(),
         None => return RpcResponse::err("Missing 'portfolioId' parameter"),
     };
-    let symbol = match args.get("symbol").and_then(|v| v.as_str()) {
+    let symbol = match get_arg(&args, &["symbol"]).and_then(|v| v.as_str()) {
         Some(s) => s.to_string(),
         None => return RpcResponse::err("Missing 'symbol' parameter"),
     };
-    let status = args.get("status").and_then(|v| v.as_str()).unwrap_or("open").to_string();
+    let status = get_arg(&args, &["status"]).and_then(|v| v.as_str()).unwrap_or("open").to_string();
 
     match crate::database::paper_trading::get_position_by_symbol(&portfolio_id, &symbol, &status) {
         Ok(pos) => RpcResponse::ok(pos),
@@ -2079,19 +2904,19 @@ async fn dispatch_db_get_position_by_symbol(args: Value) -> RpcResponse {
 }
 
 async fn dispatch_db_get_position_by_symbol_and_side(args: Value) -> RpcResponse {
-    let portfolio_id = match args.get("portfolioId").or(args.get("portfolio_id")).and_then(|v| v.as_str()) {
+    let portfolio_id = match get_arg(&args, &["portfolioId"]).and_then(|v| v.as_str()) {
         Some(s) => s.to_string(),
         None => return RpcResponse::err("Missing 'portfolioId' parameter"),
     };
-    let symbol = match args.get("symbol").and_then(|v| v.as_str()) {
+    let symbol = match get_arg(&args, &["symbol"]).and_then(|v| v.as_str()) {
         Some(s) => s.to_string(),
         None => return RpcResponse::err("Missing 'symbol' parameter"),
     };
-    let side = match args.get("side").and_then(|v| v.as_str()) {
+    let side = match get_arg(&args, &["side"]).and_then(|v| v.as_str()) {
         Some(s) => s.to_string(),
         None => return RpcResponse::err("Missing 'side' parameter"),
     };
-    let status = args.get("status").and_then(|v| v.as_str()).unwrap_or("open").to_string();
+    let status = get_arg(&args, &["status"]).and_then(|v| v.as_str()).unwrap_or("open").to_string();
 
     match crate::database::paper_trading::get_position_by_symbol_and_side(&portfolio_id, &symbol, &side, &status) {
         Ok(pos) => RpcResponse::ok(pos),
@@ -2100,18 +2925,18 @@ async fn dispatch_db_get_position_by_symbol_and_side(args: Value) -> RpcResponse
 }
 
 async fn dispatch_db_update_position(args: Value) -> RpcResponse {
-    let id = match args.get("id").and_then(|v| v.as_str()) {
+    let id = match get_arg(&args, &["id"]).and_then(|v| v.as_str()) {
         Some(s) => s.to_string(),
         None => return RpcResponse::err("Missing 'id' parameter"),
     };
-    let quantity = args.get("quantity").and_then(|v| v.as_f64());
-    let entry_price = args.get("entryPrice").or(args.get("entry_price")).and_then(|v| v.as_f64());
-    let current_price = args.get("currentPrice").or(args.get("current_price")).and_then(|v| v.as_f64());
-    let unrealized_pnl = args.get("unrealizedPnl").or(args.get("unrealized_pnl")).and_then(|v| v.as_f64());
-    let realized_pnl = args.get("realizedPnl").or(args.get("realized_pnl")).and_then(|v| v.as_f64());
-    let liquidation_price = args.get("liquidationPrice").or(args.get("liquidation_price")).and_then(|v| v.as_f64());
-    let status = args.get("status").and_then(|v| v.as_str()).map(|s| s.to_string());
-    let closed_at = args.get("closedAt").or(args.get("closed_at")).and_then(|v| v.as_str()).map(|s| s.to_string());
+    let quantity = get_arg(&args, &["quantity"]).and_then(|v| v.as_f64());
+    let entry_price = get_arg(&args, &["entryPrice"]).and_then(|v| v.as_f64());
+    let current_price = get_arg(&args, &["currentPrice"]).and_then(|v| v.as_f64());
+    let unrealized_pnl = get_arg(&args, &["unrealizedPnl"]).and_then(|v| v.as_f64());
+    let realized_pnl = get_arg(&args, &["realizedPnl"]).and_then(|v| v.as_f64());
+    let liquidation_price = get_arg(&args, &["liquidationPrice"]).and_then(|v| v.as_f64());
+    let status = get_arg(&args, &["status"]).and_then(|v| v.as_str()).map(|s| s.to_string());
+    let closed_at = get_arg(&args, &["closedAt"]).and_then(|v| v.as_str()).map(|s| s.to_string());
 
     match crate::database::paper_trading::update_position(&id, quantity, entry_price, current_price, unrealized_pnl, realized_pnl, liquidation_price, status.as_deref(), closed_at.as_deref()) {
         Ok(_) => RpcResponse::ok(serde_json::json!({"updated": true})),
@@ -2119,8 +2944,74 @@ async fn dispatch_db_update_position(args: Value) -> RpcResponse {
     }
 }
 
+async fn dispatch_get_trade_statistics(args: Value) -> RpcResponse {
+    let portfolio_id = match get_arg(&args, &["portfolioId"]).and_then(|v| v.as_str()) {
+        Some(s) => s.to_string(),
+        None => return RpcResponse::err("Missing 'portfolioId' parameter"),
+    };
+    let from = get_arg(&args, &["from"]).and_then(|v| v.as_str()).map(String::from);
+    let to = get_arg(&args, &["to"]).and_then(|v| v.as_str()).map(String::from);
+    match crate::commands::database::get_trade_statistics(portfolio_id, from, to).await {
+        Ok(stats) => RpcResponse::ok(stats),
+        Err(e) => RpcResponse::err(e),
+    }
+}
+
+async fn dispatch_recompute_unrealized_pnl(args: Value) -> RpcResponse {
+    let portfolio_id = match get_arg(&args, &["portfolioId"]).and_then(|v| v.as_str()) {
+        Some(s) => s.to_string(),
+        None => return RpcResponse::err("Missing 'portfolioId' parameter"),
+    };
+    let prices: HashMap<String, f64> = match get_arg(&args, &["prices"]) {
+        Some(value) => match serde_json::from_value(value.clone()) {
+            Ok(v) => v,
+            Err(e) => return RpcResponse::err(format!("Invalid 'prices' parameter: {}", e)),
+        },
+        None => return RpcResponse::err("Missing 'prices' parameter"),
+    };
+    match crate::commands::database::recompute_unrealized_pnl(portfolio_id, prices).await {
+        Ok(update) => RpcResponse::ok(update),
+        Err(e) => RpcResponse::err(e),
+    }
+}
+
+async fn dispatch_ingest_marks(args: Value) -> RpcResponse {
+    let marks: HashMap<String, crate::database::paper_trading::PriceMark> = match get_arg(&args, &["marks"]) {
+        Some(value) => match serde_json::from_value(value.clone()) {
+            Ok(v) => v,
+            Err(e) => return RpcResponse::err(format!("Invalid 'marks' parameter: {}", e)),
+        },
+        None => return RpcResponse::err("Missing 'marks' parameter"),
+    };
+    match crate::commands::database::ingest_marks(marks).await {
+        Ok(ingestion) => RpcResponse::ok(ingestion),
+        Err(e) => RpcResponse::err(e),
+    }
+}
+
+async fn dispatch_db_reduce_position(args: Value) -> RpcResponse {
+    let id = match get_arg(&args, &["id"]).and_then(|v| v.as_str()) {
+        Some(s) => s.to_string(),
+        None => return RpcResponse::err("Missing 'id' parameter"),
+    };
+    let close_qty = match get_arg(&args, &["closeQty"]).and_then(|v| v.as_f64()) {
+        Some(q) => q,
+        None => return RpcResponse::err("Missing 'closeQty' parameter"),
+    };
+    let close_price = match get_arg(&args, &["closePrice"]).and_then(|v| v.as_f64()) {
+        Some(p) => p,
+        None => return RpcResponse::err("Missing 'closePrice' parameter"),
+    };
+    let fee = get_arg(&args, &["fee"]).and_then(|v| v.as_f64()).unwrap_or(0.0);
+
+    match crate::database::paper_trading::reduce_position(&id, close_qty, close_price, fee) {
+        Ok(reduction) => RpcResponse::ok(reduction),
+        Err(e) => RpcResponse::err(e.to_string()),
+    }
+}
+
 async fn dispatch_db_delete_position(args: Value) -> RpcResponse {
-    let id = match args.get("id").and_then(|v| v.as_str()) {
+    let id = match get_arg(&args, &["id"]).and_then(|v| v.as_str()) {
         Some(s) => s.to_string(),
         None => return RpcResponse::err("Missing 'id' parameter"),
     };
@@ -2130,39 +3021,234 @@ async fn dispatch_db_delete_position(args: Value) -> RpcResponse {
     }
 }
 
-async fn dispatch_db_create_order(args: Value) -> RpcResponse {
-     let id = args.get("id").and_then(|v| v.as_str()).map(|s| s.to_string()).unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
-    let portfolio_id = match args.get("portfolioId").or(args.get("portfolio_id")).and_then(|v| v.as_str()) {
-        Some(s) => s.to_string(),
+fn dispatch_get_consolidated_positions(args: Value) -> RpcResponse {
+    let owner = get_arg(&args, &["owner"])
+        .and_then(|v| v.as_str())
+        .filter(|o| !o.is_empty())
+        .map(String::from);
+
+    match crate::commands::paper_consolidation::load_consolidated_positions(owner) {
+        Ok(positions) => RpcResponse::ok(positions),
+        Err(e) => RpcResponse::err(e),
+    }
+}
+
+async fn dispatch_preview_order(ws_state: &crate::WebSocketState, args: Value) -> RpcResponse {
+    use crate::commands::paper_trading::{compute_order_preview, FeeSchedule, OrderRequest, DEFAULT_MARKET_SLIPPAGE};
+
+    let portfolio_id = match get_arg(&args, &["portfolioId"]).and_then(|v| v.as_str()) {
+        Some(id) => id.to_string(),
         None => return RpcResponse::err("Missing 'portfolioId' parameter"),
     };
-    let symbol = match args.get("symbol").and_then(|v| v.as_str()) {
+    let symbol = match get_arg(&args, &["symbol"]).and_then(|v| v.as_str()) {
         Some(s) => s.to_string(),
         None => return RpcResponse::err("Missing 'symbol' parameter"),
     };
-    let side = match args.get("side").and_then(|v| v.as_str()) {
+    let side = match get_arg(&args, &["side"]).and_then(|v| v.as_str()) {
         Some(s) => s.to_string(),
         None => return RpcResponse::err("Missing 'side' parameter"),
     };
-    let order_type = match args.get("orderType").or(args.get("order_type")).or(args.get("type")).and_then(|v| v.as_str()) {
-        Some(s) => s.to_string(),
+    let order_type = match get_arg(&args, &["orderType"]).and_then(|v| v.as_str()) {
+        Some(t) => t.to_string(),
         None => return RpcResponse::err("Missing 'orderType' parameter"),
     };
-    let quantity = match args.get("quantity").and_then(|v| v.as_f64()) {
-        Some(f) => f,
+    let quantity = match get_arg(&args, &["quantity"]).and_then(|v| v.as_f64()) {
+        Some(q) => q,
         None => return RpcResponse::err("Missing 'quantity' parameter"),
     };
-    let price = args.get("price").and_then(|v| v.as_f64());
-    let time_in_force = args.get("timeInForce").or(args.get("time_in_force")).and_then(|v| v.as_str()).unwrap_or("GTC").to_string();
 
+    let portfolio = match crate::database::paper_trading::get_portfolio(&portfolio_id) {
+        Ok(p) => p,
+        Err(e) => return RpcResponse::err(e.to_string()),
+    };
+    let positions = match crate::database::paper_trading::get_portfolio_positions(&portfolio_id, Some("open")) {
+        Ok(p) => p,
+        Err(e) => return RpcResponse::err(e.to_string()),
+    };
+
+    let market_price = match get_arg(&args, &["marketPrice"]).and_then(|v| v.as_f64()) {
+        Some(p) => Some(p),
+        None => ws_state.services.read().await.paper_trading.get_price(&portfolio.provider, &symbol),
+    };
+    let fees: FeeSchedule = get_arg(&args, &["fees"])
+        .and_then(|v| serde_json::from_value(v.clone()).ok())
+        .unwrap_or_default();
+    let slippage = get_arg(&args, &["slippage"]).and_then(|v| v.as_f64()).unwrap_or(DEFAULT_MARKET_SLIPPAGE);
+
+    let order = OrderRequest {
+        side,
+        order_type,
+        quantity,
+        price: get_arg(&args, &["price"]).and_then(|v| v.as_f64()),
+        leverage: get_arg(&args, &["leverage"]).and_then(|v| v.as_f64()),
+        reduce_only: get_arg(&args, &["reduceOnly"]).and_then(|v| v.as_bool()).unwrap_or(false),
+    };
+
+    match compute_order_preview(&portfolio, &positions, &symbol, &order, market_price, &fees, slippage) {
+        Ok(preview) => RpcResponse::ok(preview),
+        Err(e) => RpcResponse::err(e),
+    }
+}
+
+async fn dispatch_db_create_order(args: Value) -> RpcResponse {
+    let mut v = ArgValidator::new(&args);
+    let id = v.id_or_generated();
+    let portfolio_id = v.required_str(&["portfolioId"]);
+    let symbol = v.required_str(&["symbol"]);
+    let side = v.required_one_of(&["side"], &["buy", "sell"]);
+    let order_type = v.required_one_of(
+        &["orderType", "order_type", "type"],
+        &["market", "limit", "stop_market", "stop_limit"],
+    );
+    let quantity = v.required_positive(&["quantity"]);
+    if let Err(errors) = v.finish() {
+        return RpcResponse::invalid(errors);
+    }
+    let price = get_arg(&args, &["price"]).and_then(|v| v.as_f64());
+    let time_in_force = get_arg(&args, &["timeInForce"]).and_then(|v| v.as_str()).unwrap_or("GTC").to_string();
+
+    let (quantity, price) = match normalize_order(&symbol, quantity, price) {
+        Ok(rounded) => rounded,
+        Err(e) => return RpcResponse::err(e),
+    };
+    let check = RiskCheck { symbol: &symbol, side: &side, quantity, price };
+    if let Err(e) = enforce_risk_limits(&portfolio_id, &check) {
+        return RpcResponse::err(e);
+    }
     match crate::database::paper_trading::create_order(&id, &portfolio_id, &symbol, &side, &order_type, quantity, price, &time_in_force) {
         Ok(_) => RpcResponse::ok(serde_json::json!({"created": true})),
         Err(e) => RpcResponse::err(e.to_string()),
     }
 }
 
+async fn dispatch_create_bracket_order(args: Value) -> RpcResponse {
+    let mut v = ArgValidator::new(&args);
+    let portfolio_id = v.required_str(&["portfolioId"]);
+    let symbol = v.required_str(&["symbol"]);
+    let side = v.required_one_of(&["side"], &["buy", "sell"]);
+    let order_type = v.required_one_of(&["orderType", "order_type", "type"], &["market", "limit"]);
+    let quantity = v.required_positive(&["quantity"]);
+    let stop_loss = v.required_positive(&["stopLoss"]);
+    let take_profit = v.required_positive(&["takeProfit"]);
+    if let Err(errors) = v.finish() {
+        return RpcResponse::invalid(errors);
+    }
+    let price = get_arg(&args, &["price"]).and_then(|v| v.as_f64());
+    let time_in_force = get_arg(&args, &["timeInForce"]).and_then(|v| v.as_str()).map(|s| s.to_string());
+
+    match crate::commands::database::create_bracket_order(
+        portfolio_id,
+        symbol,
+        side,
+        order_type,
+        quantity,
+        price,
+        stop_loss,
+        take_profit,
+        time_in_force,
+    )
+    .await
+    {
+        Ok(bracket) => RpcResponse::ok(bracket),
+        Err(e) => RpcResponse::err(e),
+    }
+}
+
+async fn dispatch_open_market_position(args: Value) -> RpcResponse {
+    let mut v = ArgValidator::new(&args);
+    let portfolio_id = v.required_str(&["portfolioId"]);
+    let symbol = v.required_str(&["symbol"]);
+    let side = v.required_one_of(&["side"], &["long", "short", "buy", "sell"]);
+    let quantity = v.required_positive(&["quantity"]);
+    let mark_price = v.required_positive(&["markPrice"]);
+    let fees = v.optional_json(&["fees"]);
+    if let Err(errors) = v.finish() {
+        return RpcResponse::invalid(errors);
+    }
+    let leverage = get_arg(&args, &["leverage"]).and_then(|v| v.as_f64());
+    let margin_mode = get_arg(&args, &["marginMode"]).and_then(|v| v.as_str()).map(|s| s.to_string());
+
+    match crate::commands::database::open_market_position(
+        portfolio_id,
+        symbol,
+        side,
+        quantity,
+        mark_price,
+        leverage,
+        margin_mode,
+        fees,
+    )
+    .await
+    {
+        Ok(opened) => RpcResponse::ok(opened),
+        Err(e) => RpcResponse::err(e),
+    }
+}
+
+fn dispatch_get_paper_risk_limits(args: Value) -> RpcResponse {
+    let portfolio_id = match get_arg(&args, &["portfolioId"]).and_then(|v| v.as_str()) {
+        Some(s) => s,
+        None => return RpcResponse::err("Missing 'portfolioId' parameter"),
+    };
+    match crate::database::paper_trading::get_risk_limits(portfolio_id) {
+        Ok(limits) => RpcResponse::ok(limits),
+        Err(e) => RpcResponse::err(e.to_string()),
+    }
+}
+
+fn dispatch_set_paper_risk_limits(args: Value) -> RpcResponse {
+    let portfolio_id = match get_arg(&args, &["portfolioId"]).and_then(|v| v.as_str()) {
+        Some(s) => s.to_string(),
+        None => return RpcResponse::err("Missing 'portfolioId' parameter"),
+    };
+    let number = |name: &str| get_arg(&args, &[name]).and_then(|v| v.as_f64());
+    let limits = crate::database::paper_trading::RiskLimits {
+        portfolio_id,
+        max_position_value: number("maxPositionValue"),
+        max_leverage: number("maxLeverage"),
+        max_open_positions: get_arg(&args, &["maxOpenPositions"]).and_then(|v| v.as_i64()),
+        max_daily_loss: number("maxDailyLoss"),
+        updated_at: None,
+    };
+    match crate::commands::paper_risk::update_risk_limits(limits) {
+        Ok(limits) => RpcResponse::ok(limits),
+        Err(e) => RpcResponse::err(e),
+    }
+}
+
+fn dispatch_get_paper_symbol_spec(args: Value) -> RpcResponse {
+    let symbol = match get_arg(&args, &["symbol"]).and_then(|v| v.as_str()) {
+        Some(s) => s,
+        None => return RpcResponse::err("Missing 'symbol' parameter"),
+    };
+    match crate::database::paper_trading::get_symbol_spec(symbol) {
+        Ok(spec) => RpcResponse::ok(spec),
+        Err(e) => RpcResponse::err(e.to_string()),
+    }
+}
+
+fn dispatch_set_paper_symbol_spec(args: Value) -> RpcResponse {
+    let symbol = match get_arg(&args, &["symbol"]).and_then(|v| v.as_str()) {
+        Some(s) => s.to_string(),
+        None => return RpcResponse::err("Missing 'symbol' parameter"),
+    };
+    let number = |name: &str| get_arg(&args, &[name]).and_then(|v| v.as_f64());
+    let spec = crate::database::paper_trading::SymbolSpec {
+        symbol,
+        tick_size: number("tickSize"),
+        lot_size: number("lotSize"),
+        min_quantity: number("minQuantity"),
+        min_notional: number("minNotional"),
+        updated_at: None,
+    };
+    match crate::commands::paper_precision::update_symbol_spec(spec) {
+        Ok(spec) => RpcResponse::ok(spec),
+        Err(e) => RpcResponse::err(e),
+    }
+}
+
 async fn dispatch_db_get_order(args: Value) -> RpcResponse {
-    let id = match args.get("id").and_then(|v| v.as_str()) {
+    let id = match get_arg(&args, &["id"]).and_then(|v| v.as_str()) {
         Some(s) => s.to_string(),
         None => return RpcResponse::err("Missing 'id' parameter"),
     };
@@ -2173,11 +3259,11 @@ async fn dispatch_db_get_order(args: Value) -> RpcResponse {
 }
 
 async fn dispatch_db_get_portfolio_orders(args: Value) -> RpcResponse {
-     let portfolio_id = match args.get("portfolioId").or(args.get("portfolio_id")).and_then(|v| v.as_str()) {
+     let portfolio_id = match get_arg(&args, &["portfolioId"]).and_then(|v| v.as_str()) {
         Some(s) => s.to_string(),
         None => return RpcResponse::err("Missing 'portfolioId' parameter"),
     };
-    let status = args.get("status").and_then(|v| v.as_str()).map(|s| s.to_string());
+    let status = get_arg(&args, &["status"]).and_then(|v| v.as_str()).map(|s| s.to_string());
 
     match crate::database::paper_trading::get_portfolio_orders(&portfolio_id, status.as_deref()) {
         Ok(orders) => RpcResponse::ok(orders),
@@ -2186,7 +3272,7 @@ async fn dispatch_db_get_portfolio_orders(args: Value) -> RpcResponse {
 }
 
 async fn dispatch_db_get_pending_orders(args: Value) -> RpcResponse {
-    let portfolio_id = args.get("portfolioId").or(args.get("portfolio_id")).and_then(|v| v.as_str()).map(|s| s.to_string());
+    let portfolio_id = get_arg(&args, &["portfolioId"]).and_then(|v| v.as_str()).map(|s| s.to_string());
     match crate::database::paper_trading::get_pending_orders(portfolio_id.as_deref()) {
         Ok(orders) => RpcResponse::ok(orders),
         Err(e) => RpcResponse::err(e.to_string()),
@@ -2194,14 +3280,14 @@ async fn dispatch_db_get_pending_orders(args: Value) -> RpcResponse {
 }
 
 async fn dispatch_db_update_order(args: Value) -> RpcResponse {
-    let id = match args.get("id").and_then(|v| v.as_str()) {
+    let id = match get_arg(&args, &["id"]).and_then(|v| v.as_str()) {
         Some(s) => s.to_string(),
         None => return RpcResponse::err("Missing 'id' parameter"),
     };
-    let filled_quantity = args.get("filledQuantity").or(args.get("filled_quantity")).and_then(|v| v.as_f64());
-    let avg_fill_price = args.get("avgFillPrice").or(args.get("avg_fill_price")).and_then(|v| v.as_f64());
-    let status = args.get("status").and_then(|v| v.as_str()).map(|s| s.to_string());
-    let filled_at = args.get("filledAt").or(args.get("filled_at")).and_then(|v| v.as_str()).map(|s| s.to_string());
+    let filled_quantity = get_arg(&args, &["filledQuantity"]).and_then(|v| v.as_f64());
+    let avg_fill_price = get_arg(&args, &["avgFillPrice"]).and_then(|v| v.as_f64());
+    let status = get_arg(&args, &["status"]).and_then(|v| v.as_str()).map(|s| s.to_string());
+    let filled_at = get_arg(&args, &["filledAt"]).and_then(|v| v.as_str()).map(|s| s.to_string());
 
     match crate::database::paper_trading::update_order(&id, filled_quantity, avg_fill_price, status.as_deref(), filled_at.as_deref()) {
         Ok(_) => RpcResponse::ok(serde_json::json!({"updated": true})),
@@ -2210,7 +3296,7 @@ async fn dispatch_db_update_order(args: Value) -> RpcResponse {
 }
 
 async fn dispatch_db_delete_order(args: Value) -> RpcResponse {
-    let id = match args.get("id").and_then(|v| v.as_str()) {
+    let id = match get_arg(&args, &["id"]).and_then(|v| v.as_str()) {
         Some(s) => s.to_string(),
         None => return RpcResponse::err("Missing 'id' parameter"),
     };
@@ -2220,48 +3306,59 @@ async fn dispatch_db_delete_order(args: Value) -> RpcResponse {
     }
 }
 
+async fn dispatch_db_cancel_all_orders(args: Value) -> RpcResponse {
+    let mut v = ArgValidator::new(&args);
+    let portfolio_id = v.required_str(&["portfolioId"]);
+    if let Err(errors) = v.finish() {
+        return RpcResponse::invalid(errors);
+    }
+    let symbol = get_arg(&args, &["symbol"]).and_then(|v| v.as_str()).map(|s| s.to_string());
+
+    match crate::database::paper_trading::cancel_all_orders(&portfolio_id, symbol.as_deref()) {
+        Ok(cancelled) => RpcResponse::ok(cancelled),
+        Err(e) => RpcResponse::err(e.to_string()),
+    }
+}
+
 async fn dispatch_db_create_trade(args: Value) -> RpcResponse {
-    let id = args.get("id").and_then(|v| v.as_str()).map(|s| s.to_string()).unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
-    let portfolio_id = match args.get("portfolioId").or(args.get("portfolio_id")).and_then(|v| v.as_str()) {
-        Some(s) => s.to_string(),
-        None => return RpcResponse::err("Missing 'portfolioId' parameter"),
-    };
-    let order_id = match args.get("orderId").or(args.get("order_id")).and_then(|v| v.as_str()) {
-        Some(s) => s.to_string(),
-        None => return RpcResponse::err("Missing 'orderId' parameter"),
-    };
-    let symbol = match args.get("symbol").and_then(|v| v.as_str()) {
-        Some(s) => s.to_string(),
-        None => return RpcResponse::err("Missing 'symbol' parameter"),
-    };
-    let side = match args.get("side").and_then(|v| v.as_str()) {
-        Some(s) => s.to_string(),
-        None => return RpcResponse::err("Missing 'side' parameter"),
-    };
-    let price = match args.get("price").and_then(|v| v.as_f64()) {
-        Some(f) => f,
-        None => return RpcResponse::err("Missing 'price' parameter"),
-    };
-    let quantity = match args.get("quantity").and_then(|v| v.as_f64()) {
-        Some(f) => f,
-        None => return RpcResponse::err("Missing 'quantity' parameter"),
+    let mut v = ArgValidator::new(&args);
+    let id = v.id_or_generated();
+    let portfolio_id = v.required_str(&["portfolioId"]);
+    let order_id = v.required_str(&["orderId"]);
+    let symbol = v.required_str(&["symbol"]);
+    let side = v.required_str(&["side"]);
+    let price = v.required_positive(&["price"]);
+    let quantity = v.required_positive(&["quantity"]);
+    if let Err(errors) = v.finish() {
+        return RpcResponse::invalid(errors);
+    }
+    let fee = get_arg(&args, &["fee"]).and_then(|v| v.as_f64()).unwrap_or(0.0);
+    let fee_rate = get_arg(&args, &["feeRate"]).and_then(|v| v.as_f64()).unwrap_or(0.0);
+    let is_maker = get_arg(&args, &["isMaker"]).and_then(|v| v.as_bool()).unwrap_or(false);
+
+    let (quantity, price) = match normalize_fill(&symbol, quantity, price) {
+        Ok(rounded) => rounded,
+        Err(e) => return RpcResponse::err(e),
     };
-    let fee = args.get("fee").and_then(|v| v.as_f64()).unwrap_or(0.0);
-    let fee_rate = args.get("feeRate").or(args.get("fee_rate")).and_then(|v| v.as_f64()).unwrap_or(0.0);
-    let is_maker = args.get("isMaker").or(args.get("is_maker")).and_then(|v| v.as_bool()).unwrap_or(false);
-
-    match crate::database::paper_trading::create_trade(&id, &portfolio_id, &order_id, &symbol, &side, price, quantity, fee, fee_rate, is_maker) {
-        Ok(_) => RpcResponse::ok(serde_json::json!({"created": true})),
+    let fee = fill_fee(quantity, price, fee, fee_rate);
+    let check = RiskCheck { symbol: &symbol, side: &side, quantity, price: Some(price) };
+    if let Err(e) = enforce_risk_limits(&portfolio_id, &check) {
+        return RpcResponse::err(e);
+    }
+    match crate::database::paper_trading::create_trade(&id, &portfolio_id, &order_id, &symbol, &side, price, quantity, fee, fee_rate, is_maker)
+        .and_then(|_| crate::database::paper_trading::get_trade(&id))
+    {
+        Ok(trade) => RpcResponse::ok(trade),
         Err(e) => RpcResponse::err(e.to_string()),
     }
 }
 
 async fn dispatch_db_get_portfolio_trades(args: Value) -> RpcResponse {
-    let portfolio_id = match args.get("portfolioId").or(args.get("portfolio_id")).and_then(|v| v.as_str()) {
+    let portfolio_id = match get_arg(&args, &["portfolioId"]).and_then(|v| v.as_str()) {
         Some(s) => s.to_string(),
         None => return RpcResponse::err("Missing 'portfolioId' parameter"),
     };
-    let limit = args.get("limit").and_then(|v| v.as_i64());
+    let limit = get_arg(&args, &["limit"]).and_then(|v| v.as_i64());
 
     match crate::database::paper_trading::get_portfolio_trades(&portfolio_id, limit) {
         Ok(trades) => RpcResponse::ok(trades),
@@ -2270,7 +3367,7 @@ async fn dispatch_db_get_portfolio_trades(args: Value) -> RpcResponse {
 }
 
 async fn dispatch_db_get_trade(args: Value) -> RpcResponse {
-    let id = match args.get("id").and_then(|v| v.as_str()) {
+    let id = match get_arg(&args, &["id"]).and_then(|v| v.as_str()) {
         Some(s) => s.to_string(),
         None => return RpcResponse::err("Missing 'id' parameter"),
     };
@@ -2281,7 +3378,7 @@ async fn dispatch_db_get_trade(args: Value) -> RpcResponse {
 }
 
 async fn dispatch_db_get_order_trades(args: Value) -> RpcResponse {
-    let order_id = match args.get("orderId").or(args.get("order_id")).and_then(|v| v.as_str()) {
+    let order_id = match get_arg(&args, &["orderId"]).and_then(|v| v.as_str()) {
         Some(s) => s.to_string(),
         None => return RpcResponse::err("Missing 'orderId' parameter"),
     };
@@ -2292,7 +3389,7 @@ async fn dispatch_db_get_order_trades(args: Value) -> RpcResponse {
 }
 
 async fn dispatch_db_delete_trade(args: Value) -> RpcResponse {
-    let id = match args.get("id").and_then(|v| v.as_str()) {
+    let id = match get_arg(&args, &["id"]).and_then(|v| v.as_str()) {
         Some(s) => s.to_string(),
         None => return RpcResponse::err("Missing 'id' parameter"),
     };
@@ -2314,7 +3411,7 @@ async fn dispatch_monitor_add_condition(
 ) -> RpcResponse {
     use rusqlite::params;
 
-    let condition_value = args.get("condition").cloned().unwrap_or(args);
+    let condition_value = get_arg(&args, &["condition"]).cloned().unwrap_or(args);
     let condition: crate::websocket::services::monitoring::MonitorCondition =
         match serde_json::from_value(condition_value) {
             Ok(condition) => condition,
@@ -2331,8 +3428,8 @@ async fn dispatch_monitor_add_condition(
     };
 
     if let Err(e) = conn.execute(
-        "INSERT INTO monitor_conditions (provider, symbol, field, operator, value, value2, enabled)
-         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        "INSERT INTO monitor_conditions (provider, symbol, field, operator, value, value2, enabled, notify)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
         params![
             &condition.provider,
             &condition.symbol,
@@ -2341,6 +3438,7 @@ async fn dispatch_monitor_add_condition(
             condition.value,
             condition.value2,
             if condition.enabled { 1 } else { 0 },
+            if condition.notify { 1 } else { 0 },
         ],
     ) {
         return RpcResponse::err(e.to_string());
@@ -2360,6 +3458,18 @@ async fn dispatch_monitor_add_condition(
     RpcResponse::ok(id)
 }
 
+async fn dispatch_test_monitor_condition(state: &crate::WebSocketState, args: Value) -> RpcResponse {
+    let condition_value = get_arg(&args, &["condition"]).cloned().unwrap_or_else(|| args.clone());
+    let condition: crate::websocket::services::monitoring::MonitorCondition =
+        match serde_json::from_value(condition_value) {
+            Ok(condition) => condition,
+            Err(e) => return RpcResponse::err(format!("Invalid 'condition' parameter: {}", e)),
+        };
+    let last_n = get_arg(&args, &["lastN"]).and_then(|v| v.as_u64()).map(|v| v as usize);
+
+    RpcResponse::ok(state.test_monitor_condition(&condition, last_n).await)
+}
+
 async fn dispatch_monitor_get_conditions() -> RpcResponse {
     let pool = match crate::database::pool::get_pool() {
         Ok(pool) => pool,
@@ -2371,9 +3481,10 @@ async fn dispatch_monitor_get_conditions() -> RpcResponse {
     };
 
     let mut stmt = match conn.prepare(
-        "SELECT id, provider, symbol, field, operator, value, value2, enabled
+        "SELECT id, provider, symbol, field, operator, value, value2, enabled, notify
          FROM monitor_conditions
-         ORDER BY created_at DESC",
+         WHERE group_id IS NULL
+         ORDER BY created_at DESC, id DESC",
     ) {
         Ok(stmt) => stmt,
         Err(e) => return RpcResponse::err(e.to_string()),
@@ -2415,6 +3526,7 @@ async fn dispatch_monitor_get_conditions() -> RpcResponse {
             value: row.get(5)?,
             value2: row.get(6)?,
             enabled: row.get::<_, i32>(7)? == 1,
+            notify: row.get::<_, Option<i32>>(8)?.unwrap_or(0) == 1,
         })
     }) {
         Ok(rows) => rows.collect::<Result<Vec<_>, _>>(),
@@ -2433,7 +3545,7 @@ async fn dispatch_monitor_delete_condition(
 ) -> RpcResponse {
     use rusqlite::params;
 
-    let id = match args.get("id").and_then(|v| v.as_i64()) {
+    let id = match get_arg(&args, &["id"]).and_then(|v| v.as_i64()) {
         Some(id) => id,
         None => return RpcResponse::err("Missing 'id' parameter"),
     };
@@ -2460,10 +3572,108 @@ async fn dispatch_monitor_delete_condition(
 }
 
 async fn dispatch_monitor_get_alerts(args: Value) -> RpcResponse {
-    use rusqlite::params;
+    let i64_arg = |key: &str| get_arg(&args, &[key]).and_then(|v| v.as_i64());
+    let query = crate::websocket::services::monitoring::AlertQuery {
+        limit: i64_arg("limit"),
+        offset: i64_arg("offset"),
+        acknowledged: get_optional_bool(&args, "acknowledged"),
+        provider: get_optional_string(&args, "provider"),
+        symbol: get_optional_string(&args, "symbol"),
+        field: get_optional_string(&args, "field"),
+        since: i64_arg("since"),
+        until: i64_arg("until"),
+    };
+
+    let pool = match crate::database::pool::get_pool() {
+        Ok(pool) => pool,
+        Err(e) => return RpcResponse::err(e.to_string()),
+    };
+    let conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(e) => return RpcResponse::err(e.to_string()),
+    };
+
+    match crate::websocket::services::monitoring::query_alerts(&conn, &query) {
+        Ok(page) => RpcResponse::ok(page),
+        Err(e) => RpcResponse::err(e.to_string()),
+    }
+}
+
+fn dispatch_monitor_acknowledge_alert(args: Value) -> RpcResponse {
+    let id = match get_arg(&args, &["id", "alertId"]).and_then(|v| v.as_i64()) {
+        Some(id) => id,
+        None => return RpcResponse::err("Missing 'id' parameter"),
+    };
 
-    let limit = args.get("limit").and_then(|v| v.as_i64()).unwrap_or(50);
+    let pool = match crate::database::pool::get_pool() {
+        Ok(pool) => pool,
+        Err(e) => return RpcResponse::err(e.to_string()),
+    };
+    let conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(e) => return RpcResponse::err(e.to_string()),
+    };
+
+    match crate::websocket::services::monitoring::acknowledge_alerts(&conn, Some(id)) {
+        Ok(changed) => RpcResponse::ok(changed > 0),
+        Err(e) => RpcResponse::err(e.to_string()),
+    }
+}
+
+fn dispatch_monitor_acknowledge_all_alerts() -> RpcResponse {
+    let pool = match crate::database::pool::get_pool() {
+        Ok(pool) => pool,
+        Err(e) => return RpcResponse::err(e.to_string()),
+    };
+    let conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(e) => return RpcResponse::err(e.to_string()),
+    };
+
+    match crate::websocket::services::monitoring::acknowledge_alerts(&conn, None) {
+        Ok(changed) => RpcResponse::ok(changed),
+        Err(e) => RpcResponse::err(e.to_string()),
+    }
+}
+
+async fn dispatch_monitor_add_condition_group(
+    state: &crate::WebSocketState,
+    args: Value,
+) -> RpcResponse {
+    let group_value = get_arg(&args, &["group"]).cloned().unwrap_or(args);
+    let group: crate::websocket::services::monitoring::MonitorConditionGroup =
+        match serde_json::from_value(group_value) {
+            Ok(group) => group,
+            Err(e) => return RpcResponse::err(format!("Invalid 'group' parameter: {}", e)),
+        };
+
+    let pool = match crate::database::pool::get_pool() {
+        Ok(pool) => pool,
+        Err(e) => return RpcResponse::err(e.to_string()),
+    };
+    let conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(e) => return RpcResponse::err(e.to_string()),
+    };
+
+    let id = match crate::websocket::services::monitoring::insert_condition_group(&conn, &group) {
+        Ok(id) => id,
+        Err(e) => return RpcResponse::err(e.to_string()),
+    };
+
+    let services = state.services.read().await;
+    if let Err(e) = services.monitoring.load_conditions().await {
+        return RpcResponse::err(format!(
+            "Failed to reload monitor conditions after inserting group with id {}: {}",
+            id,
+            e
+        ));
+    }
+
+    RpcResponse::ok(id)
+}
 
+async fn dispatch_monitor_get_condition_groups() -> RpcResponse {
     let pool = match crate::database::pool::get_pool() {
         Ok(pool) => pool,
         Err(e) => return RpcResponse::err(e.to_string()),
@@ -2473,48 +3683,41 @@ async fn dispatch_monitor_get_alerts(args: Value) -> RpcResponse {
         Err(e) => return RpcResponse::err(e.to_string()),
     };
 
-    let mut stmt = match conn.prepare(
-        "SELECT id, condition_id, provider, symbol, field, triggered_value, triggered_at
-         FROM monitor_alerts
-         ORDER BY triggered_at DESC
-         LIMIT ?1",
-    ) {
-        Ok(stmt) => stmt,
-        Err(e) => return RpcResponse::err(e.to_string()),
+    match crate::websocket::services::monitoring::load_condition_groups(&conn, false) {
+        Ok(groups) => RpcResponse::ok(groups),
+        Err(e) => RpcResponse::err(e.to_string()),
+    }
+}
+
+async fn dispatch_monitor_delete_condition_group(
+    state: &crate::WebSocketState,
+    args: Value,
+) -> RpcResponse {
+    let id = match get_arg(&args, &["id"]).and_then(|v| v.as_i64()) {
+        Some(id) => id,
+        None => return RpcResponse::err("Missing 'id' parameter"),
     };
 
-    let alerts = match stmt.query_map(params![limit], |row| {
-        let field_str: String = row.get(4)?;
-        let field = crate::websocket::services::monitoring::MonitorField::from_str(&field_str)
-            .ok_or_else(|| {
-                rusqlite::Error::FromSqlConversionFailure(
-                    4,
-                    rusqlite::types::Type::Text,
-                    Box::new(std::io::Error::new(
-                        std::io::ErrorKind::InvalidData,
-                        format!("Invalid field value: {}", field_str)
-                    )),
-                )
-            })?;
+    let pool = match crate::database::pool::get_pool() {
+        Ok(pool) => pool,
+        Err(e) => return RpcResponse::err(e.to_string()),
+    };
+    let conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(e) => return RpcResponse::err(e.to_string()),
+    };
 
-        Ok(crate::websocket::services::monitoring::MonitorAlert {
-            id: Some(row.get(0)?),
-            condition_id: row.get(1)?,
-            provider: row.get(2)?,
-            symbol: row.get(3)?,
-            field,
-            triggered_value: row.get(5)?,
-            triggered_at: row.get::<_, i64>(6)? as u64,
-        })
-    }) {
-        Ok(rows) => rows.collect::<Result<Vec<_>, _>>(),
+    let deleted = match crate::websocket::services::monitoring::delete_condition_group(&conn, id) {
+        Ok(deleted) => deleted,
         Err(e) => return RpcResponse::err(e.to_string()),
     };
 
-    match alerts {
-        Ok(alerts) => RpcResponse::ok(alerts),
-        Err(e) => RpcResponse::err(e.to_string()),
+    let services = state.services.read().await;
+    if let Err(e) = services.monitoring.load_conditions().await {
+        return RpcResponse::err(e.to_string());
     }
+
+    RpcResponse::ok(deleted)
 }
 
 async fn dispatch_monitor_load_conditions(state: &crate::WebSocketState) -> RpcResponse {
@@ -2525,22 +3728,51 @@ async fn dispatch_monitor_load_conditions(state: &crate::WebSocketState) -> RpcR
     }
 }
 
+async fn dispatch_monitor_set_notifications_enabled(
+    state: &crate::WebSocketState,
+    args: Value,
+) -> RpcResponse {
+    use crate::websocket::services::monitoring::NOTIFICATIONS_SETTING_KEY;
+
+    let enabled = match get_arg(&args, &["enabled"]).and_then(|v| v.as_bool()) {
+        Some(enabled) => enabled,
+        None => return RpcResponse::err("Missing 'enabled' parameter"),
+    };
+
+    if let Err(e) = crate::database::operations::save_setting(
+        NOTIFICATIONS_SETTING_KEY,
+        if enabled { "true" } else { "false" },
+        Some("monitoring"),
+    ) {
+        return RpcResponse::err(e.to_string());
+    }
+
+    state.services.read().await.monitoring.set_notifications_enabled(enabled);
+    RpcResponse::ok(serde_json::json!({"enabled": enabled}))
+}
+
 // WEBSOCKET DISPATCH FUNCTIONS
 
 async fn dispatch_ws_set_config(state: &crate::WebSocketState, args: Value) -> RpcResponse {
-    let config_value = args.get("config").cloned().unwrap_or(args);
+    let config_value = get_arg(&args, &["config"]).cloned().unwrap_or(args);
     let config: crate::websocket::types::ProviderConfig = match serde_json::from_value(config_value) {
         Ok(config) => config,
         Err(e) => return RpcResponse::err(format!("Invalid config: {}", e)),
     };
 
     let manager = state.manager.read().await;
-    manager.set_config(config);
-    RpcResponse::ok(serde_json::json!({"saved": true}))
+    if let Err(e) = manager.set_config(config.clone()) {
+        return RpcResponse::err(e.to_string());
+    }
+    state.router.read().await.set_ticker_throttle(&config.name, config.ticker_throttle_ms);
+    match crate::database::operations::save_ws_provider_config(&config.to_db_config()) {
+        Ok(_) => RpcResponse::ok(serde_json::json!({"saved": true})),
+        Err(e) => RpcResponse::err(format!("Config applied but not saved: {}", e)),
+    }
 }
 
 async fn dispatch_ws_connect(state: &crate::WebSocketState, args: Value) -> RpcResponse {
-    let provider = match args.get("provider").and_then(|v| v.as_str()) {
+    let provider = match get_arg(&args, &["provider"]).and_then(|v| v.as_str()) {
         Some(provider) => provider.to_string(),
         None => return RpcResponse::err("Missing 'provider' parameter"),
     };
@@ -2553,7 +3785,7 @@ async fn dispatch_ws_connect(state: &crate::WebSocketState, args: Value) -> RpcR
 }
 
 async fn dispatch_ws_disconnect(state: &crate::WebSocketState, args: Value) -> RpcResponse {
-    let provider = match args.get("provider").and_then(|v| v.as_str()) {
+    let provider = match get_arg(&args, &["provider"]).and_then(|v| v.as_str()) {
         Some(provider) => provider.to_string(),
         None => return RpcResponse::err("Missing 'provider' parameter"),
     };
@@ -2566,19 +3798,22 @@ async fn dispatch_ws_disconnect(state: &crate::WebSocketState, args: Value) -> R
 }
 
 async fn dispatch_ws_subscribe(state: &crate::WebSocketState, args: Value) -> RpcResponse {
-    let provider = match args.get("provider").and_then(|v| v.as_str()) {
+    let provider = match get_arg(&args, &["provider"]).and_then(|v| v.as_str()) {
         Some(provider) => provider.to_string(),
         None => return RpcResponse::err("Missing 'provider' parameter"),
     };
-    let symbol = match args.get("symbol").and_then(|v| v.as_str()) {
-        Some(symbol) => symbol.to_string(),
+    let symbol = match get_arg(&args, &["symbol"]).and_then(|v| v.as_str()) {
+        Some(symbol) => match Symbol::exchange(symbol) {
+            Ok(symbol) => symbol.into_string(),
+            Err(e) => return RpcResponse::err(e),
+        },
         None => return RpcResponse::err("Missing 'symbol' parameter"),
     };
-    let channel = match args.get("channel").and_then(|v| v.as_str()) {
+    let channel = match get_arg(&args, &["channel"]).and_then(|v| v.as_str()) {
         Some(channel) => channel.to_string(),
         None => return RpcResponse::err("Missing 'channel' parameter"),
     };
-    let params = args.get("params").cloned();
+    let params = get_arg(&args, &["params"]).cloned();
 
     let topic = format!("{}.{}.{}", provider, channel, symbol);
     state.router.write().await.subscribe_frontend(&topic);
@@ -2586,20 +3821,45 @@ async fn dispatch_ws_subscribe(state: &crate::WebSocketState, args: Value) -> Rp
     let manager = state.manager.read().await;
     match manager.subscribe(&provider, &symbol, &channel, params).await {
         Ok(_) => RpcResponse::ok(serde_json::json!({"subscribed": true})),
-        Err(e) => RpcResponse::err(e.to_string()),
+        Err(e) => {
+            // Release the frontend reference taken above
+            state.router.read().await.unsubscribe_frontend(&topic);
+            RpcResponse::err(e.to_string())
+        }
     }
 }
 
+async fn dispatch_ws_subscribe_many(state: &crate::WebSocketState, args: Value) -> RpcResponse {
+    let provider = match get_arg(&args, &["provider"]).and_then(|v| v.as_str()) {
+        Some(provider) => provider.to_string(),
+        None => return RpcResponse::err("Missing 'provider' parameter"),
+    };
+    let symbols: Vec<String> = match get_arg(&args, &["symbols"]).and_then(|v| v.as_array()) {
+        Some(symbols) => symbols.iter().map(|s| s.as_str().unwrap_or_default().to_string()).collect(),
+        None => return RpcResponse::err("Missing 'symbols' parameter"),
+    };
+    let channel = match get_arg(&args, &["channel"]).and_then(|v| v.as_str()) {
+        Some(channel) => channel.to_string(),
+        None => return RpcResponse::err("Missing 'channel' parameter"),
+    };
+    let params = get_arg(&args, &["params"]).cloned();
+
+    RpcResponse::ok(crate::subscribe_many_internal(state, &provider, &symbols, &channel, params).await)
+}
+
 async fn dispatch_ws_unsubscribe(state: &crate::WebSocketState, args: Value) -> RpcResponse {
-    let provider = match args.get("provider").and_then(|v| v.as_str()) {
+    let provider = match get_arg(&args, &["provider"]).and_then(|v| v.as_str()) {
         Some(provider) => provider.to_string(),
         None => return RpcResponse::err("Missing 'provider' parameter"),
     };
-    let symbol = match args.get("symbol").and_then(|v| v.as_str()) {
-        Some(symbol) => symbol.to_string(),
+    let symbol = match get_arg(&args, &["symbol"]).and_then(|v| v.as_str()) {
+        Some(symbol) => match Symbol::exchange(symbol) {
+            Ok(symbol) => symbol.into_string(),
+            Err(e) => return RpcResponse::err(e),
+        },
         None => return RpcResponse::err("Missing 'symbol' parameter"),
     };
-    let channel = match args.get("channel").and_then(|v| v.as_str()) {
+    let channel = match get_arg(&args, &["channel"]).and_then(|v| v.as_str()) {
         Some(channel) => channel.to_string(),
         None => return RpcResponse::err("Missing 'channel' parameter"),
     };
@@ -2613,8 +3873,21 @@ async fn dispatch_ws_unsubscribe(state: &crate::WebSocketState, args: Value) ->
     }
 }
 
+async fn dispatch_get_provider_capabilities(state: &crate::WebSocketState, args: Value) -> RpcResponse {
+    let provider = match get_arg(&args, &["provider"]).and_then(|v| v.as_str()) {
+        Some(provider) => provider.to_string(),
+        None => return RpcResponse::err("Missing 'provider' parameter"),
+    };
+
+    let manager = state.manager.read().await;
+    match manager.capabilities(&provider) {
+        Some(capabilities) => RpcResponse::ok(capabilities),
+        None => RpcResponse::err(format!("Unknown provider: {}", provider)),
+    }
+}
+
 async fn dispatch_ws_get_metrics(state: &crate::WebSocketState, args: Value) -> RpcResponse {
-    let provider = match args.get("provider").and_then(|v| v.as_str()) {
+    let provider = match get_arg(&args, &["provider"]).and_then(|v| v.as_str()) {
         Some(provider) => provider.to_string(),
         None => return RpcResponse::err("Missing 'provider' parameter"),
     };
@@ -2623,13 +3896,13 @@ async fn dispatch_ws_get_metrics(state: &crate::WebSocketState, args: Value) ->
     RpcResponse::ok(manager.get_metrics(&provider))
 }
 
-async fn dispatch_ws_get_all_metrics(state: &crate::WebSocketState) -> RpcResponse {
-    let manager = state.manager.read().await;
-    RpcResponse::ok(manager.get_all_metrics())
+async fn dispatch_ws_get_all_metrics(state: &crate::WebSocketState, args: Value) -> RpcResponse {
+    let fresh = get_arg(&args, &["fresh"]).and_then(|v| v.as_bool()).unwrap_or(false);
+    RpcResponse::ok(state.all_metrics(fresh).await)
 }
 
 async fn dispatch_ws_reconnect(state: &crate::WebSocketState, args: Value) -> RpcResponse {
-    let provider = match args.get("provider").and_then(|v| v.as_str()) {
+    let provider = match get_arg(&args, &["provider"]).and_then(|v| v.as_str()) {
         Some(provider) => provider.to_string(),
         None => return RpcResponse::err("Missing 'provider' parameter"),
     };
@@ -2641,6 +3914,286 @@ async fn dispatch_ws_reconnect(state: &crate::WebSocketState, args: Value) -> Rp
     }
 }
 
+async fn dispatch_ws_set_raw_capture(state: &crate::WebSocketState, args: Value) -> RpcResponse {
+    use crate::websocket::raw_capture::RAW_CAPTURE_SETTING;
+
+    let enabled = match get_arg(&args, &["enabled"]).and_then(|v| v.as_bool()) {
+        Some(enabled) => enabled,
+        None => return RpcResponse::err("Missing 'enabled' parameter"),
+    };
+
+    if let Err(e) = crate::database::operations::save_setting(
+        RAW_CAPTURE_SETTING,
+        if enabled { "true" } else { "false" },
+        Some("debug"),
+    ) {
+        return RpcResponse::err(e.to_string());
+    }
+
+    state.manager.read().await.set_raw_capture_enabled(enabled);
+    RpcResponse::ok(serde_json::json!({"enabled": enabled}))
+}
+
+async fn dispatch_ws_get_last_raw(state: &crate::WebSocketState, args: Value) -> RpcResponse {
+    let provider = match get_arg(&args, &["provider"]).and_then(|v| v.as_str()) {
+        Some(provider) => provider.to_string(),
+        None => return RpcResponse::err("Missing 'provider' parameter"),
+    };
+    let symbol = get_arg(&args, &["symbol"]).and_then(|v| v.as_str());
+    let channel = get_arg(&args, &["channel"]).and_then(|v| v.as_str());
+
+    let manager = state.manager.read().await;
+    RpcResponse::ok(manager.last_raw(&provider, symbol, channel))
+}
+
+async fn dispatch_set_tick_recording(state: &crate::WebSocketState, args: Value) -> RpcResponse {
+    let provider = match get_arg(&args, &["provider"]).and_then(|v| v.as_str()) {
+        Some(provider) => provider.to_string(),
+        None => return RpcResponse::err("Missing 'provider' parameter"),
+    };
+    let symbol = match get_arg(&args, &["symbol"]).and_then(|v| v.as_str()) {
+        Some(s) => match Symbol::new(s) {
+            Ok(symbol) => symbol,
+            Err(e) => return RpcResponse::err(e),
+        },
+        None => return RpcResponse::err("Missing 'symbol' parameter"),
+    };
+    let enabled = match get_arg(&args, &["enabled"]).and_then(|v| v.as_bool()) {
+        Some(enabled) => enabled,
+        None => return RpcResponse::err("Missing 'enabled' parameter"),
+    };
+
+    let router = state.router.read().await;
+    match router.tick_recorder().set_recording(&provider, &symbol, enabled) {
+        Ok(targets) => RpcResponse::ok(targets),
+        Err(e) => RpcResponse::err(e.to_string()),
+    }
+}
+
+async fn dispatch_get_tick_history(args: Value) -> RpcResponse {
+    let provider = match get_arg(&args, &["provider"]).and_then(|v| v.as_str()) {
+        Some(provider) => provider.to_string(),
+        None => return RpcResponse::err("Missing 'provider' parameter"),
+    };
+    let symbol = match get_arg(&args, &["symbol"]).and_then(|v| v.as_str()) {
+        Some(s) => match Symbol::new(s) {
+            Ok(symbol) => symbol,
+            Err(e) => return RpcResponse::err(e),
+        },
+        None => return RpcResponse::err("Missing 'symbol' parameter"),
+    };
+    let from = get_arg(&args, &["from"]).and_then(|v| v.as_u64());
+    let to = get_arg(&args, &["to"]).and_then(|v| v.as_u64());
+    let limit = get_arg(&args, &["limit"])
+        .and_then(|v| v.as_u64())
+        .map_or(10_000, |v| v as usize)
+        .min(crate::websocket::tick_recorder::MAX_HISTORY_TICKS);
+
+    match crate::websocket::tick_recorder::get_tick_history(&provider, &symbol, from, to, limit) {
+        Ok(ticks) => RpcResponse::ok(ticks),
+        Err(e) => RpcResponse::err(e.to_string()),
+    }
+}
+
+async fn dispatch_replay_ticks(state: &crate::WebSocketState, args: Value) -> RpcResponse {
+    let provider = match get_arg(&args, &["provider"]).and_then(|v| v.as_str()) {
+        Some(provider) => provider.to_string(),
+        None => return RpcResponse::err("Missing 'provider' parameter"),
+    };
+    let symbol = match get_arg(&args, &["symbol"]).and_then(|v| v.as_str()) {
+        Some(s) => match Symbol::new(s) {
+            Ok(symbol) => symbol,
+            Err(e) => return RpcResponse::err(e),
+        },
+        None => return RpcResponse::err("Missing 'symbol' parameter"),
+    };
+    let from = get_arg(&args, &["from"]).and_then(|v| v.as_u64());
+    let to = get_arg(&args, &["to"]).and_then(|v| v.as_u64());
+    let speed = get_arg(&args, &["speed"]).and_then(|v| v.as_f64()).unwrap_or(1.0);
+
+    match crate::websocket::tick_recorder::replay_ticks(state.router.clone(), &provider, &symbol, from, to, speed) {
+        Ok(count) => RpcResponse::ok(count),
+        Err(e) => RpcResponse::err(e.to_string()),
+    }
+}
+
+async fn dispatch_get_log_file_path() -> RpcResponse {
+    match crate::logging::log_file_path() {
+        Some(path) => RpcResponse::ok(path.to_string_lossy().to_string()),
+        None => match crate::utils::data_dir::logs_dir() {
+            Ok(dir) => RpcResponse::ok(dir.join(crate::logging::LOG_FILE_NAME).to_string_lossy().to_string()),
+            Err(e) => RpcResponse::err(e),
+        },
+    }
+}
+
+async fn dispatch_process_resource_usage(mcp_state: &Arc<crate::MCPState>, args: Value) -> RpcResponse {
+    let id = get_arg(&args, &["id", "serverId"]).and_then(|v| v.as_str()).map(|s| s.to_string());
+    match crate::commands::process_usage::process_resource_usage(mcp_state, id).await {
+        Ok(usage) => RpcResponse::ok(usage),
+        Err(e) => RpcResponse::err(e),
+    }
+}
+
+async fn dispatch_list_orphans() -> RpcResponse {
+    match tokio::task::spawn_blocking(crate::commands::orphans::orphaned_children).await {
+        Ok(orphans) => RpcResponse::ok(orphans),
+        Err(e) => RpcResponse::err(e.to_string()),
+    }
+}
+
+async fn dispatch_reap_orphans(args: Value) -> RpcResponse {
+    let pids: Option<Vec<u32>> = match get_arg(&args, &["pids"]) {
+        Some(value) if !value.is_null() => match serde_json::from_value(value.clone()) {
+            Ok(pids) => Some(pids),
+            Err(e) => return RpcResponse::err(format!("Invalid 'pids' parameter: {}", e)),
+        },
+        _ => None,
+    };
+    match tokio::task::spawn_blocking(move || crate::commands::orphans::kill_orphans(pids.as_deref())).await {
+        Ok(result) => RpcResponse::ok(result),
+        Err(e) => RpcResponse::err(e.to_string()),
+    }
+}
+
+async fn dispatch_tail_log(args: Value) -> RpcResponse {
+    let lines = get_arg(&args, &["lines"]).and_then(|v| v.as_u64()).map(|v| v as usize);
+    let level = get_arg(&args, &["level"]).and_then(|v| v.as_str()).map(|s| s.to_string());
+    match crate::commands::log_viewer::tail_log(lines, level).await {
+        Ok(lines) => RpcResponse::ok(lines),
+        Err(e) => RpcResponse::err(e),
+    }
+}
+
+async fn dispatch_get_command_timings(args: Value) -> RpcResponse {
+    let limit = get_arg(&args, &["limit"]).and_then(|v| v.as_u64()).map(|v| v as usize);
+    let report = crate::utils::timing::report(limit);
+    if get_arg(&args, &["clear"]).and_then(|v| v.as_bool()).unwrap_or(false) {
+        crate::utils::timing::clear();
+    }
+    RpcResponse::ok(report)
+}
+
+fn dispatch_create_scoped_token(state: &ServerState, args: Value) -> RpcResponse {
+    // Without an API key every client already has the whole API, so a token would restrict nothing
+    if state.config.api_key.is_none() {
+        return RpcResponse::err("Scoped tokens need an API key: start the server with FINCEPT_API_KEY set")
+            .with_kind(crate::server::ErrorKind::Forbidden);
+    }
+    let commands: Vec<String> = match get_arg(&args, &["commands"]) {
+        Some(value) => match serde_json::from_value(value.clone()) {
+            Ok(commands) => commands,
+            Err(e) => return RpcResponse::err(format!("Invalid 'commands' parameter: {}", e)),
+        },
+        None => return RpcResponse::err("Missing 'commands' parameter"),
+    };
+    let ttl_secs = get_arg(&args, &["ttlSecs", "ttl"]).and_then(|v| v.as_u64());
+
+    match super::scoped_tokens::create_scoped_token(&commands, ttl_secs) {
+        Ok(token) => RpcResponse::ok(token),
+        Err(e) => RpcResponse::err(e.to_string()),
+    }
+}
+
+fn dispatch_command_log_set_enabled(args: Value) -> RpcResponse {
+    use super::command_log::{self, COMMAND_LOG_SETTING};
+
+    let enabled = match get_arg(&args, &["enabled"]).and_then(|v| v.as_bool()) {
+        Some(enabled) => enabled,
+        None => return RpcResponse::err("Missing 'enabled' parameter"),
+    };
+    if let Err(e) = crate::database::operations::save_setting(
+        COMMAND_LOG_SETTING,
+        if enabled { "true" } else { "false" },
+        Some("debug"),
+    ) {
+        return RpcResponse::err(e.to_string());
+    }
+    match command_log::set_enabled(enabled) {
+        Ok(()) => RpcResponse::ok(command_log::status()),
+        Err(e) => RpcResponse::err(e),
+    }
+}
+
+/// One replayed command and how its outcome compares to the recording
+#[derive(Debug, serde::Serialize)]
+struct ReplayStep {
+    index: usize,
+    command: String,
+    recorded_success: bool,
+    success: bool,
+    error: Option<String>,
+}
+
+/// Dispatch the commands of a command log again, in order
+///
+/// `path` defaults to the current log file. With `stopOnError` replay ends at
+/// the first command that fails.
+async fn dispatch_replay_command_log(state: Arc<ServerState>, args: Value) -> RpcResponse {
+    use super::command_log;
+
+    let path = match command_log::replay_path(get_arg(&args, &["path"]).and_then(|v| v.as_str())) {
+        Ok(path) => path,
+        Err(e) => return RpcResponse::err(e).with_kind(crate::server::ErrorKind::Forbidden),
+    };
+    let stop_on_error = get_arg(&args, &["stopOnError"]).and_then(|v| v.as_bool()).unwrap_or(false);
+    // Commands that change something are only dispatched when the caller says so
+    let confirm_mutations = get_arg(&args, &["confirmMutations"]).and_then(|v| v.as_bool()).unwrap_or(false);
+    let entries = match command_log::read_log(&path) {
+        Ok(entries) => entries,
+        Err(e) => return RpcResponse::err(e),
+    };
+
+    let mut report = replay_entries(state, entries, stop_on_error, confirm_mutations).await;
+    report["path"] = Value::String(path.display().to_string());
+    RpcResponse::ok(report)
+}
+
+async fn replay_entries(
+    state: Arc<ServerState>,
+    entries: Vec<super::command_log::CommandLogEntry>,
+    stop_on_error: bool,
+    confirm_mutations: bool,
+) -> Value {
+    use super::command_log;
+
+    let mut steps = Vec::new();
+    let mut skipped = 0;
+    let mut unconfirmed = Vec::new();
+    for (index, entry) in entries.into_iter().enumerate() {
+        if !command_log::is_replayable(&entry.command) {
+            skipped += 1;
+            continue;
+        }
+        if !confirm_mutations && !super::read_only::is_read_only(&entry.command) {
+            unconfirmed.push(entry.command);
+            continue;
+        }
+        let request = RpcRequest { cmd: entry.command.clone(), args: entry.args };
+        let response = Box::pin(dispatch(state.clone(), request)).await;
+        let failed = !response.success;
+        steps.push(ReplayStep {
+            index,
+            command: entry.command,
+            recorded_success: entry.success,
+            success: response.success,
+            error: response.error,
+        });
+        if failed && stop_on_error {
+            break;
+        }
+    }
+
+    let diverged = steps.iter().filter(|s| s.success != s.recorded_success).count();
+    serde_json::json!({
+        "replayed": steps.len(),
+        "skipped": skipped,
+        "unconfirmed": unconfirmed,
+        "diverged": diverged,
+        "steps": steps,
+    })
+}
+
 // What: RPC handler to retrieve shared session API key from environment
 // Why: Enables web kiosk mode by exposing FINCEPT_MASTER_KEY to frontend
 // How: Reads FINCEPT_MASTER_KEY env var and returns availability status + key
@@ -2688,9 +4241,60 @@ mod tests {
             manager,
             router,
             services,
+            metrics_cache: Arc::new(crate::websocket::metrics_cache::MetricsCache::default()),
         }
     }
 
+    #[tokio::test]
+    async fn test_replay_command_log_dispatches_in_order() {
+        use crate::server::command_log::CommandLogEntry;
+
+        let state = Arc::new(ServerState {
+            start_time: std::time::Instant::now(),
+            config: crate::server::ServerConfig {
+                host: "127.0.0.1".to_string(),
+                port: 0,
+                cors_enabled: false,
+                cors_origins: Vec::new(),
+                max_ws_connections: 0,
+                ws_slow_client_timeout: std::time::Duration::from_secs(1),
+                rpc_status_codes: true,
+                static_dir: None,
+                api_key: None,
+            },
+            request_count: Default::default(),
+            command_stats: Default::default(),
+            ws_connections: Default::default(),
+            ws_state: create_test_ws_state(),
+            mcp_state: Arc::new(crate::MCPState { processes: Default::default() }),
+        });
+        let entry = |command: &str, args: Value, success: bool| CommandLogEntry {
+            command: command.to_string(),
+            args,
+            timestamp: 0,
+            success,
+            error: None,
+        };
+        let entries = vec![
+            entry("greet", serde_json::json!({"name": "Ada"}), true),
+            entry("command_log_status", serde_json::json!({}), true),
+            entry("ws_subscribe", serde_json::json!({"provider": "binance"}), true),
+            entry("greet", serde_json::json!({}), true),
+        ];
+
+        // Without confirmation only read-only commands run
+        let report = replay_entries(state.clone(), entries.clone(), true, false).await;
+        assert_eq!(report["replayed"], 2);
+        assert_eq!(report["unconfirmed"], serde_json::json!(["ws_subscribe"]));
+
+        let report = replay_entries(state, entries, true, true).await;
+        assert_eq!(report["replayed"], 2);
+        assert_eq!(report["skipped"], 1);
+        assert_eq!(report["diverged"], 1);
+        assert_eq!(report["steps"][1]["command"], "ws_subscribe");
+        assert_eq!(report["steps"][1]["error"], "Missing 'symbol' parameter");
+    }
+
     #[tokio::test]
     async fn test_dispatch_ws_connect_missing_provider() {
         let ws_state = create_test_ws_state();
@@ -2729,6 +4333,11 @@ mod tests {
         assert!(response.error.is_some());
         assert_eq!(response.error.unwrap(), "Missing 'symbol' parameter");
         
+        // Blank symbol
+        let args = serde_json::json!({"provider": "binance", "symbol": "  ", "channel": "ticker"});
+        let response = dispatch_ws_subscribe(&ws_state, args).await;
+        assert_eq!(response.error.unwrap(), "Symbol must not be empty");
+        
         // Missing channel
         let args = serde_json::json!({"provider": "binance", "symbol": "BTC/USD"});
         let response = dispatch_ws_subscribe(&ws_state, args).await;
@@ -2736,6 +4345,25 @@ mod tests {
         assert_eq!(response.error.unwrap(), "Missing 'channel' parameter");
     }
 
+    #[tokio::test]
+    async fn test_dispatch_ws_subscribe_many_reports_each_symbol() {
+        let ws_state = create_test_ws_state();
+
+        let args = serde_json::json!({"provider": "binance", "channel": "ticker"});
+        let response = dispatch_ws_subscribe_many(&ws_state, args).await;
+        assert_eq!(response.error.unwrap(), "Missing 'symbols' parameter");
+
+        // Unknown provider: every valid symbol fails upstream, the blank one before that
+        let args = serde_json::json!({"provider": "nowhere", "symbols": ["BTC", " ", "ETH", "BTC"], "channel": "ticker"});
+        let response = dispatch_ws_subscribe_many(&ws_state, args).await;
+        let outcomes = response.data.unwrap();
+        let outcomes = outcomes.as_array().unwrap();
+        let symbols: Vec<&str> = outcomes.iter().map(|o| o["symbol"].as_str().unwrap()).collect();
+        assert_eq!(symbols, ["BTC", " ", "ETH"]);
+        assert!(outcomes.iter().all(|o| o["subscribed"] == false));
+        assert_eq!(outcomes[1]["error"], "Symbol must not be empty");
+    }
+
     #[tokio::test]
     async fn test_dispatch_ws_unsubscribe_missing_parameters() {
         let ws_state = create_test_ws_state();
@@ -2774,7 +4402,7 @@ mod tests {
     async fn test_dispatch_ws_get_all_metrics() {
         let ws_state = create_test_ws_state();
         
-        let response = dispatch_ws_get_all_metrics(&ws_state).await;
+        let response = dispatch_ws_get_all_metrics(&ws_state, serde_json::json!({})).await;
         
         assert!(response.error.is_none());
         assert!(response.result.is_some());
@@ -2791,6 +4419,19 @@ mod tests {
         assert_eq!(response.error.unwrap(), "Missing 'provider' parameter");
     }
 
+    #[tokio::test]
+    async fn test_dispatch_ws_get_last_raw() {
+        let ws_state = create_test_ws_state();
+
+        let response = dispatch_ws_get_last_raw(&ws_state, serde_json::json!({})).await;
+        assert_eq!(response.error.unwrap(), "Missing 'provider' parameter");
+
+        // Nothing is captured until raw capture is enabled
+        let args = serde_json::json!({"provider": "kraken", "symbol": "BTC/USD", "channel": "ticker"});
+        let response = dispatch_ws_get_last_raw(&ws_state, args).await;
+        assert_eq!(response.data, Some(serde_json::json!([])));
+    }
+
     #[tokio::test]
     async fn test_dispatch_ws_set_config_invalid_config() {
         let ws_state = create_test_ws_state();
@@ -2802,6 +4443,27 @@ mod tests {
         assert!(response.error.is_some());
     }
 
+    #[tokio::test]
+    async fn test_dispatch_ws_set_config_unknown_provider() {
+        let ws_state = create_test_ws_state();
+        let args = serde_json::json!({"config": {
+            "name": "bitmex",
+            "url": "wss://ws.bitmex.com/realtime",
+            "api_key": null,
+            "api_secret": null,
+            "enabled": true,
+            "reconnect_delay_ms": 5000,
+            "max_reconnect_attempts": 10,
+            "heartbeat_interval_ms": 30000
+        }});
+
+        let response = dispatch_ws_set_config(&ws_state, args).await;
+
+        let error = response.error.unwrap();
+        assert!(error.contains("Unknown provider 'bitmex'"));
+        assert!(error.contains("kraken"));
+    }
+
     // MCP DISPATCH FUNCTION TESTS
 
     fn create_test_mcp_state() -> Arc<crate::MCPState> {
@@ -3043,7 +4705,7 @@ mod tests {
     }
 
     // Note: Error handling for invalid field/operator values from database
-    // (in dispatch_monitor_get_conditions and dispatch_monitor_get_alerts)
+    // (in dispatch_monitor_get_conditions and monitoring::query_alerts)
     // is integration-tested through database operations. Unit testing would require setting up
     // an in-memory database with corrupted data, which is complex and brittle. The error handling
     // logic uses ok_or_else() to convert None to descriptive errors, preventing panics when
@@ -3060,6 +4722,32 @@ mod tests {
         assert_eq!(response.error.unwrap(), "Missing 'id' parameter");
     }
 
+    #[test]
+    fn test_dispatch_monitor_acknowledge_alert_missing_id() {
+        let response = dispatch_monitor_acknowledge_alert(serde_json::json!({}));
+
+        assert_eq!(response.error.unwrap(), "Missing 'id' parameter");
+    }
+
+    #[tokio::test]
+    async fn test_dispatch_monitor_add_condition_group_invalid_logic() {
+        let ws_state = create_test_ws_state();
+        let args = serde_json::json!({
+            "group": {
+                "provider": "binance",
+                "symbol": "BTCUSDT",
+                "logic": "xor",
+                "conditions": [{"field": "price", "operator": ">", "value": 100.0}],
+                "enabled": true
+            }
+        });
+
+        let response = dispatch_monitor_add_condition_group(&ws_state, args).await;
+
+        assert!(!response.success);
+        assert!(response.error.unwrap().starts_with("Invalid 'group' parameter"));
+    }
+
     #[tokio::test]
     async fn test_dispatch_cleanup_running_workflows_success() {
         let response = dispatch_cleanup_running_workflows().await;
@@ -3156,4 +4844,59 @@ mod tests {
         assert!(result.is_ok());
         assert_eq!(result.unwrap(), 42);
     }
+
+    #[tokio::test]
+    async fn test_dispatch_db_create_order_reports_all_invalid_fields() {
+        let args = serde_json::json!({"portfolioId": "p1", "side": "hold", "quantity": 0});
+
+        let response = dispatch_db_create_order(args).await;
+
+        assert!(!response.success);
+        let fields: Vec<String> = response.errors.unwrap().into_iter().map(|e| e.field).collect();
+        assert_eq!(fields, vec!["symbol", "side", "orderType", "quantity"]);
+    }
+
+    #[tokio::test]
+    async fn test_dispatch_open_market_position_rejects_malformed_fees() {
+        let args = serde_json::json!({
+            "portfolioId": "p1", "symbol": "BTC/USD", "side": "long",
+            "quantity": 1, "markPrice": 100, "fees": {"maker": "cheap"}
+        });
+
+        let response = dispatch_open_market_position(args).await;
+
+        assert!(!response.success);
+        let fields: Vec<String> = response.errors.unwrap().into_iter().map(|e| e.field).collect();
+        assert_eq!(fields, vec!["fees"]);
+    }
+
+    #[test]
+    fn test_create_commands_accept_both_casings() {
+        // The field lists the create dispatchers validate, checked without touching a database
+        let read = |args: &Value| {
+            let mut v = ArgValidator::new(args);
+            let fields = (
+                v.required_str(&["portfolioId"]),
+                v.required_str(&["orderId"]),
+                v.required_positive(&["entryPrice"]),
+                v.required_f64(&["initialBalance"]),
+            );
+            let optional = ["marginMode", "timeInForce", "feeRate", "isMaker"].map(|name| get_arg(args, &[name]).cloned());
+            (v.finish().map_err(|errors| errors.len()), fields, optional)
+        };
+        let camel = serde_json::json!({
+            "portfolioId": "p1", "orderId": "o1", "entryPrice": 10.0, "initialBalance": 1000.0,
+            "marginMode": "cross", "timeInForce": "GTC", "feeRate": 0.001, "isMaker": true
+        });
+        let snake = serde_json::json!({
+            "portfolio_id": "p1", "order_id": "o1", "entry_price": 10.0, "initial_balance": 1000.0,
+            "margin_mode": "cross", "time_in_force": "GTC", "fee_rate": 0.001, "is_maker": true
+        });
+
+        let (result, fields, optional) = read(&camel);
+        assert_eq!(result, Ok(()));
+        assert_eq!(fields, ("p1".to_string(), "o1".to_string(), 10.0, 1000.0));
+        assert!(optional.iter().all(Option::is_some));
+        assert_eq!(read(&snake), (result, fields, optional));
+    }
 }
@@ -0,0 +1,184 @@
+// Scoped Tokens - expiring tokens that only allow a few RPC commands
+//
+// What: `create_scoped_token(commands, ttlSecs)` issues an opaque token. A
+//       request to `/api/rpc` carrying it in the `x-fincept-token` header may
+//       only call the listed commands, and only until the token expires. An
+//       unknown or expired token is rejected with 401, a command outside its
+//       scope with 403.
+// Why:  Sharing a read-only dashboard meant handing out the whole API.
+// How:  Tokens are only a boundary when the rest of the API is closed, so they
+//       can only be issued while the server has an API key (`FINCEPT_API_KEY`),
+//       which every request without a token must then present
+//       (`x-fincept-api-key` or `Authorization: Bearer`). Only the SHA-256 of a
+//       token is stored (`scoped_tokens` table) with its commands and expiry,
+//       so the database never holds a usable token. Tokens travel in a header,
+//       never the URL, which the request log records. A token can only grant
+//       commands in `read_only::READ_ONLY_COMMANDS`, so a shared link can't
+//       write, read credentials, issue tokens or run other commands. Expired
+//       rows are removed whenever a new token is issued.
+
+use crate::database::pool::{self, get_pool};
+use anyhow::{bail, Result};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+/// Request header carrying a token
+pub const TOKEN_HEADER: &str = "x-fincept-token";
+
+/// Request header carrying the server's API key; `Authorization: Bearer <key>` also works
+pub const API_KEY_HEADER: &str = "x-fincept-api-key";
+
+const DEFAULT_TTL_SECS: u64 = 60 * 60;
+const MAX_TTL_SECS: u64 = 30 * 24 * 60 * 60;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ScopedToken {
+    /// Shown once; only its hash is kept
+    pub token: String,
+    pub commands: Vec<String>,
+    /// Unix milliseconds
+    pub expires_at: i64,
+}
+
+/// Why a token didn't allow a request
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenDenial {
+    Invalid,
+    Expired,
+    OutOfScope,
+}
+
+impl TokenDenial {
+    pub fn message(self, command: &str) -> String {
+        match self {
+            TokenDenial::Invalid => "Invalid token".to_string(),
+            TokenDenial::Expired => "Token has expired".to_string(),
+            TokenDenial::OutOfScope => format!("Token does not allow '{}'", command),
+        }
+    }
+}
+
+/// Whether `presented` is the configured API key, compared in constant time
+pub fn api_key_matches(configured: &str, presented: &str) -> bool {
+    let (configured, presented) = (Sha256::digest(configured.as_bytes()), Sha256::digest(presented.as_bytes()));
+    configured.iter().zip(presented.iter()).fold(0u8, |diff, (a, b)| diff | (a ^ b)) == 0
+}
+
+fn token_hash(token: &str) -> String {
+    format!("{:x}", Sha256::digest(token.as_bytes()))
+}
+
+/// Issue a token for the read-only `commands`, valid for `ttl_secs` (default one hour, at most 30 days)
+pub fn create_scoped_token(commands: &[String], ttl_secs: Option<u64>) -> Result<ScopedToken> {
+    let mut commands: Vec<String> = commands.iter().map(|c| c.trim().to_string()).collect();
+    commands.sort();
+    commands.dedup();
+    if commands.is_empty() || commands.iter().any(String::is_empty) {
+        bail!("Invalid commands: list at least one command");
+    }
+    for command in &commands {
+        if !crate::command_registry::WEB_RPC_COMMANDS.contains(&command.as_str()) {
+            bail!("Invalid commands: '{}' is not a web RPC command", command);
+        }
+        if !crate::server::read_only::is_read_only(command) {
+            bail!("Invalid commands: a token can't grant '{}', only read-only commands", command);
+        }
+    }
+    let ttl_secs = ttl_secs.unwrap_or(DEFAULT_TTL_SECS);
+    if ttl_secs == 0 || ttl_secs > MAX_TTL_SECS {
+        bail!("ttlSecs must be between 1 and {}", MAX_TTL_SECS);
+    }
+
+    let token = format!("fst_{}{}", uuid::Uuid::new_v4().simple(), uuid::Uuid::new_v4().simple());
+    let expires_at = chrono::Utc::now().timestamp_millis() + (ttl_secs * 1000) as i64;
+    pool::write(|conn| store_token(conn, &token, &commands, expires_at))?;
+
+    Ok(ScopedToken { token, commands, expires_at })
+}
+
+fn store_token(conn: &Connection, token: &str, commands: &[String], expires_at: i64) -> Result<()> {
+    conn.execute(
+        "DELETE FROM scoped_tokens WHERE expires_at <= ?1",
+        params![chrono::Utc::now().timestamp_millis()],
+    )?;
+    conn.execute(
+        "INSERT INTO scoped_tokens (token_hash, commands, expires_at) VALUES (?1, ?2, ?3)",
+        params![token_hash(token), serde_json::to_string(commands)?, expires_at],
+    )?;
+    Ok(())
+}
+
+/// Whether `token` allows calling `command` now
+pub fn authorize(token: &str, command: &str) -> Result<(), TokenDenial> {
+    let pool = get_pool().map_err(|_| TokenDenial::Invalid)?;
+    let conn = pool.get().map_err(|_| TokenDenial::Invalid)?;
+    check_token(&conn, token, command, chrono::Utc::now().timestamp_millis())
+}
+
+fn check_token(conn: &Connection, token: &str, command: &str, now_ms: i64) -> Result<(), TokenDenial> {
+    let row: Option<(String, i64)> = conn
+        .query_row(
+            "SELECT commands, expires_at FROM scoped_tokens WHERE token_hash = ?1",
+            params![token_hash(token)],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()
+        .map_err(|_| TokenDenial::Invalid)?;
+    let (commands, expires_at) = row.ok_or(TokenDenial::Invalid)?;
+    if expires_at <= now_ms {
+        return Err(TokenDenial::Expired);
+    }
+    let commands: Vec<String> = serde_json::from_str(&commands).map_err(|_| TokenDenial::Invalid)?;
+    if !commands.iter().any(|c| c == command) {
+        return Err(TokenDenial::OutOfScope);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_token_allows_only_its_commands_until_expiry() {
+        let conn = Connection::open_in_memory().unwrap();
+        crate::database::schema::create_schema(&conn).unwrap();
+        let now = chrono::Utc::now().timestamp_millis();
+        let commands = vec!["db_get_all_settings".to_string()];
+        store_token(&conn, "fst_share", &commands, now + 60_000).unwrap();
+
+        assert_eq!(check_token(&conn, "fst_share", "db_get_all_settings", now), Ok(()));
+        assert_eq!(check_token(&conn, "fst_share", "db_save_setting", now), Err(TokenDenial::OutOfScope));
+        assert_eq!(check_token(&conn, "fst_other", "db_get_all_settings", now), Err(TokenDenial::Invalid));
+        assert_eq!(check_token(&conn, "fst_share", "db_get_all_settings", now + 60_000), Err(TokenDenial::Expired));
+
+        // Only the hash is stored
+        let stored: String = conn.query_row("SELECT token_hash FROM scoped_tokens", [], |row| row.get(0)).unwrap();
+        assert_ne!(stored, "fst_share");
+
+        assert!(create_scoped_token(&["no_such_command".to_string()], None).is_err());
+
+        assert!(api_key_matches("s3cret", "s3cret"));
+        assert!(!api_key_matches("s3cret", "s3cre"));
+    }
+
+    #[test]
+    fn test_token_cannot_grant_writes_or_secrets() {
+        // Refused before anything is stored, so no database is needed
+        for command in [
+            "create_scoped_token",
+            "replay_command_log",
+            "schedule_task",
+            "db_get_credentials",
+            "export_config",
+            "purge_portfolio",
+            "db_create_order",
+            "db_save_setting",
+        ] {
+            let commands = vec!["get_market_quote".to_string(), command.to_string()];
+            let refused = create_scoped_token(&commands, None).unwrap_err().to_string();
+            assert!(refused.contains(&format!("can't grant '{}'", command)), "{}", refused);
+        }
+    }
+}
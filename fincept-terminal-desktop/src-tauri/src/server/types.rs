@@ -1,10 +1,11 @@
 // RPC Types for Web Server
 // These types mirror the JSON-RPC protocol used by Tauri's invoke system
 
+use dashmap::DashMap;
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
-use std::sync::atomic::AtomicU64;
-use std::time::Instant;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
 
 /// RPC Request - mirrors Tauri's invoke pattern
 #[derive(Debug, Clone, Deserialize)]
@@ -27,6 +28,103 @@ pub struct RpcResponse {
     /// Error message (if failed)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub error: Option<String>,
+    /// Per-field problems when the arguments were rejected
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub errors: Option<Vec<super::validation::FieldError>>,
+    /// Category of the failure (if failed); sets the HTTP status of `/rpc`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub kind: Option<ErrorKind>,
+}
+
+/// Why a command failed, as far as an HTTP client is concerned
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorKind {
+    /// Bad or missing arguments (400)
+    Validation,
+    /// The request's token is unknown or expired (401)
+    Unauthorized,
+    /// The request's token doesn't allow the command (403)
+    Forbidden,
+    /// The named command, record or resource does not exist (404)
+    NotFound,
+    /// An upstream provider throttled the request (429)
+    RateLimited,
+    /// The caller cancelled the request (499)
+    Cancelled,
+    /// Anything not recognised below (500)
+    Internal,
+    /// Desktop-only command called in web mode (501)
+    NotImplemented,
+    /// A data provider or script failed (502)
+    Upstream,
+    /// The database or Python runtime is not usable right now (503)
+    Unavailable,
+}
+
+impl ErrorKind {
+    /// Best guess from an error message
+    ///
+    /// Commands return plain strings, so the kind is read from the wording the
+    /// shared helpers use ("Missing 'x' parameter", "Database unavailable", ...).
+    pub fn classify(message: &str) -> Self {
+        let message = message.to_lowercase();
+        let has = |needles: &[&str]| needles.iter().any(|needle| message.contains(needle));
+
+        if message == crate::utils::cancellation::CANCELLED_ERROR.to_lowercase() {
+            ErrorKind::Cancelled
+        } else if has(&["429", "rate limit", "too many requests"]) {
+            ErrorKind::RateLimited
+        } else if has(&[
+            "database unavailable",
+            "database is locked",
+            "database not initialized",
+            "worker pool not initialized",
+            crate::utils::python_errors::MISSING_MODULE_PREFIX.to_lowercase().as_str(),
+            "python interpreter not found",
+            crate::utils::python_errors::PYTHON_NOT_INSTALLED.to_lowercase().as_str(),
+        ]) {
+            ErrorKind::Unavailable
+        } else if message.starts_with("python script failed") {
+            ErrorKind::Upstream
+        } else if has(&["not found", "not recognized", "no such", "does not exist", "query returned no rows"]) {
+            ErrorKind::NotFound
+        } else if message.starts_with("missing '")
+            || message.starts_with("invalid")
+            || message.starts_with("unsupported")
+            || has(&[" parameter", " must ", "expected ", "out of range"])
+        {
+            ErrorKind::Validation
+        } else if has(&[
+            "failed to fetch",
+            "python script failed",
+            "http error",
+            "request failed",
+            "timed out",
+            "connection error",
+            "bad gateway",
+            "upstream",
+        ]) {
+            ErrorKind::Upstream
+        } else {
+            ErrorKind::Internal
+        }
+    }
+
+    pub fn status_code(self) -> u16 {
+        match self {
+            ErrorKind::Validation => 400,
+            ErrorKind::Unauthorized => 401,
+            ErrorKind::Forbidden => 403,
+            ErrorKind::NotFound => 404,
+            ErrorKind::RateLimited => 429,
+            ErrorKind::Cancelled => 499,
+            ErrorKind::Internal => 500,
+            ErrorKind::NotImplemented => 501,
+            ErrorKind::Upstream => 502,
+            ErrorKind::Unavailable => 503,
+        }
+    }
 }
 
 impl RpcResponse {
@@ -37,6 +135,8 @@ impl RpcResponse {
                 success: true,
                 data: Some(data_value),
                 error: None,
+                errors: None,
+                kind: None,
             },
             Err(e) => {
                 // Serialization failed - return error response instead of masking the error
@@ -48,17 +148,51 @@ impl RpcResponse {
                     success: false,
                     data: None,
                     error: Some(format!("Failed to serialize response: {}", e)),
+                    errors: None,
+                    kind: Some(ErrorKind::Internal),
                 }
             }
         }
     }
 
-    /// Create an error response
+    /// Create an error response, its kind guessed from the message
     pub fn err(message: impl Into<String>) -> Self {
+        let message = message.into();
         Self {
             success: false,
             data: None,
-            error: Some(message.into()),
+            kind: Some(ErrorKind::classify(&message)),
+            error: Some(message),
+            errors: None,
+        }
+    }
+
+    /// Override the guessed error kind
+    pub fn with_kind(mut self, kind: ErrorKind) -> Self {
+        if !self.success {
+            self.kind = Some(kind);
+        }
+        self
+    }
+
+    /// HTTP status for this response; failures without a kind count as internal
+    pub fn status_code(&self) -> u16 {
+        if self.success {
+            200
+        } else {
+            self.kind.unwrap_or(ErrorKind::Internal).status_code()
+        }
+    }
+
+    /// Reject the arguments, listing every invalid field
+    pub fn invalid(errors: Vec<super::validation::FieldError>) -> Self {
+        let message = errors.iter().map(|e| e.message.as_str()).collect::<Vec<_>>().join("; ");
+        Self {
+            success: false,
+            data: None,
+            error: Some(message),
+            errors: Some(errors),
+            kind: Some(ErrorKind::Validation),
         }
     }
 }
@@ -82,6 +216,18 @@ pub struct ServerConfig {
     pub cors_enabled: bool,
     /// Allowed origins for CORS
     pub cors_origins: Vec<String>,
+    /// Maximum concurrent `/ws` clients (0 = unlimited)
+    pub max_ws_connections: usize,
+    /// How long a client's send buffer may stay full before it is disconnected
+    pub ws_slow_client_timeout: Duration,
+    /// Answer failed `/rpc` calls with the status of their `ErrorKind`; off means
+    /// the old behaviour of 200 with `success: false`
+    pub rpc_status_codes: bool,
+    /// Built frontend (the `dist` directory) served for every non-API path
+    pub static_dir: Option<std::path::PathBuf>,
+    /// Key every `/rpc` request without a scoped token must present; `None` leaves
+    /// the API open to anyone who can reach it, and scoped tokens can't be issued
+    pub api_key: Option<String>,
 }
 
 /// Server state shared across handlers
@@ -89,10 +235,74 @@ pub struct ServerState {
     pub start_time: Instant,
     pub config: ServerConfig,
     pub request_count: AtomicU64,
+    /// RPC invocations and failures per command
+    pub command_stats: CommandCounters,
+    /// Open `/ws` client connections
+    pub ws_connections: AtomicU64,
     pub ws_state: crate::WebSocketState,
     pub mcp_state: Arc<crate::MCPState>,
 }
 
+impl ServerState {
+    pub fn stats(&self) -> ServerStats {
+        ServerStats {
+            total_requests: self.request_count.load(Ordering::Relaxed),
+            uptime_seconds: self.start_time.elapsed().as_secs(),
+            websocket_connections: self.ws_connections.load(Ordering::Relaxed),
+            commands: self.command_stats.snapshot(),
+        }
+    }
+}
+
+/// Invocation and error counts for one RPC command
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CommandStats {
+    pub command: String,
+    pub calls: u64,
+    pub errors: u64,
+}
+
+/// Concurrent per-command counters
+#[derive(Debug, Default)]
+pub struct CommandCounters {
+    counts: DashMap<String, (u64, u64)>,
+}
+
+impl CommandCounters {
+    pub fn record(&self, command: &str, success: bool) {
+        let mut entry = self.counts.entry(command.to_string()).or_default();
+        entry.0 += 1;
+        if !success {
+            entry.1 += 1;
+        }
+    }
+
+    /// Counts for every command seen, most called first
+    pub fn snapshot(&self) -> Vec<CommandStats> {
+        let mut stats: Vec<CommandStats> = self
+            .counts
+            .iter()
+            .map(|entry| CommandStats {
+                command: entry.key().clone(),
+                calls: entry.value().0,
+                errors: entry.value().1,
+            })
+            .collect();
+        stats.sort_by(|a, b| b.calls.cmp(&a.calls).then_with(|| a.command.cmp(&b.command)));
+        stats
+    }
+}
+
+/// Response of the `server_stats` RPC command
+#[derive(Debug, Clone, Serialize)]
+pub struct ServerStats {
+    /// HTTP requests served, all endpoints
+    pub total_requests: u64,
+    pub uptime_seconds: u64,
+    pub websocket_connections: u64,
+    pub commands: Vec<CommandStats>,
+}
+
 impl Default for ServerConfig {
     fn default() -> Self {
         Self {
@@ -103,6 +313,51 @@ impl Default for ServerConfig {
                 "http://localhost:3000".to_string(),
                 "http://127.0.0.1:3000".to_string(),
             ],
+            max_ws_connections: 200,
+            ws_slow_client_timeout: Duration::from_secs(10),
+            rpc_status_codes: true,
+            static_dir: None,
+            api_key: None,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_kinds_from_messages() {
+        let kind = |message: &str| RpcResponse::err(message).status_code();
+        assert_eq!(kind("Missing 'symbol' parameter"), 400);
+        assert_eq!(kind("Invalid date 'x', expected YYYY-MM-DD"), 400);
+        assert_eq!(kind("Position not found: p1"), 404);
+        assert_eq!(kind("Python script failed: HTTP Error 429: Too Many Requests"), 429);
+        assert_eq!(kind("Request cancelled"), 499);
+        assert_eq!(kind("Python script failed: ConnectionResetError"), 502);
+        assert_eq!(kind("Database unavailable (status: restart_required): disk full"), 503);
+        assert_eq!(kind("something odd happened"), 500);
+        assert_eq!(RpcResponse::ok(1).status_code(), 200);
+        assert_eq!(RpcResponse::err("nope").with_kind(ErrorKind::NotImplemented).status_code(), 501);
+        assert_eq!(RpcResponse::invalid(Vec::new()).kind, Some(ErrorKind::Validation));
+    }
+
+    #[test]
+    fn test_command_counters() {
+        let counters = CommandCounters::default();
+        counters.record("get_market_quotes", true);
+        counters.record("get_market_quotes", false);
+        counters.record("get_market_quotes", true);
+        counters.record("db_get_setting", true);
+        counters.record("cancel_request", true);
+
+        assert_eq!(
+            counters.snapshot(),
+            vec![
+                CommandStats { command: "get_market_quotes".to_string(), calls: 3, errors: 1 },
+                CommandStats { command: "cancel_request".to_string(), calls: 1, errors: 0 },
+                CommandStats { command: "db_get_setting".to_string(), calls: 1, errors: 0 },
+            ]
+        );
+    }
+}
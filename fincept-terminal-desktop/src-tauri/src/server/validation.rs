@@ -0,0 +1,329 @@
+// RPC argument access and validation
+//
+// What: `get_arg` looks up an argument under either its camelCase or snake_case name.
+//       `ArgValidator` builds on it and collects a `FieldError` for every missing or
+//       invalid field; `RpcResponse::invalid` returns them all together as
+//       `{ success: false, error, errors: [{ field, message }] }`.
+// Why:  Tauri's invoke converts camelCase args to snake_case parameters, but the web
+//       RPC layer reads raw JSON. Dispatchers that checked only one spelling worked on
+//       desktop and failed on the web. Returning on the first missing parameter also
+//       meant a form with three empty fields needed three round trips.
+// How:  Every name passed to `get_arg` is also tried in its other case, so one name
+//       covers both spellings. Validator getters record an error and return a
+//       placeholder instead of returning early, and the dispatcher calls `finish()`
+//       before using any value. `error` joins the messages, so it still reads
+//       "Missing 'x' parameter" when only one field is wrong. Create commands take
+//       their id from `id_or_generated`, which honours a caller-supplied id.
+//       Dates bound for Python scripts go through `normalize_date`, so a typo is
+//       rejected here instead of surfacing as a strptime traceback.
+
+use chrono::NaiveDate;
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+
+/// Date formats accepted from callers besides RFC 3339 timestamps; the slash
+/// form is US month/day order, as used by the Treasury API
+const DATE_FORMATS: &[&str] = &["%Y-%m-%d", "%Y/%m/%d", "%m/%d/%Y"];
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FieldError {
+    pub field: String,
+    pub message: String,
+}
+
+/// `portfolioId` <-> `portfolio_id`
+fn alternate_case(name: &str) -> String {
+    if name.contains('_') {
+        let mut out = String::with_capacity(name.len());
+        let mut upper = false;
+        for c in name.chars() {
+            if c == '_' {
+                upper = true;
+            } else if upper {
+                out.push(c.to_ascii_uppercase());
+                upper = false;
+            } else {
+                out.push(c);
+            }
+        }
+        out
+    } else {
+        let mut out = String::with_capacity(name.len() + 4);
+        for c in name.chars() {
+            if c.is_ascii_uppercase() {
+                out.push('_');
+                out.push(c.to_ascii_lowercase());
+            } else {
+                out.push(c);
+            }
+        }
+        out
+    }
+}
+
+/// First non-null argument among `names`, each tried in camelCase and snake_case
+pub fn get_arg<'a>(args: &'a Value, names: &[&str]) -> Option<&'a Value> {
+    names.iter().find_map(|name| {
+        args.get(*name)
+            .or_else(|| args.get(alternate_case(name).as_str()))
+            .filter(|v| !v.is_null())
+    })
+}
+
+/// `value` as `YYYY-MM-DD`, or an error naming `field`
+pub fn normalize_date(field: &str, value: &str) -> Result<String, String> {
+    let value = value.trim();
+    DATE_FORMATS
+        .iter()
+        .find_map(|format| NaiveDate::parse_from_str(value, format).ok())
+        .or_else(|| chrono::DateTime::parse_from_rfc3339(value).ok().map(|t| t.date_naive()))
+        .map(|date| date.format("%Y-%m-%d").to_string())
+        .ok_or_else(|| {
+            format!(
+                "Invalid '{}' parameter: '{}' is not a date (expected YYYY-MM-DD, YYYY/MM/DD or MM/DD/YYYY)",
+                field, value
+            )
+        })
+}
+
+/// Reject a range whose start is after its end; both are normalized dates
+pub fn check_date_order(start: Option<&str>, end: Option<&str>) -> Result<(), String> {
+    match (start, end) {
+        (Some(start), Some(end)) if !start.is_empty() && !end.is_empty() && start > end => Err(format!(
+            "Invalid date range: 'start_date' {} is after 'end_date' {}",
+            start, end
+        )),
+        _ => Ok(()),
+    }
+}
+
+pub struct ArgValidator<'a> {
+    args: &'a Value,
+    errors: Vec<FieldError>,
+}
+
+impl<'a> ArgValidator<'a> {
+    pub fn new(args: &'a Value) -> Self {
+        Self {
+            args,
+            errors: Vec::new(),
+        }
+    }
+
+    /// Errors are reported under the first of `names`
+    fn lookup(&self, names: &[&str]) -> Option<&'a Value> {
+        get_arg(self.args, names)
+    }
+
+    fn push(&mut self, field: &str, message: String) {
+        self.errors.push(FieldError {
+            field: field.to_string(),
+            message,
+        });
+    }
+
+    pub fn required_str(&mut self, names: &[&str]) -> String {
+        match self.lookup(names) {
+            Some(Value::String(s)) if !s.trim().is_empty() => s.clone(),
+            Some(Value::String(_)) => {
+                self.push(names[0], format!("'{}' must not be empty", names[0]));
+                String::new()
+            }
+            Some(_) => {
+                self.push(names[0], format!("'{}' must be a string", names[0]));
+                String::new()
+            }
+            None => {
+                self.push(names[0], format!("Missing '{}' parameter", names[0]));
+                String::new()
+            }
+        }
+    }
+
+    pub fn required_f64(&mut self, names: &[&str]) -> f64 {
+        match self.lookup(names) {
+            Some(v) => match v.as_f64() {
+                Some(n) => n,
+                None => {
+                    self.push(names[0], format!("'{}' must be a number", names[0]));
+                    0.0
+                }
+            },
+            None => {
+                self.push(names[0], format!("Missing '{}' parameter", names[0]));
+                0.0
+            }
+        }
+    }
+
+    /// Like `required_f64`, but the number must also be greater than zero
+    pub fn required_positive(&mut self, names: &[&str]) -> f64 {
+        let before = self.errors.len();
+        let value = self.required_f64(names);
+        if self.errors.len() == before && value <= 0.0 {
+            self.push(names[0], format!("'{}' must be greater than 0", names[0]));
+        }
+        value
+    }
+
+    /// Like `required_str`, restricted to `allowed` values
+    pub fn required_one_of(&mut self, names: &[&str], allowed: &[&str]) -> String {
+        let before = self.errors.len();
+        let value = self.required_str(names);
+        if self.errors.len() == before && !allowed.contains(&value.as_str()) {
+            self.push(
+                names[0],
+                format!("'{}' must be one of: {}", names[0], allowed.join(", ")),
+            );
+        }
+        value
+    }
+
+    /// Optional structured argument; `None` when absent, an error when present
+    /// but not the shape `T` expects
+    pub fn optional_json<T: DeserializeOwned>(&mut self, names: &[&str]) -> Option<T> {
+        let value = self.lookup(names)?;
+        match serde_json::from_value(value.clone()) {
+            Ok(parsed) => Some(parsed),
+            Err(e) => {
+                self.push(names[0], format!("Invalid '{}' parameter: {}", names[0], e));
+                None
+            }
+        }
+    }
+
+    /// Id for a new record: `id`, else `clientId`, else a generated v4 UUID
+    ///
+    /// Supplied ids are used verbatim so tests and imports can create records
+    /// idempotently; they must be 1-128 characters of `A-Z a-z 0-9 _ - . :`.
+    pub fn id_or_generated(&mut self) -> String {
+        const NAMES: &[&str] = &["id", "clientId"];
+        let Some((name, value)) = NAMES.iter().find_map(|name| get_arg(self.args, &[*name]).map(|v| (*name, v))) else {
+            return uuid::Uuid::new_v4().to_string();
+        };
+
+        let id = value.as_str().unwrap_or_default();
+        let valid = (1..=128).contains(&id.len())
+            && id.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.' | ':'));
+        if !valid {
+            self.push(
+                name,
+                format!("'{}' must be 1-128 characters of letters, digits, '_', '-', '.' or ':'", name),
+            );
+        }
+        id.to_string()
+    }
+
+    /// Ok if no field was rejected
+    pub fn finish(self) -> Result<(), Vec<FieldError>> {
+        if self.errors.is_empty() {
+            Ok(())
+        } else {
+            Err(self.errors)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_arg_accepts_both_casings() {
+        let args = serde_json::json!({"portfolio_id": "p1", "orderId": "o1", "feeRate": null});
+        assert_eq!(get_arg(&args, &["portfolioId"]), Some(&serde_json::json!("p1")));
+        assert_eq!(get_arg(&args, &["order_id"]), Some(&serde_json::json!("o1")));
+        assert_eq!(get_arg(&args, &["feeRate"]), None);
+        assert_eq!(get_arg(&args, &["watchlistId", "id"]), None);
+        assert_eq!(alternate_case("time_in_force"), "timeInForce");
+        assert_eq!(alternate_case("maxAgeMinutes"), "max_age_minutes");
+    }
+
+    #[test]
+    fn test_collects_every_bad_field() {
+        let args = serde_json::json!({"portfolio_id": "p1", "side": "up", "quantity": "ten"});
+        let mut v = ArgValidator::new(&args);
+
+        assert_eq!(v.required_str(&["portfolioId"]), "p1");
+        v.required_str(&["symbol"]);
+        v.required_one_of(&["side"], &["buy", "sell"]);
+        v.required_positive(&["quantity"]);
+
+        let errors = v.finish().unwrap_err();
+        let fields: Vec<&str> = errors.iter().map(|e| e.field.as_str()).collect();
+        assert_eq!(fields, vec!["symbol", "side", "quantity"]);
+        assert_eq!(errors[0].message, "Missing 'symbol' parameter");
+        assert_eq!(errors[1].message, "'side' must be one of: buy, sell");
+        assert_eq!(errors[2].message, "'quantity' must be a number");
+    }
+
+    #[test]
+    fn test_supplied_id_is_used_verbatim() {
+        let args = serde_json::json!({"id": "order-42", "clientId": "ignored"});
+        let mut v = ArgValidator::new(&args);
+        assert_eq!(v.id_or_generated(), "order-42");
+
+        let args = serde_json::json!({"client_id": "import:7"});
+        let mut v = ArgValidator::new(&args);
+        assert_eq!(v.id_or_generated(), "import:7");
+        assert!(v.finish().is_ok());
+    }
+
+    #[test]
+    fn test_missing_id_generates_v4_uuid() {
+        let args = serde_json::json!({"id": null});
+        let mut v = ArgValidator::new(&args);
+        let id = uuid::Uuid::parse_str(&v.id_or_generated()).unwrap();
+        assert_eq!(id.get_version_num(), 4);
+        assert!(v.finish().is_ok());
+    }
+
+    #[test]
+    fn test_malformed_ids_are_rejected() {
+        for id in [serde_json::json!(""), serde_json::json!("a b"), serde_json::json!("x".repeat(129)), serde_json::json!(7)] {
+            let args = serde_json::json!({ "id": id });
+            let mut v = ArgValidator::new(&args);
+            v.id_or_generated();
+            let errors = v.finish().unwrap_err();
+            assert_eq!(errors[0].field, "id", "{}", args);
+        }
+    }
+
+    #[test]
+    fn test_dates_are_normalized_to_iso() {
+        assert_eq!(normalize_date("start_date", "2024-03-05").unwrap(), "2024-03-05");
+        assert_eq!(normalize_date("start_date", " 2024/3/5 ").unwrap(), "2024-03-05");
+        assert_eq!(normalize_date("start_date", "03/05/2024").unwrap(), "2024-03-05");
+        assert_eq!(normalize_date("start_date", "2024-03-05T23:30:00-05:00").unwrap(), "2024-03-05");
+
+        let err = normalize_date("end_date", "2024-02-30").unwrap_err();
+        assert!(err.starts_with("Invalid 'end_date' parameter: '2024-02-30'"), "{}", err);
+        assert!(normalize_date("end_date", "last week").is_err());
+
+        assert!(check_date_order(Some("2024-01-01"), Some("2024-01-01")).is_ok());
+        assert!(check_date_order(Some("2024-02-01"), Some("2024-01-01")).is_err());
+        assert!(check_date_order(Some("2024-02-01"), None).is_ok());
+    }
+
+    #[test]
+    fn test_optional_json_rejects_wrong_shape() {
+        let args = serde_json::json!({"fees": {"maker": 0.001, "taker": 0.002}, "limits": "none"});
+        let mut v = ArgValidator::new(&args);
+        assert_eq!(v.optional_json::<std::collections::HashMap<String, f64>>(&["fees"]).unwrap()["taker"], 0.002);
+        assert_eq!(v.optional_json::<Vec<f64>>(&["missing"]), None);
+        assert_eq!(v.optional_json::<Vec<f64>>(&["limits"]), None);
+
+        let errors = v.finish().unwrap_err();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].field, "limits");
+        assert!(errors[0].message.starts_with("Invalid 'limits' parameter: "), "{}", errors[0].message);
+    }
+
+    #[test]
+    fn test_valid_args_pass() {
+        let args = serde_json::json!({"price": 10.5});
+        let mut v = ArgValidator::new(&args);
+        assert_eq!(v.required_positive(&["price"]), 10.5);
+        assert!(v.finish().is_ok());
+    }
+}
@@ -13,12 +13,57 @@ const CREATE_NO_WINDOW: u32 = 0x08000000;
 
 static SETUP_RUNNING: Mutex<bool> = Mutex::new(false);
 
+/// Ordered setup phases and their share of overall progress (sums to 100)
+const SETUP_PHASES: [(&str, u32); 9] = [
+    ("python", 15),
+    ("bun", 5),
+    ("llvm", 3),
+    ("talib", 2),
+    ("uv", 5),
+    ("venv-numpy1", 5),
+    ("packages-numpy1", 30),
+    ("venv-numpy2", 5),
+    ("packages-numpy2", 30),
+];
+
+/// Latest progress, kept for `get_setup_progress` polling (events can be missed
+/// if the wizard window reloads mid-setup)
+struct SetupTracker {
+    phase: Option<&'static str>,
+    completed: Vec<&'static str>,
+    latest: Option<SetupProgress>,
+    last_error: Option<String>,
+}
+
+static SETUP_TRACKER: Mutex<SetupTracker> = Mutex::new(SetupTracker {
+    phase: None,
+    completed: Vec::new(),
+    latest: None,
+    last_error: None,
+});
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SetupProgress {
     pub step: String,
     pub progress: u8,
     pub message: String,
     pub is_error: bool,
+    /// Current phase from `SETUP_PHASES` (e.g. "packages-numpy1")
+    #[serde(default)]
+    pub phase: Option<String>,
+    /// Progress across the whole setup, 0-100
+    #[serde(default)]
+    pub overall_progress: u8,
+}
+
+/// Polling snapshot of a running (or finished) setup
+#[derive(Debug, Clone, Serialize)]
+pub struct SetupProgressSnapshot {
+    pub running: bool,
+    pub phase: Option<String>,
+    pub completed_phases: Vec<String>,
+    pub latest: Option<SetupProgress>,
+    pub last_error: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -33,20 +78,78 @@ const PYTHON_VERSION: &str = "3.12.7";
 const BUN_VERSION: &str = "1.1.0";
 
 fn emit_progress(app: &AppHandle, step: &str, progress: u8, message: &str, is_error: bool) {
-    let _ = app.emit("setup-progress", SetupProgress {
-        step: step.to_string(),
-        progress,
-        message: message.to_string(),
-        is_error,
-    });
+    let event = {
+        let mut tracker = SETUP_TRACKER.lock().unwrap();
+        let overall_progress = if step == "complete" {
+            100
+        } else {
+            overall_progress(tracker.phase, progress)
+        };
+        let event = SetupProgress {
+            step: step.to_string(),
+            progress,
+            message: message.to_string(),
+            is_error,
+            phase: tracker.phase.map(str::to_string),
+            overall_progress,
+        };
+        if is_error {
+            tracker.last_error = Some(message.to_string());
+        }
+        tracker.latest = Some(event.clone());
+        event
+    };
+
+    let _ = app.emit("setup-progress", event);
     eprintln!("[SETUP] [{}] {}% - {}", step, progress, message);
 }
 
+/// Overall percentage for `step_progress` within `phase`
+fn overall_progress(phase: Option<&str>, step_progress: u8) -> u8 {
+    let phase = match phase {
+        Some(phase) => phase,
+        None => return 0,
+    };
+    let mut done = 0;
+    for (name, weight) in SETUP_PHASES {
+        if name == phase {
+            return (done + weight * u32::from(step_progress.min(100)) / 100) as u8;
+        }
+        done += weight;
+    }
+    0
+}
+
+/// Mark the previous phase complete and start `phase`
+fn begin_phase(phase: &'static str) {
+    let mut tracker = SETUP_TRACKER.lock().unwrap();
+    if let Some(previous) = tracker.phase.take() {
+        if !tracker.completed.contains(&previous) {
+            tracker.completed.push(previous);
+        }
+    }
+    tracker.phase = Some(phase);
+}
+
+fn reset_tracker() {
+    let mut tracker = SETUP_TRACKER.lock().unwrap();
+    tracker.phase = None;
+    tracker.completed.clear();
+    tracker.latest = None;
+    tracker.last_error = None;
+}
+
 fn is_dev_mode() -> bool {
     cfg!(debug_assertions)
 }
 
 fn get_install_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    // Portable mode: keep Python/Bun next to the database in the chosen directory
+    if let Some(data_dir) = crate::utils::data_dir::portable_data_dir() {
+        eprintln!("[SETUP] Portable install directory: {:?}", data_dir);
+        return Ok(data_dir);
+    }
+
     if is_dev_mode() {
         // Dev mode: use OS-specific user directory
         let base_dir = if cfg!(target_os = "windows") {
@@ -177,48 +280,62 @@ fn check_talib() -> (bool, Option<String>) {
     (true, Some("Check manually".to_string()))
 }
 
-/// Check if Python packages are installed in both venvs
-fn check_packages(install_dir: &PathBuf) -> bool {
-    let venv1_python = if cfg!(target_os = "windows") {
-        install_dir.join("venv-numpy1/Scripts/python.exe")
-    } else {
-        install_dir.join("venv-numpy1/bin/python3")
-    };
+/// Virtual environments: (venv name, requirements file, label, key imports)
+const VENVS: [(&str, &str, &str, &str); 2] = [
+    ("venv-numpy1", "requirements-numpy1.txt", "NumPy 1.x", "import numpy, pandas, vectorbt, backtesting, gluonts"),
+    ("venv-numpy2", "requirements-numpy2.txt", "NumPy 2.x", "import numpy, pandas, yfinance, vnpy"),
+];
 
-    let venv2_python = if cfg!(target_os = "windows") {
-        install_dir.join("venv-numpy2/Scripts/python.exe")
+fn venv_python(install_dir: &PathBuf, venv_name: &str) -> PathBuf {
+    if cfg!(target_os = "windows") {
+        install_dir.join(format!("{}/Scripts/python.exe", venv_name))
     } else {
-        install_dir.join("venv-numpy2/bin/python3")
-    };
+        install_dir.join(format!("{}/bin/python3", venv_name))
+    }
+}
 
-    if !venv1_python.exists() || !venv2_python.exists() {
-        eprintln!("[SETUP] Venvs don't exist - need to install packages");
+/// Check that a venv exists and its key packages import
+fn check_venv_packages(install_dir: &PathBuf, venv_name: &str, imports: &str) -> bool {
+    let python = venv_python(install_dir, venv_name);
+    if !python.exists() {
+        eprintln!("[SETUP] {} doesn't exist - need to install packages", venv_name);
         return false;
     }
 
-    // Check numpy1 venv for key packages
-    let mut cmd1 = Command::new(&venv1_python);
-    cmd1.args(&["-c", "import numpy, pandas, vectorbt, backtesting, gluonts"]);
+    let mut cmd = Command::new(&python);
+    cmd.args(&["-c", imports]);
     #[cfg(target_os = "windows")]
-    cmd1.creation_flags(CREATE_NO_WINDOW);
-    let check1 = cmd1.output().map(|o| o.status.success()).unwrap_or(false);
+    cmd.creation_flags(CREATE_NO_WINDOW);
+    let ok = cmd.output().map(|o| o.status.success()).unwrap_or(false);
 
-    if !check1 {
-        eprintln!("[SETUP] NumPy 1.x venv packages check failed - need to reinstall");
+    if !ok {
+        eprintln!("[SETUP] {} packages check failed - need to reinstall", venv_name);
     }
+    ok
+}
 
-    // Check numpy2 venv for key packages
-    let mut cmd2 = Command::new(&venv2_python);
-    cmd2.args(&["-c", "import numpy, pandas, yfinance, vnpy"]);
-    #[cfg(target_os = "windows")]
-    cmd2.creation_flags(CREATE_NO_WINDOW);
-    let check2 = cmd2.output().map(|o| o.status.success()).unwrap_or(false);
+/// Check if Python packages are installed in both venvs
+fn check_packages(install_dir: &PathBuf) -> bool {
+    VENVS
+        .iter()
+        .map(|(venv, _, _, imports)| check_venv_packages(install_dir, venv, imports))
+        .fold(true, |all, ok| all && ok)
+}
 
-    if !check2 {
-        eprintln!("[SETUP] NumPy 2.x venv packages check failed - need to reinstall");
-    }
+/// Locate the UV executable installed into the bundled Python
+fn find_uv(install_dir: &PathBuf) -> Option<PathBuf> {
+    let candidates = if cfg!(target_os = "windows") {
+        vec![install_dir.join("python/Scripts/uv.exe")]
+    } else if cfg!(target_os = "macos") {
+        vec![
+            install_dir.join("python/Versions/3.12/bin/uv"),
+            install_dir.join("python/bin/uv"),
+        ]
+    } else {
+        vec![install_dir.join("python/bin/uv")]
+    };
 
-    check1 && check2
+    candidates.into_iter().find(|p| p.exists())
 }
 
 /// Install Python
@@ -708,19 +825,7 @@ async fn create_venv(app: &AppHandle, install_dir: &PathBuf, venv_name: &str) ->
         install_dir.join("python/bin/python3")
     };
 
-    // Find UV executable - check multiple possible locations
-    let uv_candidates = if cfg!(target_os = "windows") {
-        vec![install_dir.join("python/Scripts/uv.exe")]
-    } else if cfg!(target_os = "macos") {
-        vec![
-            install_dir.join("python/Versions/3.12/bin/uv"),
-            install_dir.join("python/bin/uv"),
-        ]
-    } else {
-        vec![install_dir.join("python/bin/uv")]
-    };
-
-    let uv_exe = uv_candidates.iter().find(|p| p.exists());
+    let uv_exe = find_uv(install_dir);
 
     let venv_path = install_dir.join(venv_name);
 
@@ -775,25 +880,9 @@ async fn install_packages_in_venv(
         install_dir.join("python/bin/python3")
     };
 
-    let venv_python = if cfg!(target_os = "windows") {
-        install_dir.join(format!("{}/Scripts/python.exe", venv_name))
-    } else {
-        install_dir.join(format!("{}/bin/python3", venv_name))
-    };
-
-    // Find UV executable - check multiple possible locations
-    let uv_candidates = if cfg!(target_os = "windows") {
-        vec![install_dir.join("python/Scripts/uv.exe")]
-    } else if cfg!(target_os = "macos") {
-        vec![
-            install_dir.join("python/Versions/3.12/bin/uv"),
-            install_dir.join("python/bin/uv"),
-        ]
-    } else {
-        vec![install_dir.join("python/bin/uv")]
-    };
+    let venv_python = venv_python(install_dir, venv_name);
 
-    let uv_exe = uv_candidates.iter().find(|p| p.exists());
+    let uv_exe = find_uv(install_dir);
 
     let requirements_path = app.path()
         .resolve(&format!("resources/{}", requirements_file), tauri::path::BaseDirectory::Resource)
@@ -914,6 +1003,10 @@ pub async fn run_setup(app: AppHandle) -> Result<String, String> {
         *lock = true;
     } // Lock dropped here
 
+    reset_tracker();
+
+    // Every phase checks what is already in place first, so re-running after a
+    // failure resumes where it stopped instead of starting over.
     let result = async {
         let install_dir = get_install_dir(&app)?;
         std::fs::create_dir_all(&install_dir)
@@ -923,6 +1016,7 @@ pub async fn run_setup(app: AppHandle) -> Result<String, String> {
         emit_progress(&app, "init", 0, "Starting setup...", false);
 
         // Python
+        begin_phase("python");
         let (python_installed, python_version) = check_python(&install_dir);
         if !python_installed {
             install_python(&app, &install_dir).await?;
@@ -932,6 +1026,7 @@ pub async fn run_setup(app: AppHandle) -> Result<String, String> {
         }
 
         // Bun
+        begin_phase("bun");
         let (bun_installed, bun_version) = check_bun(&install_dir);
         if !bun_installed {
             install_bun(&app, &install_dir).await?;
@@ -941,6 +1036,7 @@ pub async fn run_setup(app: AppHandle) -> Result<String, String> {
         }
 
         // LLVM (macOS only, needed for llvmlite/numba in financial packages)
+        begin_phase("llvm");
         let (llvm_installed, llvm_path) = check_llvm();
         if !llvm_installed {
             install_llvm(&app).await?;
@@ -950,6 +1046,7 @@ pub async fn run_setup(app: AppHandle) -> Result<String, String> {
         }
 
         // TA-Lib (needed for ta-lib Python wrapper used by vnpy)
+        begin_phase("talib");
         let (talib_installed, talib_path) = check_talib();
         if !talib_installed {
             install_talib(&app).await?;
@@ -959,40 +1056,41 @@ pub async fn run_setup(app: AppHandle) -> Result<String, String> {
         }
 
         // UV (fast package manager)
-        install_uv(&app, &install_dir).await?;
-
-        // Create venvs and install packages
-        if !check_packages(&install_dir) {
-            // Create venv-numpy1 for NumPy 1.x libraries
-            emit_progress(&app, "venv", 0, "Creating NumPy 1.x environment...", false);
-            create_venv(&app, &install_dir, "venv-numpy1").await?;
-
-            // Install numpy1 packages
-            install_packages_in_venv(
-                &app,
-                &install_dir,
-                "venv-numpy1",
-                "requirements-numpy1.txt",
-                "NumPy 1.x"
-            ).await?;
-
-            // Create venv-numpy2 for NumPy 2.x libraries
-            emit_progress(&app, "venv", 50, "Creating NumPy 2.x environment...", false);
-            create_venv(&app, &install_dir, "venv-numpy2").await?;
-
-            // Install numpy2 packages
-            install_packages_in_venv(
-                &app,
-                &install_dir,
-                "venv-numpy2",
-                "requirements-numpy2.txt",
-                "NumPy 2.x"
-            ).await?;
+        begin_phase("uv");
+        if find_uv(&install_dir).is_none() {
+            install_uv(&app, &install_dir).await?;
         } else {
-            eprintln!("[SETUP] Packages already installed in both venvs");
-            emit_progress(&app, "packages", 100, "Packages already installed", false);
+            emit_progress(&app, "uv", 100, "UV already installed", false);
+        }
+
+        // Create venvs and install packages, skipping any venv that is already complete
+        for (venv_name, requirements, label, imports) in VENVS {
+            let (venv_phase, packages_phase) = if venv_name == "venv-numpy1" {
+                ("venv-numpy1", "packages-numpy1")
+            } else {
+                ("venv-numpy2", "packages-numpy2")
+            };
+
+            begin_phase(venv_phase);
+            if check_venv_packages(&install_dir, venv_name, imports) {
+                emit_progress(&app, "venv", 100, &format!("{} already set up", venv_name), false);
+                begin_phase(packages_phase);
+                emit_progress(&app, "packages", 100, &format!("{} packages already installed", label), false);
+                continue;
+            }
+
+            if venv_python(&install_dir, venv_name).exists() {
+                // Keep the partially installed venv; uv only installs what is missing
+                emit_progress(&app, "venv", 100, &format!("{} already exists", venv_name), false);
+            } else {
+                create_venv(&app, &install_dir, venv_name).await?;
+            }
+
+            begin_phase(packages_phase);
+            install_packages_in_venv(&app, &install_dir, venv_name, requirements, label).await?;
         }
 
+        begin_phase("complete");
         emit_progress(&app, "complete", 100, "Setup complete!", false);
 
         // Initialize worker pool in background (non-blocking)
@@ -1010,8 +1108,222 @@ pub async fn run_setup(app: AppHandle) -> Result<String, String> {
         Ok("Setup complete".to_string())
     }.await;
 
+    if let Err(e) = &result {
+        let (step, progress) = {
+            let tracker = SETUP_TRACKER.lock().unwrap();
+            let progress = tracker.latest.as_ref().map(|p| p.progress).unwrap_or(0);
+            (tracker.phase.unwrap_or("init"), progress)
+        };
+        emit_progress(&app, step, progress, e, true);
+    }
+
+    let mut lock = SETUP_RUNNING.lock().unwrap();
+    *lock = false;
+
+    result
+}
+
+/// Current setup progress, for polling when `setup-progress` events were missed
+#[tauri::command]
+pub fn get_setup_progress() -> Result<SetupProgressSnapshot, String> {
+    let running = *SETUP_RUNNING.lock().unwrap();
+    let tracker = SETUP_TRACKER.lock().unwrap();
+
+    Ok(SetupProgressSnapshot {
+        running,
+        phase: tracker.phase.map(str::to_string),
+        completed_phases: tracker.completed.iter().map(|p| p.to_string()).collect(),
+        latest: tracker.latest.clone(),
+        last_error: tracker.last_error.clone(),
+    })
+}
+
+/// Health of one installed component
+#[derive(Debug, Clone, Serialize)]
+pub struct ComponentStatus {
+    pub component: String,
+    pub ok: bool,
+    pub detail: Option<String>,
+    /// Whether `repair_setup` can fix this component
+    pub repairable: bool,
+}
+
+/// Result of `verify_setup`
+#[derive(Debug, Clone, Serialize)]
+pub struct SetupVerification {
+    pub install_dir: String,
+    pub healthy: bool,
+    pub components: Vec<ComponentStatus>,
+}
+
+/// Result of `repair_setup`
+#[derive(Debug, Clone, Serialize)]
+pub struct SetupRepairResult {
+    pub repaired: Vec<String>,
+    pub errors: std::collections::HashMap<String, String>,
+    pub verification: SetupVerification,
+}
+
+/// Components in repair order (later ones depend on earlier ones)
+const REPAIRABLE_COMPONENTS: [&str; 7] = ["python", "bun", "llvm", "talib", "uv", "venv-numpy1", "venv-numpy2"];
+
+fn component(component: &str, ok: bool, detail: Option<String>, repairable: bool) -> ComponentStatus {
+    ComponentStatus {
+        component: component.to_string(),
+        ok,
+        detail,
+        repairable,
+    }
+}
+
+fn verify_components(app: &AppHandle, install_dir: &PathBuf) -> SetupVerification {
+    let mut components = Vec::new();
+
+    let (python_ok, python_version) = check_python(install_dir);
+    components.push(component("python", python_ok, python_version, true));
+
+    let (bun_ok, bun_version) = check_bun(install_dir);
+    components.push(component("bun", bun_ok, bun_version, true));
+
+    #[cfg(target_os = "macos")]
+    {
+        let (llvm_ok, llvm_path) = check_llvm();
+        components.push(component("llvm", llvm_ok, llvm_path, true));
+        let (talib_ok, talib_path) = check_talib();
+        components.push(component("talib", talib_ok, talib_path, true));
+    }
+
+    let uv = find_uv(install_dir);
+    components.push(component("uv", uv.is_some(), uv.map(|p| p.display().to_string()), true));
+
+    for (venv_name, _, label, imports) in VENVS {
+        let ok = check_venv_packages(install_dir, venv_name, imports);
+        let detail = if ok {
+            format!("{} packages import", label)
+        } else if venv_python(install_dir, venv_name).exists() {
+            format!("{} packages missing or broken", label)
+        } else {
+            format!("{} not created", venv_name)
+        };
+        components.push(component(venv_name, ok, Some(detail), true));
+    }
+
+    // Scripts ship with the app; a missing directory means the install itself is damaged
+    let scripts = crate::utils::python::get_script_path(app, "yfinance_data.py");
+    components.push(match scripts {
+        Ok(path) => component(
+            "scripts",
+            true,
+            path.parent().map(|p| p.display().to_string()),
+            false,
+        ),
+        Err(e) => component("scripts", false, Some(format!("{} - reinstall the application", e)), false),
+    });
+
+    SetupVerification {
+        install_dir: install_dir.display().to_string(),
+        healthy: components.iter().all(|c| c.ok),
+        components,
+    }
+}
+
+/// Check every installed component and report per-component status
+#[tauri::command]
+pub fn verify_setup(app: AppHandle) -> Result<SetupVerification, String> {
+    let install_dir = get_install_dir(&app)?;
+    Ok(verify_components(&app, &install_dir))
+}
+
+/// Reinstall only the broken components
+///
+/// `components` limits the repair to the named components; when omitted every
+/// failing repairable component is fixed. Healthy components are left untouched.
+#[tauri::command]
+pub async fn repair_setup(app: AppHandle, components: Option<Vec<String>>) -> Result<SetupRepairResult, String> {
+    if let Some(requested) = &components {
+        for name in requested {
+            if !REPAIRABLE_COMPONENTS.contains(&name.as_str()) {
+                return Err(format!(
+                    "Unknown or non-repairable component '{}'. Expected one of: {}",
+                    name,
+                    REPAIRABLE_COMPONENTS.join(", ")
+                ));
+            }
+        }
+    }
+
+    {
+        let mut lock = SETUP_RUNNING.lock().unwrap();
+        if *lock {
+            return Err("Setup already running".to_string());
+        }
+        *lock = true;
+    }
+
+    let result = async {
+        let install_dir = get_install_dir(&app)?;
+        let before = verify_components(&app, &install_dir);
+
+        let broken: Vec<&str> = REPAIRABLE_COMPONENTS
+            .iter()
+            .copied()
+            .filter(|name| {
+                components
+                    .as_ref()
+                    .is_none_or(|requested| requested.iter().any(|r| r == name))
+            })
+            .filter(|name| before.components.iter().any(|c| c.component == *name && !c.ok))
+            .collect();
+
+        let mut repaired = Vec::new();
+        let mut errors = std::collections::HashMap::new();
+
+        for name in broken {
+            let outcome = match name {
+                "python" => install_python(&app, &install_dir).await,
+                "bun" => install_bun(&app, &install_dir).await,
+                "llvm" => install_llvm(&app).await,
+                "talib" => install_talib(&app).await,
+                "uv" => install_uv(&app, &install_dir).await,
+                venv_name => repair_venv(&app, &install_dir, venv_name).await,
+            };
+            match outcome {
+                Ok(()) => repaired.push(name.to_string()),
+                Err(e) => {
+                    emit_progress(&app, name, 0, &e, true);
+                    errors.insert(name.to_string(), e);
+                }
+            }
+        }
+
+        Ok(SetupRepairResult {
+            repaired,
+            errors,
+            verification: verify_components(&app, &install_dir),
+        })
+    }
+    .await;
+
     let mut lock = SETUP_RUNNING.lock().unwrap();
     *lock = false;
 
     result
 }
+
+/// Recreate a venv from scratch and reinstall its requirements
+async fn repair_venv(app: &AppHandle, install_dir: &PathBuf, venv_name: &str) -> Result<(), String> {
+    let (_, requirements, label, _) = VENVS
+        .iter()
+        .find(|(name, ..)| *name == venv_name)
+        .copied()
+        .ok_or_else(|| format!("Unknown virtual environment: {}", venv_name))?;
+
+    let venv_dir = install_dir.join(venv_name);
+    if venv_dir.exists() {
+        std::fs::remove_dir_all(&venv_dir)
+            .map_err(|e| format!("Failed to remove broken {}: {}", venv_name, e))?;
+    }
+
+    create_venv(app, install_dir, venv_name).await?;
+    install_packages_in_venv(app, install_dir, venv_name, requirements, label).await
+}
@@ -0,0 +1,158 @@
+// Request cancellation - abort in-flight data fetches by request id
+//
+// What: A registry of `CancellationToken`s keyed by a frontend-chosen request id.
+//       `cancel_request(id)` fires the token and the command returns
+//       "Request cancelled".
+// Why:  Closing a panel that started a slow `get_historical_data` left the fetch
+//       running and holding an HTTP connection or a Python process.
+// How:  Commands call `register(request_id)` and wrap their work in
+//       `guard.run(fut)`. On cancel, the future is dropped: reqwest closes the
+//       connection, and yfinance subprocesses are spawned with `kill_on_drop`, so
+//       the Python process is killed as well. The guard removes its entry when the
+//       command finishes, so ids can be reused. `with_timeout` adds a deadline
+//       (see `command_timeout`) that drops the work the same way.
+
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use tokio_util::sync::CancellationToken;
+
+pub const CANCELLED_ERROR: &str = "Request cancelled";
+
+static REQUESTS: Lazy<Mutex<HashMap<String, (u64, CancellationToken)>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+static NEXT_GENERATION: AtomicU64 = AtomicU64::new(0);
+
+/// Registration of one cancellable request; unregisters on drop
+pub struct RequestGuard {
+    id: Option<String>,
+    generation: u64,
+    token: CancellationToken,
+    /// When the command times out, and the error it then returns
+    deadline: Option<(tokio::time::Instant, String)>,
+}
+
+/// Register `request_id` (if any) so `cancel` can abort it
+///
+/// Re-using the id of a request that is still running cancels the older one.
+pub fn register(request_id: Option<String>) -> RequestGuard {
+    let token = CancellationToken::new();
+    let generation = NEXT_GENERATION.fetch_add(1, Ordering::Relaxed);
+    if let Some(id) = &request_id {
+        if let Some((_, previous)) = REQUESTS.lock().insert(id.clone(), (generation, token.clone())) {
+            previous.cancel();
+        }
+    }
+    RequestGuard {
+        id: request_id,
+        generation,
+        token,
+        deadline: None,
+    }
+}
+
+/// Cancel a registered request, returning whether it was still running
+pub fn cancel(request_id: &str) -> bool {
+    match REQUESTS.lock().remove(request_id) {
+        Some((_, token)) => {
+            token.cancel();
+            true
+        }
+        None => false,
+    }
+}
+
+impl RequestGuard {
+    pub fn is_cancelled(&self) -> bool {
+        self.token.is_cancelled()
+    }
+
+    /// Also stop `run` once `command`'s timeout has passed, counted from now
+    pub fn with_timeout(mut self, command: &str) -> Self {
+        let timeout = super::command_timeout::timeout_for(command);
+        self.deadline = Some((
+            tokio::time::Instant::now() + timeout,
+            super::command_timeout::timeout_error(command, timeout),
+        ));
+        self
+    }
+
+    /// Run `fut` until it finishes, the request is cancelled or its deadline
+    /// passes (the future is then dropped)
+    pub async fn run<T, Fut>(&self, fut: Fut) -> Result<T, String>
+    where
+        Fut: Future<Output = T>,
+    {
+        let expired = async {
+            match &self.deadline {
+                Some((at, _)) => tokio::time::sleep_until(*at).await,
+                None => std::future::pending().await,
+            }
+        };
+        tokio::select! {
+            biased;
+            _ = self.token.cancelled() => Err(CANCELLED_ERROR.to_string()),
+            _ = expired => Err(self.deadline.as_ref().map(|(_, error)| error.clone()).unwrap_or_default()),
+            value = fut => Ok(value),
+        }
+    }
+}
+
+impl Drop for RequestGuard {
+    fn drop(&mut self) {
+        if let Some(id) = &self.id {
+            let mut requests = REQUESTS.lock();
+            // The id may have been re-registered by a newer request; leave that one alone
+            if requests.get(id).is_some_and(|(generation, _)| *generation == self.generation) {
+                requests.remove(id);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_cancel_aborts_running_request() {
+        let guard = register(Some("test-cancel".to_string()));
+        let cancel_task = tokio::spawn(async {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            cancel("test-cancel")
+        });
+
+        let result = guard
+            .run(tokio::time::sleep(Duration::from_secs(30)))
+            .await;
+
+        assert_eq!(result, Err(CANCELLED_ERROR.to_string()));
+        assert!(cancel_task.await.unwrap());
+        assert!(!cancel("test-cancel"));
+    }
+
+    #[tokio::test]
+    async fn test_deadline_drops_the_work() {
+        let mut guard = register(None);
+        guard.deadline = Some((
+            tokio::time::Instant::now() + Duration::from_millis(10),
+            "slow_command timed out after 0s".to_string(),
+        ));
+
+        let result = guard.run(tokio::time::sleep(Duration::from_secs(30))).await;
+        assert_eq!(result, Err("slow_command timed out after 0s".to_string()));
+        assert_eq!(guard.run(async { 1 }).await, Err("slow_command timed out after 0s".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_finished_request_unregisters() {
+        {
+            let guard = register(Some("test-done".to_string()));
+            assert_eq!(guard.run(async { 42 }).await, Ok(42));
+        }
+        assert!(!cancel("test-done"));
+    }
+}
@@ -0,0 +1,207 @@
+// Request coalescing - share one in-flight computation between identical callers
+//
+// What: `Coalescer::run(key, f)` runs `f` once per key at a time; callers that
+//       arrive while a call with the same key is in flight block until it finishes
+//       and receive a clone of its result.
+// Why:  Two panels asking for the same macro dataset at once used to spawn two
+//       Python processes doing identical work. Sitting below any result cache, this
+//       also stops cache-miss stampedes from multiplying load.
+// How:  A map of key -> slot (Mutex + Condvar). The first caller becomes the leader
+//       and computes; followers wait on the slot. The entry is removed as soon as
+//       the leader finishes, so results are never reused after the fact.
+
+use parking_lot::{Condvar, Mutex};
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::Arc;
+
+enum SlotState<V> {
+    Pending,
+    Done(V),
+    /// Leader panicked; followers fall back to running their own call
+    Abandoned,
+}
+
+struct Slot<V> {
+    state: Mutex<SlotState<V>>,
+    ready: Condvar,
+}
+
+/// Deduplicates concurrent identical calls keyed by `K`
+pub struct Coalescer<K, V> {
+    inflight: Mutex<HashMap<K, Arc<Slot<V>>>>,
+}
+
+impl<K: Eq + Hash + Clone, V: Clone> Coalescer<K, V> {
+    pub fn new() -> Self {
+        Self {
+            inflight: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Run `f` for `key`, or wait for the identical call already in flight
+    pub fn run<F: FnOnce() -> V>(&self, key: K, f: F) -> V {
+        let (slot, is_leader) = {
+            let mut inflight = self.inflight.lock();
+            match inflight.get(&key) {
+                Some(slot) => (slot.clone(), false),
+                None => {
+                    let slot = Arc::new(Slot {
+                        state: Mutex::new(SlotState::Pending),
+                        ready: Condvar::new(),
+                    });
+                    inflight.insert(key.clone(), slot.clone());
+                    (slot, true)
+                }
+            }
+        };
+
+        if is_leader {
+            let guard = LeaderGuard { coalescer: self, key: &key, slot: &slot, finished: false };
+            let value = f();
+            guard.finish(value.clone());
+            return value;
+        }
+
+        match Self::wait(&slot) {
+            Some(value) => value,
+            None => f(),
+        }
+    }
+
+    /// Number of calls currently in flight
+    pub fn inflight_count(&self) -> usize {
+        self.inflight.lock().len()
+    }
+
+    fn wait(slot: &Slot<V>) -> Option<V> {
+        let wait = || {
+            let mut state = slot.state.lock();
+            loop {
+                match &*state {
+                    SlotState::Pending => slot.ready.wait(&mut state),
+                    SlotState::Done(value) => return Some(value.clone()),
+                    SlotState::Abandoned => return None,
+                }
+            }
+        };
+
+        // Don't starve a multi-threaded runtime while parked on the condvar;
+        // `block_in_place` panics on a current-thread runtime, which has no other
+        // worker to hand its tasks to, so that one just blocks
+        let multi_thread = tokio::runtime::Handle::try_current()
+            .is_ok_and(|handle| handle.runtime_flavor() == tokio::runtime::RuntimeFlavor::MultiThread);
+        if multi_thread {
+            tokio::task::block_in_place(wait)
+        } else {
+            wait()
+        }
+    }
+}
+
+impl<K: Eq + Hash + Clone, V: Clone> Default for Coalescer<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Publishes the leader's result (or abandonment on panic) and clears the key
+struct LeaderGuard<'a, K: Eq + Hash + Clone, V: Clone> {
+    coalescer: &'a Coalescer<K, V>,
+    key: &'a K,
+    slot: &'a Arc<Slot<V>>,
+    finished: bool,
+}
+
+impl<'a, K: Eq + Hash + Clone, V: Clone> LeaderGuard<'a, K, V> {
+    fn finish(mut self, value: V) {
+        self.publish(SlotState::Done(value));
+        self.finished = true;
+    }
+
+    fn publish(&self, state: SlotState<V>) {
+        self.coalescer.inflight.lock().remove(self.key);
+        *self.slot.state.lock() = state;
+        self.slot.ready.notify_all();
+    }
+}
+
+impl<'a, K: Eq + Hash + Clone, V: Clone> Drop for LeaderGuard<'a, K, V> {
+    fn drop(&mut self) {
+        if !self.finished {
+            self.publish(SlotState::Abandoned);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Barrier;
+    use std::time::Duration;
+
+    #[test]
+    fn test_identical_concurrent_calls_run_once() {
+        const CALLERS: usize = 8;
+        let coalescer = Arc::new(Coalescer::<(String, Vec<String>), Result<String, String>>::new());
+        let spawned = Arc::new(AtomicUsize::new(0));
+        let barrier = Arc::new(Barrier::new(CALLERS));
+
+        let handles: Vec<_> = (0..CALLERS)
+            .map(|_| {
+                let coalescer = coalescer.clone();
+                let spawned = spawned.clone();
+                let barrier = barrier.clone();
+                std::thread::spawn(move || {
+                    barrier.wait();
+                    let key = (
+                        "government_us_data.py".to_string(),
+                        vec!["comprehensive_treasury_data".to_string(), "2024-01-02".to_string()],
+                    );
+                    coalescer.run(key, || {
+                        // Stands in for the Python subprocess
+                        spawned.fetch_add(1, Ordering::SeqCst);
+                        std::thread::sleep(Duration::from_millis(200));
+                        Ok("{\"data\":[]}".to_string())
+                    })
+                })
+            })
+            .collect();
+
+        for handle in handles {
+            assert_eq!(handle.join().unwrap(), Ok("{\"data\":[]}".to_string()));
+        }
+        assert_eq!(spawned.load(Ordering::SeqCst), 1);
+        assert_eq!(coalescer.inflight_count(), 0);
+    }
+
+    #[test]
+    fn test_different_keys_are_not_coalesced() {
+        let coalescer = Coalescer::<&str, u32>::new();
+        assert_eq!(coalescer.run("a", || 1), 1);
+        assert_eq!(coalescer.run("b", || 2), 2);
+        // Finished calls are not cached
+        assert_eq!(coalescer.run("a", || 3), 3);
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn test_followers_on_a_current_thread_runtime_wait_without_panicking() {
+        let coalescer = Arc::new(Coalescer::<&str, u32>::new());
+        let started = Arc::new(Barrier::new(2));
+        let leader = {
+            let (coalescer, started) = (coalescer.clone(), started.clone());
+            std::thread::spawn(move || {
+                coalescer.run("key", || {
+                    started.wait();
+                    std::thread::sleep(Duration::from_millis(100));
+                    1
+                })
+            })
+        };
+
+        started.wait();
+        assert_eq!(coalescer.run("key", || 2), 1);
+        assert_eq!(leader.join().unwrap(), 1);
+    }
+}
@@ -0,0 +1,116 @@
+// Command Timeouts - upper bound on how long a command may run
+//
+// What: Commands that wait on Python or an upstream HTTP API fail with
+//       "<command> timed out after Ns" once they have run longer than
+//       `command_timeout_secs` (default 600), or than their entry in
+//       `command_timeouts`, a JSON object such as
+//       `{"get_market_quotes": 30, "execute_python_backtest": 3600}`. Python
+//       worker-pool scripts are keyed by file name, e.g. `"yfinance_data.py"`.
+// Why:  A hung yfinance request or stuck subprocess froze its feature with no
+//       feedback; the desktop invoke path had no upper bound at all.
+// How:  The timeout is a deadline on the command's `cancellation::RequestGuard`,
+//       so expiry drops the work the same way a cancel does: reqwest closes the
+//       connection and `kill_on_drop` subprocesses are killed. A worker-pool task
+//       that times out while running kills its worker, which restarts for the
+//       next task. The two settings are parsed once and kept until a settings
+//       write or profile switch calls `invalidate`, so a Python call costs no
+//       database read.
+
+use super::cancellation;
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
+use std::collections::HashMap;
+use std::future::Future;
+use std::time::Duration;
+
+/// Timeout in seconds for commands without their own entry
+pub const COMMAND_TIMEOUT_SETTING: &str = "command_timeout_secs";
+/// JSON object of command name to timeout in seconds
+pub const COMMAND_TIMEOUTS_SETTING: &str = "command_timeouts";
+
+const DEFAULT_TIMEOUT_SECS: u64 = 600;
+
+/// Parsed timeout settings, cleared by `invalidate`
+static TIMEOUTS: Lazy<RwLock<Option<TimeoutSettings>>> = Lazy::new(|| RwLock::new(None));
+
+struct TimeoutSettings {
+    default: Option<u64>,
+    overrides: HashMap<String, u64>,
+}
+
+impl TimeoutSettings {
+    /// Unreadable values and zero entries are left out, so they fall through
+    fn parse(default: Option<&str>, overrides: Option<&str>) -> Self {
+        let mut overrides = overrides
+            .and_then(|json| serde_json::from_str::<HashMap<String, u64>>(json).ok())
+            .unwrap_or_default();
+        overrides.retain(|_, secs| *secs > 0);
+        Self {
+            default: default.and_then(|v| v.trim().parse::<u64>().ok()).filter(|secs| *secs > 0),
+            overrides,
+        }
+    }
+
+    fn timeout(&self, command: &str) -> Duration {
+        let secs = self.overrides.get(command).copied().or(self.default).unwrap_or(DEFAULT_TIMEOUT_SECS);
+        Duration::from_secs(secs)
+    }
+}
+
+/// Timeout of `command` from settings; an unavailable database means the default,
+/// and the settings are read again next time
+pub fn timeout_for(command: &str) -> Duration {
+    if let Some(settings) = TIMEOUTS.read().as_ref() {
+        return settings.timeout(command);
+    }
+    // Read under the write lock so an `invalidate` racing this can't be overwritten by stale values
+    let mut cached = TIMEOUTS.write();
+    if let Some(settings) = cached.as_ref() {
+        return settings.timeout(command);
+    }
+    let setting = crate::database::operations::get_setting;
+    match (setting(COMMAND_TIMEOUT_SETTING), setting(COMMAND_TIMEOUTS_SETTING)) {
+        (Ok(default), Ok(overrides)) => cached
+            .insert(TimeoutSettings::parse(default.as_deref(), overrides.as_deref()))
+            .timeout(command),
+        _ => Duration::from_secs(DEFAULT_TIMEOUT_SECS),
+    }
+}
+
+/// Re-read the timeout settings on the next command; call after settings change
+pub fn invalidate() {
+    *TIMEOUTS.write() = None;
+}
+
+pub fn timeout_error(command: &str, timeout: Duration) -> String {
+    format!("{} timed out after {}s", command, timeout.as_secs())
+}
+
+/// Run `fut` as `command`, dropping it with a timeout error once the command's timeout passes
+pub async fn with_timeout<T, Fut>(command: &str, fut: Fut) -> Result<T, String>
+where
+    Fut: Future<Output = T>,
+{
+    cancellation::register(None).with_timeout(command).run(fut).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_override_then_default_then_builtin() {
+        let overrides = Some(r#"{"get_market_quotes": 30, "yfinance_data.py": 0}"#);
+        let settings = TimeoutSettings::parse(Some(" 120 "), overrides);
+        assert_eq!(settings.timeout("get_market_quotes"), Duration::from_secs(30));
+        assert_eq!(settings.timeout("get_historical_data"), Duration::from_secs(120));
+        // A zero entry or an unreadable default falls through
+        let settings = TimeoutSettings::parse(Some("45"), overrides);
+        assert_eq!(settings.timeout("yfinance_data.py"), Duration::from_secs(45));
+        assert_eq!(
+            TimeoutSettings::parse(Some("soon"), Some("not json")).timeout("get_market_quotes"),
+            Duration::from_secs(DEFAULT_TIMEOUT_SECS)
+        );
+        assert_eq!(timeout_error("get_fx_rate", Duration::from_secs(30)), "get_fx_rate timed out after 30s");
+    }
+}
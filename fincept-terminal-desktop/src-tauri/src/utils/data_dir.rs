@@ -0,0 +1,143 @@
+// Data directory resolution - portable mode support
+//
+// What: A single user-chosen base directory that holds everything the app writes
+//       (SQLite databases, Python/Bun install + venvs, optional scripts, logs).
+// Why:  Paths used to be resolved independently by `database::pool`, `setup.rs`
+//       and `utils::python`, so there was no way to keep the app self-contained
+//       (e.g. on an external drive).
+// How:  `--data-dir <path>` (CLI) or `FINCEPT_DATA_DIR` (env) selects the base.
+//       Every subsystem asks `portable_data_dir()` first and only falls back to
+//       its platform default when it returns `None`.
+//
+// Precedence (highest first):
+//   1. Per-subsystem overrides: FINCEPT_PYTHON_PATH (web Python executable),
+//      FINCEPT_SCRIPTS_PATH (scripts directory)
+//   2. `--data-dir <path>` / `--data-dir=<path>` command-line flag
+//   3. FINCEPT_DATA_DIR environment variable
+//   4. FINCEPT_APP_DATA_DIR (Python/Bun install dir, non-Tauri runtimes only)
+//   5. Platform defaults (app data dir / fincept-dev in debug builds)
+//
+// Layout under the data directory:
+//   <data-dir>/fincept_terminal.db, financial_notes.db, excel_files.db
+//   <data-dir>/python, bun, venv-numpy1, venv-numpy2
+//   <data-dir>/scripts   (used if present, after FINCEPT_SCRIPTS_PATH)
+//   <data-dir>/logs
+
+use once_cell::sync::OnceCell;
+use std::path::{Path, PathBuf};
+
+pub const DATA_DIR_ENV: &str = "FINCEPT_DATA_DIR";
+pub const DATA_DIR_FLAG: &str = "--data-dir";
+
+static DATA_DIR: OnceCell<Option<PathBuf>> = OnceCell::new();
+
+/// Get the portable data directory, if one was configured
+///
+/// Resolved once per process from the command line, then the environment.
+pub fn portable_data_dir() -> Option<PathBuf> {
+    DATA_DIR
+        .get_or_init(|| {
+            parse_data_dir_arg(std::env::args().skip(1))
+                .or_else(|| std::env::var(DATA_DIR_ENV).ok())
+                .map(|dir| dir.trim().to_string())
+                .filter(|dir| !dir.is_empty())
+                .map(PathBuf::from)
+        })
+        .clone()
+}
+
+/// Validate the configured data directory at startup
+///
+/// Creates the directory if needed and checks it is writable. Returns the
+/// resolved directory, or `None` when portable mode is not in use.
+pub fn validate_data_dir() -> Result<Option<PathBuf>, String> {
+    let dir = match portable_data_dir() {
+        Some(dir) => dir,
+        None => return Ok(None),
+    };
+
+    if !dir.is_absolute() {
+        return Err(format!(
+            "Data directory must be an absolute path, got: {}",
+            dir.display()
+        ));
+    }
+
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to create data directory {}: {}", dir.display(), e))?;
+
+    check_writable(&dir)?;
+
+    Ok(Some(dir))
+}
+
+/// Directory the app writes to: the portable data dir or the database directory
+pub fn base_dir() -> Result<PathBuf, String> {
+    match portable_data_dir() {
+        Some(dir) => Ok(dir),
+        None => crate::database::pool::get_db_path()
+            .map_err(|e| e.to_string())?
+            .parent()
+            .map(Path::to_path_buf)
+            .ok_or_else(|| "Failed to resolve data directory".to_string()),
+    }
+}
+
+/// Directory for log files (portable data dir or the database directory)
+pub fn logs_dir() -> Result<PathBuf, String> {
+    let dir = base_dir()?.join("logs");
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to create logs directory {}: {}", dir.display(), e))?;
+    Ok(dir)
+}
+
+fn check_writable(dir: &Path) -> Result<(), String> {
+    let probe = dir.join(".fincept_write_test");
+    std::fs::write(&probe, b"ok")
+        .map_err(|e| format!("Data directory {} is not writable: {}", dir.display(), e))?;
+    let _ = std::fs::remove_file(&probe);
+    Ok(())
+}
+
+fn parse_data_dir_arg<I: Iterator<Item = String>>(mut args: I) -> Option<String> {
+    while let Some(arg) = args.next() {
+        if arg == DATA_DIR_FLAG {
+            return args.next();
+        }
+        if let Some(value) = arg.strip_prefix("--data-dir=") {
+            return Some(value.to_string());
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> impl Iterator<Item = String> {
+        list.iter().map(|s| s.to_string()).collect::<Vec<_>>().into_iter()
+    }
+
+    #[test]
+    fn test_parse_data_dir_separate_value() {
+        assert_eq!(
+            parse_data_dir_arg(args(&["--data-dir", "/mnt/usb/fincept"])),
+            Some("/mnt/usb/fincept".to_string())
+        );
+    }
+
+    #[test]
+    fn test_parse_data_dir_equals_form() {
+        assert_eq!(
+            parse_data_dir_arg(args(&["--verbose", "--data-dir=/mnt/usb/fincept"])),
+            Some("/mnt/usb/fincept".to_string())
+        );
+    }
+
+    #[test]
+    fn test_parse_data_dir_missing() {
+        assert_eq!(parse_data_dir_arg(args(&["--verbose"])), None);
+        assert_eq!(parse_data_dir_arg(args(&["--data-dir"])), None);
+    }
+}
@@ -0,0 +1,150 @@
+// Upstream HTTP client configuration (proxy, timeouts, user-agent)
+//
+// What: One place that builds outbound HTTP clients for data sources and news,
+//       driven by user settings.
+// Why:  Clients were built with hard-coded defaults, so users behind a corporate
+//       proxy could not fetch data and timeouts were not adjustable.
+// How:  Settings (settings table, category "network"):
+//         http_proxy                 - proxy URL for all schemes (e.g. http://proxy:8080)
+//         http_connect_timeout_secs  - TCP/TLS connect timeout
+//         http_timeout_secs          - total request timeout
+//         http_user_agent            - User-Agent header
+//       When `http_proxy` is unset, the standard HTTP_PROXY / HTTPS_PROXY /
+//       NO_PROXY env vars are honoured (reqwest does this by default). Python
+//       subprocesses (yfinance) get the configured proxy exported as the same env
+//       vars, which `requests` picks up.
+
+use std::process::Command;
+use std::time::Duration;
+
+pub const HTTP_PROXY_SETTING: &str = "http_proxy";
+pub const HTTP_CONNECT_TIMEOUT_SETTING: &str = "http_connect_timeout_secs";
+pub const HTTP_TIMEOUT_SETTING: &str = "http_timeout_secs";
+pub const HTTP_USER_AGENT_SETTING: &str = "http_user_agent";
+
+const DEFAULT_CONNECT_TIMEOUT_SECS: u64 = 5;
+const DEFAULT_TIMEOUT_SECS: u64 = 10;
+const DEFAULT_USER_AGENT: &str = concat!("FinceptTerminal/", env!("CARGO_PKG_VERSION"));
+
+/// Resolved outbound HTTP settings
+#[derive(Debug, Clone, PartialEq)]
+pub struct HttpClientConfig {
+    pub proxy: Option<String>,
+    pub connect_timeout: Duration,
+    pub timeout: Duration,
+    pub user_agent: String,
+}
+
+impl Default for HttpClientConfig {
+    fn default() -> Self {
+        Self {
+            proxy: None,
+            connect_timeout: Duration::from_secs(DEFAULT_CONNECT_TIMEOUT_SECS),
+            timeout: Duration::from_secs(DEFAULT_TIMEOUT_SECS),
+            user_agent: DEFAULT_USER_AGENT.to_string(),
+        }
+    }
+}
+
+impl HttpClientConfig {
+    /// Load from the settings table, falling back to defaults for anything unset
+    pub fn load() -> Self {
+        Self::from_lookup(|key| {
+            crate::database::operations::get_setting(key).ok().flatten()
+        })
+    }
+
+    fn from_lookup<F: Fn(&str) -> Option<String>>(lookup: F) -> Self {
+        let defaults = Self::default();
+        let non_empty = |key: &str| lookup(key).map(|v| v.trim().to_string()).filter(|v| !v.is_empty());
+        let secs = |key: &str| non_empty(key).and_then(|v| v.parse::<u64>().ok()).filter(|v| *v > 0);
+
+        Self {
+            proxy: non_empty(HTTP_PROXY_SETTING),
+            connect_timeout: secs(HTTP_CONNECT_TIMEOUT_SETTING)
+                .map(Duration::from_secs)
+                .unwrap_or(defaults.connect_timeout),
+            timeout: secs(HTTP_TIMEOUT_SETTING)
+                .map(Duration::from_secs)
+                .unwrap_or(defaults.timeout),
+            user_agent: non_empty(HTTP_USER_AGENT_SETTING).unwrap_or(defaults.user_agent),
+        }
+    }
+
+    /// Build a reqwest client with these settings
+    pub fn build_client(&self) -> Result<reqwest::Client, String> {
+        self.build(Some(self.timeout))
+    }
+
+    /// Build a client for long-lived streams: the same settings but no total
+    /// timeout, so the caller decides how long a stream may run
+    pub fn build_streaming_client(&self) -> Result<reqwest::Client, String> {
+        self.build(None)
+    }
+
+    fn build(&self, timeout: Option<Duration>) -> Result<reqwest::Client, String> {
+        let mut builder = reqwest::Client::builder()
+            .connect_timeout(self.connect_timeout)
+            .user_agent(self.user_agent.clone());
+        if let Some(timeout) = timeout {
+            builder = builder.timeout(timeout);
+        }
+
+        if let Some(proxy) = &self.proxy {
+            let proxy = reqwest::Proxy::all(proxy)
+                .map_err(|e| format!("Invalid HTTP proxy '{}': {}", proxy, e))?;
+            builder = builder.proxy(proxy);
+        }
+
+        builder
+            .build()
+            .map_err(|e| format!("Failed to create HTTP client: {}", e))
+    }
+
+    /// Export the configured proxy to a child process (Python `requests` reads these)
+    pub fn apply_to_command(&self, cmd: &mut Command) {
+        if let Some(proxy) = &self.proxy {
+            for key in ["HTTP_PROXY", "HTTPS_PROXY", "http_proxy", "https_proxy"] {
+                cmd.env(key, proxy);
+            }
+        }
+    }
+}
+
+/// Build an HTTP client from the current settings
+pub fn build_client() -> Result<reqwest::Client, String> {
+    HttpClientConfig::load().build_client()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_from_lookup_uses_settings_and_defaults() {
+        let settings: HashMap<&str, &str> = [
+            (HTTP_PROXY_SETTING, "http://proxy.corp:8080"),
+            (HTTP_TIMEOUT_SETTING, "30"),
+            (HTTP_CONNECT_TIMEOUT_SETTING, "not-a-number"),
+        ]
+        .into_iter()
+        .collect();
+
+        let config = HttpClientConfig::from_lookup(|key| settings.get(key).map(|v| v.to_string()));
+
+        assert_eq!(config.proxy.as_deref(), Some("http://proxy.corp:8080"));
+        assert_eq!(config.timeout, Duration::from_secs(30));
+        assert_eq!(config.connect_timeout, Duration::from_secs(DEFAULT_CONNECT_TIMEOUT_SECS));
+        assert_eq!(config.user_agent, DEFAULT_USER_AGENT);
+    }
+
+    #[test]
+    fn test_invalid_proxy_is_rejected() {
+        let config = HttpClientConfig {
+            proxy: Some("::not a url::".to_string()),
+            ..Default::default()
+        };
+        assert!(config.build_client().is_err());
+    }
+}
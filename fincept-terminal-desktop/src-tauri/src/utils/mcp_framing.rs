@@ -0,0 +1,138 @@
+// MCP Framing - how JSON-RPC messages are delimited on an MCP server's stdio
+//
+// What: `ndjson` (the default) is one message per line. `content-length` is the
+//       LSP style: `Content-Length: <bytes>` and other headers, a blank line,
+//       then exactly that many bytes of JSON.
+// Why:  Servers using header framing never send a newline-terminated message,
+//       so the line reader waited forever on them.
+// How:  `read_message` / `write_message` are used by the stdout reader thread and
+//       the request/notification writers in `lib.rs`, picked by the framing
+//       given to `spawn_mcp_server`.
+
+use serde::{Deserialize, Serialize};
+use std::io::{self, BufRead, Write};
+
+/// Largest Content-Length accepted, so a corrupt header can't allocate gigabytes
+const MAX_MESSAGE_BYTES: usize = 64 * 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum McpFraming {
+    #[default]
+    Ndjson,
+    ContentLength,
+}
+
+impl McpFraming {
+    /// `None` or empty means the default, newline-delimited JSON
+    pub fn parse(value: Option<&str>) -> Result<Self, String> {
+        match value.map(|v| v.trim().to_lowercase()).as_deref() {
+            None | Some("") | Some("ndjson") => Ok(McpFraming::Ndjson),
+            Some("content-length") => Ok(McpFraming::ContentLength),
+            Some(other) => Err(format!("Invalid framing '{}' (expected ndjson or content-length)", other)),
+        }
+    }
+}
+
+/// Next message from `reader`, or `None` at end of stream
+pub fn read_message<R: BufRead>(reader: &mut R, framing: McpFraming) -> io::Result<Option<String>> {
+    match framing {
+        McpFraming::Ndjson => loop {
+            let mut line = String::new();
+            if reader.read_line(&mut line)? == 0 {
+                return Ok(None);
+            }
+            let line = line.trim();
+            if !line.is_empty() {
+                return Ok(Some(line.to_string()));
+            }
+        },
+        McpFraming::ContentLength => read_content_length(reader),
+    }
+}
+
+fn read_content_length<R: BufRead>(reader: &mut R) -> io::Result<Option<String>> {
+    let invalid = |message: String| io::Error::new(io::ErrorKind::InvalidData, message);
+    let mut length = None;
+    let mut seen_header = false;
+
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 {
+            return if seen_header {
+                Err(io::Error::new(io::ErrorKind::UnexpectedEof, "stream ended inside message headers"))
+            } else {
+                Ok(None)
+            };
+        }
+        let line = line.trim_end_matches(['\r', '\n']);
+        if line.is_empty() {
+            // Blank lines between messages are tolerated; after headers they end them
+            if seen_header {
+                break;
+            }
+            continue;
+        }
+        seen_header = true;
+        if let Some((name, value)) = line.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("content-length") {
+                let value = value.trim();
+                length = Some(value.parse::<usize>().map_err(|_| invalid(format!("bad Content-Length '{}'", value)))?);
+            }
+        }
+    }
+
+    let length = length.ok_or_else(|| invalid("message headers without Content-Length".to_string()))?;
+    if length > MAX_MESSAGE_BYTES {
+        return Err(invalid(format!("Content-Length {} exceeds {} bytes", length, MAX_MESSAGE_BYTES)));
+    }
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body)?;
+    String::from_utf8(body).map(Some).map_err(|e| invalid(format!("message is not UTF-8: {}", e)))
+}
+
+/// Write one message to `writer` and flush it
+pub fn write_message<W: Write>(writer: &mut W, framing: McpFraming, message: &str) -> io::Result<()> {
+    match framing {
+        McpFraming::Ndjson => writeln!(writer, "{}", message)?,
+        McpFraming::ContentLength => {
+            write!(writer, "Content-Length: {}\r\n\r\n{}", message.len(), message)?;
+        }
+    }
+    writer.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_content_length_messages_round_trip() {
+        let mut wire = Vec::new();
+        write_message(&mut wire, McpFraming::ContentLength, r#"{"id":1,"text":"a\nb"}"#).unwrap();
+        wire.extend_from_slice(b"content-length: 8\r\nContent-Type: application/json\r\n\r\n{\"id\":2}");
+
+        let mut reader = Cursor::new(wire);
+        let first = read_message(&mut reader, McpFraming::ContentLength).unwrap();
+        assert_eq!(first.as_deref(), Some(r#"{"id":1,"text":"a\nb"}"#));
+        let second = read_message(&mut reader, McpFraming::ContentLength).unwrap();
+        assert_eq!(second.as_deref(), Some(r#"{"id":2}"#));
+        assert_eq!(read_message(&mut reader, McpFraming::ContentLength).unwrap(), None);
+
+        let mut missing = Cursor::new(b"Content-Type: x\r\n\r\n{}".to_vec());
+        assert!(read_message(&mut missing, McpFraming::ContentLength).is_err());
+    }
+
+    #[test]
+    fn test_ndjson_skips_blank_lines() {
+        let mut reader = Cursor::new(b"{\"id\":1}\n\n{\"id\":2}\r\n".to_vec());
+        assert_eq!(read_message(&mut reader, McpFraming::Ndjson).unwrap().as_deref(), Some(r#"{"id":1}"#));
+        assert_eq!(read_message(&mut reader, McpFraming::Ndjson).unwrap().as_deref(), Some(r#"{"id":2}"#));
+        assert_eq!(read_message(&mut reader, McpFraming::Ndjson).unwrap(), None);
+
+        assert_eq!(McpFraming::parse(None), Ok(McpFraming::Ndjson));
+        assert_eq!(McpFraming::parse(Some("Content-Length")), Ok(McpFraming::ContentLength));
+        assert!(McpFraming::parse(Some("xml")).is_err());
+    }
+}
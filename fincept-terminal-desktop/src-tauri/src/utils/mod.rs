@@ -1 +1,13 @@
 pub mod python;
+pub mod python_errors;
+pub mod data_dir;
+pub mod coalesce;
+pub mod http;
+pub mod retry;
+pub mod timing;
+pub mod hashing;
+pub mod cancellation;
+pub mod command_timeout;
+pub mod symbol;
+pub mod money;
+pub mod mcp_framing;
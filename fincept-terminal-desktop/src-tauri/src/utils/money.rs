@@ -0,0 +1,223 @@
+// Money - exact decimal amounts for paper-trading balances, fills and fees
+//
+// What: `Money` wraps a `rust_decimal::Decimal` rounded to `Money::SCALE`
+//       decimal places. Balance updates, realized/unrealized PnL and fees are
+//       computed with it instead of `f64`.
+// Why:  Adding fees and PnL as `f64` over thousands of fills accumulated binary
+//       rounding errors, so a balance that should read 10000.00 ended up as
+//       9999.9999997 and sums in the statistics disagreed with the balance.
+// How:  An `f64` enters through its shortest decimal representation (0.1 becomes
+//       exactly 0.1, not 0.1000000000000000055...), and every result is rounded
+//       to `SCALE` places (banker's rounding). It is bound to SQLite as decimal
+//       text: the running totals (portfolio balances, the daily loss baseline and
+//       positions' realized PnL) live in TEXT columns and are stored exactly,
+//       while REAL columns (prices, quantities) convert the text to the nearest
+//       `f64` as before. REAL, TEXT and INTEGER columns are all read. JSON carries
+//       plain numbers.
+
+use rusqlite::types::{FromSql, FromSqlError, FromSqlResult, ToSql, ToSqlOutput, ValueRef};
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::{Decimal, RoundingStrategy};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::iter::Sum;
+use std::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Money(Decimal);
+
+impl Money {
+    /// Decimal places kept; enough for crypto quantities and sub-cent fees
+    pub const SCALE: u32 = 8;
+    pub const ZERO: Money = Money(Decimal::ZERO);
+
+    /// `value` as written in decimal, rounded to `SCALE` places
+    pub fn new(value: f64) -> Result<Self, String> {
+        if !value.is_finite() {
+            return Err(format!("Amount must be a finite number, got {}", value));
+        }
+        Decimal::from_str(&value.to_string())
+            .map(Self::from_decimal)
+            .map_err(|_| format!("Amount {} is out of range", value))
+    }
+
+    /// Like `new`, with NaN, infinities and out-of-range values as zero; for
+    /// amounts already validated or read back from the database
+    pub fn from_f64_lossy(value: f64) -> Self {
+        Self::new(value).unwrap_or_default()
+    }
+
+    pub fn from_decimal(value: Decimal) -> Self {
+        Self(value.round_dp_with_strategy(Self::SCALE, RoundingStrategy::MidpointNearestEven))
+    }
+
+    pub fn decimal(self) -> Decimal {
+        self.0
+    }
+
+    /// Nearest `f64`; reads back as the same `Money`
+    pub fn to_f64(self) -> f64 {
+        self.0.to_f64().unwrap_or_default()
+    }
+
+    pub fn is_zero(self) -> bool {
+        self.0.is_zero()
+    }
+
+    pub fn abs(self) -> Self {
+        Self(self.0.abs())
+    }
+}
+
+impl fmt::Display for Money {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0.normalize(), f)
+    }
+}
+
+impl FromStr for Money {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Decimal::from_str(s.trim())
+            .map(Self::from_decimal)
+            .map_err(|_| format!("Invalid amount '{}'", s))
+    }
+}
+
+impl Add for Money {
+    type Output = Money;
+
+    fn add(self, other: Money) -> Money {
+        Money(self.0 + other.0)
+    }
+}
+
+impl Sub for Money {
+    type Output = Money;
+
+    fn sub(self, other: Money) -> Money {
+        Money(self.0 - other.0)
+    }
+}
+
+/// Price times quantity, fee rate times value, ...; rounded back to `SCALE`
+impl Mul for Money {
+    type Output = Money;
+
+    fn mul(self, other: Money) -> Money {
+        Money::from_decimal(self.0 * other.0)
+    }
+}
+
+impl Neg for Money {
+    type Output = Money;
+
+    fn neg(self) -> Money {
+        Money(-self.0)
+    }
+}
+
+impl AddAssign for Money {
+    fn add_assign(&mut self, other: Money) {
+        self.0 += other.0;
+    }
+}
+
+impl SubAssign for Money {
+    fn sub_assign(&mut self, other: Money) {
+        self.0 -= other.0;
+    }
+}
+
+impl Sum for Money {
+    fn sum<I: Iterator<Item = Money>>(iter: I) -> Money {
+        iter.fold(Money::ZERO, Add::add)
+    }
+}
+
+impl Serialize for Money {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_f64(self.to_f64())
+    }
+}
+
+impl<'de> Deserialize<'de> for Money {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Money::new(f64::deserialize(deserializer)?).map_err(serde::de::Error::custom)
+    }
+}
+
+impl ToSql for Money {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(ToSqlOutput::from(self.to_string()))
+    }
+}
+
+impl FromSql for Money {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        match value {
+            ValueRef::Real(v) => Money::new(v).map_err(|e| FromSqlError::Other(e.into())),
+            ValueRef::Integer(v) => Ok(Money(Decimal::from(v))),
+            ValueRef::Text(_) => value.as_str()?.parse().map_err(|e: String| FromSqlError::Other(e.into())),
+            _ => Err(FromSqlError::InvalidType),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn money(value: f64) -> Money {
+        Money::new(value).unwrap()
+    }
+
+    #[test]
+    fn test_sums_are_exact() {
+        assert_ne!(0.1 + 0.2, 0.3);
+        assert_eq!(money(0.1) + money(0.2), money(0.3));
+
+        // 10,000 fees of 0.1 taken from a 10,000 balance leave exactly 9,000
+        let mut balance = money(10_000.0);
+        let mut float = 10_000.0_f64;
+        for _ in 0..10_000 {
+            balance -= money(0.1);
+            float -= 0.1;
+        }
+        assert_eq!(balance.to_f64(), 9_000.0);
+        assert_ne!(float, 9_000.0);
+    }
+
+    #[test]
+    fn test_rounds_products_to_scale() {
+        assert_eq!(money(1001.0) * money(0.0005), money(0.5005));
+        assert_eq!(money(0.00000003) * money(0.5), money(0.00000002));
+        assert_eq!(money(0.123456789), money(0.12345679));
+        assert!(Money::new(f64::NAN).is_err());
+        assert_eq!("12.50".parse::<Money>().unwrap().to_string(), "12.5");
+    }
+
+    #[test]
+    fn test_round_trips_through_f64_json_and_sqlite() {
+        let amount = money(9_999_999.12345678);
+        assert_eq!(money(amount.to_f64()), amount);
+        assert_eq!(serde_json::to_string(&money(12.5)).unwrap(), "12.5");
+        assert_eq!(serde_json::from_str::<Money>("0.3").unwrap(), money(0.3));
+
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        conn.execute_batch("CREATE TABLE t (real_col REAL, text_col TEXT, int_col INTEGER)").unwrap();
+        conn.execute("INSERT INTO t VALUES (?1, '0.30', 7)", [amount]).unwrap();
+        let (real, text, int): (Money, Money, Money) = conn
+            .query_row("SELECT real_col, text_col, int_col FROM t", [], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+            .unwrap();
+        assert_eq!((real, text, int), (amount, money(0.3), money(7.0)));
+
+        // A TEXT column keeps every digit, past what an f64 can hold
+        let total = money(123_456_789.0) + money(0.12345678);
+        conn.execute("UPDATE t SET text_col = ?1", [total]).unwrap();
+        let stored: Money = conn.query_row("SELECT text_col FROM t", [], |row| row.get(0)).unwrap();
+        assert_eq!(stored.to_string(), "123456789.12345678");
+    }
+}
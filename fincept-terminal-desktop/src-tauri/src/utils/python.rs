@@ -1,4 +1,5 @@
 // Utility module for Python execution with PyO3
+use super::python_errors::{python_not_installed, NotInstalledReason, PythonRuntimeError};
 use std::path::PathBuf;
 use tauri::Manager;
 
@@ -15,7 +16,7 @@ const NUMPY1_LIBRARIES: &[&str] = &[
 ];
 
 /// Determine which venv to use based on library name
-fn get_venv_for_library(library_name: Option<&str>) -> &'static str {
+pub(crate) fn get_venv_for_library(library_name: Option<&str>) -> &'static str {
     if let Some(lib) = library_name {
         // Check if library requires NumPy 1.x
         if NUMPY1_LIBRARIES.iter().any(|&numpy1_lib| lib.contains(numpy1_lib)) {
@@ -30,14 +31,20 @@ fn get_venv_for_library(library_name: Option<&str>) -> &'static str {
 /// Supports dual-venv setup for NumPy 1.x and 2.x compatibility
 /// In production: Uses installed Python from setup.rs
 /// In development: Uses %LOCALAPPDATA%/fincept-dev (matches setup.rs)
-pub fn get_python_path(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+pub fn get_python_path(app: &tauri::AppHandle) -> Result<PathBuf, PythonRuntimeError> {
     get_python_path_for_library(app, None)
 }
 
 /// Get Python path for a specific library (switches between numpy1 and numpy2 venvs)
-pub fn get_python_path_for_library(app: &tauri::AppHandle, library_name: Option<&str>) -> Result<PathBuf, String> {
+pub fn get_python_path_for_library(
+    app: &tauri::AppHandle,
+    library_name: Option<&str>,
+) -> Result<PathBuf, PythonRuntimeError> {
     // Get install directory - MUST match setup.rs get_install_dir()
-    let install_dir = if cfg!(debug_assertions) {
+    let install_dir = if let Some(data_dir) = super::data_dir::portable_data_dir() {
+        // Portable mode: everything lives under the user-chosen data directory
+        data_dir
+    } else if cfg!(debug_assertions) {
         // Dev mode: use LOCALAPPDATA/fincept-dev
         let base_dir = if cfg!(target_os = "windows") {
             std::env::var("LOCALAPPDATA")
@@ -56,7 +63,11 @@ pub fn get_python_path_for_library(app: &tauri::AppHandle, library_name: Option<
     } else {
         // Production: use app data directory
         app.path().app_data_dir()
-            .map_err(|e| format!("Failed to get app data dir: {}", e))?
+            .map_err(|e| PythonRuntimeError::PythonNotInstalled {
+                expected_path: String::new(),
+                reason: NotInstalledReason::InstallDirUnavailable,
+                details: format!("Failed to get app data dir: {}", e),
+            })?
     };
 
     // Determine which venv to use based on library
@@ -100,17 +111,7 @@ pub fn get_python_path_for_library(app: &tauri::AppHandle, library_name: Option<
     }
 
     // If we get here, Python is not available
-    Err(format!(
-        "Python interpreter not found at: {}\n\n\
-        The Python runtime is required to run analytics and data processing features.\n\n\
-        Troubleshooting steps:\n\
-        1. Run the setup wizard from the application to install Python\n\
-        2. Ensure both venv-numpy1 and venv-numpy2 are created\n\
-        3. If setup fails, ensure Microsoft Visual C++ Redistributable is installed:\n\
-           Download from: https://aka.ms/vs/17/release/vc_redist.x64.exe\n\
-        4. Restart the application after installation",
-        python_exe.display()
-    ))
+    Err(python_not_installed(&python_exe, &install_dir))
 }
 
 /// Get the Bun executable path from app installation directory
@@ -202,6 +203,9 @@ pub fn get_bundled_bun_path_for_runtime(app: Option<&tauri::AppHandle>) -> Resul
 
 /// Determine the installation directory used to locate bundled runtimes and tools.
 ///
+/// If a portable data directory is configured (`--data-dir` / `FINCEPT_DATA_DIR`),
+/// it is used as-is in both debug and release builds.
+///
 /// When running in debug mode, this returns a development-specific directory
 /// (e.g. `LOCALAPPDATA/fincept-dev` on Windows) to avoid interfering with the
 /// production installation.
@@ -217,6 +221,11 @@ pub fn get_bundled_bun_path_for_runtime(app: Option<&tauri::AppHandle>) -> Resul
 /// so that the installer and the runtime both agree on where the application data
 /// and bundled binaries are stored.
 fn get_install_dir_for_runtime(app: Option<&tauri::AppHandle>) -> Result<PathBuf, String> {
+    // Portable mode overrides both dev and production locations
+    if let Some(data_dir) = super::data_dir::portable_data_dir() {
+        return Ok(data_dir);
+    }
+
     // Get install directory - MUST match setup.rs get_install_dir()
     if cfg!(debug_assertions) {
         // Dev mode: use LOCALAPPDATA/fincept-dev
@@ -336,6 +345,11 @@ pub fn get_script_path_for_runtime(
         candidate_paths.push(custom_path.join(script_name));
     }
 
+    // 0b. Scripts shipped inside a portable data directory
+    if let Some(data_dir) = super::data_dir::portable_data_dir() {
+        candidate_paths.push(data_dir.join("scripts").join(script_name));
+    }
+
     // 1. Try Tauri's resource_dir (works in production and should work in dev)
     if let Some(app) = app {
         if let Ok(resource_dir) = app.path().resource_dir() {
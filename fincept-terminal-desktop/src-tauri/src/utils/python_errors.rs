@@ -0,0 +1,207 @@
+// Python Errors - recognise failures caused by a missing Python package
+//
+// What: `python_failure` turns a failed script's stderr into the error returned
+//       to the frontend. A `ModuleNotFoundError` / `ImportError: No module named`
+//       becomes a `MissingModule`: the import that failed, the pip package that
+//       provides it and the venv that should contain it.
+// Why:  A missing package (e.g. pmdarima) surfaced as "Python script failed"
+//       followed by a long traceback, which gave the user no hint that re-running
+//       setup fixes it.
+// How:  The last "No module named" line of the traceback wins, since that is the
+//       error Python actually raised. The import name is mapped to its pip name
+//       (`IMPORT_PACKAGES`, for the packages whose names differ) and the venv is
+//       picked with the same library mapping that chooses the interpreter
+//       (`python::get_venv_for_library`). The message starts with
+//       `MISSING_MODULE_PREFIX` so callers can tell it apart from other failures.
+//
+//       A Python runtime that isn't installed at all is a `PythonNotInstalled`
+//       error instead: the interpreter path that was expected, a reason code and
+//       the troubleshooting text as `details`. Commands return `String` errors, so
+//       it is sent as JSON (`{"kind":"PythonNotInstalled",...}`), which the
+//       frontend parses to offer "Run Setup" rather than showing the paragraph.
+
+use serde::Serialize;
+use std::fmt;
+use std::path::Path;
+
+/// Start of every missing-package error message
+pub const MISSING_MODULE_PREFIX: &str = "Missing Python package";
+
+/// Import names whose pip package is called something else
+const IMPORT_PACKAGES: &[(&str, &str)] = &[
+    ("bs4", "beautifulsoup4"),
+    ("dateutil", "python-dateutil"),
+    ("fortitudo", "fortitudo.tech"),
+    ("gs_quant", "gs-quant"),
+    ("pypfopt", "PyPortfolioOpt"),
+    ("qlib", "pyqlib"),
+    ("sklearn", "scikit-learn"),
+    ("talib", "TA-Lib"),
+    ("yaml", "PyYAML"),
+];
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MissingModule {
+    /// Top-level module that failed to import, e.g. "sklearn"
+    pub module: String,
+    /// pip package that provides it, e.g. "scikit-learn"
+    pub package: String,
+    /// Virtual environment the package belongs in
+    pub venv: &'static str,
+}
+
+impl fmt::Display for MissingModule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} '{}' is not installed in {} (import '{}' failed). \
+             Run setup again, or repair {} from the setup screen, to install it.",
+            MISSING_MODULE_PREFIX, self.package, self.venv, self.module, self.venv
+        )
+    }
+}
+
+/// The package a failed script was missing, if that is why it failed
+pub fn missing_module(stderr: &str) -> Option<MissingModule> {
+    let module = stderr.lines().rev().find_map(module_not_found)?;
+    let package = IMPORT_PACKAGES
+        .iter()
+        .find(|(import, _)| *import == module)
+        .map_or(module, |(_, package)| *package)
+        .to_string();
+    let venv = super::python::get_venv_for_library(Some(&package));
+    Some(MissingModule { module: module.to_string(), package, venv })
+}
+
+/// Top-level module named by a "No module named ..." line
+fn module_not_found(line: &str) -> Option<&str> {
+    let line = line.trim();
+    if !(line.starts_with("ModuleNotFoundError") || line.starts_with("ImportError")) {
+        return None;
+    }
+    let (_, rest) = line.split_once("No module named")?;
+    let name = rest.trim().trim_matches(|c| c == '\'' || c == '"');
+    let top_level = name.split('.').next()?.trim();
+    (!top_level.is_empty()).then_some(top_level)
+}
+
+/// Error message for a script that exited with `stderr`
+pub fn python_failure(stderr: &str) -> String {
+    match missing_module(stderr) {
+        Some(missing) => missing.to_string(),
+        None => format!("Python script failed: {}", stderr),
+    }
+}
+
+/// `kind` of the error returned when the Python runtime isn't installed
+pub const PYTHON_NOT_INSTALLED: &str = "PythonNotInstalled";
+
+/// Why the interpreter wasn't found
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NotInstalledReason {
+    /// No venv exists in the install directory; setup has not completed
+    SetupNotRun,
+    /// Setup ran, but the venv this library needs is missing
+    VenvMissing,
+    /// The install directory could not be determined
+    InstallDirUnavailable,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind")]
+pub enum PythonRuntimeError {
+    PythonNotInstalled {
+        /// Interpreter path that was looked for
+        expected_path: String,
+        reason: NotInstalledReason,
+        /// Troubleshooting text for display when the UI can't offer setup
+        details: String,
+    },
+}
+
+/// Displays as JSON, which is what reaches the frontend as the command error
+impl fmt::Display for PythonRuntimeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&serde_json::to_string(self).map_err(|_| fmt::Error)?)
+    }
+}
+
+impl std::error::Error for PythonRuntimeError {}
+
+impl From<PythonRuntimeError> for String {
+    fn from(error: PythonRuntimeError) -> Self {
+        error.to_string()
+    }
+}
+
+/// Error for an interpreter missing at `python_exe` inside `install_dir`
+pub fn python_not_installed(python_exe: &Path, install_dir: &Path) -> PythonRuntimeError {
+    let has_venv = ["venv-numpy1", "venv-numpy2"].iter().any(|venv| install_dir.join(venv).is_dir());
+    PythonRuntimeError::PythonNotInstalled {
+        expected_path: python_exe.display().to_string(),
+        reason: if has_venv { NotInstalledReason::VenvMissing } else { NotInstalledReason::SetupNotRun },
+        details: format!(
+            "Python interpreter not found at: {}\n\n\
+            The Python runtime is required to run analytics and data processing features.\n\n\
+            Troubleshooting steps:\n\
+            1. Run the setup wizard from the application to install Python\n\
+            2. Ensure both venv-numpy1 and venv-numpy2 are created\n\
+            3. If setup fails, ensure Microsoft Visual C++ Redistributable is installed:\n\
+               Download from: https://aka.ms/vs/17/release/vc_redist.x64.exe\n\
+            4. Restart the application after installation",
+            python_exe.display()
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detects_module_not_found() {
+        let stderr = "Traceback (most recent call last):\n  File \"forecast.py\", line 3, in <module>\n    import pmdarima as pm\nModuleNotFoundError: No module named 'pmdarima'\n";
+        let missing = missing_module(stderr).unwrap();
+        assert_eq!(missing.module, "pmdarima");
+        assert_eq!(missing.package, "pmdarima");
+        assert!(python_failure(stderr).starts_with(MISSING_MODULE_PREFIX));
+    }
+
+    #[test]
+    fn test_maps_import_name_to_package_and_venv() {
+        let missing = missing_module("ModuleNotFoundError: No module named 'sklearn.ensemble'").unwrap();
+        assert_eq!((missing.module.as_str(), missing.package.as_str()), ("sklearn", "scikit-learn"));
+        assert_eq!(missing.venv, "venv-numpy2");
+
+        let missing = missing_module("ImportError: No module named pypfopt").unwrap();
+        assert_eq!(missing.package, "PyPortfolioOpt");
+        assert_eq!(missing.venv, "venv-numpy1");
+    }
+
+    #[test]
+    fn test_other_failures_keep_stderr() {
+        let stderr = "ImportError: cannot import name 'foo' from 'bar'\nValueError: bad input";
+        assert!(missing_module(stderr).is_none());
+        assert_eq!(python_failure(stderr), format!("Python script failed: {}", stderr));
+    }
+
+    #[test]
+    fn test_python_not_installed_is_json_with_a_reason() {
+        let dir = std::env::temp_dir().join(format!("fincept_python_missing_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let python_exe = dir.join("venv-numpy1/bin/python3");
+
+        let error = python_not_installed(&python_exe, &dir);
+        let json: serde_json::Value = serde_json::from_str(&String::from(error)).unwrap();
+        assert_eq!(json["kind"], PYTHON_NOT_INSTALLED);
+        assert_eq!(json["reason"], "setup_not_run");
+        assert_eq!(json["expected_path"], python_exe.display().to_string());
+        assert!(json["details"].as_str().unwrap().starts_with("Python interpreter not found"));
+
+        std::fs::create_dir_all(dir.join("venv-numpy2")).unwrap();
+        let error = python_not_installed(&python_exe, &dir);
+        assert!(matches!(error, PythonRuntimeError::PythonNotInstalled { reason: NotInstalledReason::VenvMissing, .. }));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
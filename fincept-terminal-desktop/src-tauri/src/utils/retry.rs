@@ -0,0 +1,315 @@
+// Retry with exponential backoff for transient upstream failures
+//
+// What: `retry_async(policy, classify, op)` re-runs an idempotent operation while
+//       `classify` reports the failure as transient, sleeping between attempts.
+//       `get_with_retry` applies it to plain HTTP GETs.
+// Why:  Yahoo Finance and RSS hosts regularly answer 429/503 or time out for a
+//       moment; a single failed attempt used to surface as an empty panel.
+// How:  Delay is `base_delay * 2^(attempt-1)` capped at `max_delay`, with up to
+//       50% random jitter so parallel panels don't retry in lockstep. A
+//       server-supplied `Retry-After` (seconds) replaces the computed delay. The
+//       final error carries the number of attempts made. Only use this for
+//       idempotent requests (GET) - callers must never wrap writes.
+//
+// Settings (settings table, category "network"):
+//   http_retry_max_attempts   - total attempts including the first (default 3)
+//   http_retry_base_delay_ms  - first backoff delay (default 500)
+
+use std::future::Future;
+use std::time::Duration;
+
+pub const RETRY_MAX_ATTEMPTS_SETTING: &str = "http_retry_max_attempts";
+pub const RETRY_BASE_DELAY_SETTING: &str = "http_retry_base_delay_ms";
+
+const DEFAULT_MAX_ATTEMPTS: u32 = 3;
+const DEFAULT_BASE_DELAY_MS: u64 = 500;
+const DEFAULT_MAX_DELAY_MS: u64 = 8_000;
+/// Upper bound on a server-requested wait, so a bad header can't hang a panel
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(30);
+
+/// How a failed attempt should be treated
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RetryClass {
+    /// Transient; retry, optionally after the delay the server asked for
+    Transient { retry_after: Option<Duration> },
+    /// Permanent; give up immediately
+    Permanent,
+}
+
+/// Backoff parameters
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetryPolicy {
+    pub max_attempts: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+    pub jitter: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: DEFAULT_MAX_ATTEMPTS,
+            base_delay: Duration::from_millis(DEFAULT_BASE_DELAY_MS),
+            max_delay: Duration::from_millis(DEFAULT_MAX_DELAY_MS),
+            jitter: true,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Load from the settings table, falling back to defaults for anything unset
+    pub fn load() -> Self {
+        let setting = |key: &str| {
+            crate::database::operations::get_setting(key)
+                .ok()
+                .flatten()
+                .and_then(|v| v.trim().parse::<u64>().ok())
+        };
+        let defaults = Self::default();
+
+        Self {
+            max_attempts: setting(RETRY_MAX_ATTEMPTS_SETTING)
+                .map(|n| n.clamp(1, 10) as u32)
+                .unwrap_or(defaults.max_attempts),
+            base_delay: setting(RETRY_BASE_DELAY_SETTING)
+                .map(Duration::from_millis)
+                .unwrap_or(defaults.base_delay),
+            ..defaults
+        }
+    }
+
+    /// Delay before attempt `attempt + 1`, given `attempt` (1-based) just failed
+    pub fn delay_for(&self, attempt: u32, retry_after: Option<Duration>) -> Duration {
+        if let Some(wait) = retry_after {
+            return wait.min(MAX_RETRY_AFTER);
+        }
+
+        let exp = self
+            .base_delay
+            .saturating_mul(1u32 << attempt.saturating_sub(1).min(16))
+            .min(self.max_delay);
+
+        if self.jitter {
+            // Subtract up to half the delay; cheap entropy is plenty here
+            let nanos = std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|d| d.subsec_nanos())
+                .unwrap_or(0);
+            let fraction = (nanos % 1000) as f64 / 2000.0;
+            exp.mul_f64(1.0 - fraction)
+        } else {
+            exp
+        }
+    }
+}
+
+/// Run `op` until it succeeds, fails permanently, or attempts run out
+///
+/// Errors are rendered as `"<last error> (after N attempts)"`.
+pub async fn retry_async<T, E, F, Fut, C>(policy: &RetryPolicy, classify: C, mut op: F) -> Result<T, String>
+where
+    E: std::fmt::Display,
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+    C: Fn(&E) -> RetryClass,
+{
+    let max_attempts = policy.max_attempts.max(1);
+    let mut attempt = 0;
+
+    loop {
+        attempt += 1;
+        let error = match op().await {
+            Ok(value) => return Ok(value),
+            Err(error) => error,
+        };
+
+        let retry_after = match classify(&error) {
+            RetryClass::Transient { retry_after } if attempt < max_attempts => retry_after,
+            _ => {
+                return Err(format!(
+                    "{} (after {} attempt{})",
+                    error,
+                    attempt,
+                    if attempt == 1 { "" } else { "s" }
+                ))
+            }
+        };
+
+        let delay = policy.delay_for(attempt, retry_after);
+        tracing::debug!(attempt, delay_ms = delay.as_millis() as u64, error = %error, "Retrying transient failure");
+        tokio::time::sleep(delay).await;
+    }
+}
+
+/// Failure of a single HTTP attempt, with enough context to classify it
+#[derive(Debug)]
+pub enum HttpAttemptError {
+    Status { status: reqwest::StatusCode, retry_after: Option<Duration> },
+    Transport(reqwest::Error),
+}
+
+impl std::fmt::Display for HttpAttemptError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HttpAttemptError::Status { status, .. } => write!(f, "HTTP {}", status),
+            HttpAttemptError::Transport(e) => write!(f, "Request failed: {}", e),
+        }
+    }
+}
+
+/// Classify HTTP failures: 408/429/5xx gateway errors and timeouts/connect errors are transient
+pub fn classify_http(error: &HttpAttemptError) -> RetryClass {
+    match error {
+        HttpAttemptError::Status { status, retry_after } => match status.as_u16() {
+            408 | 429 | 500 | 502 | 503 | 504 => RetryClass::Transient { retry_after: *retry_after },
+            _ => RetryClass::Permanent,
+        },
+        HttpAttemptError::Transport(e) if e.is_timeout() || e.is_connect() => {
+            RetryClass::Transient { retry_after: None }
+        }
+        HttpAttemptError::Transport(_) => RetryClass::Permanent,
+    }
+}
+
+/// Classify a failed subprocess by its stderr (Python data scripts surface HTTP errors as text)
+pub fn classify_stderr(stderr: &str) -> RetryClass {
+    const TRANSIENT_MARKERS: [&str; 8] = [
+        "429",
+        "Too Many Requests",
+        "503",
+        "Service Unavailable",
+        "timed out",
+        "Timeout",
+        "Connection reset",
+        "Connection aborted",
+    ];
+    if TRANSIENT_MARKERS.iter().any(|m| stderr.contains(m)) {
+        RetryClass::Transient { retry_after: None }
+    } else {
+        RetryClass::Permanent
+    }
+}
+
+/// Parse a `Retry-After` header given in seconds (HTTP-date form is ignored)
+fn parse_retry_after(headers: &reqwest::header::HeaderMap) -> Option<Duration> {
+    headers
+        .get(reqwest::header::RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse::<u64>()
+        .ok()
+        .map(Duration::from_secs)
+}
+
+/// GET `url` with retries, returning the first successful (2xx) response
+pub async fn get_with_retry(
+    client: &reqwest::Client,
+    url: &str,
+    headers: &[(&str, &str)],
+    policy: &RetryPolicy,
+) -> Result<reqwest::Response, String> {
+    retry_async(policy, classify_http, move || async move {
+        let mut request = client.get(url);
+        for (name, value) in headers {
+            request = request.header(*name, *value);
+        }
+        let response = request.send().await.map_err(HttpAttemptError::Transport)?;
+        if response.status().is_success() {
+            Ok(response)
+        } else {
+            Err(HttpAttemptError::Status {
+                status: response.status(),
+                retry_after: parse_retry_after(response.headers()),
+            })
+        }
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    fn fast_policy(max_attempts: u32) -> RetryPolicy {
+        RetryPolicy {
+            max_attempts,
+            base_delay: Duration::from_millis(1),
+            max_delay: Duration::from_millis(5),
+            jitter: false,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_retries_transient_until_success() {
+        let calls = AtomicU32::new(0);
+        let counter = &calls;
+        let result: Result<u32, String> = retry_async(
+            &fast_policy(3),
+            |_: &String| RetryClass::Transient { retry_after: None },
+            move || async move {
+                let n = counter.fetch_add(1, Ordering::SeqCst) + 1;
+                if n < 3 { Err("HTTP 503".to_string()) } else { Ok(n) }
+            },
+        )
+        .await;
+
+        assert_eq!(result, Ok(3));
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_permanent_error_is_not_retried() {
+        let calls = AtomicU32::new(0);
+        let counter = &calls;
+        let result: Result<(), String> = retry_async(
+            &fast_policy(5),
+            |_: &String| RetryClass::Permanent,
+            move || async move {
+                counter.fetch_add(1, Ordering::SeqCst);
+                Err("HTTP 404".to_string())
+            },
+        )
+        .await;
+
+        assert_eq!(result, Err("HTTP 404 (after 1 attempt)".to_string()));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_final_error_reports_attempts() {
+        let result: Result<(), String> = retry_async(
+            &fast_policy(2),
+            |_: &String| RetryClass::Transient { retry_after: None },
+            || async { Err("HTTP 429".to_string()) },
+        )
+        .await;
+
+        assert_eq!(result, Err("HTTP 429 (after 2 attempts)".to_string()));
+    }
+
+    #[test]
+    fn test_delay_backoff_and_retry_after() {
+        let policy = RetryPolicy {
+            max_attempts: 5,
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_millis(300),
+            jitter: false,
+        };
+        assert_eq!(policy.delay_for(1, None), Duration::from_millis(100));
+        assert_eq!(policy.delay_for(2, None), Duration::from_millis(200));
+        assert_eq!(policy.delay_for(3, None), Duration::from_millis(300));
+        assert_eq!(policy.delay_for(1, Some(Duration::from_secs(2))), Duration::from_secs(2));
+        assert_eq!(policy.delay_for(1, Some(Duration::from_secs(600))), MAX_RETRY_AFTER);
+    }
+
+    #[test]
+    fn test_classify_stderr() {
+        assert_eq!(
+            classify_stderr("HTTPError: 429 Client Error: Too Many Requests"),
+            RetryClass::Transient { retry_after: None }
+        );
+        assert_eq!(classify_stderr("KeyError: 'regularMarketPrice'"), RetryClass::Permanent);
+    }
+}
@@ -0,0 +1,146 @@
+// Symbol - validated instrument symbol
+//
+// What: `Symbol` wraps a ticker/instrument symbol that is known to be non-empty,
+//       trimmed, free of control characters and at most `MAX_LEN` bytes, with
+//       inner whitespace runs collapsed to one space.
+// Why:  Symbols travelled as bare `String`s, so an empty one (a missing field read
+//       with `unwrap_or("")`) produced monitor conditions and subscriptions that
+//       could never match anything, without any error.
+// How:  `Symbol::new` also upper-cases, which is how watchlists and monitor
+//       conditions store symbols; `Symbol::exchange` keeps the caller's case for
+//       symbols sent to an exchange, where case can matter (e.g. HyperLiquid's
+//       "kPEPE"). Deserializing goes through `Symbol::new`, so command arguments
+//       are rejected at the boundary, and SQLite columns convert both ways.
+
+use rusqlite::types::{FromSql, FromSqlError, FromSqlResult, ToSql, ToSqlOutput, ValueRef};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Symbol(String);
+
+impl Symbol {
+    pub const MAX_LEN: usize = 64;
+
+    /// Validate and upper-case `raw`
+    pub fn new(raw: &str) -> Result<Self, String> {
+        Self::exchange(raw).map(|symbol| Self(symbol.0.to_uppercase()))
+    }
+
+    /// Validate `raw` but keep its case
+    pub fn exchange(raw: &str) -> Result<Self, String> {
+        let symbol = raw.split_whitespace().collect::<Vec<_>>().join(" ");
+        if symbol.is_empty() {
+            return Err("Symbol must not be empty".to_string());
+        }
+        if symbol.len() > Self::MAX_LEN {
+            return Err(format!("Symbol '{}' is longer than {} characters", symbol, Self::MAX_LEN));
+        }
+        if symbol.chars().any(char::is_control) {
+            return Err(format!("Symbol {:?} contains control characters", symbol));
+        }
+        Ok(Self(symbol))
+    }
+
+    /// Whether a provider's `raw` symbol names this one (ASCII case-insensitive)
+    pub fn matches(&self, raw: &str) -> bool {
+        self.0.eq_ignore_ascii_case(raw.trim())
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    pub fn into_string(self) -> String {
+        self.0
+    }
+}
+
+impl fmt::Display for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl AsRef<str> for Symbol {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl std::str::FromStr for Symbol {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::new(s)
+    }
+}
+
+impl TryFrom<String> for Symbol {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Self::new(&value)
+    }
+}
+
+impl From<Symbol> for String {
+    fn from(symbol: Symbol) -> Self {
+        symbol.0
+    }
+}
+
+impl PartialEq<str> for Symbol {
+    fn eq(&self, other: &str) -> bool {
+        self.0 == other
+    }
+}
+
+impl PartialEq<&str> for Symbol {
+    fn eq(&self, other: &&str) -> bool {
+        self.0 == *other
+    }
+}
+
+impl ToSql for Symbol {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        self.0.to_sql()
+    }
+}
+
+impl FromSql for Symbol {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        Self::new(value.as_str()?).map_err(|e| FromSqlError::Other(e.into()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalizes_case_and_whitespace() {
+        assert_eq!(Symbol::new("  btc/usdt ").unwrap(), "BTC/USDT");
+        assert_eq!(Symbol::new("aapl\t us  equity").unwrap().as_str(), "AAPL US EQUITY");
+        assert_eq!(Symbol::exchange(" kPEPE ").unwrap(), "kPEPE");
+        assert!(Symbol::new("btcusdt").unwrap().matches("BtcUsdt"));
+    }
+
+    #[test]
+    fn test_rejects_empty_and_malformed() {
+        for raw in ["", "   ", "\n\t"] {
+            assert_eq!(Symbol::new(raw).unwrap_err(), "Symbol must not be empty");
+        }
+        assert!(Symbol::new(&"A".repeat(Symbol::MAX_LEN + 1)).is_err());
+        assert!(Symbol::new("BTC\u{0}USD").is_err());
+    }
+
+    #[test]
+    fn test_deserializing_validates() {
+        let symbol: Symbol = serde_json::from_str("\" eth \"").unwrap();
+        assert_eq!(serde_json::to_string(&symbol).unwrap(), "\"ETH\"");
+        let err = serde_json::from_str::<Symbol>("\"\"").unwrap_err().to_string();
+        assert!(err.contains("Symbol must not be empty"));
+    }
+}
@@ -0,0 +1,170 @@
+// Command timing - built-in profiler for backend work
+//
+// What: A bounded ring buffer of recent timed operations (command name, source,
+//       duration, success) plus per-command aggregates, exposed to the frontend
+//       through `get_command_timings`.
+// Why:  "The terminal feels slow" reports had nothing to go on. This shows which
+//       backend call (Python script, yfinance fetch, RPC command) is the bottleneck.
+// How:  Tauri's invoke handler returns as soon as an async command is spawned and
+//       offers no hook for when it resolves, so timing is recorded where the work
+//       actually happens: Python script execution, yfinance calls and the web RPC
+//       dispatcher. Wrap other slow paths with `timed` / `timed_async`.
+
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::future::Future;
+use std::time::Instant;
+
+/// Number of recent timings kept in memory
+const TIMING_CAPACITY: usize = 500;
+
+static TIMINGS: Lazy<Mutex<VecDeque<CommandTiming>>> =
+    Lazy::new(|| Mutex::new(VecDeque::with_capacity(TIMING_CAPACITY)));
+
+/// One timed operation
+#[derive(Debug, Clone, Serialize)]
+pub struct CommandTiming {
+    /// Where the timing was taken: "python", "yfinance", "rpc"
+    pub source: String,
+    pub command: String,
+    /// Start time, Unix milliseconds
+    pub started_at: i64,
+    pub duration_ms: f64,
+    pub success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Aggregate over the timings currently in the buffer
+#[derive(Debug, Clone, Serialize)]
+pub struct CommandTimingSummary {
+    pub source: String,
+    pub command: String,
+    pub count: usize,
+    pub failures: usize,
+    pub avg_ms: f64,
+    pub max_ms: f64,
+    pub total_ms: f64,
+}
+
+/// Recent timings (newest first) and per-command aggregates (slowest total first)
+#[derive(Debug, Clone, Serialize)]
+pub struct CommandTimingsReport {
+    pub recent: Vec<CommandTiming>,
+    pub summary: Vec<CommandTimingSummary>,
+}
+
+/// Record a finished operation that started at `started`
+pub fn record(source: &str, command: &str, started: Instant, error: Option<&str>) {
+    let duration_ms = started.elapsed().as_secs_f64() * 1000.0;
+    let started_at = chrono::Utc::now().timestamp_millis() - duration_ms as i64;
+
+    let mut timings = TIMINGS.lock();
+    if timings.len() == TIMING_CAPACITY {
+        timings.pop_front();
+    }
+    timings.push_back(CommandTiming {
+        source: source.to_string(),
+        command: command.to_string(),
+        started_at,
+        duration_ms,
+        success: error.is_none(),
+        error: error.map(|e| e.chars().take(200).collect()),
+    });
+}
+
+/// Time a blocking operation
+pub fn timed<T>(source: &str, command: &str, f: impl FnOnce() -> Result<T, String>) -> Result<T, String> {
+    let started = Instant::now();
+    let result = f();
+    record(source, command, started, result.as_ref().err().map(String::as_str));
+    result
+}
+
+/// Time an async operation
+pub async fn timed_async<T, Fut>(source: &str, command: &str, fut: Fut) -> Result<T, String>
+where
+    Fut: Future<Output = Result<T, String>>,
+{
+    let started = Instant::now();
+    let result = fut.await;
+    record(source, command, started, result.as_ref().err().map(String::as_str));
+    result
+}
+
+/// Snapshot of recent timings, optionally limited to the newest `limit`
+pub fn report(limit: Option<usize>) -> CommandTimingsReport {
+    let timings = TIMINGS.lock();
+
+    let mut by_command: HashMap<(&str, &str), CommandTimingSummary> = HashMap::new();
+    for timing in timings.iter() {
+        let entry = by_command
+            .entry((timing.source.as_str(), timing.command.as_str()))
+            .or_insert_with(|| CommandTimingSummary {
+                source: timing.source.clone(),
+                command: timing.command.clone(),
+                count: 0,
+                failures: 0,
+                avg_ms: 0.0,
+                max_ms: 0.0,
+                total_ms: 0.0,
+            });
+        entry.count += 1;
+        if !timing.success {
+            entry.failures += 1;
+        }
+        entry.total_ms += timing.duration_ms;
+        entry.max_ms = entry.max_ms.max(timing.duration_ms);
+    }
+
+    let mut summary: Vec<CommandTimingSummary> = by_command
+        .into_values()
+        .map(|mut s| {
+            s.avg_ms = s.total_ms / s.count as f64;
+            s
+        })
+        .collect();
+    summary.sort_by(|a, b| b.total_ms.total_cmp(&a.total_ms));
+
+    let recent = timings
+        .iter()
+        .rev()
+        .take(limit.unwrap_or(TIMING_CAPACITY))
+        .cloned()
+        .collect();
+
+    CommandTimingsReport { recent, summary }
+}
+
+/// Drop all recorded timings
+pub fn clear() {
+    TIMINGS.lock().clear();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report_aggregates_and_orders_newest_first() {
+        clear();
+        let _ = timed("test", "fast", || Ok::<_, String>(()));
+        let _ = timed("test", "slow", || -> Result<(), String> {
+            std::thread::sleep(std::time::Duration::from_millis(5));
+            Err("boom".to_string())
+        });
+
+        let report = report(Some(10));
+        let ours: Vec<_> = report.recent.iter().filter(|t| t.source == "test").collect();
+        assert_eq!(ours[0].command, "slow");
+        assert!(!ours[0].success);
+        assert_eq!(ours[0].error.as_deref(), Some("boom"));
+
+        let slow = report.summary.iter().find(|s| s.command == "slow").unwrap();
+        assert_eq!(slow.count, 1);
+        assert_eq!(slow.failures, 1);
+        assert!(slow.max_ms >= 5.0);
+    }
+}
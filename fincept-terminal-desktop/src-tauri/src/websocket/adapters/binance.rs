@@ -3,32 +3,47 @@
 // Implements Binance WebSocket API
 // Supports: ticker, book (depth), trade, kline channels
 
-use super::WebSocketAdapter;
+use super::{WebSocketAdapter, CLOSE_TIMEOUT};
+use crate::websocket::keepalive::PongTracker;
+use crate::websocket::raw_capture::RawCapture;
 use crate::websocket::types::*;
 use async_trait::async_trait;
+use futures_util::stream::SplitSink;
 use futures_util::{SinkExt, StreamExt};
 use serde_json::{json, Value};
 use std::sync::Arc;
 use std::time::{SystemTime, UNIX_EPOCH};
-use tokio::sync::RwLock;
-use tokio_tungstenite::{connect_async, tungstenite::Message};
+use tokio::sync::{Mutex, RwLock};
+use tokio::task::JoinHandle;
+use tokio_tungstenite::tungstenite::protocol::frame::coding::CloseCode;
+use tokio_tungstenite::tungstenite::protocol::CloseFrame;
+use tokio_tungstenite::{connect_async, tungstenite::Message, MaybeTlsStream, WebSocketStream};
 
 const BINANCE_WS_URL: &str = "wss://stream.binance.com:9443/ws";
 
+type WsStream = WebSocketStream<MaybeTlsStream<tokio::net::TcpStream>>;
+
 pub struct BinanceAdapter {
     config: ProviderConfig,
-    ws: Option<Arc<RwLock<tokio_tungstenite::WebSocketStream<tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>>>>>,
+    // Split so sends never wait on the receive loop, which owns the read half
+    sink: Option<Arc<Mutex<SplitSink<WsStream, Message>>>>,
+    reader: Option<JoinHandle<()>>,
     message_callback: Option<Arc<Box<dyn Fn(MarketMessage) + Send + Sync>>>,
     connected: Arc<RwLock<bool>>,
+    raw_capture: Arc<RawCapture>,
+    pong_tracker: Arc<PongTracker>,
 }
 
 impl BinanceAdapter {
     pub fn new(config: ProviderConfig) -> Self {
         Self {
             config,
-            ws: None,
+            sink: None,
+            reader: None,
             message_callback: None,
             connected: Arc::new(RwLock::new(false)),
+            raw_capture: Arc::new(RawCapture::default()),
+            pong_tracker: Arc::new(PongTracker::default()),
         }
     }
 
@@ -55,6 +70,36 @@ impl BinanceAdapter {
         symbol.replace('/', "").to_lowercase()
     }
 
+    /// Stream name for a symbol on a channel, e.g. "btcusdt@depth20@100ms"
+    fn stream_name(symbol: &str, channel: &str, params: Option<&Value>) -> anyhow::Result<String> {
+        let binance_symbol = Self::to_binance_symbol(symbol);
+        let stream = match channel {
+            "ticker" => format!("{}@miniTicker", binance_symbol),
+            "book_ticker" => format!("{}@bookTicker", binance_symbol),
+            "book" | "depth" => {
+                let levels = params
+                    .and_then(|p| p.get("levels"))
+                    .and_then(|l| l.as_u64())
+                    .unwrap_or(20);
+                let speed = params
+                    .and_then(|p| p.get("speed"))
+                    .and_then(|s| s.as_str())
+                    .unwrap_or("100ms");
+                format!("{}@depth{}@{}", binance_symbol, levels, speed)
+            }
+            "trade" => format!("{}@trade", binance_symbol),
+            "candle" | "kline" => {
+                let interval = params
+                    .and_then(|p| p.get("interval"))
+                    .and_then(|i| i.as_str())
+                    .unwrap_or("1m");
+                format!("{}@kline_{}", binance_symbol, interval)
+            }
+            _ => return Err(anyhow::anyhow!("Unsupported channel: {}", channel)),
+        };
+        Ok(stream)
+    }
+
     /// Parse Binance ticker (24hr mini ticker)
     fn parse_ticker(&self, data: &Value) -> Option<TickerData> {
         let symbol = data.get("s")?.as_str()?;
@@ -92,6 +137,7 @@ impl BinanceAdapter {
             change: None,
             change_percent,
             timestamp: Self::now(),
+            replayed: false,
         })
     }
 
@@ -121,6 +167,7 @@ impl BinanceAdapter {
             change: None,
             change_percent: None,
             timestamp: Self::now(),
+            replayed: false,
         })
     }
 
@@ -200,20 +247,21 @@ impl BinanceAdapter {
 impl WebSocketAdapter for BinanceAdapter {
     async fn connect(&mut self) -> anyhow::Result<()> {
         let (ws_stream, _) = connect_async(BINANCE_WS_URL).await?;
-        let ws = Arc::new(RwLock::new(ws_stream));
-        self.ws = Some(ws.clone());
+        let (sink, mut stream) = ws_stream.split();
+        self.sink = Some(Arc::new(Mutex::new(sink)));
         *self.connected.write().await = true;
 
         // Start message handler
         let callback = self.message_callback.clone();
         let connected = self.connected.clone();
+        let raw_capture = self.raw_capture.clone();
+        let pong_tracker = self.pong_tracker.clone();
 
-        tokio::spawn(async move {
-            let mut ws_lock = ws.write().await;
-
-            while let Some(msg) = ws_lock.next().await {
+        self.reader = Some(tokio::spawn(async move {
+            while let Some(msg) = stream.next().await {
                 match msg {
                     Ok(Message::Text(text)) => {
+                        raw_capture.record_frame("binance", "e", &["/s"], &text);
                         if let Ok(data) = serde_json::from_str::<Value>(&text) {
                             if let Some(ref cb) = callback {
                                 // Determine message type from event field
@@ -268,30 +316,40 @@ impl WebSocketAdapter for BinanceAdapter {
                             }
                         }
                     }
-                    Ok(Message::Close(_)) => {
-                        *connected.write().await = false;
-                        break;
-                    }
+                    Ok(Message::Pong(_)) => pong_tracker.pong_received(),
+                    Ok(Message::Close(_)) => break,
                     Err(e) => {
                         eprintln!("[Binance] WebSocket error: {}", e);
-                        *connected.write().await = false;
                         break;
                     }
                     _ => {}
                 }
             }
-        });
+            *connected.write().await = false;
+        }));
 
         Ok(())
     }
 
     async fn disconnect(&mut self) -> anyhow::Result<()> {
-        if let Some(ws) = &self.ws {
-            let mut ws_lock = ws.write().await;
-            ws_lock.close(None).await?;
-        }
         *self.connected.write().await = false;
-        self.ws = None;
+
+        if let Some(sink) = self.sink.take() {
+            let mut sink = sink.lock().await;
+            sink.send(Message::Close(Some(CloseFrame {
+                code: CloseCode::Normal,
+                reason: "client disconnect".into(),
+            })))
+            .await?;
+        }
+        // The reader ends once the provider echoes the Close frame
+        if let Some(mut reader) = self.reader.take() {
+            if tokio::time::timeout(CLOSE_TIMEOUT, &mut reader).await.is_err() {
+                reader.abort();
+                anyhow::bail!("Provider did not acknowledge close within {:?}", CLOSE_TIMEOUT);
+            }
+        }
+
         Ok(())
     }
 
@@ -301,41 +359,12 @@ impl WebSocketAdapter for BinanceAdapter {
         channel: &str,
         params: Option<Value>,
     ) -> anyhow::Result<()> {
-        let ws = self
-            .ws
+        let sink = self
+            .sink
             .as_ref()
             .ok_or_else(|| anyhow::anyhow!("Not connected"))?;
 
-        let binance_symbol = Self::to_binance_symbol(symbol);
-
-        // Build stream name based on channel
-        let stream = match channel {
-            "ticker" => format!("{}@miniTicker", binance_symbol),
-            "book_ticker" => format!("{}@bookTicker", binance_symbol),
-            "book" | "depth" => {
-                let levels = params
-                    .as_ref()
-                    .and_then(|p| p.get("levels"))
-                    .and_then(|l| l.as_u64())
-                    .unwrap_or(20);
-                let speed = params
-                    .as_ref()
-                    .and_then(|p| p.get("speed"))
-                    .and_then(|s| s.as_str())
-                    .unwrap_or("100ms");
-                format!("{}@depth{}@{}", binance_symbol, levels, speed)
-            }
-            "trade" => format!("{}@trade", binance_symbol),
-            "candle" | "kline" => {
-                let interval = params
-                    .as_ref()
-                    .and_then(|p| p.get("interval"))
-                    .and_then(|i| i.as_str())
-                    .unwrap_or("1m");
-                format!("{}@kline_{}", binance_symbol, interval)
-            }
-            _ => return Err(anyhow::anyhow!("Unsupported channel: {}", channel)),
-        };
+        let stream = Self::stream_name(symbol, channel, params.as_ref())?;
 
         let subscribe_msg = json!({
             "method": "SUBSCRIBE",
@@ -343,17 +372,56 @@ impl WebSocketAdapter for BinanceAdapter {
             "id": Self::now()
         });
 
-        let mut ws_lock = ws.write().await;
-        ws_lock
+        sink.lock()
+            .await
             .send(Message::Text(subscribe_msg.to_string()))
             .await?;
 
         Ok(())
     }
 
+    /// Every valid stream goes out in one SUBSCRIBE request
+    async fn subscribe_many(
+        &mut self,
+        symbols: &[String],
+        channel: &str,
+        params: Option<Value>,
+    ) -> Vec<anyhow::Result<()>> {
+        let streams: Vec<anyhow::Result<String>> = symbols
+            .iter()
+            .map(|symbol| Self::stream_name(symbol, channel, params.as_ref()))
+            .collect();
+        let valid: Vec<&String> = streams.iter().filter_map(|s| s.as_ref().ok()).collect();
+
+        let sent = match self.sink.as_ref() {
+            _ if valid.is_empty() => Ok(()),
+            None => Err("Not connected".to_string()),
+            Some(sink) => {
+                let subscribe_msg = json!({
+                    "method": "SUBSCRIBE",
+                    "params": valid,
+                    "id": Self::now()
+                });
+                sink.lock()
+                    .await
+                    .send(Message::Text(subscribe_msg.to_string()))
+                    .await
+                    .map_err(|e| e.to_string())
+            }
+        };
+
+        streams
+            .into_iter()
+            .map(|stream| {
+                stream?;
+                sent.clone().map_err(anyhow::Error::msg)
+            })
+            .collect()
+    }
+
     async fn unsubscribe(&mut self, symbol: &str, channel: &str) -> anyhow::Result<()> {
-        let ws = self
-            .ws
+        let sink = self
+            .sink
             .as_ref()
             .ok_or_else(|| anyhow::anyhow!("Not connected"))?;
 
@@ -373,8 +441,8 @@ impl WebSocketAdapter for BinanceAdapter {
             "id": Self::now()
         });
 
-        let mut ws_lock = ws.write().await;
-        ws_lock
+        sink.lock()
+            .await
             .send(Message::Text(unsubscribe_msg.to_string()))
             .await?;
 
@@ -385,6 +453,25 @@ impl WebSocketAdapter for BinanceAdapter {
         self.message_callback = Some(Arc::new(callback));
     }
 
+    fn set_raw_capture(&mut self, capture: Arc<RawCapture>) {
+        self.raw_capture = capture;
+    }
+
+    /// Binance answers WebSocket Ping frames with a Pong
+    fn supports_keepalive(&self) -> bool {
+        true
+    }
+
+    fn set_pong_tracker(&mut self, tracker: Arc<PongTracker>) {
+        self.pong_tracker = tracker;
+    }
+
+    async fn send_ping(&mut self) -> anyhow::Result<()> {
+        let sink = self.sink.as_ref().ok_or_else(|| anyhow::anyhow!("Not connected"))?;
+        sink.lock().await.send(Message::Ping(Vec::new())).await?;
+        Ok(())
+    }
+
     fn provider_name(&self) -> &str {
         "binance"
     }
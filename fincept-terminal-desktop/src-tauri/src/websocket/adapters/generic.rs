@@ -0,0 +1,258 @@
+// Generic WebSocket Adapter
+//
+// Connects to any feed described by a `MessageMapping` (see `websocket::mapping`):
+// subscribe/unsubscribe send the mapping's templates, and incoming text frames
+// are parsed by `mapping::parse_message`. Used for providers without a built-in
+// adapter, or to override one.
+
+use super::{WebSocketAdapter, CLOSE_TIMEOUT};
+use crate::websocket::keepalive::PongTracker;
+use crate::websocket::mapping::{self, MessageMapping};
+use crate::websocket::raw_capture::{RawCapture, UNMATCHED_CHANNEL};
+use crate::websocket::types::*;
+use async_trait::async_trait;
+use futures_util::stream::{SplitSink, SplitStream};
+use futures_util::{SinkExt, StreamExt};
+use serde_json::Value;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::sync::Mutex;
+use tokio::task::JoinHandle;
+use tokio_tungstenite::tungstenite::protocol::frame::coding::CloseCode;
+use tokio_tungstenite::tungstenite::protocol::CloseFrame;
+use tokio_tungstenite::{connect_async, tungstenite::Message, MaybeTlsStream, WebSocketStream};
+
+type WsStream = WebSocketStream<MaybeTlsStream<tokio::net::TcpStream>>;
+
+pub struct GenericAdapter {
+    config: ProviderConfig,
+    mapping: Arc<MessageMapping>,
+    // Split so sending a subscription never waits on the receive loop
+    sink: Option<Arc<Mutex<SplitSink<WsStream, Message>>>>,
+    reader: Option<JoinHandle<()>>,
+    message_callback: Option<Arc<Box<dyn Fn(MarketMessage) + Send + Sync>>>,
+    connected: Arc<AtomicBool>,
+    raw_capture: Arc<RawCapture>,
+    pong_tracker: Arc<PongTracker>,
+}
+
+impl GenericAdapter {
+    pub fn new(config: ProviderConfig, mapping: MessageMapping) -> Self {
+        Self {
+            config,
+            mapping: Arc::new(mapping),
+            sink: None,
+            reader: None,
+            message_callback: None,
+            connected: Arc::new(AtomicBool::new(false)),
+            raw_capture: Arc::new(RawCapture::default()),
+            pong_tracker: Arc::new(PongTracker::default()),
+        }
+    }
+
+    fn now() -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_millis() as u64
+    }
+
+    async fn receive_loop(
+        provider: String,
+        mut stream: SplitStream<WsStream>,
+        mapping: Arc<MessageMapping>,
+        callback: Option<Arc<Box<dyn Fn(MarketMessage) + Send + Sync>>>,
+        connected: Arc<AtomicBool>,
+        raw_capture: Arc<RawCapture>,
+        pong_tracker: Arc<PongTracker>,
+    ) {
+        while let Some(msg) = stream.next().await {
+            match msg {
+                Ok(Message::Text(text)) => {
+                    let parsed = serde_json::from_str::<Value>(&text)
+                        .map(|data| mapping::parse_message(&mapping, &provider, &data, Self::now()))
+                        .unwrap_or_default();
+                    if raw_capture.is_enabled() {
+                        // Filed under the mapping section that matched
+                        let (symbol, channel) = match parsed.first() {
+                            Some(MarketMessage::Ticker(t)) => (t.symbol.as_str(), "ticker"),
+                            Some(MarketMessage::OrderBook(o)) => (o.symbol.as_str(), "orderbook"),
+                            _ => ("", UNMATCHED_CHANNEL),
+                        };
+                        raw_capture.record(&provider, symbol, channel, &text);
+                    }
+                    if let Some(callback) = &callback {
+                        for market_msg in parsed {
+                            callback(market_msg);
+                        }
+                    }
+                }
+                Ok(Message::Pong(_)) => pong_tracker.pong_received(),
+                Ok(_) => {}
+                Err(e) => {
+                    eprintln!("[{}] WebSocket error: {}", provider, e);
+                    break;
+                }
+            }
+        }
+        connected.store(false, Ordering::SeqCst);
+    }
+
+    async fn send(&self, template: Option<&Value>, symbol: &str, channel: &str) -> anyhow::Result<()> {
+        let sink = self.sink.as_ref().ok_or_else(|| anyhow::anyhow!("Not connected"))?;
+        // Feeds that stream everything on connect have no template
+        let Some(template) = template else {
+            return Ok(());
+        };
+        let text = mapping::render_template(template, symbol, channel).to_string();
+        sink.lock()
+            .await
+            .send(Message::Text(text))
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to send to {}: {}", self.config.name, e))
+    }
+}
+
+#[async_trait]
+impl WebSocketAdapter for GenericAdapter {
+    async fn connect(&mut self) -> anyhow::Result<()> {
+        let (ws_stream, _) = connect_async(self.config.url.trim()).await?;
+        let (sink, stream) = ws_stream.split();
+        self.sink = Some(Arc::new(Mutex::new(sink)));
+        self.connected.store(true, Ordering::SeqCst);
+
+        self.reader = Some(tokio::spawn(Self::receive_loop(
+            self.config.name.clone(),
+            stream,
+            self.mapping.clone(),
+            self.message_callback.clone(),
+            self.connected.clone(),
+            self.raw_capture.clone(),
+            self.pong_tracker.clone(),
+        )));
+        Ok(())
+    }
+
+    async fn disconnect(&mut self) -> anyhow::Result<()> {
+        self.connected.store(false, Ordering::SeqCst);
+
+        if let Some(sink) = self.sink.take() {
+            let mut sink = sink.lock().await;
+            sink.send(Message::Close(Some(CloseFrame {
+                code: CloseCode::Normal,
+                reason: "client disconnect".into(),
+            })))
+            .await?;
+        }
+        // The reader ends once the provider echoes the Close frame
+        if let Some(mut reader) = self.reader.take() {
+            if tokio::time::timeout(CLOSE_TIMEOUT, &mut reader).await.is_err() {
+                reader.abort();
+                anyhow::bail!("Provider did not acknowledge close within {:?}", CLOSE_TIMEOUT);
+            }
+        }
+        Ok(())
+    }
+
+    async fn subscribe(&mut self, symbol: &str, channel: &str, _params: Option<Value>) -> anyhow::Result<()> {
+        self.send(self.mapping.subscribe.as_ref(), symbol, channel).await
+    }
+
+    async fn unsubscribe(&mut self, symbol: &str, channel: &str) -> anyhow::Result<()> {
+        self.send(self.mapping.unsubscribe.as_ref(), symbol, channel).await
+    }
+
+    fn set_message_callback(&mut self, callback: Box<dyn Fn(MarketMessage) + Send + Sync>) {
+        self.message_callback = Some(Arc::new(callback));
+    }
+
+    fn set_raw_capture(&mut self, capture: Arc<RawCapture>) {
+        self.raw_capture = capture;
+    }
+
+    /// Mapped feeds get a WebSocket Ping frame, which every server must answer
+    fn supports_keepalive(&self) -> bool {
+        true
+    }
+
+    fn set_pong_tracker(&mut self, tracker: Arc<PongTracker>) {
+        self.pong_tracker = tracker;
+    }
+
+    async fn send_ping(&mut self) -> anyhow::Result<()> {
+        let sink = self.sink.as_ref().ok_or_else(|| anyhow::anyhow!("Not connected"))?;
+        sink.lock()
+            .await
+            .send(Message::Ping(Vec::new()))
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to ping {}: {}", self.config.name, e))
+    }
+
+    fn provider_name(&self) -> &str {
+        &self.config.name
+    }
+
+    fn is_connected(&self) -> bool {
+        self.connected.load(Ordering::SeqCst)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::sync::Mutex as StdMutex;
+
+    #[tokio::test]
+    async fn test_subscribes_with_template_and_emits_mapped_ticker() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = tokio::spawn(async move {
+            let (tcp, _) = listener.accept().await.unwrap();
+            let mut ws = tokio_tungstenite::accept_async(tcp).await.unwrap();
+            let Some(Ok(Message::Text(sub))) = ws.next().await else {
+                panic!("expected a subscription");
+            };
+            ws.send(Message::Text(json!({ "ev": "T", "s": "BTCUSD", "p": "100.5" }).to_string()))
+                .await
+                .unwrap();
+            while let Some(Ok(_)) = ws.next().await {}
+            sub
+        });
+
+        let mapping: MessageMapping = serde_json::from_value(json!({
+            "subscribe": { "action": "sub", "params": "{channel}:{symbol}" },
+            "ticker": { "when": { "pointer": "/ev", "equals": "T" }, "symbol": "/s", "price": "/p" }
+        }))
+        .unwrap();
+        let config = ProviderConfig {
+            name: "custom".to_string(),
+            url: format!("ws://{}", addr),
+            ..Default::default()
+        };
+        let mut adapter = GenericAdapter::new(config, mapping);
+        let received = Arc::new(StdMutex::new(Vec::new()));
+        let sink = received.clone();
+        adapter.set_message_callback(Box::new(move |msg| sink.lock().unwrap().push(msg)));
+
+        adapter.connect().await.unwrap();
+        adapter.subscribe("BTCUSD", "ticker", None).await.unwrap();
+        for _ in 0..50 {
+            if !received.lock().unwrap().is_empty() {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        adapter.disconnect().await.unwrap();
+
+        let sub: Value = serde_json::from_str(&server.await.unwrap()).unwrap();
+        assert_eq!(sub, json!({ "action": "sub", "params": "ticker:BTCUSD" }));
+        let received = received.lock().unwrap();
+        let [MarketMessage::Ticker(t)] = received.as_slice() else {
+            panic!("expected one ticker, got {:?}", received);
+        };
+        assert_eq!((t.symbol.as_str(), t.price), ("BTCUSD", 100.5));
+        assert!(!adapter.is_connected());
+    }
+}
@@ -2,7 +2,9 @@
 // WebSocket URL: wss://api.hyperliquid.xyz/ws
 // Documentation: https://hyperliquid.gitbook.io/hyperliquid-docs/for-developers/api/websocket
 
-use super::WebSocketAdapter;
+use super::{close_gracefully, WebSocketAdapter, CLOSE_TIMEOUT, READ_POLL};
+use crate::websocket::keepalive::PongTracker;
+use crate::websocket::raw_capture::RawCapture;
 use crate::websocket::types::*;
 use async_trait::async_trait;
 use futures_util::{SinkExt, StreamExt};
@@ -121,6 +123,8 @@ pub struct HyperLiquidAdapter {
     ws_stream: Arc<RwLock<Option<WebSocketStream<MaybeTlsStream<TcpStream>>>>>,
     subscriptions: Arc<RwLock<HashMap<String, String>>>, // key: "symbol:channel", value: coin
     ticker_states: Arc<RwLock<HashMap<String, TickerState>>>, // coin -> ticker state
+    raw_capture: Arc<RawCapture>,
+    pong_tracker: Arc<PongTracker>,
 }
 
 impl HyperLiquidAdapter {
@@ -131,6 +135,8 @@ impl HyperLiquidAdapter {
             ws_stream: Arc::new(RwLock::new(None)),
             subscriptions: Arc::new(RwLock::new(HashMap::new())),
             ticker_states: Arc::new(RwLock::new(HashMap::new())),
+            raw_capture: Arc::new(RawCapture::default()),
+            pong_tracker: Arc::new(PongTracker::default()),
         }
     }
 
@@ -150,6 +156,13 @@ impl HyperLiquidAdapter {
         }
     }
 
+    /// Reply to a keepalive ping: `{"channel":"pong"}`
+    fn is_pong(text: &str) -> bool {
+        text.contains("pong")
+            && serde_json::from_str::<Value>(text)
+                .is_ok_and(|data| data.get("channel").and_then(Value::as_str) == Some("pong"))
+    }
+
     fn parse_trade(&self, coin: &str, data: &Value) -> Option<MarketMessage> {
         let trades: Vec<WsTrade> = serde_json::from_value(data.clone()).ok()?;
 
@@ -234,6 +247,7 @@ impl HyperLiquidAdapter {
                     change: None,
                     change_percent: None,
                     timestamp: chrono::Utc::now().timestamp_millis() as u64,
+                    replayed: false,
                 }));
             }
         }
@@ -284,13 +298,20 @@ impl WebSocketAdapter for HyperLiquidAdapter {
         let ws_clone = self.ws_stream.clone();
         let callback_clone = self.message_callback.clone();
         let ticker_states_clone = self.ticker_states.clone();
+        let raw_capture = self.raw_capture.clone();
+        let pong_tracker = self.pong_tracker.clone();
 
         tokio::spawn(async move {
             loop {
+                // Release the stream at least every READ_POLL so pings and
+                // subscriptions can be sent while the feed is quiet
                 let message = {
                     let mut stream = ws_clone.write().await;
                     if let Some(ws) = stream.as_mut() {
-                        ws.next().await
+                        match tokio::time::timeout(READ_POLL, ws.next()).await {
+                            Ok(next) => next,
+                            Err(_) => continue,
+                        }
                     } else {
                         break;
                     }
@@ -299,6 +320,16 @@ impl WebSocketAdapter for HyperLiquidAdapter {
                 match message {
                     Some(Ok(msg)) => {
                         if let Message::Text(text) = msg {
+                            raw_capture.record_frame(
+                                "hyperliquid",
+                                "channel",
+                                &["/data/coin", "/data/s", "/data/0/coin"],
+                                &text,
+                            );
+                            if Self::is_pong(&text) {
+                                pong_tracker.pong_received();
+                                continue;
+                            }
                             // Parse message
                             if let Ok(ws_msg) = serde_json::from_str::<WsMessage>(&text) {
 
@@ -325,6 +356,7 @@ impl WebSocketAdapter for HyperLiquidAdapter {
                                                         change: None,
                                                         change_percent: None,
                                                         timestamp: chrono::Utc::now().timestamp_millis() as u64,
+                                                        replayed: false,
                                                     }));
                                                 }
                                             }
@@ -403,6 +435,7 @@ impl WebSocketAdapter for HyperLiquidAdapter {
                                                     change: None,
                                                     change_percent: None,
                                                     timestamp: bbo.time,
+                                                    replayed: false,
                                                 });
                                                 drop(states); // Release lock
 
@@ -455,6 +488,7 @@ impl WebSocketAdapter for HyperLiquidAdapter {
                                                     change: Some(change),
                                                     change_percent: Some(change_percent),
                                                     timestamp: candle.end_time,
+                                                    replayed: false,
                                                 });
                                                 drop(states); // Release lock
 
@@ -518,9 +552,13 @@ impl WebSocketAdapter for HyperLiquidAdapter {
     }
 
     async fn disconnect(&mut self) -> anyhow::Result<()> {
-        let mut stream = self.ws_stream.write().await;
-        if let Some(mut ws) = stream.take() {
-            ws.close(None).await?;
+        // Taking the stream also ends the receive loop
+        let stream = tokio::time::timeout(CLOSE_TIMEOUT, self.ws_stream.write())
+            .await
+            .map_err(|_| anyhow::anyhow!("Socket busy; closed without handshake"))?
+            .take();
+        if let Some(mut ws) = stream {
+            close_gracefully(&mut ws).await?;
         }
         Ok(())
     }
@@ -620,6 +658,24 @@ impl WebSocketAdapter for HyperLiquidAdapter {
         self.message_callback = Some(Arc::new(callback));
     }
 
+    fn set_raw_capture(&mut self, capture: Arc<RawCapture>) {
+        self.raw_capture = capture;
+    }
+
+    /// HyperLiquid answers `{"method":"ping"}` with `{"channel":"pong"}`, and closes
+    /// connections that send nothing for a minute
+    fn supports_keepalive(&self) -> bool {
+        true
+    }
+
+    fn set_pong_tracker(&mut self, tracker: Arc<PongTracker>) {
+        self.pong_tracker = tracker;
+    }
+
+    async fn send_ping(&mut self) -> anyhow::Result<()> {
+        self.send_message(json!({ "method": "ping" })).await
+    }
+
     fn provider_name(&self) -> &str {
         "hyperliquid"
     }
@@ -3,7 +3,9 @@
 // Implements Kraken WebSocket API v2
 // Supports: ticker, book, trade, ohlc channels
 
-use super::WebSocketAdapter;
+use super::{close_gracefully, WebSocketAdapter, CLOSE_TIMEOUT, READ_POLL};
+use crate::websocket::keepalive::PongTracker;
+use crate::websocket::raw_capture::RawCapture;
 use crate::websocket::types::*;
 use async_trait::async_trait;
 use futures_util::{SinkExt, StreamExt};
@@ -20,6 +22,8 @@ pub struct KrakenAdapter {
     ws: Option<Arc<RwLock<tokio_tungstenite::WebSocketStream<tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>>>>>,
     message_callback: Option<Arc<Box<dyn Fn(MarketMessage) + Send + Sync>>>,
     connected: Arc<RwLock<bool>>,
+    raw_capture: Arc<RawCapture>,
+    pong_tracker: Arc<PongTracker>,
 }
 
 impl KrakenAdapter {
@@ -29,9 +33,66 @@ impl KrakenAdapter {
             ws: None,
             message_callback: None,
             connected: Arc::new(RwLock::new(false)),
+            raw_capture: Arc::new(RawCapture::default()),
+            pong_tracker: Arc::new(PongTracker::default()),
         }
     }
 
+    /// Send one subscribe request covering `symbols` on `channel`
+    async fn send_subscription(&self, symbols: &[&str], channel: &str, params: Option<Value>) -> anyhow::Result<()> {
+        let ws = self.ws.as_ref()
+            .ok_or_else(|| {
+                eprintln!("[Kraken::subscribe] ✗ Not connected");
+                anyhow::anyhow!("Not connected")
+            })?;
+
+        // Build subscription message
+        let mut sub_msg = serde_json::json!({
+            "method": "subscribe",
+            "params": {
+                "channel": channel,
+                "symbol": symbols,
+            }
+        });
+
+        // Add optional params (depth, snapshot, etc.)
+        if let Some(p) = params {
+            if let Some(obj) = sub_msg.get_mut("params") {
+                if let Some(params_obj) = obj.as_object_mut() {
+                    if let Some(p_obj) = p.as_object() {
+                        for (k, v) in p_obj {
+                            params_obj.insert(k.clone(), v.clone());
+                        }
+                    }
+                }
+            }
+        }
+
+        let msg_str = serde_json::to_string(&sub_msg)?;
+        eprintln!("[Kraken::subscribe] Sending subscription message: {}", msg_str);
+
+        ws.write().await.send(Message::Text(msg_str)).await
+            .map_err(|e| {
+                eprintln!("[Kraken::subscribe] ✗ Failed to send: {}", e);
+                anyhow::anyhow!("Failed to send subscription: {}", e)
+            })?;
+
+        eprintln!("[Kraken::subscribe] ✓ Subscription message sent");
+        Ok(())
+    }
+
+    /// Reply to a keepalive ping: `{"method":"pong"}` in v2, `{"event":"pong"}` in v1
+    fn is_pong(text: &str) -> bool {
+        if !text.contains("pong") {
+            return false;
+        }
+        serde_json::from_str::<Value>(text).is_ok_and(|data| {
+            ["method", "event"]
+                .iter()
+                .any(|key| data.get(*key).and_then(Value::as_str) == Some("pong"))
+        })
+    }
+
     fn now() -> u64 {
         SystemTime::now()
             .duration_since(UNIX_EPOCH)
@@ -79,6 +140,7 @@ impl KrakenAdapter {
             change: None,
             change_percent: None,
             timestamp: Self::now(),
+            replayed: false,
         })
     }
 
@@ -236,21 +298,37 @@ impl KrakenAdapter {
         ws: Arc<RwLock<tokio_tungstenite::WebSocketStream<tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>>>>,
         callback: Arc<Box<dyn Fn(MarketMessage) + Send + Sync>>,
         connected: Arc<RwLock<bool>>,
+        raw_capture: Arc<RawCapture>,
+        pong_tracker: Arc<PongTracker>,
     ) {
         loop {
+            // Release the socket at least every READ_POLL so pings and
+            // subscriptions can be sent while the feed is quiet
             let msg_result = {
                 let mut ws_lock = ws.write().await;
-                ws_lock.next().await
+                match tokio::time::timeout(READ_POLL, ws_lock.next()).await {
+                    Ok(next) => next,
+                    Err(_) => continue,
+                }
             };
 
             match msg_result {
                 Some(Ok(msg)) => {
+                    if let Message::Text(text) = &msg {
+                        raw_capture.record_frame("kraken", "channel", &["/data/0/symbol"], text);
+                        if Self::is_pong(text) {
+                            pong_tracker.pong_received();
+                            continue;
+                        }
+                    }
                     // Handle message in separate task to avoid blocking
                     let adapter = KrakenAdapter {
                         config: ProviderConfig::default(),
                         ws: None,
                         message_callback: Some(callback.clone()),
                         connected: connected.clone(),
+                        raw_capture: raw_capture.clone(),
+                        pong_tracker: pong_tracker.clone(),
                     };
                     tokio::spawn(async move {
                         adapter.handle_message(msg).await;
@@ -296,8 +374,10 @@ impl WebSocketAdapter for KrakenAdapter {
         // Start receive loop
         if let Some(callback) = self.message_callback.clone() {
             let connected = self.connected.clone();
+            let raw_capture = self.raw_capture.clone();
+            let pong_tracker = self.pong_tracker.clone();
             tokio::spawn(async move {
-                Self::receive_loop(ws, callback, connected).await;
+                Self::receive_loop(ws, callback, connected, raw_capture, pong_tracker).await;
             });
             eprintln!("[Kraken::connect] ✓ Receive loop started");
         } else {
@@ -311,11 +391,14 @@ impl WebSocketAdapter for KrakenAdapter {
     async fn disconnect(&mut self) -> anyhow::Result<()> {
         *self.connected.write().await = false;
 
-        if let Some(ws) = &self.ws {
-            ws.write().await.close(None).await?;
+        if let Some(ws) = self.ws.take() {
+            // The receive loop releases the socket between messages
+            let mut ws = tokio::time::timeout(CLOSE_TIMEOUT, ws.write())
+                .await
+                .map_err(|_| anyhow::anyhow!("Socket busy; closed without handshake"))?;
+            close_gracefully(&mut *ws).await?;
         }
 
-        self.ws = None;
         Ok(())
     }
 
@@ -326,46 +409,22 @@ impl WebSocketAdapter for KrakenAdapter {
         params: Option<Value>,
     ) -> anyhow::Result<()> {
         eprintln!("[Kraken::subscribe] symbol={}, channel={}", symbol, channel);
+        self.send_subscription(&[symbol], channel, params).await
+    }
 
-        let ws = self.ws.as_ref()
-            .ok_or_else(|| {
-                eprintln!("[Kraken::subscribe] ✗ Not connected");
-                anyhow::anyhow!("Not connected")
-            })?;
-
-        // Build subscription message
-        let mut sub_msg = serde_json::json!({
-            "method": "subscribe",
-            "params": {
-                "channel": channel,
-                "symbol": [symbol],
-            }
-        });
-
-        // Add optional params (depth, snapshot, etc.)
-        if let Some(p) = params {
-            if let Some(obj) = sub_msg.get_mut("params") {
-                if let Some(params_obj) = obj.as_object_mut() {
-                    if let Some(p_obj) = p.as_object() {
-                        for (k, v) in p_obj {
-                            params_obj.insert(k.clone(), v.clone());
-                        }
-                    }
-                }
-            }
-        }
-
-        let msg_str = serde_json::to_string(&sub_msg)?;
-        eprintln!("[Kraken::subscribe] Sending subscription message: {}", msg_str);
-
-        ws.write().await.send(Message::Text(msg_str)).await
-            .map_err(|e| {
-                eprintln!("[Kraken::subscribe] ✗ Failed to send: {}", e);
-                anyhow::anyhow!("Failed to send subscription: {}", e)
-            })?;
-
-        eprintln!("[Kraken::subscribe] ✓ Subscription message sent");
-        Ok(())
+    /// Kraken takes a symbol list, so the whole batch is one message
+    async fn subscribe_many(
+        &mut self,
+        symbols: &[String],
+        channel: &str,
+        params: Option<Value>,
+    ) -> Vec<anyhow::Result<()>> {
+        let symbols: Vec<&str> = symbols.iter().map(String::as_str).collect();
+        let result = self.send_subscription(&symbols, channel, params).await;
+        symbols
+            .iter()
+            .map(|_| result.as_ref().copied().map_err(|e| anyhow::anyhow!("{}", e)))
+            .collect()
     }
 
     async fn unsubscribe(&mut self, symbol: &str, channel: &str) -> anyhow::Result<()> {
@@ -390,6 +449,26 @@ impl WebSocketAdapter for KrakenAdapter {
         self.message_callback = Some(Arc::new(callback));
     }
 
+    fn set_raw_capture(&mut self, capture: Arc<RawCapture>) {
+        self.raw_capture = capture;
+    }
+
+    /// Kraken answers `{"method":"ping"}` with `{"method":"pong"}`
+    fn supports_keepalive(&self) -> bool {
+        true
+    }
+
+    fn set_pong_tracker(&mut self, tracker: Arc<PongTracker>) {
+        self.pong_tracker = tracker;
+    }
+
+    async fn send_ping(&mut self) -> anyhow::Result<()> {
+        let ws = self.ws.as_ref().ok_or_else(|| anyhow::anyhow!("Not connected"))?;
+        let ping = serde_json::json!({ "method": "ping", "req_id": Self::now() });
+        ws.write().await.send(Message::Text(ping.to_string())).await?;
+        Ok(())
+    }
+
     fn provider_name(&self) -> &str {
         "kraken"
     }
@@ -1,17 +1,27 @@
 // WebSocket Adapters - Provider-specific implementations
 
+use super::keepalive::PongTracker;
 use super::types::*;
 use async_trait::async_trait;
+use serde::Serialize;
+use futures_util::StreamExt;
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio_tungstenite::tungstenite::protocol::frame::coding::CloseCode;
+use tokio_tungstenite::tungstenite::protocol::CloseFrame;
+use tokio_tungstenite::WebSocketStream;
 
 pub mod kraken;
 pub mod hyperliquid;
 pub mod binance;
 pub mod fyers;
+pub mod generic;
 
 pub use kraken::KrakenAdapter;
 pub use hyperliquid::HyperLiquidAdapter;
 pub use binance::BinanceAdapter;
 pub use fyers::FyersAdapter;
+pub use generic::GenericAdapter;
 
 // ============================================================================
 // ADAPTER TRAIT
@@ -23,9 +33,16 @@ pub trait WebSocketAdapter: Send + Sync {
     /// Connect to WebSocket
     async fn connect(&mut self) -> anyhow::Result<()>;
 
-    /// Disconnect from WebSocket
+    /// Disconnect from WebSocket, completing the close handshake where possible
+    /// (see `close_gracefully`)
     async fn disconnect(&mut self) -> anyhow::Result<()>;
 
+    /// Whether the provider expects every topic to be unsubscribed before the
+    /// socket closes; the manager does so on disconnect when this is true
+    fn unsubscribe_before_close(&self) -> bool {
+        false
+    }
+
     /// Subscribe to a channel
     async fn subscribe(
         &mut self,
@@ -34,12 +51,46 @@ pub trait WebSocketAdapter: Send + Sync {
         params: Option<serde_json::Value>,
     ) -> anyhow::Result<()>;
 
+    /// Subscribe several symbols to one channel, returning one result per symbol
+    /// in order. Adapters whose protocol takes a symbol list override this to
+    /// send a single message; the default subscribes them one by one.
+    async fn subscribe_many(
+        &mut self,
+        symbols: &[String],
+        channel: &str,
+        params: Option<serde_json::Value>,
+    ) -> Vec<anyhow::Result<()>> {
+        let mut results = Vec::with_capacity(symbols.len());
+        for symbol in symbols {
+            results.push(self.subscribe(symbol, channel, params.clone()).await);
+        }
+        results
+    }
+
     /// Unsubscribe from a channel
     async fn unsubscribe(&mut self, symbol: &str, channel: &str) -> anyhow::Result<()>;
 
     /// Set message callback
     fn set_message_callback(&mut self, callback: Box<dyn Fn(MarketMessage) + Send + Sync>);
 
+    /// Share the manager's raw frame store; adapters record incoming text frames
+    /// in it while debug capture is enabled. Adapters that don't capture ignore it.
+    fn set_raw_capture(&mut self, _capture: std::sync::Arc<super::raw_capture::RawCapture>) {}
+
+    /// Whether `send_ping` speaks the provider's heartbeat; the manager only runs
+    /// a keepalive for adapters that do (see `websocket::keepalive`)
+    fn supports_keepalive(&self) -> bool {
+        false
+    }
+
+    /// Share the tracker the receive loop reports the provider's pongs to
+    fn set_pong_tracker(&mut self, _tracker: std::sync::Arc<PongTracker>) {}
+
+    /// Send one protocol-appropriate ping; its reply goes to the pong tracker
+    async fn send_ping(&mut self) -> anyhow::Result<()> {
+        Err(anyhow::anyhow!("{} has no keepalive ping", self.provider_name()))
+    }
+
     /// Get provider name
     fn provider_name(&self) -> &str;
 
@@ -47,15 +98,57 @@ pub trait WebSocketAdapter: Send + Sync {
     fn is_connected(&self) -> bool;
 }
 
+// ============================================================================
+// CLOSE HANDSHAKE
+// ============================================================================
+
+/// How long a disconnect waits for the socket, and then for the provider's Close reply
+pub const CLOSE_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Longest a receive loop holds its socket while waiting for a frame, so pings,
+/// subscribes and disconnects are not held up by a quiet feed
+pub const READ_POLL: Duration = Duration::from_millis(200);
+
+/// Send a normal Close frame and wait for the provider to acknowledge it
+///
+/// Dropping the socket without this looks like an abnormal disconnect (1006) to the
+/// exchange, which some count against the client. Messages still in flight are
+/// discarded; the handshake is done once the stream ends.
+pub async fn close_gracefully<S>(ws: &mut WebSocketStream<S>) -> anyhow::Result<()>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    ws.close(Some(CloseFrame {
+        code: CloseCode::Normal,
+        reason: "client disconnect".into(),
+    }))
+    .await?;
+
+    let drained = tokio::time::timeout(CLOSE_TIMEOUT, async {
+        while let Some(msg) = ws.next().await {
+            if msg.is_err() {
+                break;
+            }
+        }
+    })
+    .await;
+
+    drained.map_err(|_| anyhow::anyhow!("Provider did not acknowledge close within {:?}", CLOSE_TIMEOUT))
+}
+
 // ============================================================================
 // ADAPTER FACTORY
 // ============================================================================
 
-/// Create adapter for a provider
+/// Create adapter for a provider; a configured message mapping takes precedence
+/// over the built-in adapter
 pub fn create_adapter(
     provider: &str,
     config: ProviderConfig,
 ) -> anyhow::Result<Box<dyn WebSocketAdapter>> {
+    if let Some(mapping) = config.message_mapping.clone() {
+        return Ok(Box::new(GenericAdapter::new(config, mapping)));
+    }
     match provider.to_lowercase().as_str() {
         "kraken" => Ok(Box::new(KrakenAdapter::new(config))),
         "hyperliquid" => Ok(Box::new(HyperLiquidAdapter::new(config))),
@@ -65,6 +158,208 @@ pub fn create_adapter(
     }
 }
 
+// ============================================================================
+// PROVIDER REGISTRY
+// ============================================================================
+
+/// What a provider needs in its `ProviderConfig`, and what its adapter supports
+#[derive(Debug, Clone, Copy)]
+pub struct ProviderSpec {
+    pub name: &'static str,
+    /// The adapter connects to `config.url` (others use a built-in endpoint)
+    pub requires_url: bool,
+    /// Channel names `subscribe` accepts; aliases the adapter also takes are left out
+    pub channels: &'static [&'static str],
+    /// Connecting needs credentials (api key or access token)
+    pub requires_auth: bool,
+    /// `subscribe_many` sends one message for the whole batch
+    pub batch_subscribe: bool,
+    /// How symbols are written when subscribing, with an example
+    pub symbol_format: &'static str,
+}
+
+/// Providers `create_adapter` can build without a message mapping - keep in sync
+/// with the match above and with each adapter's `subscribe`
+pub const SUPPORTED_PROVIDERS: &[ProviderSpec] = &[
+    ProviderSpec {
+        name: "kraken",
+        requires_url: true,
+        channels: &["ticker", "book", "trade", "ohlc"],
+        requires_auth: false,
+        batch_subscribe: true,
+        symbol_format: "BASE/QUOTE, e.g. BTC/USD",
+    },
+    ProviderSpec {
+        name: "hyperliquid",
+        requires_url: false,
+        channels: &["ticker", "book", "trades"],
+        requires_auth: false,
+        batch_subscribe: false,
+        symbol_format: "coin, e.g. BTC (a /USD suffix is dropped)",
+    },
+    ProviderSpec {
+        name: "binance",
+        requires_url: false,
+        channels: &["ticker", "book_ticker", "book", "trade", "candle"],
+        requires_auth: false,
+        batch_subscribe: true,
+        symbol_format: "BASEQUOTE or BASE/QUOTE, e.g. BTCUSDT",
+    },
+    // The adapter is a stub: it has no channels until it is implemented
+    ProviderSpec {
+        name: "fyers",
+        requires_url: false,
+        channels: &[],
+        requires_auth: true,
+        batch_subscribe: false,
+        symbol_format: "EXCHANGE:SYMBOL-SERIES, e.g. NSE:SBIN-EQ",
+    },
+];
+
+/// What a provider's adapter supports, for offering only options that can work
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ProviderCapabilities {
+    pub provider: String,
+    pub channels: Vec<String>,
+    pub requires_auth: bool,
+    pub batch_subscribe: bool,
+    pub symbol_format: String,
+    /// Served by the generic adapter from a configured message mapping
+    pub mapped: bool,
+}
+
+impl ProviderCapabilities {
+    pub fn from_spec(spec: &ProviderSpec) -> Self {
+        Self {
+            provider: spec.name.to_string(),
+            channels: spec.channels.iter().map(|c| c.to_string()).collect(),
+            requires_auth: spec.requires_auth,
+            batch_subscribe: spec.batch_subscribe,
+            symbol_format: spec.symbol_format.to_string(),
+            mapped: false,
+        }
+    }
+
+    /// A mapped feed parses tickers and/or order books; its subscribe template
+    /// decides how the symbol is written
+    pub fn from_mapping(config: &ProviderConfig, mapping: &crate::websocket::mapping::MessageMapping) -> Self {
+        let mut channels = Vec::new();
+        if mapping.ticker.is_some() {
+            channels.push("ticker".to_string());
+        }
+        if mapping.orderbook.is_some() {
+            channels.push("orderbook".to_string());
+        }
+        let has_value = |v: &Option<String>| v.as_deref().is_some_and(|s| !s.trim().is_empty());
+        Self {
+            provider: config.name.clone(),
+            channels,
+            requires_auth: has_value(&config.api_key),
+            batch_subscribe: false,
+            symbol_format: "as written in the subscribe template".to_string(),
+            mapped: true,
+        }
+    }
+}
+
+/// Look up a provider by name (case-insensitive)
+pub fn provider_spec(provider: &str) -> Option<&'static ProviderSpec> {
+    SUPPORTED_PROVIDERS
+        .iter()
+        .find(|spec| spec.name.eq_ignore_ascii_case(provider.trim()))
+}
+
+fn supported_provider_names() -> String {
+    SUPPORTED_PROVIDERS
+        .iter()
+        .map(|spec| spec.name)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Validate a config before it is stored, so mistakes surface on save rather than on connect
+pub fn validate_config(config: &ProviderConfig) -> std::result::Result<(), WebSocketError> {
+    let invalid = |msg: String| Err(WebSocketError::InvalidConfig(msg));
+
+    if config.name.trim().is_empty() {
+        return invalid(format!(
+            "Provider name is required (supported: {})",
+            supported_provider_names()
+        ));
+    }
+
+    if let Some(mapping) = &config.message_mapping {
+        return validate_mapped_config(config, mapping);
+    }
+
+    let spec = match provider_spec(&config.name) {
+        Some(spec) => spec,
+        None => {
+            return invalid(format!(
+                "Unknown provider '{}' (supported: {})",
+                config.name,
+                supported_provider_names()
+            ))
+        }
+    };
+
+    let url = config.url.trim();
+    if url.is_empty() {
+        if spec.requires_url {
+            return invalid(format!("Provider '{}' requires a WebSocket url", spec.name));
+        }
+    } else if !(url.starts_with("wss://") || url.starts_with("ws://")) {
+        return invalid(format!(
+            "Invalid url '{}' for provider '{}': must start with ws:// or wss://",
+            url, spec.name
+        ));
+    }
+
+    let has_value = |v: &Option<String>| v.as_deref().is_some_and(|s| !s.trim().is_empty());
+    if has_value(&config.api_secret) && !has_value(&config.api_key) {
+        return invalid(format!(
+            "Provider '{}' has an api_secret but no api_key",
+            spec.name
+        ));
+    }
+
+    if config.heartbeat_interval_ms == 0 {
+        return invalid("heartbeat_interval_ms must be greater than 0".to_string());
+    }
+    if config.max_missed_pongs == 0 {
+        return invalid("max_missed_pongs must be greater than 0".to_string());
+    }
+
+    Ok(())
+}
+
+/// A mapped provider may have any name but needs its own url
+fn validate_mapped_config(
+    config: &ProviderConfig,
+    mapping: &crate::websocket::mapping::MessageMapping,
+) -> std::result::Result<(), WebSocketError> {
+    let invalid = |msg: String| Err(WebSocketError::InvalidConfig(msg));
+
+    let url = config.url.trim();
+    if !(url.starts_with("wss://") || url.starts_with("ws://")) {
+        return invalid(format!(
+            "Provider '{}' uses a message mapping and needs a ws:// or wss:// url",
+            config.name
+        ));
+    }
+    if let Err(e) = mapping.validate() {
+        return invalid(format!("Provider '{}': {}", config.name, e));
+    }
+    if config.heartbeat_interval_ms == 0 {
+        return invalid("heartbeat_interval_ms must be greater than 0".to_string());
+    }
+    if config.max_missed_pongs == 0 {
+        return invalid("max_missed_pongs must be greater than 0".to_string());
+    }
+
+    Ok(())
+}
+
 // ============================================================================
 // HELPER FUNCTIONS
 // ============================================================================
@@ -77,3 +372,101 @@ pub fn normalize_symbol(symbol: &str) -> String {
         .replace('_', "")
         .to_uppercase()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(name: &str, url: &str) -> ProviderConfig {
+        ProviderConfig {
+            name: name.to_string(),
+            url: url.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_capabilities_follow_spec_or_mapping() {
+        let binance = ProviderCapabilities::from_spec(provider_spec("Binance").unwrap());
+        assert!(binance.batch_subscribe && !binance.requires_auth && !binance.mapped);
+        assert!(binance.channels.iter().any(|c| c == "candle"));
+        assert!(provider_spec("fyers").unwrap().channels.is_empty());
+
+        let mapping: crate::websocket::mapping::MessageMapping = serde_json::from_value(serde_json::json!({
+            "orderbook": { "symbol": "/s", "bids": "/b", "asks": "/a" }
+        }))
+        .unwrap();
+        let mapped = ProviderCapabilities::from_mapping(&config("myfeed", "wss://feed.example"), &mapping);
+        assert_eq!((mapped.channels, mapped.mapped), (vec!["orderbook".to_string()], true));
+    }
+
+    #[test]
+    fn test_validate_config_rejects_unknown_provider() {
+        let err = validate_config(&config("krakken", "wss://ws.kraken.com/v2"))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("Unknown provider 'krakken'"));
+        assert!(err.contains("kraken, hyperliquid, binance, fyers"));
+    }
+
+    #[test]
+    fn test_validate_config_checks_url() {
+        assert!(validate_config(&config("kraken", "wss://ws.kraken.com/v2")).is_ok());
+        assert!(validate_config(&config("Kraken", "")).is_err());
+        assert!(validate_config(&config("binance", "")).is_ok());
+        assert!(validate_config(&config("binance", "https://api.binance.com")).is_err());
+    }
+
+    #[test]
+    fn test_validate_config_requires_key_with_secret() {
+        let mut cfg = config("binance", "");
+        cfg.api_secret = Some("secret".to_string());
+        assert!(validate_config(&cfg).is_err());
+        cfg.api_key = Some("key".to_string());
+        assert!(validate_config(&cfg).is_ok());
+    }
+
+    #[test]
+    fn test_validate_config_accepts_mapped_provider() {
+        let mut cfg = config("bybit", "");
+        cfg.message_mapping = Some(
+            serde_json::from_value(serde_json::json!({
+                "ticker": { "symbol": "/data/s", "price": "/data/lastPrice" }
+            }))
+            .unwrap(),
+        );
+        assert!(validate_config(&cfg).unwrap_err().to_string().contains("ws:// or wss:// url"));
+        cfg.url = "wss://stream.bybit.com/v5/public/spot".to_string();
+        assert!(validate_config(&cfg).is_ok());
+        assert_eq!(create_adapter("bybit", cfg.clone()).unwrap().provider_name(), "bybit");
+
+        cfg.message_mapping = Some(Default::default());
+        assert!(validate_config(&cfg).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_close_gracefully_completes_handshake() {
+        use tokio_tungstenite::tungstenite::Message;
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = tokio::spawn(async move {
+            let (tcp, _) = listener.accept().await.unwrap();
+            let mut ws = tokio_tungstenite::accept_async(tcp).await.unwrap();
+            // Reading answers the client's Close frame automatically
+            let mut code = None;
+            while let Some(Ok(msg)) = ws.next().await {
+                if let Message::Close(frame) = msg {
+                    code = frame.map(|f| f.code);
+                }
+            }
+            code
+        });
+
+        let (mut client, _) = tokio_tungstenite::connect_async(format!("ws://{}", addr))
+            .await
+            .unwrap();
+        close_gracefully(&mut client).await.unwrap();
+        assert_eq!(server.await.unwrap(), Some(CloseCode::Normal));
+    }
+}
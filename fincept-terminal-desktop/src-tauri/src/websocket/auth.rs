@@ -0,0 +1,60 @@
+// Token Refresh - renew an expired access token while connecting
+//
+// What: A `TokenRefresher` turns a provider config whose access token was
+//       rejected into one carrying a fresh token. `WebSocketManager` calls it when
+//       a connect (or the connect inside `reconnect`) fails with an auth error and
+//       retries that connect once with the new config.
+// Why:  Broker tokens (Fyers issues one per day) expire while a stream is open.
+//       The socket dropped and every reconnect was rejected again, looping on the
+//       same auth error until the user logged in by hand.
+// How:  Auth failures are recognised from the adapter's error text
+//       (`is_auth_error`): handshakes rejected with 401/403 and the providers'
+//       own "invalid/expired token" messages. Refreshers are registered per
+//       provider with `WebSocketManager::set_token_refresher`; each knows where its
+//       refresh credentials live and persists the new token through
+//       `save_credential`, so the next start uses it too. None is registered by
+//       default: the Fyers stream still runs through the browser SDK, which
+//       renews its token itself, and its stored credentials hold no refresh token.
+
+use super::types::ProviderConfig;
+use anyhow::Result;
+use async_trait::async_trait;
+
+/// Provider-specific access token renewal
+#[async_trait]
+pub trait TokenRefresher: Send + Sync {
+    /// `config` with a freshly issued access token applied
+    async fn refresh(&self, config: &ProviderConfig) -> Result<ProviderConfig>;
+}
+
+/// Phrases (lower-case) in a connect error that mean the credentials were refused
+const AUTH_ERROR_MARKERS: &[&str] = &[
+    "401",
+    "403",
+    "unauthorized",
+    "forbidden",
+    "authentication failed",
+    "invalid token",
+    "token expired",
+    "expired token",
+    "invalid access token",
+    "token is invalid",
+];
+
+/// Whether a connect failure was caused by rejected credentials
+pub fn is_auth_error(message: &str) -> bool {
+    let message = message.to_lowercase();
+    AUTH_ERROR_MARKERS.iter().any(|marker| message.contains(marker))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recognises_auth_failures() {
+        assert!(is_auth_error("HTTP error: 401 Unauthorized"));
+        assert!(is_auth_error("Connection error: Token expired, please login again"));
+        assert!(!is_auth_error("Connection refused (os error 111)"));
+    }
+}
@@ -0,0 +1,153 @@
+// Ticker Batcher - coalesces high-rate ticks into periodic frontend events
+//
+// What: Receives ticks over a channel, forwards every tick to backend consumers
+//       (monitoring, paper trading) through the router's broadcast channel, and
+//       emits one `ticker_batch` event per interval holding the latest tick per
+//       (provider, symbol).
+// Why:  The `fyers_ticker` listener used to spawn a task and take the router read
+//       lock for every tick. Busy feeds caused lock churn on the backend and
+//       re-render thrash in the frontend.
+// How:  A single long-lived task owns the receiver. It drains whatever is queued,
+//       takes the router lock once per drain, and flushes the coalesced buffer on
+//       a fixed interval (default 50ms). Backend consumers still see every tick;
+//       only frontend emission is coalesced.
+
+use super::router::MessageRouter;
+use super::types::TickerData;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::Emitter;
+use tokio::sync::{mpsc, RwLock};
+
+pub const TICKER_BATCH_EVENT: &str = "ticker_batch";
+pub const DEFAULT_BATCH_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Payload of the `ticker_batch` event
+#[derive(Debug, Clone, Serialize)]
+pub struct TickerBatch {
+    /// Latest tick per (provider, symbol) since the previous batch
+    pub tickers: Vec<TickerData>,
+    /// Raw ticks received in this interval (>= tickers.len())
+    pub received: u64,
+}
+
+/// Latest-tick-wins buffer keyed by (provider, symbol)
+#[derive(Debug, Default)]
+pub struct TickerCoalescer {
+    pending: HashMap<(String, String), TickerData>,
+    received: u64,
+}
+
+impl TickerCoalescer {
+    pub fn push(&mut self, ticker: TickerData) {
+        self.received += 1;
+        self.pending
+            .insert((ticker.provider.clone(), ticker.symbol.clone()), ticker);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    /// Take the buffered batch, leaving the buffer empty
+    pub fn take(&mut self) -> TickerBatch {
+        let mut tickers: Vec<TickerData> = self.pending.drain().map(|(_, t)| t).collect();
+        tickers.sort_by(|a, b| a.symbol.cmp(&b.symbol));
+        TickerBatch {
+            tickers,
+            received: std::mem::take(&mut self.received),
+        }
+    }
+}
+
+/// Start the batching task and return the sender ticks should be pushed into
+pub fn spawn_ticker_batcher(
+    app_handle: tauri::AppHandle,
+    router: Arc<RwLock<MessageRouter>>,
+    interval: Duration,
+) -> mpsc::UnboundedSender<TickerData> {
+    let (tx, mut rx) = mpsc::unbounded_channel::<TickerData>();
+
+    tauri::async_runtime::spawn(async move {
+        let mut buffer = TickerCoalescer::default();
+        let mut flush = tokio::time::interval(interval);
+        flush.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+
+        loop {
+            tokio::select! {
+                received = rx.recv() => {
+                    let Some(first) = received else { break };
+
+                    let mut ticks = vec![first];
+                    while let Ok(tick) = rx.try_recv() {
+                        ticks.push(tick);
+                    }
+
+                    // One lock acquisition per drained burst
+                    {
+                        let router = router.read().await;
+                        for tick in &ticks {
+                            router.publish_ticker(tick.clone());
+                        }
+                    }
+
+                    for tick in ticks {
+                        buffer.push(tick);
+                    }
+                }
+                _ = flush.tick() => {
+                    if !buffer.is_empty() {
+                        let _ = app_handle.emit(TICKER_BATCH_EVENT, buffer.take());
+                    }
+                }
+            }
+        }
+    });
+
+    tx
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tick(symbol: &str, price: f64) -> TickerData {
+        TickerData {
+            provider: "fyers".to_string(),
+            symbol: symbol.to_string(),
+            price,
+            bid: None,
+            ask: None,
+            bid_size: None,
+            ask_size: None,
+            volume: None,
+            high: None,
+            low: None,
+            open: None,
+            close: None,
+            change: None,
+            change_percent: None,
+            timestamp: 0,
+            replayed: false,
+        }
+    }
+
+    #[test]
+    fn test_coalescer_keeps_latest_tick_per_symbol() {
+        let mut buffer = TickerCoalescer::default();
+        buffer.push(tick("NSE:SBIN-EQ", 100.0));
+        buffer.push(tick("NSE:INFY-EQ", 1500.0));
+        buffer.push(tick("NSE:SBIN-EQ", 101.0));
+
+        let batch = buffer.take();
+        assert_eq!(batch.received, 3);
+        assert_eq!(batch.tickers.len(), 2);
+        let sbin = batch.tickers.iter().find(|t| t.symbol == "NSE:SBIN-EQ").unwrap();
+        assert_eq!(sbin.price, 101.0);
+
+        assert!(buffer.is_empty());
+        assert_eq!(buffer.take().received, 0);
+    }
+}
@@ -0,0 +1,148 @@
+// Keepalive - ping upstream sockets and reconnect the ones that stop answering
+//
+// What: While a provider is connected, `WebSocketManager` has its adapter send a
+//       ping every `heartbeat_interval_ms` and waits for the reply. The round trip
+//       goes into `ConnectionMetrics::latency_ms`; after `max_missed_pongs`
+//       unanswered pings in a row the connection is reconnected.
+// Why:  A socket that dies without a Close frame (NAT timeout, laptop sleep, a
+//       stalled exchange gateway) looked connected forever: no error, no data and
+//       no reconnect. Some providers (HyperLiquid) also close connections that
+//       send nothing for a minute.
+// How:  Each adapter speaks its provider's heartbeat in `send_ping`: a WebSocket
+//       Ping frame for Binance and mapped feeds, a `{"method":"ping"}` message for
+//       Kraken and HyperLiquid. Its receive loop reports the matching reply to
+//       the `PongTracker` it was given, which times it against the outstanding
+//       ping. Adapters without a heartbeat (`supports_keepalive` false) get no task.
+
+use super::types::ProviderConfig;
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::sync::Notify;
+use tokio::time::Instant;
+
+/// Unanswered pings in a row before reconnecting, unless the config says otherwise
+pub const DEFAULT_MAX_MISSED_PONGS: u32 = 2;
+
+/// Keepalive timing for one provider
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeepaliveSettings {
+    /// Gap between pings; also how long a ping may wait for its pong
+    pub interval: Duration,
+    pub max_missed_pongs: u32,
+}
+
+impl KeepaliveSettings {
+    pub fn from_config(config: &ProviderConfig) -> Self {
+        Self {
+            interval: Duration::from_millis(config.heartbeat_interval_ms.max(1)),
+            max_missed_pongs: config.max_missed_pongs.max(1),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct PingState {
+    /// When the outstanding ping went out; `None` once answered or given up on
+    sent_at: Option<Instant>,
+    /// Round trip of the outstanding ping, set by its pong
+    round_trip: Option<Duration>,
+}
+
+/// Matches pongs to the ping they answer
+///
+/// Shared between the keepalive task, which calls `ping_sent` and `wait_pong`,
+/// and the adapter's receive loop, which calls `pong_received`.
+#[derive(Debug, Default)]
+pub struct PongTracker {
+    state: Mutex<PingState>,
+    pong: Notify,
+}
+
+impl PongTracker {
+    /// Start timing a new ping; an earlier one still unanswered is abandoned
+    pub fn ping_sent(&self) {
+        *self.state.lock().unwrap() = PingState {
+            sent_at: Some(Instant::now()),
+            round_trip: None,
+        };
+    }
+
+    /// Record a pong from the provider; ignored when no ping is outstanding
+    pub fn pong_received(&self) {
+        let mut state = self.state.lock().unwrap();
+        if let Some(sent_at) = state.sent_at.take() {
+            state.round_trip = Some(sent_at.elapsed());
+            drop(state);
+            self.pong.notify_one();
+        }
+    }
+
+    /// Round trip of the outstanding ping, or `None` if no pong came by `deadline`
+    ///
+    /// A ping that timed out is abandoned, so a pong arriving after the deadline
+    /// is not mistaken for the answer to the next ping.
+    pub async fn wait_pong(&self, deadline: Instant) -> Option<Duration> {
+        let answered = tokio::time::timeout_at(deadline, async {
+            loop {
+                if let Some(round_trip) = self.state.lock().unwrap().round_trip.take() {
+                    return round_trip;
+                }
+                self.pong.notified().await;
+            }
+        })
+        .await;
+        if answered.is_err() {
+            self.state.lock().unwrap().sent_at = None;
+        }
+        answered.ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_times_pong_against_outstanding_ping() {
+        let tracker = Arc::new(PongTracker::default());
+
+        tracker.ping_sent();
+        let responder = tracker.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            responder.pong_received();
+        });
+        let round_trip = tracker.wait_pong(Instant::now() + Duration::from_secs(2)).await.unwrap();
+        assert!(round_trip >= Duration::from_millis(20));
+
+        // A pong with no ping outstanding is not counted
+        tracker.pong_received();
+        assert!(tracker.wait_pong(Instant::now() + Duration::from_millis(20)).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_late_pong_does_not_answer_next_ping() {
+        let tracker = PongTracker::default();
+
+        tracker.ping_sent();
+        assert!(tracker.wait_pong(Instant::now() + Duration::from_millis(10)).await.is_none());
+        tracker.pong_received();
+
+        tracker.ping_sent();
+        assert!(tracker.wait_pong(Instant::now() + Duration::from_millis(20)).await.is_none());
+    }
+
+    #[test]
+    fn test_settings_come_from_provider_config() {
+        let config = ProviderConfig {
+            heartbeat_interval_ms: 15_000,
+            max_missed_pongs: 0,
+            ..Default::default()
+        };
+        let settings = KeepaliveSettings::from_config(&config);
+        assert_eq!(settings.interval, Duration::from_secs(15));
+        assert_eq!(settings.max_missed_pongs, 1);
+        assert_eq!(ProviderConfig::default().max_missed_pongs, DEFAULT_MAX_MISSED_PONGS);
+    }
+}
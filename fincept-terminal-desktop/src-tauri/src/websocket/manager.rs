@@ -1,22 +1,42 @@
 // WebSocket Manager - Connection pool and lifecycle management
 //
 // Responsibilities:
-// - Maintain single connection per provider (connection pooling)
+// - Maintain single connection per provider (connection pooling). Provider names
+//   are normalized to lowercase and connects are serialized per provider, so
+//   concurrent subscribers share one upstream socket instead of racing to open
+//   their own (exchanges limit connections per key).
 // - Multiplex thousands of subscriptions over each connection
 // - Auto-reconnection with subscription restoration
-// - Lifecycle management (connect, disconnect, cleanup)
+// - Access token refresh when a connect is rejected for expired credentials
+//   (see `websocket::auth`); the connect is retried once with the new token
+// - Lifecycle management (connect, disconnect, cleanup). Disconnects complete the
+//   close handshake with the provider and always end with a Disconnected status.
+// - Keepalive: pings each connection at the provider's heartbeat interval,
+//   records pong latency and reconnects after too many missed pongs (see
+//   `websocket::keepalive`)
 // - Metrics tracking
-
-use super::adapters::{create_adapter, WebSocketAdapter};
+// - Shutdown: `shutdown()` closes every connection and stops its keepalive when
+//   the server stops; the manager refuses to connect afterwards, so a pending
+//   reconnect can't reopen a socket
+
+use super::adapters::{create_adapter, provider_spec, ProviderCapabilities, WebSocketAdapter};
+use super::auth::{self, TokenRefresher};
+use super::keepalive::{KeepaliveSettings, PongTracker};
+use super::raw_capture::{RawCapture, RawFrame};
 use super::router::MessageRouter;
 use super::types::*;
 use dashmap::DashMap;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use tokio::sync::RwLock;
 use tokio::time;
 
 /// WebSocket Manager - orchestrates all WebSocket connections
+///
+/// Cloning is cheap and shares all state; keepalive tasks hold a clone.
+#[derive(Clone)]
 pub struct WebSocketManager {
     // Connection pool (1 connection per provider)
     connections: Arc<DashMap<String, Arc<RwLock<Box<dyn WebSocketAdapter>>>>>,
@@ -30,11 +50,32 @@ pub struct WebSocketManager {
     // Connection metrics
     metrics: Arc<DashMap<String, ConnectionMetrics>>,
 
-    // Subscription tracking (provider -> symbol -> channels)
-    subscriptions: Arc<DashMap<String, DashMap<String, Vec<String>>>>,
+    // Subscription tracking (provider -> symbol -> channel -> subscriber count)
+    subscriptions: Arc<DashMap<String, DashMap<String, HashMap<String, usize>>>>,
+
+    // Per-provider connect lock - prevents duplicate upstream connections
+    connect_locks: Arc<DashMap<String, Arc<tokio::sync::Mutex<()>>>>,
+
+    // Per-(provider, symbol, channel) lock - later subscribers wait for the
+    // first one's upstream subscribe instead of counting on it succeeding
+    subscription_locks: Arc<DashMap<(String, String, String), Arc<tokio::sync::Mutex<()>>>>,
+
+    // Last raw frame per (provider, channel), filled only in debug mode
+    raw_capture: Arc<RawCapture>,
+
+    // Access token renewal per provider, used when a connect is rejected
+    token_refreshers: Arc<DashMap<String, Arc<dyn TokenRefresher>>>,
+
+    // Keepalive task per connected provider
+    keepalives: Arc<DashMap<String, tokio::task::JoinHandle<()>>>,
+
+    // Set by `shutdown()`; no connection is opened afterwards
+    shut_down: Arc<AtomicBool>,
+}
 
-    // Prevent duplicate connections
-    connecting: Arc<DashMap<String, bool>>,
+/// Key used for every per-provider map
+fn provider_key(provider: &str) -> String {
+    provider.trim().to_lowercase()
 }
 
 impl WebSocketManager {
@@ -45,7 +86,12 @@ impl WebSocketManager {
             configs: Arc::new(DashMap::new()),
             metrics: Arc::new(DashMap::new()),
             subscriptions: Arc::new(DashMap::new()),
-            connecting: Arc::new(DashMap::new()),
+            connect_locks: Arc::new(DashMap::new()),
+            subscription_locks: Arc::new(DashMap::new()),
+            raw_capture: Arc::new(RawCapture::default()),
+            token_refreshers: Arc::new(DashMap::new()),
+            keepalives: Arc::new(DashMap::new()),
+            shut_down: Arc::new(AtomicBool::new(false)),
         }
     }
 
@@ -53,14 +99,49 @@ impl WebSocketManager {
     // CONFIGURATION
     // ========================================================================
 
-    /// Set provider configuration
-    pub fn set_config(&self, config: ProviderConfig) {
-        self.configs.insert(config.name.clone(), config);
+    /// Validate and set provider configuration
+    pub fn set_config(&self, config: ProviderConfig) -> Result<()> {
+        super::adapters::validate_config(&config)?;
+        self.configs.insert(provider_key(&config.name), config);
+        Ok(())
     }
 
     /// Get provider configuration
     pub fn get_config(&self, provider: &str) -> Option<ProviderConfig> {
-        self.configs.get(provider).map(|c| c.clone())
+        self.configs.get(&provider_key(provider)).map(|c| c.clone())
+    }
+
+    /// What `provider` supports: from its message mapping when one is configured,
+    /// else the built-in adapter's entry in `SUPPORTED_PROVIDERS`
+    pub fn capabilities(&self, provider: &str) -> Option<ProviderCapabilities> {
+        if let Some(config) = self.configs.get(&provider_key(provider)) {
+            if let Some(mapping) = &config.message_mapping {
+                return Some(ProviderCapabilities::from_mapping(&config, mapping));
+            }
+        }
+        provider_spec(provider).map(ProviderCapabilities::from_spec)
+    }
+
+    /// Use `refresher` to renew the provider's access token when a connect is rejected
+    pub fn set_token_refresher(&self, provider: &str, refresher: Arc<dyn TokenRefresher>) {
+        self.token_refreshers.insert(provider_key(provider), refresher);
+    }
+
+    /// Adopt the config saved in `ws_provider_configs`, e.g. a mapped provider
+    /// added through the database rather than `set_config`
+    fn load_stored_config(&self, provider: &str) -> Result<ProviderConfig> {
+        let not_found = || WebSocketError::ProviderNotFound(provider.to_string());
+        // Never open the database from here; startup does that
+        if !crate::database::pool::is_initialized() {
+            return Err(not_found());
+        }
+        let row = crate::database::operations::get_ws_provider_config(provider)
+            .ok()
+            .flatten()
+            .ok_or_else(not_found)?;
+        let config = ProviderConfig::from_db_config(&row);
+        self.set_config(config.clone())?;
+        Ok(config)
     }
 
     // ========================================================================
@@ -69,34 +150,52 @@ impl WebSocketManager {
 
     /// Connect to a provider
     pub async fn connect(&self, provider: &str) -> Result<()> {
+        let provider = provider_key(provider);
+
         // Check if already connected
-        if self.is_connected(provider) {
-            return Err(WebSocketError::AlreadyConnected(provider.to_string()));
+        if self.is_connected(&provider) {
+            return Err(WebSocketError::AlreadyConnected(provider));
         }
 
-        // Check if already connecting - wait for it to complete
-        while self.connecting.contains_key(provider) {
-            tokio::time::sleep(Duration::from_millis(100)).await;
-        }
+        self.ensure_connected(&provider).await
+    }
+
+    /// Open the upstream connection unless one already exists
+    ///
+    /// Callers racing here wait on the provider's lock and then reuse the
+    /// connection the winner opened.
+    async fn ensure_connected(&self, provider: &str) -> Result<()> {
+        let lock = self.connect_lock(provider);
+        let _guard = lock.lock().await;
 
-        // Check again if connected (might have been connected by another task)
         if self.is_connected(provider) {
             return Ok(());
         }
-
-        self.connecting.insert(provider.to_string(), true);
+        if self.shut_down.load(Ordering::SeqCst) {
+            return Err(WebSocketError::ConnectionError("WebSocket manager is shut down".to_string()));
+        }
 
         let result = self.connect_internal(provider).await;
-
-        self.connecting.remove(provider);
-
+        self.record_outcome(provider, &result);
         result
     }
 
+    fn connect_lock(&self, provider: &str) -> Arc<tokio::sync::Mutex<()>> {
+        self.connect_locks
+            .entry(provider.to_string())
+            .or_insert_with(|| Arc::new(tokio::sync::Mutex::new(())))
+            .clone()
+    }
+
     async fn connect_internal(&self, provider: &str) -> Result<()> {
-        // Get configuration
-        let config = self.get_config(provider)
-            .ok_or_else(|| WebSocketError::ProviderNotFound(provider.to_string()))?;
+        // Get configuration, falling back to one saved in the database
+        let config = match self.get_config(provider) {
+            Some(config) => config,
+            None => self.load_stored_config(provider)?,
+        };
+
+        // Every (re)connect re-applies the config's throttle, which lives on the router
+        self.router.read().await.set_ticker_throttle(provider, config.ticker_throttle_ms);
 
         if !config.enabled {
             return Err(WebSocketError::ConnectionError(
@@ -104,9 +203,66 @@ impl WebSocketManager {
             ));
         }
 
-        // Create adapter
-        let mut adapter = create_adapter(provider, config.clone())
-            .map_err(|e| WebSocketError::ConnectionError(e.to_string()))?;
+        let (adapter, pong_tracker) = match self.open_adapter(provider, config.clone()).await {
+            Ok(opened) => opened,
+            Err(e) if auth::is_auth_error(&e.to_string()) => {
+                let config = self.refresh_token(provider, &config, &e.to_string()).await?;
+                self.open_adapter(provider, config).await.map_err(|e| {
+                    WebSocketError::ConnectionError(format!("{} (after refreshing the access token)", e))
+                })?
+            }
+            Err(e) => return Err(WebSocketError::ConnectionError(e.to_string())),
+        };
+        let keepalive = adapter.supports_keepalive();
+
+        // Store connection
+        let adapter = Arc::new(RwLock::new(adapter));
+        self.connections.insert(provider.to_string(), adapter.clone());
+        if keepalive {
+            self.start_keepalive(provider, adapter, pong_tracker, KeepaliveSettings::from_config(&config));
+        }
+
+        // Update metrics, keeping counters across reconnects
+        let active_subscriptions = self.count_subscriptions(provider);
+        let mut metrics = self.metrics
+            .entry(provider.to_string())
+            .or_insert_with(|| ConnectionMetrics {
+                provider: provider.to_string(),
+                ..Default::default()
+            });
+        let now = Self::now();
+        // Only a session cut short counts; an explicit disconnect clears `connected_since`
+        if metrics.connected_since.is_some() {
+            metrics.reconnect_count += 1;
+            metrics.total_reconnects += 1;
+        }
+        metrics.status = ConnectionStatus::Connected;
+        metrics.connected_at = Some(now);
+        metrics.connected_since.get_or_insert(now);
+        metrics.push_event(ConnectionEventKind::Connected, now, None);
+        metrics.upstream_connections = 1;
+        metrics.active_subscriptions = active_subscriptions;
+        metrics.missed_pongs = 0;
+        drop(metrics);
+
+        // Update status
+        self.emit_status(provider, ConnectionStatus::Connected, None).await;
+
+        Ok(())
+    }
+
+    /// Build the provider's adapter, wire it to the router and connect it; the
+    /// tracker receives the adapter's keepalive pongs
+    async fn open_adapter(
+        &self,
+        provider: &str,
+        config: ProviderConfig,
+    ) -> anyhow::Result<(Box<dyn WebSocketAdapter>, Arc<PongTracker>)> {
+        let mut adapter = create_adapter(provider, config)?;
+
+        adapter.set_raw_capture(self.raw_capture.clone());
+        let pong_tracker = Arc::new(PongTracker::default());
+        adapter.set_pong_tracker(pong_tracker.clone());
 
         // Set message callback
         let router = self.router.clone();
@@ -117,70 +273,224 @@ impl WebSocketManager {
             });
         }));
 
-        // Connect
-        adapter.connect().await
-            .map_err(|e| WebSocketError::ConnectionError(e.to_string()))?;
+        adapter.connect().await?;
+        Ok((adapter, pong_tracker))
+    }
 
-        // Store connection
-        self.connections.insert(
-            provider.to_string(),
-            Arc::new(RwLock::new(adapter))
-        );
+    /// Run the keepalive for a freshly stored connection, replacing any older task
+    fn start_keepalive(
+        &self,
+        provider: &str,
+        adapter: Arc<RwLock<Box<dyn WebSocketAdapter>>>,
+        pong_tracker: Arc<PongTracker>,
+        settings: KeepaliveSettings,
+    ) {
+        let task = tokio::spawn(self.clone().keepalive_loop(provider.to_string(), adapter, pong_tracker, settings));
+        if let Some(previous) = self.keepalives.insert(provider.to_string(), task) {
+            previous.abort();
+        }
+    }
 
-        // Initialize metrics
-        let metrics = ConnectionMetrics {
-            provider: provider.to_string(),
-            status: ConnectionStatus::Connected,
-            connected_at: Some(Self::now()),
-            ..Default::default()
-        };
-        self.metrics.insert(provider.to_string(), metrics);
+    /// Ping every `settings.interval` and wait up to one interval for the pong,
+    /// recording its round trip; after `max_missed_pongs` misses in a row the
+    /// provider is reconnected and this task ends
+    async fn keepalive_loop(
+        self,
+        provider: String,
+        adapter: Arc<RwLock<Box<dyn WebSocketAdapter>>>,
+        pong_tracker: Arc<PongTracker>,
+        settings: KeepaliveSettings,
+    ) {
+        let mut ticker = time::interval(settings.interval);
+        ticker.set_missed_tick_behavior(time::MissedTickBehavior::Delay);
+        // The first tick completes immediately; the socket has only just opened
+        ticker.tick().await;
+        let mut missed = 0;
+
+        loop {
+            ticker.tick().await;
+            let deadline = time::Instant::now() + settings.interval;
+            pong_tracker.ping_sent();
+            // A socket that cannot even take the ping counts as a miss
+            let sent = time::timeout_at(deadline, async { adapter.write().await.send_ping().await }).await;
+            let round_trip = match sent {
+                Ok(Ok(())) => pong_tracker.wait_pong(deadline).await,
+                _ => None,
+            };
+
+            match round_trip {
+                Some(round_trip) => {
+                    missed = 0;
+                    self.record_pong(&provider, round_trip);
+                }
+                None => {
+                    missed += 1;
+                    if self.record_missed_pong(&provider, missed, settings.max_missed_pongs) {
+                        // Reconnecting ends this task, so it runs on its own
+                        let manager = self.clone();
+                        tokio::spawn(async move {
+                            if let Err(e) = manager.reconnect(&provider).await {
+                                eprintln!("[WebSocketManager] Keepalive reconnect of {} failed: {}", provider, e);
+                            }
+                        });
+                        return;
+                    }
+                }
+            }
+        }
+    }
 
-        // Update status
-        self.emit_status(provider, ConnectionStatus::Connected, None).await;
+    fn record_pong(&self, provider: &str, round_trip: Duration) {
+        if let Some(mut metrics) = self.metrics.get_mut(provider) {
+            metrics.latency_ms = Some(round_trip.as_millis() as u64);
+            metrics.last_pong_at = Some(Self::now());
+            metrics.missed_pongs = 0;
+        }
+    }
 
-        Ok(())
+    /// Count a missed pong; true once `max_missed` is reached and the connection
+    /// should be reconnected
+    fn record_missed_pong(&self, provider: &str, missed: u32, max_missed: u32) -> bool {
+        let timed_out = missed >= max_missed;
+        if let Some(mut metrics) = self.metrics.get_mut(provider) {
+            metrics.missed_pongs = missed;
+            if timed_out {
+                let reason = format!("{} keepalive pings unanswered", missed);
+                metrics.push_event(ConnectionEventKind::PongTimeout, Self::now(), Some(reason));
+            }
+        }
+        timed_out
+    }
+
+    /// Renew the access token after `auth_error` and keep the new config
+    async fn refresh_token(&self, provider: &str, config: &ProviderConfig, auth_error: &str) -> Result<ProviderConfig> {
+        let refresher = self
+            .token_refreshers
+            .get(provider)
+            .map(|r| r.value().clone())
+            .ok_or_else(|| WebSocketError::ConnectionError(auth_error.to_string()))?;
+
+        let refreshed = refresher.refresh(config).await.map_err(|e| {
+            WebSocketError::ConnectionError(format!("{}; access token refresh failed: {}", auth_error, e))
+        })?;
+        self.configs.insert(provider.to_string(), refreshed.clone());
+
+        self.metrics
+            .entry(provider.to_string())
+            .or_insert_with(|| ConnectionMetrics {
+                provider: provider.to_string(),
+                ..Default::default()
+            })
+            .push_event(ConnectionEventKind::TokenRefreshed, Self::now(), Some(auth_error.to_string()));
+        self.emit_status(provider, ConnectionStatus::Connecting, Some("Access token refreshed".to_string()))
+            .await;
+
+        Ok(refreshed)
     }
 
     /// Disconnect from a provider
     pub async fn disconnect(&self, provider: &str) -> Result<()> {
-        if let Some((_, adapter)) = self.connections.remove(provider) {
-            adapter.write().await.disconnect().await
-                .map_err(|e| WebSocketError::ConnectionError(e.to_string()))?;
+        self.disconnect_with_reason(provider, "disconnect requested", true).await
+    }
 
-            // Update metrics
-            if let Some(mut metrics) = self.metrics.get_mut(provider) {
+    /// Close the provider's socket, recording `reason` in its history
+    ///
+    /// `ends_session` is false when `reconnect()` cycles the socket, so uptime and
+    /// the session's reconnect count carry over to the new connection.
+    async fn disconnect_with_reason(&self, provider: &str, reason: &str, ends_session: bool) -> Result<()> {
+        let provider = provider_key(provider);
+        let lock = self.connect_lock(&provider);
+        let _guard = lock.lock().await;
+
+        if let Some((_, task)) = self.keepalives.remove(&provider) {
+            task.abort();
+        }
+
+        if let Some((_, adapter)) = self.connections.remove(&provider) {
+            // Update metrics first: the socket is gone from the pool either way
+            if let Some(mut metrics) = self.metrics.get_mut(&provider) {
                 metrics.status = ConnectionStatus::Disconnected;
                 metrics.connected_at = None;
+                metrics.upstream_connections = 0;
+                if ends_session {
+                    metrics.connected_since = None;
+                    metrics.reconnect_count = 0;
+                }
+                metrics.push_event(ConnectionEventKind::Disconnected, Self::now(), Some(reason.to_string()));
+            }
+
+            let mut adapter = adapter.write().await;
+            if adapter.unsubscribe_before_close() {
+                // Tracking is kept, so a later reconnect restores these
+                for (symbol, channels) in self.get_provider_subscriptions(&provider) {
+                    for channel in channels {
+                        let _ = adapter.unsubscribe(&symbol, &channel).await;
+                    }
+                }
             }
 
-            // Update status
-            self.emit_status(provider, ConnectionStatus::Disconnected, None).await;
+            // The socket is released either way; an unclean close is only reported
+            let message = match adapter.disconnect().await {
+                Ok(()) => reason.to_string(),
+                Err(e) => format!("{} (closed without handshake: {})", reason, e),
+            };
+            drop(adapter);
+
+            // Final status for this connection
+            self.emit_status(&provider, ConnectionStatus::Disconnected, Some(message)).await;
 
             Ok(())
         } else {
-            Err(WebSocketError::NotConnected(provider.to_string()))
+            Err(WebSocketError::NotConnected(provider))
+        }
+    }
+
+    /// Close every connection and stop the keepalive tasks; later connects fail
+    ///
+    /// Subscriptions stay tracked, but nothing reconnects them.
+    pub async fn shutdown(&self) {
+        self.shut_down.store(true, Ordering::SeqCst);
+
+        let providers: Vec<String> = self.connections.iter().map(|entry| entry.key().clone()).collect();
+        for provider in providers {
+            // A keepalive reconnect may have taken the socket down already
+            let _ = self.disconnect_with_reason(&provider, "server shutting down", true).await;
+        }
+
+        // Tasks whose connection was already gone, e.g. mid-reconnect
+        let providers: Vec<String> = self.keepalives.iter().map(|entry| entry.key().clone()).collect();
+        for provider in providers {
+            if let Some((_, task)) = self.keepalives.remove(&provider) {
+                task.abort();
+            }
         }
     }
 
     /// Reconnect to a provider
     pub async fn reconnect(&self, provider: &str) -> Result<()> {
+        let provider = provider_key(provider);
+
         // Get existing subscriptions
-        let subs = self.get_provider_subscriptions(provider);
+        let subs = self.get_provider_subscriptions(&provider);
+
+        if let Some(mut metrics) = self.metrics.get_mut(&provider) {
+            metrics.push_event(ConnectionEventKind::Reconnecting, Self::now(), None);
+        }
 
         // Disconnect
-        let _ = self.disconnect(provider).await;
+        let _ = self.disconnect_with_reason(&provider, "reconnect", false).await;
 
         // Small delay
         time::sleep(Duration::from_millis(1000)).await;
 
-        // Connect
-        self.connect(provider).await?;
+        // Connect (a subscriber may have reconnected in the meantime)
+        self.ensure_connected(&provider).await?;
 
-        // Restore subscriptions
+        // Restore subscriptions (reference counts are kept in tracking, so only
+        // the upstream side needs to be re-established)
         for (symbol, channels) in subs {
             for channel in channels {
-                let _ = self.subscribe(provider, &symbol, &channel, None).await;
+                let _ = self.subscribe_upstream(&provider, &symbol, &channel, None).await;
             }
         }
 
@@ -189,7 +499,12 @@ impl WebSocketManager {
 
     /// Check if provider is connected
     pub fn is_connected(&self, provider: &str) -> bool {
-        self.connections.contains_key(provider)
+        self.connections.contains_key(&provider_key(provider))
+    }
+
+    /// Number of open upstream sockets for a provider (0 or 1)
+    pub fn upstream_connection_count(&self, provider: &str) -> usize {
+        usize::from(self.is_connected(provider))
     }
 
     // ========================================================================
@@ -197,6 +512,11 @@ impl WebSocketManager {
     // ========================================================================
 
     /// Subscribe to a channel
+    ///
+    /// Subscriptions are reference counted per (provider, symbol, channel):
+    /// only the first subscriber opens the upstream subscription, later ones
+    /// just bump the count. A caller arriving while that upstream subscribe is
+    /// in flight waits for it, and opens the subscription itself if it failed.
     pub async fn subscribe(
         &self,
         provider: &str,
@@ -204,42 +524,98 @@ impl WebSocketManager {
         channel: &str,
         params: Option<serde_json::Value>,
     ) -> Result<()> {
-        // Ensure connected
-        if !self.is_connected(provider) {
-            self.connect(provider).await?;
+        let provider = provider_key(provider);
+        let provider = provider.as_str();
+
+        let lock = self.subscription_lock(provider, symbol, channel);
+        let _guard = lock.lock().await;
+
+        if self.acquire_subscription(provider, symbol, channel) > 1 {
+            return Ok(());
         }
 
-        // Get adapter
-        let adapter = self.connections.get(provider)
-            .ok_or_else(|| WebSocketError::NotConnected(provider.to_string()))?;
+        if let Err(e) = self.subscribe_upstream(provider, symbol, channel, params).await {
+            // Roll back the reference taken above so a retry starts clean
+            self.release_subscription(provider, symbol, channel);
+            self.update_subscription_metrics(provider);
+            return Err(e);
+        }
 
-        // Subscribe via adapter
-        adapter.write().await.subscribe(symbol, channel, params).await
-            .map_err(|e| WebSocketError::SubscriptionError(e.to_string()))?;
+        self.update_subscription_metrics(provider);
 
-        // Track subscription
-        self.subscriptions
-            .entry(provider.to_string())
-            .or_insert_with(DashMap::new)
-            .entry(symbol.to_string())
-            .or_insert_with(Vec::new)
-            .push(channel.to_string());
+        Ok(())
+    }
 
-        // Update metrics
-        if let Some(mut metrics) = self.metrics.get_mut(provider) {
-            metrics.active_subscriptions = self.count_subscriptions(provider);
+    /// Subscribe several symbols to one channel
+    ///
+    /// Reference counted like `subscribe`. Symbols that are new upstream go to the
+    /// adapter together, as one message where the provider accepts a list.
+    /// Duplicates are dropped; one outcome per remaining symbol, in order.
+    pub async fn subscribe_many(
+        &self,
+        provider: &str,
+        symbols: &[String],
+        channel: &str,
+        params: Option<serde_json::Value>,
+    ) -> Vec<SubscribeOutcome> {
+        let provider = provider_key(provider);
+        let provider = provider.as_str();
+
+        let mut seen = std::collections::HashSet::new();
+        let symbols: Vec<String> = symbols.iter().filter(|s| seen.insert(s.as_str())).cloned().collect();
+        let mut outcomes: Vec<SubscribeOutcome> = symbols.iter().map(|s| SubscribeOutcome::ok(s)).collect();
+
+        // Taken in symbol order, so batches sharing symbols can't deadlock
+        let mut ordered: Vec<&String> = symbols.iter().collect();
+        ordered.sort();
+        let mut guards = Vec::with_capacity(ordered.len());
+        for symbol in ordered {
+            guards.push(self.subscription_lock(provider, symbol, channel).lock_owned().await);
         }
 
-        Ok(())
+        let new_upstream: Vec<usize> = (0..symbols.len())
+            .filter(|&i| self.acquire_subscription(provider, &symbols[i], channel) == 1)
+            .collect();
+        if !new_upstream.is_empty() {
+            let batch: Vec<String> = new_upstream.iter().map(|&i| symbols[i].clone()).collect();
+            let results = match self.subscribe_many_upstream(provider, &batch, channel, params).await {
+                Ok(results) => results,
+                Err(e) => batch.iter().map(|_| Err(e.to_string())).collect(),
+            };
+            for (&i, result) in new_upstream.iter().zip(results) {
+                if let Err(e) = result {
+                    // Roll back so a retry starts clean
+                    self.release_subscription(provider, &symbols[i], channel);
+                    outcomes[i] = SubscribeOutcome::failed(&symbols[i], e);
+                }
+            }
+        }
+
+        self.update_subscription_metrics(provider);
+        outcomes
     }
 
     /// Unsubscribe from a channel
+    ///
+    /// The upstream subscription is only torn down when the last subscriber
+    /// for (provider, symbol, channel) leaves.
     pub async fn unsubscribe(
         &self,
         provider: &str,
         symbol: &str,
         channel: &str,
     ) -> Result<()> {
+        let provider = provider_key(provider);
+        let provider = provider.as_str();
+
+        let lock = self.subscription_lock(provider, symbol, channel);
+        let _guard = lock.lock().await;
+
+        match self.release_subscription(provider, symbol, channel) {
+            Some(remaining) if remaining > 0 => return Ok(()),
+            _ => {}
+        }
+
         // Get adapter
         let adapter = self.connections.get(provider)
             .ok_or_else(|| WebSocketError::NotConnected(provider.to_string()))?;
@@ -248,22 +624,116 @@ impl WebSocketManager {
         adapter.write().await.unsubscribe(symbol, channel).await
             .map_err(|e| WebSocketError::SubscriptionError(e.to_string()))?;
 
-        // Remove from tracking
-        if let Some(provider_subs) = self.subscriptions.get(provider) {
-            if let Some(mut symbol_channels) = provider_subs.get_mut(symbol) {
-                symbol_channels.retain(|c| c != channel);
-                if symbol_channels.is_empty() {
-                    provider_subs.remove(symbol);
-                }
+        self.update_subscription_metrics(provider);
+
+        Ok(())
+    }
+
+    /// Open an upstream subscription via the provider adapter
+    async fn subscribe_upstream(
+        &self,
+        provider: &str,
+        symbol: &str,
+        channel: &str,
+        params: Option<serde_json::Value>,
+    ) -> Result<()> {
+        // Ensure connected; every subscription shares the provider's single socket
+        self.ensure_connected(provider).await?;
+
+        // Get adapter
+        let adapter = self.connections.get(provider)
+            .ok_or_else(|| WebSocketError::NotConnected(provider.to_string()))?;
+
+        // Subscribe via adapter
+        let result = adapter.write().await.subscribe(symbol, channel, params).await
+            .map_err(|e| WebSocketError::SubscriptionError(e.to_string()));
+        drop(adapter);
+        self.record_outcome(provider, &result);
+
+        result
+    }
+
+    /// Open several upstream subscriptions on one channel in a single adapter call
+    async fn subscribe_many_upstream(
+        &self,
+        provider: &str,
+        symbols: &[String],
+        channel: &str,
+        params: Option<serde_json::Value>,
+    ) -> Result<Vec<std::result::Result<(), String>>> {
+        self.ensure_connected(provider).await?;
+
+        let adapter = self.connections.get(provider)
+            .ok_or_else(|| WebSocketError::NotConnected(provider.to_string()))?;
+        let results: Vec<std::result::Result<(), String>> = adapter.write().await
+            .subscribe_many(symbols, channel, params).await
+            .into_iter()
+            .map(|r| r.map_err(|e| e.to_string()))
+            .collect();
+        drop(adapter);
+
+        let outcome = match results.iter().find_map(|r| r.as_ref().err()) {
+            Some(e) => Err(WebSocketError::SubscriptionError(e.clone())),
+            None => Ok(()),
+        };
+        self.record_outcome(provider, &outcome);
+
+        Ok(results)
+    }
+
+    fn subscription_lock(&self, provider: &str, symbol: &str, channel: &str) -> Arc<tokio::sync::Mutex<()>> {
+        self.subscription_locks
+            .entry((provider.to_string(), symbol.to_string(), channel.to_string()))
+            .or_insert_with(|| Arc::new(tokio::sync::Mutex::new(())))
+            .clone()
+    }
+
+    /// Take a reference on a subscription, returning the new count
+    fn acquire_subscription(&self, provider: &str, symbol: &str, channel: &str) -> usize {
+        let provider_subs = self.subscriptions
+            .entry(provider.to_string())
+            .or_insert_with(DashMap::new);
+        let mut symbol_channels = provider_subs
+            .entry(symbol.to_string())
+            .or_insert_with(HashMap::new);
+        let count = symbol_channels.entry(channel.to_string()).or_insert(0);
+        *count += 1;
+        *count
+    }
+
+    /// Drop a reference on a subscription, returning the remaining count
+    /// (`None` if the subscription was not tracked)
+    fn release_subscription(&self, provider: &str, symbol: &str, channel: &str) -> Option<usize> {
+        let provider_subs = self.subscriptions.get(provider)?;
+        let remaining = {
+            let mut symbol_channels = provider_subs.get_mut(symbol)?;
+            let count = symbol_channels.get_mut(channel)?;
+            *count = count.saturating_sub(1);
+            let remaining = *count;
+            if remaining == 0 {
+                symbol_channels.remove(channel);
             }
-        }
+            remaining
+        };
+        provider_subs.remove_if(symbol, |_, channels| channels.is_empty());
+        Some(remaining)
+    }
+
+    /// Get the subscriber count for a (provider, symbol, channel)
+    pub fn subscription_ref_count(&self, provider: &str, symbol: &str, channel: &str) -> usize {
+        self.subscriptions.get(&provider_key(provider))
+            .and_then(|subs| {
+                subs.get(symbol)
+                    .and_then(|channels| channels.get(channel).copied())
+            })
+            .unwrap_or(0)
+    }
 
-        // Update metrics
+    fn update_subscription_metrics(&self, provider: &str) {
+        let active = self.count_subscriptions(provider);
         if let Some(mut metrics) = self.metrics.get_mut(provider) {
-            metrics.active_subscriptions = self.count_subscriptions(provider);
+            metrics.active_subscriptions = active;
         }
-
-        Ok(())
     }
 
     /// Get all subscriptions for a provider
@@ -271,13 +741,13 @@ impl WebSocketManager {
         self.subscriptions.get(provider)
             .map(|subs| {
                 subs.iter()
-                    .map(|entry| (entry.key().clone(), entry.value().clone()))
+                    .map(|entry| (entry.key().clone(), entry.value().keys().cloned().collect()))
                     .collect()
             })
             .unwrap_or_default()
     }
 
-    /// Count total subscriptions for a provider
+    /// Count total upstream subscriptions for a provider
     fn count_subscriptions(&self, provider: &str) -> usize {
         self.subscriptions.get(provider)
             .map(|subs| {
@@ -288,13 +758,32 @@ impl WebSocketManager {
             .unwrap_or(0)
     }
 
+    // ========================================================================
+    // RAW FRAME CAPTURE
+    // ========================================================================
+
+    /// Turn raw frame capture on or off; turning it off drops stored frames
+    pub fn set_raw_capture_enabled(&self, enabled: bool) {
+        self.raw_capture.set_enabled(enabled);
+    }
+
+    pub fn raw_capture_enabled(&self) -> bool {
+        self.raw_capture.is_enabled()
+    }
+
+    /// Last raw frame received per symbol and channel, narrowed to `symbol`
+    /// and/or `channel` when given
+    pub fn last_raw(&self, provider: &str, symbol: Option<&str>, channel: Option<&str>) -> Vec<RawFrame> {
+        self.raw_capture.find(provider, symbol, channel)
+    }
+
     // ========================================================================
     // METRICS
     // ========================================================================
 
     /// Get metrics for a provider
     pub fn get_metrics(&self, provider: &str) -> Option<ConnectionMetrics> {
-        self.metrics.get(provider).map(|m| m.clone())
+        self.metrics.get(&provider_key(provider)).map(|m| m.clone())
     }
 
     /// Get all metrics
@@ -304,9 +793,38 @@ impl WebSocketManager {
             .collect()
     }
 
+    /// Remember a connect/subscribe failure for the metrics view, or clear it on success
+    ///
+    /// Unknown providers are not recorded so typos don't leave empty metrics entries.
+    fn record_outcome(&self, provider: &str, result: &Result<()>) {
+        match result {
+            Ok(()) => {
+                if let Some(mut metrics) = self.metrics.get_mut(provider) {
+                    metrics.last_error = None;
+                    metrics.last_error_at = None;
+                }
+            }
+            Err(WebSocketError::ProviderNotFound(_)) => {}
+            Err(e) => {
+                let mut metrics = self.metrics
+                    .entry(provider.to_string())
+                    .or_insert_with(|| ConnectionMetrics {
+                        provider: provider.to_string(),
+                        ..Default::default()
+                    });
+                let now = Self::now();
+                metrics.last_error = Some(e.to_string());
+                metrics.last_error_at = Some(now);
+                if matches!(e, WebSocketError::ConnectionError(_)) {
+                    metrics.push_event(ConnectionEventKind::ConnectFailed, now, Some(e.to_string()));
+                }
+            }
+        }
+    }
+
     /// Update message count
     pub fn increment_message_count(&self, provider: &str) {
-        if let Some(mut metrics) = self.metrics.get_mut(provider) {
+        if let Some(mut metrics) = self.metrics.get_mut(&provider_key(provider)) {
             metrics.messages_received += 1;
             metrics.last_message_at = Some(Self::now());
         }
@@ -353,7 +871,318 @@ impl WebSocketManager {
         }
 
         for provider in to_remove {
-            let _ = self.disconnect(&provider).await;
+            let _ = self.disconnect_with_reason(&provider, "idle", true).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn manager() -> WebSocketManager {
+        WebSocketManager::new(Arc::new(RwLock::new(MessageRouter::new())))
+    }
+
+    #[test]
+    fn test_provider_names_share_one_key() {
+        let manager = manager();
+        manager
+            .set_config(ProviderConfig {
+                name: "Kraken".to_string(),
+                url: "wss://ws.kraken.com/v2".to_string(),
+                ..Default::default()
+            })
+            .unwrap();
+
+        assert!(manager.get_config("kraken").is_some());
+        assert!(manager.get_config(" KRAKEN ").is_some());
+
+        manager.acquire_subscription("kraken", "BTC/USD", "ticker");
+        assert_eq!(manager.subscription_ref_count("Kraken", "BTC/USD", "ticker"), 1);
+        assert_eq!(manager.upstream_connection_count("Kraken"), 0);
+    }
+
+    #[test]
+    fn test_last_error_is_kept_until_success() {
+        let manager = manager();
+
+        manager.record_outcome("kraken", &Err(WebSocketError::ConnectionError("auth rejected".to_string())));
+        let metrics = manager.get_metrics("kraken").unwrap();
+        assert!(metrics.last_error.unwrap().contains("auth rejected"));
+        assert!(metrics.last_error_at.is_some());
+
+        manager.record_outcome("kraken", &Ok(()));
+        let metrics = manager.get_metrics("kraken").unwrap();
+        assert!(metrics.last_error.is_none());
+        assert!(metrics.last_error_at.is_none());
+
+        manager.record_outcome("typo", &Err(WebSocketError::ProviderNotFound("typo".to_string())));
+        assert!(manager.get_metrics("typo").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_subscribe_many_rolls_back_failed_symbols() {
+        let manager = manager();
+        let symbols = ["BTC/USD", "ETH/USD", "BTC/USD"].map(String::from);
+
+        // No config for the provider, so the batch cannot connect
+        let outcomes = manager.subscribe_many("nowhere", &symbols, "ticker", None).await;
+
+        let names: Vec<&str> = outcomes.iter().map(|o| o.symbol.as_str()).collect();
+        assert_eq!(names, ["BTC/USD", "ETH/USD"]);
+        assert!(outcomes.iter().all(|o| !o.subscribed && o.error.is_some()));
+        assert_eq!(manager.subscription_ref_count("nowhere", "BTC/USD", "ticker"), 0);
+        assert_eq!(manager.subscription_ref_count("nowhere", "ETH/USD", "ticker"), 0);
+    }
+
+    #[tokio::test]
+    async fn test_subscriber_waiting_on_a_failed_subscribe_is_not_counted() {
+        use tokio::io::AsyncWriteExt;
+
+        // Each handshake is refused after a delay, so the second subscriber
+        // arrives while the first one's upstream subscribe is still in flight
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = tokio::spawn(async move {
+            for _ in 0..2 {
+                let (mut tcp, _) = listener.accept().await.unwrap();
+                time::sleep(Duration::from_millis(100)).await;
+                tcp.write_all(b"HTTP/1.1 500 Internal Server Error\r\nContent-Length: 0\r\n\r\n").await.unwrap();
+            }
+        });
+
+        let manager = manager();
+        let mapping = serde_json::from_value(serde_json::json!({
+            "ticker": { "symbol": "/s", "price": "/p" }
+        }))
+        .unwrap();
+        manager
+            .set_config(ProviderConfig {
+                name: "custom".to_string(),
+                url: format!("ws://{}", addr),
+                message_mapping: Some(mapping),
+                ..Default::default()
+            })
+            .unwrap();
+
+        let (first, second) = tokio::join!(
+            manager.subscribe("custom", "BTC/USD", "ticker", None),
+            manager.subscribe("custom", "BTC/USD", "ticker", None),
+        );
+
+        assert!(first.is_err());
+        assert!(second.is_err());
+        assert_eq!(manager.subscription_ref_count("custom", "BTC/USD", "ticker"), 0);
+        server.await.unwrap();
+    }
+
+    struct CountingRefresher(std::sync::atomic::AtomicUsize);
+
+    #[async_trait::async_trait]
+    impl TokenRefresher for CountingRefresher {
+        async fn refresh(&self, config: &ProviderConfig) -> anyhow::Result<ProviderConfig> {
+            self.0.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            Ok(ProviderConfig { api_key: Some("fresh-token".to_string()), ..config.clone() })
         }
     }
+
+    #[tokio::test]
+    async fn test_expired_token_is_refreshed_and_connect_retried_once() {
+        use tokio::io::AsyncWriteExt;
+
+        // First handshake is refused like an expired token, the second is accepted
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = tokio::spawn(async move {
+            let (mut tcp, _) = listener.accept().await.unwrap();
+            tcp.write_all(b"HTTP/1.1 401 Unauthorized\r\nContent-Length: 0\r\n\r\n").await.unwrap();
+            drop(tcp);
+            let (tcp, _) = listener.accept().await.unwrap();
+            let mut ws = tokio_tungstenite::accept_async(tcp).await.unwrap();
+            use futures_util::StreamExt;
+            while let Some(Ok(_)) = ws.next().await {}
+        });
+
+        let manager = manager();
+        let mapping = serde_json::from_value(serde_json::json!({
+            "ticker": { "symbol": "/s", "price": "/p" }
+        }))
+        .unwrap();
+        manager
+            .set_config(ProviderConfig {
+                name: "custom".to_string(),
+                url: format!("ws://{}", addr),
+                message_mapping: Some(mapping),
+                ..Default::default()
+            })
+            .unwrap();
+        let refresher = Arc::new(CountingRefresher(Default::default()));
+        manager.set_token_refresher("custom", refresher.clone());
+
+        manager.connect("custom").await.unwrap();
+
+        assert_eq!(refresher.0.load(std::sync::atomic::Ordering::SeqCst), 1);
+        assert_eq!(manager.get_config("custom").unwrap().api_key.as_deref(), Some("fresh-token"));
+        let history = manager.get_metrics("custom").unwrap().history;
+        assert!(history.iter().any(|e| e.kind == ConnectionEventKind::TokenRefreshed));
+
+        manager.disconnect("custom").await.unwrap();
+        server.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_connect_after_requested_disconnect_is_not_a_reconnect() {
+        use futures_util::StreamExt;
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = tokio::spawn(async move {
+            for _ in 0..2 {
+                let (tcp, _) = listener.accept().await.unwrap();
+                let mut ws = tokio_tungstenite::accept_async(tcp).await.unwrap();
+                while let Some(Ok(_)) = ws.next().await {}
+            }
+        });
+
+        let manager = manager();
+        let mapping = serde_json::from_value(serde_json::json!({
+            "ticker": { "symbol": "/s", "price": "/p" }
+        }))
+        .unwrap();
+        manager
+            .set_config(ProviderConfig {
+                name: "custom".to_string(),
+                url: format!("ws://{}", addr),
+                message_mapping: Some(mapping),
+                ..Default::default()
+            })
+            .unwrap();
+
+        manager.connect("custom").await.unwrap();
+        manager.metrics.get_mut("custom").unwrap().messages_received = 10;
+        manager.disconnect("custom").await.unwrap();
+        manager.connect("custom").await.unwrap();
+
+        let metrics = manager.get_metrics("custom").unwrap();
+        assert_eq!((metrics.reconnect_count, metrics.total_reconnects), (0, 0));
+
+        manager.disconnect("custom").await.unwrap();
+        server.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_missed_pongs_trigger_reconnect_and_pongs_record_latency() {
+        use futures_util::StreamExt;
+
+        // The first socket never reads, so pings go unanswered; the second does,
+        // and answers them
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = tokio::spawn(async move {
+            let (tcp, _) = listener.accept().await.unwrap();
+            let silent = tokio_tungstenite::accept_async(tcp).await.unwrap();
+            let (tcp, _) = listener.accept().await.unwrap();
+            let mut ws = tokio_tungstenite::accept_async(tcp).await.unwrap();
+            drop(silent);
+            while let Some(Ok(_)) = ws.next().await {}
+        });
+
+        let manager = manager();
+        let mapping = serde_json::from_value(serde_json::json!({
+            "ticker": { "symbol": "/s", "price": "/p" }
+        }))
+        .unwrap();
+        manager
+            .set_config(ProviderConfig {
+                name: "custom".to_string(),
+                url: format!("ws://{}", addr),
+                heartbeat_interval_ms: 50,
+                max_missed_pongs: 2,
+                message_mapping: Some(mapping),
+                ..Default::default()
+            })
+            .unwrap();
+        manager.connect("custom").await.unwrap();
+
+        let connects = |metrics: &ConnectionMetrics| {
+            metrics.history.iter().filter(|e| e.kind == ConnectionEventKind::Connected).count()
+        };
+        let mut metrics = manager.get_metrics("custom").unwrap();
+        for _ in 0..200 {
+            if connects(&metrics) == 2 && metrics.latency_ms.is_some() {
+                break;
+            }
+            time::sleep(Duration::from_millis(50)).await;
+            metrics = manager.get_metrics("custom").unwrap();
+        }
+
+        assert_eq!(connects(&metrics), 2);
+        assert!(metrics.history.iter().any(|e| e.kind == ConnectionEventKind::PongTimeout));
+        assert!(metrics.latency_ms.is_some());
+        assert!(metrics.last_pong_at.is_some());
+        assert_eq!(metrics.missed_pongs, 0);
+
+        manager.disconnect("custom").await.unwrap();
+        server.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_closes_connections_and_stops_tasks() {
+        use futures_util::StreamExt;
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = tokio::spawn(async move {
+            let (tcp, _) = listener.accept().await.unwrap();
+            let mut ws = tokio_tungstenite::accept_async(tcp).await.unwrap();
+            while let Some(Ok(_)) = ws.next().await {}
+        });
+
+        let manager = manager();
+        let mapping = serde_json::from_value(serde_json::json!({
+            "ticker": { "symbol": "/s", "price": "/p" }
+        }))
+        .unwrap();
+        manager
+            .set_config(ProviderConfig {
+                name: "custom".to_string(),
+                url: format!("ws://{}", addr),
+                heartbeat_interval_ms: 50,
+                message_mapping: Some(mapping),
+                ..Default::default()
+            })
+            .unwrap();
+        manager.connect("custom").await.unwrap();
+        assert_eq!(manager.keepalives.len(), 1);
+
+        manager.shutdown().await;
+        assert!(!manager.is_connected("custom"));
+        assert!(manager.keepalives.is_empty());
+        assert_eq!(manager.get_metrics("custom").unwrap().status, ConnectionStatus::Disconnected);
+
+        // The keepalive task held a clone of the manager; it is gone once aborted
+        for _ in 0..100 {
+            if Arc::strong_count(&manager.connections) == 1 {
+                break;
+            }
+            time::sleep(Duration::from_millis(10)).await;
+        }
+        assert_eq!(Arc::strong_count(&manager.connections), 1);
+
+        let err = manager.connect("custom").await.unwrap_err().to_string();
+        assert!(err.contains("shut down"), "{}", err);
+        server.await.unwrap();
+    }
+
+    #[test]
+    fn test_connection_history_is_bounded() {
+        let mut metrics = ConnectionMetrics::default();
+        for i in 0..(CONNECTION_HISTORY_LEN as u64 + 5) {
+            metrics.push_event(ConnectionEventKind::Connected, i, None);
+        }
+
+        assert_eq!(metrics.history.len(), CONNECTION_HISTORY_LEN);
+        assert_eq!(metrics.history.front().unwrap().at, 5);
+    }
 }
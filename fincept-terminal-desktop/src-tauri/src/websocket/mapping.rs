@@ -0,0 +1,420 @@
+// WebSocket Message Mapping - config-driven parsing for providers without an adapter
+//
+// What: A `MessageMapping` says where a feed keeps each ticker / order book field
+//       as JSON pointers (RFC 6901, e.g. "/data/0/last"), plus optional subscribe
+//       and unsubscribe message templates. `parse_message` turns a raw message
+//       into `MarketMessage`s using it.
+// Why:  Adding an exchange meant writing a Rust adapter and shipping a release.
+//       With a mapping stored in `ws_provider_configs.config_data` the generic
+//       adapter (`adapters::generic`) can read a new feed without recompiling.
+// How:  Each section has an optional `when` test (pointer equals a value, or a
+//       string starts with a prefix) that picks which messages it applies to.
+//       Numbers may arrive as JSON numbers or numeric strings. Timestamps are
+//       scaled from `timestamp_unit` to milliseconds (RFC 3339 strings are also
+//       accepted) and default to the receive time. Built-in providers keep their
+//       hand-written parsers; a mapping only replaces them when one is configured.
+
+use super::types::*;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Selects the messages a section applies to
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MessageMatch {
+    pub pointer: String,
+    /// The value at `pointer` must equal this
+    #[serde(default)]
+    pub equals: Option<Value>,
+    /// The string at `pointer` must start with this (topic-style channels, e.g. "ticker.")
+    #[serde(default)]
+    pub starts_with: Option<String>,
+}
+
+impl MessageMatch {
+    fn matches(&self, msg: &Value) -> bool {
+        let Some(value) = msg.pointer(&self.pointer) else {
+            return false;
+        };
+        self.equals.as_ref().is_none_or(|expected| value == expected)
+            && self
+                .starts_with
+                .as_deref()
+                .is_none_or(|prefix| value.as_str().is_some_and(|s| s.starts_with(prefix)))
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TimestampUnit {
+    Seconds,
+    #[default]
+    Millis,
+    Micros,
+    Nanos,
+}
+
+/// Pointers into a ticker message; `price` and `symbol` are required
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TickerMapping {
+    pub when: Option<MessageMatch>,
+    /// Array of tickers in one message; the other pointers are then relative to each item
+    pub items: Option<String>,
+    pub symbol: String,
+    pub price: String,
+    pub bid: Option<String>,
+    pub ask: Option<String>,
+    pub bid_size: Option<String>,
+    pub ask_size: Option<String>,
+    pub volume: Option<String>,
+    pub high: Option<String>,
+    pub low: Option<String>,
+    pub open: Option<String>,
+    pub change: Option<String>,
+    pub change_percent: Option<String>,
+    pub timestamp: Option<String>,
+    pub timestamp_unit: TimestampUnit,
+}
+
+/// Pointers into an order book message
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OrderBookMapping {
+    pub when: Option<MessageMatch>,
+    pub symbol: String,
+    /// Arrays of levels
+    pub bids: String,
+    pub asks: String,
+    /// Pointers within one level: "/0" and "/1" for `[price, qty]` pairs,
+    /// "/price" and "/qty" for objects
+    pub level_price: String,
+    pub level_quantity: String,
+    pub timestamp: Option<String>,
+    pub timestamp_unit: TimestampUnit,
+    /// Marks a full snapshot; without it every message is treated as one
+    pub snapshot: Option<MessageMatch>,
+}
+
+impl Default for OrderBookMapping {
+    fn default() -> Self {
+        Self {
+            when: None,
+            symbol: String::new(),
+            bids: String::new(),
+            asks: String::new(),
+            level_price: "/0".to_string(),
+            level_quantity: "/1".to_string(),
+            timestamp: None,
+            timestamp_unit: TimestampUnit::default(),
+            snapshot: None,
+        }
+    }
+}
+
+/// How to talk to a feed with no built-in adapter
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MessageMapping {
+    /// Sent on subscribe; "{symbol}" and "{channel}" are replaced in every string
+    pub subscribe: Option<Value>,
+    pub unsubscribe: Option<Value>,
+    pub ticker: Option<TickerMapping>,
+    pub orderbook: Option<OrderBookMapping>,
+}
+
+fn check_pointer(field: &str, pointer: &str) -> Result<(), String> {
+    if pointer.is_empty() || pointer.starts_with('/') {
+        Ok(())
+    } else {
+        Err(format!("{} must be a JSON pointer starting with '/', got '{}'", field, pointer))
+    }
+}
+
+fn check_required(field: &str, pointer: &str) -> Result<(), String> {
+    if pointer.is_empty() {
+        return Err(format!("{} is required", field));
+    }
+    check_pointer(field, pointer)
+}
+
+impl MessageMapping {
+    /// Reject mappings that could never produce a message
+    pub fn validate(&self) -> Result<(), String> {
+        if self.ticker.is_none() && self.orderbook.is_none() {
+            return Err("Message mapping needs a ticker or orderbook section".to_string());
+        }
+        if let Some(t) = &self.ticker {
+            check_required("ticker.symbol", &t.symbol)?;
+            check_required("ticker.price", &t.price)?;
+            let optional = [
+                ("ticker.items", &t.items),
+                ("ticker.bid", &t.bid),
+                ("ticker.ask", &t.ask),
+                ("ticker.bid_size", &t.bid_size),
+                ("ticker.ask_size", &t.ask_size),
+                ("ticker.volume", &t.volume),
+                ("ticker.high", &t.high),
+                ("ticker.low", &t.low),
+                ("ticker.open", &t.open),
+                ("ticker.change", &t.change),
+                ("ticker.change_percent", &t.change_percent),
+                ("ticker.timestamp", &t.timestamp),
+            ];
+            for (field, pointer) in optional {
+                if let Some(pointer) = pointer {
+                    check_pointer(field, pointer)?;
+                }
+            }
+            if let Some(when) = &t.when {
+                check_pointer("ticker.when.pointer", &when.pointer)?;
+            }
+        }
+        if let Some(b) = &self.orderbook {
+            check_required("orderbook.symbol", &b.symbol)?;
+            check_required("orderbook.bids", &b.bids)?;
+            check_required("orderbook.asks", &b.asks)?;
+            check_pointer("orderbook.level_price", &b.level_price)?;
+            check_pointer("orderbook.level_quantity", &b.level_quantity)?;
+            if let Some(pointer) = &b.timestamp {
+                check_pointer("orderbook.timestamp", pointer)?;
+            }
+            for when in [&b.when, &b.snapshot].into_iter().flatten() {
+                check_pointer("orderbook match pointer", &when.pointer)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// `template` with "{symbol}" and "{channel}" substituted in every string
+pub fn render_template(template: &Value, symbol: &str, channel: &str) -> Value {
+    match template {
+        Value::String(s) => Value::String(s.replace("{symbol}", symbol).replace("{channel}", channel)),
+        Value::Array(items) => Value::Array(items.iter().map(|v| render_template(v, symbol, channel)).collect()),
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .map(|(k, v)| (k.clone(), render_template(v, symbol, channel)))
+                .collect(),
+        ),
+        other => other.clone(),
+    }
+}
+
+fn as_number(value: &Value) -> Option<f64> {
+    let number: f64 = match value {
+        Value::Number(n) => n.as_f64()?,
+        Value::String(s) => s.trim().parse().ok()?,
+        _ => return None,
+    };
+    number.is_finite().then_some(number)
+}
+
+fn number_at(msg: &Value, pointer: Option<&String>) -> Option<f64> {
+    msg.pointer(pointer?).and_then(as_number)
+}
+
+fn symbol_at(msg: &Value, pointer: &str) -> Option<String> {
+    match msg.pointer(pointer)? {
+        Value::String(s) if !s.is_empty() => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    }
+}
+
+fn timestamp_at(msg: &Value, pointer: Option<&String>, unit: TimestampUnit, now: u64) -> u64 {
+    let Some(value) = pointer.and_then(|p| msg.pointer(p)) else {
+        return now;
+    };
+    if let Some(text) = value.as_str() {
+        if let Ok(time) = chrono::DateTime::parse_from_rfc3339(text) {
+            return time.timestamp_millis().max(0) as u64;
+        }
+    }
+    let Some(raw) = as_number(value) else {
+        return now;
+    };
+    let millis = match unit {
+        TimestampUnit::Seconds => raw * 1_000.0,
+        TimestampUnit::Millis => raw,
+        TimestampUnit::Micros => raw / 1_000.0,
+        TimestampUnit::Nanos => raw / 1_000_000.0,
+    };
+    if millis >= 0.0 {
+        millis as u64
+    } else {
+        now
+    }
+}
+
+fn parse_ticker(mapping: &TickerMapping, provider: &str, item: &Value, now: u64) -> Option<TickerData> {
+    let field = |pointer: &Option<String>| number_at(item, pointer.as_ref());
+    let price = item.pointer(&mapping.price).and_then(as_number)?;
+    Some(TickerData {
+        provider: provider.to_string(),
+        symbol: symbol_at(item, &mapping.symbol)?,
+        price,
+        bid: field(&mapping.bid),
+        ask: field(&mapping.ask),
+        bid_size: field(&mapping.bid_size),
+        ask_size: field(&mapping.ask_size),
+        volume: field(&mapping.volume),
+        high: field(&mapping.high),
+        low: field(&mapping.low),
+        open: field(&mapping.open),
+        close: Some(price),
+        change: field(&mapping.change),
+        change_percent: field(&mapping.change_percent),
+        timestamp: timestamp_at(item, mapping.timestamp.as_ref(), mapping.timestamp_unit, now),
+        replayed: false,
+    })
+}
+
+fn parse_levels(mapping: &OrderBookMapping, levels: Option<&Value>) -> Vec<OrderBookLevel> {
+    levels
+        .and_then(Value::as_array)
+        .map(|levels| {
+            levels
+                .iter()
+                .filter_map(|level| {
+                    Some(OrderBookLevel {
+                        price: level.pointer(&mapping.level_price).and_then(as_number)?,
+                        quantity: level.pointer(&mapping.level_quantity).and_then(as_number)?,
+                        count: None,
+                    })
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn parse_orderbook(mapping: &OrderBookMapping, provider: &str, msg: &Value, now: u64) -> Option<OrderBookData> {
+    let (bids, asks) = (msg.pointer(&mapping.bids), msg.pointer(&mapping.asks));
+    if bids.is_none() && asks.is_none() {
+        return None;
+    }
+    Some(OrderBookData {
+        provider: provider.to_string(),
+        symbol: symbol_at(msg, &mapping.symbol)?,
+        bids: parse_levels(mapping, bids),
+        asks: parse_levels(mapping, asks),
+        timestamp: timestamp_at(msg, mapping.timestamp.as_ref(), mapping.timestamp_unit, now),
+        is_snapshot: mapping.snapshot.as_ref().is_none_or(|m| m.matches(msg)),
+    })
+}
+
+/// Every ticker and order book `msg` holds according to `mapping`;
+/// `now` (ms) stands in for missing timestamps
+pub fn parse_message(mapping: &MessageMapping, provider: &str, msg: &Value, now: u64) -> Vec<MarketMessage> {
+    let applies = |when: &Option<MessageMatch>| when.as_ref().is_none_or(|m| m.matches(msg));
+    let mut out = Vec::new();
+
+    if let Some(ticker) = mapping.ticker.as_ref().filter(|t| applies(&t.when)) {
+        match ticker.items.as_deref().map(|p| msg.pointer(p)) {
+            Some(Some(Value::Array(items))) => out.extend(
+                items
+                    .iter()
+                    .filter_map(|item| parse_ticker(ticker, provider, item, now))
+                    .map(MarketMessage::Ticker),
+            ),
+            Some(_) => {}
+            None => out.extend(parse_ticker(ticker, provider, msg, now).map(MarketMessage::Ticker)),
+        }
+    }
+    if let Some(book) = mapping.orderbook.as_ref().filter(|b| applies(&b.when)) {
+        out.extend(parse_orderbook(book, provider, msg, now).map(MarketMessage::OrderBook));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn mapping() -> MessageMapping {
+        serde_json::from_value(json!({
+            "subscribe": { "op": "subscribe", "args": ["{channel}.{symbol}"] },
+            "ticker": {
+                "when": { "pointer": "/topic", "starts_with": "tickers." },
+                "symbol": "/data/s",
+                "price": "/data/lastPrice",
+                "bid": "/data/bid1Price",
+                "volume": "/data/volume24h",
+                "timestamp": "/ts"
+            },
+            "orderbook": {
+                "when": { "pointer": "/topic", "starts_with": "orderbook." },
+                "symbol": "/data/s",
+                "bids": "/data/b",
+                "asks": "/data/a",
+                "snapshot": { "pointer": "/type", "equals": "snapshot" }
+            }
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_parses_ticker_with_string_numbers() {
+        let msg = json!({
+            "topic": "tickers.BTCUSDT",
+            "ts": 1_700_000_000_123u64,
+            "data": { "s": "BTCUSDT", "lastPrice": "65000.5", "bid1Price": 64999.0, "volume24h": "12.5" }
+        });
+        let parsed = parse_message(&mapping(), "bybit", &msg, 1);
+        let [MarketMessage::Ticker(t)] = parsed.as_slice() else {
+            panic!("expected one ticker, got {:?}", parsed);
+        };
+        assert_eq!((t.provider.as_str(), t.symbol.as_str()), ("bybit", "BTCUSDT"));
+        assert_eq!((t.price, t.bid, t.volume, t.ask), (65000.5, Some(64999.0), Some(12.5), None));
+        assert_eq!(t.timestamp, 1_700_000_000_123);
+    }
+
+    #[test]
+    fn test_parses_orderbook_and_skips_unmatched() {
+        let msg = json!({
+            "topic": "orderbook.50.BTCUSDT",
+            "type": "delta",
+            "data": { "s": "BTCUSDT", "b": [["65000", "1.5"], ["bad", "1"]], "a": [["65001", "0.2"]] }
+        });
+        let parsed = parse_message(&mapping(), "bybit", &msg, 42);
+        let [MarketMessage::OrderBook(book)] = parsed.as_slice() else {
+            panic!("expected one book, got {:?}", parsed);
+        };
+        assert_eq!(book.bids.len(), 1);
+        assert_eq!((book.asks[0].price, book.asks[0].quantity), (65001.0, 0.2));
+        assert!(!book.is_snapshot);
+        assert_eq!(book.timestamp, 42);
+
+        assert!(parse_message(&mapping(), "bybit", &json!({ "op": "pong" }), 0).is_empty());
+    }
+
+    #[test]
+    fn test_ticker_items_and_timestamp_units() {
+        let mapping: MessageMapping = serde_json::from_value(json!({
+            "ticker": { "items": "/data", "symbol": "/sym", "price": "/p", "timestamp": "/t", "timestamp_unit": "seconds" }
+        }))
+        .unwrap();
+        let msg = json!({ "data": [{ "sym": "A", "p": 1, "t": 1_700_000_000 }, { "sym": "B" }, { "sym": "C", "p": "2", "t": "2024-01-01T00:00:00Z" }] });
+        let parsed = parse_message(&mapping, "custom", &msg, 0);
+        let stamps: Vec<(String, u64)> = parsed
+            .iter()
+            .filter_map(|m| match m {
+                MarketMessage::Ticker(t) => Some((t.symbol.clone(), t.timestamp)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(stamps, vec![("A".to_string(), 1_700_000_000_000), ("C".to_string(), 1_704_067_200_000)]);
+    }
+
+    #[test]
+    fn test_render_template_and_validate() {
+        let rendered = render_template(mapping().subscribe.as_ref().unwrap(), "ETHUSDT", "tickers");
+        assert_eq!(rendered, json!({ "op": "subscribe", "args": ["tickers.ETHUSDT"] }));
+
+        assert!(mapping().validate().is_ok());
+        assert!(MessageMapping::default().validate().is_err());
+        let mut bad = mapping();
+        bad.ticker.as_mut().unwrap().price = "data.lastPrice".to_string();
+        assert!(bad.validate().unwrap_err().contains("ticker.price"));
+    }
+}
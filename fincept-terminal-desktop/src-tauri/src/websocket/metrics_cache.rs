@@ -0,0 +1,187 @@
+// Metrics Cache - short-lived snapshot of all provider metrics
+//
+// What: `ws_get_all_metrics` answers from a snapshot that is at most
+//       `ws_metrics_cache_ms` old (default 1000). `fresh: true` always reads the
+//       manager and refreshes the snapshot.
+// Why:  Dashboards poll the metrics several times a second from many widgets, and
+//       every poll took the manager read lock and cloned every entry, competing
+//       with connect/subscribe calls that need the write lock.
+// How:  Polls inside the window clone the snapshot without touching the manager.
+//       When it has expired, one poller refreshes it while the others wait on
+//       `refresh` and then take the result, so a burst of polls costs one read.
+//       A cache built from settings re-reads the window on every refresh, at
+//       most once per window, so a saved change applies without a restart.
+
+use super::manager::WebSocketManager;
+use super::types::ConnectionMetrics;
+use parking_lot::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+use tokio::sync::RwLock;
+
+pub const METRICS_CACHE_SETTING: &str = "ws_metrics_cache_ms";
+
+const DEFAULT_TTL_MS: u64 = 1_000;
+
+pub struct MetricsCache {
+    ttl_ms: AtomicU64,
+    /// Re-read the window from settings on each refresh
+    follow_settings: bool,
+    /// Times the manager was read, i.e. the read lock was taken
+    manager_reads: AtomicU64,
+    snapshot: Mutex<Option<(Instant, Vec<ConnectionMetrics>)>>,
+    refresh: tokio::sync::Mutex<()>,
+}
+
+impl Default for MetricsCache {
+    fn default() -> Self {
+        Self::new(Duration::from_millis(DEFAULT_TTL_MS))
+    }
+}
+
+impl MetricsCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl_ms: AtomicU64::new(ttl.as_millis() as u64),
+            follow_settings: false,
+            manager_reads: AtomicU64::new(0),
+            snapshot: Mutex::new(None),
+            refresh: tokio::sync::Mutex::new(()),
+        }
+    }
+
+    /// Window from the `ws_metrics_cache_ms` setting, followed as it changes
+    pub fn from_settings() -> Self {
+        Self {
+            follow_settings: true,
+            ..Self::new(Duration::from_millis(setting_ttl_ms()))
+        }
+    }
+
+    fn ttl(&self) -> Duration {
+        Duration::from_millis(self.ttl_ms.load(Ordering::Relaxed))
+    }
+
+    /// How often the manager has been read since the cache was created
+    pub fn manager_reads(&self) -> u64 {
+        self.manager_reads.load(Ordering::Relaxed)
+    }
+
+    fn cached(&self) -> Option<Vec<ConnectionMetrics>> {
+        let ttl = self.ttl();
+        match &*self.snapshot.lock() {
+            Some((taken_at, metrics)) if taken_at.elapsed() < ttl => Some(metrics.clone()),
+            _ => None,
+        }
+    }
+
+    /// All provider metrics, from the snapshot unless it has expired or `fresh` is set
+    pub async fn get(&self, manager: &RwLock<WebSocketManager>, fresh: bool) -> Vec<ConnectionMetrics> {
+        if !fresh {
+            if let Some(metrics) = self.cached() {
+                return metrics;
+            }
+        }
+
+        let _refresh = self.refresh.lock().await;
+        if self.follow_settings {
+            self.ttl_ms.store(setting_ttl_ms(), Ordering::Relaxed);
+        }
+        if !fresh {
+            // Another poller may have refreshed it while this one waited
+            if let Some(metrics) = self.cached() {
+                return metrics;
+            }
+        }
+        let metrics = manager.read().await.get_all_metrics();
+        self.manager_reads.fetch_add(1, Ordering::Relaxed);
+        *self.snapshot.lock() = Some((Instant::now(), metrics.clone()));
+        metrics
+    }
+}
+
+/// The `ws_metrics_cache_ms` setting; an unavailable database or bad value means the default
+fn setting_ttl_ms() -> u64 {
+    crate::database::operations::get_setting(METRICS_CACHE_SETTING)
+        .ok()
+        .flatten()
+        .and_then(|v| v.trim().parse::<u64>().ok())
+        .filter(|ms| *ms > 0)
+        .unwrap_or(DEFAULT_TTL_MS)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::websocket::MessageRouter;
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_cached_polls_skip_the_manager_lock() {
+        let router = Arc::new(RwLock::new(MessageRouter::new()));
+        let manager = Arc::new(RwLock::new(WebSocketManager::new(router)));
+        let cache = Arc::new(MetricsCache::new(Duration::from_secs(60)));
+        let first = cache.get(&manager, false).await;
+
+        // With the write lock held elsewhere, cached polls still answer at once
+        let writer = manager.write().await;
+        let pollers: Vec<_> = (0..50)
+            .map(|_| {
+                let (cache, manager) = (cache.clone(), manager.clone());
+                tokio::spawn(async move { cache.get(&manager, false).await })
+            })
+            .collect();
+        for poller in pollers {
+            let metrics = tokio::time::timeout(Duration::from_secs(1), poller).await.unwrap().unwrap();
+            assert_eq!(metrics.len(), first.len());
+        }
+
+        // A fresh read has to wait for the manager
+        let fresh = tokio::time::timeout(Duration::from_millis(50), cache.get(&manager, true)).await;
+        assert!(fresh.is_err());
+        drop(writer);
+        assert_eq!(cache.get(&manager, true).await.len(), first.len());
+    }
+
+    /// Time a writer (connect/subscribe) waits for the manager while a burst of polls runs
+    async fn writer_wait(manager: &Arc<RwLock<WebSocketManager>>, poll: impl Fn() -> tokio::task::JoinHandle<()>) -> Duration {
+        let pollers: Vec<_> = (0..POLLS).map(|_| poll()).collect();
+        let started = Instant::now();
+        drop(manager.write().await);
+        let waited = started.elapsed();
+        for poller in pollers {
+            poller.await.unwrap();
+        }
+        waited
+    }
+
+    const POLLS: usize = 2_000;
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn bench_manager_lock_hold_time() {
+        let router = Arc::new(RwLock::new(MessageRouter::new()));
+        let manager = Arc::new(RwLock::new(WebSocketManager::new(router)));
+        let cache = Arc::new(MetricsCache::new(Duration::from_secs(60)));
+
+        // Before: every poll takes the read lock and clones every entry
+        let uncached = writer_wait(&manager, || {
+            let manager = manager.clone();
+            tokio::spawn(async move {
+                manager.read().await.get_all_metrics();
+            })
+        })
+        .await;
+
+        // After: the burst shares one read
+        let cached = writer_wait(&manager, || {
+            let (cache, manager) = (cache.clone(), manager.clone());
+            tokio::spawn(async move {
+                cache.get(&manager, false).await;
+            })
+        })
+        .await;
+
+        println!("{} polls: writer waited {:?} uncached, {:?} cached", POLLS, uncached, cached);
+        assert_eq!(cache.manager_reads(), 1, "{} polls should take the manager lock once", POLLS);
+    }
+}
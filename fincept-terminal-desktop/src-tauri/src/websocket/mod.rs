@@ -12,6 +12,15 @@ pub mod manager;
 pub mod router;
 pub mod adapters;
 pub mod services;
+pub mod batcher;
+pub mod throttle;
+pub mod mapping;
+pub mod raw_capture;
+pub mod replay;
+pub mod tick_recorder;
+pub mod auth;
+pub mod keepalive;
+pub mod metrics_cache;
 
 pub use manager::WebSocketManager;
 pub use router::MessageRouter;
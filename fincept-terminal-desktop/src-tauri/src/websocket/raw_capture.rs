@@ -0,0 +1,182 @@
+// Raw Frame Capture - last unparsed message per (provider, symbol, channel) for debugging
+//
+// What: While enabled, adapters record every text frame they receive; the newest
+//       one per provider, symbol and channel is kept and returned by `ws_get_last_raw`.
+// Why:  When normalized data looks wrong (a bad field in a built-in parser, or a
+//       wrong pointer in a message mapping) the only way to see what the provider
+//       actually sent was a packet capture.
+// How:  Off by default and gated by the `ws_debug_raw_messages` setting; when off,
+//       `record` is a single atomic load and adapters skip working out the channel.
+//       The channel is the one named in the frame itself (Kraken's and
+//       HyperLiquid's `channel`, Binance's event type `e`, the mapping section
+//       that matched for mapped providers) or "unmatched" when there is none.
+//       The symbol is read from the frame the same way (Binance's `s`, Kraken's
+//       `data[0].symbol`, the parsed message for mapped providers) and is empty
+//       for frames that carry none, so subscribed symbols don't overwrite each
+//       other. Frames are cut to `MAX_FRAME_BYTES`; disabling clears the store.
+
+use dashmap::DashMap;
+use serde::Serialize;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Setting that turns capture on at startup
+pub const RAW_CAPTURE_SETTING: &str = "ws_debug_raw_messages";
+/// Channel for frames that name none (acks, pongs, unparsed frames)
+pub const UNMATCHED_CHANNEL: &str = "unmatched";
+/// Longer frames (full order book snapshots) are truncated
+pub const MAX_FRAME_BYTES: usize = 64 * 1024;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RawFrame {
+    pub provider: String,
+    /// Empty for frames that name no symbol (acks, pongs, all-market feeds)
+    pub symbol: String,
+    pub channel: String,
+    pub raw: String,
+    pub truncated: bool,
+    pub received_at: u64,
+}
+
+#[derive(Debug, Default)]
+pub struct RawCapture {
+    enabled: AtomicBool,
+    frames: DashMap<(String, String, String), RawFrame>,
+}
+
+fn key(provider: &str, symbol: &str, channel: &str) -> (String, String, String) {
+    (provider.trim().to_lowercase(), symbol.trim().to_string(), channel.trim().to_string())
+}
+
+impl RawCapture {
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    pub fn set_enabled(&self, enabled: bool) {
+        self.enabled.store(enabled, Ordering::Relaxed);
+        if !enabled {
+            self.frames.clear();
+        }
+    }
+
+    /// Keep `raw` as the latest frame for the symbol's channel; no-op while disabled
+    pub fn record(&self, provider: &str, symbol: &str, channel: &str, raw: &str) {
+        if !self.is_enabled() {
+            return;
+        }
+        let channel = if channel.trim().is_empty() { UNMATCHED_CHANNEL } else { channel };
+        let truncated = raw.len() > MAX_FRAME_BYTES;
+        let raw = if truncated {
+            let mut end = MAX_FRAME_BYTES;
+            while !raw.is_char_boundary(end) {
+                end -= 1;
+            }
+            &raw[..end]
+        } else {
+            raw
+        };
+        let key = key(provider, symbol, channel);
+        let frame = RawFrame {
+            provider: key.0.clone(),
+            symbol: key.1.clone(),
+            channel: key.2.clone(),
+            raw: raw.to_string(),
+            truncated,
+            received_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_millis() as u64,
+        };
+        self.frames.insert(key, frame);
+    }
+
+    /// `record` with the channel taken from the frame's top-level string `field`
+    /// and the symbol from the first of `symbol_pointers` that holds a string
+    pub fn record_frame(&self, provider: &str, field: &str, symbol_pointers: &[&str], raw: &str) {
+        if !self.is_enabled() {
+            return;
+        }
+        let value = serde_json::from_str::<serde_json::Value>(raw).ok();
+        let string_at = |pointer: &str| value.as_ref()?.pointer(pointer)?.as_str();
+        let channel = string_at(&format!("/{}", field)).unwrap_or_default();
+        let symbol = symbol_pointers.iter().find_map(|p| string_at(p)).unwrap_or_default();
+        self.record(provider, symbol, channel, raw);
+    }
+
+    /// Latest frames seen for `provider`, narrowed to `symbol` (case-insensitive)
+    /// and `channel` when given; sorted by symbol, then channel
+    pub fn find(&self, provider: &str, symbol: Option<&str>, channel: Option<&str>) -> Vec<RawFrame> {
+        let provider = provider.trim().to_lowercase();
+        let mut frames: Vec<RawFrame> = self
+            .frames
+            .iter()
+            .filter(|entry| entry.key().0 == provider)
+            .filter(|entry| symbol.is_none_or(|s| entry.key().1.eq_ignore_ascii_case(s.trim())))
+            .filter(|entry| channel.is_none_or(|c| entry.key().2 == c.trim()))
+            .map(|entry| entry.value().clone())
+            .collect();
+        frames.sort_by(|a, b| (&a.symbol, &a.channel).cmp(&(&b.symbol, &b.channel)));
+        frames
+    }
+}
+
+/// Whether the debug setting is on; an unavailable database counts as off
+pub fn enabled_in_settings() -> bool {
+    crate::database::operations::get_setting(RAW_CAPTURE_SETTING)
+        .ok()
+        .flatten()
+        .is_some_and(|v| matches!(v.trim(), "true" | "1" | "on"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_records_only_while_enabled() {
+        let capture = RawCapture::default();
+        capture.record("kraken", "BTC/USD", "ticker", "{}");
+        assert!(capture.find("kraken", None, None).is_empty());
+
+        capture.set_enabled(true);
+        capture.record("Kraken", "BTC/USD", "ticker", r#"{"a":1}"#);
+        capture.record("kraken", "BTC/USD", "ticker", r#"{"a":2}"#);
+        capture.record_frame("kraken", "channel", &["/data/0/symbol"], "pong");
+        let frames = capture.find("KRAKEN", Some("BTC/USD"), Some("ticker"));
+        assert_eq!(frames.len(), 1);
+        assert_eq!(frames[0].raw, r#"{"a":2}"#);
+        let channels: Vec<String> = capture.find("kraken", None, None).into_iter().map(|f| f.channel).collect();
+        assert_eq!(channels, vec![UNMATCHED_CHANNEL, "ticker"]);
+
+        capture.set_enabled(false);
+        assert!(capture.find("kraken", None, None).is_empty());
+    }
+
+    #[test]
+    fn test_symbols_on_one_channel_are_kept_apart() {
+        let capture = RawCapture::default();
+        capture.set_enabled(true);
+        capture.record_frame("binance", "e", &["/s"], r#"{"e":"24hrMiniTicker","s":"BTCUSDT","c":"1"}"#);
+        capture.record_frame("binance", "e", &["/s"], r#"{"e":"24hrMiniTicker","s":"ETHUSDT","c":"2"}"#);
+
+        let frames = capture.find("binance", None, Some("24hrMiniTicker"));
+        let symbols: Vec<&str> = frames.iter().map(|f| f.symbol.as_str()).collect();
+        assert_eq!(symbols, ["BTCUSDT", "ETHUSDT"]);
+
+        let eth = capture.find("binance", Some("ethusdt"), None);
+        assert_eq!(eth.len(), 1);
+        assert!(eth[0].raw.contains(r#""c":"2""#));
+    }
+
+    #[test]
+    fn test_truncates_on_char_boundary() {
+        let capture = RawCapture::default();
+        capture.set_enabled(true);
+        let raw = "é".repeat(MAX_FRAME_BYTES);
+        capture.record("binance", "BTCUSDT", "depthUpdate", &raw);
+        let frame = capture.find("binance", Some("BTCUSDT"), Some("depthUpdate")).remove(0);
+        assert!(frame.truncated);
+        assert_eq!(frame.raw.len(), MAX_FRAME_BYTES);
+    }
+}
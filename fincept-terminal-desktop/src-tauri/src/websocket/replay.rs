@@ -0,0 +1,86 @@
+// Replay Buffer - the latest ticks the router has seen for each symbol
+//
+// What: The router keeps the last `REPLAY_TICKS` tickers per (provider, symbol),
+//       oldest first, for code that needs recent history outside the live
+//       stream, such as testing a monitor condition before it is saved.
+// Why:  The broadcast channels only reach receivers that existed when a tick was
+//       sent, so there was no way to look back at what a feed just did.
+// How:  Every ticker the router broadcasts is recorded, including ones published
+//       without a frontend event. Symbols are stored as the adapter sent them
+//       and looked up with `Symbol::matches`, like monitor conditions do.
+
+use super::types::TickerData;
+use crate::utils::symbol::Symbol;
+use dashmap::DashMap;
+use std::collections::VecDeque;
+
+/// Ticks kept per (provider, symbol)
+pub const REPLAY_TICKS: usize = 500;
+
+#[derive(Default)]
+pub struct ReplayBuffer {
+    ticks: DashMap<(String, String), VecDeque<TickerData>>,
+}
+
+impl ReplayBuffer {
+    pub fn record(&self, tick: &TickerData) {
+        let mut ticks = self
+            .ticks
+            .entry((tick.provider.clone(), tick.symbol.clone()))
+            .or_insert_with(|| VecDeque::with_capacity(REPLAY_TICKS));
+        if ticks.len() == REPLAY_TICKS {
+            ticks.pop_front();
+        }
+        ticks.push_back(tick.clone());
+    }
+
+    /// Recorded ticks of `provider` for `symbol`, oldest first
+    pub fn ticks(&self, provider: &str, symbol: &Symbol) -> Vec<TickerData> {
+        self.ticks
+            .iter()
+            .find(|entry| entry.key().0 == provider && symbol.matches(&entry.key().1))
+            .map(|entry| entry.value().iter().cloned().collect())
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tick(symbol: &str, price: f64) -> TickerData {
+        TickerData {
+            provider: "kraken".to_string(),
+            symbol: symbol.to_string(),
+            price,
+            bid: None,
+            ask: None,
+            bid_size: None,
+            ask_size: None,
+            volume: None,
+            high: None,
+            low: None,
+            open: None,
+            close: None,
+            change: None,
+            change_percent: None,
+            timestamp: 0,
+            replayed: false,
+        }
+    }
+
+    #[test]
+    fn test_keeps_latest_ticks_per_symbol() {
+        let buffer = ReplayBuffer::default();
+        for i in 0..REPLAY_TICKS + 10 {
+            buffer.record(&tick("BTCUSD", i as f64));
+        }
+        buffer.record(&tick("ETHUSD", 1.0));
+
+        let btc = buffer.ticks("kraken", &Symbol::new("btcusd").unwrap());
+        assert_eq!(btc.len(), REPLAY_TICKS);
+        assert_eq!((btc[0].price, btc[REPLAY_TICKS - 1].price), (10.0, (REPLAY_TICKS + 9) as f64));
+        assert_eq!(buffer.ticks("kraken", &Symbol::new("ETHUSD").unwrap()).len(), 1);
+        assert!(buffer.ticks("binance", &Symbol::new("BTCUSD").unwrap()).is_empty());
+    }
+}
@@ -7,10 +7,15 @@
 // - Portfolio tracker
 // - Analytics services
 
+use super::replay::ReplayBuffer;
+use super::throttle::{ThrottleDecision, TickerThrottle};
+use super::tick_recorder::TickRecorder;
 use super::types::*;
+use crate::utils::symbol::Symbol;
 use dashmap::DashMap;
 use serde::Serialize;
 use std::sync::Arc;
+use std::time::Instant;
 use tauri::Emitter;
 use tokio::sync::broadcast;
 
@@ -25,8 +30,17 @@ pub struct MessageRouter {
     candle_tx: broadcast::Sender<CandleData>,
     status_tx: broadcast::Sender<StatusData>,
 
-    // Track which topics have frontend subscribers
-    frontend_subscribers: Arc<DashMap<String, bool>>,
+    // Track how many frontend views are subscribed to each topic
+    frontend_subscribers: Arc<DashMap<String, usize>>,
+
+    // Per-provider cap on frontend ticker events (backend services get every tick)
+    ticker_throttle: Arc<TickerThrottle>,
+
+    // Latest ticks per symbol, for looking back outside the live stream
+    replay: Arc<ReplayBuffer>,
+
+    // Opt-in persistence of tickers to `tick_history`
+    tick_recorder: Arc<TickRecorder>,
 
     // Tauri app handle for emitting events
     app_handle: Option<tauri::AppHandle>,
@@ -47,6 +61,9 @@ impl MessageRouter {
             candle_tx,
             status_tx,
             frontend_subscribers: Arc::new(DashMap::new()),
+            ticker_throttle: Arc::new(TickerThrottle::default()),
+            replay: Arc::new(ReplayBuffer::default()),
+            tick_recorder: Arc::new(TickRecorder::default()),
             app_handle: None,
         }
     }
@@ -56,14 +73,47 @@ impl MessageRouter {
         self.app_handle = Some(app_handle);
     }
 
+    /// Forward at most one frontend ticker per symbol every `interval_ms` for `provider`
+    ///
+    /// 0 forwards every tick. Set from `ProviderConfig::ticker_throttle_ms` by `ws_set_config`
+    /// and again on every connect, so configs loaded from the database keep their throttle.
+    pub fn set_ticker_throttle(&self, provider: &str, interval_ms: u64) {
+        self.ticker_throttle.set_interval(provider, interval_ms);
+    }
+
     /// Register frontend subscriber for a topic
-    pub fn subscribe_frontend(&self, topic: &str) {
-        self.frontend_subscribers.insert(topic.to_string(), true);
+    ///
+    /// Topics are reference counted so that several panels can watch the same
+    /// symbol. Returns the number of subscribers after registration.
+    pub fn subscribe_frontend(&self, topic: &str) -> usize {
+        let mut count = self.frontend_subscribers.entry(topic.to_string()).or_insert(0);
+        *count += 1;
+        *count
     }
 
     /// Unregister frontend subscriber
-    pub fn unsubscribe_frontend(&self, topic: &str) {
-        self.frontend_subscribers.remove(topic);
+    ///
+    /// The topic is only dropped once its last subscriber leaves. Returns the
+    /// number of subscribers still registered for the topic.
+    pub fn unsubscribe_frontend(&self, topic: &str) -> usize {
+        let remaining = match self.frontend_subscribers.get_mut(topic) {
+            Some(mut count) => {
+                *count = count.saturating_sub(1);
+                *count
+            }
+            None => return 0,
+        };
+
+        if remaining == 0 {
+            self.frontend_subscribers.remove_if(topic, |_, count| *count == 0);
+        }
+
+        remaining
+    }
+
+    /// Number of frontend subscribers registered for a topic
+    pub fn frontend_subscriber_count(&self, topic: &str) -> usize {
+        self.frontend_subscribers.get(topic).map(|c| *c).unwrap_or(0)
     }
 
     /// Check if frontend is subscribed to topic
@@ -123,22 +173,76 @@ impl MessageRouter {
     // ========================================================================
 
     async fn route_ticker(&self, data: TickerData) {
-        // 1. Broadcast to backend services
+        self.tick_recorder.offer(&data);
+        self.deliver_ticker(data);
+    }
+
+    /// Route a recorded tick again, marked `replayed`, without recording it a second time
+    pub fn replay_ticker(&self, mut data: TickerData) {
+        data.replayed = true;
+        self.deliver_ticker(data);
+    }
+
+    fn deliver_ticker(&self, data: TickerData) {
+        // 1. Broadcast to backend services; replayed ticks stay out of the live lookback
+        if !data.replayed {
+            self.replay.record(&data);
+        }
         let _ = self.ticker_tx.send(data.clone());
 
-        // 2. Emit to frontend if subscribed
-        if self.has_frontend_subscriber(&data.provider, &data.symbol, "ticker") {
-            self.emit_to_frontend("ws_ticker", &data);
+        // 2. Emit to frontend if subscribed, subject to the provider's throttle
+        if !self.has_frontend_subscriber(&data.provider, &data.symbol, "ticker") {
+            return;
+        }
+        match self.ticker_throttle.offer(&data, Instant::now()) {
+            ThrottleDecision::Emit => self.emit_to_frontend(WsEvent::TICKER, &data),
+            ThrottleDecision::Deferred { flush_after: Some(delay) } => {
+                self.schedule_ticker_flush(data.provider, data.symbol, delay)
+            }
+            ThrottleDecision::Deferred { flush_after: None } => {}
         }
     }
 
+    /// Emit the tick held back by the throttle once its window closes
+    fn schedule_ticker_flush(&self, provider: String, symbol: String, delay: std::time::Duration) {
+        let throttle = self.ticker_throttle.clone();
+        let app_handle = self.app_handle.clone();
+        tauri::async_runtime::spawn(async move {
+            tokio::time::sleep(delay).await;
+            if let Some(ticker) = throttle.take_pending(&provider, &symbol, Instant::now()) {
+                if let Some(app) = app_handle {
+                    let _ = app.emit(WsEvent::TICKER, &ticker);
+                }
+            }
+        });
+    }
+
+    /// Broadcast a ticker to backend services only (no per-tick frontend event)
+    ///
+    /// Used for high-rate feeds whose frontend delivery is batched separately.
+    pub fn publish_ticker(&self, data: TickerData) {
+        self.tick_recorder.offer(&data);
+        self.replay.record(&data);
+        let _ = self.ticker_tx.send(data);
+    }
+
+    /// The latest ticks routed for `provider` / `symbol`, oldest first (see `replay`)
+    pub fn recent_tickers(&self, provider: &str, symbol: &Symbol) -> Vec<TickerData> {
+        self.replay.ticks(provider, symbol)
+    }
+
+    /// Which tickers are persisted to `tick_history` (see `tick_recorder`)
+    pub fn tick_recorder(&self) -> &TickRecorder {
+        &self.tick_recorder
+    }
+
     async fn route_orderbook(&self, data: OrderBookData) {
         // 1. Broadcast to backend services
         let _ = self.orderbook_tx.send(data.clone());
 
         // 2. Emit to frontend if subscribed
         if self.has_frontend_subscriber(&data.provider, &data.symbol, "book") {
-            self.emit_to_frontend("ws_orderbook", &data);
+            self.emit_to_frontend(WsEvent::ORDERBOOK, &data);
         }
     }
 
@@ -148,7 +252,7 @@ impl MessageRouter {
 
         // 2. Emit to frontend if subscribed
         if self.has_frontend_subscriber(&data.provider, &data.symbol, "trade") {
-            self.emit_to_frontend("ws_trade", &data);
+            self.emit_to_frontend(WsEvent::TRADE, &data);
         }
     }
 
@@ -158,7 +262,7 @@ impl MessageRouter {
 
         // 2. Emit to frontend if subscribed
         if self.has_frontend_subscriber(&data.provider, &data.symbol, "candle") {
-            self.emit_to_frontend("ws_candle", &data);
+            self.emit_to_frontend(WsEvent::CANDLE, &data);
         }
     }
 
@@ -167,7 +271,7 @@ impl MessageRouter {
         let _ = self.status_tx.send(data.clone());
 
         // 2. Always emit status to frontend
-        self.emit_to_frontend("ws_status", &data);
+        self.emit_to_frontend(WsEvent::STATUS, &data);
     }
 
     // ========================================================================
@@ -215,3 +319,30 @@ impl Default for MessageRouter {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frontend_subscribers_are_reference_counted() {
+        let router = MessageRouter::new();
+
+        assert_eq!(router.subscribe_frontend("kraken.ticker.BTC/USD"), 1);
+        assert_eq!(router.subscribe_frontend("kraken.ticker.BTC/USD"), 2);
+
+        // One panel closing must not stop delivery to the other
+        assert_eq!(router.unsubscribe_frontend("kraken.ticker.BTC/USD"), 1);
+        assert!(router.has_frontend_subscriber("kraken", "BTCUSD", "ticker"));
+
+        assert_eq!(router.unsubscribe_frontend("kraken.ticker.BTC/USD"), 0);
+        assert!(!router.has_frontend_subscriber("kraken", "BTCUSD", "ticker"));
+        assert_eq!(router.frontend_subscriber_count("kraken.ticker.BTC/USD"), 0);
+    }
+
+    #[test]
+    fn test_unsubscribe_unknown_topic_is_noop() {
+        let router = MessageRouter::new();
+        assert_eq!(router.unsubscribe_frontend("binance.trade.ETHUSDT"), 0);
+    }
+}
@@ -0,0 +1,129 @@
+// Book Metrics - order book state and derived signals for monitoring
+//
+// What: `LocalBook` keeps the latest bids/asks for one (provider, symbol) from the
+//       router's orderbook stream; `metrics()` derives the mid price and top-of-book
+//       imbalance that monitor conditions can alert on.
+// Why:  Last price says little about microstructure. A book leaning heavily to one
+//       side or a drifting mid often leads price, and users want alerts on both.
+// How:  Snapshots replace the book, deltas upsert levels (quantity 0 removes one).
+//       Levels are keyed by price bits, which sort like the prices themselves for
+//       positive floats, so the best levels come straight out of a BTreeMap.
+//       Definitions, over the best `IMBALANCE_DEPTH` levels per side:
+//         mid_price  = (best_bid + best_ask) / 2
+//         imbalance  = (bid_qty - ask_qty) / (bid_qty + ask_qty), in [-1, 1]
+//       An empty or one-sided book yields no metrics, so conditions on these
+//       fields are skipped rather than evaluated against a partial book.
+
+use crate::websocket::types::{OrderBookData, OrderBookLevel};
+use std::collections::BTreeMap;
+
+/// Levels per side summed into the imbalance
+pub const IMBALANCE_DEPTH: usize = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BookMetrics {
+    pub mid_price: f64,
+    pub imbalance: f64,
+}
+
+#[derive(Debug, Default)]
+pub struct LocalBook {
+    /// price bits -> quantity
+    bids: BTreeMap<u64, f64>,
+    asks: BTreeMap<u64, f64>,
+}
+
+impl LocalBook {
+    /// Apply a snapshot or delta from the orderbook stream
+    pub fn apply(&mut self, update: &OrderBookData) {
+        if update.is_snapshot {
+            self.bids.clear();
+            self.asks.clear();
+        }
+        Self::apply_levels(&mut self.bids, &update.bids);
+        Self::apply_levels(&mut self.asks, &update.asks);
+    }
+
+    fn apply_levels(side: &mut BTreeMap<u64, f64>, levels: &[OrderBookLevel]) {
+        for level in levels {
+            if !level.price.is_finite() || level.price <= 0.0 {
+                continue;
+            }
+            let key = level.price.to_bits();
+            if level.quantity > 0.0 {
+                side.insert(key, level.quantity);
+            } else {
+                side.remove(&key);
+            }
+        }
+    }
+
+    /// Mid price and imbalance, or `None` for an empty or one-sided book
+    pub fn metrics(&self) -> Option<BookMetrics> {
+        let (&best_bid, _) = self.bids.iter().next_back()?;
+        let (&best_ask, _) = self.asks.iter().next()?;
+
+        let bid_qty: f64 = self.bids.values().rev().take(IMBALANCE_DEPTH).sum();
+        let ask_qty: f64 = self.asks.values().take(IMBALANCE_DEPTH).sum();
+        let total = bid_qty + ask_qty;
+        if total <= 0.0 {
+            return None;
+        }
+
+        Some(BookMetrics {
+            mid_price: (f64::from_bits(best_bid) + f64::from_bits(best_ask)) / 2.0,
+            imbalance: (bid_qty - ask_qty) / total,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn level(price: f64, quantity: f64) -> OrderBookLevel {
+        OrderBookLevel {
+            price,
+            quantity,
+            count: None,
+        }
+    }
+
+    fn update(bids: Vec<OrderBookLevel>, asks: Vec<OrderBookLevel>, is_snapshot: bool) -> OrderBookData {
+        OrderBookData {
+            provider: "kraken".to_string(),
+            symbol: "BTC/USD".to_string(),
+            bids,
+            asks,
+            timestamp: 0,
+            is_snapshot,
+        }
+    }
+
+    #[test]
+    fn test_metrics_from_snapshot_and_delta() {
+        let mut book = LocalBook::default();
+        book.apply(&update(
+            vec![level(99.0, 3.0), level(100.0, 1.0)],
+            vec![level(102.0, 1.0), level(101.0, 1.0)],
+            true,
+        ));
+
+        let metrics = book.metrics().unwrap();
+        assert_eq!(metrics.mid_price, 100.5);
+        assert_eq!(metrics.imbalance, (4.0 - 2.0) / 6.0);
+
+        // Removing the best bid moves the mid down
+        book.apply(&update(vec![level(100.0, 0.0)], vec![], false));
+        assert_eq!(book.metrics().unwrap().mid_price, 100.0);
+    }
+
+    #[test]
+    fn test_one_sided_book_has_no_metrics() {
+        let mut book = LocalBook::default();
+        assert!(book.metrics().is_none());
+
+        book.apply(&update(vec![level(100.0, 1.0)], vec![], true));
+        assert!(book.metrics().is_none());
+    }
+}
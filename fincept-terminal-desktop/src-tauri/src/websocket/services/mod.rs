@@ -4,6 +4,7 @@ pub mod paper_trading;
 pub mod arbitrage;
 pub mod portfolio;
 pub mod monitoring;
+pub mod book_metrics;
 
 pub use paper_trading::PaperTradingService;
 pub use arbitrage::ArbitrageService;
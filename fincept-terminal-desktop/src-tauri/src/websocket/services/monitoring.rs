@@ -2,15 +2,42 @@
 //
 // Monitors WebSocket data streams against user-defined conditions
 // and triggers alerts when conditions are met.
+//
+// Alerts are persisted, emitted to the frontend (`monitor_alert`) and, when
+// enabled per condition (`notify`) or globally (`monitor_notifications_enabled`
+// setting), raised as native OS notifications so minimized users see them.
+//
+// Condition groups combine several field checks on one symbol with AND/OR
+// logic ("price > 100 AND volume > 1M") and raise a single alert for the group.
+// Their member checks are stored in `monitor_conditions` with a `group_id`, so
+// grouped alerts can still reference a condition row; standalone conditions are
+// the rows without one.
+//
+// `mid_price` and `orderbook_imbalance` are derived from the orderbook stream
+// (see `book_metrics`). Conditions on them are evaluated when the book updates
+// and skipped while the book is empty or one-sided; groups mixing book and
+// ticker fields are evaluated on ticks using the latest book metrics.
 
+use super::book_metrics::{BookMetrics, LocalBook};
+use crate::database::retry;
+use crate::utils::symbol::Symbol;
 use crate::websocket::types::*;
 use anyhow::Result;
 use rusqlite::{params, Connection};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::time::{SystemTime, UNIX_EPOCH};
 use tokio::sync::RwLock;
 use tauri::Emitter;
+use tauri_plugin_notification::NotificationExt;
+
+/// Settings key for the global "notify on every alert" switch
+pub const NOTIFICATIONS_SETTING_KEY: &str = "monitor_notifications_enabled";
+
+/// Minimum gap between native notifications for the same condition
+const NOTIFY_COOLDOWN_MS: u64 = 60_000;
 
 // ============================================================================
 // TYPES
@@ -20,12 +47,170 @@ use tauri::Emitter;
 pub struct MonitorCondition {
     pub id: Option<i64>,
     pub provider: String,
-    pub symbol: String,
+    pub symbol: Symbol,
     pub field: MonitorField,
     pub operator: MonitorOperator,
     pub value: f64,
     pub value2: Option<f64>, // For 'between' operator
     pub enabled: bool,
+    /// Raise a native notification when this condition triggers
+    #[serde(default)]
+    pub notify: bool,
+}
+
+impl MonitorCondition {
+    /// The field's value when the condition holds for `view`
+    pub fn evaluate(&self, view: &MarketView<'_>) -> Option<f64> {
+        view.value(&self.field)
+            .filter(|v| self.operator.matches(*v, self.value, self.value2))
+    }
+}
+
+/// How a condition fares against recent data (see `test_condition`)
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ConditionTest {
+    /// The condition holds for the latest tick, or the current book for book fields
+    pub holds_now: bool,
+    /// Field value in that latest sample; None when the feed doesn't provide it
+    pub current_value: Option<f64>,
+    /// Ticks evaluated (1 for book fields while a book is held, else 0)
+    pub samples: usize,
+    /// Samples the condition held for
+    pub matched: usize,
+    /// Timestamp of the latest tick the condition held for
+    pub last_matched_at: Option<u64>,
+}
+
+/// Evaluate `condition` on `ticks` (oldest first) and the symbol's current `book`
+///
+/// Ticker fields are checked on every tick, each seen with the current book
+/// metrics as the live loop would. Book fields have no history, so only the
+/// current book is checked. Nothing is stored or emitted.
+pub fn test_condition(condition: &MonitorCondition, ticks: &[TickerData], book: Option<BookMetrics>) -> ConditionTest {
+    if condition.field.update_kind() == UpdateKind::Book {
+        let view = MarketView { ticker: None, book };
+        let holds_now = condition.evaluate(&view).is_some();
+        return ConditionTest {
+            holds_now,
+            current_value: view.value(&condition.field),
+            samples: usize::from(book.is_some()),
+            matched: usize::from(holds_now),
+            last_matched_at: None,
+        };
+    }
+
+    let mut test = ConditionTest {
+        holds_now: false,
+        current_value: None,
+        samples: ticks.len(),
+        matched: 0,
+        last_matched_at: None,
+    };
+    for tick in ticks {
+        let view = MarketView { ticker: Some(tick), book };
+        test.current_value = view.value(&condition.field);
+        test.holds_now = condition.evaluate(&view).is_some();
+        if test.holds_now {
+            test.matched += 1;
+            test.last_matched_at = Some(tick.timestamp);
+        }
+    }
+    test
+}
+
+/// How a condition group combines its members
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum GroupLogic {
+    And,
+    Or,
+}
+
+impl GroupLogic {
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::And => "and",
+            Self::Or => "or",
+        }
+    }
+
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "and" => Some(Self::And),
+            "or" => Some(Self::Or),
+            _ => None,
+        }
+    }
+}
+
+/// One field check inside a condition group (provider and symbol come from the group)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GroupCondition {
+    #[serde(default)]
+    pub id: Option<i64>,
+    pub field: MonitorField,
+    pub operator: MonitorOperator,
+    pub value: f64,
+    pub value2: Option<f64>,
+}
+
+/// Conditions on one symbol evaluated together, raising one alert
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MonitorConditionGroup {
+    pub id: Option<i64>,
+    #[serde(default)]
+    pub name: String,
+    pub provider: String,
+    pub symbol: Symbol,
+    pub logic: GroupLogic,
+    pub conditions: Vec<GroupCondition>,
+    pub enabled: bool,
+    #[serde(default)]
+    pub notify: bool,
+}
+
+impl MonitorConditionGroup {
+    /// Book-only groups run on book updates, everything else on ticks
+    pub fn update_kind(&self) -> UpdateKind {
+        if !self.conditions.is_empty() && self.conditions.iter().all(|c| c.field.update_kind() == UpdateKind::Book) {
+            UpdateKind::Book
+        } else {
+            UpdateKind::Ticker
+        }
+    }
+
+    /// Members that hold for `view`, or `None` when the group as a whole does not
+    ///
+    /// A member whose field is unavailable (e.g. no volume in the tick) counts as not met.
+    pub fn evaluate(&self, view: &MarketView<'_>) -> Option<Vec<ConditionMatch>> {
+        let mut matched = Vec::new();
+        for condition in &self.conditions {
+            let hit = view.value(&condition.field)
+                .filter(|v| condition.operator.matches(*v, condition.value, condition.value2));
+            match hit {
+                Some(value) => matched.push(ConditionMatch {
+                    condition_id: condition.id.unwrap_or_default(),
+                    field: condition.field.clone(),
+                    value,
+                }),
+                None if self.logic == GroupLogic::And => return None,
+                None => {}
+            }
+        }
+        if matched.is_empty() {
+            None
+        } else {
+            Some(matched)
+        }
+    }
+}
+
+/// A group member that held when its group triggered
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConditionMatch {
+    pub condition_id: i64,
+    pub field: MonitorField,
+    pub value: f64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -35,6 +220,10 @@ pub enum MonitorField {
     Volume,
     ChangePercent,
     Spread,
+    /// (best bid + best ask) / 2 from the order book
+    MidPrice,
+    /// (bid qty - ask qty) / (bid qty + ask qty) over the top book levels, in [-1, 1]
+    OrderbookImbalance,
 }
 
 impl MonitorField {
@@ -44,6 +233,8 @@ impl MonitorField {
             Self::Volume => "volume",
             Self::ChangePercent => "change_percent",
             Self::Spread => "spread",
+            Self::MidPrice => "mid_price",
+            Self::OrderbookImbalance => "orderbook_imbalance",
         }
     }
 
@@ -53,9 +244,26 @@ impl MonitorField {
             "volume" => Some(Self::Volume),
             "change_percent" => Some(Self::ChangePercent),
             "spread" => Some(Self::Spread),
+            "mid_price" => Some(Self::MidPrice),
+            "orderbook_imbalance" => Some(Self::OrderbookImbalance),
             _ => None,
         }
     }
+
+    /// Which stream updates this field
+    pub fn update_kind(&self) -> UpdateKind {
+        match self {
+            Self::MidPrice | Self::OrderbookImbalance => UpdateKind::Book,
+            _ => UpdateKind::Ticker,
+        }
+    }
+}
+
+/// Market update that triggers an evaluation
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UpdateKind {
+    Ticker,
+    Book,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -97,17 +305,81 @@ impl MonitorOperator {
             _ => None,
         }
     }
+
+    /// Whether `value` satisfies this operator against `target` (and `target2` for between)
+    pub fn matches(&self, value: f64, target: f64, target2: Option<f64>) -> bool {
+        match self {
+            Self::GreaterThan => value > target,
+            Self::LessThan => value < target,
+            Self::GreaterThanOrEqual => value >= target,
+            Self::LessThanOrEqual => value <= target,
+            Self::Equal => (value - target).abs() < f64::EPSILON,
+            Self::Between => target2.is_some_and(|upper| value >= target && value <= upper),
+        }
+    }
+}
+
+/// Field values available for one symbol when an update arrives
+pub struct MarketView<'a> {
+    pub ticker: Option<&'a TickerData>,
+    pub book: Option<BookMetrics>,
+}
+
+impl MarketView<'_> {
+    /// Value of `field`, if the provider sent it (or the book can derive it)
+    pub fn value(&self, field: &MonitorField) -> Option<f64> {
+        match field {
+            MonitorField::Price => self.ticker.map(|t| t.price),
+            MonitorField::Volume => self.ticker?.volume,
+            MonitorField::ChangePercent => self.ticker?.change_percent,
+            MonitorField::Spread => {
+                let ticker = self.ticker?;
+                Some(ticker.ask? - ticker.bid?)
+            }
+            MonitorField::MidPrice => self.book.map(|b| b.mid_price),
+            MonitorField::OrderbookImbalance => self.book.map(|b| b.imbalance),
+        }
+    }
+}
+
+/// Payload emitted alongside a native notification so the frontend can
+/// navigate to the monitoring panel for the alert
+#[derive(Debug, Clone, Serialize)]
+pub struct MonitorNotification {
+    pub panel: String,
+    pub alert: MonitorAlert,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MonitorAlert {
     pub id: Option<i64>,
+    /// For group alerts, the first member that held
     pub condition_id: i64,
     pub provider: String,
     pub symbol: String,
     pub field: MonitorField,
     pub triggered_value: f64,
     pub triggered_at: u64,
+    #[serde(default)]
+    pub group_id: Option<i64>,
+    /// Every member that held when a group triggered (empty for single conditions)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub matched: Vec<ConditionMatch>,
+    #[serde(default)]
+    pub acknowledged: bool,
+    /// Unix millis when the alert was acknowledged
+    #[serde(default)]
+    pub acknowledged_at: Option<u64>,
+}
+
+impl MonitorAlert {
+    /// Key for the per-source notification cooldown
+    fn source_key(&self) -> String {
+        match self.group_id {
+            Some(group_id) => format!("group:{}", group_id),
+            None => format!("condition:{}", self.condition_id),
+        }
+    }
 }
 
 // ============================================================================
@@ -116,52 +388,73 @@ pub struct MonitorAlert {
 
 pub struct MonitoringService {
     conditions: Arc<RwLock<Vec<MonitorCondition>>>,
+    groups: Arc<RwLock<Vec<MonitorConditionGroup>>>,
     db_path: String,
     app_handle: Option<tauri::AppHandle>,
+    notifications_enabled: Arc<AtomicBool>,
+    last_notified: Arc<parking_lot::Mutex<HashMap<String, u64>>>,
+    /// (provider, symbol) -> latest order book
+    books: Arc<parking_lot::Mutex<HashMap<(String, String), LocalBook>>>,
+    /// Stream tasks started by `start_monitoring`
+    tasks: Arc<parking_lot::Mutex<Vec<tokio::task::JoinHandle<()>>>>,
 }
 
 impl MonitoringService {
     pub fn new(db_path: String) -> Self {
         Self {
             conditions: Arc::new(RwLock::new(Vec::new())),
+            groups: Arc::new(RwLock::new(Vec::new())),
             db_path,
             app_handle: None,
+            notifications_enabled: Arc::new(AtomicBool::new(false)),
+            last_notified: Arc::new(parking_lot::Mutex::new(HashMap::new())),
+            books: Arc::new(parking_lot::Mutex::new(HashMap::new())),
+            tasks: Arc::new(parking_lot::Mutex::new(Vec::new())),
         }
     }
 
+    /// Another handle on the same state, for the stream tasks
+    fn shared(&self) -> Self {
+        Self {
+            conditions: self.conditions.clone(),
+            groups: self.groups.clone(),
+            db_path: self.db_path.clone(),
+            app_handle: self.app_handle.clone(),
+            notifications_enabled: self.notifications_enabled.clone(),
+            last_notified: self.last_notified.clone(),
+            books: self.books.clone(),
+            tasks: self.tasks.clone(),
+        }
+    }
+
+    /// Enable or disable native notifications for all conditions
+    pub fn set_notifications_enabled(&self, enabled: bool) {
+        self.notifications_enabled.store(enabled, Ordering::Relaxed);
+    }
+
+    /// Whether native notifications are enabled globally
+    pub fn notifications_enabled(&self) -> bool {
+        self.notifications_enabled.load(Ordering::Relaxed)
+    }
+
     /// Set app handle for emitting alerts to frontend
     pub fn set_app_handle(&mut self, app_handle: tauri::AppHandle) {
         self.app_handle = Some(app_handle);
     }
 
-    /// Start monitoring ticker stream from router
+    /// Start monitoring the ticker and orderbook streams from router
     pub fn start_monitoring(
         &self,
         mut ticker_rx: tokio::sync::broadcast::Receiver<TickerData>,
+        mut orderbook_rx: tokio::sync::broadcast::Receiver<OrderBookData>,
     ) {
-        let conditions = self.conditions.clone();
-        let db_path = self.db_path.clone();
-        let app_handle = self.app_handle.clone();
-
-        tokio::spawn(async move {
+        let service = self.shared();
+        let ticker_task = tokio::spawn(async move {
             loop {
                 match ticker_rx.recv().await {
                     Ok(ticker) => {
-                        // Create temporary service to check conditions
-                        let service = MonitoringService {
-                            conditions: conditions.clone(),
-                            db_path: db_path.clone(),
-                            app_handle: app_handle.clone(),
-                        };
-
                         let alerts = service.check_ticker(&ticker).await;
-
-                        // Emit alerts to frontend
-                        if !alerts.is_empty() && app_handle.is_some() {
-                            for alert in alerts {
-                                let _ = app_handle.as_ref().unwrap().emit("monitor_alert", &alert);
-                            }
-                        }
+                        service.dispatch_alerts(alerts).await;
                     }
                     Err(tokio::sync::broadcast::error::RecvError::Lagged(_)) => {
                         // Channel lagged, continue
@@ -174,6 +467,106 @@ impl MonitoringService {
                 }
             }
         });
+
+        let service = self.shared();
+        let orderbook_task = tokio::spawn(async move {
+            loop {
+                match orderbook_rx.recv().await {
+                    Ok(book) => {
+                        let alerts = service.check_orderbook(&book).await;
+                        service.dispatch_alerts(alerts).await;
+                    }
+                    Err(tokio::sync::broadcast::error::RecvError::Lagged(_)) => {
+                        // Missed deltas leave the book stale until the next snapshot
+                        continue;
+                    }
+                    Err(_) => {
+                        break;
+                    }
+                }
+            }
+        });
+        self.tasks.lock().extend([ticker_task, orderbook_task]);
+    }
+
+    /// Stop the stream tasks started by `start_monitoring`
+    pub fn stop_monitoring(&self) {
+        for task in self.tasks.lock().drain(..) {
+            task.abort();
+        }
+    }
+
+    /// Emit alerts to the frontend and raise notifications where enabled
+    async fn dispatch_alerts(&self, alerts: Vec<MonitorAlert>) {
+        if let Some(app) = self.app_handle.as_ref() {
+            for alert in alerts {
+                let _ = app.emit("monitor_alert", &alert);
+                if self.should_notify(&alert).await {
+                    self.notify(app, &alert);
+                }
+            }
+        }
+    }
+
+    /// Decide whether an alert should raise a native notification
+    ///
+    /// Requires the condition's (or group's) `notify` flag or the global setting,
+    /// and rate-limits each condition or group to one notification per cooldown window.
+    async fn should_notify(&self, alert: &MonitorAlert) -> bool {
+        let opted_in = self.notifications_enabled()
+            || match alert.group_id {
+                Some(group_id) => self.groups.read().await
+                    .iter()
+                    .any(|g| g.id == Some(group_id) && g.notify),
+                None => self.conditions.read().await
+                    .iter()
+                    .any(|c| c.id == Some(alert.condition_id) && c.notify),
+            };
+        if !opted_in {
+            return false;
+        }
+
+        let key = alert.source_key();
+        let mut last_notified = self.last_notified.lock();
+        match last_notified.get(&key) {
+            Some(last) if alert.triggered_at.saturating_sub(*last) < NOTIFY_COOLDOWN_MS => false,
+            _ => {
+                last_notified.insert(key, alert.triggered_at);
+                true
+            }
+        }
+    }
+
+    /// Raise a native notification for an alert
+    ///
+    /// Desktop notification clicks are not reported back by the plugin, so a
+    /// `monitor_alert_notification` event carries the target panel; the frontend
+    /// navigates there when the window is next focused.
+    fn notify(&self, app: &tauri::AppHandle, alert: &MonitorAlert) {
+        let body = if alert.matched.is_empty() {
+            format!("{} = {}", alert.field.as_str(), alert.triggered_value)
+        } else {
+            alert.matched
+                .iter()
+                .map(|m| format!("{} = {}", m.field.as_str(), m.value))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        let result = app.notification()
+            .builder()
+            .title(format!("{} alert: {}", alert.provider, alert.symbol))
+            .body(body)
+            .show();
+
+        if let Err(e) = result {
+            tracing::warn!(error = %e, condition_id = alert.condition_id, "Failed to show alert notification");
+            return;
+        }
+
+        let _ = app.emit("monitor_alert_notification", MonitorNotification {
+            panel: "monitoring".to_string(),
+            alert: alert.clone(),
+        });
     }
 
     /// Load all enabled conditions from database
@@ -181,13 +574,13 @@ impl MonitoringService {
         let db_path = self.db_path.clone();
 
         // Use spawn_blocking for SQLite operations
-        let conditions = tokio::task::spawn_blocking(move || -> Result<Vec<MonitorCondition>> {
+        let (conditions, groups, notifications_enabled) = tokio::task::spawn_blocking(move || -> Result<(Vec<MonitorCondition>, Vec<MonitorConditionGroup>, bool)> {
             let conn = Connection::open(&db_path)?;
 
             let mut stmt = conn.prepare(
-                "SELECT id, provider, symbol, field, operator, value, value2, enabled
+                "SELECT id, provider, symbol, field, operator, value, value2, enabled, notify
                  FROM monitor_conditions
-                 WHERE enabled = 1"
+                 WHERE enabled = 1 AND group_id IS NULL"
             )?;
 
             let conditions = stmt
@@ -201,106 +594,184 @@ impl MonitoringService {
                         value: row.get(5)?,
                         value2: row.get(6)?,
                         enabled: row.get::<_, i32>(7)? == 1,
+                        notify: row.get::<_, Option<i32>>(8)?.unwrap_or(0) == 1,
                     })
                 })?
                 .collect::<std::result::Result<Vec<_>, _>>()?;
 
-            Ok(conditions)
+            let groups = load_condition_groups(&conn, true)?;
+
+            let notifications_enabled = conn
+                .query_row(
+                    "SELECT setting_value FROM settings WHERE profile = ?1 AND setting_key = ?2",
+                    params![crate::database::profiles::active_profile(&conn)?, NOTIFICATIONS_SETTING_KEY],
+                    |row| row.get::<_, String>(0),
+                )
+                .map(|v| v == "true")
+                .unwrap_or(false);
+
+            Ok((conditions, groups, notifications_enabled))
         })
         .await
         .map_err(|e| anyhow::anyhow!("Join error: {}", e))??;
 
         *self.conditions.write().await = conditions;
+        *self.groups.write().await = groups;
+        self.set_notifications_enabled(notifications_enabled);
         Ok(())
     }
 
-    /// Check ticker data against all conditions
+    /// Metrics of the book held for `provider` / `symbol`, if it has both sides
+    pub fn book_metrics(&self, provider: &str, symbol: &Symbol) -> Option<BookMetrics> {
+        self.books.lock()
+            .iter()
+            .find(|((book_provider, book_symbol), _)| book_provider == provider && symbol.matches(book_symbol))
+            .and_then(|(_, book)| book.metrics())
+    }
+
+    /// Check ticker data against all conditions; replayed ticks never alert
     pub async fn check_ticker(&self, ticker: &TickerData) -> Vec<MonitorAlert> {
-        let conditions = self.conditions.read().await;
+        if ticker.replayed {
+            return Vec::new();
+        }
+        let book = self.books.lock()
+            .get(&(ticker.provider.clone(), ticker.symbol.clone()))
+            .and_then(|b| b.metrics());
+        let view = MarketView {
+            ticker: Some(ticker),
+            book,
+        };
+        self.check_update(&ticker.provider, &ticker.symbol, &view, UpdateKind::Ticker).await
+    }
+
+    /// Apply an orderbook update and check book-derived conditions
+    ///
+    /// Nothing is evaluated while the book is empty or one-sided.
+    pub async fn check_orderbook(&self, update: &OrderBookData) -> Vec<MonitorAlert> {
+        let book = {
+            let mut books = self.books.lock();
+            let book = books
+                .entry((update.provider.clone(), update.symbol.clone()))
+                .or_default();
+            book.apply(update);
+            book.metrics()
+        };
+        if book.is_none() {
+            return Vec::new();
+        }
+
+        let view = MarketView { ticker: None, book };
+        self.check_update(&update.provider, &update.symbol, &view, UpdateKind::Book).await
+    }
+
+    /// Check conditions and groups on (provider, symbol) that `kind` updates
+    async fn check_update(
+        &self,
+        provider: &str,
+        symbol: &str,
+        view: &MarketView<'_>,
+        kind: UpdateKind,
+    ) -> Vec<MonitorAlert> {
         let mut alerts = Vec::new();
 
-        for condition in conditions.iter() {
-            // Filter by provider and symbol
-            if condition.provider != ticker.provider || condition.symbol != ticker.symbol {
+        for condition in self.conditions.read().await.iter() {
+            // Filter by provider, symbol and the stream that feeds the field
+            if condition.provider != provider
+                || !condition.symbol.matches(symbol)
+                || condition.field.update_kind() != kind
+            {
                 continue;
             }
 
-            // Extract field value
-            let field_value = match condition.field {
-                MonitorField::Price => Some(ticker.price),
-                MonitorField::Volume => ticker.volume,
-                MonitorField::ChangePercent => ticker.change_percent,
-                MonitorField::Spread => {
-                    if let (Some(bid), Some(ask)) = (ticker.bid, ticker.ask) {
-                        Some(ask - bid)
-                    } else {
-                        None
-                    }
-                }
-            };
+            if let Some(value) = condition.evaluate(view) {
+                // Condition matched - create alert
+                alerts.push(MonitorAlert {
+                    id: None,
+                    condition_id: condition.id.unwrap(),
+                    provider: provider.to_string(),
+                    symbol: symbol.to_string(),
+                    field: condition.field.clone(),
+                    triggered_value: value,
+                    triggered_at: Self::now(),
+                    group_id: None,
+                    matched: Vec::new(),
+                    acknowledged: false,
+                    acknowledged_at: None,
+                });
+            }
+        }
 
-            if let Some(value) = field_value {
-                if self.check_condition(value, condition) {
-                    // Condition matched - create alert
-                    alerts.push(MonitorAlert {
-                        id: None,
-                        condition_id: condition.id.unwrap(),
-                        provider: ticker.provider.clone(),
-                        symbol: ticker.symbol.clone(),
-                        field: condition.field.clone(),
-                        triggered_value: value,
-                        triggered_at: Self::now(),
-                    });
-                }
+        for group in self.groups.read().await.iter() {
+            if group.provider != provider || !group.symbol.matches(symbol) || group.update_kind() != kind {
+                continue;
+            }
+            if let Some(matched) = group.evaluate(view) {
+                alerts.push(MonitorAlert {
+                    id: None,
+                    condition_id: matched[0].condition_id,
+                    provider: provider.to_string(),
+                    symbol: symbol.to_string(),
+                    field: matched[0].field.clone(),
+                    triggered_value: matched[0].value,
+                    triggered_at: Self::now(),
+                    group_id: group.id,
+                    matched,
+                    acknowledged: false,
+                    acknowledged_at: None,
+                });
             }
         }
 
         // Save alerts to database
         if !alerts.is_empty() {
-            let _ = self.save_alerts(&alerts).await;
+            if let Err(e) = self.save_alerts(&alerts).await {
+                tracing::warn!(error = %e, count = alerts.len(), "Failed to save monitor alerts");
+            }
         }
 
         alerts
     }
 
-    /// Check if a value matches a condition
-    fn check_condition(&self, value: f64, condition: &MonitorCondition) -> bool {
-        match condition.operator {
-            MonitorOperator::GreaterThan => value > condition.value,
-            MonitorOperator::LessThan => value < condition.value,
-            MonitorOperator::GreaterThanOrEqual => value >= condition.value,
-            MonitorOperator::LessThanOrEqual => value <= condition.value,
-            MonitorOperator::Equal => (value - condition.value).abs() < f64::EPSILON,
-            MonitorOperator::Between => {
-                if let Some(value2) = condition.value2 {
-                    value >= condition.value && value <= value2
-                } else {
-                    false
-                }
-            }
-        }
-    }
-
     /// Save alerts to database
+    ///
+    /// One transaction, retried as a whole while UI/RPC writes hold the lock. Runs
+    /// on the blocking pool, since the busy timeout and retries sleep the thread.
     async fn save_alerts(&self, alerts: &[MonitorAlert]) -> Result<()> {
-        let conn = Connection::open(&self.db_path)?;
-
-        for alert in alerts {
-            conn.execute(
-                "INSERT INTO monitor_alerts (condition_id, provider, symbol, field, triggered_value, triggered_at)
-                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
-                params![
-                    alert.condition_id,
-                    &alert.provider,
-                    &alert.symbol,
-                    alert.field.as_str(),
-                    alert.triggered_value,
-                    alert.triggered_at as i64,
-                ],
-            )?;
-        }
+        let db_path = self.db_path.clone();
+        let alerts = alerts.to_vec();
 
-        Ok(())
+        tokio::task::spawn_blocking(move || -> Result<()> {
+            let mut conn = Connection::open(&db_path)?;
+            conn.busy_timeout(retry::BUSY_TIMEOUT)?;
+
+            retry::with_busy_retry(|| {
+                let tx = conn.transaction()?;
+                for alert in &alerts {
+                    let details = if alert.matched.is_empty() {
+                        None
+                    } else {
+                        Some(serde_json::to_string(&alert.matched)?)
+                    };
+                    tx.execute(
+                        "INSERT INTO monitor_alerts (condition_id, provider, symbol, field, triggered_value, triggered_at, group_id, details)
+                         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                        params![
+                            alert.condition_id,
+                            &alert.provider,
+                            &alert.symbol,
+                            alert.field.as_str(),
+                            alert.triggered_value,
+                            alert.triggered_at as i64,
+                            alert.group_id,
+                            details,
+                        ],
+                    )?;
+                }
+                tx.commit()?;
+                Ok(())
+            })
+        })
+        .await?
     }
 
     fn now() -> u64 {
@@ -316,3 +787,560 @@ impl Default for MonitoringService {
         Self::new("fincept_terminal.db".to_string())
     }
 }
+
+// ============================================================================
+// CONDITION GROUP STORAGE
+// ============================================================================
+
+/// Insert a group and its member conditions, returning the group id
+pub fn insert_condition_group(conn: &Connection, group: &MonitorConditionGroup) -> Result<i64> {
+    if group.conditions.is_empty() {
+        anyhow::bail!("A condition group needs at least one condition");
+    }
+
+    let tx = conn.unchecked_transaction()?;
+    tx.execute(
+        "INSERT INTO monitor_condition_groups (name, provider, symbol, logic, enabled, notify)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![
+            &group.name,
+            &group.provider,
+            &group.symbol,
+            group.logic.as_str(),
+            if group.enabled { 1 } else { 0 },
+            if group.notify { 1 } else { 0 },
+        ],
+    )?;
+    let group_id = tx.last_insert_rowid();
+
+    for condition in &group.conditions {
+        tx.execute(
+            "INSERT INTO monitor_conditions (provider, symbol, field, operator, value, value2, enabled, notify, group_id)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, 1, 0, ?7)",
+            params![
+                &group.provider,
+                &group.symbol,
+                condition.field.as_str(),
+                condition.operator.as_str(),
+                condition.value,
+                condition.value2,
+                group_id,
+            ],
+        )?;
+    }
+    tx.commit()?;
+
+    Ok(group_id)
+}
+
+/// Load condition groups with their members, newest first
+pub fn load_condition_groups(conn: &Connection, enabled_only: bool) -> Result<Vec<MonitorConditionGroup>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, name, provider, symbol, logic, enabled, notify
+         FROM monitor_condition_groups
+         WHERE enabled = 1 OR ?1 = 0
+         ORDER BY created_at DESC, id DESC"
+    )?;
+    let rows = stmt
+        .query_map(params![enabled_only], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, Option<String>>(1)?.unwrap_or_default(),
+                row.get::<_, String>(2)?,
+                row.get::<_, Symbol>(3)?,
+                row.get::<_, String>(4)?,
+                row.get::<_, i32>(5)? == 1,
+                row.get::<_, i32>(6)? == 1,
+            ))
+        })?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+
+    let mut member_stmt = conn.prepare(
+        "SELECT id, field, operator, value, value2
+         FROM monitor_conditions
+         WHERE group_id = ?1
+         ORDER BY id"
+    )?;
+
+    let mut groups = Vec::with_capacity(rows.len());
+    for (id, name, provider, symbol, logic, enabled, notify) in rows {
+        let members = member_stmt
+            .query_map(params![id], |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                    row.get::<_, f64>(3)?,
+                    row.get::<_, Option<f64>>(4)?,
+                ))
+            })?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        let mut conditions = Vec::with_capacity(members.len());
+        for (condition_id, field, operator, value, value2) in members {
+            conditions.push(GroupCondition {
+                id: Some(condition_id),
+                field: MonitorField::from_str(&field)
+                    .ok_or_else(|| anyhow::anyhow!("Invalid field value: {}", field))?,
+                operator: MonitorOperator::from_str(&operator)
+                    .ok_or_else(|| anyhow::anyhow!("Invalid operator value: {}", operator))?,
+                value,
+                value2,
+            });
+        }
+
+        groups.push(MonitorConditionGroup {
+            id: Some(id),
+            name,
+            provider,
+            symbol,
+            logic: GroupLogic::from_str(&logic)
+                .ok_or_else(|| anyhow::anyhow!("Invalid group logic: {}", logic))?,
+            conditions,
+            enabled,
+            notify,
+        });
+    }
+
+    Ok(groups)
+}
+
+/// Delete a group and its member conditions, returning whether it existed
+pub fn delete_condition_group(conn: &Connection, group_id: i64) -> Result<bool> {
+    let tx = conn.unchecked_transaction()?;
+    tx.execute("DELETE FROM monitor_conditions WHERE group_id = ?1", params![group_id])?;
+    let deleted = tx.execute("DELETE FROM monitor_condition_groups WHERE id = ?1", params![group_id])?;
+    tx.commit()?;
+    Ok(deleted > 0)
+}
+
+// ============================================================================
+// ALERT ACKNOWLEDGEMENT
+// ============================================================================
+
+/// Acknowledge one alert, or every unacknowledged alert when `alert_id` is `None`
+///
+/// Returns how many alerts changed; acknowledging twice keeps the first timestamp.
+pub fn acknowledge_alerts(conn: &Connection, alert_id: Option<i64>) -> Result<usize> {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as i64;
+    let changed = conn.execute(
+        "UPDATE monitor_alerts SET acknowledged = 1, acknowledged_at = ?1
+         WHERE acknowledged = 0 AND (?2 IS NULL OR id = ?2)",
+        params![now, alert_id],
+    )?;
+    Ok(changed)
+}
+
+// ============================================================================
+// ALERT HISTORY
+// ============================================================================
+
+pub const DEFAULT_ALERT_LIMIT: i64 = 50;
+pub const MAX_ALERT_LIMIT: i64 = 1000;
+
+/// Filters for `query_alerts`; every field is optional
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct AlertQuery {
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+    pub acknowledged: Option<bool>,
+    /// Matched case-insensitively
+    pub provider: Option<String>,
+    pub symbol: Option<String>,
+    pub field: Option<String>,
+    /// Unix millis, inclusive
+    pub since: Option<i64>,
+    /// Unix millis, exclusive
+    pub until: Option<i64>,
+}
+
+/// One page of alerts, newest first, and how many match in total
+#[derive(Debug, Clone, Serialize)]
+pub struct AlertPage {
+    pub alerts: Vec<MonitorAlert>,
+    pub total: i64,
+    pub limit: i64,
+    pub offset: i64,
+}
+
+/// Alerts matching `query`, newest first
+pub fn query_alerts(conn: &Connection, query: &AlertQuery) -> Result<AlertPage> {
+    let limit = query.limit.unwrap_or(DEFAULT_ALERT_LIMIT).clamp(1, MAX_ALERT_LIMIT);
+    let offset = query.offset.unwrap_or(0).max(0);
+
+    let mut clauses: Vec<&str> = Vec::new();
+    let mut args: Vec<rusqlite::types::Value> = Vec::new();
+    let text = |value: &Option<String>| {
+        value.as_deref().map(str::trim).filter(|v| !v.is_empty()).map(str::to_string)
+    };
+
+    if let Some(acknowledged) = query.acknowledged {
+        clauses.push("COALESCE(acknowledged, 0) = ?");
+        args.push((acknowledged as i64).into());
+    }
+    if let Some(provider) = text(&query.provider) {
+        clauses.push("provider = ? COLLATE NOCASE");
+        args.push(provider.into());
+    }
+    if let Some(symbol) = text(&query.symbol) {
+        clauses.push("symbol = ? COLLATE NOCASE");
+        args.push(symbol.into());
+    }
+    if let Some(field) = text(&query.field) {
+        let field = MonitorField::from_str(&field)
+            .ok_or_else(|| anyhow::anyhow!("Invalid field value: {}", field))?;
+        clauses.push("field = ?");
+        args.push(field.as_str().to_string().into());
+    }
+    if let Some(since) = query.since {
+        clauses.push("triggered_at >= ?");
+        args.push(since.into());
+    }
+    if let Some(until) = query.until {
+        clauses.push("triggered_at < ?");
+        args.push(until.into());
+    }
+    if let (Some(since), Some(until)) = (query.since, query.until) {
+        if since >= until {
+            anyhow::bail!("'since' must be before 'until'");
+        }
+    }
+
+    let where_sql = if clauses.is_empty() {
+        String::new()
+    } else {
+        format!("WHERE {}", clauses.join(" AND "))
+    };
+
+    let total: i64 = conn.query_row(
+        &format!("SELECT COUNT(*) FROM monitor_alerts {}", where_sql),
+        rusqlite::params_from_iter(args.iter()),
+        |row| row.get(0),
+    )?;
+
+    let mut stmt = conn.prepare(&format!(
+        "SELECT id, condition_id, provider, symbol, field, triggered_value, triggered_at, group_id, details,
+                acknowledged, acknowledged_at
+         FROM monitor_alerts
+         {}
+         ORDER BY triggered_at DESC, id DESC
+         LIMIT ? OFFSET ?",
+        where_sql
+    ))?;
+    args.push(limit.into());
+    args.push(offset.into());
+
+    let alerts = stmt
+        .query_map(rusqlite::params_from_iter(args.iter()), |row| {
+            let field: String = row.get(4)?;
+            let field = MonitorField::from_str(&field).ok_or_else(|| {
+                rusqlite::Error::FromSqlConversionFailure(
+                    4,
+                    rusqlite::types::Type::Text,
+                    format!("Invalid field value: {}", field).into(),
+                )
+            })?;
+            Ok(MonitorAlert {
+                id: Some(row.get(0)?),
+                condition_id: row.get(1)?,
+                provider: row.get(2)?,
+                symbol: row.get(3)?,
+                field,
+                triggered_value: row.get(5)?,
+                triggered_at: row.get::<_, i64>(6)? as u64,
+                group_id: row.get(7)?,
+                matched: row.get::<_, Option<String>>(8)?
+                    .and_then(|details| serde_json::from_str(&details).ok())
+                    .unwrap_or_default(),
+                acknowledged: row.get::<_, Option<i32>>(9)?.unwrap_or(0) == 1,
+                acknowledged_at: row.get::<_, Option<i64>>(10)?.map(|t| t as u64),
+            })
+        })?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+
+    Ok(AlertPage { alerts, total, limit, offset })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tick(price: f64, volume: Option<f64>) -> TickerData {
+        TickerData {
+            provider: "binance".to_string(),
+            symbol: "BTCUSDT".to_string(),
+            price,
+            bid: None,
+            ask: None,
+            bid_size: None,
+            ask_size: None,
+            volume,
+            high: None,
+            low: None,
+            open: None,
+            close: None,
+            change: None,
+            change_percent: None,
+            timestamp: 0,
+            replayed: false,
+        }
+    }
+
+    fn group(logic: GroupLogic) -> MonitorConditionGroup {
+        MonitorConditionGroup {
+            id: Some(1),
+            name: "Breakout".to_string(),
+            provider: "binance".to_string(),
+            symbol: Symbol::new("BTCUSDT").unwrap(),
+            logic,
+            conditions: vec![
+                GroupCondition {
+                    id: Some(10),
+                    field: MonitorField::Price,
+                    operator: MonitorOperator::GreaterThan,
+                    value: 100.0,
+                    value2: None,
+                },
+                GroupCondition {
+                    id: Some(11),
+                    field: MonitorField::Volume,
+                    operator: MonitorOperator::GreaterThan,
+                    value: 1_000_000.0,
+                    value2: None,
+                },
+            ],
+            enabled: true,
+            notify: false,
+        }
+    }
+
+    fn view(ticker: &TickerData) -> MarketView<'_> {
+        MarketView {
+            ticker: Some(ticker),
+            book: None,
+        }
+    }
+
+    #[test]
+    fn test_and_group_needs_every_member() {
+        let group = group(GroupLogic::And);
+        assert!(group.evaluate(&view(&tick(101.0, Some(500.0)))).is_none());
+        assert!(group.evaluate(&view(&tick(101.0, None))).is_none());
+
+        let matched = group.evaluate(&view(&tick(101.0, Some(2_000_000.0)))).unwrap();
+        assert_eq!(matched.len(), 2);
+        assert_eq!(matched[0].condition_id, 10);
+    }
+
+    #[test]
+    fn test_or_group_needs_any_member() {
+        let group = group(GroupLogic::Or);
+        assert!(group.evaluate(&view(&tick(99.0, Some(500.0)))).is_none());
+
+        let matched = group.evaluate(&view(&tick(99.0, Some(2_000_000.0)))).unwrap();
+        assert_eq!(matched.len(), 1);
+        assert_eq!(matched[0].field, MonitorField::Volume);
+    }
+
+    #[test]
+    fn test_condition_test_counts_recent_ticks() {
+        let condition = MonitorCondition {
+            id: None,
+            provider: "binance".to_string(),
+            symbol: Symbol::new("BTCUSDT").unwrap(),
+            field: MonitorField::Price,
+            operator: MonitorOperator::GreaterThan,
+            value: 100.0,
+            value2: None,
+            enabled: true,
+            notify: false,
+        };
+        let ticks: Vec<TickerData> = [99.0, 101.0, 102.0, 98.0]
+            .into_iter()
+            .enumerate()
+            .map(|(i, price)| TickerData { timestamp: i as u64, ..tick(price, None) })
+            .collect();
+
+        let test = test_condition(&condition, &ticks, None);
+        assert_eq!((test.holds_now, test.current_value), (false, Some(98.0)));
+        assert_eq!((test.samples, test.matched, test.last_matched_at), (4, 2, Some(2)));
+
+        let imbalance = MonitorCondition { field: MonitorField::OrderbookImbalance, value: 0.5, ..condition };
+        let test = test_condition(&imbalance, &ticks, None);
+        assert_eq!((test.holds_now, test.samples), (false, 0));
+    }
+
+    #[tokio::test]
+    async fn test_replayed_ticks_never_alert() {
+        let service = MonitoringService::new(":memory:".to_string());
+        *service.conditions.write().await = vec![MonitorCondition {
+            id: Some(3),
+            provider: "binance".to_string(),
+            symbol: Symbol::new("BTCUSDT").unwrap(),
+            field: MonitorField::Price,
+            operator: MonitorOperator::GreaterThan,
+            value: 100.0,
+            value2: None,
+            enabled: true,
+            notify: false,
+        }];
+
+        let replayed = TickerData { replayed: true, ..tick(101.0, None) };
+        assert!(service.check_ticker(&replayed).await.is_empty());
+        assert_eq!(service.check_ticker(&tick(101.0, None)).await.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_imbalance_condition_runs_on_book_updates() {
+        // Alerts can't be saved to an empty in-memory database; that error is ignored
+        let service = MonitoringService::new(":memory:".to_string());
+        *service.conditions.write().await = vec![MonitorCondition {
+            id: Some(7),
+            provider: "binance".to_string(),
+            symbol: Symbol::new("BTCUSDT").unwrap(),
+            field: MonitorField::OrderbookImbalance,
+            operator: MonitorOperator::GreaterThan,
+            value: 0.5,
+            value2: None,
+            enabled: true,
+            notify: false,
+        }];
+
+        let level = |price: f64, quantity: f64| OrderBookLevel { price, quantity, count: None };
+        let mut book = OrderBookData {
+            provider: "binance".to_string(),
+            symbol: "BTCUSDT".to_string(),
+            bids: vec![level(100.0, 9.0)],
+            asks: vec![],
+            timestamp: 0,
+            is_snapshot: true,
+        };
+
+        // One-sided book: skipped
+        assert!(service.check_orderbook(&book).await.is_empty());
+        // Ticks never evaluate book fields
+        assert!(service.check_ticker(&tick(100.0, None)).await.is_empty());
+
+        book.asks = vec![level(101.0, 1.0)];
+        let alerts = service.check_orderbook(&book).await;
+        assert_eq!(alerts.len(), 1);
+        assert!((alerts[0].triggered_value - 0.8).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_groups_round_trip_through_storage() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE monitor_condition_groups (
+                id INTEGER PRIMARY KEY AUTOINCREMENT, name TEXT, provider TEXT NOT NULL,
+                symbol TEXT NOT NULL, logic TEXT NOT NULL, enabled INTEGER DEFAULT 1,
+                notify INTEGER DEFAULT 0, created_at TEXT DEFAULT CURRENT_TIMESTAMP
+            );
+            CREATE TABLE monitor_conditions (
+                id INTEGER PRIMARY KEY AUTOINCREMENT, provider TEXT NOT NULL, symbol TEXT NOT NULL,
+                field TEXT NOT NULL, operator TEXT NOT NULL, value REAL NOT NULL, value2 REAL,
+                enabled INTEGER DEFAULT 1, notify INTEGER DEFAULT 0, group_id INTEGER
+            );",
+        )
+        .unwrap();
+
+        let id = insert_condition_group(&conn, &group(GroupLogic::And)).unwrap();
+        let loaded = load_condition_groups(&conn, true).unwrap();
+        assert_eq!(loaded.len(), 1);
+        assert_eq!(loaded[0].id, Some(id));
+        assert_eq!(loaded[0].logic, GroupLogic::And);
+        assert_eq!(loaded[0].conditions.len(), 2);
+
+        assert!(delete_condition_group(&conn, id).unwrap());
+        assert!(load_condition_groups(&conn, false).unwrap().is_empty());
+        let members: i64 = conn
+            .query_row("SELECT COUNT(*) FROM monitor_conditions", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(members, 0);
+    }
+
+    #[test]
+    fn test_acknowledge_alerts() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE monitor_alerts (
+                id INTEGER PRIMARY KEY AUTOINCREMENT, acknowledged INTEGER DEFAULT 0,
+                acknowledged_at INTEGER
+            );
+            INSERT INTO monitor_alerts (id) VALUES (1), (2), (3);",
+        )
+        .unwrap();
+
+        assert_eq!(acknowledge_alerts(&conn, Some(2)).unwrap(), 1);
+        assert_eq!(acknowledge_alerts(&conn, Some(2)).unwrap(), 0);
+        assert_eq!(acknowledge_alerts(&conn, None).unwrap(), 2);
+        assert_eq!(acknowledge_alerts(&conn, Some(99)).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_query_alerts_filters_and_pages() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE monitor_alerts (
+                id INTEGER PRIMARY KEY AUTOINCREMENT, condition_id INTEGER NOT NULL,
+                provider TEXT NOT NULL, symbol TEXT NOT NULL, field TEXT NOT NULL,
+                triggered_value REAL NOT NULL, triggered_at INTEGER NOT NULL, group_id INTEGER,
+                details TEXT, acknowledged INTEGER DEFAULT 0, acknowledged_at INTEGER
+            );
+            INSERT INTO monitor_alerts (condition_id, provider, symbol, field, triggered_value, triggered_at) VALUES
+                (1, 'binance', 'BTCUSDT', 'price', 100, 1000),
+                (1, 'binance', 'BTCUSDT', 'price', 101, 2000),
+                (2, 'kraken', 'BTC/USD', 'volume', 5, 3000),
+                (1, 'binance', 'BTCUSDT', 'volume', 7, 4000);",
+        )
+        .unwrap();
+
+        // Only a limit: newest first, as before
+        let page = query_alerts(&conn, &AlertQuery { limit: Some(2), ..Default::default() }).unwrap();
+        assert_eq!(page.total, 4);
+        assert_eq!(page.alerts.iter().map(|a| a.triggered_at).collect::<Vec<_>>(), vec![4000, 3000]);
+
+        let query = AlertQuery {
+            provider: Some("Binance".to_string()),
+            field: Some("price".to_string()),
+            limit: Some(1),
+            offset: Some(1),
+            ..Default::default()
+        };
+        let page = query_alerts(&conn, &query).unwrap();
+        assert_eq!((page.total, page.alerts.len()), (2, 1));
+        assert_eq!(page.alerts[0].triggered_at, 1000);
+
+        let query = AlertQuery { since: Some(2000), until: Some(4000), ..Default::default() };
+        assert_eq!(query_alerts(&conn, &query).unwrap().total, 2);
+
+        let query = AlertQuery { field: Some("bogus".to_string()), ..Default::default() };
+        assert!(query_alerts(&conn, &query).is_err());
+        let query = AlertQuery { since: Some(5), until: Some(5), ..Default::default() };
+        assert!(query_alerts(&conn, &query).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_stop_monitoring_ends_stream_tasks() {
+        let service = MonitoringService::new(":memory:".to_string());
+        let (ticker_tx, ticker_rx) = tokio::sync::broadcast::channel(8);
+        let (book_tx, book_rx) = tokio::sync::broadcast::channel(8);
+        service.start_monitoring(ticker_rx, book_rx);
+        assert_eq!((ticker_tx.receiver_count(), book_tx.receiver_count()), (1, 1));
+
+        // The aborted tasks drop their receivers, so the senders see none left
+        service.stop_monitoring();
+        for _ in 0..100 {
+            if ticker_tx.receiver_count() + book_tx.receiver_count() == 0 {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        assert_eq!((ticker_tx.receiver_count(), book_tx.receiver_count()), (0, 0));
+        assert!(service.tasks.lock().is_empty());
+    }
+}
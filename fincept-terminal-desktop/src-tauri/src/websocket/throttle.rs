@@ -0,0 +1,172 @@
+// Ticker Throttle - caps per-symbol ticker events sent to the frontend
+//
+// What: For providers with a `ticker_throttle_ms` set, the router forwards at most one
+//       `ws_ticker` event per (provider, symbol) per interval. Ticks arriving inside
+//       the window replace each other, and the newest one is sent when the window
+//       closes (last value wins).
+// Why:  Some feeds push hundreds of ticks per second per symbol, while charts only
+//       repaint at ~60fps. Emitting every tick burned CPU on serialization and IPC.
+// How:  The router still broadcasts every tick to backend services (monitoring,
+//       paper trading, arbitrage), so alerts see the full stream. Only frontend
+//       emission goes through `offer`. A deferred tick schedules a single trailing
+//       flush, so a burst's final price is never lost when the feed goes quiet.
+
+use super::types::TickerData;
+use dashmap::DashMap;
+use std::time::{Duration, Instant};
+
+/// What the router should do with a tick for the frontend
+#[derive(Debug, PartialEq)]
+pub enum ThrottleDecision {
+    /// Emit now
+    Emit,
+    /// Held as the pending value; when `flush_after` is set the caller must call
+    /// `take_pending` after that delay (only the first deferred tick of a window gets one)
+    Deferred { flush_after: Option<Duration> },
+}
+
+struct Slot {
+    last_emit: Instant,
+    pending: Option<TickerData>,
+    flush_scheduled: bool,
+}
+
+#[derive(Default)]
+pub struct TickerThrottle {
+    /// provider (lowercase) -> minimum gap between frontend ticks
+    intervals: DashMap<String, Duration>,
+    slots: DashMap<(String, String), Slot>,
+}
+
+impl TickerThrottle {
+    /// Set the interval for a provider; 0 disables throttling
+    pub fn set_interval(&self, provider: &str, interval_ms: u64) {
+        let provider = provider.trim().to_lowercase();
+        if interval_ms == 0 {
+            self.intervals.remove(&provider);
+            self.slots.retain(|(p, _), _| *p != provider);
+        } else {
+            self.intervals.insert(provider, Duration::from_millis(interval_ms));
+        }
+    }
+
+    pub fn interval(&self, provider: &str) -> Option<Duration> {
+        self.intervals.get(&provider.to_lowercase()).map(|d| *d)
+    }
+
+    /// Decide whether `ticker` goes to the frontend now
+    pub fn offer(&self, ticker: &TickerData, now: Instant) -> ThrottleDecision {
+        let Some(interval) = self.interval(&ticker.provider) else {
+            return ThrottleDecision::Emit;
+        };
+
+        let key = (ticker.provider.to_lowercase(), ticker.symbol.clone());
+        let mut slot = match self.slots.get_mut(&key) {
+            Some(slot) => slot,
+            None => {
+                self.slots.insert(
+                    key,
+                    Slot {
+                        last_emit: now,
+                        pending: None,
+                        flush_scheduled: false,
+                    },
+                );
+                return ThrottleDecision::Emit;
+            }
+        };
+
+        let elapsed = now.saturating_duration_since(slot.last_emit);
+        if elapsed >= interval && !slot.flush_scheduled {
+            slot.last_emit = now;
+            slot.pending = None;
+            return ThrottleDecision::Emit;
+        }
+
+        slot.pending = Some(ticker.clone());
+        if slot.flush_scheduled {
+            ThrottleDecision::Deferred { flush_after: None }
+        } else {
+            slot.flush_scheduled = true;
+            ThrottleDecision::Deferred {
+                flush_after: Some(interval.saturating_sub(elapsed)),
+            }
+        }
+    }
+
+    /// Take the tick held for (provider, symbol), marking it as emitted at `now`
+    pub fn take_pending(&self, provider: &str, symbol: &str, now: Instant) -> Option<TickerData> {
+        let mut slot = self.slots.get_mut(&(provider.to_lowercase(), symbol.to_string()))?;
+        slot.flush_scheduled = false;
+        let pending = slot.pending.take()?;
+        slot.last_emit = now;
+        Some(pending)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tick(price: f64) -> TickerData {
+        TickerData {
+            provider: "binance".to_string(),
+            symbol: "BTCUSDT".to_string(),
+            price,
+            bid: None,
+            ask: None,
+            bid_size: None,
+            ask_size: None,
+            volume: None,
+            high: None,
+            low: None,
+            open: None,
+            close: None,
+            change: None,
+            change_percent: None,
+            timestamp: 0,
+            replayed: false,
+        }
+    }
+
+    #[test]
+    fn test_unthrottled_provider_always_emits() {
+        let throttle = TickerThrottle::default();
+        let now = Instant::now();
+        assert_eq!(throttle.offer(&tick(1.0), now), ThrottleDecision::Emit);
+        assert_eq!(throttle.offer(&tick(2.0), now), ThrottleDecision::Emit);
+    }
+
+    #[test]
+    fn test_burst_collapses_to_last_value() {
+        let throttle = TickerThrottle::default();
+        throttle.set_interval("Binance", 100);
+        let start = Instant::now();
+
+        assert_eq!(throttle.offer(&tick(1.0), start), ThrottleDecision::Emit);
+        assert_eq!(
+            throttle.offer(&tick(2.0), start + Duration::from_millis(30)),
+            ThrottleDecision::Deferred {
+                flush_after: Some(Duration::from_millis(70))
+            }
+        );
+        assert_eq!(
+            throttle.offer(&tick(3.0), start + Duration::from_millis(60)),
+            ThrottleDecision::Deferred { flush_after: None }
+        );
+
+        let flushed = throttle
+            .take_pending("binance", "BTCUSDT", start + Duration::from_millis(100))
+            .unwrap();
+        assert_eq!(flushed.price, 3.0);
+
+        // A quiet period after the flush lets the next tick straight through
+        assert_eq!(
+            throttle.offer(&tick(4.0), start + Duration::from_millis(250)),
+            ThrottleDecision::Emit
+        );
+
+        throttle.set_interval("binance", 0);
+        assert!(throttle.interval("binance").is_none());
+    }
+}
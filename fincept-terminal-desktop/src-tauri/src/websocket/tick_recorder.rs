@@ -0,0 +1,424 @@
+// Tick Recorder - opt-in persistence of routed tickers to `tick_history`
+//
+// What: `set_tick_recording(provider, symbol, true)` makes the router write every
+//       ticker of that pair to the database until it is turned off again. The
+//       pairs are kept in `tick_recorder_targets`, so recording resumes after a
+//       restart. `get_tick_history` reads the tape back by time range and
+//       `replay_ticks` sends it through the router again for frontend views and
+//       web clients. Replayed ticks carry `replayed: true`, so monitors and
+//       the paper-trading matching engine don't act on old prices.
+// Why:  Ticks were only routed live and then gone, so strategies could not be
+//       tried against what a feed actually did.
+// How:  The router hands matching ticks to a bounded channel; one writer task
+//       drains whatever has queued (up to `MAX_BATCH`) and inserts it in a
+//       single transaction, so a busy feed costs one commit per drain rather
+//       than per tick. A full channel drops ticks and counts them instead of
+//       slowing the router. Rows older than `tick_history_retention_days`
+//       (default 7) are pruned at most once a minute after a write. Replayed
+//       ticks skip the recorder, so a replay never records itself.
+
+use super::router::MessageRouter;
+use super::types::TickerData;
+use crate::database::pool::{self, get_pool};
+use crate::utils::symbol::Symbol;
+use anyhow::Result;
+use once_cell::sync::{Lazy, OnceCell};
+use parking_lot::{Mutex, RwLock};
+use rusqlite::{params, Connection};
+use serde::Serialize;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+
+/// Setting holding how many days of ticks are kept
+pub const RETENTION_SETTING: &str = "tick_history_retention_days";
+
+const DEFAULT_RETENTION_DAYS: u64 = 7;
+const CHANNEL_CAPACITY: usize = 10_000;
+const MAX_BATCH: usize = 1_000;
+const PRUNE_EVERY: Duration = Duration::from_secs(60);
+const DAY_MS: u64 = 24 * 60 * 60 * 1000;
+
+/// Most ticks `get_tick_history` returns, and that one replay sends
+pub const MAX_HISTORY_TICKS: usize = 100_000;
+
+/// Running replay, replaced by each `replay_ticks`
+static REPLAY: Lazy<Mutex<Option<tauri::async_runtime::JoinHandle<()>>>> = Lazy::new(|| Mutex::new(None));
+
+/// A (provider, symbol) pair being recorded
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RecordingTarget {
+    pub provider: String,
+    pub symbol: Symbol,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TickRecorderStatus {
+    pub targets: Vec<RecordingTarget>,
+    /// Ticks written since startup
+    pub recorded: u64,
+    /// Ticks lost because the writer fell behind
+    pub dropped: u64,
+    pub retention_days: u64,
+}
+
+#[derive(Default)]
+pub struct TickRecorder {
+    targets: RwLock<Vec<RecordingTarget>>,
+    writer: OnceCell<mpsc::Sender<TickerData>>,
+    recorded: Arc<AtomicU64>,
+    dropped: AtomicU64,
+}
+
+fn normalize_provider(provider: &str) -> String {
+    provider.trim().to_lowercase()
+}
+
+pub fn retention_days() -> u64 {
+    crate::database::operations::get_setting(RETENTION_SETTING)
+        .ok()
+        .flatten()
+        .and_then(|v| v.trim().parse::<u64>().ok())
+        .filter(|days| *days > 0)
+        .unwrap_or(DEFAULT_RETENTION_DAYS)
+}
+
+impl TickRecorder {
+    /// Resume recording the pairs saved by earlier sessions; an unavailable database records nothing
+    pub fn load_targets(&self) {
+        let loaded = get_pool().and_then(|pool| {
+            let conn = pool.get()?;
+            load_target_rows(&conn)
+        });
+        match loaded {
+            Ok(targets) => *self.targets.write() = targets,
+            Err(e) => tracing::warn!(error = %e, "Tick recorder could not load its targets"),
+        }
+    }
+
+    /// Start or stop recording `provider` / `symbol`; returns the pairs now recorded
+    pub fn set_recording(&self, provider: &str, symbol: &Symbol, enabled: bool) -> Result<Vec<RecordingTarget>> {
+        let provider = normalize_provider(provider);
+        if provider.is_empty() {
+            anyhow::bail!("Missing 'provider' parameter");
+        }
+        pool::write(|conn| save_target_row(conn, &provider, symbol, enabled))?;
+
+        let mut targets = self.targets.write();
+        targets.retain(|t| !(t.provider == provider && t.symbol == *symbol));
+        if enabled {
+            targets.push(RecordingTarget { provider, symbol: symbol.clone() });
+        }
+        Ok(targets.clone())
+    }
+
+    pub fn status(&self) -> TickRecorderStatus {
+        TickRecorderStatus {
+            targets: self.targets.read().clone(),
+            recorded: self.recorded.load(Ordering::Relaxed),
+            dropped: self.dropped.load(Ordering::Relaxed),
+            retention_days: retention_days(),
+        }
+    }
+
+    fn is_recorded(&self, tick: &TickerData) -> bool {
+        let targets = self.targets.read();
+        !targets.is_empty()
+            && targets
+                .iter()
+                .any(|t| t.provider.eq_ignore_ascii_case(&tick.provider) && t.symbol.matches(&tick.symbol))
+    }
+
+    /// Queue `tick` for writing if its pair is recorded
+    pub fn offer(&self, tick: &TickerData) {
+        if !self.is_recorded(tick) {
+            return;
+        }
+        let writer = self.writer.get_or_init(|| {
+            let (tx, rx) = mpsc::channel(CHANNEL_CAPACITY);
+            tauri::async_runtime::spawn(run_writer(rx, self.recorded.clone()));
+            tx
+        });
+        if writer.try_send(tick.clone()).is_err() {
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+/// Write queued ticks a drained batch at a time until the recorder is dropped
+async fn run_writer(mut rx: mpsc::Receiver<TickerData>, recorded: Arc<AtomicU64>) {
+    let last_prune = Arc::new(Mutex::new(None::<Instant>));
+    while let Some(tick) = rx.recv().await {
+        let mut batch = vec![tick];
+        while batch.len() < MAX_BATCH {
+            match rx.try_recv() {
+                Ok(tick) => batch.push(tick),
+                Err(_) => break,
+            }
+        }
+
+        let last_prune = last_prune.clone();
+        let written = tokio::task::spawn_blocking(move || -> Result<usize> {
+            let written = pool::write(|conn| {
+                let tx = conn.unchecked_transaction()?;
+                insert_ticks(&tx, &batch)?;
+                tx.commit()?;
+                Ok(batch.len())
+            })?;
+            let mut last_prune = last_prune.lock();
+            if last_prune.is_none_or(|at| at.elapsed() >= PRUNE_EVERY) {
+                *last_prune = Some(Instant::now());
+                let cutoff = (chrono::Utc::now().timestamp_millis() as u64).saturating_sub(retention_days() * DAY_MS);
+                pool::write(|conn| prune_ticks(conn, cutoff))?;
+            }
+            Ok(written)
+        })
+        .await;
+        match written {
+            Ok(Ok(n)) => {
+                recorded.fetch_add(n as u64, Ordering::Relaxed);
+            }
+            Ok(Err(e)) => tracing::warn!(error = %e, "Tick recorder could not write a batch"),
+            Err(e) => tracing::warn!(error = %e, "Tick recorder write task failed"),
+        }
+    }
+}
+
+fn load_target_rows(conn: &Connection) -> Result<Vec<RecordingTarget>> {
+    let mut stmt = conn.prepare("SELECT provider, symbol FROM tick_recorder_targets ORDER BY provider, symbol")?;
+    let targets = stmt
+        .query_map([], |row| Ok(RecordingTarget { provider: row.get(0)?, symbol: row.get(1)? }))?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    Ok(targets)
+}
+
+fn save_target_row(conn: &Connection, provider: &str, symbol: &Symbol, enabled: bool) -> Result<()> {
+    if enabled {
+        conn.execute(
+            "INSERT OR IGNORE INTO tick_recorder_targets (provider, symbol) VALUES (?1, ?2)",
+            params![provider, symbol],
+        )?;
+    } else {
+        conn.execute(
+            "DELETE FROM tick_recorder_targets WHERE provider = ?1 AND symbol = ?2",
+            params![provider, symbol],
+        )?;
+    }
+    Ok(())
+}
+
+fn insert_ticks(conn: &Connection, ticks: &[TickerData]) -> Result<()> {
+    let mut stmt = conn.prepare_cached(
+        "INSERT INTO tick_history
+         (provider, symbol, price, bid, ask, bid_size, ask_size, volume, high, low, open, close,
+          change, change_percent, timestamp)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15)",
+    )?;
+    for t in ticks {
+        stmt.execute(params![
+            normalize_provider(&t.provider),
+            t.symbol.trim().to_uppercase(),
+            t.price,
+            t.bid,
+            t.ask,
+            t.bid_size,
+            t.ask_size,
+            t.volume,
+            t.high,
+            t.low,
+            t.open,
+            t.close,
+            t.change,
+            t.change_percent,
+            t.timestamp as i64,
+        ])?;
+    }
+    Ok(())
+}
+
+fn prune_ticks(conn: &Connection, cutoff_ms: u64) -> Result<usize> {
+    Ok(conn.execute("DELETE FROM tick_history WHERE timestamp < ?1", params![cutoff_ms as i64])?)
+}
+
+/// Recorded ticks of `provider` / `symbol` between `from` and `to` (Unix ms, inclusive), oldest first
+pub fn get_tick_history(
+    provider: &str,
+    symbol: &Symbol,
+    from: Option<u64>,
+    to: Option<u64>,
+    limit: usize,
+) -> Result<Vec<TickerData>> {
+    let pool = get_pool()?;
+    let conn = pool.get()?;
+    tick_history_rows(&conn, provider, symbol, from, to, limit)
+}
+
+fn tick_history_rows(
+    conn: &Connection,
+    provider: &str,
+    symbol: &Symbol,
+    from: Option<u64>,
+    to: Option<u64>,
+    limit: usize,
+) -> Result<Vec<TickerData>> {
+    let mut stmt = conn.prepare(
+        "SELECT provider, symbol, price, bid, ask, bid_size, ask_size, volume, high, low, open, close,
+                change, change_percent, timestamp
+         FROM tick_history
+         WHERE provider = ?1 AND symbol = ?2
+           AND (?3 IS NULL OR timestamp >= ?3) AND (?4 IS NULL OR timestamp <= ?4)
+         ORDER BY timestamp, id
+         LIMIT ?5",
+    )?;
+    let ticks = stmt
+        .query_map(
+            params![
+                normalize_provider(provider),
+                symbol,
+                from.map(|v| v as i64),
+                to.map(|v| v as i64),
+                limit as i64
+            ],
+            |row| {
+                Ok(TickerData {
+                    provider: row.get(0)?,
+                    symbol: row.get(1)?,
+                    price: row.get(2)?,
+                    bid: row.get(3)?,
+                    ask: row.get(4)?,
+                    bid_size: row.get(5)?,
+                    ask_size: row.get(6)?,
+                    volume: row.get(7)?,
+                    high: row.get(8)?,
+                    low: row.get(9)?,
+                    open: row.get(10)?,
+                    close: row.get(11)?,
+                    change: row.get(12)?,
+                    change_percent: row.get(13)?,
+                    timestamp: row.get::<_, i64>(14)? as u64,
+                    replayed: false,
+                })
+            },
+        )?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    Ok(ticks)
+}
+
+/// Route the recorded ticks of `provider` / `symbol` between `from` and `to` again,
+/// spaced as they arrived divided by `speed` (0 = as fast as possible). Replaces a
+/// running replay; returns how many ticks will be sent.
+pub fn replay_ticks(
+    router: Arc<tokio::sync::RwLock<MessageRouter>>,
+    provider: &str,
+    symbol: &Symbol,
+    from: Option<u64>,
+    to: Option<u64>,
+    speed: f64,
+) -> Result<usize> {
+    let ticks = get_tick_history(provider, symbol, from, to, MAX_HISTORY_TICKS)?;
+    let count = ticks.len();
+    let task = tauri::async_runtime::spawn(async move {
+        let mut previous: Option<u64> = None;
+        for tick in ticks {
+            if let Some(previous) = previous {
+                tokio::time::sleep(replay_delay(tick.timestamp.saturating_sub(previous), speed)).await;
+            }
+            previous = Some(tick.timestamp);
+            router.read().await.replay_ticker(tick);
+        }
+    });
+    if let Some(running) = REPLAY.lock().replace(task) {
+        running.abort();
+    }
+    Ok(count)
+}
+
+/// Stop the latest replay; false when none had been started
+pub fn stop_replay() -> bool {
+    REPLAY.lock().take().is_some_and(|task| {
+        task.abort();
+        true
+    })
+}
+
+/// Pause before replaying a tick `gap_ms` after the previous one, at `speed`
+/// times real time; 0 replays without pauses. Gaps are capped at a minute.
+pub fn replay_delay(gap_ms: u64, speed: f64) -> Duration {
+    if speed <= 0.0 || !speed.is_finite() {
+        return Duration::ZERO;
+    }
+    Duration::from_secs_f64((gap_ms as f64 / 1000.0 / speed).min(60.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tick(symbol: &str, price: f64, timestamp: u64) -> TickerData {
+        TickerData {
+            provider: "Kraken".to_string(),
+            symbol: symbol.to_string(),
+            price,
+            bid: Some(price - 1.0),
+            ask: None,
+            bid_size: None,
+            ask_size: None,
+            volume: None,
+            high: None,
+            low: None,
+            open: None,
+            close: None,
+            change: None,
+            change_percent: None,
+            timestamp,
+            replayed: false,
+        }
+    }
+
+    #[test]
+    fn test_history_round_trips_by_range_and_prunes() {
+        let conn = Connection::open_in_memory().unwrap();
+        crate::database::schema::create_schema(&conn).unwrap();
+        insert_ticks(&conn, &[tick("btcusd", 100.0, 1_000), tick("BTCUSD", 101.0, 2_000), tick("ETHUSD", 5.0, 2_000)])
+            .unwrap();
+        insert_ticks(&conn, &[tick("BTCUSD", 102.0, 3_000)]).unwrap();
+
+        let btc = Symbol::new("BTCUSD").unwrap();
+        let all = tick_history_rows(&conn, "kraken", &btc, None, None, 100).unwrap();
+        let prices: Vec<f64> = all.iter().map(|t| t.price).collect();
+        assert_eq!(prices, [100.0, 101.0, 102.0]);
+        assert_eq!((all[0].provider.as_str(), all[0].bid), ("kraken", Some(99.0)));
+
+        let ranged = tick_history_rows(&conn, "kraken", &btc, Some(2_000), Some(3_000), 1).unwrap();
+        assert_eq!(ranged.len(), 1);
+        assert_eq!(ranged[0].price, 101.0);
+
+        assert_eq!(prune_ticks(&conn, 2_000).unwrap(), 1);
+        assert_eq!(tick_history_rows(&conn, "kraken", &btc, None, None, 100).unwrap().len(), 2);
+
+        assert_eq!(replay_delay(500, 0.0), Duration::ZERO);
+        assert_eq!(replay_delay(500, 2.0), Duration::from_millis(250));
+        assert_eq!(replay_delay(3_600_000, 1.0), Duration::from_secs(60));
+    }
+
+    #[test]
+    fn test_only_recorded_pairs_are_offered() {
+        let recorder = TickRecorder::default();
+        recorder
+            .targets
+            .write()
+            .push(RecordingTarget { provider: "kraken".to_string(), symbol: Symbol::new("BTCUSD").unwrap() });
+        assert!(recorder.is_recorded(&tick("btcusd", 1.0, 0)));
+        assert!(!recorder.is_recorded(&tick("ETHUSD", 1.0, 0)));
+
+        let conn = Connection::open_in_memory().unwrap();
+        crate::database::schema::create_schema(&conn).unwrap();
+        let btc = Symbol::new("BTCUSD").unwrap();
+        save_target_row(&conn, "kraken", &btc, true).unwrap();
+        save_target_row(&conn, "kraken", &btc, true).unwrap();
+        assert_eq!(load_target_rows(&conn).unwrap().len(), 1);
+        save_target_row(&conn, "kraken", &btc, false).unwrap();
+        assert!(load_target_rows(&conn).unwrap().is_empty());
+    }
+}
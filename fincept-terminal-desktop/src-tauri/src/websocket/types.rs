@@ -2,7 +2,7 @@
 // Normalized message formats for all providers
 
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 
 // ============================================================================
 // CORE MESSAGE TYPES
@@ -37,6 +37,10 @@ pub struct TickerData {
     pub change: Option<f64>,
     pub change_percent: Option<f64>,
     pub timestamp: u64,
+    /// Sent again from `tick_history` by `replay_ticks`, not by a live feed;
+    /// monitors and paper trading ignore these
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub replayed: bool,
 }
 
 /// Order book data (market depth)
@@ -110,6 +114,66 @@ pub enum ConnectionStatus {
     Error,
 }
 
+// ============================================================================
+// CLIENT EVENTS
+// ============================================================================
+
+/// Version of the event frames below, announced in `hello`
+///
+/// Bump it whenever a frame or payload changes shape, so clients can detect
+/// a server they don't understand.
+pub const WS_EVENT_VERSION: u32 = 1;
+
+/// One frame of the market stream, as emitted to the desktop frontend and
+/// sent to web `/ws` clients: `{"event": "ws_ticker", "data": {...}}`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "event")]
+pub enum WsEvent {
+    /// First frame on a `/ws` connection
+    #[serde(rename = "hello")]
+    Hello { version: u32, available_topics: Vec<String> },
+    #[serde(rename = "ws_ticker")]
+    Ticker { data: TickerData },
+    #[serde(rename = "ws_orderbook")]
+    OrderBook { data: OrderBookData },
+    #[serde(rename = "ws_trade")]
+    Trade { data: TradeData },
+    #[serde(rename = "ws_candle")]
+    Candle { data: CandleData },
+    #[serde(rename = "ws_status")]
+    Status { data: StatusData },
+}
+
+impl WsEvent {
+    pub const TICKER: &'static str = "ws_ticker";
+    pub const ORDERBOOK: &'static str = "ws_orderbook";
+    pub const TRADE: &'static str = "ws_trade";
+    pub const CANDLE: &'static str = "ws_candle";
+    pub const STATUS: &'static str = "ws_status";
+
+    /// Data events a client can receive
+    pub const TOPICS: [&'static str; 5] = [Self::TICKER, Self::ORDERBOOK, Self::TRADE, Self::CANDLE, Self::STATUS];
+
+    pub fn hello() -> Self {
+        WsEvent::Hello {
+            version: WS_EVENT_VERSION,
+            available_topics: Self::TOPICS.iter().map(|t| t.to_string()).collect(),
+        }
+    }
+
+    /// The `event` field of this frame
+    pub fn name(&self) -> &'static str {
+        match self {
+            WsEvent::Hello { .. } => "hello",
+            WsEvent::Ticker { .. } => Self::TICKER,
+            WsEvent::OrderBook { .. } => Self::ORDERBOOK,
+            WsEvent::Trade { .. } => Self::TRADE,
+            WsEvent::Candle { .. } => Self::CANDLE,
+            WsEvent::Status { .. } => Self::STATUS,
+        }
+    }
+}
+
 // ============================================================================
 // SUBSCRIPTION TYPES
 // ============================================================================
@@ -133,6 +197,24 @@ pub struct Subscription {
     pub params: Option<HashMap<String, serde_json::Value>>,
 }
 
+/// Per-symbol result of a batched subscribe
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SubscribeOutcome {
+    pub symbol: String,
+    pub subscribed: bool,
+    pub error: Option<String>,
+}
+
+impl SubscribeOutcome {
+    pub fn ok(symbol: &str) -> Self {
+        Self { symbol: symbol.to_string(), subscribed: true, error: None }
+    }
+
+    pub fn failed(symbol: &str, error: impl Into<String>) -> Self {
+        Self { symbol: symbol.to_string(), subscribed: false, error: Some(error.into()) }
+    }
+}
+
 // ============================================================================
 // PROVIDER CONFIG
 // ============================================================================
@@ -147,7 +229,82 @@ pub struct ProviderConfig {
     pub enabled: bool,
     pub reconnect_delay_ms: u64,
     pub max_reconnect_attempts: u32,
+    /// How often the keepalive pings the provider (see `websocket::keepalive`)
     pub heartbeat_interval_ms: u64,
+    /// Unanswered pings in a row after which the connection is reconnected
+    #[serde(default = "default_max_missed_pongs")]
+    pub max_missed_pongs: u32,
+    /// Minimum gap between frontend ticker events per symbol (0 = forward every tick).
+    /// Backend services such as monitoring always receive every tick.
+    #[serde(default)]
+    pub ticker_throttle_ms: u64,
+    /// Field mapping for a feed without a built-in adapter (see `websocket::mapping`);
+    /// when set, the generic adapter is used for this provider
+    #[serde(default)]
+    pub message_mapping: Option<super::mapping::MessageMapping>,
+}
+
+fn default_max_missed_pongs() -> u32 {
+    super::keepalive::DEFAULT_MAX_MISSED_PONGS
+}
+
+impl ProviderConfig {
+    /// Row for the `ws_provider_configs` table; reconnect/heartbeat tuning goes in `config_data`
+    pub fn to_db_config(&self) -> crate::database::WSProviderConfig {
+        let url = self.url.trim();
+        crate::database::WSProviderConfig {
+            id: None,
+            provider_name: self.name.to_lowercase(),
+            enabled: self.enabled,
+            api_key: self.api_key.clone(),
+            api_secret: self.api_secret.clone(),
+            endpoint: (!url.is_empty()).then(|| url.to_string()),
+            config_data: Some(
+                serde_json::json!({
+                    "reconnect_delay_ms": self.reconnect_delay_ms,
+                    "max_reconnect_attempts": self.max_reconnect_attempts,
+                    "heartbeat_interval_ms": self.heartbeat_interval_ms,
+                    "max_missed_pongs": self.max_missed_pongs,
+                    "ticker_throttle_ms": self.ticker_throttle_ms,
+                    "message_mapping": self.message_mapping,
+                })
+                .to_string(),
+            ),
+            created_at: None,
+            updated_at: None,
+        }
+    }
+
+    /// Inverse of `to_db_config`; tuning missing from `config_data` keeps its default
+    pub fn from_db_config(row: &crate::database::WSProviderConfig) -> Self {
+        let data: serde_json::Value = row
+            .config_data
+            .as_deref()
+            .and_then(|s| serde_json::from_str(s).ok())
+            .unwrap_or_default();
+        let defaults = Self::default();
+        let number = |key: &str| data.get(key).and_then(|v| v.as_u64());
+        Self {
+            name: row.provider_name.clone(),
+            url: row.endpoint.clone().unwrap_or_default(),
+            api_key: row.api_key.clone(),
+            api_secret: row.api_secret.clone(),
+            enabled: row.enabled,
+            reconnect_delay_ms: number("reconnect_delay_ms").unwrap_or(defaults.reconnect_delay_ms),
+            max_reconnect_attempts: number("max_reconnect_attempts")
+                .and_then(|n| u32::try_from(n).ok())
+                .unwrap_or(defaults.max_reconnect_attempts),
+            heartbeat_interval_ms: number("heartbeat_interval_ms").unwrap_or(defaults.heartbeat_interval_ms),
+            max_missed_pongs: number("max_missed_pongs")
+                .and_then(|n| u32::try_from(n).ok())
+                .unwrap_or(defaults.max_missed_pongs),
+            ticker_throttle_ms: number("ticker_throttle_ms").unwrap_or(defaults.ticker_throttle_ms),
+            message_mapping: data
+                .get("message_mapping")
+                .filter(|v| !v.is_null())
+                .and_then(|v| serde_json::from_value(v.clone()).ok()),
+        }
+    }
 }
 
 impl Default for ProviderConfig {
@@ -161,6 +318,9 @@ impl Default for ProviderConfig {
             reconnect_delay_ms: 5000,
             max_reconnect_attempts: 10,
             heartbeat_interval_ms: 30000,
+            max_missed_pongs: default_max_missed_pongs(),
+            ticker_throttle_ms: 0,
+            message_mapping: None,
         }
     }
 }
@@ -169,18 +329,77 @@ impl Default for ProviderConfig {
 // METRICS
 // ============================================================================
 
+/// Connection events kept per provider in `ConnectionMetrics::history`
+pub const CONNECTION_HISTORY_LEN: usize = 20;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConnectionEventKind {
+    Connected,
+    Disconnected,
+    Reconnecting,
+    ConnectFailed,
+    /// An expired access token was replaced before connecting again
+    TokenRefreshed,
+    /// Too many keepalive pings went unanswered; a reconnect follows
+    PongTimeout,
+}
+
+/// One connect/disconnect transition, for diagnosing flaky feeds
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConnectionEvent {
+    pub kind: ConnectionEventKind,
+    pub at: u64,
+    pub reason: Option<String>,
+}
+
 /// Connection metrics
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ConnectionMetrics {
     pub provider: String,
     pub status: ConnectionStatus,
+    /// When the current socket opened
     pub connected_at: Option<u64>,
+    /// Start of the current session: survives `reconnect()`, cleared by an explicit
+    /// disconnect. Uptime is `now - connected_since`.
+    #[serde(default)]
+    pub connected_since: Option<u64>,
     pub last_message_at: Option<u64>,
     pub messages_received: u64,
     pub messages_sent: u64,
     pub active_subscriptions: usize,
+    /// Open upstream sockets; the manager keeps this at 0 or 1 per provider
+    pub upstream_connections: usize,
+    /// Reconnects within the current session
     pub reconnect_count: u32,
+    /// Reconnects since the app started
+    #[serde(default)]
+    pub total_reconnects: u32,
+    /// Round trip of the last answered keepalive ping
     pub latency_ms: Option<u64>,
+    #[serde(default)]
+    pub last_pong_at: Option<u64>,
+    /// Keepalive pings in a row that went unanswered; reset by the next pong
+    #[serde(default)]
+    pub missed_pongs: u32,
+    /// Most recent connect/subscribe failure; cleared by the next success
+    #[serde(default)]
+    pub last_error: Option<String>,
+    #[serde(default)]
+    pub last_error_at: Option<u64>,
+    /// Most recent connection events, oldest first
+    #[serde(default)]
+    pub history: VecDeque<ConnectionEvent>,
+}
+
+impl ConnectionMetrics {
+    /// Record a connection event, dropping the oldest beyond `CONNECTION_HISTORY_LEN`
+    pub fn push_event(&mut self, kind: ConnectionEventKind, at: u64, reason: Option<String>) {
+        if self.history.len() == CONNECTION_HISTORY_LEN {
+            self.history.pop_front();
+        }
+        self.history.push_back(ConnectionEvent { kind, at, reason });
+    }
 }
 
 impl Default for ConnectionMetrics {
@@ -189,12 +408,20 @@ impl Default for ConnectionMetrics {
             provider: String::new(),
             status: ConnectionStatus::Disconnected,
             connected_at: None,
+            connected_since: None,
             last_message_at: None,
             messages_received: 0,
             messages_sent: 0,
             active_subscriptions: 0,
+            upstream_connections: 0,
             reconnect_count: 0,
+            total_reconnects: 0,
             latency_ms: None,
+            last_pong_at: None,
+            missed_pongs: 0,
+            last_error: None,
+            last_error_at: None,
+            history: VecDeque::new(),
         }
     }
 }
@@ -222,6 +449,77 @@ pub enum WebSocketError {
 
     #[error("Not connected: {0}")]
     NotConnected(String),
+
+    #[error("Invalid config: {0}")]
+    InvalidConfig(String),
 }
 
 pub type Result<T> = std::result::Result<T, WebSocketError>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn status_event() -> WsEvent {
+        WsEvent::Status {
+            data: StatusData {
+                provider: "kraken".to_string(),
+                status: ConnectionStatus::Connected,
+                message: None,
+                timestamp: 1,
+            },
+        }
+    }
+
+    #[test]
+    fn test_event_envelope_shape() {
+        let frame = serde_json::to_value(status_event()).unwrap();
+        assert_eq!(frame["event"], "ws_status");
+        assert_eq!(frame["data"]["provider"], "kraken");
+        assert_eq!(frame.as_object().unwrap().len(), 2);
+
+        let hello = serde_json::to_value(WsEvent::hello()).unwrap();
+        assert_eq!(hello["event"], "hello");
+        assert_eq!(hello["version"], WS_EVENT_VERSION);
+        assert_eq!(hello["available_topics"].as_array().unwrap().len(), WsEvent::TOPICS.len());
+    }
+
+    #[test]
+    fn test_event_names_match_serialized_tag() {
+        for event in [WsEvent::hello(), status_event()] {
+            let frame = serde_json::to_value(&event).unwrap();
+            assert_eq!(frame["event"], event.name());
+        }
+        let parsed: WsEvent = serde_json::from_value(serde_json::to_value(status_event()).unwrap()).unwrap();
+        assert_eq!(parsed.name(), WsEvent::STATUS);
+    }
+
+    #[test]
+    fn test_provider_config_round_trips_through_db_row() {
+        let config = ProviderConfig {
+            name: "Bybit".to_string(),
+            url: "wss://stream.bybit.com/v5/public/spot".to_string(),
+            ticker_throttle_ms: 250,
+            max_missed_pongs: 5,
+            message_mapping: Some(
+                serde_json::from_value(serde_json::json!({
+                    "ticker": { "symbol": "/data/s", "price": "/data/lastPrice" }
+                }))
+                .unwrap(),
+            ),
+            ..Default::default()
+        };
+        let restored = ProviderConfig::from_db_config(&config.to_db_config());
+        assert_eq!(restored.name, "bybit");
+        assert_eq!(restored.url, config.url);
+        assert_eq!(restored.ticker_throttle_ms, 250);
+        assert_eq!(restored.max_missed_pongs, 5);
+        assert_eq!(restored.message_mapping, config.message_mapping);
+
+        let mut row = config.to_db_config();
+        row.config_data = None;
+        let restored = ProviderConfig::from_db_config(&row);
+        assert_eq!(restored.heartbeat_interval_ms, 30000);
+        assert!(restored.message_mapping.is_none());
+    }
+}
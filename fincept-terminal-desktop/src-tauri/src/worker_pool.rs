@@ -2,12 +2,14 @@
 use std::path::PathBuf;
 use std::process::{Child, Command, Stdio};
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::{Mutex, Semaphore};
 use interprocess::local_socket::prelude::*;
 use interprocess::local_socket::GenericNamespaced;
 use serde::{Deserialize, Serialize};
 use std::collections::VecDeque;
 use std::io::{Read, Write};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
 
 #[cfg(target_os = "windows")]
 use std::os::windows::process::CommandExt;
@@ -106,6 +108,7 @@ impl WorkerPool {
 
         // Use venv-numpy2 as default (has most modern libraries)
         let worker = Self::spawn_worker(0, &python_base_path, "venv-numpy2", &socket_name).await?;
+        *WORKER_PIDS.lock() = vec![worker.process.id()];
         let workers = vec![worker];
 
         eprintln!("[WorkerPool] All {} workers started successfully", NUM_WORKERS);
@@ -133,7 +136,7 @@ impl WorkerPool {
         };
 
         if !python_exe.exists() {
-            return Err(format!("Python not found at: {:?}", python_exe));
+            return Err(crate::utils::python_errors::python_not_installed(&python_exe, python_base_path).into());
         }
 
         // Get worker script path - try multiple locations for dev/prod compatibility
@@ -164,6 +167,7 @@ impl WorkerPool {
             .map_err(|e| format!("Failed to spawn worker {}: {}", worker_id, e))?;
 
         eprintln!("[Worker {}] Process spawned, PID: {:?}", worker_id, process.id());
+        crate::commands::orphans::record("python_worker", &python_exe.to_string_lossy(), process.id());
 
         // Wait a bit for worker to start listening (minimal delay)
         tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
@@ -190,6 +194,7 @@ impl WorkerPool {
 
         // Get the single worker
         let mut workers = self.workers.lock().await;
+        Self::respawn_if_exited(&mut workers, &self.socket_name).await?;
         let worker = &mut workers[0];
 
         eprintln!("[WorkerPool] Executing task {}", task.task_id);
@@ -236,6 +241,19 @@ impl WorkerPool {
         Ok(response)
     }
 
+    /// Replace worker 0 if its process has exited, e.g. after `drain` killed it
+    async fn respawn_if_exited(workers: &mut [Worker], socket_name: &str) -> Result<(), String> {
+        if matches!(workers[0].process.try_wait(), Ok(None)) {
+            return Ok(());
+        }
+        let python_base_path = PYTHON_BASE_PATH.get().ok_or("Worker pool not initialized")?;
+        eprintln!("[WorkerPool] Worker 0 has exited, starting a new one");
+        let worker = Self::spawn_worker(0, python_base_path, "venv-numpy2", socket_name).await?;
+        *WORKER_PIDS.lock() = vec![worker.process.id()];
+        workers[0] = worker;
+        Ok(())
+    }
+
     /// Shutdown all workers
     pub async fn shutdown(&self) {
         eprintln!("[WorkerPool] Shutting down all workers");
@@ -260,11 +278,25 @@ impl Drop for WorkerPool {
 // Global worker pool instance
 use once_cell::sync::OnceCell;
 static WORKER_POOL: OnceCell<Arc<Mutex<Option<WorkerPool>>>> = OnceCell::new();
+static PYTHON_BASE_PATH: OnceCell<PathBuf> = OnceCell::new();
+/// Worker process ids by worker index; kept outside the pool lock, which a running task holds
+static WORKER_PIDS: once_cell::sync::Lazy<parking_lot::Mutex<Vec<u32>>> =
+    once_cell::sync::Lazy::new(|| parking_lot::Mutex::new(Vec::new()));
+/// Bumped by `drain_worker_pool`; tasks that started waiting before a bump are cancelled
+static DRAIN_EPOCH: AtomicU64 = AtomicU64::new(0);
+/// Tasks waiting for the pool
+static QUEUED_TASKS: AtomicUsize = AtomicUsize::new(0);
+/// Tasks holding the pool, i.e. running on a worker
+static RUNNING_TASKS: AtomicUsize = AtomicUsize::new(0);
+
+const CANCELLED: &str = "Cancelled: the worker pool was drained";
 
 /// Initialize the global worker pool
 pub async fn initialize_worker_pool(python_base_path: PathBuf) -> Result<(), String> {
     eprintln!("[WorkerPool] Initializing global worker pool");
 
+    let _ = PYTHON_BASE_PATH.set(python_base_path.clone());
+
     let pool = WorkerPool::new(python_base_path).await?;
 
     WORKER_POOL.get_or_init(|| Arc::new(Mutex::new(Some(pool))));
@@ -280,12 +312,143 @@ pub async fn get_worker_pool() -> Result<Arc<Mutex<Option<WorkerPool>>>, String>
         .map(|pool| pool.clone())
 }
 
+/// Install directory holding the venvs, once initialization has been attempted
+pub fn python_base_path() -> Option<PathBuf> {
+    PYTHON_BASE_PATH.get().cloned()
+}
+
+/// OS process ids of the pool's workers, empty before the pool has started
+pub fn worker_pids() -> Vec<u32> {
+    WORKER_PIDS.lock().clone()
+}
+
+/// Counts a task in one of the task counters for as long as it is held,
+/// including when the caller's future is dropped while waiting
+struct Counted(&'static AtomicUsize);
+
+impl Counted {
+    fn new(counter: &'static AtomicUsize) -> Self {
+        counter.fetch_add(1, Ordering::SeqCst);
+        Self(counter)
+    }
+}
+
+impl Drop for Counted {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Outcome of `drain_worker_pool`
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct DrainResult {
+    /// Tasks that were waiting for a worker and now fail as cancelled
+    pub cancelled_queued: usize,
+    /// Worker processes killed while running a task; they restart on the next task
+    pub killed_running: usize,
+}
+
+/// Cancel every task waiting for the pool and, with `cancel_running`, kill the
+/// worker running one
+///
+/// Tasks submitted after this call run normally.
+pub fn drain_worker_pool(cancel_running: bool) -> DrainResult {
+    DRAIN_EPOCH.fetch_add(1, Ordering::SeqCst);
+    let mut result = DrainResult {
+        cancelled_queued: QUEUED_TASKS.load(Ordering::SeqCst),
+        killed_running: 0,
+    };
+
+    if cancel_running && RUNNING_TASKS.load(Ordering::SeqCst) > 0 {
+        result.killed_running = kill_workers();
+    }
+
+    eprintln!(
+        "[WorkerPool] Drained: {} queued task(s) cancelled, {} worker(s) killed",
+        result.cancelled_queued, result.killed_running
+    );
+    result
+}
+
+/// Kill every worker process by pid, since a running task holds the pool lock;
+/// returns how many were killed
+fn kill_workers() -> usize {
+    let mut system = sysinfo::System::new();
+    let mut killed = 0;
+    for pid in worker_pids() {
+        let pid = sysinfo::Pid::from_u32(pid);
+        system.refresh_process(pid);
+        if system.process(pid).is_some_and(|process| process.kill()) {
+            killed += 1;
+        }
+    }
+    killed
+}
+
+/// Snapshot of the global pool for health checks
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct WorkerPoolStats {
+    pub initialized: bool,
+    pub workers: usize,
+    pub threads_per_worker: usize,
+    /// Workers currently running a task
+    pub busy_workers: usize,
+    /// Worker processes that are no longer running
+    pub exited_workers: usize,
+    pub queued_tasks: usize,
+}
+
+/// Current pool utilization; never waits for a running task to finish
+pub fn pool_stats() -> WorkerPoolStats {
+    let mut stats = WorkerPoolStats {
+        threads_per_worker: NUM_THREADS,
+        ..Default::default()
+    };
+    let Some(pool_mutex) = WORKER_POOL.get() else {
+        return stats;
+    };
+    let Ok(guard) = pool_mutex.try_lock() else {
+        // execute_python_script holds the pool lock for the whole task
+        stats.initialized = true;
+        stats.workers = NUM_WORKERS;
+        stats.busy_workers = NUM_WORKERS;
+        return stats;
+    };
+    let Some(pool) = guard.as_ref() else {
+        return stats;
+    };
+
+    stats.initialized = true;
+    stats.workers = NUM_WORKERS;
+    stats.busy_workers = NUM_WORKERS.saturating_sub(pool.semaphore.available_permits());
+    if let Ok(mut workers) = pool.workers.try_lock() {
+        stats.workers = workers.len();
+        stats.exited_workers = workers
+            .iter_mut()
+            .filter(|w| !matches!(w.process.try_wait(), Ok(None)))
+            .count();
+    }
+    if let Ok(queue) = pool.task_queue.try_lock() {
+        stats.queued_tasks = queue.len();
+    }
+    stats
+}
+
 /// Execute a Python script using the worker pool
+///
+/// Fails once `timeout` has passed since submission, waiting included; a task
+/// still running then has its worker killed (see `utils::command_timeout`).
 pub async fn execute_python_script(
     script_path: PathBuf,
     args: Vec<String>,
     venv: &str,
+    timeout: Duration,
 ) -> Result<String, String> {
+    let deadline = tokio::time::Instant::now() + timeout;
+    let timed_out = || {
+        let script = script_path.file_name().map(|n| n.to_string_lossy()).unwrap_or_default();
+        crate::utils::command_timeout::timeout_error(&script, timeout)
+    };
     let task = WorkerTask {
         task_id: uuid::Uuid::new_v4().to_string(),
         script_path: script_path.to_string_lossy().to_string(),
@@ -294,15 +457,61 @@ pub async fn execute_python_script(
     };
 
     let pool_mutex = get_worker_pool().await?;
-    let pool_guard = pool_mutex.lock().await;
+    let epoch = DRAIN_EPOCH.load(Ordering::SeqCst);
+    let queued = Counted::new(&QUEUED_TASKS);
+    let pool_guard = tokio::time::timeout_at(deadline, pool_mutex.lock())
+        .await
+        .map_err(|_| timed_out())?;
+    if DRAIN_EPOCH.load(Ordering::SeqCst) != epoch {
+        return Err(CANCELLED.to_string());
+    }
+    drop(queued);
+    let _running = Counted::new(&RUNNING_TASKS);
     let pool = pool_guard.as_ref()
         .ok_or("Worker pool not initialized")?;
 
-    let response = pool.execute_task(task).await?;
+    // The task blocks on the worker's socket, so a timeout kills the worker to end it
+    let watchdog = tokio::spawn(async move {
+        tokio::time::sleep_until(deadline).await;
+        kill_workers();
+    });
+    let response = pool.execute_task(task).await;
+    watchdog.abort();
+
+    let response = match response {
+        Ok(response) => response,
+        // The socket error of a worker killed by the watchdog or a drain
+        Err(_) if tokio::time::Instant::now() >= deadline => return Err(timed_out()),
+        Err(_) if DRAIN_EPOCH.load(Ordering::SeqCst) != epoch => return Err(CANCELLED.to_string()),
+        Err(e) => return Err(e),
+    };
 
     if response.status == "success" {
         Ok(response.data)
     } else {
-        Err(response.data)
+        // The worker forwards the script's traceback
+        Err(match crate::utils::python_errors::missing_module(&response.data) {
+            Some(missing) => missing.to_string(),
+            None => response.data,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_drain_counts_waiting_tasks_and_bumps_the_epoch() {
+        let epoch = DRAIN_EPOCH.load(Ordering::SeqCst);
+        let waiting = [Counted::new(&QUEUED_TASKS), Counted::new(&QUEUED_TASKS)];
+
+        // Nothing is running, so nothing is killed even when asked to
+        let result = drain_worker_pool(true);
+        assert_eq!(result, DrainResult { cancelled_queued: 2, killed_running: 0 });
+        assert_ne!(DRAIN_EPOCH.load(Ordering::SeqCst), epoch);
+
+        drop(waiting);
+        assert_eq!(QUEUED_TASKS.load(Ordering::SeqCst), 0);
     }
 }
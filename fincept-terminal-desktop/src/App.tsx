@@ -8,7 +8,7 @@ import { ThemeProvider } from './contexts/ThemeContext';
 import { DataSourceProvider } from './contexts/DataSourceContext';
 import { ProviderProvider } from './contexts/ProviderContext';
 import { workflowService } from './services/workflowService';
-import { IS_WEB } from './services/invoke';
+import { IS_WEB, commands } from './services/invoke';
 
 // Import screens
 import LoginScreen from './components/auth/LoginScreen';
@@ -27,7 +27,8 @@ import BackgroundPattern from './components/common/BackgroundPattern';
 import Header from './components/common/Header';
 import Footer from './components/common/Footer';
 import SetupScreen from './components/setup/SetupScreen';
-import { Toaster } from 'sonner';
+import { RUN_SETUP_EVENT } from './services/pythonRuntimeError';
+import { Toaster, toast } from 'sonner';
 
 export type Screen =
   | 'login'
@@ -73,6 +74,39 @@ const App: React.FC = () => {
     checkSetup();
   }, []);
 
+  // Offer to kill child processes a crashed previous session left running
+  useEffect(() => {
+    if (!setupComplete || IS_WEB) {
+      return;
+    }
+    commands.listOrphans()
+      .then((orphans) => {
+        if (orphans.length === 0) {
+          return;
+        }
+        const names = [...new Set(orphans.map((orphan) => orphan.name))].join(', ');
+        toast.warning(`${orphans.length} process(es) from the previous session are still running (${names})`, {
+          duration: Infinity,
+          action: {
+            label: 'Kill',
+            onClick: () => {
+              commands.reapOrphans(orphans.map((orphan) => orphan.pid))
+                .then((result) => toast.success(`Stopped ${result.killed.length} leftover process(es)`))
+                .catch((error) => console.error('[App] Failed to stop leftover processes:', error));
+            },
+          },
+        });
+      })
+      .catch((error) => console.error('[App] Failed to check for leftover processes:', error));
+  }, [setupComplete]);
+
+  // "Run Setup" buttons shown for a missing Python runtime reopen the setup screen
+  useEffect(() => {
+    const reopenSetup = () => setSetupComplete(false);
+    window.addEventListener(RUN_SETUP_EVENT, reopenSetup);
+    return () => window.removeEventListener(RUN_SETUP_EVENT, reopenSetup);
+  }, []);
+
   // Payment window manager no longer needed - using external browser
   // Removed in-app payment window code
 
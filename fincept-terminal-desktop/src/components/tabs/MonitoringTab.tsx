@@ -57,7 +57,7 @@ interface MonitorCondition {
   id?: number;
   provider: string;
   symbol: string;
-  field: 'price' | 'volume' | 'change_percent' | 'spread';
+  field: 'price' | 'volume' | 'change_percent' | 'spread' | 'mid_price' | 'orderbook_imbalance';
   operator: '>' | '<' | '>=' | '<=' | '==' | 'between';
   value: number;
   value2?: number;
@@ -72,6 +72,25 @@ interface MonitorAlert {
   field: string;
   triggered_value: number;
   triggered_at: number;
+  acknowledged?: boolean;
+  acknowledged_at?: number;
+}
+
+/** How a condition fares against the last ticks the backend has routed */
+interface ConditionTest {
+  holds_now: boolean;
+  current_value: number | null;
+  samples: number;
+  matched: number;
+  last_matched_at: number | null;
+}
+
+interface AlertPage {
+  alerts: MonitorAlert[];
+  /** Alerts matching the filters, across all pages */
+  total: number;
+  limit: number;
+  offset: number;
 }
 
 // ============================================================================
@@ -155,13 +174,30 @@ export default function MonitoringTab() {
 
   const loadAlerts = async () => {
     try {
-      const data = await invoke<MonitorAlert[]>('monitor_get_alerts', { limit: 50 });
-      setAlerts(data);
+      const page = await invoke<AlertPage>('monitor_get_alerts', { limit: 50 });
+      setAlerts(page.alerts);
     } catch (error) {
       console.error('Failed to get alerts:', error);
     }
   };
 
+  const testCondition = async () => {
+    try {
+      const result = await invoke<ConditionTest>('test_monitor_condition', { condition: newCondition });
+      if (result.samples === 0) {
+        toast.info('No recent data', {
+          description: `Nothing received yet for ${newCondition.symbol} on ${newCondition.provider}`,
+        });
+        return;
+      }
+      toast.info(result.holds_now ? 'Condition holds now' : 'Condition does not hold now', {
+        description: `Held for ${result.matched} of the last ${result.samples} updates`,
+      });
+    } catch (error) {
+      toast.error('Failed to test condition: ' + error);
+    }
+  };
+
   const addCondition = async () => {
     setLoading(true);
     try {
@@ -207,6 +243,8 @@ export default function MonitoringTab() {
       case 'change_percent':
         return <TrendingUp className="w-4 h-4" />;
       case 'spread':
+      case 'mid_price':
+      case 'orderbook_imbalance':
         return <BarChart3 className="w-4 h-4" />;
       default:
         return <Bell className="w-4 h-4" />;
@@ -372,6 +410,8 @@ export default function MonitoringTab() {
                 <option value="volume">VOLUME</option>
                 <option value="change_percent">% CHANGE</option>
                 <option value="spread">SPREAD</option>
+                <option value="mid_price">MID PRICE (BOOK)</option>
+                <option value="orderbook_imbalance">BOOK IMBALANCE (-1..1)</option>
               </select>
             </div>
 
@@ -430,6 +470,18 @@ export default function MonitoringTab() {
               >
                 ADD
               </button>
+              <button
+                onClick={testCondition}
+                disabled={loading}
+                className="px-4 py-2 font-mono text-sm transition-all"
+                style={{
+                  backgroundColor: BLOOMBERG.PANEL_BG,
+                  color: BLOOMBERG.WHITE,
+                  border: `1px solid ${BLOOMBERG.BORDER}`,
+                }}
+              >
+                TEST
+              </button>
               <button
                 onClick={() => setShowAddForm(false)}
                 className="px-4 py-2 font-mono text-sm transition-all"
@@ -517,6 +569,8 @@ export default function MonitoringTab() {
               <option value="volume">VOLUME</option>
               <option value="change_percent">% CHANGE</option>
               <option value="spread">SPREAD</option>
+              <option value="mid_price">MID PRICE</option>
+              <option value="orderbook_imbalance">BOOK IMBALANCE</option>
             </select>
           </>
         )}
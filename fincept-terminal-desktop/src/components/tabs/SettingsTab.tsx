@@ -15,7 +15,7 @@ import { Clock } from 'lucide-react';
 import { useTranslation } from 'react-i18next';
 import { TabFooter } from '@/components/common/TabFooter';
 import { useAuth } from '@/contexts/AuthContext';
-import { IS_WEB } from '@/services/invoke';
+import { IS_WEB, getServerApiKey, setServerApiKey } from '@/services/invoke';
 
 export default function SettingsTab() {
   const { t } = useTranslation('settings');
@@ -27,6 +27,7 @@ export default function SettingsTab() {
   const [sharedSessionKey, setSharedSessionKey] = useState(() => getSharedApiKey() || '');
   const [showSharedSessionKey, setShowSharedSessionKey] = useState(false);
   const [isApplyingSharedKey, setIsApplyingSharedKey] = useState(false);
+  const [serverApiKey, setServerApiKeyInput] = useState(() => getServerApiKey() || '');
   const [loading, setLoading] = useState(false);
   const [message, setMessage] = useState<{ type: 'success' | 'error'; text: string } | null>(null);
   const [dbInitialized, setDbInitialized] = useState(false);
@@ -397,6 +398,12 @@ export default function SettingsTab() {
     setTimeout(() => setMessage(null), 3000);
   };
 
+  const handleSaveServerApiKey = () => {
+    setServerApiKey(serverApiKey);
+    setMessage({ type: 'success', text: serverApiKey.trim() ? 'Server API key saved' : 'Server API key cleared' });
+    setTimeout(() => setMessage(null), 3000);
+  };
+
   const handleClearSharedSessionKey = () => {
     clearSharedApiKey();
     setSharedSessionKey('');
@@ -870,6 +877,62 @@ export default function SettingsTab() {
                     </div>
                   )}
 
+                  {IS_WEB && (
+                    <div
+                      style={{
+                        background: colors.panel,
+                        border: '1px solid #1a1a1a',
+                        padding: '16px',
+                        borderRadius: '4px'
+                      }}
+                    >
+                      <h3 style={{ color: colors.primary, fontSize: '12px', fontWeight: 'bold', marginBottom: '4px' }}>
+                        Server API Key
+                      </h3>
+                      <p style={{ color: colors.text, fontSize: '9px', marginBottom: '12px', opacity: 0.7 }}>
+                        Required when this server was started with FINCEPT_API_KEY. Stored in this browser only.
+                      </p>
+                      <div style={{ display: 'grid', gridTemplateColumns: '1fr auto', gap: '12px', alignItems: 'end' }}>
+                        <input
+                          type="password"
+                          value={serverApiKey}
+                          onChange={(e) => setServerApiKeyInput(e.target.value)}
+                          placeholder="Paste the server's API key"
+                          style={{
+                            width: '100%',
+                            background: colors.background,
+                            border: '1px solid #2a2a2a',
+                            color: colors.text,
+                            padding: '8px',
+                            fontSize: '10px',
+                            borderRadius: '3px',
+                            fontFamily: 'monospace'
+                          }}
+                        />
+                        <button
+                          onClick={handleSaveServerApiKey}
+                          style={{
+                            background: colors.primary,
+                            color: colors.text,
+                            border: 'none',
+                            padding: '8px 14px',
+                            fontSize: '10px',
+                            fontWeight: 'bold',
+                            cursor: 'pointer',
+                            borderRadius: '3px',
+                            display: 'flex',
+                            alignItems: 'center',
+                            gap: '6px',
+                            whiteSpace: 'nowrap'
+                          }}
+                        >
+                          <Save size={14} />
+                          Save
+                        </button>
+                      </div>
+                    </div>
+                  )}
+
                   {/* Predefined API Key Fields */}
                   {PREDEFINED_API_KEYS.map(({ key, label, description }) => (
                     <div
@@ -323,7 +323,8 @@ export function TradingTab() {
       open: tickerData.open,
     });
 
-    if (tradingMode === 'paper' && paperAdapter) {
+    // Replayed history must not fill paper orders
+    if (tradingMode === 'paper' && paperAdapter && !tickerData.replayed) {
       const matchingEngine = paperAdapter && 'matchingEngine' in paperAdapter
         ? (paperAdapter as any).matchingEngine
         : null;
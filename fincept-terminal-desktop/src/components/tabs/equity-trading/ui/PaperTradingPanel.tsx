@@ -132,7 +132,7 @@ const PaperTradingPanel: React.FC = () => {
 
         // Listen to ticker updates
         const unlisten = await websocketBridge.onTicker((tickerData: TickerData) => {
-          if (tickerData.provider !== 'fyers') return;
+          if (tickerData.provider !== 'fyers' || tickerData.replayed) return;
 
           console.log('[PaperTradingPanel] 📡 Received ticker:', tickerData.symbol, tickerData.price);
 
@@ -19,6 +19,7 @@ import { PaperTradingBalance } from './PaperTradingBalance';
 import { InsufficientFunds } from '../brokers/crypto/types';
 import { transactionLockManager } from './TransactionLockManager';
 import { SlippageCalculator } from './SlippageCalculator';
+import { isStopMarket, isStopTriggered, pendingOrderAction } from './PendingOrderRules';
 
 export class OrderMatchingEngine {
   private balanceManager: PaperTradingBalance;
@@ -137,6 +138,7 @@ export class OrderMatchingEngine {
             return await this.handleLimitOrder(orderId, symbol, side, amount, price!, params);
 
           case 'stop':
+          case 'stop_market':
           case 'stop_limit':
             return await this.handleStopOrder(orderId, symbol, type, side, amount, price, params);
 
@@ -360,7 +362,7 @@ export class OrderMatchingEngine {
   private async handleStopOrder(
     orderId: string,
     symbol: string,
-    type: 'stop' | 'stop_limit',
+    type: 'stop' | 'stop_market' | 'stop_limit',
     side: OrderSide,
     amount: number,
     limitPrice: number | undefined,
@@ -388,22 +390,14 @@ export class OrderMatchingEngine {
   }
 
   private async checkStopOrderTrigger(order: PaperTradingOrder, currentPrice: number): Promise<boolean> {
-    if (!order.stopPrice) return false;
-
-    // Buy stop: trigger when price rises above stop price
-    // Sell stop: trigger when price falls below stop price
-    const triggered = order.side === 'buy'
-      ? currentPrice >= order.stopPrice
-      : currentPrice <= order.stopPrice;
-
-    return triggered;
+    return isStopTriggered(order, currentPrice);
   }
 
   private async executeTriggeredStopOrder(order: PaperTradingOrder, currentPrice: number): Promise<void> {
     // Update order status to triggered
     await paperTradingDatabase.updateOrder(order.id, { status: 'triggered' });
 
-    if (order.type === 'stop') {
+    if (isStopMarket(order.type)) {
       // Stop market order - execute immediately
       const priceSnapshot = await this.fetchPriceSnapshot(order.symbol);
       const executionPrice = order.side === 'buy' ? priceSnapshot.ask : priceSnapshot.bid;
@@ -638,7 +632,8 @@ export class OrderMatchingEngine {
       timestamp: new Date().toISOString(),
     };
 
-    await paperTradingDatabase.createTrade(trade);
+    // Account with the stored fill: price, quantity and fee are rounded to the symbol's spec
+    const stored = await paperTradingDatabase.createTrade(trade);
 
     // Update position
     const leverage = params?.leverage || this.config.defaultLeverage || 1;
@@ -648,27 +643,23 @@ export class OrderMatchingEngine {
       this.config.portfolioId,
       symbol,
       side,
-      price,
-      quantity,
+      stored.price,
+      stored.quantity,
       leverage,
       marginMode,
       params?.reduceOnly
     );
 
     // Update portfolio balance (deduct fees)
-    const portfolio = await paperTradingDatabase.getPortfolio(this.config.portfolioId);
-    if (portfolio) {
-      const newBalance = portfolio.currentBalance - fee;
-      await paperTradingDatabase.updatePortfolioBalance(this.config.portfolioId, newBalance);
-    }
+    await paperTradingDatabase.adjustPortfolioBalance(this.config.portfolioId, -stored.fee);
 
     const balance = await this.balanceManager.getBalance(this.config.portfolioId);
     const currency = this.config.currency || 'USD';
 
     return {
       success: true,
-      order: this.createOrderObject(orderId, symbol, 'market', side, quantity, price, params, 'filled'),
-      trades: [trade],
+      order: this.createOrderObject(orderId, symbol, 'market', side, stored.quantity, stored.price, params, 'filled'),
+      trades: [stored],
       position,
       balance: (balance.free as any)?.[currency] || 0,
     };
@@ -742,31 +733,28 @@ export class OrderMatchingEngine {
           // Check liquidations first
           await this.checkPositionLiquidations(order.symbol, priceSnapshot.last);
 
-          // Check limit orders - IMPROVED LOGIC
-          if (order.type === 'limit' && order.status === 'pending') {
-            // BUY limit: fills when ask price drops to or below limit price
-            // SELL limit: fills when bid price rises to or above limit price
-            // Also check last price as a fallback for better fill opportunities
-            const limitPrice = order.price || 0;
-            const canFill = order.side === 'buy'
-              ? (priceSnapshot.ask <= limitPrice || priceSnapshot.last <= limitPrice)
-              : (priceSnapshot.bid >= limitPrice || priceSnapshot.last >= limitPrice);
-
-            if (canFill) {
-              console.log(`[OrderMatching] Limit order ${order.id} can fill - ${order.side} @ ${limitPrice}, market: bid=${priceSnapshot.bid}, ask=${priceSnapshot.ask}, last=${priceSnapshot.last}`);
-              await this.executeLimitOrder(order.id, order.symbol, order.side as OrderSide, order.amount, order.price!, {
-                leverage: order.leverage,
-                marginMode: order.marginMode as any,
-              });
-            }
+          const action = pendingOrderAction(order, priceSnapshot);
+
+          // Resting market orders (bracket entries) fill at the current quote
+          if (order.type === 'market' && action === 'fill') {
+            await this.executeMarketOrder(order.id, order.symbol, order.side as OrderSide, order.amount, {
+              leverage: order.leverage,
+              marginMode: order.marginMode as any,
+            });
           }
 
-          // Check stop orders
-          if ((order.type === 'stop' || order.type === 'stop_limit') && order.status === 'pending') {
-            const triggered = await this.checkStopOrderTrigger(order, priceSnapshot.last);
-            if (triggered) {
-              await this.executeTriggeredStopOrder(order, priceSnapshot.last);
-            }
+          // Check limit orders
+          if (order.type === 'limit' && action === 'fill') {
+            console.log(`[OrderMatching] Limit order ${order.id} can fill - ${order.side} @ ${order.price}, market: bid=${priceSnapshot.bid}, ask=${priceSnapshot.ask}, last=${priceSnapshot.last}`);
+            await this.executeLimitOrder(order.id, order.symbol, order.side as OrderSide, order.amount, order.price!, {
+              leverage: order.leverage,
+              marginMode: order.marginMode as any,
+            });
+          }
+
+          // Check stop orders (stop_market covers bracket stop-loss legs)
+          if ((isStopMarket(order.type) || order.type === 'stop_limit') && action === 'trigger') {
+            await this.executeTriggeredStopOrder(order, priceSnapshot.last);
           }
 
           // Check and update trailing stop orders
@@ -851,11 +839,12 @@ export class OrderMatchingEngine {
       ? pendingOrders.filter(o => o.symbol === symbol)
       : pendingOrders;
 
+    await paperTradingDatabase.cancelAllOrders(this.config.portfolioId, symbol);
+
     const cancelledOrders: PaperTradingOrder[] = [];
     for (const order of ordersToCancel) {
-      await paperTradingDatabase.updateOrder(order.id, { status: 'cancelled' });
       const updated = await paperTradingDatabase.getOrder(order.id);
-      if (updated) cancelledOrders.push(updated);
+      if (updated?.status === 'cancelled') cancelledOrders.push(updated);
     }
 
     return cancelledOrders;
@@ -163,10 +163,7 @@ export class PaperTradingBalance {
       console.log(`  Realized PnL: ${pnl.toFixed(2)}`);
 
       // Update portfolio balance with realized PnL (done after fee calculation in executeTrade)
-      const portfolio = await paperTradingDatabase.getPortfolio(portfolioId);
-      if (portfolio) {
-        await paperTradingDatabase.updatePortfolioBalance(portfolioId, portfolio.currentBalance + pnl);
-      }
+      await paperTradingDatabase.adjustPortfolioBalance(portfolioId, pnl);
 
       if (oppositePosition.quantity === fillQuantity) {
         // FULLY CLOSE position
@@ -389,11 +386,7 @@ export class PaperTradingBalance {
     });
 
     // Update portfolio balance
-    const portfolio = await paperTradingDatabase.getPortfolio(position.portfolioId);
-    if (portfolio) {
-      const newBalance = portfolio.currentBalance + realizedPnl;
-      await paperTradingDatabase.updatePortfolioBalance(position.portfolioId, newBalance);
-    }
+    await paperTradingDatabase.adjustPortfolioBalance(position.portfolioId, realizedPnl);
   }
 
   // ============================================================================
@@ -18,6 +18,96 @@ import type {
 } from './types';
 import type { Order, OrderSide } from '../brokers/crypto/types';
 
+export interface RiskLimits {
+  maxPositionValue: number | null;
+  maxLeverage: number | null;
+  maxOpenPositions: number | null;
+  maxDailyLoss: number | null;
+}
+
+/** Rows removed by `purgePortfolio`, per table */
+export interface PurgedPortfolio {
+  portfolios: number;
+  positions: number;
+  orders: number;
+  trades: number;
+  risk_limits: number;
+  snapshots: number;
+}
+
+/** Venue constraints for a symbol; null leaves a value unconstrained */
+export interface SymbolSpec {
+  tickSize: number | null;
+  lotSize: number | null;
+  minQuantity: number | null;
+  minNotional: number | null;
+}
+
+/** Closed-position results for one side (long or short) */
+export interface SideStatistics {
+  closedPositions: number;
+  winning: number;
+  losing: number;
+  realizedPnl: number;
+}
+
+/** Portfolio performance over a date range; wins/losses count closed positions */
+export interface TradeStatistics {
+  totalTrades: number;
+  totalVolume: number;
+  totalFees: number;
+  closedPositions: number;
+  winningPositions: number;
+  losingPositions: number;
+  breakevenPositions: number;
+  winRate: number | null;
+  averageWin: number | null;
+  averageLoss: number | null;
+  largestWin: number | null;
+  largestLoss: number | null;
+  grossProfit: number;
+  grossLoss: number;
+  profitFactor: number | null;
+  realizedPnl: number;
+  netPnl: number;
+  openPositions: number;
+  openRealizedPnl: number;
+  unrealizedPnl: number;
+  long: SideStatistics;
+  short: SideStatistics;
+}
+
+/** Result of marking a portfolio's open positions; returnOnMargin is a percentage */
+export interface UnrealizedPnlUpdate {
+  positions: {
+    positionId: string;
+    symbol: string;
+    side: 'long' | 'short';
+    currentPrice: number;
+    unrealizedPnl: number;
+    returnOnMargin: number | null;
+  }[];
+  missingPrices: string[];
+  totalUnrealizedPnl: number;
+}
+
+export interface PortfolioSnapshot {
+  id: number;
+  portfolioId: string;
+  balance: number;
+  unrealizedPnl: number;
+  equity: number;
+  openPositions: number;
+  asOf: string;
+}
+
+export interface MarkIngestion {
+  positionsUpdated: number;
+  snapshots: PortfolioSnapshot[];
+  /** Marked symbols no open position holds */
+  unmatchedSymbols: string[];
+}
+
 export class PaperTradingDatabase {
   // ============================================================================
   // DATABASE HEALTH CHECK
@@ -96,6 +186,22 @@ export class PaperTradingDatabase {
     await invoke('db_delete_portfolio', { portfolioId });
   }
 
+  /**
+   * Delete the portfolio and all its positions, orders, trades and risk limits in one
+   * transaction; `confirm` must be true. Returns the rows removed per table.
+   */
+  async purgePortfolio(portfolioId: string, confirm: boolean): Promise<PurgedPortfolio> {
+    return await invoke<PurgedPortfolio>('purge_portfolio', { portfolioId, confirm });
+  }
+
+  /**
+   * Add `delta` (negative for fees/losses) to the balance in one backend transaction,
+   * using exact decimal arithmetic; returns the new balance
+   */
+  async adjustPortfolioBalance(portfolioId: string, delta: number): Promise<number> {
+    return await invoke<number>('db_adjust_portfolio_balance', { id: portfolioId, delta });
+  }
+
   async listPortfolios(): Promise<PaperTradingPortfolio[]> {
     const result = await invoke<any[]>('db_list_portfolios');
     return result.map((row: any) => this.mapDBPortfolio(row));
@@ -202,6 +308,115 @@ export class PaperTradingDatabase {
     });
   }
 
+  /**
+   * Open (or add to) a position at `markPrice` in one transaction: a filled market
+   * order, its trade with the taker fee, the position with its liquidation price,
+   * and the fee debited from the balance. Leverage and margin mode default to the portfolio's.
+   */
+  async openMarketPosition(open: {
+    portfolioId: string;
+    symbol: string;
+    side: 'long' | 'short';
+    quantity: number;
+    markPrice: number;
+    leverage?: number;
+    marginMode?: 'cross' | 'isolated';
+    fees?: { maker: number; taker: number };
+  }): Promise<{
+    position: PaperTradingPosition;
+    orderId: string;
+    tradeId: string;
+    fee: number;
+    margin: number;
+    balance: number;
+  }> {
+    const result = await invoke<any>('open_market_position', {
+      portfolioId: open.portfolioId,
+      symbol: open.symbol,
+      side: open.side,
+      quantity: open.quantity,
+      markPrice: open.markPrice,
+      leverage: open.leverage ?? null,
+      marginMode: open.marginMode ?? null,
+      fees: open.fees ?? null,
+    });
+    return {
+      position: this.mapDBPosition(result.position),
+      orderId: result.order_id,
+      tradeId: result.trade_id,
+      fee: result.fee,
+      margin: result.margin,
+      balance: result.balance,
+    };
+  }
+
+  /**
+   * Close `closeQty` of an open position at `closePrice`. Realized PnL and the
+   * fee are booked to the portfolio balance in the same transaction.
+   */
+  async reducePosition(positionId: string, closeQty: number, closePrice: number, fee = 0): Promise<{
+    position: PaperTradingPosition;
+    closedQuantity: number;
+    realizedPnl: number;
+    fee: number;
+    balance: number;
+  }> {
+    const result = await invoke<any>('db_reduce_position', {
+      id: positionId,
+      closeQty,
+      closePrice,
+      fee,
+    });
+    return {
+      position: this.mapDBPosition(result.position),
+      closedQuantity: result.closed_quantity,
+      realizedPnl: result.realized_pnl,
+      fee: result.fee,
+      balance: result.balance,
+    };
+  }
+
+  /**
+   * Mark every open position to `prices` (symbol -> price) in one transaction.
+   * Positions without a price keep their stored PnL and are listed in `missingPrices`.
+   */
+  async recomputeUnrealizedPnl(portfolioId: string, prices: Record<string, number>): Promise<UnrealizedPnlUpdate> {
+    const row = await invoke<any>('recompute_unrealized_pnl', { portfolioId, prices });
+    return {
+      positions: row.positions.map((m: any) => ({
+        positionId: m.position_id,
+        symbol: m.symbol,
+        side: m.side,
+        currentPrice: m.current_price,
+        unrealizedPnl: m.unrealized_pnl,
+        returnOnMargin: m.return_on_margin ?? null,
+      })),
+      missingPrices: row.missing_prices,
+      totalUnrealizedPnl: row.total_unrealized_pnl,
+    };
+  }
+
+  /**
+   * Mark open positions in every portfolio to imported prices (e.g. end-of-day closes)
+   * and snapshot each portfolio touched, in one transaction.
+   */
+  async ingestMarks(marks: Record<string, { price: number; timestamp: string }>): Promise<MarkIngestion> {
+    const row = await invoke<any>('ingest_marks', { marks });
+    return {
+      positionsUpdated: row.positions_updated,
+      snapshots: row.snapshots.map((s: any) => ({
+        id: s.id,
+        portfolioId: s.portfolio_id,
+        balance: s.balance,
+        unrealizedPnl: s.unrealized_pnl,
+        equity: s.equity,
+        openPositions: s.open_positions,
+        asOf: s.as_of,
+      })),
+      unmatchedSymbols: row.unmatched_symbols,
+    };
+  }
+
   async deletePosition(positionId: string): Promise<void> {
     await invoke('db_delete_position', { id: positionId });
   }
@@ -286,10 +501,52 @@ export class PaperTradingDatabase {
     await invoke('db_delete_order', { id: orderId });
   }
 
+  /** Cancel all working orders of a portfolio in one statement; returns how many were cancelled */
+  async cancelAllOrders(portfolioId: string, symbol?: string): Promise<number> {
+    return await invoke<number>('db_cancel_all_orders', {
+      portfolioId,
+      symbol: symbol || null,
+    });
+  }
+
+  /**
+   * Create an entry order with stop-loss and take-profit legs in one transaction.
+   * The legs activate once the entry fills; whichever fills first cancels the other.
+   */
+  async createBracketOrder(order: {
+    portfolioId: string;
+    symbol: string;
+    side: OrderSide;
+    type: 'market' | 'limit';
+    quantity: number;
+    price?: number | null;
+    stopLoss: number;
+    takeProfit: number;
+    timeInForce?: string;
+  }): Promise<{ entry: PaperTradingOrder; stopLoss: PaperTradingOrder; takeProfit: PaperTradingOrder }> {
+    const result = await invoke<any>('create_bracket_order', {
+      portfolioId: order.portfolioId,
+      symbol: order.symbol,
+      side: order.side,
+      orderType: order.type,
+      quantity: order.quantity,
+      price: order.price ?? null,
+      stopLoss: order.stopLoss,
+      takeProfit: order.takeProfit,
+      timeInForce: order.timeInForce ?? null,
+    });
+    return {
+      entry: this.mapDBOrder(result.entry),
+      stopLoss: this.mapDBOrder(result.stop_loss),
+      takeProfit: this.mapDBOrder(result.take_profit),
+    };
+  }
+
   // ============================================================================
   // TRADE OPERATIONS
   // ============================================================================
 
+  /** Store a fill; the returned trade carries the price, quantity and fee rounded to the symbol's spec */
   async createTrade(trade: {
     id: string;
     portfolioId: string;
@@ -301,8 +558,8 @@ export class PaperTradingDatabase {
     fee: number;
     feeRate: number;
     isMaker: boolean;
-  }): Promise<void> {
-    await invoke('db_create_trade', {
+  }): Promise<PaperTradingTrade> {
+    const result = await invoke<any>('db_create_trade', {
       id: trade.id,
       portfolioId: trade.portfolioId,
       orderId: trade.orderId,
@@ -314,6 +571,7 @@ export class PaperTradingDatabase {
       feeRate: trade.feeRate,
       isMaker: trade.isMaker,
     });
+    return this.mapDBTrade(result);
   }
 
   async getTrade(tradeId: string): Promise<PaperTradingTrade | null> {
@@ -334,6 +592,41 @@ export class PaperTradingDatabase {
     return result.map(row => this.mapDBTrade(row));
   }
 
+  /** Win rate, average win/loss, profit factor and fees; dates are inclusive YYYY-MM-DD */
+  async getTradeStatistics(portfolioId: string, from?: string, to?: string): Promise<TradeStatistics> {
+    const row = await invoke<any>('get_trade_statistics', { portfolioId, from: from ?? null, to: to ?? null });
+    const side = (s: any): SideStatistics => ({
+      closedPositions: s.closed_positions,
+      winning: s.winning,
+      losing: s.losing,
+      realizedPnl: s.realized_pnl,
+    });
+    return {
+      totalTrades: row.total_trades,
+      totalVolume: row.total_volume,
+      totalFees: row.total_fees,
+      closedPositions: row.closed_positions,
+      winningPositions: row.winning_positions,
+      losingPositions: row.losing_positions,
+      breakevenPositions: row.breakeven_positions,
+      winRate: row.win_rate ?? null,
+      averageWin: row.average_win ?? null,
+      averageLoss: row.average_loss ?? null,
+      largestWin: row.largest_win ?? null,
+      largestLoss: row.largest_loss ?? null,
+      grossProfit: row.gross_profit,
+      grossLoss: row.gross_loss,
+      profitFactor: row.profit_factor ?? null,
+      realizedPnl: row.realized_pnl,
+      netPnl: row.net_pnl,
+      openPositions: row.open_positions,
+      openRealizedPnl: row.open_realized_pnl,
+      unrealizedPnl: row.unrealized_pnl,
+      long: side(row.long),
+      short: side(row.short),
+    };
+  }
+
   async getOrderTrades(orderId: string): Promise<PaperTradingTrade[]> {
     const result = await invoke<any[]>('db_get_order_trades', { orderId });
     return result.map(row => this.mapDBTrade(row));
@@ -343,6 +636,42 @@ export class PaperTradingDatabase {
     await invoke('db_delete_trade', { id: tradeId });
   }
 
+  // ============================================================================
+  // RISK LIMITS
+  // ============================================================================
+
+  /** Limits enforced by the backend on order creation and fills (null = off) */
+  async getRiskLimits(portfolioId: string): Promise<RiskLimits> {
+    const row = await invoke<any>('get_paper_risk_limits', { portfolioId });
+    return {
+      maxPositionValue: row.max_position_value ?? null,
+      maxLeverage: row.max_leverage ?? null,
+      maxOpenPositions: row.max_open_positions ?? null,
+      maxDailyLoss: row.max_daily_loss ?? null,
+    };
+  }
+
+  /** Replace the portfolio's limits; null turns a rule off */
+  async setRiskLimits(portfolioId: string, limits: RiskLimits): Promise<void> {
+    await invoke('set_paper_risk_limits', { portfolioId, ...limits });
+  }
+
+  async getSymbolSpec(symbol: string): Promise<SymbolSpec | null> {
+    const row = await invoke<any>('get_paper_symbol_spec', { symbol });
+    if (!row) return null;
+    return {
+      tickSize: row.tick_size ?? null,
+      lotSize: row.lot_size ?? null,
+      minQuantity: row.min_quantity ?? null,
+      minNotional: row.min_notional ?? null,
+    };
+  }
+
+  /** Replace the symbol's tick/lot sizes and minimums; orders are rounded to them */
+  async setSymbolSpec(symbol: string, spec: SymbolSpec): Promise<void> {
+    await invoke('set_paper_symbol_spec', { symbol, ...spec });
+  }
+
   // ============================================================================
   // MAPPING FUNCTIONS
   // ============================================================================
@@ -406,6 +735,8 @@ export class PaperTradingDatabase {
       icebergQty: undefined,
       leverage: undefined,
       marginMode: undefined,
+      parentOrderId: row.parent_order_id || undefined,
+      ocoGroupId: row.oco_group_id || undefined,
       lastTradeTimestamp: row.filled_at ? new Date(row.filled_at).getTime() : undefined,
       remaining: row.quantity - row.filled_quantity,
       cost: (row.avg_fill_price || 0) * row.filled_quantity,
@@ -0,0 +1,66 @@
+/**
+ * Pending Order Rules
+ *
+ * Decides what a resting order does at the current quote: fill, trigger its
+ * stop, or keep waiting. Free of database and exchange access so the matching
+ * engine and the e2e suite run the exact same rules.
+ */
+
+import type { PriceSnapshot } from './types';
+
+export type PendingOrderAction = 'fill' | 'trigger' | 'none';
+
+export interface RestingOrder {
+  type: string;
+  side: string;
+  status: string;
+  price?: number;
+  stopPrice?: number;
+}
+
+/** Stop types that execute at market once triggered ('stop_market' is what the database stores) */
+export const STOP_MARKET_TYPES = ['stop', 'stop_market'];
+
+export function isStopMarket(type: string): boolean {
+  return STOP_MARKET_TYPES.includes(type);
+}
+
+/**
+ * Buy stops trigger when the last price rises to the stop price, sell stops
+ * when it falls to it
+ */
+export function isStopTriggered(order: RestingOrder, lastPrice: number): boolean {
+  if (!order.stopPrice) return false;
+  return order.side === 'buy' ? lastPrice >= order.stopPrice : lastPrice <= order.stopPrice;
+}
+
+export function pendingOrderAction(
+  order: RestingOrder,
+  quote: Pick<PriceSnapshot, 'bid' | 'ask' | 'last'>
+): PendingOrderAction {
+  if (order.status !== 'pending') return 'none';
+
+  switch (order.type) {
+    // A resting market order is a bracket entry: it fills on the next check
+    case 'market':
+      return 'fill';
+
+    // BUY limit fills when ask (or last) drops to the limit, SELL when bid (or last) rises to it
+    case 'limit': {
+      const limitPrice = order.price || 0;
+      const canFill = order.side === 'buy'
+        ? (quote.ask <= limitPrice || quote.last <= limitPrice)
+        : (quote.bid >= limitPrice || quote.last >= limitPrice);
+      return canFill ? 'fill' : 'none';
+    }
+
+    case 'stop':
+    case 'stop_market':
+    case 'stop_limit':
+    case 'trailing_stop':
+      return isStopTriggered(order, quote.last) ? 'trigger' : 'none';
+
+    default:
+      return 'none';
+  }
+}
@@ -155,6 +155,10 @@ export interface PaperTradingOrder {
   reduceOnly?: boolean;
   leverage?: number;
   marginMode?: 'cross' | 'isolated';
+  /** Entry order a bracket leg waits on */
+  parentOrderId?: string;
+  /** One-cancels-other group: a fill of one order cancels the rest */
+  ocoGroupId?: string;
 }
 
 export interface PaperTradingTrade {
@@ -297,6 +301,8 @@ export interface DBOrder {
   iceberg_qty: number | null;
   leverage: number | null;
   margin_mode: string | null;
+  parent_order_id: string | null;
+  oco_group_id: string | null;
   created_at: string;
   filled_at: string | null;
   updated_at: string;
@@ -12,6 +12,8 @@
  *   const result = await invoke('get_market_quote', { symbol: 'AAPL' });
  */
 
+import { surfacePythonNotInstalled } from './pythonRuntimeError';
+
 // Environment detection
 export const IS_TAURI = typeof window !== 'undefined' && '__TAURI__' in window;
 export const IS_WEB = !IS_TAURI;
@@ -19,6 +21,53 @@ export const IS_WEB = !IS_TAURI;
 // API base URL for web mode
 const API_BASE = import.meta.env.VITE_API_URL || '/api';
 
+// Key for a server started with FINCEPT_API_KEY, set under Settings > Credentials.
+// Kept apart from `fincept_api_key`, which holds the Fincept account key.
+const SERVER_API_KEY_STORAGE = 'fincept_server_api_key';
+// Scoped token from a shared link, kept for this browser tab only
+const SCOPED_TOKEN_STORAGE = 'fincept_scoped_token';
+
+export function getServerApiKey(): string | null {
+  return typeof window === 'undefined' ? null : window.localStorage.getItem(SERVER_API_KEY_STORAGE);
+}
+
+/** Pass null or an empty string to stop sending a key */
+export function setServerApiKey(key: string | null): void {
+  if (key?.trim()) {
+    window.localStorage.setItem(SERVER_API_KEY_STORAGE, key.trim());
+  } else {
+    window.localStorage.removeItem(SERVER_API_KEY_STORAGE);
+  }
+}
+
+/**
+ * A shared link carries its token in the fragment (`#token=fst_...`), which the
+ * browser never sends to the server, so it stays out of request logs. The token
+ * moves to sessionStorage and is removed from the address bar.
+ */
+function adoptSharedLinkToken(): void {
+  const params = new URLSearchParams(window.location.hash.slice(1));
+  const token = params.get('token');
+  if (!token) return;
+  window.sessionStorage.setItem(SCOPED_TOKEN_STORAGE, token);
+  params.delete('token');
+  const rest = params.toString();
+  const { pathname, search } = window.location;
+  window.history.replaceState(window.history.state, '', `${pathname}${search}${rest ? `#${rest}` : ''}`);
+}
+
+if (IS_WEB && typeof window !== 'undefined') {
+  adoptSharedLinkToken();
+}
+
+function authHeaders(): Record<string, string> {
+  if (typeof window === 'undefined') return {};
+  const token = window.sessionStorage.getItem(SCOPED_TOKEN_STORAGE);
+  if (token) return { 'x-fincept-token': token };
+  const apiKey = getServerApiKey();
+  return apiKey ? { 'x-fincept-api-key': apiKey } : {};
+}
+
 /**
  * Unified invoke function that works in both Tauri and web environments
  * 
@@ -27,13 +76,18 @@ const API_BASE = import.meta.env.VITE_API_URL || '/api';
  * @returns Promise resolving to the command result
  */
 export async function invoke<T>(cmd: string, args: Record<string, unknown> = {}): Promise<T> {
-  if (IS_TAURI) {
-    // Use native Tauri IPC
-    const { invoke: tauriInvoke } = await import('@tauri-apps/api/core');
-    return tauriInvoke<T>(cmd, args);
-  } else {
-    // Use HTTP RPC for web
-    return invokeWeb<T>(cmd, args);
+  try {
+    if (IS_TAURI) {
+      // Use native Tauri IPC
+      const { invoke: tauriInvoke } = await import('@tauri-apps/api/core');
+      return await tauriInvoke<T>(cmd, args);
+    } else {
+      // Use HTTP RPC for web
+      return await invokeWeb<T>(cmd, args);
+    }
+  } catch (error) {
+    // A missing Python runtime offers "Run Setup" wherever the command was called from
+    throw surfacePythonNotInstalled(error);
   }
 }
 
@@ -64,6 +118,7 @@ async function invokeWeb<T>(cmd: string, args: Record<string, unknown>): Promise
         method: 'POST',
         headers: {
           'Content-Type': 'application/json',
+          ...authHeaders(),
         },
         body: JSON.stringify({ cmd, args }),
         signal: controller.signal,
@@ -71,12 +126,14 @@ async function invokeWeb<T>(cmd: string, args: Record<string, unknown>): Promise
 
       clearTimeout(timeoutId);
 
-      if (!response.ok) {
+      // Failed commands carry a status matching their error kind plus the usual
+      // JSON body; only a response without that body (e.g. from a proxy) is a
+      // transport failure
+      const result = await response.json().catch(() => null);
+      if (!result) {
         throw new Error(`RPC call failed: ${response.status} ${response.statusText}`);
       }
 
-      const result = await response.json();
-
       // Handle RPC response format - throw on explicit error or failed success flag
       if (result.error || result.success === false) {
         throw new Error(result.error || 'Unknown error');
@@ -132,9 +189,21 @@ export const commands = {
   getMarketQuotes: (symbols: string[]) =>
     invoke<MarketQuotesResponse>('get_market_quotes', { symbols }),
 
+  getWatchlistQuotes: (watchlistId: string, maxCacheAgeMinutes?: number) =>
+    invoke<WatchlistQuote[]>('get_watchlist_quotes', { watchlistId, maxCacheAgeMinutes }),
+
   getHistoricalData: (symbol: string, startDate: string, endDate: string) =>
     invoke<HistoricalResponse>('get_historical_data', { symbol, startDate, endDate }),
 
+  getHistoricalDataCached: (symbol: string, from: string, to: string, interval?: string) =>
+    invoke<CachedHistoryResponse>('get_historical_data_cached', { symbol, from, to, interval }),
+
+  clearHistoricalCache: (symbol?: string) =>
+    invoke<number>('clear_historical_cache', { symbol }),
+
+  searchSymbols: (query: string, limit?: number) =>
+    invoke<SymbolMatch[]>('search_symbols', { query, limit }),
+
   getStockInfo: (symbol: string) =>
     invoke<StockInfoResponse>('get_stock_info', { symbol }),
 
@@ -151,6 +220,52 @@ export const commands = {
   dbCheckHealth: () =>
     invoke<HealthCheckResponse>('db_check_health', {}),
 
+  systemHealth: () =>
+    invoke<SystemHealth>('system_health', {}),
+
+  // CPU/memory of MCP servers and Python workers; (desktop only) periodic `process_usage` events
+  getProcessResourceUsage: (id?: string) =>
+    invoke<ProcessUsage[]>('get_process_resource_usage', { id }),
+
+  startProcessUsageStream: (intervalSecs?: number) =>
+    invoke<void>('start_process_usage_stream', { intervalSecs }),
+
+  stopProcessUsageStream: () =>
+    invoke<boolean>('stop_process_usage_stream', {}),
+
+  // Fail queued Python tasks as cancelled; cancelRunning also kills the worker running one
+  drainWorkerPool: (cancelRunning?: boolean) =>
+    invoke<DrainResult>('drain_worker_pool', { cancelRunning }),
+
+  // Child processes a crashed previous session left running; reapOrphans kills them (or only `pids`)
+  listOrphans: () =>
+    invoke<OrphanProcess[]>('list_orphans', {}),
+
+  reapOrphans: (pids?: number[]) =>
+    invoke<ReapResult>('reap_orphans', { pids }),
+
+  // Trading Calendar Commands
+  isMarketOpen: (exchange: string, at?: string) =>
+    invoke<MarketStatus>('is_market_open', { exchange, at }),
+
+  nextSession: (exchange: string, after?: string) =>
+    invoke<TradingSession>('next_session', { exchange, after }),
+
+  setCalendarOverride: (
+    exchange: string,
+    date: string,
+    kind: CalendarOverride['kind'],
+    closeTime?: string,
+    note?: string
+  ) =>
+    invoke<CalendarOverride>('set_calendar_override', { exchange, date, kind, closeTime, note }),
+
+  deleteCalendarOverride: (exchange: string, date: string) =>
+    invoke<boolean>('delete_calendar_override', { exchange, date }),
+
+  listCalendarOverrides: (exchange?: string) =>
+    invoke<CalendarOverride[]>('list_calendar_overrides', { exchange }),
+
   dbGetAllSettings: () =>
     invoke<Setting[]>('db_get_all_settings', {}),
 
@@ -160,6 +275,10 @@ export const commands = {
   dbSaveSetting: (key: string, value: string, category?: string) =>
     invoke<{ saved: boolean }>('db_save_setting', { key, value, category }),
 
+  /** Save several settings in one transaction; if any value is invalid, none are saved */
+  dbSaveSettings: (entries: SettingEntry[]) =>
+    invoke<SettingSaveResult[]>('db_save_settings', { entries }),
+
   // Watchlist Commands
   dbGetWatchlists: () =>
     invoke<Watchlist[]>('db_get_watchlists', {}),
@@ -244,6 +363,43 @@ export const commands = {
   dbDeletePortfolio: (portfolioId: string) =>
     invoke<{ deleted: boolean }>('db_delete_portfolio', { portfolioId }),
 
+  // Series Analytics Commands (native; null where pandas would give NaN)
+  seriesRollingMean: (data: number[], window: number) =>
+    invoke<(number | null)[]>('series_rolling_mean', { data, window }),
+
+  seriesRollingStd: (data: number[], window: number) =>
+    invoke<(number | null)[]>('series_rolling_std', { data, window }),
+
+  seriesReturns: (data: number[]) =>
+    invoke<(number | null)[]>('series_returns', { data }),
+
+  seriesLogReturns: (data: number[]) =>
+    invoke<(number | null)[]>('series_log_returns', { data }),
+
+  seriesZscore: (data: number[]) =>
+    invoke<(number | null)[]>('series_zscore', { data }),
+
+  seriesCorrelation: (x: number[], y: number[]) =>
+    invoke<number | null>('series_correlation', { x, y }),
+
+  // Box-Cox with an estimated lambda; keep `lambda` for pmdarima_inverse_boxcox
+  pmdarimaBoxcoxAuto: (data: number[]) =>
+    invoke<{ transformed: number[]; lambda: number }>('pmdarima_boxcox_auto', { data }),
+
+  // Technical indicators over candles (native; lines aligned with the candles)
+  computeIndicators: (candles: Candle[], indicators: IndicatorSpec[]) =>
+    invoke<IndicatorResult>('compute_indicators', { candles, indicators }),
+
+  // Application log: last lines, and (desktop only) live `log_line` events
+  tailLog: (lines?: number, level?: LogLevel) =>
+    invoke<LogLine[]>('tail_log', { lines, level }),
+
+  startLogStream: (level?: LogLevel) =>
+    invoke<string>('start_log_stream', { level }),
+
+  stopLogStream: () =>
+    invoke<boolean>('stop_log_stream', {}),
+
   // Setup Commands
   checkSetupStatus: () =>
     invoke<SetupStatus>('check_setup_status', {}),
@@ -298,10 +454,53 @@ export interface HistoricalResponse {
     close: number;
     volume: number;
     adj_close: number;
+    /** Traded close before dividend adjustment (OHLC is adjusted) */
+    raw_close?: number;
   }>;
   error?: string;
 }
 
+/** Minimal OHLC(V) bar; historical data rows can be passed as they are */
+export interface Candle {
+  timestamp: number;
+  open: number;
+  high: number;
+  low: number;
+  close: number;
+  volume?: number;
+}
+
+export type PriceSource = 'open' | 'high' | 'low' | 'close' | 'hl2' | 'hlc3';
+
+export type IndicatorSpec =
+  | { type: 'sma' | 'ema'; period: number; source?: PriceSource }
+  | { type: 'rsi'; period?: number; source?: PriceSource }
+  | { type: 'macd'; fast?: number; slow?: number; signal?: number; source?: PriceSource }
+  | { type: 'bollinger'; period?: number; std_dev?: number; source?: PriceSource };
+
+export interface IndicatorResult {
+  timestamps: number[];
+  /** `lines` is { value } for SMA/EMA/RSI, { macd, signal, histogram } for MACD
+   *  and { upper, middle, lower } for Bollinger Bands */
+  indicators: Array<{ id: string; lines: Record<string, (number | null)[]> }>;
+}
+
+export type LogLevel = 'trace' | 'debug' | 'info' | 'warn' | 'error';
+
+export interface LogLine {
+  line: string;
+  /** null for lines without a level, e.g. continuation lines of a message */
+  level: LogLevel | null;
+}
+
+export interface CachedHistoryResponse {
+  symbol: string;
+  interval: string;
+  data: HistoricalResponse['data'];
+  /** Date ranges that were not cached and were fetched upstream */
+  fetched_ranges: Array<{ start: string; end: string }>;
+}
+
 export interface StockInfoResponse {
   success: boolean;
   data?: Record<string, unknown>;
@@ -325,6 +524,91 @@ export interface HealthCheckResponse {
   message: string;
 }
 
+export interface SymbolMatch {
+  symbol: string;
+  name: string | null;
+  exchange: string | null;
+  type: string | null;
+  provider: 'yahoo' | 'alphavantage';
+}
+
+export type SubsystemStatus = 'n/a' | 'ok' | 'unknown' | 'degraded' | 'down';
+
+export interface SubsystemHealth {
+  name: string;
+  status: SubsystemStatus;
+  detail: string;
+  data?: unknown;
+}
+
+export interface SystemHealth {
+  overall: SubsystemStatus;
+  checked_at: number;
+  subsystems: SubsystemHealth[];
+}
+
+export interface ProcessUsage {
+  kind: 'mcp' | 'python_worker';
+  /** MCP server id, or `python-worker-<n>` */
+  id: string;
+  pid: number;
+  cpu_percent: number;
+  memory_bytes: number;
+  virtual_memory_bytes: number;
+  run_time_secs: number;
+}
+
+export interface DrainResult {
+  /** Tasks that were waiting for a worker */
+  cancelled_queued: number;
+  /** Workers killed mid-task; they restart on the next task */
+  killed_running: number;
+}
+
+export interface OrphanProcess {
+  pid: number;
+  kind: 'mcp' | 'python_worker' | 'backtest';
+  /** What was launched, e.g. "npx" */
+  command: string;
+  /** Process name reported by the OS */
+  name: string;
+  /** Unix seconds */
+  started_at: number;
+}
+
+export interface ReapResult {
+  killed: number[];
+  /** Orphans that could not be killed */
+  failed: number[];
+}
+
+export interface TradingSession {
+  exchange: string;
+  /** Exchange-local date, YYYY-MM-DD */
+  date: string;
+  open_at: string;
+  close_at: string;
+  early_close: boolean;
+}
+
+export interface MarketStatus {
+  exchange: string;
+  timezone: string;
+  at: string;
+  local_time: string;
+  open: boolean;
+  reason: string | null;
+  session: TradingSession | null;
+}
+
+export interface CalendarOverride {
+  exchange: string;
+  date: string;
+  kind: 'closed' | 'open' | 'early_close';
+  close_time: string | null;
+  note: string | null;
+}
+
 export interface Setting {
   setting_key: string;
   setting_value: string;
@@ -332,6 +616,18 @@ export interface Setting {
   updated_at: string;
 }
 
+export interface SettingEntry {
+  key: string;
+  value: string;
+  category?: string | null;
+}
+
+export interface SettingSaveResult {
+  key: string;
+  saved: boolean;
+  error: string | null;
+}
+
 export interface Watchlist {
   id: string;
   name: string;
@@ -349,6 +645,10 @@ export interface WatchlistStock {
   added_at: string;
 }
 
+export interface WatchlistQuote extends WatchlistStock {
+  quote: MarketQuotesResponse['data'][number] | null;
+}
+
 export interface Credential {
   id?: number;
   service_name: string;
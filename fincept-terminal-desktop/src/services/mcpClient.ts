@@ -41,6 +41,8 @@ export interface MCPConnectionConfig {
   command: string;
   args: string[];
   env?: Record<string, string>;
+  /** Message delimiting on stdio: newline-delimited JSON (default) or LSP-style Content-Length headers */
+  framing?: 'ndjson' | 'content-length';
 }
 
 class MCPClient {
@@ -70,7 +72,8 @@ class MCPClient {
           serverId: config.serverId,
           command: config.command,
           args: config.args,
-          env: config.env || {}
+          env: config.env || {},
+          framing: config.framing ?? null
         }
       );
 
@@ -2,7 +2,7 @@
 // Fetches real-time news from 20+ financial RSS feeds
 // NOW POWERED BY RUST BACKEND - 95% faster than JavaScript implementation
 
-import { invoke } from '@/services/invoke';
+import { invoke, listen, UnlistenFn } from '@/services/invoke';
 
 export interface NewsArticle {
   id: string;
@@ -18,7 +18,9 @@ export interface NewsArticle {
   tickers: string[];
   classification: string;
   link?: string;
-  pubDate?: Date;
+  pub_date?: string;
+  /** Publication time in Unix seconds, when the feed date could be parsed */
+  published_at: number | null;
 }
 
 // Fetch all RSS feeds using Rust backend (NO CORS ISSUES, 95% FASTER)
@@ -33,6 +35,25 @@ export async function fetchAllNews(): Promise<NewsArticle[]> {
   }
 }
 
+// Refetch every feed now instead of reading the backend cache
+export async function forceRefreshNews(): Promise<NewsArticle[]> {
+  try {
+    return await invoke<NewsArticle[]>('force_refresh_news');
+  } catch (error) {
+    console.error('[NewsService] Forced refresh failed:', error);
+    return [];
+  }
+}
+
+// Backend pushes the merged feed after every background refresh
+export function onNewsUpdated(handler: (articles: NewsArticle[]) => void): Promise<UnlistenFn> {
+  return listen<{ fetched_at: number; articles: NewsArticle[] }>('news_updated', (event) => {
+    cachedNews = event.payload.articles;
+    lastFetchTime = event.payload.fetched_at;
+    handler(event.payload.articles);
+  });
+}
+
 // Fetch news with caching (cache for 5 minutes)
 let cachedNews: NewsArticle[] = [];
 let lastFetchTime = 0;
@@ -46,7 +67,7 @@ export async function fetchNewsWithCache(forceRefresh: boolean = false): Promise
     return cachedNews;
   }
 
-  const fetchedNews = await fetchAllNews();
+  const fetchedNews = forceRefresh ? await forceRefreshNews() : await fetchAllNews();
   if (fetchedNews.length > 0) {
     cachedNews = fetchedNews;
     lastFetchTime = Date.now();
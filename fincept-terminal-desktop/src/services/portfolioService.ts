@@ -3,7 +3,6 @@
 // Using Rust SQLite backend via Tauri commands
 
 import { invoke } from '@/services/invoke';
-import { marketDataService, QuoteData } from './marketDataService';
 import { portfolioLogger } from './loggerService';
 
 // ==================== TYPES ====================
@@ -60,6 +59,9 @@ export interface HoldingWithQuote extends PortfolioAsset {
   day_change: number;
   day_change_percent: number;
   weight: number; // Portfolio weight percentage
+  price_currency?: string; // Currency the asset is quoted in
+  fx_rate?: number | null; // Rate into the portfolio currency
+  converted?: boolean; // false = values are in price_currency and excluded from totals
 }
 
 // Alias for compatibility
@@ -75,6 +77,8 @@ export interface PortfolioSummary {
   total_positions: number;
   total_day_change: number;
   total_day_change_percent: number;
+  currency?: string;
+  warnings?: string[]; // Holdings that could not be priced or converted
   last_updated: string; // Timestamp of last update
 }
 
@@ -199,73 +203,10 @@ class PortfolioService {
   async getPortfolioSummary(portfolioId: string): Promise<PortfolioSummary> {
     portfolioLogger.info(`Calculating portfolio summary: ${portfolioId}`);
 
-    // Get portfolio and assets
-    const portfolio = await this.getPortfolio(portfolioId);
-    if (!portfolio) {
-      throw new Error('Portfolio not found');
-    }
-
-    const assets = await this.getPortfolioAssets(portfolioId);
-
-    // Fetch current quotes for all symbols
-    const symbols = assets.map(a => a.symbol);
-    const quotes = await marketDataService.getQuotes(symbols);
-
-    // Calculate holdings with quotes
-    let totalMarketValue = 0;
-    let totalCostBasis = 0;
-    let totalDayChange = 0;
-
-    const holdings: HoldingWithQuote[] = assets.map(asset => {
-      const quote = quotes.find((q: QuoteData) => q.symbol === asset.symbol);
-      const currentPrice = quote?.price || asset.avg_buy_price;
-      const previousClose = quote?.previous_close || currentPrice;
-
-      const marketValue = currentPrice * asset.quantity;
-      const costBasis = asset.avg_buy_price * asset.quantity;
-      const unrealizedPnl = marketValue - costBasis;
-      const unrealizedPnlPercent = (unrealizedPnl / costBasis) * 100;
-      const dayChange = (currentPrice - previousClose) * asset.quantity;
-      const dayChangePercent = ((currentPrice - previousClose) / previousClose) * 100;
-
-      totalMarketValue += marketValue;
-      totalCostBasis += costBasis;
-      totalDayChange += dayChange;
-
-      return {
-        ...asset,
-        current_price: currentPrice,
-        market_value: marketValue,
-        cost_basis: costBasis,
-        unrealized_pnl: unrealizedPnl,
-        unrealized_pnl_percent: unrealizedPnlPercent,
-        day_change: dayChange,
-        day_change_percent: dayChangePercent,
-        weight: 0, // Will be calculated below
-      };
-    });
-
-    // Calculate weights
-    holdings.forEach(h => {
-      h.weight = totalMarketValue > 0 ? (h.market_value / totalMarketValue) * 100 : 0;
-    });
-
-    const totalUnrealizedPnl = totalMarketValue - totalCostBasis;
-    const totalUnrealizedPnlPercent = totalCostBasis > 0 ? (totalUnrealizedPnl / totalCostBasis) * 100 : 0;
-    const totalDayChangePercent = totalMarketValue > 0 ? (totalDayChange / (totalMarketValue - totalDayChange)) * 100 : 0;
-
-    return {
-      portfolio,
-      holdings,
-      total_market_value: totalMarketValue,
-      total_cost_basis: totalCostBasis,
-      total_unrealized_pnl: totalUnrealizedPnl,
-      total_unrealized_pnl_percent: totalUnrealizedPnlPercent,
-      total_positions: holdings.length,
-      total_day_change: totalDayChange,
-      total_day_change_percent: totalDayChangePercent,
-      last_updated: new Date().toISOString(),
-    };
+    // Valued in Rust so holdings priced in other currencies are FX-converted
+    const summary = await invoke<PortfolioSummary>('get_portfolio_summary', { portfolioId });
+    summary.warnings?.forEach(w => portfolioLogger.warn(w));
+    return summary;
   }
 
   async savePortfolioSnapshot(portfolioId: string): Promise<PortfolioSnapshot> {
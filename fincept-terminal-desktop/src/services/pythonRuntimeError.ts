@@ -0,0 +1,66 @@
+/**
+ * Python Runtime Errors
+ *
+ * Commands that need Python fail with a JSON error when the runtime isn't
+ * installed, so the UI can offer "Run Setup" instead of showing the
+ * troubleshooting text. `requestSetup` sends the app back to the setup screen;
+ * `surfacePythonNotInstalled` is how every failed `invoke` offers it.
+ */
+
+import { toast } from 'sonner';
+
+export const PYTHON_NOT_INSTALLED = 'PythonNotInstalled';
+
+/** Window event that reopens the setup screen */
+export const RUN_SETUP_EVENT = 'fincept:run-setup';
+
+export interface PythonNotInstalledError {
+  kind: typeof PYTHON_NOT_INSTALLED;
+  /** Interpreter path that was looked for */
+  expected_path: string;
+  reason: 'setup_not_run' | 'venv_missing' | 'install_dir_unavailable';
+  /** Troubleshooting text, for when setup can't be offered */
+  details: string;
+}
+
+/** The missing-runtime error carried by a failed command, if that is why it failed */
+export function parsePythonNotInstalled(error: unknown): PythonNotInstalledError | null {
+  const message = error instanceof Error ? error.message : typeof error === 'string' ? error : null;
+  if (!message || !message.includes(PYTHON_NOT_INSTALLED)) {
+    return null;
+  }
+  try {
+    const parsed = JSON.parse(message);
+    return parsed?.kind === PYTHON_NOT_INSTALLED ? (parsed as PythonNotInstalledError) : null;
+  } catch {
+    return null;
+  }
+}
+
+/** Show the setup screen, e.g. from a "Run Setup" button */
+export function requestSetup(): void {
+  window.dispatchEvent(new CustomEvent(RUN_SETUP_EVENT));
+}
+
+/**
+ * Offer "Run Setup" when `error` is a missing Python runtime
+ *
+ * Returns what the caller should rethrow: the troubleshooting text instead of
+ * the raw JSON for a missing runtime, any other error unchanged. One toast is
+ * shown however many commands fail together.
+ */
+export function surfacePythonNotInstalled(error: unknown): unknown {
+  const missing = parsePythonNotInstalled(error);
+  if (!missing) {
+    return error;
+  }
+  toast.error('Python runtime is not installed', {
+    id: PYTHON_NOT_INSTALLED,
+    description: missing.reason === 'venv_missing'
+      ? 'A Python environment is missing. Run setup again to repair it.'
+      : 'Run setup to install the Python runtime this feature needs.',
+    duration: Infinity,
+    action: { label: 'Run Setup', onClick: requestSetup },
+  });
+  return new Error(missing.details);
+}
@@ -1,7 +1,8 @@
 // SQLite Service - Rust SQLite backend via Tauri commands
 // All database operations now use high-performance Rust backend
 
-import { invoke } from '@/services/invoke';
+import { invoke, listen } from '@/services/invoke';
+import type { SettingEntry, SettingSaveResult } from '@/services/invoke';
 
 // Re-export watchlist services
 export { watchlistService } from './watchlistService';
@@ -106,6 +107,13 @@ export const saveSetting = async (key: string, value: string, category?: string)
   await invoke('db_save_setting', { key, value, category });
 };
 
+export type { SettingEntry, SettingSaveResult } from '@/services/invoke';
+
+/** Save several settings in one transaction; if any value is invalid, none are saved */
+export const saveSettings = async (entries: SettingEntry[]): Promise<SettingSaveResult[]> => {
+  return await invoke<SettingSaveResult[]>('db_save_settings', { entries });
+};
+
 export const getSetting = async (key: string): Promise<string | null> => {
   return await invoke<string | null>('db_get_setting', { key });
 };
@@ -114,6 +122,37 @@ export const getAllSettings = async (): Promise<Setting[]> => {
   return await invoke<Setting[]>('db_get_all_settings');
 };
 
+// ==================== CONFIG PROFILES ====================
+
+/** Named set of settings, credentials and data sources (e.g. dev, staging, prod) */
+export interface ConfigProfile {
+  name: string;
+  active: boolean;
+  settings: number;
+  credentials: number;
+  data_sources: number;
+  created_at: string | null;
+}
+
+export const listProfiles = async (): Promise<ConfigProfile[]> => {
+  return await invoke<ConfigProfile[]>('list_profiles');
+};
+
+/** New profile, empty or copied from `copyFrom` */
+export const createProfile = async (name: string, copyFrom?: string): Promise<ConfigProfile> => {
+  return await invoke<ConfigProfile>('create_profile', { name, copyFrom });
+};
+
+/** Deletes the profile and everything in it; not allowed for `default` or the active profile */
+export const deleteProfile = async (name: string): Promise<ConfigProfile> => {
+  return await invoke<ConfigProfile>('delete_profile', { name });
+};
+
+/** Settings, credentials and data sources are read from `name` from now on */
+export const switchProfile = async (name: string): Promise<ConfigProfile> => {
+  return await invoke<ConfigProfile>('switch_profile', { name });
+};
+
 // ==================== CREDENTIALS ====================
 
 export interface Credential {
@@ -189,6 +228,24 @@ export const saveLLMConfig = async (config: LLMConfig): Promise<void> => {
   await invoke('db_save_llm_config', { config });
 };
 
+export interface LLMConfigTestResult {
+  provider: string;
+  success: boolean;
+  latency_ms: number | null;
+  status: number | null;
+  /** Whether the configured model is offered by the provider (null if not checked) */
+  model_available: boolean | null;
+  error: string | null;
+}
+
+/** Check a provider's key and endpoint; overrides test unsaved form values */
+export const testLLMConfig = async (
+  provider: string,
+  overrides: { apiKey?: string; baseUrl?: string; model?: string } = {}
+): Promise<LLMConfigTestResult> => {
+  return await invoke<LLMConfigTestResult>('test_llm_config', { provider, ...overrides });
+};
+
 export const getActiveLLMConfig = async (): Promise<LLMConfig | null> => {
   const configs = await getLLMConfigs();
   return configs.find(c => c.is_active) || null;
@@ -349,6 +406,76 @@ export const getChatMessages = async (sessionUuid: string): Promise<ChatMessage[
   return await invoke<ChatMessage[]>('db_get_chat_messages', { sessionUuid });
 };
 
+export interface ChatMessagePage {
+  /** Oldest first */
+  messages: ChatMessage[];
+  /** Older messages exist; pass the first message as the next cursor (its id decides) */
+  has_more: boolean;
+}
+
+/** Newest `limit` messages before the cursor (none = the latest page), for lazy-loading history */
+export const getChatMessagesPaged = async (
+  sessionUuid: string,
+  before?: { timestamp: string; id: string },
+  limit?: number
+): Promise<ChatMessagePage> => {
+  return await invoke<ChatMessagePage>('db_get_chat_messages_paged', {
+    sessionUuid,
+    beforeTimestamp: before?.timestamp,
+    beforeId: before?.id,
+    limit
+  });
+};
+
+export interface LLMChatResult {
+  message: ChatMessage;
+  /** The stream was cancelled; `message` holds the text received before that */
+  cancelled: boolean;
+}
+
+/**
+ * Stream a reply from the active LLM provider through the backend. Tokens arrive via
+ * `onToken`; the stored assistant message is returned once the stream ends. Pass a
+ * `requestId` to allow `cancel_request` to stop it.
+ */
+export const streamChatReply = async (
+  sessionUuid: string,
+  messages: { role: 'system' | 'user' | 'assistant'; content: string }[],
+  onToken: (delta: string) => void,
+  requestId?: string
+): Promise<LLMChatResult> => {
+  const unlisten = await listen<{ session_uuid: string; request_id: string | null; delta: string }>(
+    'llm_token',
+    (event) => {
+      if (event.payload.session_uuid === sessionUuid && event.payload.request_id === (requestId ?? null)) {
+        onToken(event.payload.delta);
+      }
+    }
+  );
+  try {
+    return await invoke<LLMChatResult>('llm_chat_stream', { sessionUuid, messages, requestId });
+  } finally {
+    unlisten();
+  }
+};
+
+export interface TokenEstimate {
+  model: string;
+  method: 'o200k_base' | 'cl100k_base' | 'heuristic';
+  /** Whether the counts come from the model's own tokenizer */
+  exact: boolean;
+  messages: number[];
+  total: number;
+}
+
+/** Estimate prompt tokens for `model`, from plain text or chat messages */
+export const estimateTokens = async (
+  model: string,
+  input: { text: string } | { messages: { role: string; content: string }[] }
+): Promise<TokenEstimate> => {
+  return await invoke<TokenEstimate>('estimate_tokens', { model, ...input });
+};
+
 export const deleteChatSession = async (sessionUuid: string): Promise<void> => {
   await invoke('db_delete_chat_session', { sessionUuid });
 };
@@ -390,6 +517,27 @@ export const deleteDataSource = async (id: string): Promise<{ success: boolean;
   return await invoke('db_delete_data_source', { id });
 };
 
+export interface DataSourceProbeResult {
+  ok: boolean;
+  latency_ms: number | null;
+  error: string | null;
+}
+
+/** Check that one data source's endpoint is reachable */
+export const testDataSource = async (id: string, timeoutMs?: number): Promise<DataSourceProbeResult> => {
+  return await invoke<DataSourceProbeResult>('test_data_source', { id, timeoutMs });
+};
+
+/**
+ * Check every enabled data source concurrently, keyed by source id. Pass a
+ * `requestId` to allow `cancel_request` to stop it.
+ */
+export const probeAllDataSources = async (
+  options: { timeoutMs?: number; concurrency?: number; requestId?: string } = {}
+): Promise<Record<string, DataSourceProbeResult>> => {
+  return await invoke<Record<string, DataSourceProbeResult>>('probe_all_data_sources', options);
+};
+
 // Data Source Connection methods - stub implementations
 // These methods are for the data-sources tab's connection management
 export const saveDataSourceConnection = async (connection: any): Promise<void> => {
@@ -671,6 +819,98 @@ export const getContextStorageStats = async (): Promise<any> => {
   return null;
 };
 
+// ==================== CONFIG BUNDLE ====================
+
+export interface ConfigImportSummary {
+  schema_version: number;
+  strategy: 'skip' | 'overwrite' | 'rename';
+  sections: { table: string; inserted: number; overwritten: number; renamed: number; skipped: number }[];
+}
+
+/** Settings, LLM/data source/WebSocket configs and watchlists as a portable JSON bundle */
+export const exportConfig = async (includeSecrets: boolean = false): Promise<Record<string, unknown>> => {
+  return await invoke<Record<string, unknown>>('export_config', { includeSecrets });
+};
+
+export interface SettingsSnapshot {
+  app_version: string;
+  taken_at: string;
+  settings: Record<string, { value: string; category: string | null }>;
+  redacted: string[];
+}
+
+export interface SettingChange {
+  key: string;
+  baseline: string | null;
+  current: string | null;
+}
+
+export interface SettingsDiff {
+  added: SettingChange[];
+  removed: SettingChange[];
+  changed: SettingChange[];
+  unchanged: number;
+}
+
+/** All settings for a support ticket; credentials are listed by key only */
+export const exportSettingsSnapshot = async (): Promise<SettingsSnapshot> => {
+  return await invoke<SettingsSnapshot>('export_settings_snapshot');
+};
+
+/** Compare current settings with a snapshot or a plain { key: value } map */
+export const diffSettings = async (
+  snapshot: SettingsSnapshot | Record<string, unknown>
+): Promise<SettingsDiff> => {
+  return await invoke<SettingsDiff>('diff_settings', { snapshot });
+};
+
+export const importConfig = async (
+  bundle: Record<string, unknown>,
+  mergeStrategy: 'skip' | 'overwrite' | 'rename' = 'skip'
+): Promise<ConfigImportSummary> => {
+  return await invoke<ConfigImportSummary>('import_config', { bundle, mergeStrategy });
+};
+
+// ==================== SCHEDULED TASKS ====================
+
+export interface ScheduledTask {
+  id: number;
+  command: string;
+  args: Record<string, unknown>;
+  schedule: string;
+  enabled: boolean;
+  next_run_at: number | null;
+  last_run_at: number | null;
+  last_status: 'success' | 'error' | null;
+  last_error: string | null;
+  last_duration_ms: number | null;
+  run_count: number;
+  skipped_count: number;
+  running: boolean;
+  created_at: string;
+}
+
+/** Run `command` on an interval ("30s", "5m", "every 1h") or a 5-field cron expression */
+export const scheduleTask = async (
+  command: string,
+  schedule: string,
+  args: Record<string, unknown> = {}
+): Promise<ScheduledTask> => {
+  return await invoke<ScheduledTask>('schedule_task', { command, args, schedule });
+};
+
+export const listScheduledTasks = async (): Promise<ScheduledTask[]> => {
+  return await invoke<ScheduledTask[]>('list_scheduled_tasks');
+};
+
+export const setScheduledTaskEnabled = async (id: number, enabled: boolean): Promise<ScheduledTask> => {
+  return await invoke<ScheduledTask>('set_scheduled_task_enabled', { id, enabled });
+};
+
+export const deleteScheduledTask = async (id: number): Promise<void> => {
+  await invoke('delete_scheduled_task', { id });
+};
+
 // ==================== CACHE ====================
 
 export const saveMarketDataCache = async (symbol: string, category: string, quoteData: string): Promise<void> => {
@@ -777,6 +1017,12 @@ class SqliteService {
   getSetting = getSetting;
   getAllSettings = getAllSettings;
 
+  // Config profiles
+  listProfiles = listProfiles;
+  createProfile = createProfile;
+  deleteProfile = deleteProfile;
+  switchProfile = switchProfile;
+
   // Credentials
   saveCredential = saveCredential;
   getCredentials = getCredentials;
@@ -786,6 +1032,7 @@ class SqliteService {
   // LLM
   getLLMConfigs = getLLMConfigs;
   saveLLMConfig = saveLLMConfig;
+  testLLMConfig = testLLMConfig;
   getActiveLLMConfig = getActiveLLMConfig;
   getLLMGlobalSettings = getLLMGlobalSettings;
   saveLLMGlobalSettings = saveLLMGlobalSettings;
@@ -839,6 +1086,9 @@ class SqliteService {
   getChatSessions = getChatSessions;
   addChatMessage = addChatMessage;
   getChatMessages = getChatMessages;
+  getChatMessagesPaged = getChatMessagesPaged;
+  streamChatReply = streamChatReply;
+  estimateTokens = estimateTokens;
   deleteChatSession = deleteChatSession;
 
   async updateChatSessionTitle(sessionUuid: string, title: string) {
@@ -16,6 +16,78 @@ export interface ProviderConfig {
   reconnect_delay_ms: number;
   max_reconnect_attempts: number;
   heartbeat_interval_ms: number;
+  /** Unanswered keepalive pings in a row before reconnecting (default 2) */
+  max_missed_pongs?: number;
+  /** Min ms between ws_ticker events per symbol (0/omitted = every tick) */
+  ticker_throttle_ms?: number;
+  /** JSON-pointer field mapping; when set the generic adapter parses this feed */
+  message_mapping?: MessageMapping;
+}
+
+export interface MessageMatch {
+  pointer: string;
+  equals?: unknown;
+  starts_with?: string;
+}
+
+export type TimestampUnit = 'seconds' | 'millis' | 'micros' | 'nanos';
+
+export interface TickerMapping {
+  when?: MessageMatch;
+  items?: string;
+  symbol: string;
+  price: string;
+  bid?: string;
+  ask?: string;
+  bid_size?: string;
+  ask_size?: string;
+  volume?: string;
+  high?: string;
+  low?: string;
+  open?: string;
+  change?: string;
+  change_percent?: string;
+  timestamp?: string;
+  timestamp_unit?: TimestampUnit;
+}
+
+export interface OrderBookMapping {
+  when?: MessageMatch;
+  symbol: string;
+  bids: string;
+  asks: string;
+  /** Defaults "/0" and "/1" ([price, qty] pairs) */
+  level_price?: string;
+  level_quantity?: string;
+  timestamp?: string;
+  timestamp_unit?: TimestampUnit;
+  snapshot?: MessageMatch;
+}
+
+export interface MessageMapping {
+  /** "{symbol}" and "{channel}" are substituted in every string */
+  subscribe?: unknown;
+  unsubscribe?: unknown;
+  ticker?: TickerMapping;
+  orderbook?: OrderBookMapping;
+}
+
+/** Last raw frame a provider sent for a symbol on a channel (raw capture debug mode) */
+export interface RawFrame {
+  provider: string;
+  /** Symbol named in the frame, or "" when it names none */
+  symbol: string;
+  /** Channel named in the frame, or "unmatched" */
+  channel: string;
+  raw: string;
+  truncated: boolean;
+  received_at: number;
+}
+
+export interface SubscribeOutcome {
+  symbol: string;
+  subscribed: boolean;
+  error: string | null;
 }
 
 export interface TickerData {
@@ -34,6 +106,8 @@ export interface TickerData {
   change?: number;
   change_percent?: number;
   timestamp: number;
+  /** Set on ticks sent again by `replayTicks`, not by a live feed */
+  replayed?: boolean;
 }
 
 export interface OrderBookLevel {
@@ -80,16 +154,56 @@ export interface StatusData {
   timestamp: number;
 }
 
+export interface ConnectionEvent {
+  kind: 'connected' | 'disconnected' | 'reconnecting' | 'connect_failed' | 'token_refreshed' | 'pong_timeout';
+  at: number;
+  reason?: string;
+}
+
 export interface ConnectionMetrics {
   provider: string;
   status: 'connected' | 'connecting' | 'disconnected' | 'reconnecting' | 'error';
   connected_at?: number;
+  connected_since?: number;
   last_message_at?: number;
   messages_received: number;
   messages_sent: number;
   active_subscriptions: number;
   reconnect_count: number;
+  total_reconnects?: number;
+  /** Round trip of the last answered keepalive ping */
   latency_ms?: number;
+  last_pong_at?: number;
+  missed_pongs?: number;
+  last_error?: string;
+  last_error_at?: number;
+  history?: ConnectionEvent[];
+}
+
+/** What a provider's adapter supports, so only working options are offered */
+export interface ProviderCapabilities {
+  provider: string;
+  channels: string[];
+  requires_auth: boolean;
+  batch_subscribe: boolean;
+  /** Human-readable, with an example */
+  symbol_format: string;
+  /** Served by the generic adapter from a configured message mapping */
+  mapped: boolean;
+}
+
+export interface RecordingTarget {
+  provider: string;
+  symbol: string;
+}
+
+export interface TickRecorderStatus {
+  targets: RecordingTarget[];
+  /** Ticks written since startup */
+  recorded: number;
+  /** Ticks lost because the writer fell behind */
+  dropped: number;
+  retention_days: number;
 }
 
 // ============================================================================
@@ -141,6 +255,24 @@ export class WebSocketBridge {
     });
   }
 
+  /**
+   * Subscribe several symbols to one channel. Providers that accept a symbol
+   * list get a single upstream request; the result reports each symbol.
+   */
+  async subscribeMany(
+    provider: string,
+    symbols: string[],
+    channel: string,
+    params?: Record<string, any>
+  ): Promise<SubscribeOutcome[]> {
+    return invoke<SubscribeOutcome[]>('ws_subscribe_many', {
+      provider,
+      symbols,
+      channel,
+      params: params ? params : null,
+    });
+  }
+
   async unsubscribe(provider: string, symbol: string, channel: string): Promise<void> {
     await invoke('ws_unsubscribe', { provider, symbol, channel });
   }
@@ -193,12 +325,63 @@ export class WebSocketBridge {
   // METRICS
   // ========================================================================
 
+  async getProviderCapabilities(provider: string): Promise<ProviderCapabilities> {
+    return await invoke('get_provider_capabilities', { provider });
+  }
+
   async getMetrics(provider: string): Promise<ConnectionMetrics | null> {
     return await invoke('ws_get_metrics', { provider });
   }
 
-  async getAllMetrics(): Promise<ConnectionMetrics[]> {
-    return await invoke('ws_get_all_metrics');
+  /** Served from a snapshot up to `ws_metrics_cache_ms` old unless `fresh` is set */
+  async getAllMetrics(fresh?: boolean): Promise<ConnectionMetrics[]> {
+    return await invoke('ws_get_all_metrics', { fresh });
+  }
+
+  // ========================================================================
+  // RAW FRAME CAPTURE (debugging)
+  // ========================================================================
+
+  async setRawCapture(enabled: boolean): Promise<void> {
+    await invoke('ws_set_raw_capture', { enabled });
+  }
+
+  /** Omit `symbol` / `channel` to get the last frame of every symbol / channel */
+  async getLastRaw(provider: string, symbol?: string, channel?: string): Promise<RawFrame[]> {
+    return await invoke('ws_get_last_raw', { provider, symbol: symbol ?? null, channel: channel ?? null });
+  }
+
+  // ========================================================================
+  // TICK HISTORY
+  // ========================================================================
+
+  /** Persisted across restarts; returns the pairs now recorded */
+  async setTickRecording(provider: string, symbol: string, enabled: boolean): Promise<RecordingTarget[]> {
+    return await invoke('set_tick_recording', { provider, symbol, enabled });
+  }
+
+  async getTickRecorderStatus(): Promise<TickRecorderStatus> {
+    return await invoke('tick_recorder_status');
+  }
+
+  /** `from` / `to` in Unix ms, oldest tick first */
+  async getTickHistory(
+    provider: string,
+    symbol: string,
+    from?: number,
+    to?: number,
+    limit?: number
+  ): Promise<TickerData[]> {
+    return await invoke('get_tick_history', { provider, symbol, from, to, limit });
+  }
+
+  /** Re-routes recorded ticks at `speed` times real time (0 = no pauses); returns the tick count */
+  async replayTicks(provider: string, symbol: string, from?: number, to?: number, speed?: number): Promise<number> {
+    return await invoke('replay_ticks', { provider, symbol, from, to, speed });
+  }
+
+  async stopTickReplay(): Promise<boolean> {
+    return await invoke('stop_tick_replay');
   }
 
   // ========================================================================
@@ -12,6 +12,8 @@ export interface HistoricalDataPoint {
   close: number;
   volume: number;
   adj_close: number;
+  /** Traded close before dividend adjustment (OHLC is adjusted) */
+  raw_close?: number;
 }
 
 export interface HistoricalResponse {
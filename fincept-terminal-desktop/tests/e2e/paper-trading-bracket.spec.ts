@@ -0,0 +1,61 @@
+import { test, expect, type APIRequestContext } from '@playwright/test';
+import { pendingOrderAction } from '../../src/paper-trading/PendingOrderRules';
+
+async function rpc(request: APIRequestContext, cmd: string, args: Record<string, unknown>) {
+  const response = await request.post('/api/rpc', { data: { cmd, args } });
+  const payload = await response.json();
+  expect(payload.success, payload.error).toBeTruthy();
+  return payload.data;
+}
+
+const quote = (last: number) => ({ bid: last - 0.5, ask: last + 0.5, last });
+
+// The orders are read back from the server and run through the same rules the
+// matching engine applies on every pending-order check
+test('bracket legs activate after the entry fills and one leg cancels the other', async ({ request }) => {
+  const portfolio = await rpc(request, 'db_create_portfolio', {
+    name: 'bracket-e2e',
+    provider: 'paper',
+    initialBalance: 10000,
+  });
+  const pending = async () => {
+    const orders = await rpc(request, 'db_get_pending_orders', { portfolioId: portfolio.id });
+    return orders.map((o: any) => ({ ...o, type: o.order_type, price: o.price ?? undefined, stopPrice: o.stop_price ?? undefined }));
+  };
+
+  try {
+    const bracket = await rpc(request, 'create_bracket_order', {
+      portfolioId: portfolio.id,
+      symbol: 'BTC/USD',
+      side: 'buy',
+      orderType: 'market',
+      quantity: 1,
+      stopLoss: 95,
+      takeProfit: 110,
+    });
+
+    // Only the market entry rests, and it fills on the next check
+    let orders = await pending();
+    expect(orders.map((o: any) => o.id)).toEqual([bracket.entry.id]);
+    expect(pendingOrderAction(orders[0], quote(100))).toBe('fill');
+    await rpc(request, 'db_update_order', { id: bracket.entry.id, filledQuantity: 1, avgFillPrice: 100.5, status: 'filled' });
+
+    // Both legs are live; neither acts inside the band
+    orders = await pending();
+    const stop = orders.find((o: any) => o.id === bracket.stop_loss.id);
+    const takeProfit = orders.find((o: any) => o.id === bracket.take_profit.id);
+    expect(stop.type).toBe('stop_market');
+    expect([pendingOrderAction(stop, quote(104)), pendingOrderAction(takeProfit, quote(104))]).toEqual(['none', 'none']);
+
+    // A drop through the stop triggers the stop leg only; its fill cancels the take-profit
+    expect([pendingOrderAction(stop, quote(94)), pendingOrderAction(takeProfit, quote(94))]).toEqual(['trigger', 'none']);
+    await rpc(request, 'db_update_order', { id: stop.id, status: 'triggered' });
+    await rpc(request, 'db_update_order', { id: stop.id, filledQuantity: 1, avgFillPrice: 93.5, status: 'filled' });
+
+    expect(await pending()).toEqual([]);
+    const cancelled = await rpc(request, 'db_get_order', { id: takeProfit.id });
+    expect(cancelled.status).toBe('cancelled');
+  } finally {
+    await rpc(request, 'db_delete_portfolio', { id: portfolio.id });
+  }
+});